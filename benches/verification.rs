@@ -0,0 +1,124 @@
+//! Benchmarks of the hot paths of a [`RemoteCache`].
+//!
+//! Namely, refreshing (i.e., parsing) a large `JWK` set, and verifying a
+//! token.
+//!
+//! ```sh
+//! cargo bench --bench verification --features testing
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use http::header::CONTENT_TYPE;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+use tokio::runtime::Runtime;
+use webcipher::api::JwksFetcher;
+use webcipher::api::JwksResponse;
+use webcipher::api::KeySet;
+use webcipher::api::RemoteCache;
+use webcipher::api::Result;
+use webcipher::testing::MockIdp;
+use webcipher::testing::KEY_PAIRS;
+use webcipher::testing::MOCK_JWK_URI;
+
+/// The number of keys published by the [`LargeIdp`].
+const KEYS: usize = 256;
+
+/// Publishes a large `JWK` set (i.e., many copies of the bundled keys, under
+/// distinct `kid`s).
+struct LargeIdp {
+    body: hyper::body::Bytes,
+}
+
+impl LargeIdp {
+    fn new() -> Self {
+        let keys = (0..KEYS)
+            .map(|index| {
+                let mut key = KEY_PAIRS[index % KEY_PAIRS.len()].key();
+                key.kid = format!("webcipher-bench-key-{}", index);
+                key
+            })
+            .collect();
+        let body = serde_json::to_vec(&KeySet { keys }).unwrap().into();
+
+        Self { body }
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for LargeIdp {
+    async fn fetch(&self, _: &http::Uri) -> Result<JwksResponse> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        Ok(JwksResponse {
+            status: StatusCode::OK,
+            headers,
+            body: self.body.clone(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct BorrowedClaims<'a> {
+    sub: &'a str,
+    iss: &'a str,
+    exp: u64,
+}
+
+fn refresh(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(LargeIdp::new())
+        .build()
+        .unwrap();
+
+    c.bench_function("refresh_large_jwks", |b| {
+        b.iter(|| runtime.block_on(remote_cache.refresh()).unwrap())
+    });
+}
+
+fn decrypt(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let idp = Arc::new(MockIdp::new());
+    let mut remote_cache = idp.remote_cache().unwrap();
+    runtime.block_on(remote_cache.refresh()).unwrap();
+
+    let claims = json!({
+        "sub": "user",
+        "iss": "https://idp.webcipher.test",
+        "exp": 20_000_000_000u64,
+    });
+    let token = idp.mint(&claims).unwrap();
+
+    c.bench_function("decrypt_unchecked", |b| {
+        b.iter(|| {
+            remote_cache
+                .decrypt_unchecked::<Value, _>(token.as_str())
+                .unwrap()
+        })
+    });
+
+    let mut buffer = Vec::new();
+
+    c.bench_function("decrypt_borrowed", |b| {
+        b.iter(|| {
+            let _ = remote_cache
+                .decrypt_borrowed::<BorrowedClaims>(&token, &mut buffer)
+                .unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, refresh, decrypt);
+criterion_main!(benches);
@@ -0,0 +1,173 @@
+//! An example `axum` service which authenticates requests using a
+//! [`RemoteCache`].
+//!
+//! In order to run without network access, the tokens are minted by (and the
+//! keys are fetched from) an in-process [`MockIdp`]. In a real application,
+//! the cache would target an actual provider instead, e.g.:
+//! `RemoteCache::new(GOOGLE_JWK_URI)`.
+//!
+//! ```sh
+//! cargo run --example axum_service --features testing,headers
+//!
+//! # Mint a token:
+//! TOKEN=$(curl -s localhost:3000/login)
+//!
+//! # Use it:
+//! curl -H "Authorization: Bearer $TOKEN" localhost:3000/me
+//!
+//! # Rotate the signing key of the identity provider:
+//! curl -X POST localhost:3000/rotate
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::routing::get;
+use axum::routing::post;
+use axum::Json;
+use axum::Router;
+use axum::TypedHeader;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use webcipher::jsonwebtoken::TokenData;
+use webcipher::prelude::BearerChallenge;
+use webcipher::prelude::BearerError;
+use webcipher::prelude::Error;
+use webcipher::prelude::RemoteCache;
+use webcipher::testing::MockIdp;
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+}
+
+#[derive(Clone)]
+struct AppState {
+    idp: Arc<MockIdp>,
+    remote_cache: Arc<RwLock<RemoteCache>>,
+}
+
+/// Mint a token for a demo user.
+async fn login(State(state): State<AppState>) -> Result<String, StatusCode> {
+    let exp = Utc::now().timestamp() as u64 + 3600;
+    let claims = Claims {
+        sub: "demo-user".into(),
+        exp,
+    };
+
+    state
+        .idp
+        .mint(&claims)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Rotate the signing key of the identity provider.
+async fn rotate(State(state): State<AppState>) -> String {
+    state.idp.rotate().into()
+}
+
+/// Reject the request with a `401` (or a `403`, if the token was valid but
+/// insufficient) and the matching `WWW-Authenticate` challenge, or with a
+/// `503` if the token was not at fault (logging what the operator can do
+/// about it).
+fn reject(error: Error) -> Response {
+    match BearerChallenge::from_error(&error) {
+        Some(challenge) => {
+            let status = match challenge.error {
+                Some(BearerError::insufficient_scope) => StatusCode::FORBIDDEN,
+                _ => StatusCode::UNAUTHORIZED,
+            };
+
+            (status, TypedHeader(challenge)).into_response()
+        },
+        None => {
+            let advice = error.advice();
+            eprintln!("{} [{}] {}", error, advice.as_str(), advice);
+
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        },
+    }
+}
+
+/// Return the claims of the authenticated user.
+async fn me(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Claims>, Response> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            let challenge = BearerChallenge::default();
+            (StatusCode::UNAUTHORIZED, TypedHeader(challenge)).into_response()
+        })?
+        .to_string();
+
+    let is_fresh = state.remote_cache.read().await.is_cache_fresh();
+
+    if !is_fresh {
+        let mut remote_cache = state.remote_cache.write().await;
+
+        if !remote_cache.is_cache_fresh() {
+            remote_cache
+                .refresh()
+                .await
+                .map_err(reject)?;
+        };
+    };
+
+    let result = state
+        .remote_cache
+        .read()
+        .await
+        .decrypt_unchecked::<Claims, _>(token.clone());
+
+    // The token may have been signed by a key that was rotated in after the
+    // last refresh; refresh once and retry.
+    let result = match result {
+        Err(Error::no_corresponding_kid_in_store) => {
+            let mut remote_cache = state.remote_cache.write().await;
+            remote_cache
+                .refresh()
+                .await
+                .map_err(reject)?;
+            remote_cache.decrypt_unchecked::<Claims, _>(token)
+        },
+        result => result,
+    };
+
+    let TokenData { claims, .. } = result.map_err(reject)?;
+
+    Ok(Json(claims))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let idp = Arc::new(MockIdp::new());
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache()?));
+
+    let state = AppState { idp, remote_cache };
+
+    let app = Router::new()
+        .route("/login", get(login))
+        .route("/rotate", post(rotate))
+        .route("/me", get(me))
+        .with_state(state);
+
+    let address = SocketAddr::from(([127, 0, 0, 1], 3000));
+    axum::Server::bind(&address)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
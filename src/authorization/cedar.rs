@@ -0,0 +1,125 @@
+//! An [`AuthorizationHook`] which evaluates `Cedar` policies in-process.
+//!
+//! Only available when the `cedar` feature is enabled.
+//!
+//! Every request is mapped to a `Cedar` request as follows:
+//! - the `principal` is `User::"<sub>"` (or unspecified, if the claims have no
+//!   `sub`),
+//! - the `action` is `Action::"<method>"` (e.g., `Action::"GET"`),
+//! - the `resource` is `Path::"<path>"` (e.g., `Path::"/me"`),
+//! - and the `context` is `{ "claims": <claims> }`.
+//!
+//! ```ignore
+//! let hook = CedarHook::new(r#"
+//!     permit(principal, action == Action::"GET", resource)
+//!     when { context.claims.email_verified };
+//! "#)?;
+//! ```
+//!
+//! ### Note:
+//! `Cedar` has no representation for `null`s or floating point numbers, so a
+//! request whose claims contain either fails with
+//! [`Error::authorization_failed`].
+
+use async_trait::async_trait;
+use cedar_policy::Authorizer;
+use cedar_policy::Context;
+use cedar_policy::Decision;
+use cedar_policy::Entities;
+use cedar_policy::EntityUid;
+use cedar_policy::PolicySet;
+use cedar_policy::Request;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::authorization::AuthorizationHook;
+use crate::authorization::RequestContext;
+use crate::error::Error;
+use crate::prelude;
+
+fn failed<E>(error: E) -> Error
+where
+    E: ToString,
+{
+    Error::authorization_failed {
+        message: error.to_string(),
+    }
+}
+
+/// Build the `Cedar` entity uid `<type_name>::"<id>"`.
+fn entity_uid(type_name: &str, id: &str) -> prelude::Result<EntityUid> {
+    let type_name = type_name.parse().map_err(failed)?;
+    let id = id.parse().map_err(failed)?;
+
+    Ok(EntityUid::from_type_name_and_id(type_name, id))
+}
+
+/// Evaluates a set of `Cedar` policies for every decision.
+///
+/// See the [module level documentation](`self`).
+#[derive(Debug)]
+pub struct CedarHook {
+    policies: PolicySet,
+    entities: Entities,
+}
+
+impl CedarHook {
+    /// Create a [`CedarHook`] from the given policies.
+    ///
+    /// Fails with [`Error::invalid_policy`] if the policies cannot be parsed.
+    pub fn new(policies: &str) -> prelude::Result<Self> {
+        let policies = policies.parse::<PolicySet>().map_err(|error| {
+            Error::invalid_policy {
+                message: error.to_string(),
+            }
+        })?;
+        let entities = Entities::empty();
+
+        Ok(Self { policies, entities })
+    }
+
+    /// Set the entities (e.g., the groups that users belong to) that the
+    /// policies are evaluated against.
+    pub fn with_entities(mut self, entities: Entities) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    /// The policies of this hook.
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+}
+
+#[async_trait]
+impl AuthorizationHook for CedarHook {
+    async fn authorize(
+        &self,
+        claims: &Value,
+        context: &RequestContext,
+    ) -> prelude::Result<()> {
+        let Self { policies, entities } = self;
+        let RequestContext { method, uri, .. } = context;
+
+        let principal = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .map(|sub| entity_uid("User", sub))
+            .transpose()?;
+        let action = entity_uid("Action", method.as_str())?;
+        let resource = entity_uid("Path", uri.path())?;
+        let context =
+            Context::from_json_value(json!({ "claims": claims }), None)
+                .map_err(failed)?;
+
+        let request =
+            Request::new(principal, Some(action), Some(resource), context);
+        let response =
+            Authorizer::new().is_authorized(&request, policies, entities);
+
+        match response.decision() {
+            Decision::Allow => Ok(()),
+            Decision::Deny => Err(Error::access_denied),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Authorization decisions, made right after a token has been verified.
+//!
+//! An [`AuthorizationHook`] is given the verified claims along with the
+//! [`RequestContext`] (i.e., the method, `uri`, and headers of the incoming
+//! request), and either allows or denies the request. Centralizing these
+//! decisions inside of a policy engine avoids scattering ad-hoc checks across
+//! handlers, or stacking a second middleware on top of the authentication
+//! one.
+//!
+//! ```ignore
+//! let hook = OpaHook::new("http://localhost:8181/v1/data/authz/allow")?;
+//! let context = RequestContext::from_request(&request);
+//!
+//! let TokenData { claims, .. } = remote_cache
+//!     .decrypt_authorized::<Claims, _, _>(token, &context, &hook)
+//!     .await?;
+//! ```
+//!
+//! Two adapters are provided:
+//! - [`OpaHook`](`opa::OpaHook`), which queries an `Open Policy Agent` server
+//!   over `http`.
+//! - [`CedarHook`](`cedar::CedarHook`), which evaluates `Cedar` policies
+//!   in-process (only available when the `cedar` feature is enabled).
+//!
+//! Step-up authentication requirements (see [`step_up`]) are also expressed
+//! as a hook. Simpler checks on the granted scopes and roles do not need a
+//! policy engine at all (see [`permissions`]).
+//!
+//! ### Note:
+//! A denied request fails with [`Error::access_denied`], which
+//! [`BearerChallenge::from_error`](`crate::challenge::BearerChallenge::from_error`)
+//! maps to an `insufficient_scope` challenge (i.e., a `403`). A hook which is
+//! unable to reach a decision fails with [`Error::authorization_failed`]
+//! instead, which is not the client's fault.
+//!
+//! [`Error::access_denied`]: `crate::error::Error::access_denied`
+//! [`Error::authorization_failed`]: `crate::error::Error::authorization_failed`
+
+#[cfg(feature = "cedar")]
+pub mod cedar;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub mod opa;
+pub mod permissions;
+pub mod step_up;
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::header::Entry;
+use http::header::AUTHORIZATION;
+use http::header::COOKIE;
+use http::header::PROXY_AUTHORIZATION;
+use http::HeaderMap;
+use http::Method;
+use http::Request;
+use http::Uri;
+use serde_json::Value;
+
+use crate::prelude;
+
+/// The parts of an incoming request that an [`AuthorizationHook`] can base
+/// its decision on.
+#[derive(Clone, Debug, Default)]
+pub struct RequestContext {
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+}
+
+impl RequestContext {
+    /// Create a [`RequestContext`] without any headers.
+    pub fn new(method: Method, uri: Uri) -> Self {
+        let headers = HeaderMap::new();
+
+        Self {
+            method,
+            uri,
+            headers,
+        }
+    }
+
+    /// Create a [`RequestContext`] from the given request.
+    ///
+    /// Credential headers (i.e., `Authorization`, `Proxy-Authorization`, and
+    /// `Cookie`) are marked as sensitive, so the token is never printed by the
+    /// [`fmt::Debug`](`std::fmt::Debug`) implementation.
+    pub fn from_request<B>(request: &Request<B>) -> Self {
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        let mut headers = request.headers().clone();
+
+        for name in [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE] {
+            if let Entry::Occupied(mut entry) = headers.entry(name) {
+                entry.iter_mut().for_each(|value| value.set_sensitive(true));
+            };
+        }
+
+        Self {
+            method,
+            uri,
+            headers,
+        }
+    }
+}
+
+/// Decides whether a request, made using a verified token, is allowed.
+///
+/// Used by
+/// [`RemoteCache::decrypt_authorized`](`crate::key_caches::remote::RemoteCache::decrypt_authorized`).
+#[async_trait]
+pub trait AuthorizationHook: Send + Sync {
+    /// Allow (i.e., return `Ok(())`) or deny (i.e., fail with
+    /// [`Error::access_denied`]) the request.
+    ///
+    /// Fail with [`Error::authorization_failed`] if no decision could be
+    /// reached (e.g., the policy engine is unreachable).
+    ///
+    /// [`Error::access_denied`]: `crate::error::Error::access_denied`
+    /// [`Error::authorization_failed`]: `crate::error::Error::authorization_failed`
+    async fn authorize(
+        &self,
+        claims: &Value,
+        context: &RequestContext,
+    ) -> prelude::Result<()>;
+}
+
+#[async_trait]
+impl<H> AuthorizationHook for Arc<H>
+where
+    H: AuthorizationHook + ?Sized,
+{
+    async fn authorize(
+        &self,
+        claims: &Value,
+        context: &RequestContext,
+    ) -> prelude::Result<()> {
+        self.as_ref().authorize(claims, context).await
+    }
+}
@@ -0,0 +1,161 @@
+//! An [`AuthorizationHook`] backed by an `Open Policy Agent` server.
+//!
+//! Every decision is made by `POST`ing the following input document to the
+//! configured `uri` (i.e., a rule of the
+//! [Data API](https://www.openpolicyagent.org/docs/latest/rest-api/#data-api)):
+//!
+//! ```json
+//! {
+//!     "input": {
+//!         "claims": { "sub": "...", ... },
+//!         "request": { "method": "GET", "path": "/me", "query": null }
+//!     }
+//! }
+//! ```
+//!
+//! The request is allowed if, and only if, the rule evaluates to `true`. An
+//! undefined rule (i.e., a response without a `result`) denies the request.
+//!
+//! ### Note:
+//! The headers of the request are *not* sent, so that the token never leaves
+//! the process.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::header::CONTENT_TYPE;
+use http::Method;
+use hyper::client::HttpConnector;
+use hyper::Body;
+use hyper::Client;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::authorization::AuthorizationHook;
+use crate::authorization::RequestContext;
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::fetch::check_scheme;
+use crate::key_caches::remote::tls::connector;
+use crate::key_caches::remote::tls::Connector;
+use crate::prelude;
+
+fn failed<E>(error: E) -> Error
+where
+    E: ToString,
+{
+    Error::authorization_failed {
+        message: error.to_string(),
+    }
+}
+
+/// Queries an `Open Policy Agent` server for every decision.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Debug)]
+pub struct OpaHook {
+    uri: http::Uri,
+    timeout: Option<Duration>,
+    client: Client<Connector>,
+}
+
+impl OpaHook {
+    /// Create an [`OpaHook`] which queries the rule located at the given
+    /// `uri` (e.g., `http://localhost:8181/v1/data/httpapi/authz/allow`).
+    ///
+    /// Unlike `JWK` endpoints, plain `http` targets are allowed, since `OPA`
+    /// is usually deployed as a sidecar.
+    pub fn new<I>(uri: I) -> prelude::Result<Self>
+    where
+        String: From<I>,
+    {
+        let uri = String::from(uri).parse::<http::Uri>()?;
+        check_scheme(&uri, true)?;
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        let client = Client::builder()
+            .build(connector(http, &FetchConfig::default())?);
+        let timeout = None;
+
+        Ok(Self {
+            uri,
+            timeout,
+            client,
+        })
+    }
+
+    /// Set the maximum amount of time that a single decision is allowed to
+    /// take.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The `uri` of the queried rule.
+    pub fn uri(&self) -> &http::Uri {
+        &self.uri
+    }
+
+    async fn query(&self, input: Value) -> prelude::Result<Value> {
+        let Self { uri, client, .. } = self;
+
+        let body = serde_json::to_vec(&json!({ "input": input }))
+            .map_err(failed)?;
+        let request = http::Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(failed)?;
+
+        let response = client.request(request).await.map_err(failed)?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(failed)?;
+
+        if !status.is_success() {
+            return Err(failed(format!(
+                "`OPA` responded with status code {}.",
+                status.as_u16(),
+            )));
+        };
+
+        serde_json::from_slice(&body).map_err(failed)
+    }
+}
+
+#[async_trait]
+impl AuthorizationHook for OpaHook {
+    async fn authorize(
+        &self,
+        claims: &Value,
+        context: &RequestContext,
+    ) -> prelude::Result<()> {
+        let RequestContext { method, uri, .. } = context;
+
+        let input = json!({
+            "claims": claims,
+            "request": {
+                "method": method.as_str(),
+                "path": uri.path(),
+                "query": uri.query(),
+            },
+        });
+
+        let response = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.query(input))
+                .await
+                .map_err(|_| failed("The request to `OPA` timed out."))??,
+            None => self.query(input).await?,
+        };
+
+        match response.get("result") {
+            Some(Value::Bool(true)) => Ok(()),
+            Some(Value::Bool(false)) | None => Err(Error::access_denied),
+            Some(_) => Err(failed("The `OPA` rule is not a boolean.")),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! The scopes and roles granted by a verified token.
+//!
+//! Providers grant permissions through a handful of claims, in more than one
+//! format. [`Permissions`] collects all of them:
+//! - `scope`: a space-delimited string (e.g., `"openid read:users"`), as
+//!   according to [RFC8693](https://datatracker.ietf.org/doc/html/rfc8693#section-4.2).
+//! - `scp`: an array (e.g., `Okta`), or a space-delimited string (e.g.,
+//!   `Microsoft`).
+//! - `roles` and `groups`: arrays.
+//!
+//! ```ignore
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<StandardClaims, _>(token)?;
+//! let permissions = claims.permissions();
+//!
+//! if !permissions.has_scope("read:users") {
+//!     return Err(Error::access_denied);
+//! }
+//! ```
+//!
+//! Any claims type can be decrypted into [`Permissions`] directly as well
+//! (e.g., `remote_cache.decrypt::<Permissions, _>(token)`), and verified
+//! claims can be parsed with [`Permissions::from_claims`].
+//!
+//! ### Note:
+//! Claims of an unexpected type grant nothing. Provider-specific claims
+//! (e.g., `Auth0`'s `permissions`, or `Cognito`'s `cognito:groups`) are not
+//! collected.
+
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The scopes and roles (including groups) granted by a token.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(from = "Value")]
+pub struct Permissions {
+    /// The granted scopes (i.e., the `scope` and `scp` claims).
+    pub scopes: BTreeSet<String>,
+
+    /// The granted roles (i.e., the `roles` and `groups` claims).
+    pub roles: BTreeSet<String>,
+}
+
+impl Permissions {
+    /// Collect the permissions granted by the given (verified) claims.
+    pub fn from_claims(claims: &Value) -> Self {
+        Self::from_fn(|name| claims.get(name))
+    }
+
+    /// Collect the permissions granted by the claims that the given function
+    /// returns (by name).
+    pub(crate) fn from_fn<'a, F>(claim: F) -> Self
+    where
+        F: Fn(&str) -> Option<&'a Value>,
+    {
+        let collect = |names: [&str; 2]| {
+            names
+                .into_iter()
+                .filter_map(&claim)
+                .flat_map(values)
+                .map(String::from)
+                .collect()
+        };
+
+        Self {
+            scopes: collect(["scope", "scp"]),
+            roles: collect(["roles", "groups"]),
+        }
+    }
+
+    /// Check to see if the given scope was granted.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
+
+    /// Check to see if the given role (or group) was granted.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.contains(role)
+    }
+
+    /// Check to see if the given permission was granted, either as a scope
+    /// or as a role.
+    pub fn has(&self, permission: &str) -> bool {
+        self.has_scope(permission) || self.has_role(permission)
+    }
+
+    /// Check to see if any of the given permissions was granted (see
+    /// [`has`](`Permissions::has`)).
+    ///
+    /// Returns `false` if no permission is given.
+    pub fn has_any<I>(&self, permissions: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        permissions
+            .into_iter()
+            .any(|permission| self.has(permission.as_ref()))
+    }
+
+    /// Check to see if all of the given permissions were granted (see
+    /// [`has`](`Permissions::has`)).
+    ///
+    /// Returns `true` if no permission is given.
+    pub fn has_all<I>(&self, permissions: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        permissions
+            .into_iter()
+            .all(|permission| self.has(permission.as_ref()))
+    }
+}
+
+impl From<Value> for Permissions {
+    fn from(claims: Value) -> Self {
+        Self::from_claims(&claims)
+    }
+}
+
+/// The values of a space-delimited string, or of an array of strings.
+fn values(claim: &Value) -> Vec<&str> {
+    match claim {
+        Value::String(value) => value.split_whitespace().collect(),
+        Value::Array(values) => {
+            values.iter().filter_map(Value::as_str).collect()
+        },
+        _ => Vec::new(),
+    }
+}
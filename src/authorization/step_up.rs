@@ -0,0 +1,110 @@
+//! Step-up authentication requirements on the `acr`, `amr`, and `auth_time`
+//! claims of `ID` tokens.
+//!
+//! Some resources require a stronger (or more recent) authentication than the
+//! one the token was issued for (e.g., changing a password requires `mfa`). A
+//! [`StepUpPolicy`] describes that requirement, and rejects tokens which do
+//! not meet it with [`Error::step_up_required`], which
+//! [`BearerChallenge::from_error`](`crate::challenge::BearerChallenge::from_error`)
+//! maps to an `insufficient_user_authentication` challenge (as according to
+//! [RFC9470](https://datatracker.ietf.org/doc/html/rfc9470)). The client can
+//! then re-authenticate the user, and retry with the new token.
+//!
+//! ```ignore
+//! let policy = StepUpPolicy {
+//!     amr: vec!["mfa".into()],
+//!     max_age: Some(300),
+//!     ..Default::default()
+//! };
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt_step_up::<Claims, _>(token, &policy)?;
+//! ```
+//!
+//! [`StepUpPolicy`] also implements [`AuthorizationHook`], so it can be used
+//! wherever a hook is expected.
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::authorization::AuthorizationHook;
+use crate::authorization::RequestContext;
+use crate::error::Error;
+use crate::prelude;
+use crate::time::now;
+
+/// The authentication that a token must have been issued for.
+///
+/// Every requirement is optional (i.e., the default policy accepts every
+/// token).
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, Default, PartialEq, Eq)]
+pub struct StepUpPolicy {
+    /// The accepted authentication context classes, in order of preference.
+    ///
+    /// If not empty, the `acr` claim must be one of them.
+    pub acr_values: Vec<String>,
+
+    /// The required authentication methods (e.g., `mfa` or `hwk`).
+    ///
+    /// The `amr` claim must contain *all* of them.
+    pub amr: Vec<String>,
+
+    /// The maximum amount of time (in seconds) since the user last
+    /// authenticated (i.e., since the `auth_time` claim).
+    pub max_age: Option<u64>,
+}
+
+impl StepUpPolicy {
+    /// Check to see if the given claims meet this policy.
+    ///
+    /// Fails with [`Error::step_up_required`] if they do not (including if a
+    /// required claim is missing).
+    pub fn check(&self, claims: &Value) -> prelude::Result<()> {
+        let Self {
+            acr_values,
+            amr,
+            max_age,
+        } = self;
+
+        let acr = claims.get("acr").and_then(Value::as_str);
+        let acr_met = acr_values.is_empty()
+            || acr.is_some_and(|acr| {
+                acr_values.iter().any(|value| value == acr)
+            });
+
+        let methods = claims.get("amr").and_then(Value::as_array);
+        let amr_met = amr.iter().all(|required| {
+            methods.is_some_and(|methods| {
+                methods.iter().any(|method| method == required.as_str())
+            })
+        });
+
+        let auth_time = claims.get("auth_time").and_then(Value::as_u64);
+        let max_age_met = max_age.is_none_or(|max_age| {
+            auth_time.is_some_and(|auth_time| {
+                now().saturating_sub(auth_time) <= max_age
+            })
+        });
+
+        match acr_met && amr_met && max_age_met {
+            true => Ok(()),
+            false => Err(Error::step_up_required {
+                acr_values: acr_values.clone(),
+                max_age: *max_age,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl AuthorizationHook for StepUpPolicy {
+    async fn authorize(
+        &self,
+        claims: &Value,
+        _: &RequestContext,
+    ) -> prelude::Result<()> {
+        self.check(claims)
+    }
+}
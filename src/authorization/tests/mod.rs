@@ -0,0 +1,209 @@
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+mod opa;
+mod permissions;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::Method;
+use http::Request;
+use jsonwebtoken::TokenData;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::authorization::step_up::StepUpPolicy;
+use crate::authorization::AuthorizationHook;
+use crate::authorization::RequestContext;
+use crate::error::Error;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::testing::MockIdp;
+
+/// Only allows admins.
+struct AdminHook;
+
+#[async_trait]
+impl AuthorizationHook for AdminHook {
+    async fn authorize(
+        &self,
+        claims: &Value,
+        _: &RequestContext,
+    ) -> prelude::Result<()> {
+        match claims.get("admin") {
+            Some(Value::Bool(true)) => Ok(()),
+            _ => Err(Error::access_denied),
+        }
+    }
+}
+
+async fn setup() -> (Arc<MockIdp>, RemoteCache) {
+    let idp = Arc::new(MockIdp::new());
+    let mut remote_cache = idp.remote_cache().unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    (idp, remote_cache)
+}
+
+fn context() -> RequestContext {
+    RequestContext::new(Method::GET, "/me?verbose=true".parse().unwrap())
+}
+
+#[tokio::test]
+/// The hook should decide whether a verified token is allowed.
+async fn test_decrypt_authorized() {
+    let (idp, remote_cache) = setup().await;
+
+    let claims =
+        json!({ "sub": "user", "admin": true, "exp": 20_000_000_000u64 });
+    let token = idp.mint(&claims).unwrap();
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_authorized::<Value, _, _>(token, &context(), &AdminHook)
+        .await
+        .unwrap();
+    assert_eq!(claims["sub"], "user");
+
+    let token = idp
+        .mint(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+    let error = remote_cache
+        .decrypt_authorized::<Value, _, _>(token, &context(), &AdminHook)
+        .await
+        .unwrap_err();
+    assert_eq!(error, Error::access_denied);
+}
+
+#[tokio::test]
+/// The hook should never be invoked for tokens which fail verification.
+async fn test_unverified_token() {
+    let (_, remote_cache) = setup().await;
+
+    let hook: Arc<dyn AuthorizationHook> = Arc::new(AdminHook);
+    let error = remote_cache
+        .decrypt_authorized::<Value, _, _>("a.b.c", &context(), &hook)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::unable_to_verify_token(_)));
+}
+
+#[test]
+/// Credentials should never be printed.
+fn test_from_request() {
+    let request = Request::builder()
+        .uri("/me")
+        .header("authorization", "Bearer secret-token")
+        .header("x-tenant", "acme")
+        .body(())
+        .unwrap();
+
+    let context = RequestContext::from_request(&request);
+    let debug = format!("{:?}", context);
+
+    assert_eq!(context.headers["x-tenant"], "acme");
+    assert!(!debug.contains("secret-token"));
+}
+
+#[tokio::test]
+/// Tokens should only be accepted if they were issued for the required
+/// authentication; otherwise, a step-up should be required.
+async fn test_step_up() {
+    let (idp, remote_cache) = setup().await;
+    let now = chrono::Utc::now().timestamp() as u64;
+
+    let policy = StepUpPolicy {
+        acr_values: vec!["urn:acr:gold".into(), "urn:acr:silver".into()],
+        amr: vec!["mfa".into()],
+        max_age: Some(300),
+    };
+    let required = Error::step_up_required {
+        acr_values: policy.acr_values.clone(),
+        max_age: Some(300),
+    };
+
+    let token = idp
+        .mint(&json!({
+            "sub": "user",
+            "acr": "urn:acr:silver",
+            "amr": ["pwd", "mfa"],
+            "auth_time": now - 60,
+            "exp": 20_000_000_000u64,
+        }))
+        .unwrap();
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_step_up::<Value, _>(token, &policy)
+        .unwrap();
+    assert_eq!(claims["sub"], "user");
+
+    let deviations = [
+        json!({ "acr": "urn:acr:bronze", "amr": ["mfa"], "auth_time": now }),
+        json!({ "acr": "urn:acr:gold", "amr": ["pwd"], "auth_time": now }),
+        json!({ "acr": "urn:acr:gold", "amr": "mfa", "auth_time": now }),
+        json!({ "acr": "urn:acr:gold", "amr": ["mfa"], "auth_time": 1 }),
+        json!({ "acr": "urn:acr:gold", "amr": ["mfa"] }),
+    ];
+
+    for mut claims in deviations {
+        claims["exp"] = json!(20_000_000_000u64);
+
+        let token = idp.mint(&claims).unwrap();
+        let error = remote_cache
+            .decrypt_step_up::<Value, _>(token, &policy)
+            .unwrap_err();
+        assert_eq!(error, required);
+    }
+
+    let claims = json!({ "sub": "user" });
+    assert_eq!(StepUpPolicy::default().check(&claims), Ok(()));
+    assert_eq!(
+        policy.authorize(&claims, &context()).await,
+        Err(required),
+    );
+}
+
+#[cfg(feature = "cedar")]
+#[tokio::test]
+/// `Cedar` policies should be evaluated against the claims and the request.
+async fn test_cedar() {
+    use crate::authorization::cedar::CedarHook;
+
+    let hook = CedarHook::new(
+        r#"
+        permit(
+            principal == User::"alice",
+            action == Action::"GET",
+            resource == Path::"/me"
+        );
+
+        permit(principal, action, resource)
+        when { context.claims.admin == true };
+        "#,
+    )
+    .unwrap();
+
+    let alice = json!({ "sub": "alice", "admin": false });
+    let bob = json!({ "sub": "bob", "admin": false });
+    let admin = json!({ "sub": "bob", "admin": true });
+    let delete = RequestContext::new(Method::DELETE, "/me".parse().unwrap());
+
+    assert_eq!(hook.authorize(&alice, &context()).await, Ok(()));
+    assert_eq!(
+        hook.authorize(&bob, &context()).await,
+        Err(Error::access_denied),
+    );
+    assert_eq!(
+        hook.authorize(&alice, &delete).await,
+        Err(Error::access_denied),
+    );
+    assert_eq!(hook.authorize(&admin, &delete).await, Ok(()));
+}
+
+#[cfg(feature = "cedar")]
+#[test]
+/// Unparsable policies should be rejected upfront.
+fn test_invalid_cedar_policy() {
+    use crate::authorization::cedar::CedarHook;
+
+    let error = CedarHook::new("permit(principal").unwrap_err();
+
+    assert!(matches!(error, Error::invalid_policy { .. }));
+}
@@ -0,0 +1,113 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use hyper::service::make_service_fn;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Server;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::authorization::opa::OpaHook;
+use crate::authorization::tests::context;
+use crate::authorization::AuthorizationHook;
+use crate::error::Error;
+
+/// Serve an `OPA` mock which answers every query with the given status and
+/// body, after passing the input document to `respond`.
+async fn serve_opa<F>(respond: F) -> SocketAddr
+where
+    F: Fn(Value) -> (StatusCode, Value) + Clone + Send + Sync + 'static,
+{
+    let make_service = make_service_fn(move |_| {
+        let respond = respond.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let respond = respond.clone();
+
+                async move {
+                    let body = hyper::body::to_bytes(request.into_body())
+                        .await
+                        .unwrap();
+                    let input = serde_json::from_slice::<Value>(&body).unwrap();
+                    let (status, body) = respond(input["input"].clone());
+
+                    let response = Response::builder()
+                        .status(status)
+                        .body(Body::from(body.to_string()))
+                        .unwrap();
+
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+
+    let server =
+        Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let address = server.local_addr();
+    tokio::spawn(server);
+
+    address
+}
+
+#[tokio::test]
+/// `OPA` should be given the claims and the request, and its result should be
+/// the decision.
+async fn test_opa() {
+    let address = serve_opa(|input| {
+        assert_eq!(input["request"]["method"], "GET");
+        assert_eq!(input["request"]["path"], "/me");
+        assert_eq!(input["request"]["query"], "verbose=true");
+
+        (StatusCode::OK, json!({ "result": input["claims"]["admin"] }))
+    })
+    .await;
+
+    let hook =
+        OpaHook::new(format!("http://{}/v1/data/authz/allow", address))
+            .unwrap();
+
+    let admin = json!({ "sub": "user", "admin": true });
+    let user = json!({ "sub": "user", "admin": false });
+
+    assert_eq!(hook.authorize(&admin, &context()).await, Ok(()));
+    assert_eq!(
+        hook.authorize(&user, &context()).await,
+        Err(Error::access_denied),
+    );
+}
+
+#[tokio::test]
+/// Undefined decisions should deny the request, while failures should not be
+/// mistaken for decisions.
+async fn test_opa_failures() {
+    let claims = json!({ "sub": "user" });
+
+    let address = serve_opa(|_| (StatusCode::OK, json!({}))).await;
+    let hook = OpaHook::new(format!("http://{}/", address)).unwrap();
+    assert_eq!(
+        hook.authorize(&claims, &context()).await,
+        Err(Error::access_denied),
+    );
+
+    let address =
+        serve_opa(|_| (StatusCode::INTERNAL_SERVER_ERROR, json!({}))).await;
+    let hook = OpaHook::new(format!("http://{}/", address)).unwrap();
+    assert!(matches!(
+        hook.authorize(&claims, &context()).await,
+        Err(Error::authorization_failed { .. }),
+    ));
+
+    let address =
+        serve_opa(|_| (StatusCode::OK, json!({ "result": "yes" }))).await;
+    let hook = OpaHook::new(format!("http://{}/", address)).unwrap();
+    assert!(matches!(
+        hook.authorize(&claims, &context()).await,
+        Err(Error::authorization_failed { .. }),
+    ));
+}
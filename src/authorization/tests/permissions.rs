@@ -0,0 +1,84 @@
+use serde_json::json;
+
+use crate::authorization::permissions::Permissions;
+use crate::authorization::tests::setup;
+use crate::key_caches::standard::StandardClaims;
+
+#[test]
+/// Scopes should be collected from both delimited strings and arrays, and
+/// roles from both the `roles` and the `groups` claims.
+fn test_from_claims() {
+    let permissions = Permissions::from_claims(&json!({
+        "scope": "openid  read:users",
+        "scp": ["write:users"],
+        "roles": ["admin"],
+        "groups": ["engineering", 42],
+    }));
+
+    assert_eq!(permissions.scopes, [
+        "openid".to_string(),
+        "read:users".into(),
+        "write:users".into(),
+    ]
+    .into());
+    assert_eq!(permissions.roles, [
+        "admin".to_string(),
+        "engineering".into(),
+    ]
+    .into());
+
+    // `Microsoft` sends `scp` as a delimited string.
+    let permissions = Permissions::from_claims(&json!({ "scp": "User.Read" }));
+    assert!(permissions.has_scope("User.Read"));
+
+    // Claims of an unexpected type should grant nothing.
+    let permissions = Permissions::from_claims(&json!({ "scope": 42 }));
+    assert_eq!(permissions, Permissions::default());
+}
+
+#[test]
+/// Permissions should be granted either as a scope or as a role.
+fn test_has() {
+    let permissions = Permissions::from_claims(&json!({
+        "scope": "read:users",
+        "roles": ["admin"],
+    }));
+
+    assert!(permissions.has_scope("read:users"));
+    assert!(!permissions.has_scope("admin"));
+    assert!(permissions.has_role("admin"));
+    assert!(!permissions.has_role("read:users"));
+
+    assert!(permissions.has_any(["write:users", "admin"]));
+    assert!(!permissions.has_any(["write:users", "owner"]));
+    assert!(!permissions.has_any([] as [&str; 0]));
+
+    assert!(permissions.has_all(["read:users", "admin"]));
+    assert!(!permissions.has_all(["read:users", "owner"]));
+    assert!(permissions.has_all(Vec::<String>::new()));
+}
+
+#[tokio::test]
+/// Tokens should be decryptable into their permissions directly, or into
+/// [`StandardClaims`].
+async fn test_decrypt_permissions() {
+    let (idp, remote_cache) = setup().await;
+    let token = idp
+        .mint(&json!({
+            "exp": 20_000_000_000u64,
+            "scope": "read:users",
+            "groups": ["engineering"],
+        }))
+        .unwrap();
+
+    let data = remote_cache
+        .decrypt::<Permissions, _>(token.as_str())
+        .unwrap();
+    assert!(data.claims.has_all(["read:users", "engineering"]));
+
+    let data = remote_cache.decrypt::<StandardClaims, _>(token).unwrap();
+    assert_eq!(data.claims.permissions(), Permissions::from_claims(&json!({
+        "scope": "read:users",
+        "groups": ["engineering"],
+    })));
+}
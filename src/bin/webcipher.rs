@@ -0,0 +1,46 @@
+//! The `webcipher` command-line tool.
+//!
+//! ```sh
+//! # Probe a provider (by its issuer identifier, or its `JWK` set `uri`).
+//! webcipher doctor https://accounts.google.com
+//! ```
+//!
+//! Exits with `0` if the provider can be used by this crate, with `1` if it
+//! cannot (or cannot be reached), and with `2` on invalid usage.
+
+use std::process::ExitCode;
+
+use webcipher::doctor;
+
+const USAGE: &str = "Usage: webcipher doctor <issuer-or-jwks-uri>";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let uri = match args.as_slice() {
+        [command, uri] if command == "doctor" => uri,
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    match doctor::check(uri).await {
+        Ok(report) => {
+            println!("{}", report);
+
+            match report.is_ok() {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            }
+        },
+        Err(error) => {
+            let advice = error.advice();
+
+            eprintln!("{}", error);
+            eprintln!("advice [{}]: {}", advice.as_str(), advice);
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,319 @@
+//! `WWW-Authenticate` challenges for rejected tokens, as according to
+//! [RFC6750, Section 3](https://datatracker.ietf.org/doc/html/rfc6750#section-3)
+//! (and [RFC9470](https://datatracker.ietf.org/doc/html/rfc9470), for step-up
+//! authentication).
+//!
+//! Every integration (e.g., an `axum` or `tower` middleware) should use
+//! [`BearerChallenge::from_error`] to map an [`Error`] to a challenge, so that
+//! all of them respond the exact same way.
+//!
+//! ```ignore
+//! match remote_cache.decrypt::<Claims, _>(token) {
+//!     Ok(token_data) => ...,
+//!     Err(error) => match BearerChallenge::from_error(&error) {
+//!         // `401`, along with the challenge.
+//!         Some(challenge) => (StatusCode::UNAUTHORIZED, TypedHeader(challenge)),
+//!
+//!         // Not the client's fault (e.g., the keys could not be fetched).
+//!         None => StatusCode::SERVICE_UNAVAILABLE,
+//!     },
+//! }
+//! ```
+//!
+//! With the `headers` feature enabled, [`BearerChallenge`] implements
+//! [`headers::Header`], so it can be used with `TypedHeader` (of `axum` or
+//! `axum-extra`).
+//!
+//! ### Note:
+//! The `error_description` never contains the token itself; it is a fixed
+//! message per error.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use derive_more::Display;
+use http::HeaderValue;
+use jsonwebtoken::errors::ErrorKind;
+
+use crate::error::Error;
+
+/// The error codes of a `Bearer` challenge.
+///
+/// Taken from [RFC6750, Section 3.1](https://datatracker.ietf.org/doc/html/rfc6750#section-3.1).
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, Display)]
+pub enum BearerError {
+    /// The request is missing a required parameter, or is otherwise
+    /// malformed.
+    #[display(fmt = "invalid_request")]
+    invalid_request,
+
+    /// The token is expired, revoked, malformed, or invalid for other
+    /// reasons.
+    #[display(fmt = "invalid_token")]
+    invalid_token,
+
+    /// The token does not grant the privileges required by the request.
+    #[display(fmt = "insufficient_scope")]
+    insufficient_scope,
+
+    /// The token was issued for a weaker (or older) authentication of the user
+    /// than the one required by the request.
+    ///
+    /// Taken from [RFC9470, Section 3](https://datatracker.ietf.org/doc/html/rfc9470#section-3).
+    #[display(fmt = "insufficient_user_authentication")]
+    insufficient_user_authentication,
+}
+
+/// A `Bearer` challenge, sent as the value of a `WWW-Authenticate` header.
+///
+/// Requests without any credentials should be answered with the default
+/// challenge (i.e., without an `error`), as according to
+/// [RFC6750, Section 3.1](https://datatracker.ietf.org/doc/html/rfc6750#section-3.1).
+#[derive(Clone, Hash, Debug, Default, PartialEq, Eq)]
+pub struct BearerChallenge {
+    pub realm: Option<String>,
+    pub error: Option<BearerError>,
+    pub error_description: Option<String>,
+
+    /// The (space separated) authentication context classes that the user
+    /// should re-authenticate with.
+    pub acr_values: Option<String>,
+
+    /// The maximum amount of time (in seconds) since the user last
+    /// authenticated.
+    pub max_age: Option<u64>,
+}
+
+impl BearerChallenge {
+    /// Map the given [`Error`] to a challenge.
+    ///
+    /// Returns [`None`] if the error is not caused by the token (e.g., the
+    /// keys could not be fetched, or the cache is stale); such errors should
+    /// be answered with a `5xx` status code instead.
+    ///
+    /// A request denied by an
+    /// [`AuthorizationHook`](`crate::authorization::AuthorizationHook`) is
+    /// mapped to [`BearerError::insufficient_scope`], which should be answered
+    /// with a `403` (instead of a `401`).
+    ///
+    /// A token which does not meet a
+    /// [`StepUpPolicy`](`crate::authorization::step_up::StepUpPolicy`) is
+    /// mapped to a [`step_up`](`BearerChallenge::step_up`) challenge.
+    pub fn from_error(error: &Error) -> Option<Self> {
+        if let Error::access_denied = error {
+            return Some(Self {
+                error: Some(BearerError::insufficient_scope),
+                error_description: Some(
+                    "The token does not grant access to this resource.".into(),
+                ),
+                ..Self::default()
+            });
+        };
+
+        if let Error::step_up_required {
+            acr_values,
+            max_age,
+        } = error
+        {
+            return Some(Self::step_up(acr_values, *max_age));
+        };
+
+        let description = match error {
+            Error::unable_to_verify_token(error) => match error.kind() {
+                ErrorKind::ExpiredSignature => "The token has expired.",
+                ErrorKind::ImmatureSignature => "The token is not yet valid.",
+                ErrorKind::InvalidSignature => {
+                    "The signature of the token is invalid."
+                },
+                ErrorKind::InvalidAudience => "The audience is invalid.",
+                ErrorKind::InvalidIssuer => "The issuer is invalid.",
+                _ => "The token could not be verified.",
+            },
+            Error::invalid_algorithm => {
+                "Only the `RS256` algorithm is allowed to be used."
+            },
+            Error::unrecognized_typ => "Only the `JWT` type is supported.",
+            Error::no_kid_present => "The token has no `kid`.",
+            Error::no_corresponding_kid_in_store
+            | Error::unable_to_parse_kid_into_uuid { .. } => {
+                "The token was not signed by a known key."
+            },
+            Error::revoked_key => {
+                "The key that signed the token has been revoked."
+            },
+            Error::compressed_token => "Compressed tokens are not supported.",
+            _ => return None,
+        };
+
+        Some(Self {
+            error: Some(BearerError::invalid_token),
+            error_description: Some(description.into()),
+            ..Self::default()
+        })
+    }
+
+    /// A challenge requiring the user to re-authenticate with one of the
+    /// given authentication context classes (if any), and no longer than
+    /// `max_age` seconds ago (if given).
+    ///
+    /// Should be answered with a `401`.
+    pub fn step_up<A>(acr_values: &[A], max_age: Option<u64>) -> Self
+    where
+        A: AsRef<str>,
+    {
+        let acr_values = acr_values
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            error: Some(BearerError::insufficient_user_authentication),
+            error_description: Some(
+                "A different authentication level is required.".into(),
+            ),
+            acr_values: Some(acr_values).filter(|values| !values.is_empty()),
+            max_age,
+            ..Self::default()
+        }
+    }
+
+    /// Set the `realm` of this challenge.
+    pub fn with_realm<R>(mut self, realm: R) -> Self
+    where
+        String: From<R>,
+    {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// The value of the `WWW-Authenticate` header.
+    ///
+    /// Every parameter is sanitized to visible `ASCII` beforehand; should the
+    /// value still be unencodable, a bare `Bearer` challenge is returned.
+    pub fn header_value(&self) -> HeaderValue {
+        HeaderValue::try_from(self.to_string())
+            .unwrap_or_else(|_| HeaderValue::from_static("Bearer"))
+    }
+}
+
+/// Keep only the characters allowed inside of a quoted parameter value (i.e.,
+/// visible `ASCII` and spaces, except for `"` and `\`).
+fn quote(value: &str) -> String {
+    let value = value
+        .chars()
+        .filter(|c| matches!(c, ' '..='~') && !matches!(c, '"' | '\\'))
+        .collect::<String>();
+
+    format!("\"{}\"", value)
+}
+
+impl fmt::Display for BearerChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            realm,
+            error,
+            error_description,
+            acr_values,
+            max_age,
+        } = self;
+
+        let error = error.map(|error| error.to_string());
+        let max_age = max_age.map(|max_age| max_age.to_string());
+        let params = [
+            ("realm", realm),
+            ("error", &error),
+            ("error_description", error_description),
+            ("acr_values", acr_values),
+            ("max_age", &max_age),
+        ];
+
+        f.write_str("Bearer")?;
+
+        let params = params
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .as_ref()
+                    .map(|value| format!("{}={}", name, quote(value)))
+            })
+            .collect::<Vec<_>>();
+
+        if !params.is_empty() {
+            write!(f, " {}", params.join(", "))?;
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "headers")]
+impl headers::Header for BearerChallenge {
+    fn name() -> &'static http::HeaderName {
+        &http::header::WWW_AUTHENTICATE
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values
+            .next()
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(headers::Error::invalid)?;
+
+        let params = match value.split_once(' ') {
+            Some(("Bearer", params)) => params,
+            None if value == "Bearer" => "",
+            _ => return Err(headers::Error::invalid()),
+        };
+
+        let mut challenge = Self::default();
+
+        // Values never contain `"`, so splitting on it alternates between
+        // names and values.
+        let mut parts = params.split('"');
+        while let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            let name = name.trim_matches(|c| matches!(c, ',' | ' ' | '='));
+            let value = Some(value.to_string());
+
+            match name {
+                "realm" => challenge.realm = value,
+                "error_description" => challenge.error_description = value,
+                "acr_values" => challenge.acr_values = value,
+                "max_age" => {
+                    challenge.max_age = value
+                        .map(|value| value.parse())
+                        .transpose()
+                        .map_err(|_| headers::Error::invalid())?
+                },
+                "error" => {
+                    challenge.error = Some(match value.as_deref() {
+                        Some("invalid_request") => BearerError::invalid_request,
+                        Some("invalid_token") => BearerError::invalid_token,
+                        Some("insufficient_scope") => {
+                            BearerError::insufficient_scope
+                        },
+                        Some("insufficient_user_authentication") => {
+                            BearerError::insufficient_user_authentication
+                        },
+                        _ => return Err(headers::Error::invalid()),
+                    })
+                },
+                _ => (),
+            };
+        }
+
+        Ok(challenge)
+    }
+
+    fn encode<E>(&self, values: &mut E)
+    where
+        E: Extend<HeaderValue>,
+    {
+        values.extend(std::iter::once(self.header_value()));
+    }
+}
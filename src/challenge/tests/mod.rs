@@ -0,0 +1,117 @@
+use jsonwebtoken::errors::ErrorKind;
+
+use crate::challenge::BearerChallenge;
+use crate::challenge::BearerError;
+use crate::error::Error;
+
+#[test]
+/// Requests without credentials should be challenged without an error code.
+fn test_default_challenge() {
+    assert_eq!(BearerChallenge::default().to_string(), "Bearer");
+}
+
+#[test]
+/// Token errors should map to `invalid_token`, as according to RFC6750.
+fn test_invalid_token() {
+    let error =
+        Error::unable_to_verify_token(ErrorKind::ExpiredSignature.into());
+    let challenge = BearerChallenge::from_error(&error)
+        .unwrap()
+        .with_realm("example");
+
+    assert_eq!(challenge.error, Some(BearerError::invalid_token));
+    assert_eq!(
+        challenge.header_value(),
+        "Bearer realm=\"example\", error=\"invalid_token\", \
+         error_description=\"The token has expired.\"",
+    );
+}
+
+#[test]
+/// Denied requests should map to `insufficient_scope`.
+fn test_insufficient_scope() {
+    let challenge = BearerChallenge::from_error(&Error::access_denied).unwrap();
+
+    assert_eq!(challenge.error, Some(BearerError::insufficient_scope));
+}
+
+#[test]
+/// Tokens which do not meet a step-up policy should map to
+/// `insufficient_user_authentication`, as according to RFC9470.
+fn test_step_up() {
+    let error = Error::step_up_required {
+        acr_values: vec!["urn:acr:gold".into(), "urn:acr:silver".into()],
+        max_age: Some(300),
+    };
+    let challenge = BearerChallenge::from_error(&error).unwrap();
+
+    assert_eq!(
+        challenge.error,
+        Some(BearerError::insufficient_user_authentication),
+    );
+    assert_eq!(
+        challenge.header_value(),
+        "Bearer error=\"insufficient_user_authentication\", \
+         error_description=\"A different authentication level is required.\", \
+         acr_values=\"urn:acr:gold urn:acr:silver\", max_age=\"300\"",
+    );
+
+    let challenge = BearerChallenge::step_up::<&str>(&[], None);
+    assert_eq!(challenge.acr_values, None);
+    assert_eq!(challenge.max_age, None);
+}
+
+#[test]
+/// Errors which are not caused by the token should not be challenged.
+fn test_server_errors() {
+    let errors = [
+        Error::stale_cache,
+        Error::verification_overloaded,
+        Error::authorization_failed {
+            message: "timeout".into(),
+        },
+        Error::unable_to_fetch_keys {
+            message: "timeout".into(),
+        },
+    ];
+
+    for error in errors {
+        assert_eq!(BearerChallenge::from_error(&error), None);
+    }
+}
+
+#[test]
+/// Quotes and non-`ASCII` characters should never end up in the header.
+fn test_quoting() {
+    let challenge =
+        BearerChallenge::default().with_realm("a \"realm\"\n\u{e9}");
+
+    assert_eq!(challenge.to_string(), "Bearer realm=\"a realm\"");
+}
+
+#[cfg(feature = "headers")]
+#[test]
+/// Encoding and then decoding a challenge should be lossless.
+fn test_typed_header_roundtrip() {
+    use headers::Header;
+
+    let errors = [
+        Error::revoked_key,
+        Error::step_up_required {
+            acr_values: vec!["urn:acr:gold".into()],
+            max_age: Some(300),
+        },
+    ];
+
+    for error in errors {
+        let challenge = BearerChallenge::from_error(&error)
+            .unwrap()
+            .with_realm("example");
+
+        let mut values = Vec::new();
+        challenge.encode(&mut values);
+
+        let decoded = BearerChallenge::decode(&mut values.iter()).unwrap();
+        assert_eq!(decoded, challenge);
+    }
+}
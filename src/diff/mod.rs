@@ -0,0 +1,119 @@
+//! Comparing the claims of two tokens of the same user.
+//!
+//! During a silent re-authentication (e.g., a token refresh), the claims of
+//! the newly-minted token are expected to identify the same user as the old
+//! one. [`claims_diff`] highlights the claims which changed, flagging the ones
+//! which identify the user (see [`IDENTITY_CLAIMS`]), so that account
+//! takeovers or email changes can be detected:
+//!
+//! ```ignore
+//! let old = registry.decrypt::<Value, _, _>(&tpa, old_token)?.claims;
+//! let new = registry.decrypt::<Value, _, _>(&tpa, new_token)?.claims;
+//!
+//! let diff = claims_diff(&old, &new);
+//! if diff.has_identity_changes() {
+//!     // Prints the names of the changed claims (but not their values).
+//!     eprintln!("Identity changed during refresh: {:?}", diff);
+//! }
+//! ```
+//!
+//! Only top-level claims are compared; nested values are compared as a whole.
+//! The values of the changed claims are [`Redacted`], so that a diff can be
+//! logged without leaking them.
+
+#[cfg(test)]
+mod tests;
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::redact::Redacted;
+
+/// The claims which identify the user (or the provider which vouches for
+/// them).
+pub const IDENTITY_CLAIMS: &[&str] = &[
+    "iss",
+    "sub",
+    "email",
+    "email_verified",
+    "phone_number",
+    "phone_number_verified",
+    "preferred_username",
+];
+
+/// A single claim which differs between two tokens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimChange {
+    /// The name of the claim.
+    pub claim: String,
+
+    /// The value of the claim in the old token, if it was present.
+    pub old: Option<Redacted<Value>>,
+
+    /// The value of the claim in the new token, if it is present.
+    pub new: Option<Redacted<Value>>,
+}
+
+impl ClaimChange {
+    /// Check to see if the claim is one of the [`IDENTITY_CLAIMS`].
+    pub fn is_identity(&self) -> bool {
+        IDENTITY_CLAIMS.contains(&self.claim.as_str())
+    }
+}
+
+/// The claims which differ between two tokens.
+///
+/// Created by calling [`claims_diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClaimsDiff {
+    /// Every claim which was added, removed, or changed, ordered by name.
+    pub changes: Vec<ClaimChange>,
+}
+
+impl ClaimsDiff {
+    /// Check to see if both tokens carry exactly the same claims.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes of the [`IDENTITY_CLAIMS`].
+    pub fn identity_changes(&self) -> impl Iterator<Item = &ClaimChange> {
+        self.changes.iter().filter(|change| change.is_identity())
+    }
+
+    /// Check to see if any of the [`IDENTITY_CLAIMS`] changed.
+    pub fn has_identity_changes(&self) -> bool {
+        self.identity_changes().next().is_some()
+    }
+}
+
+/// Compare the claims of an old token to the claims of a new one.
+///
+/// Claims which are not `JSON` objects are treated as having no claims at
+/// all.
+///
+/// See the [module level documentation](`self`).
+pub fn claims_diff(old: &Value, new: &Value) -> ClaimsDiff {
+    let empty = Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+
+    let mut claims = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    claims.sort();
+    claims.dedup();
+
+    let changes = claims
+        .into_iter()
+        .filter_map(|claim| {
+            let (old, new) = (old.get(claim), new.get(claim));
+
+            (old != new).then(|| ClaimChange {
+                claim: claim.clone(),
+                old: old.cloned().map(Redacted::new),
+                new: new.cloned().map(Redacted::new),
+            })
+        })
+        .collect();
+
+    ClaimsDiff { changes }
+}
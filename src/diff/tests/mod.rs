@@ -0,0 +1,62 @@
+use serde_json::json;
+
+use crate::diff::claims_diff;
+use crate::diff::ClaimChange;
+use crate::redact::Redacted;
+
+#[test]
+/// Tokens which only differ by their timestamps should not report any
+/// identity changes.
+fn test_refresh() {
+    let old = json!({ "sub": "user", "email": "a@x.com", "iat": 1, "exp": 2 });
+    let new = json!({ "sub": "user", "email": "a@x.com", "iat": 3, "exp": 4 });
+
+    let diff = claims_diff(&old, &new);
+
+    let claims = diff
+        .changes
+        .iter()
+        .map(|ClaimChange { claim, .. }| claim.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(claims, ["exp", "iat"]);
+    assert!(!diff.has_identity_changes());
+
+    assert!(claims_diff(&old, &old).is_empty());
+}
+
+#[test]
+/// Added, removed, and changed identity claims should be flagged.
+fn test_identity_changes() {
+    let old = json!({ "sub": "user", "email": "a@x.com", "name": "A" });
+    let new = json!({
+        "sub": "user",
+        "email": "b@x.com",
+        "name": "B",
+        "phone_number": "+1",
+    });
+
+    let diff = claims_diff(&old, &new);
+
+    assert!(diff.has_identity_changes());
+    assert_eq!(
+        diff.identity_changes().collect::<Vec<_>>(),
+        [
+            &ClaimChange {
+                claim: "email".into(),
+                old: Some(Redacted::new(json!("a@x.com"))),
+                new: Some(Redacted::new(json!("b@x.com"))),
+            },
+            &ClaimChange {
+                claim: "phone_number".into(),
+                old: None,
+                new: Some(Redacted::new(json!("+1"))),
+            },
+        ],
+    );
+    assert_eq!(diff.changes.len(), 3);
+    assert!(!format!("{:?}", diff).contains("x.com"));
+
+    let diff = claims_diff(&json!(null), &json!({ "sub": "user" }));
+    assert_eq!(diff.changes[0].old, None);
+    assert!(diff.has_identity_changes());
+}
@@ -0,0 +1,577 @@
+//! A prober for the endpoints of a provider, which catches integration
+//! problems before deploying.
+//!
+//! Given either the issuer identifier of a provider, or the `uri` of its `JWK`
+//! set, [`check`] fetches the provider configuration document (if any) and the
+//! `JWK` set, and reports what this crate will (and won't) be able to do with
+//! them: the advertised algorithms, the caching behavior of the `JWK` set, the
+//! size of each key, and any configuration which cannot be handled (e.g., a
+//! provider which only publishes `EC` keys, or which sends no `max-age`).
+//!
+//! ```ignore
+//! let report = doctor::check("https://accounts.google.com").await?;
+//!
+//! println!("{}", report);
+//! assert!(report.is_ok());
+//! ```
+//!
+//! The same report is printed by the `doctor` subcommand of the `webcipher`
+//! binary:
+//!
+//! ```sh
+//! webcipher doctor https://accounts.google.com
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http::header::CACHE_CONTROL;
+use jsonwebtoken::Algorithm;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::discovery::discover;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::discovery::WELL_KNOWN_PATH;
+use crate::key_caches::remote::fetch::check_scheme;
+use crate::key_caches::remote::fetch::detect_format;
+use crate::key_caches::remote::fetch::fetch_json;
+use crate::key_caches::remote::fetch::parse_max_age;
+use crate::key_caches::remote::fetch::parse_x509_map;
+use crate::key_caches::remote::fetch::to_cache;
+use crate::key_caches::remote::fetch::EXPIRY_LEEWAY;
+use crate::key_caches::remote::fetcher::default_fetcher;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyOperation;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+use crate::prelude;
+
+/// The smallest `RSA` modulus (in bits) which is not reported as weak.
+pub const MIN_RSA_BITS: usize = 2048;
+
+/// How severe a [`Finding`] is.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth knowing, but requires no action.
+    Info,
+
+    /// Works, but is likely to cause problems (e.g., excessive refreshes).
+    Warning,
+
+    /// Does not work with this crate.
+    Error,
+}
+
+impl Severity {
+    /// The name of this severity, as printed in a [`Report`].
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A single observation about the configuration of a provider.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+
+    /// A human-readable description (and, where possible, a remedy).
+    pub message: String,
+}
+
+impl Finding {
+    fn new<M>(severity: Severity, message: M) -> Self
+    where
+        String: From<M>,
+    {
+        Self {
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// The inspection of a single published key.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct KeyReport {
+    /// The `kid` of the key (empty if it has none).
+    pub kid: String,
+    pub kty: KeyType,
+    pub alg: Option<Algorithm>,
+
+    /// The size of the modulus of an `RSA` key (in bits), if it can be read.
+    pub bits: Option<usize>,
+
+    /// Whether a [`RemoteCache`] can verify tokens with this key.
+    ///
+    /// [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+    pub usable: bool,
+}
+
+/// The outcome of [`check`]ing a provider.
+///
+/// Printing a [`Report`] (i.e., through its [`fmt::Display`] implementation)
+/// gives a human-readable summary.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// The `uri` of the `JWK` set that was inspected.
+    pub jwks_uri: String,
+
+    /// The provider configuration document, if one was discovered.
+    pub metadata: Option<ProviderMetadata>,
+
+    /// The `cache-control` header of the `JWK` set, if one was sent.
+    pub cache_control: Option<String>,
+
+    /// The `max-age` directive of the `cache-control` header, if any.
+    pub max_age: Option<u64>,
+
+    /// Every (readable) published key, in order.
+    pub keys: Vec<KeyReport>,
+
+    /// Every finding, in order of discovery.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Check to see if none of the findings is a [`Severity::Error`].
+    pub fn is_ok(&self) -> bool {
+        self.findings
+            .iter()
+            .all(|Finding { severity, .. }| *severity != Severity::Error)
+    }
+
+    /// Every algorithm that is either advertised by the provider
+    /// configuration document, or set as the `alg` of a published key.
+    pub fn algorithms(&self) -> BTreeSet<String> {
+        let Self { metadata, keys, .. } = self;
+
+        let advertised = metadata
+            .iter()
+            .flat_map(|metadata| {
+                &metadata.id_token_signing_alg_values_supported
+            })
+            .cloned();
+        let published = keys
+            .iter()
+            .filter_map(|KeyReport { alg, .. }| alg.as_ref())
+            .map(|alg| format!("{:?}", alg));
+
+        advertised.chain(published).collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            jwks_uri,
+            metadata,
+            cache_control,
+            keys,
+            findings,
+            ..
+        } = self;
+
+        if let Some(ProviderMetadata { issuer, .. }) = metadata {
+            writeln!(f, "issuer:        {}", issuer)?;
+        };
+
+        let algorithms = self.algorithms().into_iter().collect::<Vec<_>>();
+
+        writeln!(f, "jwks_uri:      {}", jwks_uri)?;
+        writeln!(f, "algorithms:    {}", algorithms.join(", "))?;
+        writeln!(
+            f,
+            "cache-control: {}",
+            cache_control.as_deref().unwrap_or("(none)"),
+        )?;
+
+        writeln!(f, "keys:")?;
+        for KeyReport {
+            kid,
+            kty,
+            alg,
+            bits,
+            usable,
+        } in keys
+        {
+            let alg = alg
+                .map(|alg| format!("{:?}", alg))
+                .unwrap_or_else(|| "(no alg)".into());
+            let bits = bits
+                .map(|bits| format!("{} bits", bits))
+                .unwrap_or_else(|| "(unknown size)".into());
+            let usable = match usable {
+                true => "usable",
+                false => "unusable",
+            };
+
+            writeln!(
+                f,
+                "  - {} {} {} {} ({})",
+                display_kid(kid),
+                kty.as_str(),
+                alg,
+                bits,
+                usable,
+            )?;
+        }
+
+        writeln!(f, "findings:")?;
+        for Finding { severity, message } in findings {
+            writeln!(f, "  [{}] {}", severity.as_str(), message)?;
+        }
+
+        match self.is_ok() {
+            true => write!(f, "OK"),
+            false => write!(f, "NOT OK"),
+        }
+    }
+}
+
+/// Probe the given provider, using the default
+/// [`HyperFetcher`](`crate::key_caches::remote::fetcher::HyperFetcher`) and
+/// [`FetchConfig`].
+///
+/// Fails with [`Error::missing_fetcher`] if no `TLS` backend is enabled (see
+/// [`check_with`] instead).
+///
+/// See [`check_with`].
+pub async fn check(issuer_or_jwks_uri: &str) -> prelude::Result<Report> {
+    let config = FetchConfig::default();
+    let fetcher = default_fetcher(&config)?;
+
+    check_with(fetcher.as_ref(), issuer_or_jwks_uri, &config).await
+}
+
+/// Probe the given provider, using the given fetcher and configuration.
+///
+/// The given `uri` is first treated as an issuer identifier (optionally
+/// followed by the [`WELL_KNOWN_PATH`]). If no provider configuration document
+/// can be fetched from it, it is treated as the `uri` of a `JWK` set instead.
+///
+/// Problems with the configuration are reported as [`Finding`]s. Only
+/// problems which prevent the inspection altogether (e.g., a `JWK` set which
+/// cannot be fetched, or a discovery document which is for another issuer)
+/// fail with the according [`Error`].
+pub async fn check_with(
+    fetcher: &dyn JwksFetcher,
+    issuer_or_jwks_uri: &str,
+    config: &FetchConfig,
+) -> prelude::Result<Report> {
+    let mut findings = vec![];
+
+    let issuer = issuer_or_jwks_uri
+        .strip_suffix(WELL_KNOWN_PATH)
+        .unwrap_or(issuer_or_jwks_uri);
+
+    let metadata = match discover(fetcher, issuer, config).await {
+        Ok(metadata) => Some(metadata),
+        Err(error @ Error::invalid_discovery_document { .. }) => {
+            return Err(error)
+        },
+        Err(_) => None,
+    };
+
+    let jwks_uri = match &metadata {
+        Some(metadata) => {
+            findings.extend(check_algorithms(metadata));
+            metadata.jwks_uri.clone()
+        },
+        None => {
+            findings.push(Finding::new(
+                Severity::Info,
+                format!(
+                    "No discovery document was found; `{}` is treated as the \
+                     `uri` of a `JWK` set.",
+                    issuer_or_jwks_uri,
+                ),
+            ));
+            issuer_or_jwks_uri.to_string()
+        },
+    };
+
+    let uri = jwks_uri.parse::<http::Uri>()?;
+
+    if check_scheme(&uri, config.allow_insecure_http).is_err() {
+        findings.push(Finding::new(
+            Severity::Error,
+            "The `JWK` set is not served over `https`.",
+        ));
+    };
+
+    let JwksResponse { headers, body, .. } =
+        fetch_json(fetcher, uri, config).await?;
+
+    let cache_control = headers
+        .get(CACHE_CONTROL)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+    let max_age = parse_max_age(&headers).ok().flatten();
+
+    findings.extend(check_max_age(cache_control.as_deref(), max_age));
+
+    let mut keys = vec![];
+
+    let read = read_keys(&body, config.format)?;
+
+    for (index, key) in read.into_iter().enumerate() {
+        match key {
+            Ok(key) => keys.push(inspect(key, &mut findings)),
+            Err(message) => findings.push(Finding::new(
+                Severity::Warning,
+                format!(
+                    "Key #{} cannot be read, and is ignored: {}",
+                    index, message,
+                ),
+            )),
+        };
+    }
+
+    findings.extend(check_keys(&keys));
+
+    Ok(Report {
+        jwks_uri,
+        metadata,
+        cache_control,
+        max_age,
+        keys,
+        findings,
+    })
+}
+
+/// Read every key of the given body, keeping the reason why each unreadable
+/// key cannot be read.
+fn read_keys(
+    body: &[u8],
+    format: JwksFormat,
+) -> prelude::Result<Vec<Result<Key, String>>> {
+    let body: Value = serde_json::from_slice(body)?;
+
+    let format = match format {
+        JwksFormat::Auto => detect_format(&body),
+        format => format,
+    };
+
+    match format {
+        JwksFormat::X509Map => {
+            Ok(parse_x509_map(body)?.into_iter().map(Ok).collect())
+        },
+        JwksFormat::Auto | JwksFormat::Jwks => {
+            let keys = body.get("keys").and_then(Value::as_array).ok_or(
+                Error::unable_to_fetch_keys {
+                    message: "No 'keys' array contained in the returned \
+                              object."
+                        .into(),
+                },
+            )?;
+
+            let keys = keys
+                .iter()
+                .map(|key| {
+                    serde_json::from_value::<Key>(key.clone())
+                        .map_err(|error| error.to_string())
+                })
+                .collect();
+
+            Ok(keys)
+        },
+    }
+}
+
+fn check_algorithms(metadata: &ProviderMetadata) -> Vec<Finding> {
+    let algorithms = &metadata.id_token_signing_alg_values_supported;
+    let unsupported = algorithms
+        .iter()
+        .filter(|algorithm| *algorithm != "RS256")
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    match (algorithms.is_empty(), unsupported.len() == algorithms.len()) {
+        (true, _) => vec![Finding::new(
+            Severity::Warning,
+            "The discovery document advertises no signing algorithms.",
+        )],
+        (false, true) => vec![Finding::new(
+            Severity::Error,
+            format!(
+                "Only `{}` are advertised, but only `RS256` is supported.",
+                unsupported.join("`, `"),
+            ),
+        )],
+        (false, false) if !unsupported.is_empty() => vec![Finding::new(
+            Severity::Info,
+            format!(
+                "Tokens signed with `{}` will be rejected (only `RS256` is \
+                 supported).",
+                unsupported.join("`, `"),
+            ),
+        )],
+        (false, false) => vec![],
+    }
+}
+
+fn check_max_age(
+    cache_control: Option<&str>,
+    max_age: Option<u64>,
+) -> Vec<Finding> {
+    let message = match (cache_control, max_age) {
+        (None, _) => "No `cache-control` header is sent".to_string(),
+        (Some(_), None) => {
+            "The `cache-control` header has no `max-age` directive".to_string()
+        },
+        (Some(_), Some(max_age)) if max_age <= EXPIRY_LEEWAY => format!(
+            "The `max-age` ({}s) is within the leeway of {}s",
+            max_age, EXPIRY_LEEWAY,
+        ),
+        (Some(_), Some(_)) => return vec![],
+    };
+
+    vec![Finding::new(
+        Severity::Warning,
+        format!(
+            "{}, so the keys are never fresh: refresh them on a schedule \
+             (see `RefreshSchedule`) instead of whenever they expire.",
+            message,
+        ),
+    )]
+}
+
+/// Inspect the given key, reporting why it cannot be used (if so) and whether
+/// it is weak.
+fn inspect(key: Key, findings: &mut Vec<Finding>) -> KeyReport {
+    let mut completed = key.clone();
+    if completed.n.is_empty() || completed.e.is_empty() {
+        let _ = completed.complete_from_x5c();
+    };
+
+    let bits = match key.kty {
+        KeyType::RSA => modulus_bits(&completed.n),
+        KeyType::EC | KeyType::oct | KeyType::OKP | KeyType::Other(_) => None,
+    };
+
+    let reason = unusable_reason(&key);
+
+    let Key { kid, kty, alg, .. } = key;
+
+    if let Some(reason) = &reason {
+        findings.push(Finding::new(
+            Severity::Warning,
+            format!("The key {} is ignored: {}.", display_kid(&kid), reason),
+        ));
+    };
+
+    if let Some(bits) = bits.filter(|bits| *bits < MIN_RSA_BITS) {
+        findings.push(Finding::new(
+            Severity::Warning,
+            format!(
+                "The key {} is weak ({} bits, instead of at least {}).",
+                display_kid(&kid),
+                bits,
+                MIN_RSA_BITS,
+            ),
+        ));
+    };
+
+    KeyReport {
+        kid,
+        kty,
+        alg,
+        bits,
+        usable: reason.is_none(),
+    }
+}
+
+/// The reason why a [`RemoteCache`] cannot use the given key, if any.
+///
+/// The checks mirror the filters of [`to_cache`], which has the final say.
+///
+/// [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+fn unusable_reason(key: &Key) -> Option<String> {
+    let Key {
+        kty, alg, r#use, ..
+    } = key;
+
+    match kty {
+        KeyType::RSA => (),
+        KeyType::EC | KeyType::oct | KeyType::OKP | KeyType::Other(_) => {
+            return Some(format!(
+                "only `RSA` keys are supported, not `{}`",
+                kty.as_str(),
+            ))
+        },
+    };
+
+    match alg {
+        Some(Algorithm::RS256) | None => (),
+        Some(alg) => {
+            return Some(format!(
+                "only `RS256` is supported, not `{:?}`",
+                alg,
+            ))
+        },
+    };
+
+    match r#use {
+        Use::sig => (),
+        Use::enc | Use::Other(_) => {
+            return Some("it is not a signing key".into())
+        },
+    };
+
+    if !key.allows(&KeyOperation::Verify) {
+        return Some("its `key_ops` exclude `verify`".into());
+    };
+
+    match to_cache([key.clone()]).is_empty() {
+        true => Some("its public key cannot be read".into()),
+        false => None,
+    }
+}
+
+/// Report the keys (as a whole) which cannot be handled.
+fn check_keys(keys: &[KeyReport]) -> Vec<Finding> {
+    if keys.iter().any(|KeyReport { usable, .. }| *usable) {
+        return vec![];
+    };
+
+    let is_ec_only = !keys.is_empty()
+        && keys.iter().all(|KeyReport { kty, .. }| *kty == KeyType::EC);
+
+    let message = match is_ec_only {
+        true => "Only `EC` keys are published, but only `RSA` keys are \
+                 supported.",
+        false => "No usable keys are published.",
+    };
+
+    vec![Finding::new(Severity::Error, message)]
+}
+
+/// The size (in bits) of the given (base64url encoded) `RSA` modulus.
+fn modulus_bits(n: &str) -> Option<usize> {
+    let n = URL_SAFE_NO_PAD.decode(n).ok()?;
+    let start = n.iter().position(|byte| *byte != 0)?;
+    let significant = n.get(start..)?;
+    let leading_zeros = significant.first()?.leading_zeros() as usize;
+
+    Some(significant.len() * 8 - leading_zeros)
+}
+
+fn display_kid(kid: &str) -> String {
+    match kid.is_empty() {
+        true => "(without a `kid`)".into(),
+        false => format!("`{}`", kid),
+    }
+}
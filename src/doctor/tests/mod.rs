@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http::header::CACHE_CONTROL;
+use http::header::CONTENT_TYPE;
+use http::HeaderMap;
+use http::StatusCode;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::doctor::check_with;
+use crate::doctor::Finding;
+use crate::doctor::Report;
+use crate::doctor::Severity;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::prelude;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+const ISSUER: &str = "https://accounts.example.com";
+const DISCOVERY_URI: &str =
+    "https://accounts.example.com/.well-known/openid-configuration";
+const JWKS_URI: &str = "https://keys.example.com/certs";
+
+/// A [`JwksFetcher`] serving canned documents, by `uri`.
+#[derive(Default)]
+struct Endpoints {
+    documents: BTreeMap<String, JwksResponse>,
+}
+
+impl Endpoints {
+    fn with(
+        mut self,
+        uri: &str,
+        document: Value,
+        cache_control: Option<&str>,
+    ) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+        if let Some(cache_control) = cache_control {
+            headers.insert(CACHE_CONTROL, cache_control.parse().unwrap());
+        };
+
+        let response = JwksResponse {
+            status: StatusCode::OK,
+            headers,
+            body: serde_json::to_vec(&document).unwrap().into(),
+        };
+
+        self.documents.insert(uri.into(), response);
+        self
+    }
+
+    fn with_discovery(self, algorithms: &[&str]) -> Self {
+        let document = json!({
+            "issuer": ISSUER,
+            "jwks_uri": JWKS_URI,
+            "id_token_signing_alg_values_supported": algorithms,
+        });
+
+        self.with(DISCOVERY_URI, document, None)
+    }
+
+    fn with_jwks(self, keys: Vec<Value>, cache_control: Option<&str>) -> Self {
+        self.with(JWKS_URI, json!({ "keys": keys }), cache_control)
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for Endpoints {
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        let response = self.documents.get(&uri.to_string()).cloned();
+
+        Ok(response.unwrap_or(JwksResponse {
+            status: StatusCode::NOT_FOUND,
+            ..Default::default()
+        }))
+    }
+}
+
+fn rsa_key() -> Value {
+    serde_json::to_value(KEY_PAIRS[0].key()).unwrap()
+}
+
+fn ec_key() -> Value {
+    json!({
+        "kty": "EC",
+        "kid": "ec",
+        "use": "sig",
+        "alg": "ES256",
+        "crv": "P-256",
+        "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+        "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+    })
+}
+
+async fn check(endpoints: Endpoints, uri: &str) -> prelude::Result<Report> {
+    check_with(&endpoints, uri, &FetchConfig::default()).await
+}
+
+fn severities(report: &Report) -> Vec<Severity> {
+    report
+        .findings
+        .iter()
+        .map(|Finding { severity, .. }| *severity)
+        .collect()
+}
+
+#[tokio::test]
+/// A well-configured provider should be reported as such, through either its
+/// issuer identifier or its discovery `uri`.
+async fn test_healthy_issuer() {
+    for uri in [ISSUER, DISCOVERY_URI] {
+        let endpoints = Endpoints::default()
+            .with_discovery(&["RS256"])
+            .with_jwks(vec![rsa_key()], Some("public, max-age=86400"));
+
+        let report = check(endpoints, uri).await.unwrap();
+
+        assert!(report.is_ok());
+        assert!(report.findings.is_empty());
+        assert_eq!(report.jwks_uri, JWKS_URI);
+        assert_eq!(report.metadata.unwrap().issuer, ISSUER);
+        assert_eq!(report.max_age, Some(86_400));
+        assert_eq!(report.keys.len(), 1);
+        assert_eq!(report.keys[0].kid, KEY_PAIRS[0].kid);
+        assert_eq!(report.keys[0].bits, Some(2048));
+        assert!(report.keys[0].usable);
+    }
+}
+
+#[tokio::test]
+/// A `JWK` set `uri` should be inspected directly, and missing caching
+/// directives should be flagged.
+async fn test_jwks_uri() {
+    let cache_controls = [None, Some("no-cache"), Some("max-age=600")];
+
+    for cache_control in cache_controls {
+        let endpoints =
+            Endpoints::default().with_jwks(vec![rsa_key()], cache_control);
+
+        let report = check(endpoints, JWKS_URI).await.unwrap();
+
+        assert!(report.is_ok());
+        assert!(report.metadata.is_none());
+        assert_eq!(report.cache_control.as_deref(), cache_control);
+        assert_eq!(severities(&report), [Severity::Info, Severity::Warning]);
+    }
+}
+
+#[tokio::test]
+/// Providers which only publish (or advertise) what this crate cannot verify
+/// should be flagged as errors.
+async fn test_unsupported() {
+    let endpoints = Endpoints::default()
+        .with_discovery(&["ES256"])
+        .with_jwks(vec![ec_key()], Some("max-age=86400"));
+
+    let report = check(endpoints, ISSUER).await.unwrap();
+
+    assert!(!report.is_ok());
+    assert_eq!(
+        severities(&report),
+        [Severity::Error, Severity::Warning, Severity::Error],
+    );
+    assert!(report.findings[2].message.contains("Only `EC` keys"));
+    assert!(!report.keys[0].usable);
+    assert!(report.to_string().ends_with("NOT OK"));
+
+    let endpoints = Endpoints::default()
+        .with_discovery(&["RS256", "ES256"])
+        .with_jwks(vec![rsa_key(), ec_key()], Some("max-age=86400"));
+
+    let report = check(endpoints, ISSUER).await.unwrap();
+
+    assert!(report.is_ok());
+    assert_eq!(severities(&report), [Severity::Info, Severity::Warning]);
+    assert_eq!(
+        report.algorithms().into_iter().collect::<Vec<_>>(),
+        ["ES256", "RS256"],
+    );
+}
+
+#[tokio::test]
+/// Weak and unreadable keys should be flagged.
+async fn test_weak_and_unreadable_keys() {
+    let mut weak_key = rsa_key();
+    weak_key["kid"] = json!("weak");
+    weak_key["n"] = json!(URL_SAFE_NO_PAD.encode([0x3f; 128]));
+
+    let keys = vec![rsa_key(), weak_key, json!({ "kid": "broken" })];
+    let endpoints =
+        Endpoints::default().with_jwks(keys, Some("max-age=86400"));
+
+    let report = check(endpoints, JWKS_URI).await.unwrap();
+
+    assert!(report.is_ok());
+    assert_eq!(report.keys.len(), 2);
+    assert_eq!(report.keys[1].bits, Some(1022));
+    assert!(report.findings[1].message.contains("`weak` is weak"));
+    assert!(report.findings[2].message.starts_with("Key #2"));
+}
+
+#[tokio::test]
+/// Discovery documents of other issuers, and `JWK` sets which cannot be
+/// fetched, should fail the check altogether.
+async fn test_failures() {
+    let endpoints = Endpoints::default().with(
+        DISCOVERY_URI,
+        json!({ "issuer": "https://evil.example.com", "jwks_uri": JWKS_URI }),
+        None,
+    );
+
+    let error = check(endpoints, ISSUER).await.unwrap_err();
+    assert!(matches!(error, Error::invalid_discovery_document { .. }));
+
+    let error = check(Endpoints::default(), JWKS_URI).await.unwrap_err();
+    assert_eq!(error, Error::unexpected_status { status: 404 });
+}
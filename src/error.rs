@@ -1,20 +1,32 @@
 //! Errors that can appear during performing operations required by this crate.
 
+use std::fmt;
+
 use derive_more::Display;
+use jsonwebtoken::errors::ErrorKind;
+
+use crate::redact::redact_jwt_error;
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, Display)]
 pub enum Error {
     /// The given `Uri` is invalid.
     ///
     /// ### Note:
     /// We expect the `Uri` to use the `https` scheme.
-    ///
-    /// ### Extension:
-    /// We may extend this library to support the `http` scheme as well.
+    /// The `http` scheme can be allowed for local development, by using
+    /// [`RemoteCacheBuilder::allow_insecure_http`](`crate::key_caches::remote::builder::RemoteCacheBuilder::allow_insecure_http`).
     #[display(fmt = "The given `uri` must be valid `https`.")]
     invalid_uri,
 
+    /// The configured root certificates or client identity could not be used.
+    ///
+    /// The message string contains the error that the `TLS` backend issued.
+    #[display(fmt = "The `TLS` configuration is invalid. {}", message)]
+    invalid_tls_config {
+        message: String,
+    },
+
     /// The given `JWT` or fetched `JWK` contained an invalid algorithm.
     ///
     /// ### Note:
@@ -33,6 +45,43 @@ pub enum Error {
         message: String,
     },
 
+    /// A response was received, but it had a non-successful (i.e., non `2xx`)
+    /// status code.
+    #[display(fmt = "The `JWK` endpoint responded with status code {}.", status)]
+    unexpected_status {
+        status: u16,
+    },
+
+    /// The `JWK` endpoint redirected back to a `uri` which had already been
+    /// visited.
+    #[display(fmt = "The `JWK` endpoint redirected in a loop.")]
+    redirect_loop,
+
+    /// The `JWK` endpoint redirected more times than the configured
+    /// [`RedirectPolicy`](`crate::key_caches::remote::config::RedirectPolicy`)
+    /// allows.
+    #[display(fmt = "The `JWK` endpoint redirected more than {} times.", limit)]
+    too_many_redirects {
+        limit: usize,
+    },
+
+    /// A response was received, but its body exceeded the configured maximum
+    /// size (in bytes).
+    #[display(fmt = "The response from the `JWK` endpoint exceeded {} bytes.", limit)]
+    response_too_large {
+        limit: usize,
+    },
+
+    /// A response was received, but its `Content-Type` was not `JSON`.
+    ///
+    /// ### Note:
+    /// `application/json`, as well as any `+json` media type (e.g.,
+    /// `application/jwk-set+json`), are accepted.
+    #[display(fmt = "The response from the `JWK` endpoint has an unexpected content-type: {}.", content_type)]
+    unexpected_content_type {
+        content_type: String,
+    },
+
     /// A response was received, but it was not able to be parsed into a `Json`
     /// object.
     #[display(fmt = "The response from the fetch request is unrecognized. {}", message)]
@@ -40,6 +89,50 @@ pub enum Error {
         message: String,
     },
 
+    /// The `OpenID Connect` provider configuration document could not be
+    /// used (e.g., it has no `jwks_uri`, or it is for a different issuer).
+    #[display(fmt = "The discovery document is invalid. {}", message)]
+    invalid_discovery_document {
+        message: String,
+    },
+
+    /// The given bytes are not a (readable) snapshot of a `RemoteCache`, or
+    /// the snapshot was taken of a cache with a different `uri`.
+    #[display(fmt = "The snapshot is invalid. {}", message)]
+    invalid_snapshot {
+        message: String,
+    },
+
+    /// The leaf certificate of a `JWK`'s `x5c` chain is malformed, or holds
+    /// an unsupported key.
+    #[display(fmt = "The certificate is invalid. {}", message)]
+    invalid_certificate {
+        message: String,
+    },
+
+    /// A `JWK` cannot be converted to (or from) a
+    /// [`jsonwebtoken::jwk::Jwk`].
+    #[display(fmt = "The `JWK` cannot be converted. {}", message)]
+    invalid_jwk {
+        message: String,
+    },
+
+    /// A [`CacheStore`](`crate::key_caches::remote::store::CacheStore`) was
+    /// unable to load or save a snapshot, or a
+    /// [`ReplayStore`](`crate::key_caches::replay::ReplayStore`) was unable
+    /// to record a token.
+    ///
+    /// The message string contains the error that the store issued.
+    #[display(fmt = "The cache store failed. {}", message)]
+    store_failed {
+        message: String,
+    },
+
+    /// The token could not be decoded or verified.
+    ///
+    /// ### Note:
+    /// The inner error never contains any fragment of the token (see
+    /// [`crate::redact`]).
     unable_to_verify_token(
         jsonwebtoken::errors::Error,
     ),
@@ -72,6 +165,49 @@ pub enum Error {
     #[display(fmt = "No matching `kid` in the key-cache.")]
     no_corresponding_kid_in_store,
 
+    /// The key that signed the token has been revoked.
+    ///
+    /// See [`revoke_key`](`crate::key_caches::local::LocalCache::revoke_key`).
+    #[display(fmt = "The key that signed the token has been revoked.")]
+    revoked_key,
+
+    /// The given key cannot be used by a
+    /// [`RemoteCache`](`crate::key_caches::remote::RemoteCache`) (i.e., it is
+    /// not an `RS256` signing key).
+    #[display(fmt = "The key cannot be used to verify tokens.")]
+    unusable_key,
+
+    /// The subject of the claims has exhausted its issuance quota.
+    ///
+    /// See [`crate::key_caches::local::quota`].
+    #[display(fmt = "The issuance quota for this subject has been exceeded.")]
+    issuance_quota_exceeded,
+
+    /// The activation of a staged key was not approved.
+    ///
+    /// See [`activate_key`](`crate::key_caches::local::LocalCache::activate_key`).
+    #[display(fmt = "The activation of the key was not approved.")]
+    rotation_not_approved,
+
+    /// No cache has been registered for the given third party auth provider.
+    #[display(fmt = "No cache is registered for the given provider.")]
+    unknown_tpa,
+
+    /// The token has no `iss` claim, or no provider has been registered for
+    /// its issuer.
+    ///
+    /// See
+    /// [`decrypt_by_issuer`](`crate::registry::KeyRegistry::decrypt_by_issuer`).
+    #[display(fmt = "No provider is registered for the issuer of the token.")]
+    unknown_issuer,
+
+    /// The given name is not the name of a
+    /// [`WellKnownTpa`](`crate::key_caches::remote::well_known::WellKnownTpa`).
+    #[display(fmt = "`{}` is not a well-known provider.", name)]
+    unknown_well_known_tpa {
+        name: String,
+    },
+
     #[display(fmt = "Unable to parse the data into a valid Uuid.")]
     unable_to_parse_kid_into_uuid {
         message: String,
@@ -81,10 +217,356 @@ pub enum Error {
     /// was unable to be parsed.
     #[display(fmt = "The headers in the response were unable to be parsed.")]
     unable_to_parse_headers,
+
+    /// The received `JWT` declares a `zip` header parameter (i.e., its payload
+    /// is compressed).
+    ///
+    /// ### Note:
+    /// Compressed tokens are always rejected; they are never decompressed.
+    /// This protects against decompression bombs.
+    #[display(fmt = "Compressed (i.e., `zip`) tokens are not supported.")]
+    compressed_token,
+
+    /// The keys in the cache have expired (and are past any grace period), so
+    /// the token was not verified.
+    ///
+    /// ### Note:
+    /// Call [`refresh`](`crate::key_caches::remote::RemoteCache::refresh`)
+    /// first.
+    #[display(fmt = "The keys in the cache have expired; refresh the cache.")]
+    stale_cache,
+
+    /// The maximum number of concurrent verifications for the cache has been
+    /// reached, and no verification finished within the wait of the
+    /// asynchronous APIs (if any). An offloaded verification which could not
+    /// be run (since the runtime is shutting down) is rejected likewise.
+    ///
+    /// ### Note:
+    /// The token was not verified; it may be retried later.
+    #[display(fmt = "Too many concurrent verifications; please retry later.")]
+    verification_overloaded,
+
+    /// No [`JwksFetcher`](`crate::key_caches::remote::fetcher::JwksFetcher`)
+    /// was given to a cache, and the default one is unavailable, since
+    /// neither the `native-tls` nor the `rustls` feature is enabled.
+    #[display(fmt = "No fetcher was given, and no TLS backend is enabled.")]
+    missing_fetcher,
+
+    /// The limit on concurrent verifications of a cache is zero (i.e., no
+    /// token could ever be verified).
+    ///
+    /// See
+    /// [`RemoteCache::set_max_concurrent_verifications`](`crate::key_caches::remote::RemoteCache::set_max_concurrent_verifications`).
+    #[display(fmt = "The limit on concurrent verifications must be positive.")]
+    invalid_verification_limit,
+
+    /// The token was verified, but an
+    /// [`AuthorizationHook`](`crate::authorization::AuthorizationHook`)
+    /// denied the request.
+    #[display(fmt = "The request was denied by the authorization policy.")]
+    access_denied,
+
+    /// The token was verified, but was not issued for a strong (or recent)
+    /// enough authentication of the user.
+    ///
+    /// Contains the requirements of the
+    /// [`StepUpPolicy`](`crate::authorization::step_up::StepUpPolicy`), so
+    /// that the client can be challenged to re-authenticate the user.
+    #[display(fmt = "Step-up authentication is required.")]
+    step_up_required {
+        acr_values: Vec<String>,
+        max_age: Option<u64>,
+    },
+
+    /// An [`AuthorizationHook`](`crate::authorization::AuthorizationHook`)
+    /// was unable to reach a decision (e.g., the policy engine is
+    /// unreachable).
+    ///
+    /// The message string contains the error that the hook issued.
+    #[display(fmt = "No authorization decision could be made. {}", message)]
+    authorization_failed {
+        message: String,
+    },
+
+    /// The given authorization policies could not be parsed.
+    #[display(fmt = "The authorization policies are invalid. {}", message)]
+    invalid_policy {
+        message: String,
+    },
+
+    /// The given claims schema is malformed, or uses an unsupported keyword.
+    ///
+    /// See [`ClaimsSchema`](`crate::registry::schema::ClaimsSchema`).
+    #[display(fmt = "The claims schema is invalid. {}", message)]
+    invalid_schema {
+        message: String,
+    },
+
+    /// The claims of a verified token do not match the claims schema of its
+    /// provider.
+    ///
+    /// The message string lists every deviation, along with its location.
+    #[display(fmt = "The claims do not match the schema. {}", message)]
+    claims_schema_mismatch {
+        message: String,
+    },
+
+    /// A claims validator of the provider of a verified token rejected it.
+    ///
+    /// The message string contains the
+    /// [`ValidationError`](`crate::registry::validator::ValidationError`)
+    /// that the validator returned.
+    #[display(fmt = "The claims were rejected by a validator. {}", message)]
+    claims_rejected {
+        message: String,
+    },
+
+    /// The `token_use` claim of a `Cognito` token is not the expected one
+    /// (e.g., an `id` token was sent instead of an `access` token).
+    ///
+    /// See
+    /// [`CognitoUserPool::validate`](`crate::key_caches::remote::cognito::CognitoUserPool::validate`).
+    #[display(fmt = "The token is not of the expected kind (`token_use`).")]
+    unexpected_token_use,
+
+    /// The `nonce` claim of a verified token is missing, or is not the one
+    /// that was sent along with the authentication request (i.e., the token
+    /// may have been replayed).
+    ///
+    /// See [`nonce`](`crate::key_caches::nonce`).
+    #[display(fmt = "The `nonce` of the token does not match.")]
+    nonce_mismatch,
+
+    /// The `at_hash` (or `c_hash`) claim of a verified `ID` token is missing,
+    /// or does not bind the given access token (or authorization code).
+    ///
+    /// See [`token_hash`](`crate::key_caches::token_hash`).
+    #[display(fmt = "The `{}` of the token does not match.", claim)]
+    token_hash_mismatch {
+        claim: String,
+    },
+
+    /// A verified token was already recorded by the
+    /// [`ReplayGuard`](`crate::key_caches::replay::ReplayGuard`) of the cache
+    /// (i.e., it is being replayed).
+    #[display(fmt = "The token has already been used.")]
+    replayed_token,
+
+    /// A registered claim (e.g., `exp`) of a verified token is not of the
+    /// type defined by RFC7519 (see
+    /// [`registered`](`crate::key_caches::registered`)).
+    ///
+    /// The message string names the claim and its type, but never its value.
+    #[display(fmt = "A registered claim has an invalid type. {}", message)]
+    invalid_registered_claim {
+        message: String,
+    },
+
+    /// A header given to the
+    /// [`RemoteCacheBuilder`](`crate::key_caches::remote::builder::RemoteCacheBuilder`)
+    /// has an invalid name or value.
+    #[display(fmt = "The given header name or value is invalid.")]
+    invalid_header,
 }
 
 impl std::error::Error for Error {}
 
+impl Error {
+    /// What an operator (or a support team) can do about this error.
+    ///
+    /// ```ignore
+    /// if let Err(error) = remote_cache.refresh().await {
+    ///     let advice = error.advice();
+    ///
+    ///     // e.g., "... [check_network] Check the egress rules ..."
+    ///     eprintln!("{} [{}] {}", error, advice.as_str(), advice);
+    /// }
+    /// ```
+    pub fn advice(&self) -> Advice {
+        match self {
+            Self::unable_to_verify_token(error) => match error.kind() {
+                ErrorKind::ExpiredSignature | ErrorKind::ImmatureSignature => {
+                    Advice::CheckClock
+                },
+                _ => Advice::RejectToken,
+            },
+            Self::invalid_algorithm
+            | Self::unrecognized_typ
+            | Self::no_kid_present
+            | Self::revoked_key
+            | Self::unknown_issuer
+            | Self::unable_to_parse_kid_into_uuid { .. }
+            | Self::compressed_token
+            | Self::unexpected_token_use
+            | Self::nonce_mismatch
+            | Self::token_hash_mismatch { .. }
+            | Self::claims_rejected { .. }
+            | Self::replayed_token
+            | Self::invalid_registered_claim { .. } => Advice::RejectToken,
+            Self::no_corresponding_kid_in_store | Self::stale_cache => {
+                Advice::RefreshKeys
+            },
+            Self::unable_to_fetch_keys { .. } => Advice::CheckNetwork,
+            Self::unexpected_status { .. }
+            | Self::redirect_loop
+            | Self::too_many_redirects { .. }
+            | Self::response_too_large { .. }
+            | Self::unexpected_content_type { .. }
+            | Self::unrecognized_response { .. }
+            | Self::invalid_discovery_document { .. }
+            | Self::invalid_certificate { .. }
+            | Self::invalid_jwk { .. }
+            | Self::unable_to_parse_headers
+            | Self::claims_schema_mismatch { .. } => Advice::CheckProvider,
+            Self::invalid_uri
+            | Self::invalid_tls_config { .. }
+            | Self::invalid_header
+            | Self::unusable_key
+            | Self::rotation_not_approved
+            | Self::unknown_tpa
+            | Self::unknown_well_known_tpa { .. }
+            | Self::invalid_policy { .. }
+            | Self::invalid_schema { .. }
+            | Self::missing_fetcher
+            | Self::invalid_verification_limit => Advice::CheckConfiguration,
+            Self::invalid_snapshot { .. } | Self::store_failed { .. } => {
+                Advice::CheckStore
+            },
+            Self::verification_overloaded | Self::issuance_quota_exceeded => {
+                Advice::RetryLater
+            },
+            Self::access_denied => Advice::Deny,
+            Self::step_up_required { .. } => Advice::StepUp,
+            Self::authorization_failed { .. } => Advice::CheckPolicyEngine,
+        }
+    }
+}
+
+/// A machine-readable remedy for an [`Error`] (see [`Error::advice`]).
+///
+/// Its code (see [`as_str`](`Advice::as_str`)) is stable, so that tools can
+/// act on it (e.g., by grouping logs), while its [`fmt::Display`]
+/// implementation prints a human-readable explanation.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Advice {
+    /// The token itself is invalid; the client needs to obtain a new one.
+    RejectToken,
+
+    /// The token is expired (or not yet valid).
+    CheckClock,
+
+    /// The token may have been signed by a key which is not cached yet.
+    RefreshKeys,
+
+    /// The provider could not be reached.
+    CheckNetwork,
+
+    /// The provider responded, but not as expected.
+    CheckProvider,
+
+    /// This crate was configured incorrectly.
+    CheckConfiguration,
+
+    /// The [`CacheStore`](`crate::key_caches::remote::store::CacheStore`)
+    /// failed.
+    CheckStore,
+
+    /// The operation was refused for now, but may succeed later.
+    RetryLater,
+
+    /// The request is not authorized.
+    Deny,
+
+    /// The user needs to re-authenticate more strongly.
+    StepUp,
+
+    /// The authorization policy engine could not be consulted.
+    CheckPolicyEngine,
+}
+
+impl Advice {
+    /// Every advice.
+    pub const ALL: [Self; 11] = [
+        Self::RejectToken,
+        Self::CheckClock,
+        Self::RefreshKeys,
+        Self::CheckNetwork,
+        Self::CheckProvider,
+        Self::CheckConfiguration,
+        Self::CheckStore,
+        Self::RetryLater,
+        Self::Deny,
+        Self::StepUp,
+        Self::CheckPolicyEngine,
+    ];
+
+    /// The (stable) code of this advice (e.g., `"refresh_keys"`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RejectToken => "reject_token",
+            Self::CheckClock => "check_clock",
+            Self::RefreshKeys => "refresh_keys",
+            Self::CheckNetwork => "check_network",
+            Self::CheckProvider => "check_provider",
+            Self::CheckConfiguration => "check_configuration",
+            Self::CheckStore => "check_store",
+            Self::RetryLater => "retry_later",
+            Self::Deny => "deny",
+            Self::StepUp => "step_up",
+            Self::CheckPolicyEngine => "check_policy_engine",
+        }
+    }
+
+    /// A human-readable explanation of this advice.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::RejectToken => {
+                "Reject the token; the client needs to obtain a new one."
+            },
+            Self::CheckClock => {
+                "Check the clock of this host; otherwise, the client needs to \
+                 obtain a new token."
+            },
+            Self::RefreshKeys => {
+                "Refresh the cache, or check whether the provider has rotated \
+                 its keys."
+            },
+            Self::CheckNetwork => {
+                "Check the egress rules, proxy, and DNS settings of this host, \
+                 and whether the provider is available."
+            },
+            Self::CheckProvider => {
+                "Check the provider (e.g., with `webcipher doctor <uri>`); it \
+                 responded unexpectedly."
+            },
+            Self::CheckConfiguration => {
+                "Check the configuration of the caches and the registry (e.g., \
+                 the `uri`s, headers, `TLS` settings, and providers)."
+            },
+            Self::CheckStore => {
+                "Check the cache store (e.g., `Redis`, or the shared volume)."
+            },
+            Self::RetryLater => {
+                "Retry later (e.g., respond with a `503`), or raise the limit."
+            },
+            Self::Deny => "Deny the request; the token does not grant access.",
+            Self::StepUp => {
+                "Challenge the client to re-authenticate the user (see \
+                 `BearerChallenge`)."
+            },
+            Self::CheckPolicyEngine => {
+                "Check that the authorization policy engine is available."
+            },
+        }
+    }
+}
+
+impl fmt::Display for Advice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
 impl From<hyper::Error> for Error {
     fn from(e: hyper::Error) -> Self {
         Self::unable_to_fetch_keys {
@@ -109,7 +591,7 @@ impl From<http::uri::InvalidUri> for Error {
 
 impl From<jsonwebtoken::errors::Error> for Error {
     fn from(e: jsonwebtoken::errors::Error) -> Self {
-        Self::unable_to_verify_token(e)
+        Self::unable_to_verify_token(redact_jwt_error(e))
     }
 }
 
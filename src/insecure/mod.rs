@@ -0,0 +1,66 @@
+//! Reading tokens *without* verifying them.
+//!
+//! ### Warning:
+//! Nothing returned by this module can be trusted: anyone can mint a token
+//! with any header and any claims. Only use it to decide *how* to verify a
+//! token (e.g., which provider to verify it against), and never to decide
+//! whether to accept it.
+//!
+//! For example, tokens can be routed to the right provider by their `iss`
+//! claim before being verified:
+//!
+//! ```ignore
+//! let (_, claims) = insecure::inspect_token(token)?;
+//!
+//! let tpa = match claims["iss"].as_str() {
+//!     Some("https://accounts.google.com") => WellKnownTpa::Google,
+//!     Some("https://appleid.apple.com") => WellKnownTpa::Apple,
+//!     _ => return Err(Unauthorized),
+//! };
+//!
+//! // The token is only trusted from here on.
+//! let data = registry.decrypt::<Claims, _, _>(&tpa, token)?;
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::decode_header;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::Header;
+use serde_json::Value;
+
+use crate::key_caches::reject_compressed;
+use crate::prelude;
+
+/// Decode the header and the claims of the given token, *without* verifying
+/// its signature (or any of its claims, e.g., its `exp` time).
+///
+/// Fails with [`Error::unable_to_verify_token`] if the token is not made up of
+/// three segments, or if its header or its claims cannot be decoded, and with
+/// [`Error::compressed_token`] if its claims are compressed.
+///
+/// See the [module level documentation](`self`).
+///
+/// [`Error::unable_to_verify_token`]: `crate::error::Error::unable_to_verify_token`
+/// [`Error::compressed_token`]: `crate::error::Error::compressed_token`
+pub fn inspect_token(token: &str) -> prelude::Result<(Header, Value)> {
+    reject_compressed(token)?;
+
+    let header = decode_header(token)?;
+
+    let payload = match token.split('.').collect::<Vec<_>>().as_slice() {
+        [_, payload, _] => *payload,
+        _ => Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?,
+    };
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+    let claims = serde_json::from_slice(&payload)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    Ok((header, claims))
+}
@@ -0,0 +1,56 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::Algorithm;
+use serde_json::json;
+
+use crate::insecure::inspect_token;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+fn segment(value: &serde_json::Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+}
+
+#[test]
+/// The header and the claims should be decoded, regardless of whether the
+/// token is valid.
+fn test_inspect_token() {
+    let claims = json!({ "iss": "https://issuer.example.com", "exp": 1 });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+
+    let (header, inspected) = inspect_token(&token).unwrap();
+    assert_eq!(header.alg, Algorithm::RS256);
+    assert_eq!(header.kid.as_deref(), Some(KEY_PAIRS[0].kid));
+    assert_eq!(inspected, claims);
+
+    let header = json!({ "alg": "HS256", "typ": "JWT" });
+    let forged = format!("{}.{}.forged", segment(&header), segment(&claims));
+
+    let (header, inspected) = inspect_token(&forged).unwrap();
+    assert_eq!(header.alg, Algorithm::HS256);
+    assert_eq!(inspected, claims);
+}
+
+#[test]
+/// Malformed and compressed tokens should be rejected, without quoting them.
+fn test_invalid_tokens() {
+    let header = segment(&json!({ "alg": "RS256" }));
+    let tokens = [
+        String::from("a.b"),
+        format!("{}.{}", header, segment(&json!({}))),
+        format!("{}.{}.c.d", header, segment(&json!({}))),
+        format!("{}.not-base64!.c", header),
+        format!("{}.{}.c", header, URL_SAFE_NO_PAD.encode("[\"secret\"")),
+    ];
+
+    for token in tokens {
+        let error = inspect_token(&token).unwrap_err();
+
+        assert!(matches!(error, Error::unable_to_verify_token(_)));
+        assert!(!format!("{:?}", error).contains("secret"));
+    }
+
+    let compressed = segment(&json!({ "alg": "RS256", "zip": "DEF" }));
+    let token = format!("{}.{}.c", compressed, segment(&json!({})));
+    assert_eq!(inspect_token(&token).unwrap_err(), Error::compressed_token);
+}
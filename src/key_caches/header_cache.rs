@@ -0,0 +1,148 @@
+//! Memoizing the parsed headers of tokens.
+//!
+//! Gateways often see the same token (or tokens with the same header) many
+//! times over. A [`super::remote::RemoteCache`] with a [`HeaderCache`] looks
+//! up the header of each token by its first segment, instead of decoding it
+//! (i.e., `base64` and `JSON`) every time:
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .header_cache(LruHeaderCache::new(1024))
+//!     .build()?;
+//! ```
+//!
+//! Only the header is memoized; the signature and the claims of every token
+//! are still verified and decoded, so this can be used even when memoizing
+//! the result of a verification is not an option (e.g., for policy reasons).
+//!
+//! ### Note:
+//! Headers are memoized *before* the token is verified, so anyone can insert
+//! entries. The cache must therefore be bounded (as [`LruHeaderCache`] is),
+//! so that unique headers can only evict other entries, instead of growing
+//! the cache indefinitely. A memoized header is always exactly the decoded
+//! segment it is keyed by, so entries cannot be poisoned.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use jsonwebtoken::Header;
+
+/// A (thread-safe) memo of parsed headers, keyed by the first segment of the
+/// token they were decoded from.
+///
+/// Only headers which passed the checks that are independent of the cache's
+/// configuration (e.g., which are not compressed) are inserted.
+///
+/// See the [module level documentation](`self`).
+pub trait HeaderCache: Send + Sync {
+    /// The header previously decoded from the given segment, if it is still
+    /// memoized.
+    fn get(&self, segment: &str) -> Option<Header>;
+
+    /// Memoize the header decoded from the given segment.
+    fn insert(&self, segment: &str, header: Header);
+}
+
+/// An in-memory [`HeaderCache`], holding up to `capacity` headers.
+///
+/// Once full, the least recently used header is evicted.
+#[derive(Debug)]
+pub struct LruHeaderCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The headers, along with the tick of their last use.
+    headers: BTreeMap<String, (Header, u64)>,
+
+    /// The segments, indexed by the tick of their last use.
+    recency: BTreeMap<u64, String>,
+
+    tick: u64,
+}
+
+impl Entries {
+    /// Advance the tick, marking the given segment as its last use.
+    fn touch(&mut self, segment: &str) -> u64 {
+        self.tick += 1;
+        self.recency.insert(self.tick, segment.into());
+        self.tick
+    }
+}
+
+impl LruHeaderCache {
+    /// Create an empty cache holding up to `capacity` headers.
+    ///
+    /// A `capacity` of `0` never memoizes anything.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The maximum number of headers this cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of headers currently memoized.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .headers
+            .len()
+    }
+
+    /// Check to see if no headers are currently memoized.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl HeaderCache for LruHeaderCache {
+    fn get(&self, segment: &str) -> Option<Header> {
+        let Self { entries, .. } = self;
+
+        let mut entries =
+            entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let used = entries.headers.get(segment).map(|(_, used)| *used)?;
+        entries.recency.remove(&used);
+        let tick = entries.touch(segment);
+
+        let (header, used) = entries.headers.get_mut(segment)?;
+        *used = tick;
+
+        Some(header.clone())
+    }
+
+    fn insert(&self, segment: &str, header: Header) {
+        let Self { capacity, entries } = self;
+
+        if *capacity == 0 {
+            return;
+        };
+
+        let mut entries =
+            entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        match entries.headers.get(segment).map(|(_, used)| *used) {
+            Some(used) => {
+                entries.recency.remove(&used);
+            },
+            None if entries.headers.len() >= *capacity => {
+                if let Some((_, evicted)) = entries.recency.pop_first() {
+                    entries.headers.remove(&evicted);
+                };
+            },
+            None => (),
+        };
+
+        let tick = entries.touch(segment);
+        entries.headers.insert(segment.into(), (header, tick));
+    }
+}
@@ -0,0 +1,85 @@
+//! A common interface over the [`LocalCache`] and the [`RemoteCache`].
+//!
+//! First-party tokens (signed by a [`LocalCache`]) and third-party tokens
+//! (signed by a provider, whose keys are held by a [`RemoteCache`]) are
+//! otherwise verified through entirely separate code paths. The [`KeyCache`]
+//! trait lets a [`KeyRegistry`](`crate::registry::KeyRegistry`) hold both
+//! kinds of caches (or any other implementation) side by side:
+//!
+//! ```ignore
+//! let mut local_cache = LocalCache::new(Algorithm::HS256);
+//! local_cache.add_key(kid, encoding_key, decoding_key);
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_well_known(Tpa::Google)
+//!     .add_cache(Tpa::FirstParty, local_cache)
+//!     .finish()
+//!     .await?;
+//!
+//! // Routed to the `LocalCache`.
+//! let data = registry.decrypt::<Claims, _, _>(&Tpa::FirstParty, token)?;
+//! ```
+//!
+//! The trait is object safe, so its [`decrypt`](`KeyCache::decrypt`) returns
+//! the claims as a `JSON` value; the registry deserializes them into the
+//! requested type.
+
+use std::any::Any;
+
+use async_trait::async_trait;
+use jsonwebtoken::TokenData;
+use serde_json::Value;
+
+use crate::key_caches::local::LocalCache;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// A cache of keys which can verify tokens.
+///
+/// See the [module level documentation](`self`).
+#[async_trait]
+pub trait KeyCache: Any + Send + Sync {
+    /// Refresh the keys of this cache (e.g., by fetching them again).
+    async fn refresh(&mut self) -> prelude::Result<()>;
+
+    /// Decrypt (and verify) the given token, using the keys of this cache.
+    fn decrypt(&self, token: &str) -> prelude::Result<TokenData<Value>>;
+
+    /// Check to see if the keys of this cache have not expired yet.
+    fn is_fresh(&self) -> bool;
+}
+
+/// Keys are refreshed by fetching them, and tokens are verified exactly as in
+/// [`RemoteCache::decrypt`] (i.e., only while the cache is usable).
+#[async_trait]
+impl KeyCache for RemoteCache {
+    async fn refresh(&mut self) -> prelude::Result<()> {
+        RemoteCache::refresh(self).await
+    }
+
+    fn decrypt(&self, token: &str) -> prelude::Result<TokenData<Value>> {
+        RemoteCache::decrypt(self, token)
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.is_cache_fresh()
+    }
+}
+
+/// Keys are added (and revoked) locally, so refreshing is a no-op, and the
+/// cache is always fresh. Tokens are verified exactly as in
+/// [`LocalCache::decrypt`], including their `exp` claim.
+#[async_trait]
+impl KeyCache for LocalCache {
+    async fn refresh(&mut self) -> prelude::Result<()> {
+        Ok(())
+    }
+
+    fn decrypt(&self, token: &str) -> prelude::Result<TokenData<Value>> {
+        LocalCache::decrypt(self, token, true)
+    }
+
+    fn is_fresh(&self) -> bool {
+        true
+    }
+}
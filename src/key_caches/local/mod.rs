@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use jsonwebtoken::encode;
 use jsonwebtoken::Algorithm;
@@ -14,8 +16,15 @@ use uuid::Uuid;
 
 use crate::error::Error;
 use crate::key_caches::decrypt;
+use crate::key_caches::KeyHint;
+use crate::key_caches::Selected;
+use crate::key_caches::local::quota::IssuanceQuota;
 use crate::prelude;
+use crate::prelude::Timestamp;
+use crate::time::now;
+use crate::time::Clock;
 
+pub mod quota;
 #[cfg(test)]
 mod tests;
 
@@ -23,13 +32,37 @@ mod tests;
 pub struct LocalCache {
     pub(crate) algorithm: Algorithm,
     pub(crate) keys: BTreeMap<Uuid, (EncodingKey, DecodingKey)>,
+    pub(crate) revoked: BTreeMap<Uuid, Timestamp>,
+    pub(crate) issuance_quota: Option<IssuanceQuota>,
+    pub(crate) pending: BTreeSet<Uuid>,
+    pub(crate) rotation_approver: Option<RotationApprover>,
+    pub(crate) clock: Option<Arc<dyn Clock>>,
 }
 
+/// A call-back which must confirm before a staged key is activated for
+/// signing (see [`LocalCache::activate_key`]).
+///
+/// Returning `true` approves the activation of the key with the given `kid`.
+pub type RotationApprover = Arc<dyn Fn(&Uuid) -> bool + Send + Sync>;
+
 impl LocalCache {
     pub fn new(algorithm: Algorithm) -> Self {
         let keys = BTreeMap::default();
+        let revoked = BTreeMap::default();
+        let issuance_quota = None;
+        let pending = BTreeSet::default();
+        let rotation_approver = None;
+        let clock = None;
 
-        Self { algorithm, keys }
+        Self {
+            algorithm,
+            keys,
+            revoked,
+            issuance_quota,
+            pending,
+            rotation_approver,
+            clock,
+        }
     }
 
     pub fn add_key(
@@ -46,30 +79,156 @@ impl LocalCache {
         &mut self,
         kid: Uuid,
     ) {
-        let Self { keys, .. } = self;
+        let Self { keys, pending, .. } = self;
         keys.remove(&kid);
+        pending.remove(&kid);
+    }
+
+    /// Stage a new key for rotation.
+    ///
+    /// A staged key is immediately used to verify tokens (so that it can be
+    /// distributed ahead of time), but is *not* used for signing until it has
+    /// been activated (see [`activate_key`](`LocalCache::activate_key`)).
+    pub fn stage_key(
+        &mut self,
+        kid: Uuid,
+        encoding_key: EncodingKey,
+        decoding_key: DecodingKey,
+    ) {
+        let Self { keys, pending, .. } = self;
+        let _ = keys.insert(kid, (encoding_key, decoding_key));
+        let _ = pending.insert(kid);
+    }
+
+    /// Activate a staged key, so that it will be used for signing.
+    ///
+    /// If a rotation approver has been set (see
+    /// [`set_rotation_approver`](`LocalCache::set_rotation_approver`)), it
+    /// must confirm the activation; otherwise, the key stays staged and
+    /// [`Error::rotation_not_approved`] is returned.
+    ///
+    /// Activating a key which is not staged returns
+    /// [`Error::no_corresponding_kid_in_store`].
+    pub fn activate_key(&mut self, kid: Uuid) -> prelude::Result<()> {
+        let Self {
+            pending,
+            rotation_approver,
+            ..
+        } = self;
+
+        if !pending.contains(&kid) {
+            return Err(Error::no_corresponding_kid_in_store);
+        };
+
+        match rotation_approver {
+            Some(rotation_approver) if !rotation_approver(&kid) => {
+                Err(Error::rotation_not_approved)
+            },
+            _ => {
+                pending.remove(&kid);
+                Ok(())
+            },
+        }
+    }
+
+    /// Set (or, if [`None`], remove) the call-back which must confirm the
+    /// activation of staged keys.
+    ///
+    /// This is useful in regulated environments, e.g., in order to require
+    /// the sign-off of two operators (or a deployment flag) before a new
+    /// signing key goes live.
+    pub fn set_rotation_approver<F>(&mut self, rotation_approver: Option<F>)
+    where
+        F: Fn(&Uuid) -> bool + Send + Sync + 'static,
+    {
+        self.rotation_approver = rotation_approver.map(|rotation_approver| {
+            Arc::new(rotation_approver) as RotationApprover
+        });
+    }
+
+    /// Revoke the key with the given `kid`, starting at the given time (in
+    /// Unix-Time).
+    ///
+    /// Once the revocation has taken effect, *all* tokens signed by that key
+    /// are rejected with [`Error::revoked_key`], even if they have not expired
+    /// yet. The key itself is kept (so that the revocation can be persisted
+    /// alongside it), but is no longer used by
+    /// [`encrypt`](`LocalCache::encrypt`).
+    ///
+    /// Revoking an already revoked key will overwrite the previous time.
+    ///
+    /// ### Note:
+    /// The revocation set is not persisted by this cache. Use
+    /// [`revoked`](`LocalCache::revoked`) and
+    /// [`revoked_mut`](`LocalCache::revoked_mut`) to store and restore it.
+    pub fn revoke_key(
+        &mut self,
+        kid: Uuid,
+        at: Timestamp,
+    ) {
+        let Self { revoked, .. } = self;
+        let _ = revoked.insert(kid, at);
     }
 
+    /// Check to see if the key with the given `kid` has been revoked (as of
+    /// now).
+    pub fn is_revoked(&self, kid: &Uuid) -> bool {
+        let Self { revoked, .. } = self;
+        let now = self.now();
+
+        revoked.get(kid).is_some_and(|at| *at <= now)
+    }
+
+    /// Set (or, if [`None`], remove) the per-subject issuance quota.
+    ///
+    /// The quota is consulted by [`encrypt`](`LocalCache::encrypt`) for every
+    /// token containing a `sub` claim; tokens without one are not limited.
+    /// See [`quota`].
+    pub fn set_issuance_quota<F>(&mut self, issuance_quota: Option<F>)
+    where
+        F: Fn(&str, Timestamp) -> bool + Send + Sync + 'static,
+    {
+        self.issuance_quota = issuance_quota
+            .map(|issuance_quota| Arc::new(issuance_quota) as IssuanceQuota);
+    }
+
+    /// Sign the given claims using a random (active and non-revoked) key.
+    ///
+    /// ### Note:
+    /// If an issuance quota has been set (see
+    /// [`set_issuance_quota`](`LocalCache::set_issuance_quota`)) and the
+    /// subject has exhausted it, the claims are rejected with
+    /// [`Error::issuance_quota_exceeded`]. The quota is only consulted once
+    /// the claims have been signed, so that failed issuances are not counted.
     pub fn encrypt<Claims>(&self, claims: Claims) -> prelude::Result<String>
     where
         Claims: Serialize,
     {
-        let Self { algorithm, keys } = self;
+        let Self {
+            algorithm,
+            keys,
+            issuance_quota,
+            ..
+        } = self;
+
+        let kids = || {
+            keys.keys().filter(|kid| {
+                !self.pending.contains(kid) && !self.is_revoked(kid)
+            })
+        };
 
-        let length = keys.len();
+        let length = kids().count();
         let rand_index = match length {
             0 => 0,
             _ => fastrand::usize(..length),
         };
 
-        let kid = *keys
-            .keys()
-            .collect::<Vec<_>>()
-            .get(rand_index)
+        let kid = kids()
+            .nth(rand_index)
             .ok_or(Error::no_corresponding_kid_in_store)?;
 
         let (encoding_key, _) =
-            keys.get(&kid).ok_or(Error::no_corresponding_kid_in_store)?;
+            keys.get(kid).ok_or(Error::no_corresponding_kid_in_store)?;
 
         let header = Header {
             alg: *algorithm,
@@ -80,6 +239,19 @@ impl LocalCache {
 
         let token = encode(&header, &claims, encoding_key)?;
 
+        // Only count issuances which have actually been signed.
+        if let Some(issuance_quota) = issuance_quota {
+            let claims = serde_json::to_value(&claims)?;
+            let now = self.now();
+
+            match claims.get("sub").and_then(|sub| sub.as_str()) {
+                Some(sub) if !issuance_quota(sub, now) => {
+                    return Err(Error::issuance_quota_exceeded)
+                },
+                _ => (),
+            };
+        };
+
         Ok(token)
     }
 
@@ -89,16 +261,25 @@ impl LocalCache {
         validate_exp: bool,
     ) -> prelude::Result<TokenData<Claims>>
     where
-        String: From<I>,
+        I: AsRef<str>,
         Claims: for<'de> Deserialize<'de>,
     {
-        let Self { algorithm, keys } = self;
+        let Self {
+            algorithm,
+            keys,
+            clock,
+            ..
+        } = self;
 
-        let selector = |kid: &String| {
-            let kid = Uuid::from_str(&*kid)?;
+        let selector = |key_hint: &KeyHint| {
+            let kid = key_hint.kid.ok_or(Error::no_kid_present)?;
+            let kid = Uuid::from_str(kid)?;
+            if self.is_revoked(&kid) {
+                return Err(Error::revoked_key);
+            };
             let x = keys
                 .get(&kid)
-                .map(|(_, decoding_key)| decoding_key)
+                .map(|(_, decoding_key)| Selected::from(decoding_key))
                 .ok_or(Error::no_corresponding_kid_in_store);
             x
         };
@@ -106,7 +287,33 @@ impl LocalCache {
         let mut validation = Validation::new(*algorithm);
         validation.validate_exp = validate_exp;
 
-        decrypt(token, selector, Some(validation), false)
+        decrypt(
+            token.as_ref(),
+            selector,
+            Some(validation),
+            false,
+            false,
+            None,
+            clock.as_deref(),
+        )
+    }
+
+    /// Set (or, if [`None`], remove) the [`Clock`] that revocations, issuance
+    /// quotas, and the `exp` of tokens are checked against.
+    ///
+    /// See [`time`](`crate::time`).
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.clock = clock;
+    }
+
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.clock.as_ref()
+    }
+
+    /// The current time, according to the [`Clock`] of this [`LocalCache`]
+    /// (or else, the system).
+    fn now(&self) -> Timestamp {
+        self.clock.as_ref().map_or_else(now, |clock| clock.now())
     }
 
     pub fn keys(&self) -> &BTreeMap<Uuid, (EncodingKey, DecodingKey)> {
@@ -119,6 +326,18 @@ impl LocalCache {
         &mut self.keys
     }
 
+    pub fn revoked(&self) -> &BTreeMap<Uuid, Timestamp> {
+        &self.revoked
+    }
+
+    pub fn revoked_mut(&mut self) -> &mut BTreeMap<Uuid, Timestamp> {
+        &mut self.revoked
+    }
+
+    pub fn pending(&self) -> &BTreeSet<Uuid> {
+        &self.pending
+    }
+
     pub fn algorithm(&self) -> &Algorithm {
         &self.algorithm
     }
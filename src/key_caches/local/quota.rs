@@ -0,0 +1,79 @@
+//! Per-subject token issuance quotas for a [`super::LocalCache`].
+//!
+//! Services which mint short-lived tokens on behalf of users (e.g.,
+//! verification or password-reset tokens) are an easy target for abuse.
+//! A quota limits how many tokens can be issued for the same `sub` claim
+//! within some window of time.
+//!
+//! Quotas are call-back based, so that they can be backed by a shared store
+//! (e.g., a database) when running multiple instances. A simple, in-memory
+//! [`FixedWindowQuota`] is provided for single-instance services.
+//!
+//! ```ignore
+//! let quota = FixedWindowQuota::new(5, Duration::from_secs(3600));
+//! local_cache.set_issuance_quota(Some(move |sub: &str, now| quota.check(sub, now)));
+//!
+//! // The sixth token for "user" within the hour is rejected.
+//! let err = local_cache.encrypt(json!({ "sub": "user" })).unwrap_err();
+//! assert_eq!(err, Error::issuance_quota_exceeded);
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::prelude::Timestamp;
+
+/// A call-back deciding whether another token can be issued for the given
+/// subject (i.e., `sub` claim) at the given time (in Unix-Time).
+///
+/// Returning `true` allows (and counts) the issuance.
+pub type IssuanceQuota = Arc<dyn Fn(&str, Timestamp) -> bool + Send + Sync>;
+
+/// An in-memory quota allowing up to `max_tokens` issuances per subject within
+/// each fixed window of time.
+#[derive(Debug)]
+pub struct FixedWindowQuota {
+    max_tokens: usize,
+    window: Duration,
+    issued: Mutex<BTreeMap<String, (Timestamp, usize)>>,
+}
+
+impl FixedWindowQuota {
+    pub fn new(max_tokens: usize, window: Duration) -> Self {
+        Self {
+            max_tokens,
+            window,
+            issued: Mutex::default(),
+        }
+    }
+
+    /// Check (and count) an issuance for the given subject at the given time.
+    ///
+    /// Windows start at the first issuance for a subject, and are reset once
+    /// they have elapsed.
+    pub fn check(&self, sub: &str, now: Timestamp) -> bool {
+        let Self {
+            max_tokens,
+            window,
+            issued,
+        } = self;
+
+        let mut issued =
+            issued.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        issued.retain(|_, (start, _)| {
+            now < start.saturating_add(window.as_secs())
+        });
+
+        let (_, count) = issued.entry(sub.into()).or_insert((now, 0));
+
+        match *count < *max_tokens {
+            true => {
+                *count += 1;
+                true
+            },
+            false => false,
+        }
+    }
+}
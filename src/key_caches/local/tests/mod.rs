@@ -1,10 +1,24 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::Algorithm;
 use jsonwebtoken::DecodingKey;
 use jsonwebtoken::EncodingKey;
 use jsonwebtoken::TokenData;
 use uuid::Uuid;
 
+use crate::error::Error;
+use crate::key_caches::key_cache::KeyCache;
+use crate::key_caches::local::quota::FixedWindowQuota;
 use crate::key_caches::local::LocalCache;
+use crate::time::now;
+use crate::time::Clock;
+use crate::time::ManualClock;
 
 #[test]
 /// This test will test to make sure that encryption and decryption using the
@@ -37,3 +51,295 @@ fn basic() {
 
     assert_eq!(claims, decrypted_claims);
 }
+
+#[test]
+/// Tokens containing a `zip` header parameter must be rejected before they are
+/// decoded.
+fn compressed() {
+    let kid = Uuid::new_v4();
+    let ek = EncodingKey::from_secret("Hailey is the best!".as_ref());
+    let dk = DecodingKey::from_secret("Hailey is the best!".as_ref());
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(kid, ek, dk);
+
+    let header = URL_SAFE_NO_PAD.encode(format!(
+        r#"{{"alg":"HS512","typ":"JWT","kid":"{}","zip":"DEF"}}"#,
+        kid
+    ));
+    let token = format!("{}.e30.c2ln", header);
+
+    let err = local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .unwrap_err();
+
+    assert_eq!(err, Error::compressed_token);
+}
+
+#[test]
+/// Tokens signed by a revoked key must be rejected, even if they have not
+/// expired yet, while tokens signed by other keys remain valid.
+fn revoked() {
+    let revoked_kid = Uuid::new_v4();
+    let kid = Uuid::new_v4();
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(
+        revoked_kid,
+        EncodingKey::from_secret("compromised".as_ref()),
+        DecodingKey::from_secret("compromised".as_ref()),
+    );
+
+    let claims = serde_json::json!({ "exp": 20_000_000_000u64 });
+    let token = local_cache.encrypt(&claims).unwrap();
+
+    local_cache.add_key(
+        kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache.revoke_key(revoked_kid, 0);
+
+    let err = local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .unwrap_err();
+    assert_eq!(err, Error::revoked_key);
+
+    let token = local_cache.encrypt(&claims).unwrap();
+    local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .unwrap();
+
+    assert_eq!(local_cache.revoked().get(&revoked_kid), Some(&0));
+}
+
+#[test]
+/// Revocations scheduled in the future must not take effect yet.
+fn revoked_in_future() {
+    let kid = Uuid::new_v4();
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(
+        kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache.revoke_key(kid, 20_000_000_000);
+
+    let claims = serde_json::json!({ "exp": 20_000_000_000u64 });
+    let token = local_cache.encrypt(&claims).unwrap();
+
+    local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .unwrap();
+}
+
+#[test]
+/// Revocations and the `exp` of tokens must be checked against the clock of
+/// the cache (if it has one).
+fn clock() {
+    let kid = Uuid::new_v4();
+    let clock = Arc::new(ManualClock::new(1_000_000_000));
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(
+        kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache.set_clock(Some(Arc::clone(&clock) as Arc<dyn Clock>));
+    local_cache.revoke_key(kid, 1_000_003_600);
+
+    let claims = serde_json::json!({ "exp": 1_000_000_060u64 });
+    let token = local_cache.encrypt(&claims).unwrap();
+    local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .unwrap();
+
+    clock.advance(Duration::from_secs(121));
+    let err = local_cache
+        .decrypt::<serde_json::Value, _>(&token, true)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::unable_to_verify_token(error)
+            if *error.kind() == ErrorKind::ExpiredSignature,
+    ));
+
+    clock.set(1_000_003_600);
+    assert!(local_cache.is_revoked(&kid));
+    let err = local_cache
+        .decrypt::<serde_json::Value, _>(&token, false)
+        .err()
+        .unwrap();
+    assert_eq!(err, Error::revoked_key);
+}
+
+#[test]
+/// Subjects exceeding their issuance quota must be rejected, while other
+/// subjects (and claims without a subject) are unaffected.
+fn issuance_quota() {
+    let kid = Uuid::new_v4();
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(
+        kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+
+    let quota = FixedWindowQuota::new(2, Duration::from_secs(3600));
+    local_cache
+        .set_issuance_quota(Some(move |sub: &str, now| quota.check(sub, now)));
+
+    let claims = serde_json::json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    local_cache.encrypt(&claims).unwrap();
+    local_cache.encrypt(&claims).unwrap();
+
+    let err = local_cache.encrypt(&claims).unwrap_err();
+    assert_eq!(err, Error::issuance_quota_exceeded);
+
+    let claims =
+        serde_json::json!({ "sub": "other", "exp": 20_000_000_000u64 });
+    local_cache.encrypt(&claims).unwrap();
+
+    let claims = serde_json::json!({ "exp": 20_000_000_000u64 });
+    local_cache.encrypt(&claims).unwrap();
+}
+
+#[test]
+/// A [`FixedWindowQuota`] must reset once its window has elapsed.
+fn fixed_window_quota() {
+    let quota = FixedWindowQuota::new(1, Duration::from_secs(60));
+
+    assert!(quota.check("user", 1_000));
+    assert!(!quota.check("user", 1_059));
+    assert!(quota.check("user", 1_060));
+}
+
+#[test]
+/// A [`FixedWindowQuota`] whose window never elapses must still be enforced.
+fn fixed_window_quota_unbounded() {
+    let quota = FixedWindowQuota::new(1, Duration::MAX);
+
+    assert!(quota.check("user", 1_000));
+    assert!(!quota.check("user", u64::MAX - 1));
+}
+
+#[test]
+/// Issuances which fail (e.g., since there is no signing key) must not count
+/// towards the issuance quota.
+fn issuance_quota_without_keys() {
+    let kid = Uuid::new_v4();
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    let quota = FixedWindowQuota::new(1, Duration::from_secs(3600));
+    local_cache
+        .set_issuance_quota(Some(move |sub: &str, now| quota.check(sub, now)));
+
+    let claims = serde_json::json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let err = local_cache.encrypt(&claims).unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    local_cache.add_key(
+        kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache.encrypt(&claims).unwrap();
+}
+
+#[test]
+/// Claims which cannot be signed (e.g., since the key does not fit the
+/// algorithm) must not count towards the issuance quota.
+fn issuance_quota_unsigned() {
+    let quota = Arc::new(FixedWindowQuota::new(1, Duration::from_secs(3600)));
+    let shared = Arc::clone(&quota);
+
+    let mut local_cache = LocalCache::new(Algorithm::RS256);
+    local_cache.add_key(
+        Uuid::new_v4(),
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache
+        .set_issuance_quota(Some(move |sub: &str, now| shared.check(sub, now)));
+
+    let claims = serde_json::json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let err = local_cache.encrypt(&claims).unwrap_err();
+    assert_ne!(err, Error::issuance_quota_exceeded);
+    assert!(quota.check("user", now()));
+}
+
+#[test]
+/// Staged keys must verify tokens, but must only be used for signing once
+/// their activation has been approved.
+fn rotation_approval() {
+    let old_kid = Uuid::new_v4();
+    let new_kid = Uuid::new_v4();
+
+    let mut local_cache = LocalCache::new(Algorithm::HS512);
+    local_cache.add_key(
+        old_kid,
+        EncodingKey::from_secret("Hailey is the best!".as_ref()),
+        DecodingKey::from_secret("Hailey is the best!".as_ref()),
+    );
+    local_cache.stage_key(
+        new_kid,
+        EncodingKey::from_secret("rotated".as_ref()),
+        DecodingKey::from_secret("rotated".as_ref()),
+    );
+
+    let approvals = Arc::new(AtomicUsize::new(0));
+    local_cache.set_rotation_approver(Some({
+        let approvals = approvals.clone();
+        move |_: &Uuid| approvals.load(Ordering::SeqCst) >= 2
+    }));
+
+    let claims = serde_json::json!({ "exp": 20_000_000_000u64 });
+    for _ in 0..8 {
+        let token = local_cache.encrypt(&claims).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.kid, Some(old_kid.to_string()));
+    }
+
+    approvals.fetch_add(1, Ordering::SeqCst);
+    let err = local_cache.activate_key(new_kid).unwrap_err();
+    assert_eq!(err, Error::rotation_not_approved);
+
+    approvals.fetch_add(1, Ordering::SeqCst);
+    local_cache.activate_key(new_kid).unwrap();
+    local_cache.remove_key(old_kid);
+
+    let token = local_cache.encrypt(&claims).unwrap();
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.kid, Some(new_kid.to_string()));
+    assert!(local_cache.pending().is_empty());
+}
+
+#[tokio::test]
+/// Through the `KeyCache` trait, the cache should always be fresh, and verify
+/// tokens (including their `exp` claim) into `JSON` values.
+async fn key_cache() {
+    let mut local_cache = LocalCache::new(Algorithm::HS256);
+    local_cache.add_key(
+        Uuid::new_v4(),
+        EncodingKey::from_secret("first-party".as_ref()),
+        DecodingKey::from_secret("first-party".as_ref()),
+    );
+
+    let cache: &mut dyn KeyCache = &mut local_cache;
+    cache.refresh().await.unwrap();
+    assert!(cache.is_fresh());
+
+    let claims = serde_json::json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let token = local_cache.encrypt(&claims).unwrap();
+    let TokenData { claims: decrypted, .. } =
+        KeyCache::decrypt(&local_cache, &token).unwrap();
+    assert_eq!(decrypted, claims);
+
+    let expired = local_cache.encrypt(serde_json::json!({ "exp": 1 })).unwrap();
+    let err = KeyCache::decrypt(&local_cache, &expired).unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+}
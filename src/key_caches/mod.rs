@@ -7,60 +7,412 @@
 //! source and re-compute the corresponding [`DecodingKey`] if the `JWK`s at the
 //! source have not been rotated yet.
 
+use std::collections::HashSet;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use jsonwebtoken::decode;
-use jsonwebtoken::decode_header;
+use jsonwebtoken::get_current_timestamp;
 use jsonwebtoken::Algorithm;
 use jsonwebtoken::DecodingKey;
 use jsonwebtoken::Header;
 use jsonwebtoken::TokenData;
 use jsonwebtoken::Validation;
+use serde::de::IgnoredAny;
 use serde::Deserialize;
+use serde_json::Value;
 
+use crate::key_caches::header_cache::HeaderCache;
+use crate::key_caches::registered::check_registered_claims;
+use crate::key_caches::validation::validate;
 use crate::prelude;
 use crate::prelude::Error;
+use crate::prelude::Timestamp;
+use crate::time::Clock;
 
+pub mod header_cache;
+pub mod key_cache;
 pub mod local;
+pub mod nonce;
+pub mod normalized;
+mod projection;
+pub mod registered;
 pub mod remote;
+pub mod replay;
+pub mod standard;
+pub mod token_hash;
+mod validation;
 
 /// Decrypt the given token into it's [`TokenData`] struct.
 ///
-/// If the `alg` in the headers is not [`Algorithm::RS256`], or if a `kid` is
-/// not present (or if it is present but the cache does not contain a match),
-/// this function will return an error. Otherwise, the function will return try
-/// to decrypt the data using the [`DecodingKey`] found by calling the call-back
-/// function.
-fn decrypt<'b, Claims, I, F>(
-    token: I,
+/// If the `alg` in the headers is not [`Algorithm::RS256`], or if the
+/// call-back function cannot find a key matching the [`KeyHint`] of the
+/// headers, this function will return an error. Otherwise, the function will
+/// return try to decrypt the data using the [`DecodingKey`] found by calling
+/// the call-back function.
+///
+/// Tokens which declare a `zip` (i.e., compression) header parameter are
+/// rejected *before* any further processing takes place (see
+/// [`reject_compressed`]).
+///
+/// If `strict_claims` is set, the types of the registered claims are checked
+/// (see [`check_registered_claims`]) before the claims are deserialized.
+///
+/// If a [`HeaderCache`] is given, the headers are looked up in it first (see
+/// [`check_header`]).
+///
+/// If a [`Clock`] is given, the `exp` and `nbf` claims are checked against it
+/// (see [`validate`]), instead of against the time of the system.
+///
+/// Signatures which the selected key has already verified (see [`Selected`])
+/// are not verified again.
+///
+/// ### Note:
+/// The token is borrowed throughout: its headers and its payload are only
+/// decoded once, and the [`KeyHint`] borrows from the headers.
+fn decrypt<'b, Claims, F>(
+    token: &str,
     selector: F,
     validation: Option<Validation>,
     rs256_alg_required: bool,
+    strict_claims: bool,
+    header_cache: Option<&dyn HeaderCache>,
+    clock: Option<&dyn Clock>,
 ) -> prelude::Result<TokenData<Claims>>
 where
-    String: From<I>,
     Claims: for<'a> Deserialize<'a>,
-    F: for<'a> Fn(&'a String) -> prelude::Result<&'b DecodingKey>,
+    F: for<'a, 'h> Fn(&'a KeyHint<'h>) -> prelude::Result<Selected<'b>>,
+{
+    let header = check_header(token, rs256_alg_required, header_cache)?;
+
+    let mut validation =
+        validation.unwrap_or_else(|| Validation::new(header.alg));
+    let selected = selector(&KeyHint::from(&header))?;
+    let mut payload = Vec::new();
+    verify_signature(token, &header, selected, &validation, &mut payload)?;
+
+    let now = clock.map_or_else(get_current_timestamp, |clock| clock.now());
+
+    let claims = match strict_claims {
+        true => {
+            let required_spec_claims =
+                std::mem::take(&mut validation.required_spec_claims);
+
+            let claims = serde_json::from_slice::<Value>(&payload)
+                .map_err(jsonwebtoken::errors::Error::from)?;
+            validate(&payload, &validation, now)?;
+            check_registered_claims(&claims)?;
+            check_required_claims(&claims, required_spec_claims)?;
+
+            serde_json::from_value(claims)
+                .map_err(jsonwebtoken::errors::Error::from)?
+        },
+        false => {
+            let claims = serde_json::from_slice(&payload)
+                .map_err(jsonwebtoken::errors::Error::from)?;
+            validate(&payload, &validation, now)?;
+
+            claims
+        },
+    };
+
+    Ok(TokenData { header, claims })
+}
+
+/// Verify the signature of the given token (whose headers were already
+/// decoded), decoding its payload into the given buffer.
+///
+/// `RSA` signatures are verified directly (unless the selected key has
+/// already verified an `RS256` signature). Any other signature is verified by
+/// `jsonwebtoken` instead, which also checks that the family of the
+/// [`DecodingKey`] matches the `alg` (at the cost of decoding the headers
+/// once more).
+fn verify_signature(
+    token: &str,
+    header: &Header,
+    selected: Selected,
+    validation: &Validation,
+    buffer: &mut Vec<u8>,
+) -> prelude::Result<()> {
+    use jsonwebtoken::errors::ErrorKind;
+
+    let Header { alg, .. } = *header;
+    let Selected {
+        decoding_key,
+        verified,
+    } = selected;
+    let error = |kind| jsonwebtoken::errors::Error::from(kind);
+
+    let (message, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| error(ErrorKind::InvalidToken))?;
+    let payload = match message.split_once('.') {
+        Some((_, payload)) if !payload.contains('.') => payload,
+        _ => Err(error(ErrorKind::InvalidToken))?,
+    };
+
+    if validation.algorithms.is_empty() {
+        Err(error(ErrorKind::MissingAlgorithm))?;
+    };
+
+    if !validation.algorithms.contains(&alg) {
+        Err(error(ErrorKind::InvalidAlgorithm))?;
+    };
+
+    match alg {
+        Algorithm::RS256 if verified => (),
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512 => {
+            let verified = jsonwebtoken::crypto::verify(
+                signature,
+                message.as_bytes(),
+                decoding_key,
+                alg,
+            )?;
+
+            if !verified {
+                Err(error(ErrorKind::InvalidSignature))?;
+            };
+        },
+        _ => {
+            let mut signature_only = Validation::new(alg);
+            signature_only.required_spec_claims.clear();
+            signature_only.validate_exp = false;
+
+            let _ = decode::<IgnoredAny>(token, decoding_key, &signature_only)?;
+        },
+    };
+
+    buffer.clear();
+    URL_SAFE_NO_PAD
+        .decode_vec(payload, buffer)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    Ok(())
+}
+
+/// Check that the given (type-checked) claims contain the required claims.
+///
+/// `jsonwebtoken` treats a registered claim of the wrong type as missing. The
+/// required claims are therefore only checked *after* the types are, so that
+/// a mistyped claim is reported as such (instead of as missing).
+fn check_required_claims(
+    claims: &Value,
+    required_spec_claims: HashSet<String>,
+) -> prelude::Result<()> {
+    for claim in required_spec_claims {
+        let present = match (claim.as_str(), claims.get(&claim)) {
+            ("exp" | "nbf", Some(value)) => value.is_u64(),
+            (_, value) => value.is_some(),
+        };
+
+        if !present {
+            Err(jsonwebtoken::errors::Error::from(
+                jsonwebtoken::errors::ErrorKind::MissingRequiredClaim(claim),
+            ))?;
+        };
+    }
+
+    Ok(())
+}
+
+/// Decrypt the given token, deserializing its claims *from* the given buffer.
+///
+/// This behaves exactly as [`decrypt`] (requiring the `RS256` alg, and
+/// checked against the given time), except that the payload is decoded into
+/// the given buffer, and the claims are then allowed to borrow from it (e.g.,
+/// as `&'a str` fields). Re-using the same buffer across calls avoids
+/// allocating per token.
+///
+/// If `strict_claims` is set, the types of the registered claims are checked
+/// (see [`check_registered_claims`]) before anything else is parsed.
+fn decrypt_borrowed<'a, 'b, Claims, F>(
+    token: &str,
+    buffer: &'a mut Vec<u8>,
+    selector: F,
+    validation: Option<Validation>,
+    strict_claims: bool,
+    header_cache: Option<&dyn HeaderCache>,
+    now: Timestamp,
+) -> prelude::Result<Claims>
+where
+    Claims: Deserialize<'a>,
+    F: for<'c, 'h> Fn(&'c KeyHint<'h>) -> prelude::Result<Selected<'b>>,
 {
-    let token: String = token.into();
-    let Header { typ, alg, kid, .. } = decode_header(&token)?;
+    let header = check_header(token, true, header_cache)?;
+
+    let validation = validation.unwrap_or_else(|| Validation::new(header.alg));
+    let selected = selector(&KeyHint::from(&header))?;
+    verify_signature(token, &header, selected, &validation, buffer)?;
+    let buffer: &'a [u8] = buffer;
+
+    if strict_claims {
+        let claims = serde_json::from_slice(buffer)
+            .map_err(jsonwebtoken::errors::Error::from)?;
+        check_registered_claims(&claims)?;
+    };
+
+    validate(buffer, &validation, now)?;
+
+    let claims = serde_json::from_slice(buffer)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    Ok(claims)
+}
+
+/// The members of the headers of a token which identify the key that signed
+/// it.
+///
+/// Tokens of providers which publish keys without a `kid` may carry the
+/// thumbprint of the key's certificate instead (i.e., the `x5t#S256` or `x5t`
+/// members).
+pub(crate) struct KeyHint<'a> {
+    pub(crate) kid: Option<&'a str>,
+    pub(crate) x5t_s256: Option<&'a str>,
+    pub(crate) x5t: Option<&'a str>,
+}
 
-    match (rs256_alg_required, alg) {
+/// The key which was selected to verify a token (see [`decrypt`]).
+pub(crate) struct Selected<'a> {
+    pub(crate) decoding_key: &'a DecodingKey,
+
+    /// Whether the key has already verified the (`RS256`) signature of the
+    /// token (e.g., while trying every key), in which case the signature is
+    /// not verified again.
+    pub(crate) verified: bool,
+}
+
+impl<'a> From<&'a DecodingKey> for Selected<'a> {
+    fn from(decoding_key: &'a DecodingKey) -> Self {
+        Self {
+            decoding_key,
+            verified: false,
+        }
+    }
+}
+
+impl<'a> From<&'a Header> for KeyHint<'a> {
+    fn from(header: &'a Header) -> Self {
+        let Header {
+            kid, x5t_s256, x5t, ..
+        } = header;
+
+        Self {
+            kid: kid.as_deref(),
+            x5t_s256: x5t_s256.as_deref(),
+            x5t: x5t.as_deref(),
+        }
+    }
+}
+
+/// Check the headers of the given token, returning them.
+///
+/// Tokens which declare a `zip` header parameter are rejected first (see
+/// [`reject_compressed`]). Then, the `alg` (if [`Algorithm::RS256`] is
+/// required) and the `typ` (which must be `JWT`) are checked.
+///
+/// If a [`HeaderCache`] is given, the headers are looked up in it by the first
+/// segment of the token, and only decoded (and memoized) if they are missing.
+/// Headers are only memoized once they are known not to be compressed.
+fn check_header(
+    token: &str,
+    rs256_alg_required: bool,
+    header_cache: Option<&dyn HeaderCache>,
+) -> prelude::Result<Header> {
+    let segment = token.split('.').next().unwrap_or_default();
+
+    let header = match header_cache {
+        Some(header_cache) => decode_memoized(segment, header_cache)?,
+        None => decode_segment(segment)?,
+    };
+
+    match (rs256_alg_required, header.alg) {
         (true, Algorithm::RS256) | (false, _) => (),
         (true, _) => Err(Error::invalid_algorithm)?,
     };
 
-    let _ = typ
-        .map(|typ| typ.to_lowercase())
-        .and_then(|typ| match &*typ {
-            "jwt" => Some(()),
-            _ => None,
-        })
-        .ok_or(Error::unrecognized_typ)?;
+    match header.typ.as_deref() {
+        Some(typ) if typ.eq_ignore_ascii_case("jwt") => (),
+        _ => Err(Error::unrecognized_typ)?,
+    };
+
+    Ok(header)
+}
+
+/// Decode the given (first) segment of a token into its headers, unless they
+/// are already memoized inside of the given [`HeaderCache`].
+fn decode_memoized(
+    segment: &str,
+    header_cache: &dyn HeaderCache,
+) -> prelude::Result<Header> {
+    if let Some(header) = header_cache.get(segment) {
+        return Ok(header);
+    };
+
+    let header = decode_segment(segment)?;
+    header_cache.insert(segment, header.clone());
+
+    Ok(header)
+}
 
-    let kid = kid.ok_or(Error::no_kid_present)?;
-    let validation = validation.unwrap_or(Validation::new(alg));
-    let decoding_key = selector(&kid)?;
+/// Decode the given (first) segment of a token into its headers, rejecting
+/// compressed tokens (see [`reject_compressed`]).
+///
+/// The segment is only base64-decoded once.
+fn decode_segment(segment: &str) -> prelude::Result<Header> {
+    #[derive(Deserialize)]
+    struct ZipHeader {
+        #[serde(default)]
+        zip: Option<IgnoredAny>,
+    }
+
+    let decoded = URL_SAFE_NO_PAD.decode(segment);
+
+    if let Ok(ZipHeader { zip: Some(_) }) = decoded
+        .as_deref()
+        .map_err(drop)
+        .and_then(|decoded| serde_json::from_slice(decoded).map_err(drop))
+    {
+        return Err(Error::compressed_token);
+    };
+
+    let decoded = decoded.map_err(jsonwebtoken::errors::Error::from)?;
+    let header = serde_json::from_slice(&decoded)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    Ok(header)
+}
+
+/// Reject tokens whose header contains the `zip` parameter.
+///
+/// The `zip` parameter indicates that the payload has been compressed (see
+/// [RFC7516, Section 4.1.3](https://datatracker.ietf.org/doc/html/rfc7516#section-4.1.3)).
+/// Compressed payloads are never decompressed by this crate. This means that a
+/// maliciously crafted token (i.e., a "decompression bomb") can never cause
+/// this crate to inflate a small input into an arbitrarily large buffer.
+///
+/// Rejecting these tokens upfront also provides a clear
+/// [`Error::compressed_token`] error, instead of an opaque failure from
+/// trying to parse the still-compressed payload.
+pub(crate) fn reject_compressed(token: &str) -> prelude::Result<()> {
+    #[derive(Deserialize)]
+    struct ZipHeader {
+        #[serde(default)]
+        zip: Option<serde_json::Value>,
+    }
 
-    let claim = decode(&token, decoding_key, &validation)?;
+    let header = token
+        .split('.')
+        .next()
+        .and_then(|header| URL_SAFE_NO_PAD.decode(header).ok())
+        .and_then(|header| serde_json::from_slice::<ZipHeader>(&header).ok());
 
-    Ok(claim)
+    match header {
+        Some(ZipHeader { zip: Some(_) }) => Err(Error::compressed_token),
+        _ => Ok(()),
+    }
 }
@@ -0,0 +1,39 @@
+//! Checking the `nonce` claim of `ID` tokens.
+//!
+//! In the `OpenID Connect` implicit and hybrid flows (e.g., `Sign in with
+//! Apple`, or `Google`'s sign-in buttons), the `ID` token is handed to the
+//! client directly. An app which sends a fresh `nonce` along with each
+//! authentication request (and stores it, e.g., in the session of the user)
+//! can reject tokens which were not issued for that request:
+//!
+//! ```ignore
+//! let nonce = session.take("nonce")?;
+//!
+//! let TokenData { claims, .. } = registry
+//!     .decrypt_with_nonce::<GoogleClaims, _, _>(&tpa, token, &nonce)?;
+//! ```
+//!
+//! ### Note:
+//! Some clients send a hash of the `nonce` instead of the `nonce` itself
+//! (e.g., the `SHA-256` digest which `Apple` recommends for native apps);
+//! the expected value is then that hash.
+//!
+//! For more information, please visit: <https://openid.net/specs/openid-connect-core-1_0.html#NonceNotes>.
+
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::prelude;
+
+/// Check that the given (verified) claims carry the given `nonce`.
+///
+/// Fails with [`Error::nonce_mismatch`] if the `nonce` claim is missing, is
+/// not a string, or is not the given one.
+///
+/// See the [module level documentation](`self`).
+pub fn check_nonce(claims: &Value, nonce: &str) -> prelude::Result<()> {
+    match claims.get("nonce").and_then(Value::as_str) {
+        Some(claimed) if claimed == nonce => Ok(()),
+        _ => Err(Error::nonce_mismatch),
+    }
+}
@@ -0,0 +1,168 @@
+//! Who a user is, regardless of which provider signed them in.
+//!
+//! Every provider names (and nests) the claims describing a user differently.
+//! The claims of each well-known provider convert into [`NormalizedClaims`],
+//! which a [`KeyRegistry`](`crate::registry::KeyRegistry`) of well-known
+//! providers can do directly:
+//!
+//! ```ignore
+//! let TokenData { claims, .. } = registry.decrypt_normalized(&tpa, token)?;
+//! let user = users.find_or_create(&claims.provider, &claims.subject)?;
+//! ```
+//!
+//! ### Note:
+//! Subjects are only unique per provider (and per issuer); always key users
+//! by both.
+
+use serde::Serialize;
+
+use crate::key_caches::remote::apple::AppleClaims;
+use crate::key_caches::remote::facebook::FacebookClaims;
+use crate::key_caches::remote::firebase::FirebaseClaims;
+use crate::key_caches::remote::google::GoogleClaims;
+use crate::key_caches::remote::microsoft::MicrosoftClaims;
+use crate::key_caches::remote::well_known::WellKnownTpa;
+
+/// The claims describing a user, common to every provider.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, PartialEq, Eq, Serialize)]
+pub struct NormalizedClaims {
+    /// The name of the provider (e.g., `"google"`, see
+    /// [`WellKnownTpa::as_str`]).
+    pub provider: String,
+
+    /// The `iss` claim.
+    pub issuer: String,
+
+    /// The `sub` claim (i.e., the id of the user at the provider).
+    pub subject: String,
+
+    pub email: Option<String>,
+
+    /// Only present if the provider states it.
+    pub email_verified: Option<bool>,
+
+    /// The display name of the user.
+    pub name: Option<String>,
+
+    /// The `uri` of the user's profile picture.
+    pub picture: Option<String>,
+}
+
+impl From<GoogleClaims> for NormalizedClaims {
+    fn from(claims: GoogleClaims) -> Self {
+        let GoogleClaims {
+            iss,
+            sub,
+            email,
+            email_verified,
+            name,
+            picture,
+            ..
+        } = claims;
+
+        Self {
+            provider: WellKnownTpa::Google.to_string(),
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified,
+            name,
+            picture: picture.map(|picture| picture.to_string()),
+        }
+    }
+}
+
+impl From<AppleClaims> for NormalizedClaims {
+    /// `Apple` only shares the name of the user with the app (once, outside
+    /// of the token), so it is never present.
+    fn from(claims: AppleClaims) -> Self {
+        let AppleClaims {
+            iss,
+            sub,
+            email,
+            email_verified,
+            ..
+        } = claims;
+
+        Self {
+            provider: WellKnownTpa::Apple.to_string(),
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified,
+            name: None,
+            picture: None,
+        }
+    }
+}
+
+impl From<FacebookClaims> for NormalizedClaims {
+    fn from(claims: FacebookClaims) -> Self {
+        let FacebookClaims {
+            iss,
+            sub,
+            email,
+            name,
+            picture,
+            ..
+        } = claims;
+
+        Self {
+            provider: WellKnownTpa::Facebook.to_string(),
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified: None,
+            name,
+            picture,
+        }
+    }
+}
+
+impl From<FirebaseClaims> for NormalizedClaims {
+    fn from(claims: FirebaseClaims) -> Self {
+        let FirebaseClaims {
+            iss,
+            sub,
+            email,
+            email_verified,
+            name,
+            picture,
+            ..
+        } = claims;
+
+        Self {
+            provider: WellKnownTpa::Firebase.to_string(),
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified,
+            name,
+            picture,
+        }
+    }
+}
+
+impl From<MicrosoftClaims> for NormalizedClaims {
+    fn from(claims: MicrosoftClaims) -> Self {
+        let MicrosoftClaims {
+            iss,
+            sub,
+            email,
+            name,
+            ..
+        } = claims;
+
+        Self {
+            provider: WellKnownTpa::Microsoft.to_string(),
+            issuer: iss,
+            subject: sub,
+            email,
+            email_verified: None,
+            name,
+            picture: None,
+        }
+    }
+}
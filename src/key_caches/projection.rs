@@ -0,0 +1,172 @@
+//! Selective deserialization of the claims inside of a `JWT`.
+//!
+//! Large (e.g., enterprise) tokens may contain hundreds of claims, while the
+//! caller may only be interested in a few of them (e.g., `sub` and `exp`).
+//! Instead of building the entire claims object, only the claims located at the
+//! given [`JSON Pointer`]s are kept; everything else is skipped over while
+//! parsing.
+//!
+//! [`JSON Pointer`]: https://datatracker.ietf.org/doc/html/rfc6901
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::de::DeserializeSeed;
+use serde::de::IgnoredAny;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::prelude;
+
+/// A tree of `JSON Pointer` segments.
+///
+/// A `terminal` node indicates that the entire value at that location should
+/// be kept.
+#[derive(Default)]
+struct Node {
+    terminal: bool,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn from_pointers<P>(pointers: &[P]) -> Self
+    where
+        P: AsRef<str>,
+    {
+        let mut root = Self::default();
+
+        for pointer in pointers {
+            let pointer = pointer.as_ref();
+
+            let node = pointer
+                .split('/')
+                .skip(1)
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .fold(&mut root, |node, segment| {
+                    node.children.entry(segment).or_default()
+                });
+
+            node.terminal = true;
+        }
+
+        root
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for &Node {
+    type Value = Option<Value>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self.terminal {
+            true => Value::deserialize(deserializer).map(Some),
+            false => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for &Node {
+    type Value = Option<Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut projected = Map::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match self.children.get(&key) {
+                Some(child) => {
+                    if let Some(value) = map.next_value_seed(child)? {
+                        projected.insert(key, value);
+                    };
+                },
+                None => {
+                    let _ = map.next_value::<IgnoredAny>()?;
+                },
+            };
+        }
+
+        Ok(Some(Value::Object(projected)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+
+        Ok(None)
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+}
+
+/// Decode the claims (i.e., the second portion) of the given token, keeping
+/// only the values located at the given `JSON Pointer`s.
+///
+/// Pointers may only traverse objects; arrays can be kept as a whole (e.g.,
+/// `/groups`), but not indexed into (e.g., `/groups/0`).
+///
+/// ### Warning:
+/// This function does *not* verify the token.
+/// The token must be verified before calling this function.
+pub(crate) fn project<P>(token: &str, pointers: &[P]) -> prelude::Result<Value>
+where
+    P: AsRef<str>,
+{
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or(jsonwebtoken::errors::ErrorKind::InvalidToken)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    let root = Node::from_pointers(pointers);
+    let mut deserializer = serde_json::Deserializer::from_slice(&payload);
+
+    let claims = (&root)
+        .deserialize(&mut deserializer)
+        .map_err(jsonwebtoken::errors::Error::from)?
+        .unwrap_or(Value::Object(Map::new()));
+
+    Ok(claims)
+}
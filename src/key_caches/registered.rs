@@ -0,0 +1,154 @@
+//! Type-checking the registered claims of a token.
+//!
+//! [RFC7519, Section 4.1](https://datatracker.ietf.org/doc/html/rfc7519#section-4.1)
+//! defines the types of the registered claims: `exp`, `nbf`, and `iat` are
+//! numbers (i.e., `NumericDate`s), `iss`, `sub`, and `jti` are strings, and
+//! `aud` is a string or an array of strings (see [`Audience`]).
+//!
+//! A token which deviates from these types usually fails to deserialize with
+//! an opaque `serde` error (or, for `exp`, as if the claim were missing).
+//! [`check_registered_claims`] instead reports which claim deviated, and how:
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .strict_claims(true)
+//!     .build()?;
+//!
+//! // Fails with `Error::invalid_registered_claim { message }`, where
+//! // `message` is e.g. "`exp` is a string, instead of a number."
+//! let data = remote_cache.decrypt::<Claims, _>(token)?;
+//! ```
+//!
+//! The messages only ever name the claim and its type, never its value.
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::prelude;
+
+/// An article and the name of the type of the given value (e.g., "a string").
+fn described(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// The error for the given value, which should have been of the `expected`
+/// type.
+fn invalid(subject: &str, value: &Value, expected: &str) -> Error {
+    Error::invalid_registered_claim {
+        message: format!(
+            "{} is {}, instead of {}.",
+            subject,
+            described(value),
+            expected,
+        ),
+    }
+}
+
+/// Check the types of the registered claims of the given claims.
+///
+/// Claims which are absent are not checked (i.e., whether a claim is required
+/// is up to the [`Validation`](`jsonwebtoken::Validation`)), and neither are
+/// claims which are not registered. Claims which are not a `JSON` object are
+/// rejected as a whole.
+///
+/// Fails with [`Error::invalid_registered_claim`] on the first claim (by
+/// name) whose type deviates.
+///
+/// See the [module level documentation](`self`).
+///
+/// [`Error::invalid_registered_claim`]: `crate::error::Error::invalid_registered_claim`
+pub fn check_registered_claims(claims: &Value) -> prelude::Result<()> {
+    let claims = match claims {
+        Value::Object(claims) => claims,
+        claims => return Err(invalid("The payload", claims, "an object")),
+    };
+
+    for (claim, value) in claims {
+        let (subject, value, expected) = match (claim.as_str(), value) {
+            ("exp" | "nbf" | "iat", Value::Number(_))
+            | ("iss" | "sub" | "jti", Value::String(_))
+            | ("aud", Value::String(_)) => continue,
+            ("exp" | "nbf" | "iat", value) => {
+                (format!("`{}`", claim), value, "a number")
+            },
+            ("iss" | "sub" | "jti", value) => {
+                (format!("`{}`", claim), value, "a string")
+            },
+            ("aud", Value::Array(audiences)) => {
+                match audiences.iter().find(|aud| !aud.is_string()) {
+                    Some(value) => {
+                        ("An element of `aud`".into(), value, "a string")
+                    },
+                    None => continue,
+                }
+            },
+            ("aud", value) => ("`aud`".into(), value, "a string or an array"),
+            _ => continue,
+        };
+
+        return Err(invalid(&subject, value, expected));
+    }
+
+    Ok(())
+}
+
+/// An `aud` claim, which is either a single audience or an array of them
+/// (see the [module level documentation](`self`)).
+///
+/// Either form is (de)serialized as is.
+#[derive(Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Every audience, in order.
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Self::Single(audience) => std::slice::from_ref(audience),
+            Self::Multiple(audiences) => audiences,
+        }
+    }
+
+    /// Check to see if the given audience is one of the audiences.
+    pub fn contains(&self, audience: &str) -> bool {
+        self.as_slice().iter().any(|aud| aud == audience)
+    }
+
+    /// Convert into an array of every audience, in order.
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::Single(audience) => vec![audience],
+            Self::Multiple(audiences) => audiences,
+        }
+    }
+}
+
+impl From<String> for Audience {
+    fn from(audience: String) -> Self {
+        Self::Single(audience)
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(audience: &str) -> Self {
+        Self::Single(audience.into())
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(audiences: Vec<String>) -> Self {
+        Self::Multiple(audiences)
+    }
+}
@@ -3,13 +3,114 @@
 //! For more information, please visit: <https://developer.apple.com/documentation/sign_in_with_apple/fetch_apple_s_public_key_for_verifying_token_signature>.
 
 use serde::Deserialize;
+use serde::Deserializer;
+
+use crate::key_caches::registered::Audience;
 
 /// The URI for `Apple`'s public `JWK`s.
-pub const APPLE_JWK_URI: &'static str = "https://appleid.apple.com/auth/keys";
+pub const APPLE_JWK_URI: &str = "https://appleid.apple.com/auth/keys";
 
 /// Claims made by `Apple`.
 ///
 /// `JWT`'s issued by `Apple` should have a body (i.e., the second portion of
-/// the `JWT`) that are `base64URL` decrypted into the below struct.
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Claims
+/// which depend on the requested scopes (or on the platform) are optional.
+///
+/// ### Note:
+/// `Apple` encodes some of its booleans as strings (i.e., `"true"` or
+/// `"false"`); both encodings are accepted.
+///
+/// For more information, please visit: <https://developer.apple.com/documentation/sign_in_with_apple/sign_in_with_apple_rest_api/authenticating_users_with_sign_in_with_apple>.
 #[derive(Debug, Deserialize)]
-pub struct AppleClaims;
+pub struct AppleClaims {
+    /// Always `"https://appleid.apple.com"`.
+    pub iss: String,
+
+    /// The client id of the app (i.e., its bundle id, or its services id).
+    pub aud: Audience,
+
+    pub exp: u64,
+    pub iat: u64,
+
+    /// The stable identifier of the user.
+    pub sub: String,
+
+    pub c_hash: Option<String>,
+    pub at_hash: Option<String>,
+
+    /// Possibly a private relay address (see `is_private_email`).
+    pub email: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_bool")]
+    pub email_verified: Option<bool>,
+
+    #[serde(default, deserialize_with = "deserialize_bool")]
+    pub is_private_email: Option<bool>,
+
+    pub auth_time: Option<u64>,
+    pub nonce: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_bool")]
+    pub nonce_supported: Option<bool>,
+
+    /// Only present on platforms which support it.
+    pub real_user_status: Option<RealUserStatus>,
+
+    /// The identifier of the user within the team which transferred the app,
+    /// while the app is being transferred.
+    pub transfer_sub: Option<String>,
+}
+
+/// Whether the user appears to be a real person, as stated by the
+/// `real_user_status` claim.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u8")]
+pub enum RealUserStatus {
+    /// The platform does not support this indicator (`0`).
+    Unsupported,
+
+    /// `Apple` could not determine whether the user is real (`1`).
+    Unknown,
+
+    /// The user appears to be a real person (`2`).
+    LikelyReal,
+}
+
+impl TryFrom<u8> for RealUserStatus {
+    type Error = String;
+
+    fn try_from(real_user_status: u8) -> Result<Self, Self::Error> {
+        match real_user_status {
+            0 => Ok(Self::Unsupported),
+            1 => Ok(Self::Unknown),
+            2 => Ok(Self::LikelyReal),
+            _ => Err("`real_user_status` is not one of 0, 1, or 2.".into()),
+        }
+    }
+}
+
+/// Deserialize a boolean which is either a `JSON` boolean, or a string
+/// holding one (i.e., `"true"` or `"false"`).
+fn deserialize_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bool {
+        Bool(bool),
+        String(String),
+    }
+
+    match Option::<Bool>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Bool::Bool(value)) => Ok(Some(value)),
+        Some(Bool::String(value)) => match value.as_str() {
+            "true" => Ok(Some(true)),
+            "false" => Ok(Some(false)),
+            _ => Err(serde::de::Error::custom(
+                "expected a boolean, or a string holding one",
+            )),
+        },
+    }
+}
@@ -0,0 +1,114 @@
+//! `Auth0` JWT Claim object.
+//!
+//! Every `Auth0` tenant has keys (and an issuer) of its own, located by the
+//! domain of the tenant:
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::for_auth0("example.us.auth0.com")?;
+//! remote_cache.refresh().await?;
+//!
+//! assert_eq!(remote_cache.issuer(), Some("https://example.us.auth0.com/"));
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<Auth0Claims, _>(token)?;
+//! let roles = claims.namespaced("https://example.com/").get("roles");
+//! ```
+//!
+//! For more information, please visit: <https://auth0.com/docs/secure/tokens/json-web-tokens/validate-json-web-tokens>.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::discovery::origin;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The `iss` claim of the tokens of the given `Auth0` tenant (e.g.,
+/// `"example.us.auth0.com"`).
+///
+/// Note that it ends with a slash.
+pub fn auth0_issuer(domain: &str) -> String {
+    format!("{}/", origin(domain))
+}
+
+/// The URI for the public `JWK`s of the given `Auth0` tenant.
+pub fn auth0_jwk_uri(domain: &str) -> String {
+    format!("{}/.well-known/jwks.json", origin(domain))
+}
+
+impl RemoteCache {
+    /// Generate a new [`RemoteCache`] targeting the public keys of the
+    /// `Auth0` tenant with the given domain (e.g., `"example.us.auth0.com"`,
+    /// or a custom domain).
+    ///
+    /// The issuer and the endpoints of the tenant are derived from its
+    /// domain (see [`issuer`](`RemoteCache::issuer`)), so no discovery request
+    /// is made. Just like with [`RemoteCache::new`], no keys are fetched yet.
+    pub fn for_auth0(domain: &str) -> prelude::Result<Self> {
+        let origin = origin(domain);
+
+        Self::from_metadata(ProviderMetadata {
+            issuer: auth0_issuer(domain),
+            jwks_uri: auth0_jwk_uri(domain),
+            token_endpoint: Some(format!("{}/oauth/token", origin)),
+            userinfo_endpoint: Some(format!("{}/userinfo", origin)),
+            id_token_signing_alg_values_supported: vec!["RS256".into()],
+        })
+    }
+}
+
+/// Claims made by `Auth0`.
+///
+/// `JWT`'s issued by `Auth0` should have a body (i.e., the second portion of
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Both `ID`
+/// and access tokens are supported; claims which only one of them carries
+/// are optional.
+///
+/// Custom claims (which `Auth0` requires to be namespaced, e.g.,
+/// `"https://example.com/roles"`) are kept inside of `extra`, along with any
+/// other unrecognized claim (see [`namespaced`](`Auth0Claims::namespaced`)).
+#[derive(Debug, Deserialize)]
+pub struct Auth0Claims {
+    pub iss: String,
+    pub sub: String,
+
+    pub aud: Audience,
+
+    pub iat: u64,
+    pub exp: u64,
+
+    pub azp: Option<String>,
+    pub scope: Option<String>,
+
+    /// Only present if `RBAC` (and adding permissions to access tokens) is
+    /// enabled for the `API`.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub nickname: Option<String>,
+    pub picture: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Auth0Claims {
+    /// The custom claims inside of the given namespace (e.g.,
+    /// `"https://example.com/"`), keyed by their names without it (e.g.,
+    /// `"roles"`).
+    pub fn namespaced(&self, namespace: &str) -> BTreeMap<&str, &Value> {
+        self.extra
+            .iter()
+            .filter_map(|(name, value)| {
+                name.strip_prefix(namespace).map(|name| (name, value))
+            })
+            .collect()
+    }
+}
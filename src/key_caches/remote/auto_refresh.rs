@@ -0,0 +1,377 @@
+//! Refreshing a shared [`RemoteCache`] in the background.
+//!
+//! ```ignore
+//! let remote_cache = Arc::new(RwLock::new(RemoteCache::new(GOOGLE_JWK_URI)?));
+//!
+//! let auto_refresh = AutoRefresh::new(RefreshSchedule::TtlBased)
+//!     .retry_interval(Duration::from_secs(10))
+//!     .on_error(|error| eprintln!("Unable to refresh the keys: {}", error));
+//!
+//! // The task runs until the handle is dropped.
+//! let handle = RemoteCache::spawn_auto_refresh(&remote_cache, auto_refresh);
+//!
+//! // Meanwhile, requests can be verified as usual.
+//! let claims = remote_cache.read().await.decrypt::<MyClaims, _>(token)?;
+//! ```
+//!
+//! The keys are fetched *without* holding the lock; the write lock is only
+//! taken in order to swap the freshly fetched keys in. Verifications therefore
+//! never wait on the network.
+//!
+//! Instead of being stopped by dropping its handle, the task can also be owned
+//! by a [`TaskSet`] (see [`RemoteCache::spawn_auto_refresh_in`]), in order to
+//! be shut down gracefully along with every other background task.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::failover::Failover;
+use crate::key_caches::remote::fetch::fetch_any;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::store::read_through;
+use crate::key_caches::remote::store::write_through;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::store::ReadThrough;
+use crate::key_caches::remote::Cache;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::tasks::TaskSet;
+
+/// When a background task refreshes a [`RemoteCache`].
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub enum RefreshSchedule {
+    /// Refresh the cache at a fixed interval.
+    Interval(Duration),
+
+    /// Refresh the cache whenever it needs to be refreshed (see
+    /// [`needs_refresh`](`RemoteCache::needs_refresh`)), i.e., right before
+    /// its keys expire, or earlier if a
+    /// [`RefreshAheadPolicy`](`crate::key_caches::remote::policy::RefreshAheadPolicy`)
+    /// has been set.
+    TtlBased,
+}
+
+type ErrorCallback = Arc<dyn Fn(&Error) + Send + Sync>;
+
+/// The configuration of a background refresh task.
+///
+/// Spawned by calling [`RemoteCache::spawn_auto_refresh`].
+#[derive(Clone)]
+pub struct AutoRefresh {
+    pub(crate) schedule: RefreshSchedule,
+    pub(crate) jitter: bool,
+    pub(crate) min_interval: Duration,
+    pub(crate) retry_interval: Duration,
+    pub(crate) on_error: Option<ErrorCallback>,
+}
+
+impl fmt::Debug for AutoRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoRefresh")
+            .field("schedule", &self.schedule)
+            .field("jitter", &self.jitter)
+            .field("min_interval", &self.min_interval)
+            .field("retry_interval", &self.retry_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AutoRefresh {
+    /// Create a new [`AutoRefresh`] configuration with the given schedule.
+    ///
+    /// Jitter is enabled, failed refreshes are retried every 30secs, and
+    /// [`RefreshSchedule::TtlBased`] refreshes are at least 60secs apart.
+    pub fn new(schedule: RefreshSchedule) -> Self {
+        Self {
+            schedule,
+            jitter: true,
+            min_interval: Duration::from_secs(60),
+            retry_interval: Duration::from_secs(30),
+            on_error: None,
+        }
+    }
+
+    /// If `true`, each delay is randomly shortened by up to 10%. This avoids
+    /// many instances refreshing in lockstep.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the minimum amount of time between two successful
+    /// [`RefreshSchedule::TtlBased`] refreshes.
+    ///
+    /// This guards against providers which do not send an expiry time (or
+    /// which send a very short one).
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Set the amount of time to wait before retrying a failed refresh.
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Set a call-back which is called with the error of every failed
+    /// refresh.
+    ///
+    /// A failed refresh leaves the previous keys in place.
+    pub fn on_error<F>(mut self, on_error: F) -> Self
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(on_error));
+        self
+    }
+
+    /// The delay before the next refresh.
+    fn delay(&self, remote_cache: &RemoteCache, failed: bool) -> Duration {
+        let Self {
+            schedule,
+            jitter,
+            min_interval,
+            retry_interval,
+            ..
+        } = self;
+
+        let delay = match (failed, remote_cache.refreshed_at, schedule) {
+            (true, ..) => *retry_interval,
+            (false, None, _) => return Duration::ZERO,
+            (false, Some(_), RefreshSchedule::Interval(interval)) => *interval,
+            (false, Some(_), RefreshSchedule::TtlBased) => remote_cache
+                .refresh_due_in()
+                .unwrap_or_default()
+                .max(*min_interval),
+        };
+
+        match jitter {
+            true => delay.mul_f64(0.9 + fastrand::f64() / 10.0),
+            false => delay,
+        }
+    }
+}
+
+/// A handle to a background refresh task.
+///
+/// The task is stopped when this handle is dropped.
+#[derive(Debug)]
+pub struct AutoRefreshHandle {
+    task: JoinHandle<()>,
+}
+
+impl AutoRefreshHandle {
+    /// Stop the background refresh task.
+    ///
+    /// Equivalent to dropping this handle.
+    pub fn shutdown(self) {}
+
+    /// Check to see if the background refresh task has stopped.
+    ///
+    /// This only happens if the task panicked (e.g., inside of the error
+    /// call-back).
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for AutoRefreshHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Freshly fetched keys (or a fresher snapshot restored from the store),
+/// waiting to be swapped into a shared [`RemoteCache`].
+pub(crate) enum Fetched {
+    Restore(Box<Snapshot>),
+    Keys(Cache, Option<u64>),
+}
+
+impl Fetched {
+    /// Swap these keys into the given cache, returning the snapshot to save
+    /// to its store (if it has one).
+    pub(crate) fn swap_into(
+        self,
+        remote_cache: &mut RemoteCache,
+    ) -> prelude::Result<Option<Snapshot>> {
+        match self {
+            Self::Restore(snapshot) => {
+                remote_cache.restore(*snapshot).map(|()| None)
+            },
+            Self::Keys(keys, max_age) => {
+                remote_cache.apply(keys, max_age);
+
+                Ok(remote_cache
+                    .cache_store
+                    .as_ref()
+                    .map(|_| remote_cache.snapshot()))
+            },
+        }
+    }
+}
+
+/// Everything needed in order to refresh a shared [`RemoteCache`], detached
+/// from the cache itself (i.e., from its lock).
+pub(crate) struct Detached {
+    uri: http::Uri,
+    uris: Vec<http::Uri>,
+    refreshed_at: Option<u64>,
+    fetcher: Arc<dyn JwksFetcher>,
+    config: FetchConfig,
+    failover: Option<Arc<Failover>>,
+    cache_store: Option<Arc<dyn CacheStore>>,
+}
+
+impl Detached {
+    pub(crate) fn new(remote_cache: &RemoteCache) -> Self {
+        let RemoteCache {
+            uri,
+            refreshed_at,
+            fetcher,
+            config,
+            failover,
+            cache_store,
+            ..
+        } = remote_cache;
+
+        Self {
+            uri: uri.clone(),
+            uris: remote_cache.uris(),
+            refreshed_at: *refreshed_at,
+            fetcher: fetcher.clone(),
+            config: config.clone(),
+            failover: failover.clone(),
+            cache_store: cache_store.clone(),
+        }
+    }
+
+    /// Fetch the keys without holding any lock, and then swap them in using
+    /// the given function (which should only hold the lock for the swap
+    /// itself).
+    pub(crate) async fn refresh<S, F>(self, swap: S) -> prelude::Result<()>
+    where
+        S: FnOnce(Fetched) -> F,
+        F: Future<Output = prelude::Result<Option<Snapshot>>>,
+    {
+        let Self {
+            uri,
+            uris,
+            refreshed_at,
+            fetcher,
+            config,
+            failover,
+            cache_store,
+        } = self;
+
+        let token = match &cache_store {
+            Some(cache_store) => {
+                match read_through(cache_store.as_ref(), &uri, refreshed_at)
+                    .await
+                {
+                    ReadThrough::Restore(snapshot) => {
+                        return swap(Fetched::Restore(snapshot)).await.map(drop)
+                    },
+                    ReadThrough::Fetch(token) => token,
+                }
+            },
+            None => None,
+        };
+
+        let fetched =
+            fetch_any(fetcher.as_ref(), &uris, &config, failover.as_deref())
+                .await;
+
+        let snapshot = match fetched {
+            Ok((keys, max_age)) => swap(Fetched::Keys(keys, max_age)).await,
+            Err(error) => Err(error),
+        };
+
+        if let Some(cache_store) = &cache_store {
+            let saved = snapshot.as_ref().ok().cloned().flatten();
+
+            write_through(cache_store.as_ref(), &uri, saved, token).await;
+        };
+
+        snapshot.map(drop)
+    }
+}
+
+/// Refresh the given shared [`RemoteCache`], without holding the lock while
+/// the keys are being fetched.
+async fn refresh(remote_cache: &RwLock<RemoteCache>) -> prelude::Result<()> {
+    let detached = Detached::new(&*remote_cache.read().await);
+
+    detached
+        .refresh(|fetched| async move {
+            fetched.swap_into(&mut *remote_cache.write().await)
+        })
+        .await
+}
+
+/// Keep refreshing the given shared [`RemoteCache`], until `cancelled`
+/// resolves.
+async fn run<C>(
+    remote_cache: Arc<RwLock<RemoteCache>>,
+    auto_refresh: AutoRefresh,
+    cancelled: C,
+) where
+    C: Future<Output = ()>,
+{
+    tokio::pin!(cancelled);
+
+    let mut failed = false;
+
+    loop {
+        let delay = auto_refresh.delay(&*remote_cache.read().await, failed);
+
+        let result = tokio::select! {
+            biased;
+
+            () = &mut cancelled => return,
+            result = async {
+                tokio::time::sleep(delay).await;
+                refresh(&remote_cache).await
+            } => result,
+        };
+
+        failed = match result {
+            Ok(()) => false,
+            Err(error) => {
+                if let Some(on_error) = &auto_refresh.on_error {
+                    on_error(&error);
+                };
+                true
+            },
+        };
+    }
+}
+
+pub(crate) fn spawn(
+    remote_cache: Arc<RwLock<RemoteCache>>,
+    auto_refresh: AutoRefresh,
+) -> AutoRefreshHandle {
+    let cancelled = std::future::pending();
+    let task = tokio::spawn(run(remote_cache, auto_refresh, cancelled));
+
+    AutoRefreshHandle { task }
+}
+
+pub(crate) fn spawn_in(
+    remote_cache: Arc<RwLock<RemoteCache>>,
+    auto_refresh: AutoRefresh,
+    tasks: &TaskSet,
+) {
+    tasks.spawn(|mut cancelled| async move {
+        run(remote_cache, auto_refresh, cancelled.wait()).await
+    });
+}
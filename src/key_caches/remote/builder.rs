@@ -0,0 +1,572 @@
+//! A builder for configuring a [`RemoteCache`] before its first fetch.
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder("https://example_target.com/certs")
+//!     .timeout(Duration::from_secs(5))
+//!     .connect_timeout(Duration::from_secs(1))
+//!     .header("authorization", "Bearer my_secret")
+//!     .user_agent("my_application/1.0")
+//!     .redirect_policy(RedirectPolicy::Limited(3))
+//!     .build()?;
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::HeaderName;
+use http::header::AUTHORIZATION;
+use http::header::COOKIE;
+use http::header::PROXY_AUTHORIZATION;
+use http::header::HeaderValue;
+use http::header::USER_AGENT;
+use tokio::sync::broadcast;
+
+use crate::error::Error;
+use crate::key_caches::header_cache::HeaderCache;
+use crate::key_caches::replay::ReplayGuard;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::config::RedirectPolicy;
+use crate::key_caches::remote::config::RetryPolicy;
+use crate::key_caches::remote::discovery::discover;
+use crate::key_caches::remote::discovery::discovery_uri;
+use crate::key_caches::remote::failover::EventCallback;
+use crate::key_caches::remote::failover::Failover;
+use crate::key_caches::remote::failover::FailoverEvent;
+use crate::key_caches::remote::failover::FailoverPolicy;
+use crate::key_caches::remote::fetch::check_scheme;
+use crate::key_caches::remote::fetcher::default_fetcher;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::file;
+use crate::key_caches::remote::policy::RefreshAheadPolicy;
+use crate::key_caches::remote::policy::StalePolicy;
+use crate::key_caches::remote::rotation::ROTATION_CHANNEL_CAPACITY;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::tls::Certificate;
+use crate::key_caches::remote::tls::Identity;
+use crate::key_caches::remote::RemoteCache;
+use crate::key_caches::remote::verification_limit;
+use crate::key_caches::remote::DEFAULT_BLOCKING_THRESHOLD;
+use crate::key_caches::remote::DEFAULT_VERIFICATION_WAIT;
+use crate::observer::CacheObserver;
+use crate::prelude;
+use crate::time::Clock;
+
+/// A builder for a [`RemoteCache`].
+///
+/// Created by calling [`RemoteCache::builder`].
+/// Errors (e.g., an invalid header) are deferred until
+/// [`build`](`RemoteCacheBuilder::build`) is called.
+pub struct RemoteCacheBuilder {
+    uri: String,
+    fallback_uris: Vec<String>,
+    failover_policy: Option<FailoverPolicy>,
+    on_failover: Option<EventCallback>,
+    config: FetchConfig,
+    fetcher: Option<Arc<dyn JwksFetcher>>,
+    cache_store: Option<Arc<dyn CacheStore>>,
+    max_concurrent_verifications: Option<usize>,
+    verification_wait: Duration,
+    single_key_fallback: bool,
+    try_all_keys: bool,
+    strict_claims: bool,
+    header_cache: Option<Arc<dyn HeaderCache>>,
+    clock: Option<Arc<dyn Clock>>,
+    replay_guard: Option<ReplayGuard>,
+    blocking_threshold: usize,
+    observer: Option<Arc<dyn CacheObserver>>,
+    stale_policy: Option<StalePolicy>,
+    refresh_ahead_policy: Option<RefreshAheadPolicy>,
+    pub(crate) error: Option<Error>,
+}
+
+impl fmt::Debug for RemoteCacheBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteCacheBuilder")
+            .field("uri", &self.uri)
+            .field("fallback_uris", &self.fallback_uris)
+            .field("failover_policy", &self.failover_policy)
+            .field("config", &self.config)
+            .field(
+                "max_concurrent_verifications",
+                &self.max_concurrent_verifications,
+            )
+            .field("verification_wait", &self.verification_wait)
+            .field("single_key_fallback", &self.single_key_fallback)
+            .field("try_all_keys", &self.try_all_keys)
+            .field("strict_claims", &self.strict_claims)
+            .field("replay_guard", &self.replay_guard)
+            .field("blocking_threshold", &self.blocking_threshold)
+            .field("stale_policy", &self.stale_policy)
+            .field("refresh_ahead_policy", &self.refresh_ahead_policy)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RemoteCacheBuilder {
+    pub(crate) fn new<I>(uri: I) -> Self
+    where
+        String: From<I>,
+    {
+        let uri = uri.into();
+        let fallback_uris = Vec::new();
+        let failover_policy = None;
+        let on_failover = None;
+        let config = FetchConfig::default();
+        let fetcher = None;
+        let cache_store = None;
+        let max_concurrent_verifications = None;
+        let verification_wait = DEFAULT_VERIFICATION_WAIT;
+        let single_key_fallback = false;
+        let try_all_keys = false;
+        let strict_claims = false;
+        let header_cache = None;
+        let clock = None;
+        let replay_guard = None;
+        let blocking_threshold = DEFAULT_BLOCKING_THRESHOLD;
+        let observer = None;
+        let stale_policy = None;
+        let refresh_ahead_policy = None;
+        let error = None;
+
+        Self {
+            uri,
+            fallback_uris,
+            failover_policy,
+            on_failover,
+            config,
+            fetcher,
+            cache_store,
+            max_concurrent_verifications,
+            verification_wait,
+            single_key_fallback,
+            try_all_keys,
+            strict_claims,
+            header_cache,
+            clock,
+            replay_guard,
+            blocking_threshold,
+            observer,
+            stale_policy,
+            refresh_ahead_policy,
+            error,
+        }
+    }
+
+    /// Add a mirror of the `uri`, which is fetched from if fetching from the
+    /// `uri` (and every previously added mirror) fails.
+    ///
+    /// Useful for providers which expose region-specific endpoints.
+    pub fn fallback_uri<I>(mut self, uri: I) -> Self
+    where
+        String: From<I>,
+    {
+        self.fallback_uris.push(uri.into());
+        self
+    }
+
+    /// Keep track of the health of the `uri` and its mirrors, and fail over
+    /// between them.
+    ///
+    /// See [`failover`](`crate::key_caches::remote::failover`).
+    pub fn failover_policy(mut self, failover_policy: FailoverPolicy) -> Self {
+        self.failover_policy = Some(failover_policy);
+        self
+    }
+
+    /// Set a call-back which is called whenever the cache fails over (or
+    /// back) between the `uri` and its mirrors.
+    ///
+    /// Only used if a [`FailoverPolicy`] has been set.
+    pub fn on_failover<F>(mut self, on_failover: F) -> Self
+    where
+        F: Fn(&FailoverEvent) + Send + Sync + 'static,
+    {
+        self.on_failover = Some(Arc::new(on_failover));
+        self
+    }
+
+    /// Set the maximum amount of time that an entire fetch is allowed to
+    /// take.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum amount of time that connecting to the target is allowed
+    /// to take.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Add a header that will be sent along with every request.
+    ///
+    /// Adding the same header twice will overwrite the previous value.
+    ///
+    /// Credential headers (i.e., `Authorization`, `Proxy-Authorization`, and
+    /// `Cookie`) are marked as sensitive, so their values are never printed
+    /// by the [`fmt::Debug`] implementation of the [`FetchConfig`].
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        HeaderValue: TryFrom<V>,
+    {
+        let name = HeaderName::try_from(name).ok();
+        let value = HeaderValue::try_from(value).ok();
+
+        match (name, value) {
+            (Some(name), Some(mut value)) => {
+                let credentials = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE];
+                value.set_sensitive(credentials.contains(&name));
+
+                let _ = self.config.headers.insert(name, value);
+            },
+            _ => self.error = self.error.or(Some(Error::invalid_header)),
+        };
+
+        self
+    }
+
+    /// Set the `User-Agent` header that will be sent along with every request.
+    pub fn user_agent<V>(self, user_agent: V) -> Self
+    where
+        HeaderValue: TryFrom<V>,
+    {
+        self.header(USER_AGENT, user_agent)
+    }
+
+    /// Set how redirect responses are handled.
+    pub fn redirect_policy(mut self, redirect_policy: RedirectPolicy) -> Self {
+        self.config.redirect_policy = redirect_policy;
+        self
+    }
+
+    /// Set how failed fetches are retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.config.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Set the maximum size (in bytes) of a response body.
+    ///
+    /// Defaults to
+    /// [`DEFAULT_MAX_BODY_SIZE`](`crate::key_caches::remote::config::DEFAULT_MAX_BODY_SIZE`).
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+
+    /// Allow targeting plain `http` endpoints.
+    ///
+    /// By default, only `https` endpoints (and local `file://` `uri`s, see
+    /// [`RemoteCache::from_file`]) are allowed, and building a cache targeting
+    /// any other scheme fails with [`Error::invalid_uri`].
+    ///
+    /// ### Warning:
+    /// Never enable this in production; see
+    /// [`FetchConfig::allow_insecure_http`].
+    pub fn allow_insecure_http(mut self, allow_insecure_http: bool) -> Self {
+        self.config.allow_insecure_http = allow_insecure_http;
+        self
+    }
+
+    /// Trust the given root certificate (e.g., of a private certificate
+    /// authority), on top of the default ones.
+    ///
+    /// Can be called multiple times to trust multiple certificates.
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.config.root_certificates.push(certificate);
+        self
+    }
+
+    /// Present the given identity to endpoints which require client
+    /// authentication (i.e., `mTLS`).
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.config.identity = Some(identity);
+        self
+    }
+
+    /// Set the format of the fetched documents.
+    ///
+    /// Defaults to [`JwksFormat::Auto`], which is only worth overriding to
+    /// reject documents of the other format.
+    pub fn format(mut self, format: JwksFormat) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    /// Use the given [`JwksFetcher`] instead of the default [`HyperFetcher`].
+    ///
+    /// The `connect_timeout`, `headers`, root certificate, and identity
+    /// settings only apply to the default
+    /// [`HyperFetcher`]; a custom [`JwksFetcher`] is responsible for its own
+    /// connection handling.
+    pub fn fetcher<F>(mut self, fetcher: F) -> Self
+    where
+        F: JwksFetcher + 'static,
+    {
+        self.fetcher = Some(Arc::new(fetcher));
+        self
+    }
+
+    /// Share snapshots with other caches targeting the same `uri` (e.g., on
+    /// other replicas) through the given [`CacheStore`].
+    ///
+    /// See [`store`](`crate::key_caches::remote::store`).
+    pub fn cache_store<S>(mut self, cache_store: S) -> Self
+    where
+        S: CacheStore + 'static,
+    {
+        self.cache_store = Some(Arc::new(cache_store));
+        self
+    }
+
+    /// Limit the number of verifications that can be performed concurrently.
+    ///
+    /// See [`RemoteCache::set_max_concurrent_verifications`].
+    pub fn max_concurrent_verifications(
+        mut self,
+        max_concurrent_verifications: usize,
+    ) -> Self {
+        self.max_concurrent_verifications = Some(max_concurrent_verifications);
+        self
+    }
+
+    /// Wait for at most the given duration for a permit to verify a token
+    /// asynchronously, once the limit on concurrent verifications has been
+    /// reached.
+    ///
+    /// See [`RemoteCache::set_verification_wait`].
+    pub fn verification_wait(mut self, verification_wait: Duration) -> Self {
+        self.verification_wait = verification_wait;
+        self
+    }
+
+    /// Allow tokens without a `kid` to be verified by the only key of the
+    /// cache.
+    ///
+    /// See [`RemoteCache::set_single_key_fallback`].
+    pub fn single_key_fallback(mut self, single_key_fallback: bool) -> Self {
+        self.single_key_fallback = single_key_fallback;
+        self
+    }
+
+    /// Verify tokens whose `kid` is unknown against every key of the cache,
+    /// before giving up.
+    ///
+    /// See [`RemoteCache::set_try_all_keys`].
+    pub fn try_all_keys(mut self, try_all_keys: bool) -> Self {
+        self.try_all_keys = try_all_keys;
+        self
+    }
+
+    /// Check the types of the registered claims of tokens before their claims
+    /// are deserialized.
+    ///
+    /// See [`RemoteCache::set_strict_claims`].
+    pub fn strict_claims(mut self, strict_claims: bool) -> Self {
+        self.strict_claims = strict_claims;
+        self
+    }
+
+    /// Memoize the parsed headers of tokens inside of the given
+    /// [`HeaderCache`].
+    ///
+    /// See [`header_cache`](`crate::key_caches::header_cache`).
+    pub fn header_cache<C>(mut self, header_cache: C) -> Self
+    where
+        C: HeaderCache + 'static,
+    {
+        self.header_cache = Some(Arc::new(header_cache));
+        self
+    }
+
+    /// Check the freshness of the keys, and the `exp` (and `nbf`) of tokens,
+    /// against the given [`Clock`] instead of the time of the system.
+    ///
+    /// See [`time`](`crate::time`).
+    pub fn clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Record every verified token with the given [`ReplayGuard`], rejecting
+    /// the tokens which were already recorded.
+    ///
+    /// See [`replay`](`crate::key_caches::replay`).
+    pub fn replay_guard(mut self, replay_guard: ReplayGuard) -> Self {
+        self.replay_guard = Some(replay_guard);
+        self
+    }
+
+    /// Set the size (in bits) of the `RSA` modulus of a key, from which on the
+    /// offloaded verifications with it run on the blocking thread pool.
+    ///
+    /// See [`RemoteCache::set_blocking_threshold`].
+    pub fn blocking_threshold(mut self, blocking_threshold: usize) -> Self {
+        self.blocking_threshold = blocking_threshold;
+        self
+    }
+
+    /// Report hits, misses, refreshes, and failed verifications to the given
+    /// [`CacheObserver`].
+    ///
+    /// See [`observer`](`crate::observer`).
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: CacheObserver + 'static,
+    {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Keep serving expired keys for a bounded grace period.
+    ///
+    /// See [`StalePolicy`].
+    pub fn stale_policy(mut self, stale_policy: StalePolicy) -> Self {
+        self.stale_policy = Some(stale_policy);
+        self
+    }
+
+    /// Report that the cache needs to be refreshed before its keys expire.
+    ///
+    /// See [`RefreshAheadPolicy`].
+    pub fn refresh_ahead_policy(
+        mut self,
+        refresh_ahead_policy: RefreshAheadPolicy,
+    ) -> Self {
+        self.refresh_ahead_policy = Some(refresh_ahead_policy);
+        self
+    }
+
+    /// Build the [`RemoteCache`].
+    ///
+    /// Just like with [`RemoteCache::new`], no keys are fetched yet.
+    pub fn build(self) -> prelude::Result<RemoteCache> {
+        let Self {
+            uri,
+            fallback_uris,
+            failover_policy,
+            on_failover,
+            config,
+            fetcher,
+            cache_store,
+            max_concurrent_verifications,
+            verification_wait,
+            single_key_fallback,
+            try_all_keys,
+            strict_claims,
+            header_cache,
+            clock,
+            replay_guard,
+            blocking_threshold,
+            observer,
+            stale_policy,
+            refresh_ahead_policy,
+            error,
+        } = self;
+
+        if let Some(error) = error {
+            return Err(error);
+        };
+
+        let parse = |uri: String| {
+            let uri = file::normalize(uri).parse::<http::Uri>()?;
+
+            match file::is_file(&uri) {
+                true => file::to_path(&uri).map(drop)?,
+                false => check_scheme(&uri, config.allow_insecure_http)?,
+            };
+
+            Ok(uri)
+        };
+
+        let uri = parse(uri)?;
+        let fallback_uris = fallback_uris
+            .into_iter()
+            .map(parse)
+            .collect::<prelude::Result<Vec<_>>>()?;
+        let failover = failover_policy.map(|failover_policy| {
+            Arc::new(Failover::new(failover_policy, on_failover))
+        });
+        let provider_metadata = None;
+        let keys = Default::default();
+        let provenance = Default::default();
+        let expiry_time = None;
+        let refreshed_at = None;
+        let fetcher = match fetcher {
+            Some(fetcher) => fetcher,
+            None => default_fetcher(&config)?,
+        };
+
+        let verification_limit =
+            verification_limit(max_concurrent_verifications)?;
+        let (rotations, _) = broadcast::channel(ROTATION_CHANNEL_CAPACITY);
+
+        let store = RemoteCache {
+            uri,
+            fallback_uris,
+            failover,
+            provider_metadata,
+            keys,
+            provenance,
+            expiry_time,
+            refreshed_at,
+            stale_policy,
+            refresh_ahead_policy,
+            config,
+            fetcher,
+            cache_store,
+            verification_limit,
+            verification_wait,
+            single_key_fallback,
+            try_all_keys,
+            strict_claims,
+            header_cache,
+            clock,
+            replay_guard,
+            blocking_threshold,
+            observer,
+            rotations,
+        };
+
+        Ok(store)
+    }
+
+    /// Build the [`RemoteCache`], treating the given `uri` as the issuer
+    /// identifier of an `OpenID Connect` provider.
+    ///
+    /// The provider configuration document is fetched (using this builder's
+    /// configuration) and kept, and the cache targets the `jwks_uri` inside of
+    /// it.
+    /// Just like with [`build`](`RemoteCacheBuilder::build`), no keys are
+    /// fetched yet.
+    ///
+    /// See [`discovery`](`crate::key_caches::remote::discovery`).
+    pub async fn discover(mut self) -> prelude::Result<RemoteCache> {
+        let issuer = std::mem::take(&mut self.uri);
+        self.uri = discovery_uri(&issuer);
+
+        let mut remote_cache = self.build()?;
+        let provider_metadata = discover(
+            remote_cache.fetcher.as_ref(),
+            &issuer,
+            &remote_cache.config,
+        )
+        .await?;
+
+        let jwks_uri = provider_metadata.jwks_uri.parse::<http::Uri>()?;
+        check_scheme(&jwks_uri, remote_cache.config.allow_insecure_http)?;
+
+        remote_cache.uri = jwks_uri;
+        remote_cache.provider_metadata = Some(provider_metadata);
+
+        Ok(remote_cache)
+    }
+}
@@ -0,0 +1,189 @@
+//! `AWS Cognito` JWT Claim object.
+//!
+//! Unlike most providers, every `Cognito` user pool has keys (and an issuer)
+//! of its own, located by the region and the id of the pool:
+//!
+//! ```ignore
+//! let user_pool = CognitoUserPool::new("us-east-1", "us-east-1_AbCdEfGhI");
+//! let remote_cache = user_pool.remote_cache_builder().build()?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<CognitoClaims, _>(token)?;
+//!
+//! // Only accept access tokens, issued by the pool for the app client.
+//! user_pool.validate(&claims, TokenUse::Access, "my-app-client-id")?;
+//! ```
+//!
+//! For more information, please visit: <https://docs.aws.amazon.com/cognito/latest/developerguide/amazon-cognito-user-pools-using-tokens-verifying-a-jwt.html>.
+
+use jsonwebtoken::errors::ErrorKind;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::key_caches::remote::builder::RemoteCacheBuilder;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The URI for the public `JWK`s of the given `Cognito` user pool.
+pub fn cognito_jwk_uri(region: &str, user_pool_id: &str) -> String {
+    format!("{}/.well-known/jwks.json", cognito_issuer(region, user_pool_id))
+}
+
+/// The `iss` claim of the tokens of the given `Cognito` user pool.
+pub fn cognito_issuer(region: &str, user_pool_id: &str) -> String {
+    format!("https://cognito-idp.{}.amazonaws.com/{}", region, user_pool_id)
+}
+
+/// A `Cognito` user pool, located by its region and its id.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CognitoUserPool {
+    /// The `AWS` region of the pool (e.g., `"us-east-1"`).
+    pub region: String,
+
+    /// The id of the pool (e.g., `"us-east-1_AbCdEfGhI"`).
+    pub user_pool_id: String,
+}
+
+impl CognitoUserPool {
+    /// Locate the user pool with the given id, in the given region.
+    pub fn new<R, P>(region: R, user_pool_id: P) -> Self
+    where
+        String: From<R> + From<P>,
+    {
+        Self {
+            region: String::from(region),
+            user_pool_id: String::from(user_pool_id),
+        }
+    }
+
+    /// The URI for the public `JWK`s of this pool.
+    pub fn jwk_uri(&self) -> String {
+        let Self {
+            region,
+            user_pool_id,
+        } = self;
+
+        cognito_jwk_uri(region, user_pool_id)
+    }
+
+    /// The `iss` claim of the tokens of this pool.
+    pub fn issuer(&self) -> String {
+        let Self {
+            region,
+            user_pool_id,
+        } = self;
+
+        cognito_issuer(region, user_pool_id)
+    }
+
+    /// A [`RemoteCacheBuilder`] targeting the public keys of this pool.
+    ///
+    /// Any other configuration can be set on the returned builder as usual.
+    pub fn remote_cache_builder(&self) -> RemoteCacheBuilder {
+        RemoteCache::builder(self.jwk_uri())
+    }
+
+    /// Check that the given (verified) claims were issued by this pool, for
+    /// the given app client, as the given kind of token.
+    ///
+    /// The app client is read from the `aud` claim of `id` tokens, and from
+    /// the `client_id` claim of `access` tokens (see
+    /// [`CognitoClaims::app_client`]).
+    ///
+    /// Fails with [`Error::unexpected_token_use`] if the token is of the other
+    /// kind, and with [`Error::unable_to_verify_token`] if its issuer or its
+    /// app client do not match.
+    pub fn validate(
+        &self,
+        claims: &CognitoClaims,
+        token_use: TokenUse,
+        client_id: &str,
+    ) -> prelude::Result<()> {
+        if claims.iss != self.issuer() {
+            Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidIssuer))?;
+        };
+
+        if claims.token_use != token_use {
+            return Err(Error::unexpected_token_use);
+        };
+
+        if claims.app_client() != Some(client_id) {
+            Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidAudience))?;
+        };
+
+        Ok(())
+    }
+}
+
+/// The kind of a `Cognito` token, as stated by its `token_use` claim.
+#[derive(
+    Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenUse {
+    /// An `id` token, describing the user.
+    Id,
+
+    /// An `access` token, authorizing the user.
+    Access,
+}
+
+/// Claims made by `Cognito`.
+///
+/// `JWT`'s issued by `Cognito` should have a body (i.e., the second portion of
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Both `id`
+/// and `access` tokens are supported; claims which only one of them carries
+/// are optional.
+#[derive(Debug, Deserialize)]
+pub struct CognitoClaims {
+    pub iss: String,
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub auth_time: Option<u64>,
+    pub token_use: TokenUse,
+
+    /// The app client of an `id` token.
+    pub aud: Option<String>,
+
+    /// The app client of an `access` token.
+    pub client_id: Option<String>,
+
+    /// Named `cognito:username` in `id` tokens, and `username` in `access`
+    /// tokens.
+    #[serde(rename = "cognito:username", alias = "username")]
+    pub username: Option<String>,
+
+    #[serde(rename = "cognito:groups", default)]
+    pub groups: Vec<String>,
+
+    pub scope: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub jti: Option<String>,
+    pub origin_jti: Option<String>,
+    pub event_id: Option<String>,
+}
+
+impl CognitoClaims {
+    /// The app client that this token was issued for, regardless of its kind
+    /// (i.e., the `aud` claim of `id` tokens, and the `client_id` claim of
+    /// `access` tokens).
+    pub fn app_client(&self) -> Option<&str> {
+        let Self {
+            token_use,
+            aud,
+            client_id,
+            ..
+        } = self;
+
+        match token_use {
+            TokenUse::Id => aud.as_deref(),
+            TokenUse::Access => client_id.as_deref(),
+        }
+    }
+}
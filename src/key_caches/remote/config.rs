@@ -0,0 +1,220 @@
+//! Configuration for the `http` client that a [`super::RemoteCache`] uses to
+//! fetch its keys.
+//!
+//! Usually, this configuration is set by using a
+//! [`RemoteCacheBuilder`](`super::builder::RemoteCacheBuilder`).
+
+use std::time::Duration;
+
+use http::HeaderMap;
+
+use crate::key_caches::remote::tls::Certificate;
+use crate::key_caches::remote::tls::Identity;
+
+/// How redirect responses (i.e., `3xx` responses) are handled when fetching
+/// keys.
+///
+/// Redirects are only ever followed to `https` targets (unless
+/// [`FetchConfig::allow_insecure_http`] is set). Following a redirect
+/// back to an already visited `uri` fails with [`Error::redirect_loop`].
+/// Credentials (e.g., an `Authorization` header) are never sent along with a
+/// redirect to another origin.
+///
+/// Defaults to [`RedirectPolicy::Limited`] with 3 hops.
+///
+/// [`Error::redirect_loop`]: `crate::error::Error::redirect_loop`
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Redirects are not followed.
+    ///
+    /// The redirect response is treated as an unsuccessful response.
+    None,
+
+    /// Redirects are followed, up to the given number of hops.
+    ///
+    /// Exceeding the number of hops fails with
+    /// [`Error::too_many_redirects`](`crate::error::Error::too_many_redirects`).
+    Limited(usize),
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Limited(3)
+    }
+}
+
+/// The format of the documents served by a `JWK` endpoint.
+///
+/// Defaults to [`JwksFormat::Auto`].
+#[derive(Clone, Copy, Hash, Debug, Default, PartialEq, Eq)]
+pub enum JwksFormat {
+    /// The format is detected from each document: documents with a `keys`
+    /// member are read as [`JwksFormat::Jwks`], and objects which only map
+    /// to `PEM` certificates are read as [`JwksFormat::X509Map`].
+    #[default]
+    Auto,
+
+    /// A `JWK` set (i.e., `{"keys":[...]}`), as according to
+    /// [RFC7517, Section 5](https://datatracker.ietf.org/doc/html/rfc7517#section-5).
+    Jwks,
+
+    /// A map of `kid`s to (`PEM` encoded) `X.509` certificates, as served by
+    /// `Firebase` (see
+    /// [`FIREBASE_JWK_URI`](`crate::key_caches::remote::firebase::FIREBASE_JWK_URI`)).
+    ///
+    /// The keys are read from the certificates (see
+    /// [`x509`](`crate::key_caches::remote::x509`)).
+    X509Map,
+}
+
+/// The default maximum size (in bytes) of a `JWK` set response body.
+///
+/// Real-world `JWK` sets are a few KB at most.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+
+/// The `http` client configuration used when fetching keys.
+#[derive(Clone, Debug)]
+pub struct FetchConfig {
+    /// The maximum amount of time that a single fetch attempt (i.e.,
+    /// connecting, sending the request, following redirects, and reading the
+    /// response) is allowed to take.
+    pub timeout: Option<Duration>,
+
+    /// The maximum amount of time that connecting to the target is allowed to
+    /// take.
+    pub connect_timeout: Option<Duration>,
+
+    /// Additional headers that are sent along with every request.
+    ///
+    /// This is where, for example, an `Authorization` header for private `JWK`
+    /// endpoints or a custom `User-Agent` header would be stored.
+    /// Credentials are only sent to the origin of the `JWK` endpoint itself
+    /// (see [`RedirectPolicy`]).
+    pub headers: HeaderMap,
+
+    /// How redirect responses are handled.
+    pub redirect_policy: RedirectPolicy,
+
+    /// How failed fetches are retried.
+    ///
+    /// If [`None`], failed fetches are not retried.
+    pub retry_policy: Option<RetryPolicy>,
+
+    /// The maximum size (in bytes) of a response body.
+    ///
+    /// Larger responses are rejected with [`Error::response_too_large`]
+    /// (without buffering them entirely).
+    ///
+    /// [`Error::response_too_large`]: `crate::error::Error::response_too_large`
+    pub max_body_size: usize,
+
+    /// If `true`, plain `http` targets (and redirects to them) are allowed.
+    ///
+    /// ### Warning:
+    /// Keys fetched over plain `http` can be tampered with by anyone on the
+    /// network path. Only enable this for local development (e.g., against a
+    /// mock `OIDC` server running on `http://localhost`).
+    pub allow_insecure_http: bool,
+
+    /// Additional root certificates to trust (e.g., of a private certificate
+    /// authority), on top of the default ones.
+    pub root_certificates: Vec<Certificate>,
+
+    /// The identity presented to endpoints which require client
+    /// authentication (i.e., `mTLS`).
+    pub identity: Option<Identity>,
+
+    /// The format of the fetched documents.
+    pub format: JwksFormat,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            connect_timeout: None,
+            headers: HeaderMap::default(),
+            redirect_policy: RedirectPolicy::default(),
+            retry_policy: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            allow_insecure_http: false,
+            root_certificates: Vec::new(),
+            identity: None,
+            format: JwksFormat::default(),
+        }
+    }
+}
+
+/// How failed fetches are retried.
+///
+/// A fetch is retried if the request itself failed (e.g., a network blip or a
+/// timeout), or if the target responded with a `429` or a `5xx` status code.
+/// Any other failure (e.g., a `404`, or an unparsable body) is returned
+/// immediately.
+///
+/// The delay before the `n`th retry is `base_delay * 2^(n - 1)`, capped at
+/// `max_delay`.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts (including the first one).
+    pub max_attempts: usize,
+
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+
+    /// The maximum delay between two attempts.
+    pub max_delay: Duration,
+
+    /// If `true`, each delay is randomized to be between half of, and the
+    /// entire, computed delay. This avoids many caches retrying in lockstep.
+    pub jitter: bool,
+
+    /// If `true`, the `Retry-After` header of `429` and `503` responses is
+    /// used as the delay instead (still capped at `max_delay`).
+    pub honor_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            honor_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before the retry following the given (`1`-indexed)
+    /// attempt.
+    ///
+    /// If present, `retry_after` is used instead of the exponential delay.
+    pub(crate) fn delay(
+        &self,
+        attempt: usize,
+        retry_after: Option<Duration>,
+    ) -> Duration {
+        let Self {
+            base_delay,
+            max_delay,
+            jitter,
+            honor_retry_after,
+            ..
+        } = self;
+
+        let exponent = attempt.saturating_sub(1).min(31) as u32;
+        let delay = base_delay.saturating_mul(2u32.pow(exponent));
+
+        let delay = match (honor_retry_after, retry_after) {
+            (true, Some(retry_after)) => retry_after,
+            _ => match jitter {
+                true => delay.mul_f64(0.5 + fastrand::f64() / 2.0),
+                false => delay,
+            },
+        };
+
+        delay.min(*max_delay)
+    }
+}
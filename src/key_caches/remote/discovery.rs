@@ -0,0 +1,105 @@
+//! `OpenID Connect` discovery, as according to
+//! [OpenID Connect Discovery 1.0, Section 4](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfig).
+//!
+//! Instead of hard-coding the `JWK` `uri` of a provider (which providers
+//! occasionally move), it can be discovered from the provider's issuer
+//! identifier:
+//!
+//! ```ignore
+//! let mut remote_cache =
+//!     RemoteCache::from_issuer("https://accounts.google.com").await?;
+//!
+//! assert_eq!(remote_cache.issuer(), Some("https://accounts.google.com"));
+//! remote_cache.refresh().await?;
+//!
+//! // The rest of the document is kept as well.
+//! let userinfo_endpoint = remote_cache.userinfo_endpoint();
+//! ```
+//!
+//! A configured [`RemoteCacheBuilder`](`super::builder::RemoteCacheBuilder`)
+//! can be used as well, by calling
+//! [`discover`](`super::builder::RemoteCacheBuilder::discover`) instead of
+//! [`build`](`super::builder::RemoteCacheBuilder::build`).
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::fetch::fetch_json;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::prelude;
+
+/// The path (relative to the issuer identifier) of the provider
+/// configuration document.
+pub const WELL_KNOWN_PATH: &str = "/.well-known/openid-configuration";
+
+/// The subset of the provider configuration document used by this crate.
+///
+/// All other fields of the document are ignored.
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderMetadata {
+    /// The issuer identifier of the provider.
+    ///
+    /// This is the value that the `iss` claim of its tokens must match.
+    pub issuer: String,
+
+    /// The `uri` of the provider's `JWK` set.
+    pub jwks_uri: String,
+
+    /// The `uri` of the provider's `OAuth 2.0` token endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+
+    /// The `uri` of the provider's `UserInfo` endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userinfo_endpoint: Option<String>,
+
+    /// The algorithms that the provider may sign `ID` tokens with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// The `https` origin of the given domain, which may be given with or without
+/// its scheme and trailing slashes (e.g., `"example.com"` and
+/// `"https://example.com/"` are both `"https://example.com"`).
+pub(crate) fn origin(domain: &str) -> String {
+    let host = domain.trim_start_matches("https://").trim_end_matches('/');
+
+    format!("https://{}", host)
+}
+
+/// The `uri` of the provider configuration document of the given issuer.
+pub(crate) fn discovery_uri(issuer: &str) -> String {
+    format!("{}{}", issuer.trim_end_matches('/'), WELL_KNOWN_PATH)
+}
+
+/// Fetch the provider configuration document of the given issuer.
+///
+/// The `issuer` inside of the document must be identical to the given one,
+/// otherwise the document is rejected with
+/// [`Error::invalid_discovery_document`].
+pub(crate) async fn discover(
+    fetcher: &dyn JwksFetcher,
+    issuer: &str,
+    config: &FetchConfig,
+) -> prelude::Result<ProviderMetadata> {
+    let uri = discovery_uri(issuer).parse::<http::Uri>()?;
+    let response = fetch_json(fetcher, uri, config).await?;
+
+    let metadata = serde_json::from_slice::<ProviderMetadata>(&response.body)
+        .map_err(|error| Error::invalid_discovery_document {
+            message: error.to_string(),
+        })?;
+
+    if metadata.issuer != issuer {
+        return Err(Error::invalid_discovery_document {
+            message: format!(
+                "The document is for the issuer `{}`, not for `{}`.",
+                metadata.issuer, issuer,
+            ),
+        });
+    };
+
+    Ok(metadata)
+}
@@ -2,15 +2,48 @@
 //!
 //! For more information, please visit: <https://developers.facebook.com/docs/facebook-login/limited-login/token/validating>.
 
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
 
 /// The URI for `Facebook`'s public `JWK`s.
-pub const FACEBOOK_JWK_URI: &'static str =
+pub const FACEBOOK_JWK_URI: &str =
     "https://www.facebook.com/.well-known/oauth/openid/jwks/";
 
 /// Claims made by `Facebook`.
 ///
 /// `JWT`'s issued by `Facebook` should have a body (i.e., the second portion of
-/// the `JWT`) that are `base64URL` decrypted into the below struct.
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Claims
+/// which depend on the requested permissions are optional; any other one
+/// (e.g., `user_friends`) is kept inside of `extra`.
+///
+/// For more information, please visit: <https://developers.facebook.com/docs/facebook-login/limited-login/token>.
 #[derive(Debug, Deserialize)]
-pub struct FacebookClaims;
+pub struct FacebookClaims {
+    /// Always `"https://www.facebook.com"`.
+    pub iss: String,
+
+    /// The id of the app.
+    pub aud: Audience,
+
+    /// The (app-scoped) id of the user.
+    pub sub: String,
+
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: Option<String>,
+    pub nonce: Option<String>,
+
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub family_name: Option<String>,
+    pub picture: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
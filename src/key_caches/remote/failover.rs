@@ -0,0 +1,203 @@
+//! Failing over between region-specific `JWK` endpoints.
+//!
+//! By default, a [`super::RemoteCache`] with
+//! [`fallback_uris`](`super::RemoteCache::fallback_uris`) tries its `uri`
+//! first on every refresh, and only then each mirror in order. If the `uri`
+//! is down for a while (e.g., a regional outage), every refresh pays for its
+//! timeouts (and retries) before reaching a healthy mirror.
+//!
+//! With a [`FailoverPolicy`], the cache instead keeps track of the health of
+//! every endpoint, and refreshes from an *active* endpoint:
+//! - once the active endpoint has failed `failure_threshold` times in a row
+//!   (while a less preferred one succeeded), the cache fails over to the
+//!   endpoint which succeeded,
+//! - every `probe_interval`, the endpoints are tried in order of preference
+//!   again, so that the cache fails back as soon as a more preferred endpoint
+//!   has recovered.
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder("https://us.idp.example.com/certs")
+//!     .fallback_uri("https://eu.idp.example.com/certs")
+//!     .fallback_uri("https://ap.idp.example.com/certs")
+//!     .failover_policy(FailoverPolicy::default())
+//!     .on_failover(|event| eprintln!("{:?}", event))
+//!     .build()?;
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote_cache(Tpa::Idp, remote_cache)
+//!     .finish()
+//!     .await?;
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use crate::prelude::Timestamp;
+use crate::time::now;
+
+/// When a [`super::RemoteCache`] switches between its endpoints.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct FailoverPolicy {
+    /// The number of consecutive failures of the active endpoint after which
+    /// the cache fails over.
+    pub failure_threshold: usize,
+
+    /// How often the endpoints are tried in order of preference again, while
+    /// the cache has failed over.
+    pub probe_interval: Duration,
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            probe_interval: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Emitted whenever a [`super::RemoteCache`] switches its active endpoint.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub enum FailoverEvent {
+    /// The active endpoint kept failing, so a less preferred one is used
+    /// instead.
+    FailedOver { from: http::Uri, to: http::Uri },
+
+    /// A more preferred endpoint has recovered, and is used again.
+    FailedBack { from: http::Uri, to: http::Uri },
+}
+
+pub(crate) type EventCallback = Arc<dyn Fn(&FailoverEvent) + Send + Sync>;
+
+struct State {
+    /// Index (into the endpoints) of the active endpoint.
+    active: usize,
+
+    /// The number of consecutive failures, per endpoint.
+    failures: Vec<usize>,
+
+    /// When the endpoints were last tried in order of preference.
+    probed_at: Option<Timestamp>,
+}
+
+/// The health of the endpoints of a [`super::RemoteCache`].
+///
+/// Shared (behind an [`Arc`]) so that background refreshes can update it
+/// without holding the lock of the cache.
+pub(crate) struct Failover {
+    policy: FailoverPolicy,
+    on_event: Option<EventCallback>,
+    state: Mutex<State>,
+}
+
+impl fmt::Debug for Failover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Failover")
+            .field("policy", &self.policy)
+            .field("active", &self.state().active)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Failover {
+    pub(crate) fn new(
+        policy: FailoverPolicy,
+        on_event: Option<EventCallback>,
+    ) -> Self {
+        let state = State {
+            active: 0,
+            failures: Vec::new(),
+            probed_at: None,
+        };
+
+        Self {
+            policy,
+            on_event,
+            state: Mutex::new(state),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Index (into the endpoints) of the active endpoint.
+    pub(crate) fn active(&self) -> usize {
+        self.state().active
+    }
+
+    /// The order in which the given number of endpoints should be tried.
+    ///
+    /// Either the active endpoint followed by the remaining ones in order of
+    /// preference, or (if a probe is due) all of them in order of preference.
+    pub(crate) fn order(&self, len: usize) -> Vec<usize> {
+        let mut state = self.state();
+        let now = now();
+
+        let probe_interval = self.policy.probe_interval.as_secs();
+        let probe_due = state.probed_at.is_none_or(|probed_at| {
+            now >= probed_at.saturating_add(probe_interval)
+        });
+
+        if state.active == 0 || probe_due {
+            state.probed_at = Some(now);
+            return (0..len).collect();
+        };
+
+        let active = state.active.min(len.saturating_sub(1));
+
+        std::iter::once(active)
+            .chain((0..len).filter(|index| *index != active))
+            .collect()
+    }
+
+    /// Record the outcome of fetching from the endpoint at the given index,
+    /// switching the active endpoint (and emitting an event) if necessary.
+    pub(crate) fn record(&self, uris: &[http::Uri], index: usize, ok: bool) {
+        let event = {
+            let mut state = self.state();
+            state.failures.resize(uris.len(), 0);
+
+            let failures = match state.failures.get_mut(index) {
+                Some(failures) => failures,
+                None => return,
+            };
+
+            if !ok {
+                *failures += 1;
+                return;
+            };
+
+            *failures = 0;
+
+            let active = state.active.min(uris.len().saturating_sub(1));
+            let sustained = state.failures.get(active).is_some_and(|failures| {
+                *failures >= self.policy.failure_threshold
+            });
+            let switch = index < active || (index > active && sustained);
+
+            let (from, to) = match (uris.get(active), uris.get(index)) {
+                (Some(from), Some(to)) if switch => (from.clone(), to.clone()),
+                _ => return,
+            };
+
+            state.active = index;
+
+            match index < active {
+                true => FailoverEvent::FailedBack { from, to },
+                false => FailoverEvent::FailedOver { from, to },
+            }
+        };
+
+        if let Some(on_event) = &self.on_event {
+            on_event(&event);
+        };
+    }
+}
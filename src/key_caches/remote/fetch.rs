@@ -0,0 +1,569 @@
+//! Fetching and parsing of remote `JWK` sets.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use http::HeaderMap;
+use http::StatusCode;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::DecodingKey;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::config::RedirectPolicy;
+use crate::key_caches::remote::config::RetryPolicy;
+use crate::key_caches::remote::failover::Failover;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::file;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyOperation;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::x509::pem_body;
+use crate::key_caches::remote::Cache;
+use crate::prelude;
+use crate::prelude::Timestamp;
+
+/// The leeway (in seconds) subtracted from the `max-age` of every response,
+/// i.e., 1hr.
+pub(crate) const EXPIRY_LEEWAY: u64 = 3600;
+
+/// Fetches the according [`Key`]s from the given URI and computes the
+/// respective [`DecodingKey`] for each [`Key`].
+///
+/// The keys are unique by their `kid` (i.e., their Key-ID).
+/// Each JWT can be decrypted by a corresponding [`Key`] that has a matching
+/// `kid`.
+/// Therefore, the returned BTreeMap is indexed as: `kid -> Key`.
+///
+/// This function filters out all keys which don't can't be serialized into a
+/// [`Key`]. Furthermore, this function also filters out all keys whose `kty !=
+/// "RSA"`. This includes valid keys which use a different encryption mechanism.
+///
+/// This function specifically uses the
+/// [`from_rsa_components`](`DecodingKey::from_rsa_components`) function.
+/// This is because we expect that the target is using "RSA" encryption scheme.
+///
+/// The `max-age` of the response is returned along with the keys (see
+/// [`expiry_time`] for how it is turned into an expiry time).
+///
+/// See [`fetch_json`] for how the response is fetched. `file://` `uri`s are
+/// read from disk instead.
+pub(crate) async fn fetch(
+    fetcher: &dyn JwksFetcher,
+    uri: http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<(Cache, Option<u64>)> {
+    if file::is_file(&uri) {
+        return file::read(&uri, config).await;
+    };
+
+    let JwksResponse { headers, body, .. } =
+        fetch_json(fetcher, uri, config).await?;
+
+    let max_age = parse_max_age(&headers)?;
+    let keys = parse_keys(&body, config.format)?;
+
+    Ok((keys, max_age))
+}
+
+/// Fetch the (successful, `JSON`) response located at the given `uri`.
+///
+/// Failed fetches are retried according to the configured [`RetryPolicy`].
+/// If a `timeout` is configured, each attempt must complete within it.
+pub(crate) async fn fetch_json(
+    fetcher: &dyn JwksFetcher,
+    uri: http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<JwksResponse> {
+    let FetchConfig { retry_policy, .. } = config;
+
+    let max_attempts = retry_policy
+        .map(|RetryPolicy { max_attempts, .. }| max_attempts)
+        .unwrap_or(1);
+
+    let mut attempt = 1;
+
+    let response = loop {
+        let (error, retry_after) =
+            match fetch_with_timeout(fetcher, &uri, config).await {
+                Ok(response) if response.status.is_success() => break response,
+                Ok(JwksResponse {
+                    status, headers, ..
+                }) => {
+                    let retry_after = match status {
+                        StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::SERVICE_UNAVAILABLE => {
+                            parse_retry_after(&headers)
+                        },
+                        _ => None,
+                    };
+
+                    let error = Error::unexpected_status {
+                        status: status.as_u16(),
+                    };
+
+                    (error, retry_after)
+                },
+                Err(error) => (error, None),
+            };
+
+        let is_retryable = match &error {
+            Error::unable_to_fetch_keys { .. } => true,
+            Error::unexpected_status { status } => {
+                *status == 429 || (500..600).contains(status)
+            },
+            _ => false,
+        };
+
+        match (is_retryable && attempt < max_attempts, retry_policy) {
+            (true, Some(retry_policy)) => {
+                let delay = retry_policy.delay(attempt, retry_after);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            },
+            _ => return Err(error),
+        };
+    };
+
+    check_content_type(&response.headers)?;
+
+    if response.body.len() > config.max_body_size {
+        return Err(Error::response_too_large {
+            limit: config.max_body_size,
+        });
+    };
+
+    Ok(response)
+}
+
+/// Fetch the keys from the first of the given `uri`s (i.e., a primary `uri`,
+/// followed by its mirrors) which succeeds.
+///
+/// The `uri`s are tried in order of preference, unless a [`Failover`] is
+/// given, in which case it decides the order (and is told the outcome of every
+/// fetch).
+///
+/// Each `uri` is fetched (and retried) exactly as in [`fetch`]. If all of them
+/// fail, the error of the last one is returned.
+pub(crate) async fn fetch_any(
+    fetcher: &dyn JwksFetcher,
+    uris: &[http::Uri],
+    config: &FetchConfig,
+    failover: Option<&Failover>,
+) -> prelude::Result<(Cache, Option<u64>)> {
+    let order = match failover {
+        Some(failover) => failover.order(uris.len()),
+        None => (0..uris.len()).collect(),
+    };
+
+    let mut last_error = Error::invalid_uri;
+
+    for index in order {
+        let uri = match uris.get(index) {
+            Some(uri) => uri.clone(),
+            None => continue,
+        };
+        let result = fetch(fetcher, uri, config).await;
+
+        if let Some(failover) = failover {
+            failover.record(uris, index, result.is_ok());
+        };
+
+        match result {
+            Ok(result) => return Ok(result),
+            Err(error) => last_error = error,
+        };
+    }
+
+    Err(last_error)
+}
+
+/// Perform a single fetch attempt (following redirects), bounded by the
+/// configured `timeout`.
+async fn fetch_with_timeout(
+    fetcher: &dyn JwksFetcher,
+    uri: &http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<JwksResponse> {
+    match config.timeout {
+        Some(timeout) => {
+            tokio::time::timeout(timeout, fetch_once(fetcher, uri, config))
+                .await
+                .map_err(|_| Error::unable_to_fetch_keys {
+                    message: "The request timed out.".into(),
+                })?
+        },
+        None => fetch_once(fetcher, uri, config).await,
+    }
+}
+
+/// Perform a single fetch attempt, following redirects according to the
+/// configured [`RedirectPolicy`].
+async fn fetch_once(
+    fetcher: &dyn JwksFetcher,
+    uri: &http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<JwksResponse> {
+    let FetchConfig {
+        redirect_policy,
+        allow_insecure_http,
+        ..
+    } = config;
+
+    let max_redirects = match redirect_policy {
+        RedirectPolicy::None => 0,
+        RedirectPolicy::Limited(max_redirects) => *max_redirects,
+    };
+
+    let origin = uri.clone();
+    let mut uri = uri.clone();
+    let mut visited = vec![uri.to_string()];
+
+    loop {
+        // Credentials are only ever sent to the origin of the `JWK` endpoint
+        // itself (and never to the target of a redirect to another origin).
+        let response = match is_same_origin(&origin, &uri) {
+            true => fetcher.fetch(&uri).await?,
+            false => fetcher.fetch_anonymous(&uri).await?,
+        };
+
+        let location = response
+            .status
+            .is_redirection()
+            .then(|| response.headers.get(http::header::LOCATION))
+            .flatten();
+
+        let location = match (location, redirect_policy) {
+            (Some(location), RedirectPolicy::Limited(_)) => location,
+            _ => return Ok(response),
+        };
+
+        if visited.len() > max_redirects {
+            return Err(Error::too_many_redirects {
+                limit: max_redirects,
+            });
+        };
+
+        uri = resolve_redirect(&uri, location.to_str()?)?;
+        check_scheme(&uri, *allow_insecure_http)?;
+
+        let target = uri.to_string();
+        if visited.contains(&target) {
+            return Err(Error::redirect_loop);
+        };
+        visited.push(target);
+    }
+}
+
+/// Whether both of the given `uri`s share the same origin (i.e., the same
+/// scheme, host and port).
+fn is_same_origin(origin: &http::Uri, uri: &http::Uri) -> bool {
+    origin.scheme() == uri.scheme() && origin.authority() == uri.authority()
+}
+
+/// Make sure that the given `uri` uses the `https` scheme (or, if allowed, the
+/// `http` scheme).
+pub(crate) fn check_scheme(
+    uri: &http::Uri,
+    allow_insecure_http: bool,
+) -> prelude::Result<()> {
+    let scheme = uri.scheme();
+
+    match (scheme, allow_insecure_http) {
+        (Some(scheme), _) if *scheme == http::uri::Scheme::HTTPS => Ok(()),
+        (Some(scheme), true) if *scheme == http::uri::Scheme::HTTP => Ok(()),
+        _ => Err(Error::invalid_uri),
+    }
+}
+
+/// Make sure that the `Content-Type` header is present, and is a `JSON` media
+/// type (i.e., `application/json`, or any `+json` suffixed type).
+fn check_content_type(headers: &HeaderMap) -> prelude::Result<()> {
+    let content_type = headers
+        .get(http::header::CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        .unwrap_or_default();
+
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match essence == "application/json" || essence.ends_with("+json") {
+        true => Ok(()),
+        false => Err(Error::unexpected_content_type { content_type }),
+    }
+}
+
+/// Parse the `Retry-After` header (if present, and given in seconds).
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(http::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// The expiry time of keys which were fetched at the given time (in
+/// Unix-Time), with the given `max-age`.
+///
+/// [`EXPIRY_LEEWAY`] is subtracted from the `max-age`. A `max-age` of
+/// [`u64::MAX`] (e.g., of keys read from a file) never expires.
+pub(crate) fn expiry_time(max_age: u64, now: Timestamp) -> u64 {
+    match max_age {
+        u64::MAX => u64::MAX,
+        max_age => now.saturating_add(max_age).saturating_sub(EXPIRY_LEEWAY),
+    }
+}
+
+/// Parse the `max-age` directive of the `cache-control` header (if present).
+pub(crate) fn parse_max_age(
+    headers: &HeaderMap,
+) -> prelude::Result<Option<u64>> {
+    const CACHE_HEADER: &str = "cache-control";
+    const MAX_AGE_HEADER: &str = "max-age=";
+
+    let max_age = headers
+        .get(CACHE_HEADER)
+        .map(|value| {
+            value.to_str().map(|value| {
+                value
+                    .split(',')
+                    .filter_map(|segment| {
+                        segment
+                            .trim()
+                            .strip_prefix(MAX_AGE_HEADER)
+                            .and_then(|max_age| max_age.parse::<u64>().ok())
+                    })
+                    .next()
+            })
+        })
+        .transpose()?
+        .flatten();
+
+    Ok(max_age)
+}
+
+/// Parse the given body (a `JWK` set, or a map of `X.509` certificates) into a
+/// [`Cache`].
+///
+/// Keys which cannot be used by a [`super::RemoteCache`] are filtered out.
+/// Fails with [`Error::unable_to_fetch_keys`] if keys were published, but none
+/// of them can be used (e.g., a set of `EC` certificates), naming the `kty`
+/// of the skipped keys.
+pub(crate) fn parse_keys(
+    body: &[u8],
+    format: JwksFormat,
+) -> prelude::Result<Cache> {
+    let body: Value = serde_json::from_slice(body)?;
+
+    let format = match format {
+        JwksFormat::Auto => detect_format(&body),
+        format => format,
+    };
+
+    let keys = match format {
+        JwksFormat::X509Map => parse_x509_map(body)?,
+        JwksFormat::Auto | JwksFormat::Jwks => parse_jwks(body)?,
+    };
+    let ktys = keys
+        .iter()
+        .map(|Key { kty, .. }| format!("`{}`", kty.as_str()))
+        .collect::<BTreeSet<_>>();
+
+    let cache = to_cache(keys);
+
+    if cache.is_empty() && !ktys.is_empty() {
+        return Err(Error::unable_to_fetch_keys {
+            message: format!(
+                "None of the published keys can be used; only `RSA` signing \
+                 keys are supported (skipped: {}).",
+                ktys.into_iter().collect::<Vec<_>>().join(", "),
+            ),
+        });
+    };
+
+    Ok(cache)
+}
+
+/// Detect the format of the given document.
+///
+/// Only non-empty objects whose members are *all* `PEM` certificates are
+/// considered to be a [`JwksFormat::X509Map`].
+pub(crate) fn detect_format(body: &Value) -> JwksFormat {
+    let is_x509_map = body.as_object().is_some_and(|members| {
+        !members.contains_key("keys")
+            && !members.is_empty()
+            && members.values().all(|member| {
+                member.as_str().is_some_and(|pem| pem_body(pem).is_some())
+            })
+    });
+
+    match is_x509_map {
+        true => JwksFormat::X509Map,
+        false => JwksFormat::Jwks,
+    }
+}
+
+fn parse_jwks(body: Value) -> prelude::Result<Vec<Key>> {
+    let body = body
+        .get("keys")
+        .ok_or(Error::unable_to_fetch_keys {
+            message: "No 'keys' array contained in the returned object.".into(),
+        })?
+        .clone();
+
+    let keys = serde_json::from_value::<Vec<Value>>(body)?
+        .into_iter()
+        .filter_map(|value| serde_json::from_value::<Key>(value).ok())
+        .collect();
+
+    Ok(keys)
+}
+
+/// Convert each certificate of the given map into a [`Key`], whose `n` and
+/// `e` are then read from its `x5c` (see [`to_cache`]).
+///
+/// Certificates which are not `PEM` encoded are skipped.
+pub(crate) fn parse_x509_map(body: Value) -> prelude::Result<Vec<Key>> {
+    let members = match body {
+        Value::Object(members) => members,
+        _ => {
+            return Err(Error::unable_to_fetch_keys {
+                message: "Expected a map of `kid`s to certificates.".into(),
+            })
+        },
+    };
+
+    let keys = members
+        .into_iter()
+        .filter_map(|(kid, pem)| {
+            let der = pem.as_str().and_then(pem_body)?;
+
+            Some(Key {
+                e: String::new(),
+                kty: KeyType::RSA,
+                alg: None,
+                n: String::new(),
+                kid,
+                r#use: Use::sig,
+                key_ops: None,
+                x5u: None,
+                x5c: Some(vec![der]),
+                x5t: None,
+                x5t_s256: None,
+                crv: None,
+                x: None,
+                y: None,
+            })
+        })
+        .collect();
+
+    Ok(keys)
+}
+
+/// Build a [`Cache`] out of the given [`Key`]s.
+///
+/// Keys which cannot be used by a [`super::RemoteCache`] (i.e., anything but
+/// `RS256` signing keys, or keys whose `key_ops` exclude `verify`) are
+/// filtered out.
+///
+/// `RSA` signing keys without an `alg` (e.g., some of the keys published by
+/// `Facebook` and `Apple`) are kept; the algorithm is then taken from the
+/// header of each token (which must be `RS256`).
+///
+/// `RSA` keys which only publish an `x5c` certificate chain (instead of `n`
+/// and `e`) are completed from the leaf certificate (see
+/// [`Key::complete_from_x5c`]), and keys without a `kid` are indexed by
+/// their thumbprint (see [`Key::thumbprint_sha256`]). Certificates of any
+/// other `kty` (e.g., `EC`) are filtered out like any other key of that
+/// `kty`.
+pub(crate) fn to_cache<K>(keys: K) -> Cache
+where
+    K: IntoIterator<Item = Key>,
+{
+    to_cache_with(keys, |key| {
+        DecodingKey::from_rsa_components(&key.n, &key.e).ok()
+    })
+}
+
+/// Build a [`Cache`] out of the given [`Key`]s, just like [`to_cache`], but
+/// with the [`DecodingKey`] of each (usable) key built by the given function.
+///
+/// Keys for which the function returns [`None`] are filtered out.
+pub(crate) fn to_cache_with<K, F>(keys: K, decoding_key: F) -> Cache
+where
+    K: IntoIterator<Item = Key>,
+    F: Fn(&Key) -> Option<DecodingKey>,
+{
+    keys.into_iter()
+        .filter_map(|mut key| {
+            let Key {
+                kty, alg, r#use, ..
+            } = &key;
+
+            match kty {
+                KeyType::RSA => (),
+                KeyType::EC
+                | KeyType::oct
+                | KeyType::OKP
+                | KeyType::Other(_) => return None,
+            };
+
+            match alg {
+                Some(Algorithm::RS256) | None => (),
+                _ => return None,
+            };
+
+            match r#use {
+                Use::sig => (),
+                Use::enc | Use::Other(_) => return None,
+            };
+
+            if !key.allows(&KeyOperation::Verify) {
+                return None;
+            };
+
+            if key.n.is_empty() || key.e.is_empty() {
+                key.complete_from_x5c().ok()?;
+            };
+
+            if key.kid.is_empty() {
+                key.kid = key.thumbprint_sha256().ok()?;
+            };
+
+            let kid = key.kid.clone();
+
+            decoding_key(&key).map(|decoding_key| (kid, (key, decoding_key)))
+        })
+        .collect()
+}
+
+/// Resolve the `Location` header of a redirect response against the `uri`
+/// which issued the redirect.
+///
+/// Relative locations (e.g., `/certs`) keep the scheme and authority of the
+/// original `uri`.
+fn resolve_redirect(
+    uri: &http::Uri,
+    location: &str,
+) -> prelude::Result<http::Uri> {
+    let location = location.parse::<http::Uri>()?;
+
+    match location.scheme() {
+        Some(_) => Ok(location),
+        None => {
+            let mut parts = location.into_parts();
+            parts.scheme = uri.scheme().cloned();
+            parts.authority = uri.authority().cloned();
+
+            http::Uri::from_parts(parts).map_err(|_| Error::invalid_uri)
+        },
+    }
+}
@@ -0,0 +1,280 @@
+//! The network layer used by a [`super::RemoteCache`] to fetch its keys.
+//!
+//! By default, [`HyperFetcher`] is used (if either the `native-tls` or the
+//! `rustls` feature is enabled; otherwise, every [`super::RemoteCache`] must be
+//! given a fetcher, and fails to build with [`Error::missing_fetcher`]
+//! without one).
+//! Any other `http` client (e.g., a proxy-aware corporate client, or a mock
+//! returning canned responses in tests) can be used instead by implementing the
+//! [`JwksFetcher`] trait and passing it to
+//! [`RemoteCacheBuilder::fetcher`](`super::builder::RemoteCacheBuilder::fetcher`).
+//!
+//! ```ignore
+//! struct MyFetcher;
+//!
+//! #[async_trait]
+//! impl JwksFetcher for MyFetcher {
+//!     async fn fetch(&self, uri: &http::Uri) -> Result<JwksResponse> {
+//!         // perform the request using your preferred client...
+//!     }
+//! }
+//!
+//! let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .fetcher(MyFetcher)
+//!     .build()?;
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::HeaderMap;
+use http::StatusCode;
+use hyper::body::Bytes;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use hyper::body::HttpBody;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use hyper::client::HttpConnector;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use hyper::Client;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::key_caches::remote::tls::connector;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::key_caches::remote::tls::Connector;
+use crate::prelude;
+
+/// The raw response received from a `JWK` endpoint.
+///
+/// Parsing the response (i.e., reading the `cache-control` header and the
+/// `keys` array) is done by the [`super::RemoteCache`] itself, so that every
+/// [`JwksFetcher`] behaves the same way.
+#[derive(Clone, Debug, Default)]
+pub struct JwksResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// Performs a `GET` request against a `JWK` endpoint.
+///
+/// Implementations should *not* follow redirects or apply the overall fetch
+/// `timeout`; the [`super::RemoteCache`] takes care of both.
+#[async_trait]
+pub trait JwksFetcher: Send + Sync {
+    /// Fetch the raw response located at the given `uri`.
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse>;
+
+    /// Fetch the raw response located at the given `uri`, which a redirect
+    /// from another origin led to.
+    ///
+    /// No credentials (i.e., the `Authorization`, `Proxy-Authorization` and
+    /// `Cookie` headers) should be sent along with such a request, so that
+    /// they never leak to a third party.
+    /// Defaults to [`fetch`](`JwksFetcher::fetch`), which is fine for any
+    /// fetcher that does not send credentials in the first place.
+    async fn fetch_anonymous(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<JwksResponse> {
+        self.fetch(uri).await
+    }
+}
+
+#[async_trait]
+impl<F> JwksFetcher for Arc<F>
+where
+    F: JwksFetcher + ?Sized,
+{
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        self.as_ref().fetch(uri).await
+    }
+
+    async fn fetch_anonymous(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<JwksResponse> {
+        self.as_ref().fetch_anonymous(uri).await
+    }
+}
+
+/// The default [`JwksFetcher`], built on top of [`hyper`].
+///
+/// The `connect_timeout` and `headers` of the given [`FetchConfig`] are
+/// applied to every request (except for the credentials among the `headers`,
+/// which are never sent to another origin), and its `root_certificates` and
+/// `identity` are used for every `TLS` handshake.
+///
+/// `TLS` is provided by either `rustls` (the default, pure-Rust backend) or
+/// `native-tls`, depending on which of the corresponding features is enabled.
+///
+/// The underlying [`Client`] is created once, and reused by every fetch (as
+/// well as by every clone of this [`HyperFetcher`]). Connections are therefore
+/// pooled, and `TLS` sessions are resumed, across refreshes.
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[derive(Clone, Debug)]
+pub struct HyperFetcher {
+    config: FetchConfig,
+    client: Client<Connector>,
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl Default for HyperFetcher {
+    fn default() -> Self {
+        Self::new(FetchConfig::default())
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl HyperFetcher {
+    /// Create a new [`HyperFetcher`] using the given configuration.
+    ///
+    /// ### Panics:
+    /// If the `root_certificates` or the `identity` of the given configuration
+    /// cannot be used by the `TLS` backend.
+    /// Use [`try_new`](`HyperFetcher::try_new`) to handle this case instead.
+    #[allow(clippy::expect_used)]
+    pub fn new(config: FetchConfig) -> Self {
+        Self::try_new(config).expect("the TLS configuration should be valid")
+    }
+
+    /// Create a new [`HyperFetcher`] using the given configuration.
+    ///
+    /// Fails with [`Error::invalid_tls_config`] if the `root_certificates` or
+    /// the `identity` of the given configuration cannot be used by the `TLS`
+    /// backend.
+    pub fn try_new(config: FetchConfig) -> prelude::Result<Self> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(config.connect_timeout);
+
+        let connector = connector(http, &config)?;
+        let client = Client::builder().build::<_, hyper::Body>(connector);
+
+        Ok(Self { config, client })
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[async_trait]
+impl JwksFetcher for HyperFetcher {
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        self.request(uri, self.config.headers.clone()).await
+    }
+
+    async fn fetch_anonymous(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<JwksResponse> {
+        self.request(uri, without_credentials(&self.config.headers))
+            .await
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl HyperFetcher {
+    /// Perform a `GET` request against the given `uri`, with the given
+    /// `headers`.
+    async fn request(
+        &self,
+        uri: &http::Uri,
+        headers: HeaderMap,
+    ) -> prelude::Result<JwksResponse> {
+        let Self { config, client } = self;
+        let FetchConfig { max_body_size, .. } = config;
+
+        let mut request = hyper::Request::get(uri.clone())
+            .body(hyper::Body::empty())
+            .map_err(|_| Error::invalid_uri)?;
+        request.headers_mut().extend(headers);
+
+        let response = client.request(request).await?;
+        let (parts, body) = response.into_parts();
+        let body = read_body(body, *max_body_size).await?;
+
+        Ok(JwksResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        })
+    }
+}
+
+/// The given `headers`, without any credentials (i.e., without the
+/// `Authorization`, `Proxy-Authorization` and `Cookie` headers).
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) fn without_credentials(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    headers.remove(http::header::AUTHORIZATION);
+    headers.remove(http::header::PROXY_AUTHORIZATION);
+    headers.remove(http::header::COOKIE);
+
+    headers
+}
+
+/// The default [`JwksFetcher`] (i.e., a [`HyperFetcher`]) for the given
+/// configuration.
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) fn default_fetcher(
+    config: &FetchConfig,
+) -> prelude::Result<Arc<dyn JwksFetcher>> {
+    Ok(Arc::new(HyperFetcher::try_new(config.clone())?))
+}
+
+/// The default [`JwksFetcher`] for the given configuration.
+///
+/// Fails with [`Error::missing_fetcher`], since the [`HyperFetcher`] requires
+/// a `TLS` backend (i.e., either the `native-tls` or the `rustls` feature).
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+pub(crate) fn default_fetcher(
+    _: &FetchConfig,
+) -> prelude::Result<Arc<dyn JwksFetcher>> {
+    Err(Error::missing_fetcher)
+}
+
+/// The [`JwksFetcher`] of a [`super::RemoteCache`] built from static keys
+/// (see [`super::RemoteCache::from_keys`]).
+///
+/// Every fetch fails, so refreshing such a cache never performs any network
+/// requests (and leaves its keys in place).
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct StaticFetcher;
+
+#[async_trait]
+impl JwksFetcher for StaticFetcher {
+    async fn fetch(&self, _: &http::Uri) -> prelude::Result<JwksResponse> {
+        Err(Error::unable_to_fetch_keys {
+            message: "The keys of this cache are static.".into(),
+        })
+    }
+}
+
+/// Read the given body, giving up as soon as more than `limit` bytes have been
+/// received.
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) async fn read_body(
+    mut body: hyper::Body,
+    limit: usize,
+) -> prelude::Result<Bytes> {
+    let too_large = || Error::response_too_large { limit };
+
+    let declared = body.size_hint().exact().unwrap_or_default();
+    if declared > limit as u64 {
+        return Err(too_large());
+    };
+
+    let mut buffer = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+
+        if buffer.len() + chunk.len() > limit {
+            return Err(too_large());
+        };
+
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(buffer.into())
+}
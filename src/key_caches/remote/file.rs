@@ -0,0 +1,157 @@
+//! Loading `JWK` sets from local files (i.e., `file://` `uri`s).
+//!
+//! Deployments often mount the `JWK` set of their issuer as a file (e.g., a
+//! `Kubernetes` config-map). A [`super::RemoteCache`] targeting a `file://`
+//! `uri` reads that file instead of performing a request, but otherwise parses
+//! and filters the keys exactly as when fetching them.
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::from_file("/etc/jwks/keys.json")?;
+//!
+//! // Or, equivalently (without loading the keys yet):
+//! let remote_cache = RemoteCache::new("file:///etc/jwks/keys.json")?;
+//! ```
+//!
+//! ### Note:
+//! Keys read from a file never expire. Refreshing the cache re-reads the file,
+//! so a [`RefreshSchedule::Interval`] picks up updates to it.
+//!
+//! `file://` `uri`s are only accepted when building a cache; a remote endpoint
+//! can never redirect to one, and a discovered `jwks_uri` can never be one.
+//!
+//! [`RefreshSchedule::Interval`]: `super::auto_refresh::RefreshSchedule::Interval`
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::Error;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::fetch::parse_keys;
+use crate::key_caches::remote::Cache;
+use crate::prelude;
+
+/// The scheme of `uri`s pointing to local files.
+const FILE_SCHEME: &str = "file";
+
+/// The host of `uri`s pointing to local files.
+///
+/// `file:///path` `uri`s (i.e., without a host) cannot be represented by an
+/// [`http::Uri`], so they are normalized to `file://localhost/path`, which is
+/// equivalent (see [RFC8089](https://datatracker.ietf.org/doc/html/rfc8089)).
+const FILE_HOST: &str = "localhost";
+
+/// Check to see if the given `uri` points to a local file.
+pub(crate) fn is_file(uri: &http::Uri) -> bool {
+    uri.scheme_str() == Some(FILE_SCHEME)
+}
+
+/// Normalize `file:///path` to `file://localhost/path`.
+///
+/// Any other `uri` is returned as is.
+pub(crate) fn normalize(uri: String) -> String {
+    match uri.strip_prefix("file:///") {
+        Some(path) => format!("{}://{}/{}", FILE_SCHEME, FILE_HOST, path),
+        None => uri,
+    }
+}
+
+/// Percent-encode every byte of the given value, except for alphanumerics and
+/// the given `safe` bytes.
+pub(crate) fn encode(value: &str, safe: &[u8]) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte.is_ascii_alphanumeric() || safe.contains(&byte) {
+            true => (byte as char).into(),
+            false => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// The `file://` `uri` of the given path.
+///
+/// Relative paths are resolved against the current working directory.
+pub(crate) fn to_uri(path: &Path) -> prelude::Result<http::Uri> {
+    let path = std::path::absolute(path).map_err(|_| Error::invalid_uri)?;
+    let path = path.to_str().ok_or(Error::invalid_uri)?;
+
+    let encoded = encode(path, b"-._~/");
+    let uri = format!("{}://{}{}", FILE_SCHEME, FILE_HOST, encoded);
+
+    Ok(uri.parse()?)
+}
+
+/// The path that the given `file://` `uri` points to.
+///
+/// Fails with [`Error::invalid_uri`] if the `uri` points to a file on another
+/// host.
+pub(crate) fn to_path(uri: &http::Uri) -> prelude::Result<PathBuf> {
+    match uri.host() {
+        Some(FILE_HOST) => (),
+        _ => return Err(Error::invalid_uri),
+    };
+
+    let mut bytes = Vec::new();
+    let mut encoded = uri.path().bytes();
+
+    while let Some(byte) = encoded.next() {
+        let byte = match byte {
+            b'%' => {
+                let hex = [
+                    encoded.next().ok_or(Error::invalid_uri)?,
+                    encoded.next().ok_or(Error::invalid_uri)?,
+                ];
+                let hex = std::str::from_utf8(&hex)
+                    .map_err(|_| Error::invalid_uri)?;
+
+                u8::from_str_radix(hex, 16).map_err(|_| Error::invalid_uri)?
+            },
+            byte => byte,
+        };
+
+        bytes.push(byte);
+    }
+
+    let path = String::from_utf8(bytes).map_err(|_| Error::invalid_uri)?;
+
+    Ok(path.into())
+}
+
+/// Parse the contents of a file into a [`Cache`], along with its `max-age`
+/// (i.e., [`u64::MAX`], which never expires).
+fn parse(
+    contents: std::io::Result<Vec<u8>>,
+    config: &FetchConfig,
+) -> prelude::Result<(Cache, Option<u64>)> {
+    let contents = contents.map_err(|error| Error::unable_to_fetch_keys {
+        message: error.to_string(),
+    })?;
+
+    if contents.len() > config.max_body_size {
+        return Err(Error::response_too_large {
+            limit: config.max_body_size,
+        });
+    };
+
+    Ok((parse_keys(&contents, config.format)?, Some(u64::MAX)))
+}
+
+/// Read the keys from the file that the given `uri` points to.
+pub(crate) async fn read(
+    uri: &http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<(Cache, Option<u64>)> {
+    let path = to_path(uri)?;
+
+    parse(tokio::fs::read(path).await, config)
+}
+
+/// Read the keys from the file that the given `uri` points to, blocking the
+/// current thread.
+pub(crate) fn read_blocking(
+    uri: &http::Uri,
+    config: &FetchConfig,
+) -> prelude::Result<(Cache, Option<u64>)> {
+    let path = to_path(uri)?;
+
+    parse(std::fs::read(path), config)
+}
@@ -0,0 +1,147 @@
+//! `Firebase` JWT Claim object.
+//!
+//! `Firebase` serves its keys as a map of `kid`s to `X.509` certificates
+//! (instead of a `JWK` set), which a [`super::RemoteCache`] detects
+//! automatically (see
+//! [`JwksFormat::X509Map`](`crate::key_caches::remote::config::JwksFormat::X509Map`)).
+//!
+//! Every `Firebase` project shares those keys, but has an issuer of its own,
+//! and is the audience of its tokens; both must be checked:
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::for_firebase("my-project")?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<FirebaseClaims, _>(token)?;
+//! claims.validate("my-project")?;
+//!
+//! // Or, through a registry, which checks them for every token:
+//! let registry = KeyRegistry::builder()
+//!     .add_firebase(Tpa::Firebase, "my-project")
+//!     .finish()
+//!     .await?;
+//! ```
+//!
+//! For more information, please visit: <https://firebase.google.com/docs/auth/admin/verify-id-tokens#verify_id_tokens_using_a_third-party_jwt_library>.
+
+use std::collections::BTreeMap;
+
+use jsonwebtoken::errors::ErrorKind;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The URI for `Firebase`'s public certificates.
+pub const FIREBASE_JWK_URI: &str = "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
+
+/// The `iss` claim of the tokens of the given `Firebase` project (e.g.,
+/// `"my-project"`).
+pub fn firebase_issuer(project_id: &str) -> String {
+    format!("https://securetoken.google.com/{}", project_id)
+}
+
+impl RemoteCache {
+    /// Generate a new [`RemoteCache`] targeting the public certificates of
+    /// `Firebase`, on behalf of the `Firebase` project with the given id.
+    ///
+    /// The issuer of the project is derived from its id (see
+    /// [`issuer`](`RemoteCache::issuer`)), so no discovery request is made.
+    /// Just like with [`RemoteCache::new`], no keys are fetched yet.
+    ///
+    /// ### Note:
+    /// The keys are shared by every project, so the claims of each token
+    /// must still be checked against the project (see
+    /// [`FirebaseClaims::validate`]).
+    pub fn for_firebase(project_id: &str) -> prelude::Result<Self> {
+        Self::from_metadata(ProviderMetadata {
+            issuer: firebase_issuer(project_id),
+            jwks_uri: FIREBASE_JWK_URI.into(),
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            id_token_signing_alg_values_supported: vec!["RS256".into()],
+        })
+    }
+}
+
+/// Claims made by `Firebase`.
+///
+/// `JWT`'s issued by `Firebase` should have a body (i.e., the second portion
+/// of the `JWT`) that are `base64URL` decrypted into the below struct.
+///
+/// Custom claims (set through the `Admin SDK`) are kept inside of `extra`,
+/// along with any other unrecognized claim.
+#[derive(Debug, Deserialize)]
+pub struct FirebaseClaims {
+    /// The id of the project.
+    pub aud: Audience,
+    pub iat: u64,
+    pub exp: u64,
+    pub iss: String,
+
+    /// The `uid` of the user.
+    pub sub: String,
+
+    /// The time at which the user signed in.
+    pub auth_time: u64,
+
+    pub user_id: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub phone_number: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub firebase: FirebaseInfo,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl FirebaseClaims {
+    /// Check that the given (verified) claims were issued by, and for, the
+    /// `Firebase` project with the given id.
+    ///
+    /// Fails with [`Error::unable_to_verify_token`] if the issuer or the
+    /// audience do not match, or if the subject is empty.
+    ///
+    /// [`Error::unable_to_verify_token`]: `crate::error::Error::unable_to_verify_token`
+    pub fn validate(&self, project_id: &str) -> prelude::Result<()> {
+        let Self { aud, iss, sub, .. } = self;
+
+        if *iss != firebase_issuer(project_id) {
+            Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidIssuer))?;
+        };
+
+        if *aud != Audience::from(project_id) {
+            Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidAudience))?;
+        };
+
+        if sub.is_empty() {
+            Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidSubject))?;
+        };
+
+        Ok(())
+    }
+}
+
+/// The `firebase` claim, describing how the user signed in.
+#[derive(Debug, Deserialize)]
+pub struct FirebaseInfo {
+    /// e.g., `"password"`, `"google.com"`, or `"custom"`.
+    pub sign_in_provider: String,
+
+    /// Only present if the user completed a second factor.
+    pub sign_in_second_factor: Option<String>,
+
+    /// Only present if the user belongs to a tenant of the project.
+    pub tenant: Option<String>,
+
+    /// The identifiers of the user (e.g., their email addresses) by each
+    /// linked provider (e.g., `"email"`, or `"google.com"`).
+    #[serde(default)]
+    pub identities: BTreeMap<String, Vec<String>>,
+}
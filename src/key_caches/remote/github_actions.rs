@@ -0,0 +1,91 @@
+//! `GitHub Actions` OIDC JWT Claim object.
+//!
+//! Workflows can request a token from `GitHub` which proves the identity of
+//! the workload (i.e., the repository, the branch, the workflow, and the
+//! environment that it runs in), instead of storing long-lived secrets:
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::new(GITHUB_ACTIONS_JWK_URI)?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<GitHubActionsClaims, _>(token)?;
+//!
+//! let trusted = claims.iss == GITHUB_ACTIONS_ISSUER
+//!     && claims.aud.contains("https://deploy.example.com")
+//!     && claims.repository == "octo-org/octo-repo"
+//!     && claims.environment.as_deref() == Some("production");
+//! ```
+//!
+//! ### Note:
+//! Every workflow on `GitHub` can request such a token, so the `aud`, and at
+//! least the `repository` (or the `repository_owner`), must always be
+//! checked.
+//!
+//! For more information, please visit: <https://docs.github.com/en/actions/security-for-github-actions/security-hardening-your-deployments/about-security-hardening-with-openid-connect>.
+
+use serde::Deserialize;
+
+use crate::key_caches::registered::Audience;
+
+/// The `iss` claim of the tokens issued to `GitHub Actions` workflows.
+pub const GITHUB_ACTIONS_ISSUER: &str =
+    "https://token.actions.githubusercontent.com";
+
+/// The URI for `GitHub Actions`' public `JWK`s.
+pub const GITHUB_ACTIONS_JWK_URI: &str =
+    "https://token.actions.githubusercontent.com/.well-known/jwks";
+
+/// Claims made by `GitHub Actions`.
+///
+/// `JWT`'s issued by `GitHub Actions` should have a body (i.e., the second
+/// portion of the `JWT`) that are `base64URL` decrypted into the below
+/// struct. Numeric ids are sent (and kept) as strings.
+#[derive(Debug, Deserialize)]
+pub struct GitHubActionsClaims {
+    pub iss: String,
+
+    /// e.g., `"repo:octo-org/octo-repo:environment:production"`.
+    pub sub: String,
+
+    /// The owner of the repository (e.g., `"https://github.com/octo-org"`),
+    /// unless a custom audience was requested.
+    pub aud: Audience,
+
+    pub iat: u64,
+    pub nbf: Option<u64>,
+    pub exp: u64,
+    pub jti: String,
+
+    /// e.g., `"octo-org/octo-repo"`.
+    pub repository: String,
+    pub repository_id: String,
+    pub repository_owner: String,
+    pub repository_owner_id: String,
+    pub repository_visibility: Option<String>,
+
+    /// e.g., `"refs/heads/main"`.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub ref_type: Option<String>,
+    pub sha: String,
+    pub head_ref: Option<String>,
+    pub base_ref: Option<String>,
+
+    /// The name of the workflow.
+    pub workflow: String,
+    pub workflow_ref: Option<String>,
+    pub workflow_sha: Option<String>,
+    pub job_workflow_ref: Option<String>,
+
+    /// Only present if the job references an environment.
+    pub environment: Option<String>,
+
+    pub event_name: String,
+    pub actor: String,
+    pub actor_id: String,
+    pub run_id: String,
+    pub run_number: String,
+    pub run_attempt: String,
+    pub runner_environment: Option<String>,
+}
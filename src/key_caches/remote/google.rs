@@ -2,35 +2,68 @@
 //!
 //! For more information, please visit: <https://cloud.google.com/api-gateway/docs/authenticating-users-jwt>.
 
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
+use serde::Deserializer;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
 
 /// The URI for `Google`'s public `JWK`s.
-pub const GOOGLE_JWK_URI: &'static str =
+pub const GOOGLE_JWK_URI: &str =
     "https://www.googleapis.com/oauth2/v2/certs";
 
 /// Claims made by `Google`.
 ///
 /// `JWT`'s issued by `Google` should have a body (i.e., the second portion of
-/// the `JWT`) that are `base64URL` decrypted into the below struct.
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Only the
+/// registered claims are guaranteed; the others depend on the requested
+/// scopes (e.g., `email`, or `profile`), and are therefore optional.
+///
+/// Any other unrecognized claim is kept inside of `extra`.
+///
+/// For more information, please visit: <https://developers.google.com/identity/openid-connect/openid-connect#an-id-tokens-payload>.
 #[derive(Debug, Deserialize)]
 pub struct GoogleClaims {
-    pub aud: String,
+    pub aud: Audience,
     pub iat: u64,
     pub exp: u64,
     pub iss: String,
-
-    pub azp: String,
     pub sub: String,
-    pub email: String,
-    pub email_verified: bool,
-    pub at_hash: String,
-    pub name: String,
-
-    #[serde(with = "http_serde::uri")]
-    pub picture: http::Uri,
-
-    pub given_name: String,
-    pub family_name: String,
-    pub locale: String,
-    pub jti: String,
+
+    pub azp: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub at_hash: Option<String>,
+    pub nonce: Option<String>,
+
+    /// The hosted domain of a `Google Workspace` user.
+    pub hd: Option<String>,
+
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_uri")]
+    pub picture: Option<http::Uri>,
+
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub locale: Option<String>,
+    pub jti: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Deserialize an optional [`http::Uri`].
+fn deserialize_uri<'de, D>(
+    deserializer: D,
+) -> Result<Option<http::Uri>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Uri(#[serde(with = "http_serde::uri")] http::Uri);
+
+    Ok(Option::<Uri>::deserialize(deserializer)?.map(|Uri(uri)| uri))
 }
@@ -0,0 +1,273 @@
+//! Conversions between [`Key`]s and the `JWK` types of [`jsonwebtoken`].
+//!
+//! Applications which already hold a [`JwkSet`] (e.g., one parsed by another
+//! library, or loaded by a configuration system) can build a
+//! [`RemoteCache`] out of it directly, without re-serializing it through
+//! `JSON`:
+//!
+//! ```ignore
+//! let jwk_set: JwkSet = config.get("jwks")?;
+//!
+//! let remote_cache = RemoteCache::from_jwk_set(&jwk_set);
+//! let jwk_set: JwkSet = remote_cache.export_jwk_set();
+//! ```
+//!
+//! Single keys are converted using [`TryFrom`], which fails with
+//! [`Error::invalid_jwk`] if the key cannot be represented on the other side
+//! (e.g., a [`Jwk`] without a `use`, which [`Key`] requires, or a [`Key`] of
+//! a `kty` which [`jsonwebtoken`] does not support). A [`Jwk`] without a
+//! `kid` is converted into a [`Key`] with an empty `kid` (see [`Key::kid`]).
+//!
+//! [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+
+use jsonwebtoken::jwk::AlgorithmParameters;
+use jsonwebtoken::jwk::CommonParameters;
+use jsonwebtoken::jwk::EllipticCurve;
+use jsonwebtoken::jwk::EllipticCurveKeyParameters;
+use jsonwebtoken::jwk::EllipticCurveKeyType;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::jwk::KeyOperations;
+use jsonwebtoken::jwk::OctetKeyPairParameters;
+use jsonwebtoken::jwk::OctetKeyPairType;
+use jsonwebtoken::jwk::PublicKeyUse;
+use jsonwebtoken::jwk::RSAKeyParameters;
+use jsonwebtoken::jwk::RSAKeyType;
+
+use crate::error::Error;
+use crate::key_caches::remote::key::Curve;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyOperation;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+
+fn invalid<M>(message: M) -> Error
+where
+    String: From<M>,
+{
+    Error::invalid_jwk {
+        message: message.into(),
+    }
+}
+
+impl TryFrom<Jwk> for Key {
+    type Error = Error;
+
+    fn try_from(jwk: Jwk) -> Result<Self, Self::Error> {
+        let Jwk { common, algorithm } = jwk;
+        let CommonParameters {
+            public_key_use,
+            key_operations,
+            algorithm: alg,
+            key_id,
+            x509_url,
+            x509_chain,
+            x509_sha1_fingerprint,
+            x509_sha256_fingerprint,
+        } = common;
+
+        let kid = key_id.unwrap_or_default();
+        let r#use = match public_key_use {
+            Some(PublicKeyUse::Signature) => Use::sig,
+            Some(PublicKeyUse::Encryption) => Use::enc,
+            Some(PublicKeyUse::Other(other)) => Use::Other(other),
+            None => return Err(invalid("The key has no `use`.")),
+        };
+        let key_ops = key_operations.map(|key_operations| {
+            key_operations.into_iter().map(KeyOperation::from).collect()
+        });
+
+        let mut key = Key {
+            e: String::new(),
+            kty: KeyType::RSA,
+            alg,
+            n: String::new(),
+            kid,
+            r#use,
+            key_ops,
+            x5u: x509_url,
+            x5c: x509_chain,
+            x5t: x509_sha1_fingerprint,
+            x5t_s256: x509_sha256_fingerprint,
+            crv: None,
+            x: None,
+            y: None,
+        };
+
+        match algorithm {
+            AlgorithmParameters::RSA(RSAKeyParameters { n, e, .. }) => {
+                key.n = n;
+                key.e = e;
+            },
+            AlgorithmParameters::EllipticCurve(
+                EllipticCurveKeyParameters { curve, x, y, .. },
+            ) => {
+                key.kty = KeyType::EC;
+                key.crv = Some(curve.into());
+                key.x = Some(x);
+                key.y = Some(y);
+            },
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                curve,
+                x,
+                ..
+            }) => {
+                key.kty = KeyType::OKP;
+                key.crv = Some(curve.into());
+                key.x = Some(x);
+            },
+            AlgorithmParameters::OctetKey(_) => {
+                return Err(invalid("Symmetric keys are not supported."))
+            },
+        };
+
+        Ok(key)
+    }
+}
+
+impl TryFrom<Key> for Jwk {
+    type Error = Error;
+
+    fn try_from(key: Key) -> Result<Self, Self::Error> {
+        let Key {
+            e,
+            kty,
+            alg,
+            n,
+            kid,
+            r#use,
+            key_ops,
+            x5u,
+            x5c,
+            x5t,
+            x5t_s256,
+            crv,
+            x,
+            y,
+        } = key;
+
+        let missing = |member| invalid(format!("The key has no `{}`.", member));
+
+        let algorithm = match kty {
+            KeyType::RSA => AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n,
+                e,
+            }),
+            KeyType::EC => {
+                AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                    key_type: EllipticCurveKeyType::EC,
+                    curve: crv.ok_or_else(|| missing("crv"))?.try_into()?,
+                    x: x.ok_or_else(|| missing("x"))?,
+                    y: y.ok_or_else(|| missing("y"))?,
+                })
+            },
+            KeyType::OKP => {
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: crv.ok_or_else(|| missing("crv"))?.try_into()?,
+                    x: x.ok_or_else(|| missing("x"))?,
+                })
+            },
+            kty => {
+                return Err(invalid(format!(
+                    "The `{}` key-type is not supported.",
+                    kty.as_str(),
+                )))
+            },
+        };
+
+        let public_key_use = match r#use {
+            Use::sig => PublicKeyUse::Signature,
+            Use::enc => PublicKeyUse::Encryption,
+            Use::Other(other) => PublicKeyUse::Other(other),
+        };
+        let key_operations = key_ops.map(|key_ops| {
+            key_ops.into_iter().map(KeyOperations::from).collect()
+        });
+
+        let common = CommonParameters {
+            public_key_use: Some(public_key_use),
+            key_operations,
+            algorithm: alg,
+            key_id: (!kid.is_empty()).then_some(kid),
+            x509_url: x5u,
+            x509_chain: x5c,
+            x509_sha1_fingerprint: x5t,
+            x509_sha256_fingerprint: x5t_s256,
+        };
+
+        Ok(Jwk { common, algorithm })
+    }
+}
+
+impl From<KeyOperations> for KeyOperation {
+    fn from(key_operations: KeyOperations) -> Self {
+        match key_operations {
+            KeyOperations::Sign => Self::Sign,
+            KeyOperations::Verify => Self::Verify,
+            KeyOperations::Encrypt => Self::Encrypt,
+            KeyOperations::Decrypt => Self::Decrypt,
+            KeyOperations::WrapKey => Self::WrapKey,
+            KeyOperations::UnwrapKey => Self::UnwrapKey,
+            KeyOperations::DeriveKey => Self::DeriveKey,
+            KeyOperations::DeriveBits => Self::DeriveBits,
+            KeyOperations::Other(other) => Self::from(other),
+        }
+    }
+}
+
+impl From<KeyOperation> for KeyOperations {
+    fn from(key_operation: KeyOperation) -> Self {
+        match key_operation {
+            KeyOperation::Sign => Self::Sign,
+            KeyOperation::Verify => Self::Verify,
+            KeyOperation::Encrypt => Self::Encrypt,
+            KeyOperation::Decrypt => Self::Decrypt,
+            KeyOperation::WrapKey => Self::WrapKey,
+            KeyOperation::UnwrapKey => Self::UnwrapKey,
+            KeyOperation::DeriveKey => Self::DeriveKey,
+            KeyOperation::DeriveBits => Self::DeriveBits,
+            KeyOperation::Other(other) => Self::Other(other),
+        }
+    }
+}
+
+impl From<EllipticCurve> for Curve {
+    fn from(curve: EllipticCurve) -> Self {
+        match curve {
+            EllipticCurve::P256 => Self::P256,
+            EllipticCurve::P384 => Self::P384,
+            EllipticCurve::P521 => Self::P521,
+            EllipticCurve::Ed25519 => Self::Ed25519,
+        }
+    }
+}
+
+impl TryFrom<Curve> for EllipticCurve {
+    type Error = Error;
+
+    fn try_from(curve: Curve) -> Result<Self, Self::Error> {
+        match curve {
+            Curve::P256 => Ok(Self::P256),
+            Curve::P384 => Ok(Self::P384),
+            Curve::P521 => Ok(Self::P521),
+            Curve::Ed25519 => Ok(Self::Ed25519),
+            curve => Err(invalid(format!(
+                "The `{}` curve is not supported.",
+                curve.as_str(),
+            ))),
+        }
+    }
+}
+
+/// Convert the keys of the given [`JwkSet`], skipping those which cannot be
+/// represented as a [`Key`].
+pub(crate) fn to_keys(jwk_set: &JwkSet) -> Vec<Key> {
+    jwk_set
+        .keys
+        .iter()
+        .cloned()
+        .filter_map(|jwk| Key::try_from(jwk).ok())
+        .collect()
+}
@@ -0,0 +1,55 @@
+//! Serializable exports of the [`Key`]s held inside of a
+//! [`super::RemoteCache`].
+//!
+//! Exports are *always* ordered by `kid`.
+//! This means that exporting the same set of keys twice will produce the exact
+//! same output, regardless of the order in which the `OAuth2` provider returned
+//! them. Diffing two exports (or two cache snapshots) will therefore only show
+//! the keys that were actually rotated.
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::new(GOOGLE_JWK_URI)?;
+//! remote_cache.refresh().await?;
+//!
+//! // `{"keys":[...]}`, ordered by `kid`.
+//! let key_set: KeySet = remote_cache.export();
+//!
+//! // `{"nbf":...,"exp":...,"keys":[...]}`, also ordered by `kid`.
+//! let now = Utc::now().timestamp() as u64;
+//! let stamped: Stamped<KeySet> = remote_cache.export_stamped(Some(now));
+//! ```
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::key_caches::remote::key::Key;
+
+/// A `JWK` set, as according to [RFC7517, Section 5](https://datatracker.ietf.org/doc/html/rfc7517#section-5).
+///
+/// When produced by [`super::RemoteCache::export`], the `keys` are ordered by
+/// their `kid`.
+#[derive(Clone, Hash, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeySet {
+    pub keys: Vec<Key>,
+}
+
+/// Wraps some exported data with (optional) validity metadata.
+///
+/// The names of the fields mirror the `nbf` and `exp` claims of a `JWT`.
+/// Namely, `nbf` is the time (in Unix-Time) before which the data should not be
+/// considered valid, and `exp` is the time (in Unix-Time) after which the data
+/// should no longer be considered valid.
+///
+/// Both fields are omitted from the serialized output if they are [`None`].
+/// The wrapped data is flattened into the same object.
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Stamped<T> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+
+    #[serde(flatten)]
+    pub data: T,
+}
@@ -1,4 +1,4 @@
-//! An incomplete representations of `JWK`'s, as according to [RFC7517](https://datatracker.ietf.org/doc/html/rfc7517).
+//! A representation of `JWK`'s, as according to [RFC7517](https://datatracker.ietf.org/doc/html/rfc7517).
 //!
 //! `JWK`'s are used to validate `JWT`'s that are sent by some client.
 //!
@@ -7,10 +7,10 @@
 //! indeed, sign that token. This means that the data contained inside can be
 //! trusted as having being provisioned by the `OAuth2` provider.
 //!
-//! Note that [`Key`] is not a *COMPLETE* representation of a `JWK` as according
-//! to the RFC. Namely, we expect the fetched keys to have the fields `e` and
-//! `n`, which specifically correspond to the `RS256` encryption/decryption
-//! algorithms.
+//! Note that, while [`Key`] represents every member defined by the RFC (along
+//! with the `EC` and `OKP` parameters), the [`super::RemoteCache`] only ever
+//! uses the fields `e` and `n`, which specifically correspond to the `RS256`
+//! encryption/decryption algorithms.
 //!
 //! This means that the [`super::RemoteCache`] does not support other algorithms
 //! for `OAuth2`.
@@ -49,76 +49,340 @@
 //!     r#use: Use::sig,
 //!     e: "AQAB".into(),
 //!     kty: KeyType::RSA,
+//!     key_ops: None,
+//!     x5u: None,
+//!     x5c: None,
+//!     x5t: None,
+//!     x5t_s256: None,
+//!     crv: None,
+//!     x: None,
+//!     y: None,
 //! };
 //! ```
 //!
 //! You can take a look for yourself by visiting
 //! <https://www.googleapis.com/oauth2/v2/certs>.
 
+use std::collections::BTreeSet;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use jsonwebtoken::Algorithm;
+use ring::digest::digest;
+use ring::digest::SHA256;
 use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::key_caches::remote::x509::leaf_public_key;
+use crate::key_caches::remote::x509::PublicKey;
+use crate::prelude;
 
-/// An incomplete representation of a `JWK`.
+/// A representation of a `JWK`.
 ///
-/// This representation is incomplete.
-/// Namely, there exist fields in the RFC which are not present in [`Key`].
-/// Furthermore, [`Key`] requires that certain fields be mandatory whereas the
-/// RFC requires them to be optional.
+/// [`Key`] requires that certain fields be mandatory (i.e., `kty` and `use`)
+/// whereas the RFC requires them to be optional.
 /// This is because this representation of [`Key`] has been fine-tuned to
 /// specifically work for `OAuth2` `JWT`s.
 ///
 /// However, with that being said, all the fields in [`Key`] are as stated by
-/// the RFC.
+/// the RFC. Every other member is optional (and omitted from the serialized
+/// output if absent), so that keys of providers which do not set them are
+/// still accepted.
 ///
 /// This is a reasonable restriction since most `OAuth2` service providers use
 /// `RSA` encryption using an exponent (i.e., the `e` field) and a modulus
 /// (i.e., the `n` field).
-#[derive(Hash, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Key {
     #[serde(default)]
     pub e: String,
     pub kty: KeyType,
+
+    /// The algorithm that this [`Key`] is intended to be used with.
+    ///
+    /// Some providers omit it, in which case the algorithm is taken from the
+    /// header of each token instead.
     pub alg: Option<Algorithm>,
     #[serde(default)]
     pub n: String,
+
+    /// The Key-ID of this [`Key`].
+    ///
+    /// Some providers omit it (e.g., when publishing a single key), in which
+    /// case it is left empty, and the [`super::RemoteCache`] indexes the key
+    /// by its [`thumbprint_sha256`](`Key::thumbprint_sha256`) instead.
+    #[serde(default)]
     pub kid: String,
     pub r#use: Use,
+
+    /// The operations that this [`Key`] is intended to be used for.
+    ///
+    /// See [RFC7517, Section 4.3](https://datatracker.ietf.org/doc/html/rfc7517#section-4.3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<BTreeSet<KeyOperation>>,
+
+    /// A `uri` pointing to the `X.509` certificate (chain) of this [`Key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x5u: Option<String>,
+
+    /// The (`base64` encoded, `DER`) `X.509` certificate chain of this
+    /// [`Key`], starting with the certificate containing it.
+    ///
+    /// Keys which omit `n` and `e` are completed from it (see
+    /// [`Key::complete_from_x5c`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+
+    /// The (`base64URL` encoded) `SHA-1` thumbprint of the `X.509`
+    /// certificate of this [`Key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+
+    /// The (`base64URL` encoded) `SHA-256` thumbprint of the `X.509`
+    /// certificate of this [`Key`].
+    #[serde(
+        default,
+        rename = "x5t#S256",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub x5t_s256: Option<String>,
+
+    /// The curve of an `EC` or `OKP` [`Key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<Curve>,
+
+    /// The (`base64URL` encoded) `x` coordinate of an `EC` [`Key`], or the
+    /// public key of an `OKP` [`Key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+
+    /// The (`base64URL` encoded) `y` coordinate of an `EC` [`Key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+impl Key {
+    /// Check to see if this [`Key`] may be used for the given operation.
+    ///
+    /// Keys without a `key_ops` member may be used for every operation.
+    pub fn allows(&self, operation: &KeyOperation) -> bool {
+        self.key_ops
+            .as_ref()
+            .is_none_or(|key_ops| key_ops.contains(operation))
+    }
+
+    /// Compute the (`base64URL` encoded) `SHA-256` thumbprint of this
+    /// [`Key`], as according to
+    /// [RFC7638](https://datatracker.ietf.org/doc/html/rfc7638).
+    ///
+    /// The thumbprint only covers the required public key parameters of the
+    /// `kty` (e.g., `e`, `kty`, and `n` for `RSA`), so it stays the same
+    /// regardless of the other members (e.g., the `kid`).
+    ///
+    /// Fails with [`Error::invalid_jwk`] if one of those parameters is
+    /// missing, or if the `kty` is neither `RSA`, `EC`, nor `OKP`.
+    pub fn thumbprint_sha256(&self) -> prelude::Result<String> {
+        let Self {
+            e,
+            kty,
+            n,
+            crv,
+            x,
+            y,
+            ..
+        } = self;
+
+        let crv = crv.as_ref().map(Curve::as_str);
+
+        // The members are ordered lexicographically, as the RFC requires.
+        let members = match kty {
+            KeyType::RSA => vec![
+                ("e", required(Some(e), "e")?),
+                ("kty", kty.as_str()),
+                ("n", required(Some(n), "n")?),
+            ],
+            KeyType::EC => vec![
+                ("crv", required(crv, "crv")?),
+                ("kty", kty.as_str()),
+                ("x", required(x.as_deref(), "x")?),
+                ("y", required(y.as_deref(), "y")?),
+            ],
+            KeyType::OKP => vec![
+                ("crv", required(crv, "crv")?),
+                ("kty", kty.as_str()),
+                ("x", required(x.as_deref(), "x")?),
+            ],
+            kty => {
+                return Err(Error::invalid_jwk {
+                    message: format!(
+                        "The `{}` key-type has no thumbprint.",
+                        kty.as_str(),
+                    ),
+                })
+            },
+        };
+
+        let members = members
+            .into_iter()
+            .map(|(member, value)| {
+                format!("\"{}\":{}", member, Value::from(value))
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let thumbprint =
+            digest(&SHA256, format!("{{{}}}", members).as_bytes());
+
+        Ok(URL_SAFE_NO_PAD.encode(thumbprint))
+    }
+
+    /// Fill in the missing public key parameters of this [`Key`] (i.e., `n`
+    /// and `e`, or `crv`, `x`, and `y`) from the leaf certificate of its
+    /// `x5c` chain.
+    ///
+    /// Parameters which are already present are kept as they are.
+    /// Fails with [`Error::invalid_certificate`] if there is no chain, if its
+    /// leaf certificate cannot be read, or if it holds a key of a different
+    /// `kty`.
+    ///
+    /// ### Note:
+    /// A [`super::RemoteCache`] only verifies tokens with `RSA` keys, so only
+    /// `RSA` certificates are usable by it. `EC` parameters are read (e.g., for
+    /// the [`thumbprint_sha256`](`Key::thumbprint_sha256`)), but such keys are
+    /// still skipped when caching.
+    ///
+    /// See [`x509`](`crate::key_caches::remote::x509`).
+    pub fn complete_from_x5c(&mut self) -> prelude::Result<()> {
+        let x5c = self.x5c.as_deref().ok_or(Error::invalid_certificate {
+            message: "The key has no `x5c` member.".into(),
+        })?;
+
+        match (leaf_public_key(x5c)?, &self.kty) {
+            (PublicKey::Rsa { n, e }, KeyType::RSA) => {
+                if self.n.is_empty() {
+                    self.n = n;
+                };
+
+                if self.e.is_empty() {
+                    self.e = e;
+                };
+            },
+            (PublicKey::Ec { crv, x, y }, KeyType::EC) => {
+                self.crv = self.crv.take().or(Some(crv));
+                self.x = self.x.take().or(Some(x));
+                self.y = self.y.take().or(Some(y));
+            },
+            (_, kty) => {
+                return Err(Error::invalid_certificate {
+                    message: format!(
+                        "The certificate does not hold a `{}` key.",
+                        kty.as_str(),
+                    ),
+                })
+            },
+        };
+
+        Ok(())
+    }
+}
+
+/// The given (non-empty) public key parameter of a [`Key`], or an
+/// [`Error::invalid_jwk`] naming the missing member.
+fn required<'a>(
+    value: Option<&'a str>,
+    member: &str,
+) -> prelude::Result<&'a str> {
+    value
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| Error::invalid_jwk {
+            message: format!("The key has no `{}`.", member),
+        })
 }
 
 /// All possible key-types as stated by the RFC.
 ///
 /// This enumeration is fully complete.
 ///
-/// Namely, all variants declared in this enum are mentioned in the RFC and all
-/// variants mentioned in the RFC are declared in this enum.
+/// Namely, all variants declared in this enum are mentioned in the RFC (or in
+/// [RFC8037](https://datatracker.ietf.org/doc/html/rfc8037), for `OKP`) and
+/// all variants mentioned in the RFC are declared in this enum. Any other
+/// key-type (e.g., a vendor-specific one) is kept as [`KeyType::Other`], so
+/// that parsing a key never fails because of its key-type.
 ///
 /// Note that [`super::RemoteCache`] still expects [`KeyType::RSA`] only.
 ///
 /// Taken from [RFC7517, Section 4.1](https://datatracker.ietf.org/doc/html/rfc7517#section-4.1).
 ///
-/// >> The "kty" (key type) parameter identifies the cryptographic algorithm
-/// family used with the key, such as "RSA" or "EC". "kty" values should either
-/// be registered in the IANA "JSON Web Key Types" registry established by
-/// [JWA](https://datatracker.ietf.org/doc/html/rfc7518) or be a value that
-/// contains a Collision- Resistant Name.
-/// The "kty" value is a case-sensitive string.
-/// This member MUST be present in a JWK.
+/// > The "kty" (key type) parameter identifies the cryptographic algorithm
+/// > family used with the key, such as "RSA" or "EC". "kty" values should either
+/// > be registered in the IANA "JSON Web Key Types" registry established by
+/// > [JWA](https://datatracker.ietf.org/doc/html/rfc7518) or be a value that
+/// > contains a Collision- Resistant Name.
+/// > The "kty" value is a case-sensitive string.
+/// > This member MUST be present in a JWK.
 ///
-/// >> A list of defined "kty" values can be found in the IANA "JSON Web Key
-/// Types" registry established by [JWA](https://datatracker.ietf.org/doc/html/rfc7518);
-/// the initial contents of this registry are the values defined in Section 6.1
-/// of [JWA](https://datatracker.ietf.org/doc/html/rfc7518).
-/// The key type definitions include specification of the members to be used for
-/// those key types. Members used with specific "kty" values can be found in the
-/// IANA "JSON Web Key Parameters" registry established by
-/// [Section 8.1](https://datatracker.ietf.org/doc/html/rfc7517#section-8.1).
-#[derive(Hash, Debug, Deserialize, PartialEq, Eq)]
+/// > A list of defined "kty" values can be found in the IANA "JSON Web Key
+/// > Types" registry established by [JWA](https://datatracker.ietf.org/doc/html/rfc7518);
+/// > the initial contents of this registry are the values defined in Section 6.1
+/// > of [JWA](https://datatracker.ietf.org/doc/html/rfc7518).
+/// > The key type definitions include specification of the members to be used for
+/// > those key types. Members used with specific "kty" values can be found in the
+/// > IANA "JSON Web Key Parameters" registry established by
+/// > [Section 8.1](https://datatracker.ietf.org/doc/html/rfc7517#section-8.1).
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
 pub enum KeyType {
     /// Indicates to use the `RSA` cryptographic family of algorithms.
     RSA,
 
     /// Indicates to use the `EC` cryptographic family of algorithms.
     EC,
+
+    /// Indicates to use symmetric algorithms (e.g., `HS256`).
+    #[allow(non_camel_case_types)]
+    oct,
+
+    /// Indicates to use the `EdDSA` (or `ECDH` over `X25519` or `X448`)
+    /// family of algorithms.
+    OKP,
+
+    /// Any other key-type.
+    Other(String),
+}
+
+impl KeyType {
+    /// The name of this key-type, as used in the `kty` member.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::RSA => "RSA",
+            Self::EC => "EC",
+            Self::oct => "oct",
+            Self::OKP => "OKP",
+            Self::Other(kty) => kty,
+        }
+    }
+}
+
+impl From<String> for KeyType {
+    fn from(kty: String) -> Self {
+        match kty.as_str() {
+            "RSA" => Self::RSA,
+            "EC" => Self::EC,
+            "oct" => Self::oct,
+            "OKP" => Self::OKP,
+            _ => Self::Other(kty),
+        }
+    }
+}
+
+impl From<KeyType> for String {
+    fn from(kty: KeyType) -> Self {
+        match kty {
+            KeyType::Other(kty) => kty,
+            kty => kty.as_str().into(),
+        }
+    }
 }
 
 /// All possible uses as stated by the RFC.
@@ -126,11 +390,14 @@ pub enum KeyType {
 /// This enumeration is fully complete.
 ///
 /// Namely, all variants declared in this enum are mentioned in the RFC and all
-/// variants mentioned in the RFC are declared in this enum.
+/// variants mentioned in the RFC are declared in this enum. Any other use
+/// (e.g., `tls`) is kept as [`Use::Other`], so that parsing a key never fails
+/// because of its use.
 ///
 /// Note that [`super::RemoteCache`] still expects [`Use::sig`] only.
 #[allow(non_camel_case_types)]
-#[derive(Hash, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
 pub enum Use {
     /// Indicates that this [`Key`] is intended to be used to encrypt data.
     enc,
@@ -138,4 +405,192 @@ pub enum Use {
     /// Indicates that this [`Key`] is intended to be used to verify the
     /// signature on data.
     sig,
+
+    /// Any other use.
+    Other(String),
+}
+
+impl Use {
+    /// The name of this use, as used in the `use` member.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::enc => "enc",
+            Self::sig => "sig",
+            Self::Other(r#use) => r#use,
+        }
+    }
+}
+
+impl From<String> for Use {
+    fn from(r#use: String) -> Self {
+        match r#use.as_str() {
+            "enc" => Self::enc,
+            "sig" => Self::sig,
+            _ => Self::Other(r#use),
+        }
+    }
+}
+
+impl From<Use> for String {
+    fn from(r#use: Use) -> Self {
+        match r#use {
+            Use::Other(r#use) => r#use,
+            r#use => r#use.as_str().into(),
+        }
+    }
+}
+
+/// All possible key operations as stated by the RFC.
+///
+/// Operations which are not mentioned in the RFC are kept as
+/// [`KeyOperation::Other`].
+///
+/// Taken from [RFC7517, Section 4.3](https://datatracker.ietf.org/doc/html/rfc7517#section-4.3).
+#[derive(
+    Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(from = "String", into = "String")]
+pub enum KeyOperation {
+    /// Compute digital signatures or `MAC`s.
+    Sign,
+
+    /// Verify digital signatures or `MAC`s.
+    Verify,
+
+    /// Encrypt content.
+    Encrypt,
+
+    /// Decrypt content and validate decryption, if applicable.
+    Decrypt,
+
+    /// Encrypt keys.
+    WrapKey,
+
+    /// Decrypt keys and validate decryption, if applicable.
+    UnwrapKey,
+
+    /// Derive keys.
+    DeriveKey,
+
+    /// Derive bits not to be used as keys.
+    DeriveBits,
+
+    /// Any other operation.
+    Other(String),
+}
+
+impl KeyOperation {
+    /// The name of this operation, as used in the `key_ops` member.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Sign => "sign",
+            Self::Verify => "verify",
+            Self::Encrypt => "encrypt",
+            Self::Decrypt => "decrypt",
+            Self::WrapKey => "wrapKey",
+            Self::UnwrapKey => "unwrapKey",
+            Self::DeriveKey => "deriveKey",
+            Self::DeriveBits => "deriveBits",
+            Self::Other(operation) => operation,
+        }
+    }
+}
+
+impl From<String> for KeyOperation {
+    fn from(operation: String) -> Self {
+        match operation.as_str() {
+            "sign" => Self::Sign,
+            "verify" => Self::Verify,
+            "encrypt" => Self::Encrypt,
+            "decrypt" => Self::Decrypt,
+            "wrapKey" => Self::WrapKey,
+            "unwrapKey" => Self::UnwrapKey,
+            "deriveKey" => Self::DeriveKey,
+            "deriveBits" => Self::DeriveBits,
+            _ => Self::Other(operation),
+        }
+    }
+}
+
+impl From<KeyOperation> for String {
+    fn from(operation: KeyOperation) -> Self {
+        match operation {
+            KeyOperation::Other(operation) => operation,
+            operation => operation.as_str().into(),
+        }
+    }
+}
+
+/// All possible curves of `EC` and `OKP` keys.
+///
+/// Curves which are not registered by
+/// [RFC7518](https://datatracker.ietf.org/doc/html/rfc7518#section-6.2.1.1)
+/// or [RFC8037](https://datatracker.ietf.org/doc/html/rfc8037#section-2) are
+/// kept as [`Curve::Other`].
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum Curve {
+    /// `P-256` (for `EC` keys).
+    P256,
+
+    /// `P-384` (for `EC` keys).
+    P384,
+
+    /// `P-521` (for `EC` keys).
+    P521,
+
+    /// `Ed25519` (for `OKP` keys).
+    Ed25519,
+
+    /// `Ed448` (for `OKP` keys).
+    Ed448,
+
+    /// `X25519` (for `OKP` keys).
+    X25519,
+
+    /// `X448` (for `OKP` keys).
+    X448,
+
+    /// Any other curve.
+    Other(String),
+}
+
+impl Curve {
+    /// The name of this curve, as used in the `crv` member.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::P256 => "P-256",
+            Self::P384 => "P-384",
+            Self::P521 => "P-521",
+            Self::Ed25519 => "Ed25519",
+            Self::Ed448 => "Ed448",
+            Self::X25519 => "X25519",
+            Self::X448 => "X448",
+            Self::Other(curve) => curve,
+        }
+    }
+}
+
+impl From<String> for Curve {
+    fn from(curve: String) -> Self {
+        match curve.as_str() {
+            "P-256" => Self::P256,
+            "P-384" => Self::P384,
+            "P-521" => Self::P521,
+            "Ed25519" => Self::Ed25519,
+            "Ed448" => Self::Ed448,
+            "X25519" => Self::X25519,
+            "X448" => Self::X448,
+            _ => Self::Other(curve),
+        }
+    }
+}
+
+impl From<Curve> for String {
+    fn from(curve: Curve) -> Self {
+        match curve {
+            Curve::Other(curve) => curve,
+            curve => curve.as_str().into(),
+        }
+    }
 }
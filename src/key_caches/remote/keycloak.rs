@@ -0,0 +1,147 @@
+//! `Keycloak` JWT Claim object.
+//!
+//! Every realm of a (self-hosted) `Keycloak` server has keys (and an issuer)
+//! of its own, located by the base `uri` of the server and the name of the
+//! realm:
+//!
+//! ```ignore
+//! let mut remote_cache =
+//!     RemoteCache::for_keycloak("https://sso.example.com", "my-realm")?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<KeycloakClaims, _>(token)?;
+//!
+//! if claims.has_client_role("my-api", "admin") {
+//!     // ...
+//! }
+//! ```
+//!
+//! For more information, please visit: <https://www.keycloak.org/securing-apps/oidc-layers>.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The `iss` claim of the tokens of the given realm of the `Keycloak` server
+/// with the given base `uri` (e.g., `"https://sso.example.com"`, or
+/// `"https://sso.example.com/auth"` for legacy deployments).
+pub fn keycloak_issuer(base_uri: &str, realm: &str) -> String {
+    format!("{}/realms/{}", base_uri.trim_end_matches('/'), realm)
+}
+
+/// The URI for the public `JWK`s of the given realm of the `Keycloak` server
+/// with the given base `uri`.
+pub fn keycloak_jwk_uri(base_uri: &str, realm: &str) -> String {
+    format!(
+        "{}/protocol/openid-connect/certs",
+        keycloak_issuer(base_uri, realm),
+    )
+}
+
+impl RemoteCache {
+    /// Generate a new [`RemoteCache`] targeting the public keys of the given
+    /// realm of the `Keycloak` server with the given base `uri`.
+    ///
+    /// The issuer and the endpoints of the realm are derived from the base
+    /// `uri` and the realm (see [`issuer`](`RemoteCache::issuer`)), so no
+    /// discovery request is made. Just like with [`RemoteCache::new`], no
+    /// keys are fetched yet.
+    ///
+    /// ### Note:
+    /// Servers which are only reachable over `http` (e.g., inside of a
+    /// cluster) need to be configured through
+    /// [`RemoteCache::builder`] (see
+    /// [`allow_insecure_http`](`crate::key_caches::remote::builder::RemoteCacheBuilder::allow_insecure_http`))
+    /// with the [`keycloak_jwk_uri`] instead.
+    pub fn for_keycloak(base_uri: &str, realm: &str) -> prelude::Result<Self> {
+        let issuer = keycloak_issuer(base_uri, realm);
+        let endpoint =
+            |name| format!("{}/protocol/openid-connect/{}", issuer, name);
+
+        Self::from_metadata(ProviderMetadata {
+            jwks_uri: endpoint("certs"),
+            token_endpoint: Some(endpoint("token")),
+            userinfo_endpoint: Some(endpoint("userinfo")),
+            id_token_signing_alg_values_supported: vec!["RS256".into()],
+            issuer,
+        })
+    }
+}
+
+/// The roles granted to a user, either by a realm or by a client.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct KeycloakRoles {
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// Claims made by `Keycloak`.
+///
+/// `JWT`'s issued by `Keycloak` should have a body (i.e., the second portion
+/// of the `JWT`) that are `base64URL` decrypted into the below struct. Both
+/// `ID` and access tokens are supported; claims which only one of them
+/// carries (or which depend on the client scopes) are optional.
+///
+/// Claims added by protocol mappers are kept inside of `extra`, along with
+/// any other unrecognized claim.
+#[derive(Debug, Deserialize)]
+pub struct KeycloakClaims {
+    pub iss: String,
+    pub sub: String,
+
+    /// Only absent from access tokens without an audience.
+    pub aud: Option<Audience>,
+
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: Option<String>,
+
+    /// The kind of the token (e.g., `"Bearer"` or `"ID"`).
+    pub typ: Option<String>,
+
+    /// The client that the token was issued to.
+    pub azp: Option<String>,
+
+    pub sid: Option<String>,
+    pub scope: Option<String>,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+
+    /// The roles granted by the realm.
+    #[serde(default)]
+    pub realm_access: KeycloakRoles,
+
+    /// The roles granted by each client, by client id.
+    #[serde(default)]
+    pub resource_access: BTreeMap<String, KeycloakRoles>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl KeycloakClaims {
+    /// Check to see if the realm granted the given role.
+    pub fn has_realm_role(&self, role: &str) -> bool {
+        self.realm_access.roles.iter().any(|granted| granted == role)
+    }
+
+    /// Check to see if the given client granted the given role.
+    pub fn has_client_role(&self, client_id: &str, role: &str) -> bool {
+        self.resource_access
+            .get(client_id)
+            .is_some_and(|KeycloakRoles { roles }| {
+                roles.iter().any(|granted| granted == role)
+            })
+    }
+}
@@ -0,0 +1,153 @@
+//! `Kubernetes` service account JWT Claim object.
+//!
+//! Projected service account tokens are signed by the `API` server of the
+//! cluster, which publishes its keys at its `OIDC` discovery endpoint. From
+//! inside of the cluster (e.g., an admission webhook, or a node agent), that
+//! endpoint is reached with the credentials mounted into every pod:
+//!
+//! ```ignore
+//! // Uses the mounted `ca.crt` and `token` of the pod's service account.
+//! let mut remote_cache = RemoteCache::for_kubernetes()?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<KubernetesClaims, _>(token)?;
+//! let KubernetesInfo { namespace, pod, .. } = claims.kubernetes;
+//! ```
+//!
+//! Clusters whose issuer is reachable from the outside can be discovered
+//! instead (see [`RemoteCache::from_issuer`]), and a `JWK` set which was
+//! mounted as a file can be loaded with [`RemoteCache::from_file`].
+//!
+//! ### Note:
+//! Only projected (i.e., bound) tokens are supported; legacy tokens stored
+//! inside of secrets carry a different (flat) set of claims.
+//!
+//! For more information, please visit: <https://kubernetes.io/docs/reference/access-authn-authz/service-accounts-admin/#bound-service-account-tokens>.
+
+use std::path::Path;
+
+use http::header::AUTHORIZATION;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::builder::RemoteCacheBuilder;
+use crate::key_caches::remote::tls::Certificate;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The URI for the public `JWK`s of the cluster, from inside of it.
+pub const KUBERNETES_JWK_URI: &str =
+    "https://kubernetes.default.svc/openid/v1/jwks";
+
+/// The directory that the credentials of a pod's service account are mounted
+/// at.
+pub const SERVICE_ACCOUNT_DIR: &str =
+    "/var/run/secrets/kubernetes.io/serviceaccount";
+
+impl RemoteCacheBuilder {
+    /// Authenticate as the service account whose credentials are mounted at
+    /// the given directory (usually, [`SERVICE_ACCOUNT_DIR`]).
+    ///
+    /// The certificate authority of the cluster (i.e., `ca.crt`) is trusted,
+    /// and its `token` is sent as a bearer token along with every request.
+    /// Both files are read right away; failing to read them is deferred
+    /// until [`build`](`RemoteCacheBuilder::build`) is called, as
+    /// [`Error::unable_to_fetch_keys`].
+    ///
+    /// ### Note:
+    /// The token is read only once. The `kubelet` rotates it before it
+    /// expires, so long-running processes should rebuild their cache (e.g.,
+    /// daily), unless the cluster extends the expiry of mounted tokens.
+    pub fn service_account<P>(mut self, dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        let read = |name| {
+            std::fs::read(dir.join(name)).map_err(|error| {
+                Error::unable_to_fetch_keys {
+                    message: error.to_string(),
+                }
+            })
+        };
+
+        match (read("ca.crt"), read("token")) {
+            (Ok(ca), Ok(token)) => {
+                let token = String::from_utf8_lossy(&token);
+                let authorization = format!("Bearer {}", token.trim());
+
+                self.add_root_certificate(Certificate::from_pem(ca))
+                    .header(AUTHORIZATION, authorization)
+            },
+            (Err(error), _) | (_, Err(error)) => {
+                self.error = self.error.or(Some(error));
+                self
+            },
+        }
+    }
+}
+
+impl RemoteCache {
+    /// Generate a new [`RemoteCache`] targeting the public keys of the
+    /// cluster that this process runs in, authenticated as the pod's service
+    /// account (see [`service_account`](`RemoteCacheBuilder::service_account`)).
+    ///
+    /// Just like with [`RemoteCache::new`], no keys are fetched yet.
+    pub fn for_kubernetes() -> prelude::Result<Self> {
+        Self::builder(KUBERNETES_JWK_URI)
+            .service_account(SERVICE_ACCOUNT_DIR)
+            .build()
+    }
+}
+
+/// A `Kubernetes` object that a token is bound to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KubernetesObject {
+    pub name: String,
+    pub uid: String,
+}
+
+/// The `kubernetes.io` claim, describing the workload of the token.
+#[derive(Debug, Deserialize)]
+pub struct KubernetesInfo {
+    pub namespace: String,
+    pub serviceaccount: KubernetesObject,
+
+    /// Only present if the token is bound to a pod.
+    pub pod: Option<KubernetesObject>,
+
+    /// Only present if the token is bound to a pod, and the cluster records
+    /// its node.
+    pub node: Option<KubernetesObject>,
+
+    /// Only present if the token is bound to a secret.
+    pub secret: Option<KubernetesObject>,
+
+    /// The time after which the `API` server warns about the use of an
+    /// extended token.
+    pub warnafter: Option<u64>,
+}
+
+/// Claims made by `Kubernetes`.
+///
+/// `JWT`'s issued by `Kubernetes` should have a body (i.e., the second
+/// portion of the `JWT`) that are `base64URL` decrypted into the below
+/// struct.
+#[derive(Debug, Deserialize)]
+pub struct KubernetesClaims {
+    pub iss: String,
+
+    /// e.g., `"system:serviceaccount:default:my-service-account"`.
+    pub sub: String,
+
+    pub aud: Audience,
+    pub iat: u64,
+    pub nbf: Option<u64>,
+    pub exp: u64,
+    pub jti: Option<String>,
+
+    #[serde(rename = "kubernetes.io")]
+    pub kubernetes: KubernetesInfo,
+}
@@ -0,0 +1,63 @@
+//! `LINE` JWT Claim object.
+//!
+//! `LINE Login` issues `ID` tokens to the apps which request the `openid`
+//! scope, signed with `ES256`:
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::new(LINE_JWK_URI)?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<LineClaims, _>(token)?;
+//!
+//! let trusted =
+//!     claims.iss == LINE_ISSUER && claims.aud.contains(channel_id);
+//! ```
+//!
+//! ### Note:
+//! Only the tokens issued to native apps (i.e., through the `LINE SDK`) are
+//! signed with these keys; the tokens issued through the web login are
+//! signed with the secret of the channel (`HS256`) instead, and are therefore
+//! not supported.
+//!
+//! For more information, please visit: <https://developers.line.biz/en/docs/line-login/verify-id-token/>.
+
+use serde::Deserialize;
+
+use crate::key_caches::registered::Audience;
+
+/// The `iss` claim of the tokens issued by `LINE`.
+pub const LINE_ISSUER: &str = "https://access.line.me";
+
+/// The URI for `LINE`'s public `JWK`s.
+pub const LINE_JWK_URI: &str = "https://api.line.me/oauth2/v2.1/certs";
+
+/// Claims made by `LINE`.
+///
+/// `JWT`'s issued by `LINE` should have a body (i.e., the second portion of
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Claims
+/// which depend on the requested scopes are optional.
+#[derive(Debug, Deserialize)]
+pub struct LineClaims {
+    pub iss: String,
+
+    /// The id of the user.
+    pub sub: String,
+
+    /// The id of the channel.
+    pub aud: Audience,
+
+    pub iat: u64,
+    pub exp: u64,
+    pub auth_time: Option<u64>,
+    pub nonce: Option<String>,
+
+    /// The methods which the user authenticated with (e.g., `"pwd"`, or
+    /// `"lineautologin"`).
+    #[serde(default)]
+    pub amr: Vec<String>,
+
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub email: Option<String>,
+}
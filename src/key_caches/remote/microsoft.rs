@@ -0,0 +1,50 @@
+//! `Microsoft` (i.e., `Microsoft Entra ID`) public keys.
+//!
+//! For more information, please visit: <https://learn.microsoft.com/en-us/entra/identity-platform/access-tokens#validate-tokens>.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
+
+/// The URI for `Microsoft`'s public `JWK`s (shared by every tenant).
+pub const MICROSOFT_JWK_URI: &str =
+    "https://login.microsoftonline.com/common/discovery/v2.0/keys";
+
+/// Claims made by `Microsoft`.
+///
+/// `JWT`'s issued by `Microsoft` should have a body (i.e., the second portion
+/// of the `JWT`) that are `base64URL` decrypted into the below struct. Claims
+/// which depend on the requested scopes (or on the kind of the token) are
+/// optional; any other one is kept inside of `extra`.
+///
+/// For more information, please visit: <https://learn.microsoft.com/en-us/entra/identity-platform/id-token-claims-reference>.
+#[derive(Debug, Deserialize)]
+pub struct MicrosoftClaims {
+    /// e.g., `"https://login.microsoftonline.com/{tid}/v2.0"`.
+    pub iss: String,
+    pub aud: Audience,
+
+    /// The (app-scoped) id of the user.
+    pub sub: String,
+
+    pub iat: u64,
+    pub nbf: Option<u64>,
+    pub exp: u64,
+    pub nonce: Option<String>,
+
+    /// The id of the tenant of the user.
+    pub tid: Option<String>,
+
+    /// The (tenant-wide) id of the user.
+    pub oid: Option<String>,
+
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
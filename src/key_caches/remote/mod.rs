@@ -44,50 +44,230 @@
 //! remote_cache.refresh().await?;
 //! ```
 //!
-//! Using your own preferred methods, you can refresh the cache intermittently.
+//! The cache can also be refreshed in the background, by spawning a task (see
+//! [`auto_refresh`]).
 //!
-//! The [`tokio`] documentation page has some pretty interesting examples on how
-//! to perform intermittent updates to shared state.
-//! You can find them [here](https://tokio.rs/tokio/tutorial/shared-state).
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use tokio::sync::RwLock;
+//! # use webcipher::prelude::AutoRefresh;
+//! # use webcipher::prelude::RefreshSchedule;
+//! # use webcipher::prelude::RemoteCache;
+//! # fn main() -> webcipher::prelude::Result<()> {
+//! # let target_uri = "https://example_target.com/certs_service";
+//! let remote_cache = Arc::new(RwLock::new(RemoteCache::new(target_uri)?));
+//!
+//! // Refreshes the cache whenever its keys are about to expire, until dropped.
+//! let handle = RemoteCache::spawn_auto_refresh(
+//!     &remote_cache,
+//!     AutoRefresh::new(RefreshSchedule::TtlBased),
+//! );
+//! # Ok(())
+//! # }
+//! ```
 //!
 //! The [`Key`] struct represents the information present inside of a `JWK`
 //! (mandatory and optional) as defined by the RFC.
 
 pub mod apple;
+pub mod auth0;
+pub mod auto_refresh;
+pub mod builder;
+pub mod cognito;
+pub mod config;
+pub mod discovery;
+pub mod failover;
+pub(crate) mod fetch;
+mod file;
+pub mod fetcher;
 pub mod facebook;
+pub mod firebase;
+pub mod github_actions;
 pub mod google;
+pub mod jwk;
+pub mod jwks;
 pub mod key;
+pub mod keycloak;
+pub mod kubernetes;
+pub mod line;
+pub mod microsoft;
+pub mod okta;
+pub mod policy;
+pub mod prewarm;
+pub mod provenance;
+pub mod rotation;
+pub mod snapshot;
+pub mod store;
+pub mod tls;
+pub mod twitch;
+pub mod well_known;
+pub mod x509;
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
+use std::cell::Cell;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::Utc;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use derivative::*;
-use hyper::Client;
-use hyper_tls::HttpsConnector;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::jwk::JwkSet;
 use jsonwebtoken::Algorithm;
 use jsonwebtoken::DecodingKey;
 use jsonwebtoken::TokenData;
+use jsonwebtoken::errors::ErrorKind;
+use serde::de::DeserializeOwned;
+use serde::de::IgnoredAny;
 use serde::Deserialize;
 use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
+use tokio::sync::SemaphorePermit;
 
 pub use self::apple::AppleClaims;
 pub use self::apple::APPLE_JWK_URI;
 pub use self::facebook::FacebookClaims;
 pub use self::facebook::FACEBOOK_JWK_URI;
+pub use self::firebase::FirebaseClaims;
+pub use self::firebase::FIREBASE_JWK_URI;
 pub use self::google::GoogleClaims;
 pub use self::google::GOOGLE_JWK_URI;
+use crate::authorization::AuthorizationHook;
+use crate::authorization::step_up::StepUpPolicy;
+use crate::authorization::RequestContext;
 use crate::error::Error;
+use crate::key_caches::check_header;
 use crate::key_caches::decrypt;
+use crate::key_caches::decrypt_borrowed;
+use crate::key_caches::header_cache::HeaderCache;
+use crate::key_caches::nonce::check_nonce;
+use crate::key_caches::projection::project;
+use crate::key_caches::replay::ReplayGuard;
+use crate::key_caches::KeyHint;
+use crate::key_caches::Selected;
+use crate::key_caches::remote::auto_refresh::AutoRefresh;
+use crate::key_caches::remote::auto_refresh::AutoRefreshHandle;
+use crate::key_caches::remote::builder::RemoteCacheBuilder;
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::failover::Failover;
+use crate::key_caches::remote::fetch::expiry_time;
+use crate::key_caches::remote::fetch::fetch_any;
+use crate::key_caches::remote::fetch::parse_keys;
+use crate::key_caches::remote::fetch::to_cache;
+use crate::key_caches::remote::fetch::to_cache_with;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::StaticFetcher;
+use crate::key_caches::remote::jwk::to_keys;
+use crate::key_caches::remote::jwks::KeySet;
+use crate::key_caches::remote::jwks::Stamped;
 use crate::key_caches::remote::key::Key;
-use crate::key_caches::remote::key::KeyType;
-use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::policy::RefreshAheadPolicy;
+use crate::key_caches::remote::policy::StalePolicy;
+use crate::key_caches::remote::prewarm::PrewarmedKeys;
+use crate::key_caches::remote::prewarm::RsaComponents;
+use crate::key_caches::remote::provenance::Provenance;
+use crate::key_caches::remote::provenance::Verified;
+use crate::key_caches::remote::provenance::VerifiedToken;
+use crate::key_caches::remote::rotation::RotationEvent;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::snapshot::SNAPSHOT_MAGIC;
+use crate::key_caches::remote::snapshot::SNAPSHOT_VERSION;
+use crate::key_caches::remote::store::read_through;
+use crate::key_caches::remote::store::write_through;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::store::ReadThrough;
+use crate::observer::CacheObserver;
 use crate::prelude;
+use crate::prelude::Timestamp;
+use crate::tasks::TaskSet;
+use crate::time::now;
+use crate::time::Clock;
+use crate::time::Expiry;
 
 type Cache = BTreeMap<String, (Key, DecodingKey)>;
 
+/// Find the key whose signature matches the given token, regardless of its
+/// `kid`.
+///
+/// Every key of the cache is tried (i.e., one `RSA` verification per key), so
+/// this is only ever done on an opt-in basis. The matching key is returned as
+/// [`verified`](`Selected::verified`), so that the signature is not verified
+/// once more.
+fn signed_by<'a>(
+    keys: &'a Cache,
+    token: &str,
+) -> Option<(&'a String, Selected<'a>)> {
+    let (message, signature) = token.rsplit_once('.')?;
+
+    keys.iter()
+        .find(|(_, (_, decoding_key))| {
+            jsonwebtoken::crypto::verify(
+                signature,
+                message.as_bytes(),
+                decoding_key,
+                Algorithm::RS256,
+            )
+            .unwrap_or(false)
+        })
+        .map(|(kid, (_, decoding_key))| {
+            let selected = Selected {
+                decoding_key,
+                verified: true,
+            };
+
+            (kid, selected)
+        })
+}
+
+/// The size (in bits) of the given (base64url encoded) `RSA` modulus, as
+/// estimated from the length of its encoding (i.e., without decoding it).
+fn modulus_bits(n: &str) -> usize {
+    n.len() * 6 / 8 * 8
+}
+
+/// The semaphore which limits the concurrent verifications of a cache to the
+/// given number, if any.
+///
+/// Limits beyond [`Semaphore::MAX_PERMITS`] are clamped to it. Fails with
+/// [`Error::invalid_verification_limit`] if the limit is zero.
+pub(crate) fn verification_limit(
+    max_concurrent_verifications: Option<usize>,
+) -> prelude::Result<Option<Arc<Semaphore>>> {
+    match max_concurrent_verifications {
+        Some(0) => Err(Error::invalid_verification_limit),
+        max => Ok(max.map(|max| {
+            Arc::new(Semaphore::new(max.min(Semaphore::MAX_PERMITS)))
+        })),
+    }
+}
+
+/// The (placeholder) `uri` of a [`RemoteCache`] built from static keys.
+///
+/// The `.invalid` top-level domain is reserved, so it never resolves.
+const STATIC_JWK_URI: &str = "https://static.webcipher.invalid/certs";
+
+/// The default size (in bits) of the `RSA` modulus of a key, from which on
+/// verifications with it are offloaded to the blocking thread pool (see
+/// [`RemoteCache::set_blocking_threshold`]).
+///
+/// Every key published by the well-known providers is at least this large.
+pub const DEFAULT_BLOCKING_THRESHOLD: usize = 2048;
+
+/// How long the asynchronous verifications wait for a permit by default,
+/// once the limit on concurrent verifications has been reached (see
+/// [`RemoteCache::set_verification_wait`]).
+pub const DEFAULT_VERIFICATION_WAIT: Duration = Duration::from_millis(100);
+
 /// A refreshable key cache for remote keys used for JWT authentication.
 ///
 /// The `URI` of the target is stored and the corresponding keys are fetched
@@ -136,6 +316,23 @@ pub struct RemoteCache {
     /// [`URI`]: https://docs.rs/http/latest/http/uri/struct.Uri.html
     pub(crate) uri: http::Uri,
 
+    /// Mirrors of the `uri`, in order of preference.
+    ///
+    /// They are only fetched from if fetching from the `uri` (and every
+    /// preceding mirror) failed.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) fallback_uris: Vec<http::Uri>,
+
+    /// The health of the `uri` and its mirrors, if a
+    /// [`FailoverPolicy`](`failover::FailoverPolicy`) has been set.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) failover: Option<Arc<Failover>>,
+
+    /// The provider configuration document, if the `uri` was discovered
+    /// (see [`discovery`]).
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) provider_metadata: Option<ProviderMetadata>,
+
     /// A mapping of `kid`s (i.e., Key-IDs) and the [`Key`] that they
     /// originated from.
     ///
@@ -147,31 +344,236 @@ pub struct RemoteCache {
     #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub(crate) keys: Cache,
 
+    /// The provenance of every key which was *not* fetched, by `kid`.
+    ///
+    /// Keys without an entry were fetched (see [`provenance`]).
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) provenance: BTreeMap<String, Provenance>,
+
     /// The maximum age the [`Key`]s in this [`RemoteCache`] will live for.
     /// When this time has expired, [`refresh`](`RemoteCache::refresh`) should
     /// be called to renew the keys.
     #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub(crate) expiry_time: Option<u64>,
+
+    /// The time (in Unix-Time) of the last successful
+    /// [`refresh`](`RemoteCache::refresh`).
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) refreshed_at: Option<u64>,
+
+    /// Whether (and for how long) expired [`Key`]s can continue to be used.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) stale_policy: Option<StalePolicy>,
+
+    /// Whether (and when) the cache should be refreshed before it expires.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) refresh_ahead_policy: Option<RefreshAheadPolicy>,
+
+    /// The configuration of the `http` client used to fetch the [`Key`]s.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) config: FetchConfig,
+
+    /// The network layer used to fetch the [`Key`]s.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) fetcher: Arc<dyn JwksFetcher>,
+
+    /// Where snapshots are shared with other caches targeting the same
+    /// `uri` (see [`store`]), if anywhere.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) cache_store: Option<Arc<dyn CacheStore>>,
+
+    /// Limits the number of verifications that can be performed concurrently
+    /// by this [`RemoteCache`].
+    ///
+    /// If [`None`], verifications are unlimited.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) verification_limit: Option<Arc<Semaphore>>,
+
+    /// How long the asynchronous verifications wait for a permit, once the
+    /// limit on concurrent verifications has been reached.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) verification_wait: Duration,
+
+    /// Whether tokens without a `kid` may be verified by the only key of this
+    /// [`RemoteCache`].
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) single_key_fallback: bool,
+
+    /// Whether tokens whose `kid` is unknown may be verified by any key of
+    /// this [`RemoteCache`].
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) try_all_keys: bool,
+
+    /// Whether the types of the registered claims of tokens are checked
+    /// before their claims are deserialized.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) strict_claims: bool,
+
+    /// Where the parsed headers of tokens are memoized (see
+    /// [`header_cache`](`crate::key_caches::header_cache`)), if anywhere.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) header_cache: Option<Arc<dyn HeaderCache>>,
+
+    /// The clock that the freshness of the keys, and the `exp` (and `nbf`) of
+    /// tokens, are checked against (see [`time`](`crate::time`)).
+    ///
+    /// If [`None`], the time of the system is used.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) clock: Option<Arc<dyn Clock>>,
+
+    /// Where verified tokens are recorded, in order to reject replayed ones
+    /// (see [`replay`](`crate::key_caches::replay`)), if anywhere.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) replay_guard: Option<ReplayGuard>,
+
+    /// The size (in bits) of the `RSA` modulus of a key, from which on the
+    /// offloaded verifications with it run on the blocking thread pool.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) blocking_threshold: usize,
+
+    /// Where hits, misses, refreshes, and failed verifications are reported
+    /// (see [`observer`](`crate::observer`)), if anywhere.
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) observer: Option<Arc<dyn CacheObserver>>,
+
+    /// Where the changes to the keys (or the expiry time) of this
+    /// [`RemoteCache`] are broadcast (see [`rotation`]).
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub(crate) rotations: broadcast::Sender<RotationEvent>,
 }
 
 impl RemoteCache {
     /// Generate a new [`RemoteCache`] by asynchronously fetching the keys at
     /// the given [`http::Uri`].
+    ///
+    /// The default `http` client configuration is used.
+    /// Use [`builder`](`RemoteCache::builder`) in order to configure it.
     pub fn new<I>(uri: I) -> prelude::Result<Self>
     where
         String: From<I>,
     {
-        let uri = String::from(uri).parse::<http::Uri>()?;
-        let keys = BTreeMap::default();
-        let expiry_time = None;
+        Self::builder(uri).build()
+    }
 
-        let store = Self {
-            uri,
-            keys,
-            expiry_time,
-        };
+    /// Discover the `JWK` `uri` of the provider with the given issuer
+    /// identifier, and generate a new [`RemoteCache`] targeting it.
+    ///
+    /// The provider configuration document is kept (see
+    /// [`provider_metadata`](`RemoteCache::provider_metadata`)), but no keys
+    /// are fetched yet. See [`discovery`] for more details.
+    pub async fn from_issuer<I>(issuer: I) -> prelude::Result<Self>
+    where
+        String: From<I>,
+    {
+        Self::builder(issuer).discover().await
+    }
+
+    /// Generate a new [`RemoteCache`] targeting the `jwks_uri` of the given
+    /// (already known) provider configuration, which is kept just as if it
+    /// had been discovered.
+    pub(crate) fn from_metadata(
+        provider_metadata: ProviderMetadata,
+    ) -> prelude::Result<Self> {
+        let mut remote_cache = Self::new(provider_metadata.jwks_uri.as_str())?;
+        remote_cache.provider_metadata = Some(provider_metadata);
+
+        Ok(remote_cache)
+    }
+
+    /// Generate a new [`RemoteCache`] containing the given keys, without
+    /// performing any network requests.
+    ///
+    /// Useful for tests, air-gapped deployments, and providers which
+    /// distribute their keys out-of-band.
+    ///
+    /// Keys which cannot be used (i.e., anything but `RS256` signing keys) are
+    /// filtered out, exactly as when fetching them. The remaining keys are
+    /// [`Pinned`](`Provenance::Pinned`).
+    ///
+    /// ### Note:
+    /// The keys never expire, and refreshing the cache always fails (leaving
+    /// the keys in place). The `uri` of the cache is a placeholder which never
+    /// resolves.
+    pub fn from_keys(keys: Vec<Key>) -> Self {
+        Self::from_cache(to_cache(keys))
+    }
+
+    /// Generate a new [`RemoteCache`] containing the keys of the given `JWK`
+    /// set (i.e., a `JSON` document of the form `{"keys":[...]}`), without
+    /// performing any network requests.
+    ///
+    /// See [`from_keys`](`RemoteCache::from_keys`).
+    pub fn from_jwks_json(jwks: &str) -> prelude::Result<Self> {
+        let keys = parse_keys(jwks.as_bytes(), JwksFormat::Jwks)?;
+
+        Ok(Self::from_cache(keys))
+    }
+
+    /// Generate a new [`RemoteCache`] containing the keys of the given
+    /// (already parsed) [`JwkSet`], without performing any network requests.
+    ///
+    /// Keys which cannot be converted into a [`Key`] (e.g., keys without a
+    /// `kid`) are filtered out, along with the keys filtered out by
+    /// [`from_keys`](`RemoteCache::from_keys`). See [`jwk`].
+    pub fn from_jwk_set(jwk_set: &JwkSet) -> Self {
+        Self::from_keys(to_keys(jwk_set))
+    }
+
+    /// Generate a new [`RemoteCache`] containing the keys of the `JWK` set
+    /// stored in the given file (e.g., a mounted `Kubernetes` config-map).
+    ///
+    /// The keys are parsed and filtered exactly as when fetching them, and the
+    /// cache targets the corresponding `file://` `uri` (which can also be
+    /// given to [`new`](`RemoteCache::new`) or
+    /// [`builder`](`RemoteCache::builder`) directly).
+    ///
+    /// ### Note:
+    /// Keys read from a file never expire. Refreshing the cache re-reads the
+    /// file, so a
+    /// [`RefreshSchedule::Interval`](`auto_refresh::RefreshSchedule::Interval`)
+    /// picks up updates to it.
+    pub fn from_file<P>(path: P) -> prelude::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let uri = file::to_uri(path.as_ref())?;
+        let mut remote_cache = Self::new(uri.to_string())?;
+
+        let (keys, max_age) =
+            file::read_blocking(&remote_cache.uri, &remote_cache.config)?;
+        remote_cache.apply(keys, max_age);
+
+        Ok(remote_cache)
+    }
+
+    // The `uri` is a constant, so building never fails.
+    #[allow(clippy::expect_used)]
+    fn from_cache(keys: Cache) -> Self {
+        let mut remote_cache = Self::builder(STATIC_JWK_URI)
+            .fetcher(StaticFetcher)
+            .build()
+            .expect("the static uri should be valid");
 
-        Ok(store)
+        remote_cache.provenance = keys
+            .keys()
+            .map(|kid| (kid.clone(), Provenance::Pinned))
+            .collect();
+        remote_cache.keys = keys;
+        remote_cache.expiry_time = Some(u64::MAX);
+        remote_cache.refreshed_at = Some(remote_cache.now());
+
+        remote_cache
+    }
+
+    /// Create a [`RemoteCacheBuilder`] targeting the given [`http::Uri`].
+    ///
+    /// The builder can be used to configure the `http` client (timeouts,
+    /// headers, redirects, etc.) before the first fetch.
+    pub fn builder<I>(uri: I) -> RemoteCacheBuilder
+    where
+        String: From<I>,
+    {
+        RemoteCacheBuilder::new(uri)
     }
 
     /// Refreshes the current [`RemoteCache`] by asynchronously fetching the
@@ -179,15 +581,331 @@ impl RemoteCache {
     ///
     /// Useful for when targets rotate their keys.
     ///
+    /// If fetching from the [`URI`] fails, each of the
+    /// [`fallback_uris`](`RemoteCache::fallback_uris`) is tried in order. If
+    /// all of them fail, the error of the last one is returned.
+    ///
+    /// If a [`FailoverPolicy`](`failover::FailoverPolicy`) has been set, the
+    /// active endpoint is tried first instead (see [`failover`]).
+    ///
+    /// If a [`CacheStore`] has been set, a fresher snapshot inside of it is
+    /// restored instead of fetching, and freshly fetched keys are saved to it
+    /// (see [`store`]).
+    ///
+    /// If a [`CacheObserver`] has been set, the refresh (and its outcome) is
+    /// reported to it (see [`observer`](`crate::observer`)). Any change to the
+    /// keys (or the expiry time) is broadcast to the subscribers of this
+    /// cache (see [`rotation`]).
+    ///
     /// [`URI`]: https://docs.rs/http/latest/http/uri/struct.Uri.html
     pub async fn refresh(&mut self) -> prelude::Result<()> {
-        let Self { uri, .. } = self;
-        let (keys, expiry_time) = fetch(uri.clone()).await?;
+        let observer = self.observer.clone();
+
+        if let Some(observer) = &observer {
+            observer.on_refresh_start(&self.uri);
+        };
+
+        // Only read if anyone is subscribed.
+        let previous = (self.rotations.receiver_count() > 0)
+            .then(|| self.rotation_state());
+
+        let refreshed = self.refresh_keys().await;
+
+        if let Some(event) = previous.and_then(|previous| {
+            RotationEvent::new(&self.uri, previous, self.rotation_state())
+        }) {
+            let _ = self.rotations.send(event);
+        };
+
+        if let Some(observer) = &observer {
+            match &refreshed {
+                Ok(()) => observer.on_refresh_success(&self.uri),
+                Err(error) => observer.on_refresh_failure(&self.uri, error),
+            };
+        };
+
+        refreshed
+    }
+
+    /// The `kid`s and the expiry time of this [`RemoteCache`], as compared by
+    /// a [`RotationEvent`].
+    fn rotation_state(&self) -> (BTreeSet<String>, Option<Expiry>) {
+        (self.keys.keys().cloned().collect(), self.expiry())
+    }
+
+    /// Subscribe to the changes to the keys (or the expiry time) of this
+    /// [`RemoteCache`], which are caused by a
+    /// [`refresh`](`RemoteCache::refresh`).
+    ///
+    /// Only the changes after subscribing are received.
+    /// See [`rotation`].
+    pub fn subscribe_rotations(&self) -> broadcast::Receiver<RotationEvent> {
+        self.rotations.subscribe()
+    }
+
+    /// Restore (or else, fetch) the keys of this [`RemoteCache`].
+    ///
+    /// See [`refresh`](`RemoteCache::refresh`).
+    async fn refresh_keys(&mut self) -> prelude::Result<()> {
+        let uris = self.uris();
+        let Self {
+            uri,
+            refreshed_at,
+            config,
+            fetcher,
+            failover,
+            cache_store,
+            ..
+        } = &*self;
+        let cache_store = cache_store.clone();
+
+        let token = match &cache_store {
+            Some(cache_store) => {
+                match read_through(cache_store.as_ref(), uri, *refreshed_at)
+                    .await
+                {
+                    ReadThrough::Restore(snapshot) => {
+                        return self.restore(*snapshot)
+                    },
+                    ReadThrough::Fetch(token) => token,
+                }
+            },
+            None => None,
+        };
+
+        let fetched =
+            fetch_any(fetcher.as_ref(), &uris, config, failover.as_deref())
+                .await
+                .map(|(keys, max_age)| self.apply(keys, max_age));
+
+        if let Some(cache_store) = &cache_store {
+            let snapshot = fetched.as_ref().ok().map(|()| self.snapshot());
+
+            write_through(cache_store.as_ref(), &self.uri, snapshot, token)
+                .await;
+        };
+
+        fetched
+    }
+
+    /// Spawn a [`tokio`] task which keeps refreshing the given shared
+    /// [`RemoteCache`] in the background.
+    ///
+    /// The task runs until the returned [`AutoRefreshHandle`] is dropped.
+    /// Failed refreshes leave the previous keys in place, and are retried
+    /// (see [`AutoRefresh`]).
+    ///
+    /// ### Note:
+    /// Must be called from within a [`tokio`] runtime.
+    pub fn spawn_auto_refresh(
+        remote_cache: &Arc<RwLock<Self>>,
+        auto_refresh: AutoRefresh,
+    ) -> AutoRefreshHandle {
+        auto_refresh::spawn(Arc::clone(remote_cache), auto_refresh)
+    }
+
+    /// Spawn a [`tokio`] task which keeps refreshing the given shared
+    /// [`RemoteCache`] in the background, owned by the given [`TaskSet`].
+    ///
+    /// Behaves like [`spawn_auto_refresh`](`RemoteCache::spawn_auto_refresh`),
+    /// except that the task runs until the [`TaskSet`] is cancelled (e.g., by
+    /// [`TaskSet::shutdown`]) or dropped.
+    ///
+    /// ### Note:
+    /// Must be called from within a [`tokio`] runtime.
+    pub fn spawn_auto_refresh_in(
+        remote_cache: &Arc<RwLock<Self>>,
+        auto_refresh: AutoRefresh,
+        tasks: &TaskSet,
+    ) {
+        auto_refresh::spawn_in(Arc::clone(remote_cache), auto_refresh, tasks)
+    }
+
+    /// The `uri`, followed by the `fallback_uris`.
+    pub(crate) fn uris(&self) -> Vec<http::Uri> {
+        let Self {
+            uri, fallback_uris, ..
+        } = self;
+
+        std::iter::once(uri)
+            .chain(fallback_uris)
+            .cloned()
+            .collect()
+    }
+
+    /// Replace the keys of this [`RemoteCache`] with freshly fetched ones,
+    /// which may be cached for the given `max-age` (if known).
+    pub(crate) fn apply(&mut self, keys: Cache, max_age: Option<u64>) {
+        let now = self.now();
+
+        self.merge(keys, BTreeMap::new());
+        self.expiry_time = max_age.map(|max_age| expiry_time(max_age, now));
+        self.refreshed_at = Some(now);
+    }
+
+    /// The current time, according to the [`Clock`] of this [`RemoteCache`]
+    /// (or else, the system).
+    pub(crate) fn now(&self) -> Timestamp {
+        self.clock.as_ref().map_or_else(now, |clock| clock.now())
+    }
+
+    /// Replace the keys inside of this [`RemoteCache`] with the given ones,
+    /// keeping the keys which were not fetched (unless replaced).
+    fn merge(
+        &mut self,
+        mut keys: Cache,
+        mut provenance: BTreeMap<String, Provenance>,
+    ) {
+        provenance.retain(|kid, _| keys.contains_key(kid));
+
+        for (kid, previous) in std::mem::take(&mut self.provenance) {
+            if keys.contains_key(&kid) {
+                continue;
+            };
+
+            if let Some(entry) = self.keys.remove(&kid) {
+                keys.insert(kid.clone(), entry);
+                provenance.insert(kid, previous);
+            };
+        }
 
         self.keys = keys;
-        self.expiry_time = expiry_time;
+        self.provenance = provenance;
+    }
 
-        Ok(())
+    /// Safely decrypt the given token, *if* the keys in this cache can still
+    /// be used.
+    ///
+    /// This behaves exactly as
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`), except that
+    /// the token is rejected with [`Error::stale_cache`] if the cache is not
+    /// usable (see [`is_cache_usable`](`RemoteCache::is_cache_usable`)).
+    pub fn decrypt<Claim, I>(&self, token: I) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: for<'a> Deserialize<'a>,
+    {
+        match self.is_cache_usable() {
+            true => self.decrypt_unchecked(token),
+            false => self.observed(Err(Error::stale_cache)),
+        }
+    }
+
+    /// Decrypt the given token, and then let the given hook decide whether the
+    /// request it was sent along with is allowed.
+    ///
+    /// The token is verified exactly as in [`decrypt`](`RemoteCache::decrypt`).
+    /// The hook is only invoked for verified tokens, and is given the claims as
+    /// a `JSON` value (before they are deserialized into `Claim`).
+    ///
+    /// ```ignore
+    /// let context = RequestContext::from_request(&request);
+    ///
+    /// let TokenData { claims, .. } = remote_cache
+    ///     .decrypt_authorized::<Claims, _, _>(token, &context, &hook)
+    ///     .await?;
+    /// ```
+    ///
+    /// ### Note:
+    /// If a limit on concurrent verifications has been set (see
+    /// [`set_max_concurrent_verifications`](`RemoteCache::set_max_concurrent_verifications`)),
+    /// and that limit has been reached, the token waits for a permit (for at
+    /// most the [`verification_wait`](`RemoteCache::verification_wait`)),
+    /// before it is rejected with [`Error::verification_overloaded`]. The
+    /// permit is released before the hook is invoked.
+    ///
+    /// See [`crate::authorization`].
+    pub async fn decrypt_authorized<Claim, I, H>(
+        &self,
+        token: I,
+        context: &RequestContext,
+        hook: &H,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: DeserializeOwned,
+        H: AuthorizationHook + ?Sized,
+    {
+        let TokenData { header, claims } = {
+            let Self {
+                verification_limit,
+                verification_wait,
+                ..
+            } = self;
+
+            let permit = Self::wait_for_permit(
+                verification_limit.clone(),
+                *verification_wait,
+            );
+            let _permit = self.observed(permit.await)?;
+
+            self.decrypt_permitted::<Value>(token.as_ref(), true)?
+        };
+
+        hook.authorize(&claims, context).await?;
+
+        let claims = serde_json::from_value(claims).map_err(|error| {
+            jsonwebtoken::errors::Error::from(ErrorKind::Json(Arc::new(error)))
+        })?;
+
+        Ok(TokenData { header, claims })
+    }
+
+    /// Decrypt the given token, and then check that it was issued for the
+    /// authentication required by the given policy.
+    ///
+    /// The token is verified exactly as in [`decrypt`](`RemoteCache::decrypt`).
+    /// Tokens which do not meet the policy are rejected with
+    /// [`Error::step_up_required`].
+    ///
+    /// See [`crate::authorization::step_up`].
+    pub fn decrypt_step_up<Claim, I>(
+        &self,
+        token: I,
+        step_up_policy: &StepUpPolicy,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: DeserializeOwned,
+    {
+        let TokenData { header, claims } = self.decrypt::<Value, _>(token)?;
+
+        step_up_policy.check(&claims)?;
+
+        let claims = serde_json::from_value(claims).map_err(|error| {
+            jsonwebtoken::errors::Error::from(ErrorKind::Json(Arc::new(error)))
+        })?;
+
+        Ok(TokenData { header, claims })
+    }
+
+    /// Decrypt the given token, and then check that it carries the given
+    /// `nonce` (i.e., the one that was sent along with the authentication
+    /// request).
+    ///
+    /// The token is verified exactly as in [`decrypt`](`RemoteCache::decrypt`).
+    /// Tokens which do not carry the `nonce` are rejected with
+    /// [`Error::nonce_mismatch`].
+    ///
+    /// See [`nonce`](`crate::key_caches::nonce`).
+    pub fn decrypt_with_nonce<Claim, I>(
+        &self,
+        token: I,
+        nonce: &str,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: DeserializeOwned,
+    {
+        let TokenData { header, claims } = self.decrypt::<Value, _>(token)?;
+
+        check_nonce(&claims, nonce)?;
+
+        let claims = serde_json::from_value(claims).map_err(|error| {
+            jsonwebtoken::errors::Error::from(ErrorKind::Json(Arc::new(error)))
+        })?;
+
+        Ok(TokenData { header, claims })
     }
 
     /// Safely decrypt the given token.
@@ -209,79 +927,660 @@ impl RemoteCache {
     ///
     /// Please check the cache is fresh by calling
     /// [`is_cache_fresh`](`RemoteCache::is_cache_fresh`).
+    ///
+    /// ### Note:
+    /// If a limit on concurrent verifications has been set (see
+    /// [`set_max_concurrent_verifications`](`RemoteCache::set_max_concurrent_verifications`)),
+    /// and that limit has been reached, the token is rejected immediately with
+    /// [`Error::verification_overloaded`] (since this function cannot wait
+    /// without blocking the current thread). The asynchronous APIs (e.g.,
+    /// [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`)) wait for a
+    /// permit instead.
+    ///
+    /// If a [`ReplayGuard`] has been set, the verified token is recorded by it
+    /// (see [`replay`](`crate::key_caches::replay`)), and rejected with
+    /// [`Error::replayed_token`] if it was already recorded.
     pub fn decrypt_unchecked<Claim, I>(
         &self,
         token: I,
     ) -> prelude::Result<TokenData<Claim>>
     where
-        String: From<I>,
+        I: AsRef<str>,
         Claim: for<'a> Deserialize<'a>,
     {
-        let Self { keys, .. } = self;
-
-        let selector = |kid: &String| {
-            keys.get(&*kid)
-                .ok_or(Error::no_corresponding_kid_in_store)
-                .map(|(_, decoding_key)| decoding_key)
-        };
-
-        decrypt(token, selector, None, true)
+        self.decrypt_tracked(token.as_ref()).map(|(token_data, _)| token_data)
     }
 
-    /// Check to see if the keys in this [`RemoteCache`] instance are fresh.
-    ///
-    /// By "fresh", we mean that the `JWK`s have not expired yet.
-    ///
-    /// ### Note
-    /// Certain `OAuth2` providers don't necessarily inform clients on how long
-    /// their `JWK`s should be cached for. For example, `Apple` provides no
-    /// information on when their public keys are going to be rotated.
-    ///
-    /// If this is the case, `expiry_time` will be set to [`None`] and
-    /// [`is_cache_fresh`](`RemoteCache::is_cache_fresh`) will always return
-    /// `false`. Therefore, you should *always* call
-    /// [`refresh`](`RemoteCache::refresh`) before decrypting using
-    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`).
-    ///
-    /// ```no_run
-    /// // Assume `target.com` provides no `cache-control` header in their `http` response.
-    /// // Therefore, it will be assumed that the cache is always stale.
-    /// let uri = "https://target.com/api/certs";
-    /// let mut remote_cache = RemoteCache::new(uri).await?;
+    /// Safely decrypt the given token (exactly as in
+    /// [`decrypt`](`RemoteCache::decrypt`)), without blocking the current
+    /// [`tokio`] worker thread on the verification of its signature.
     ///
-    /// // an arbitrary amount of time passes...
+    /// Verifying an `RSA` signature takes between ~100µs and ~1ms, during
+    /// which the worker cannot drive any other task. Tokens signed by a key
+    /// whose modulus is at least as large as the
+    /// [`blocking_threshold`](`RemoteCache::blocking_threshold`) are
+    /// therefore verified on the blocking thread pool instead (see
+    /// [`tokio::task::spawn_blocking`]), which keeps the runtime responsive
+    /// under load. Any other token is verified in place.
     ///
-    /// let token = "a.b.c";
+    /// ```ignore
+    /// let remote_cache = Arc::new(RwLock::new(remote_cache));
     ///
-    /// // Call refresh first!
-    /// remote_cache.refresh();
-    /// let TokenData { claims: MyClaims { .. }, .. } = remote_cache.decrypt_unchecked::<MyClaims, _>(token)?;
+    /// let TokenData { claims, .. } =
+    ///     RemoteCache::decrypt_offloaded::<Claims, _>(&remote_cache, token)
+    ///         .await?;
     /// ```
     ///
-    /// If you somehow know the actual expiry time of the keys, you can always
-    /// mutably set the `expiry-time` manually.
+    /// ### Note:
+    /// Offloaded tokens are copied (since the blocking thread pool requires
+    /// `'static` tasks), and their headers are decoded twice (once in order to
+    /// find their key, and once more in order to verify them), unless a
+    /// [`HeaderCache`] has been set.
     ///
-    /// ```no_run
-    /// // Once again, assume `target.com` provides no `cache-control` header in their `http` response.
-    /// // Therefore, it will be assumed that the cache is always stale.
-    /// let uri = "https://target.com/api/certs";
-    /// let mut remote_cache = RemoteCache::new(uri)?;
-    /// remote_cache.refresh().await?;
+    /// If a limit on concurrent verifications has been set (see
+    /// [`set_max_concurrent_verifications`](`RemoteCache::set_max_concurrent_verifications`)),
+    /// and that limit has been reached, the token waits for a permit (for at
+    /// most the [`verification_wait`](`RemoteCache::verification_wait`)),
+    /// before it is rejected with [`Error::verification_overloaded`].
     ///
-    /// // However, you somehow know that the keys are always rotated every 4hrs.
-    /// let now = Utc::now().timestamp() as u64;
-    /// let four_hours = 3600u64 * 4u64;
-    /// let real_expiry_time = now + four_hours;
+    /// Must be called from within a [`tokio`] runtime. If the runtime is
+    /// shutting down, offloaded tokens are rejected with
+    /// [`Error::verification_overloaded`].
+    pub async fn decrypt_offloaded<Claim, I>(
+        remote_cache: &Arc<RwLock<Self>>,
+        token: I,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: DeserializeOwned + Send + 'static,
+    {
+        Self::offload(remote_cache, token.as_ref(), true).await
+    }
+
+    /// Safely decrypt the given token (exactly as in
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`)), without
+    /// blocking the current [`tokio`] worker thread on the verification of
+    /// its signature.
     ///
-    /// let expiry_time = remote_cache.expiry_time_mut();
-    /// *expiry_time = Some(real_expiry_time);
+    /// See [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`).
+    pub async fn decrypt_unchecked_offloaded<Claim, I>(
+        remote_cache: &Arc<RwLock<Self>>,
+        token: I,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: DeserializeOwned + Send + 'static,
+    {
+        Self::offload(remote_cache, token.as_ref(), false).await
+    }
+
+    /// Decrypt the given token with the given shared [`RemoteCache`] (either
+    /// checking that its keys are usable, or not), on the blocking thread pool
+    /// if verifying it is expensive.
+    async fn offload<Claim>(
+        remote_cache: &Arc<RwLock<Self>>,
+        token: &str,
+        checked: bool,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        Claim: DeserializeOwned + Send + 'static,
+    {
+        let permit = {
+            let remote_cache = remote_cache.read().await;
+            let Self {
+                verification_limit,
+                verification_wait,
+                ..
+            } = &*remote_cache;
+
+            Self::wait_for_permit(
+                verification_limit.clone(),
+                *verification_wait,
+            )
+        };
+        let permit = permit.await;
+
+        let permit = {
+            let remote_cache = remote_cache.read().await;
+            let permit = remote_cache.observed(permit)?;
+
+            if !remote_cache.is_expensive(token) {
+                return remote_cache.decrypt_permitted(token, checked);
+            };
+
+            permit
+        };
+
+        let remote_cache = Arc::clone(remote_cache);
+        let token = token.to_owned();
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+
+            remote_cache.blocking_read().decrypt_permitted(&token, checked)
+        })
+        .await
+        .map_err(|error| match error.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(_) => Error::verification_overloaded,
+        })?
+    }
+
+    /// Safely decrypt the given token (exactly as in
+    /// [`decrypt`](`RemoteCache::decrypt`)), reporting the `kid` and the
+    /// [`Provenance`] of the key which verified it.
+    ///
+    /// See [`provenance`].
+    pub fn decrypt_with_provenance<Claim, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<Verified<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: for<'a> Deserialize<'a>,
+    {
+        if !self.is_cache_usable() {
+            return self.observed(Err(Error::stale_cache));
+        };
+
+        let (token_data, kid) = self.decrypt_tracked(token.as_ref())?;
+        let provenance = self.provenance(kid).unwrap_or(Provenance::Fetched);
+
+        Ok(Verified {
+            token_data,
+            kid: kid.clone(),
+            provenance,
+        })
+    }
+
+    /// Safely decrypt the given token (exactly as in
+    /// [`decrypt`](`RemoteCache::decrypt`)), returning the `kid` and a clone
+    /// of the [`Key`] which verified it.
+    ///
+    /// ```ignore
+    /// let VerifiedToken { token_data, kid, key } =
+    ///     remote_cache.decrypt_with_key::<Claims, _>(token)?;
+    ///
+    /// audit_log.record(&token_data.claims.sub, &kid, key.x5t_s256.as_deref());
+    /// ```
+    pub fn decrypt_with_key<Claim, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<VerifiedToken<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: for<'a> Deserialize<'a>,
+    {
+        if !self.is_cache_usable() {
+            return self.observed(Err(Error::stale_cache));
+        };
+
+        let (token_data, kid) = self.decrypt_tracked(token.as_ref())?;
+        let (key, _) = self
+            .keys
+            .get(kid)
+            .ok_or(Error::no_corresponding_kid_in_store)?;
+
+        Ok(VerifiedToken {
+            token_data,
+            key: key.clone(),
+            kid: kid.clone(),
+        })
+    }
+
+    /// Decrypt the given token (exactly as in
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`)), along with
+    /// the `kid` of the key which verified it.
+    ///
+    /// The `kid` is borrowed from the cache, so that it is never cloned unless
+    /// it is actually needed.
+    fn decrypt_tracked<Claim>(
+        &self,
+        token: &str,
+    ) -> prelude::Result<(TokenData<Claim>, &String)>
+    where
+        Claim: for<'a> Deserialize<'a>,
+    {
+        let tracked = self
+            .try_permit()
+            .and_then(|_permit| self.verify_tracked(token));
+
+        self.observed(tracked)
+    }
+
+    /// Decrypt the given token (exactly as in
+    /// [`decrypt`](`RemoteCache::decrypt`), or in
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`) if not
+    /// `checked`), once a permit to verify it has been acquired.
+    fn decrypt_permitted<Claim>(
+        &self,
+        token: &str,
+        checked: bool,
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        Claim: for<'a> Deserialize<'a>,
+    {
+        if checked && !self.is_cache_usable() {
+            return self.observed(Err(Error::stale_cache));
+        };
+
+        let verified = self
+            .verify_tracked(token)
+            .map(|(token_data, _)| token_data);
+
+        self.observed(verified)
+    }
+
+    /// Acquire a permit to verify a token (if the concurrent verifications
+    /// are limited), without waiting for one.
+    ///
+    /// Fails with [`Error::verification_overloaded`] if the limit has been
+    /// reached.
+    fn try_permit(&self) -> prelude::Result<Option<SemaphorePermit<'_>>> {
+        self.verification_limit
+            .as_ref()
+            .map(|semaphore| semaphore.try_acquire())
+            .transpose()
+            .map_err(|_| Error::verification_overloaded)
+    }
+
+    /// Acquire a permit to verify a token from the given limit (if any),
+    /// waiting for at most the given duration.
+    ///
+    /// Fails with [`Error::verification_overloaded`] if no permit was
+    /// released in time.
+    async fn wait_for_permit(
+        verification_limit: Option<Arc<Semaphore>>,
+        verification_wait: Duration,
+    ) -> prelude::Result<Option<OwnedSemaphorePermit>> {
+        let Some(semaphore) = verification_limit else {
+            return Ok(None);
+        };
+
+        tokio::time::timeout(verification_wait, semaphore.acquire_owned())
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .map(Some)
+            .ok_or(Error::verification_overloaded)
+    }
+
+    /// Decrypt the given token (exactly as in
+    /// [`decrypt_tracked`](`RemoteCache::decrypt_tracked`)), without
+    /// reporting a failure to the [`CacheObserver`] of this cache.
+    fn verify_tracked<Claim>(
+        &self,
+        token: &str,
+    ) -> prelude::Result<(TokenData<Claim>, &String)>
+    where
+        Claim: for<'a> Deserialize<'a>,
+    {
+        let Self {
+            strict_claims,
+            header_cache,
+            clock,
+            replay_guard,
+            ..
+        } = self;
+
+        let used = Cell::new(None);
+        let selector = |key_hint: &KeyHint| {
+            let (kid, selected) = self.select_observed(key_hint, token)?;
+            used.set(Some(kid));
+
+            Ok(selected)
+        };
+        let used = || used.get().ok_or(Error::no_corresponding_kid_in_store);
+
+        let Some(replay_guard) = replay_guard else {
+            let token_data = decrypt(
+                token,
+                selector,
+                None,
+                true,
+                *strict_claims,
+                header_cache.as_deref(),
+                clock.as_deref(),
+            )?;
+
+            return Ok((token_data, used()?));
+        };
+
+        let TokenData { header, claims } = decrypt::<Value, _>(
+            token,
+            selector,
+            None,
+            true,
+            *strict_claims,
+            header_cache.as_deref(),
+            clock.as_deref(),
+        )?;
+        replay_guard.check(token, &claims, self.now())?;
+
+        let claims = serde_json::from_value(claims).map_err(|error| {
+            jsonwebtoken::errors::Error::from(ErrorKind::Json(Arc::new(error)))
+        })?;
+
+        Ok((TokenData { header, claims }, used()?))
+    }
+
+    /// Find the key which signed the given token, returning its `kid` and its
+    /// [`DecodingKey`].
+    ///
+    /// Keys matched by [`try_all_keys`](`RemoteCache::try_all_keys`) have
+    /// already verified the signature of the token (see [`Selected`]).
+    ///
+    /// Tokens without a `kid` are matched by the `x5t#S256` (or else, the
+    /// `x5t`) member of each key instead. Tokens without any of them are only
+    /// matched if the [`single_key_fallback`](`RemoteCache::single_key_fallback`)
+    /// is enabled, and the cache holds exactly one key. Tokens with an unknown
+    /// `kid` are only matched if
+    /// [`try_all_keys`](`RemoteCache::try_all_keys`) is enabled.
+    fn select(
+        &self,
+        key_hint: &KeyHint,
+        token: &str,
+    ) -> prelude::Result<(&String, Selected<'_>)> {
+        let Self {
+            keys,
+            single_key_fallback,
+            try_all_keys,
+            ..
+        } = self;
+        let KeyHint { kid, x5t_s256, x5t } = key_hint;
+
+        if let (Some(kid), true) = (kid, *try_all_keys) {
+            if !keys.contains_key(*kid) {
+                return signed_by(keys, token)
+                    .ok_or(Error::no_corresponding_kid_in_store);
+            };
+        };
+
+        let entry = match (kid, x5t_s256, x5t) {
+            (Some(kid), _, _) => keys.get_key_value(*kid),
+            (None, Some(x5t_s256), _) => keys.iter().find(|(_, (key, _))| {
+                key.x5t_s256.as_deref() == Some(*x5t_s256)
+            }),
+            (None, None, Some(x5t)) => keys
+                .iter()
+                .find(|(_, (key, _))| key.x5t.as_deref() == Some(*x5t)),
+            (None, None, None) => match (single_key_fallback, keys.len()) {
+                (true, 1) => keys.iter().next(),
+                _ => return Err(Error::no_kid_present),
+            },
+        };
+
+        entry
+            .map(|(kid, (_, decoding_key))| {
+                (kid, Selected::from(decoding_key))
+            })
+            .ok_or(Error::no_corresponding_kid_in_store)
+    }
+
+    /// Find the key which signed the given token (exactly as in
+    /// [`select`](`RemoteCache::select`)), reporting whether it was found to
+    /// the [`CacheObserver`] of this cache.
+    fn select_observed(
+        &self,
+        key_hint: &KeyHint,
+        token: &str,
+    ) -> prelude::Result<(&String, Selected<'_>)> {
+        let Self { uri, observer, .. } = self;
+        let selected = self.select(key_hint, token);
+
+        if let Some(observer) = observer {
+            match &selected {
+                Ok((kid, _)) => observer.on_cache_hit(uri, kid),
+                Err(_) => observer.on_cache_miss(uri, key_hint.kid),
+            };
+        };
+
+        selected
+    }
+
+    /// Report the given result of a verification to the [`CacheObserver`] of
+    /// this cache, if it failed.
+    pub(crate) fn observed<T>(
+        &self,
+        result: prelude::Result<T>,
+    ) -> prelude::Result<T> {
+        let Self { uri, observer, .. } = self;
+
+        if let (Some(observer), Err(error)) = (observer, &result) {
+            observer.on_decrypt_failure(uri, error);
+        };
+
+        result
+    }
+
+    /// Check to see if verifying the given token is expensive enough to be
+    /// offloaded (see [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`)).
+    ///
+    /// Tokens which cannot be matched to a key are rejected before any
+    /// signature is verified, and are therefore not offloaded. Tokens whose
+    /// `kid` is unknown are always offloaded if
+    /// [`try_all_keys`](`RemoteCache::try_all_keys`) is enabled, since they
+    /// may be verified against every key.
+    fn is_expensive(&self, token: &str) -> bool {
+        let Self {
+            keys,
+            try_all_keys,
+            header_cache,
+            blocking_threshold,
+            ..
+        } = self;
+
+        let Ok(header) = check_header(token, true, header_cache.as_deref())
+        else {
+            return false;
+        };
+        let key_hint = KeyHint::from(&header);
+
+        match key_hint.kid {
+            Some(kid) if *try_all_keys && !keys.contains_key(kid) => true,
+            _ => self
+                .select(&key_hint, token)
+                .ok()
+                .and_then(|(kid, _)| keys.get(kid))
+                .is_some_and(|(Key { n, .. }, _)| {
+                    modulus_bits(n) >= *blocking_threshold
+                }),
+        }
+    }
+
+    /// Decrypt the given token, deserializing claims which borrow from the
+    /// given buffer.
+    ///
+    /// The signature (and the `exp` time) of the token are verified exactly as
+    /// in [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`). However,
+    /// the payload is decoded into `buffer`, and `Claim` may borrow from it
+    /// (e.g., `&'a str` fields). Re-using the same buffer for every token
+    /// avoids the per-token allocations of the owned claims.
+    ///
+    /// ```ignore
+    /// #[derive(Deserialize)]
+    /// struct Claims<'a> {
+    ///     sub: &'a str,
+    ///     exp: u64,
+    /// }
+    ///
+    /// let mut buffer = Vec::with_capacity(1024);
+    ///
+    /// for token in tokens {
+    ///     let Claims { sub, .. } =
+    ///         remote_cache.decrypt_borrowed::<Claims>(token, &mut buffer)?;
+    /// }
+    /// ```
+    ///
+    /// ### Note:
+    /// `&str` fields can only borrow strings which contain no escape sequences.
+    /// Use `Cow<'a, str>` for fields which may contain them.
+    pub fn decrypt_borrowed<'a, Claim>(
+        &self,
+        token: &str,
+        buffer: &'a mut Vec<u8>,
+    ) -> prelude::Result<Claim>
+    where
+        Claim: Deserialize<'a>,
+    {
+        let decrypted = self
+            .try_permit()
+            .and_then(|_permit| self.verify_borrowed(token, buffer));
+
+        self.observed(decrypted)
+    }
+
+    /// Verify the given token as in
+    /// [`decrypt_borrowed`](`RemoteCache::decrypt_borrowed`), checking it
+    /// against the [`ReplayGuard`] of the cache (if any) before deserializing
+    /// its claims.
+    fn verify_borrowed<'a, Claim>(
+        &self,
+        token: &str,
+        buffer: &'a mut Vec<u8>,
+    ) -> prelude::Result<Claim>
+    where
+        Claim: Deserialize<'a>,
+    {
+        let Self {
+            strict_claims,
+            header_cache,
+            replay_guard,
+            ..
+        } = self;
+
+        let selector = |key_hint: &KeyHint| {
+            self.select_observed(key_hint, token)
+                .map(|(_, selected)| selected)
+        };
+
+        let Some(replay_guard) = replay_guard else {
+            return decrypt_borrowed(
+                token,
+                buffer,
+                selector,
+                None,
+                *strict_claims,
+                header_cache.as_deref(),
+                self.now(),
+            );
+        };
+
+        let claims = decrypt_borrowed::<Value, _>(
+            token,
+            buffer,
+            selector,
+            None,
+            *strict_claims,
+            header_cache.as_deref(),
+            self.now(),
+        )?;
+        replay_guard.check(token, &claims, self.now())?;
+
+        // The payload is still decoded in the buffer.
+        let buffer: &'a [u8] = buffer;
+        let claims = serde_json::from_slice(buffer)
+            .map_err(jsonwebtoken::errors::Error::from)?;
+
+        Ok(claims)
+    }
+
+    /// Decrypt the given token, but only deserialize the claims located at the
+    /// given [`JSON Pointer`]s into `Claim`.
+    ///
+    /// The signature (and the `exp` time) of the token are verified exactly as
+    /// in [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`). All other
+    /// claims are skipped over while parsing, which is useful for large tokens
+    /// containing many claims (e.g., hundreds of group claims) when only a
+    /// handful of them are actually needed.
+    ///
+    /// ```ignore
+    /// #[derive(Deserialize)]
+    /// struct Subject {
+    ///     sub: String,
+    ///     exp: u64,
+    /// }
+    ///
+    /// let TokenData { claims: Subject { sub, exp }, .. } =
+    ///     remote_cache.decrypt_partial::<Subject, _, _>(token, &["/sub", "/exp"])?;
+    /// ```
+    ///
+    /// ### Warning:
+    /// The same warning as for
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`) applies.
+    ///
+    /// [`JSON Pointer`]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn decrypt_partial<Claim, I, P>(
+        &self,
+        token: I,
+        pointers: &[P],
+    ) -> prelude::Result<TokenData<Claim>>
+    where
+        I: AsRef<str>,
+        Claim: for<'a> Deserialize<'a>,
+        P: AsRef<str>,
+    {
+        let token = token.as_ref();
+        let TokenData { header, .. } =
+            self.decrypt_unchecked::<IgnoredAny, _>(token)?;
+
+        let claims = project(token, pointers)?;
+        let claims = serde_json::from_value(claims)
+            .map_err(jsonwebtoken::errors::Error::from)?;
+
+        Ok(TokenData { header, claims })
+    }
+
+    /// Check to see if the keys in this [`RemoteCache`] instance are fresh.
+    ///
+    /// By "fresh", we mean that the `JWK`s have not expired yet.
+    ///
+    /// ### Note
+    /// Certain `OAuth2` providers don't necessarily inform clients on how long
+    /// their `JWK`s should be cached for. For example, `Apple` provides no
+    /// information on when their public keys are going to be rotated.
+    ///
+    /// If this is the case, `expiry_time` will be set to [`None`] and
+    /// [`is_cache_fresh`](`RemoteCache::is_cache_fresh`) will always return
+    /// `false`. Therefore, you should *always* call
+    /// [`refresh`](`RemoteCache::refresh`) before decrypting using
+    /// [`decrypt_unchecked`](`RemoteCache::decrypt_unchecked`).
+    ///
+    /// ```no_run
+    /// // Assume `target.com` provides no `cache-control` header in their `http` response.
+    /// // Therefore, it will be assumed that the cache is always stale.
+    /// let uri = "https://target.com/api/certs";
+    /// let mut remote_cache = RemoteCache::new(uri).await?;
+    ///
+    /// // an arbitrary amount of time passes...
+    ///
+    /// let token = "a.b.c";
+    ///
+    /// // Call refresh first!
+    /// remote_cache.refresh();
+    /// let TokenData { claims: MyClaims { .. }, .. } = remote_cache.decrypt_unchecked::<MyClaims, _>(token)?;
+    /// ```
+    ///
+    /// If you somehow know the actual expiry time of the keys, you can always
+    /// set the [`Expiry`] manually.
+    ///
+    /// ```no_run
+    /// // Once again, assume `target.com` provides no `cache-control` header in their `http` response.
+    /// // Therefore, it will be assumed that the cache is always stale.
+    /// let uri = "https://target.com/api/certs";
+    /// let mut remote_cache = RemoteCache::new(uri)?;
+    /// remote_cache.refresh().await?;
+    ///
+    /// // However, you somehow know that the keys are always rotated every 4hrs.
+    /// let four_hours = Duration::from_secs(3600 * 4);
+    /// remote_cache.set_expiry(Some(Expiry::after(four_hours)));
     /// ```
     pub fn is_cache_fresh(&self) -> bool {
         let Self { expiry_time, .. } = self;
 
         expiry_time
             .map(|expiry_time| {
-                let now = Utc::now().timestamp() as u64;
+                let now = self.now();
                 let time_comparison = now.cmp(&expiry_time);
 
                 match time_comparison {
@@ -292,6 +1591,308 @@ impl RemoteCache {
             .unwrap_or(false)
     }
 
+    /// The amount of time left until the keys in this [`RemoteCache`]
+    /// instance expire.
+    ///
+    /// Returns [`None`] if no expiry time is known (e.g., the cache has never
+    /// been refreshed), and [`Duration::ZERO`] if the keys have already
+    /// expired.
+    pub fn time_until_expiry(&self) -> Option<Duration> {
+        let Self { expiry_time, .. } = self;
+
+        expiry_time.map(|expiry_time| {
+            let now = self.now();
+            Duration::from_secs(expiry_time.saturating_sub(now))
+        })
+    }
+
+    /// Check to see if this [`RemoteCache`] instance should be refreshed.
+    ///
+    /// Stale caches (see [`is_cache_fresh`](`RemoteCache::is_cache_fresh`))
+    /// always need to be refreshed. Fresh caches only need to be refreshed if
+    /// a [`RefreshAheadPolicy`] has been set, and its threshold of the keys'
+    /// lifetime has elapsed.
+    ///
+    /// ```ignore
+    /// let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+    ///     .refresh_ahead_policy(RefreshAheadPolicy { threshold: 0.8 })
+    ///     .build()?;
+    /// remote_cache.refresh().await?;
+    ///
+    /// // Later on...
+    /// if remote_cache.needs_refresh() {
+    ///     remote_cache.refresh().await?;
+    /// };
+    /// ```
+    pub fn needs_refresh(&self) -> bool {
+        self.refresh_due_in()
+            .is_none_or(|refresh_due_in| refresh_due_in.is_zero())
+    }
+
+    /// The amount of time left until this [`RemoteCache`] instance needs to be
+    /// refreshed (see [`needs_refresh`](`RemoteCache::needs_refresh`)).
+    ///
+    /// Returns [`None`] if no expiry time is known.
+    pub(crate) fn refresh_due_in(&self) -> Option<Duration> {
+        let Self {
+            expiry_time,
+            refreshed_at,
+            refresh_ahead_policy,
+            ..
+        } = self;
+
+        let expiry_time = (*expiry_time)?;
+        let refresh_at = match (refresh_ahead_policy, refreshed_at) {
+            (Some(RefreshAheadPolicy { threshold }), Some(refreshed_at)) => {
+                let lifetime = expiry_time.saturating_sub(*refreshed_at);
+                let refresh_at = *refreshed_at
+                    + (lifetime as f64 * threshold.clamp(0.0, 1.0)) as u64;
+
+                refresh_at.min(expiry_time)
+            },
+            _ => expiry_time,
+        };
+
+        let now = self.now();
+        Some(Duration::from_secs(refresh_at.saturating_sub(now)))
+    }
+
+    /// Check to see if the keys in this [`RemoteCache`] instance can be used.
+    ///
+    /// Fresh caches (see [`is_cache_fresh`](`RemoteCache::is_cache_fresh`))
+    /// can always be used. Stale caches can only be used if a [`StalePolicy`]
+    /// has been set, the cache contains keys, and the grace period has not
+    /// elapsed yet.
+    ///
+    /// This is useful in order to continue verifying tokens while the `JWK`
+    /// endpoint is temporarily down (i.e., while
+    /// [`refresh`](`RemoteCache::refresh`) keeps failing).
+    pub fn is_cache_usable(&self) -> bool {
+        let Self {
+            keys,
+            expiry_time,
+            refreshed_at,
+            stale_policy,
+            ..
+        } = self;
+
+        if self.is_cache_fresh() {
+            return true;
+        };
+
+        match (stale_policy, expiry_time.or(*refreshed_at)) {
+            (Some(StalePolicy { grace }), Some(stale_since))
+                if !keys.is_empty() =>
+            {
+                let now = self.now();
+                now < stale_since.saturating_add(grace.as_secs())
+            },
+            _ => false,
+        }
+    }
+
+    /// Export the [`Key`]s inside of this [`RemoteCache`] as a `JWK` set.
+    ///
+    /// The keys are *always* ordered by their `kid`, so exporting the same
+    /// keys will always produce the same output.
+    pub fn export(&self) -> KeySet {
+        let Self { keys, .. } = self;
+
+        let keys = keys.values().map(|(key, _)| key.clone()).collect();
+
+        KeySet { keys }
+    }
+
+    /// Export the [`Key`]s inside of this [`RemoteCache`] as a [`JwkSet`],
+    /// ordered by `kid` (see [`export`](`RemoteCache::export`)).
+    ///
+    /// See [`jwk`].
+    pub fn export_jwk_set(&self) -> JwkSet {
+        let keys = self
+            .export()
+            .keys
+            .into_iter()
+            .filter_map(|key| Jwk::try_from(key).ok())
+            .collect();
+
+        JwkSet { keys }
+    }
+
+    /// Export the [`Key`]s inside of this [`RemoteCache`] (see
+    /// [`export`](`RemoteCache::export`)), wrapped together with the given
+    /// `nbf` time and the `expiry-time` of this cache.
+    pub fn export_stamped(&self, nbf: Option<u64>) -> Stamped<KeySet> {
+        let Self { expiry_time, .. } = self;
+
+        Stamped {
+            nbf,
+            exp: *expiry_time,
+            data: self.export(),
+        }
+    }
+
+    /// Take a [`Snapshot`] of the keys inside of this [`RemoteCache`].
+    ///
+    /// See the [`snapshot`] module for how to persist it.
+    pub fn snapshot(&self) -> Snapshot {
+        let Self {
+            uri,
+            expiry_time,
+            refreshed_at,
+            provider_metadata,
+            provenance,
+            ..
+        } = self;
+
+        Snapshot {
+            magic: SNAPSHOT_MAGIC.into(),
+            version: SNAPSHOT_VERSION,
+            uri: uri.to_string(),
+            keys: self.export().keys,
+            expiry: *expiry_time,
+            fetched_at: *refreshed_at,
+            provider_metadata: provider_metadata.clone(),
+            provenance: provenance.clone(),
+        }
+    }
+
+    /// Generate a new [`RemoteCache`] from the given [`Snapshot`] (e.g., one
+    /// persisted by the previous instance of a service), so that tokens can be
+    /// verified on startup without fetching the keys first.
+    ///
+    /// The cache targets the `uri` of the snapshot, with the default
+    /// configuration; to customize it, use [`builder`](`RemoteCache::builder`)
+    /// followed by [`restore`](`RemoteCache::restore`) instead.
+    ///
+    /// ### Note:
+    /// See [`restore`](`RemoteCache::restore`) for how expired snapshots are
+    /// handled.
+    pub fn from_snapshot(snapshot: Snapshot) -> prelude::Result<Self> {
+        let mut remote_cache = Self::new(snapshot.uri.clone())?;
+        remote_cache.restore(snapshot)?;
+
+        Ok(remote_cache)
+    }
+
+    /// Replace the keys inside of this [`RemoteCache`] with the ones inside of
+    /// the given [`Snapshot`].
+    ///
+    /// Fails with [`Error::invalid_snapshot`] if the snapshot was taken of a
+    /// cache with a different `uri`.
+    ///
+    /// The provider configuration document of this cache is only replaced if
+    /// the snapshot contains one. Just like when refreshing, keys which were
+    /// not fetched are kept, unless the snapshot contains the same `kid`.
+    ///
+    /// ### Note:
+    /// The snapshot is restored as is, even if its keys have already expired;
+    /// use [`is_cache_usable`](`RemoteCache::is_cache_usable`) to check
+    /// whether the cache still needs to be refreshed.
+    pub fn restore(&mut self, snapshot: Snapshot) -> prelude::Result<()> {
+        self.restore_with(snapshot, to_cache)
+    }
+
+    /// Take a [`Snapshot`] of this [`RemoteCache`], along with the (raw)
+    /// inputs of the [`DecodingKey`] of each of its keys.
+    ///
+    /// See [`prewarm`].
+    pub fn prewarm(&self) -> PrewarmedKeys {
+        let components = self
+            .keys
+            .iter()
+            .filter_map(|(kid, (Key { n, e, .. }, _))| {
+                let n = URL_SAFE_NO_PAD.decode(n).ok()?;
+                let e = URL_SAFE_NO_PAD.decode(e).ok()?;
+
+                Some((kid.clone(), RsaComponents { n, e }))
+            })
+            .collect();
+
+        PrewarmedKeys {
+            snapshot: self.snapshot(),
+            components,
+        }
+    }
+
+    /// Generate a new [`RemoteCache`] from the given [`PrewarmedKeys`] (e.g.,
+    /// ones handed over by a supervisor process).
+    ///
+    /// The cache targets the `uri` of the snapshot, with the default
+    /// configuration; to customize it, use [`builder`](`RemoteCache::builder`)
+    /// followed by [`restore_prewarmed`](`RemoteCache::restore_prewarmed`)
+    /// instead.
+    pub fn from_prewarmed(prewarmed: PrewarmedKeys) -> prelude::Result<Self> {
+        let mut remote_cache = Self::new(prewarmed.snapshot.uri.clone())?;
+        remote_cache.restore_prewarmed(prewarmed)?;
+
+        Ok(remote_cache)
+    }
+
+    /// Replace the keys inside of this [`RemoteCache`] with the ones inside of
+    /// the given [`PrewarmedKeys`].
+    ///
+    /// This behaves exactly as [`restore`](`RemoteCache::restore`), except
+    /// that the [`DecodingKey`] of each key is built from its components as
+    /// they are. Keys without components (or whose components do not match
+    /// their `n` and `e`) are parsed as usual.
+    ///
+    /// See [`prewarm`].
+    pub fn restore_prewarmed(
+        &mut self,
+        prewarmed: PrewarmedKeys,
+    ) -> prelude::Result<()> {
+        let PrewarmedKeys {
+            snapshot,
+            components,
+        } = prewarmed;
+
+        self.restore_with(snapshot, |keys| {
+            to_cache_with(keys, |key| match components.get(&key.kid) {
+                Some(components) if components.matches(key) => {
+                    let RsaComponents { n, e } = components;
+                    Some(DecodingKey::from_rsa_raw_components(n, e))
+                },
+                _ => DecodingKey::from_rsa_components(&key.n, &key.e).ok(),
+            })
+        })
+    }
+
+    fn restore_with<F>(
+        &mut self,
+        snapshot: Snapshot,
+        to_cache: F,
+    ) -> prelude::Result<()>
+    where
+        F: FnOnce(Vec<Key>) -> Cache,
+    {
+        let Snapshot {
+            uri,
+            keys,
+            expiry,
+            fetched_at,
+            provider_metadata,
+            provenance,
+            ..
+        } = snapshot;
+
+        if uri != self.uri.to_string() {
+            return Err(Error::invalid_snapshot {
+                message: format!(
+                    "The snapshot was taken of `{}`, not of `{}`.",
+                    uri, self.uri,
+                ),
+            });
+        };
+
+        self.merge(to_cache(keys), provenance);
+        self.expiry_time = expiry;
+        self.refreshed_at = fetched_at;
+        self.provider_metadata =
+            provider_metadata.or(self.provider_metadata.take());
+
+        Ok(())
+    }
+
     /// Get an immutable reference to the inner `uri` used to locate the keys.
     pub fn uri(&self) -> &http::Uri {
         &self.uri
@@ -302,12 +1903,153 @@ impl RemoteCache {
         &mut self.uri
     }
 
+    /// The provider configuration document, if the `uri` was discovered
+    /// (see [`from_issuer`](`RemoteCache::from_issuer`)).
+    pub fn provider_metadata(&self) -> Option<&ProviderMetadata> {
+        self.provider_metadata.as_ref()
+    }
+
+    /// The issuer identifier of the provider, if the `uri` was discovered.
+    ///
+    /// Tokens issued by the provider carry it as their `iss` claim.
+    pub fn issuer(&self) -> Option<&str> {
+        self.provider_metadata()
+            .map(|ProviderMetadata { issuer, .. }| issuer.as_str())
+    }
+
+    /// The `uri` of the provider's token endpoint, if the `uri` was
+    /// discovered, and the provider has one.
+    pub fn token_endpoint(&self) -> Option<&str> {
+        self.provider_metadata()
+            .and_then(|ProviderMetadata { token_endpoint, .. }| {
+                token_endpoint.as_deref()
+            })
+    }
+
+    /// The `uri` of the provider's `UserInfo` endpoint, if the `uri` was
+    /// discovered, and the provider has one.
+    pub fn userinfo_endpoint(&self) -> Option<&str> {
+        self.provider_metadata()
+            .and_then(|ProviderMetadata { userinfo_endpoint, .. }| {
+                userinfo_endpoint.as_deref()
+            })
+    }
+
+    /// The algorithms that the provider may sign `ID` tokens with.
+    ///
+    /// Empty if the `uri` was not discovered, or the provider does not
+    /// advertise them.
+    pub fn supported_algorithms(&self) -> &[String] {
+        self.provider_metadata()
+            .map(|metadata| &metadata.id_token_signing_alg_values_supported)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Get an immutable reference to the mirrors of the `uri`, in order of
+    /// preference.
+    pub fn fallback_uris(&self) -> &[http::Uri] {
+        &self.fallback_uris
+    }
+
+    /// The endpoint that this [`RemoteCache`] currently refreshes from first.
+    ///
+    /// This is always the `uri`, unless a
+    /// [`FailoverPolicy`](`failover::FailoverPolicy`) has been set and the
+    /// cache has failed over to one of its mirrors.
+    pub fn active_uri(&self) -> &http::Uri {
+        let Self {
+            uri,
+            fallback_uris,
+            failover,
+            ..
+        } = self;
+
+        match failover.as_ref().map(|failover| failover.active()) {
+            Some(active @ 1..) => fallback_uris.get(active - 1).unwrap_or(uri),
+            _ => uri,
+        }
+    }
+
+    /// Get a mutable reference to the mirrors of the `uri`, in order of
+    /// preference.
+    pub fn fallback_uris_mut(&mut self) -> &mut Vec<http::Uri> {
+        &mut self.fallback_uris
+    }
+
+    /// Iterate over the `kid`s of the [`Key`]s inside of this cache, in
+    /// order.
+    pub fn kids(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    /// Get the [`Key`] with the given `kid` (if present).
+    pub fn key(&self, kid: &str) -> Option<&Key> {
+        self.keys.get(kid).map(|(key, _)| key)
+    }
+
+    /// Get the [`Provenance`] of the key with the given `kid` (if present).
+    pub fn provenance(&self, kid: &str) -> Option<Provenance> {
+        let Self {
+            keys, provenance, ..
+        } = self;
+
+        match keys.contains_key(kid) {
+            true => provenance.get(kid).copied().or(Some(Provenance::Fetched)),
+            false => None,
+        }
+    }
+
+    /// Insert the given key with the given [`Provenance`], replacing any key
+    /// with the same `kid`.
+    ///
+    /// Fails with [`Error::unusable_key`] if the key cannot be used (i.e., it
+    /// is not an `RS256` signing key).
+    ///
+    /// See [`provenance`] for how the key is treated by later refreshes.
+    pub fn insert_key(
+        &mut self,
+        key: Key,
+        provenance: Provenance,
+    ) -> prelude::Result<()> {
+        let (kid, entry) = to_cache([key])
+            .into_iter()
+            .next()
+            .ok_or(Error::unusable_key)?;
+
+        match provenance {
+            Provenance::Fetched => self.provenance.remove(&kid),
+            _ => self.provenance.insert(kid.clone(), provenance),
+        };
+        self.keys.insert(kid, entry);
+
+        Ok(())
+    }
+
+    /// Remove the key with the given `kid` (if present), regardless of its
+    /// [`Provenance`].
+    pub fn remove_key(&mut self, kid: &str) -> Option<Key> {
+        self.provenance.remove(kid);
+        self.keys.remove(kid).map(|(key, _)| key)
+    }
+
     /// Get an immutable reference to the inner `keys` cache-map.
+    ///
+    /// ### Note:
+    /// The type of the cache-map is an implementation detail, and is *not*
+    /// part of the stable [`crate::api`]. Prefer
+    /// [`kids`](`RemoteCache::kids`) and [`key`](`RemoteCache::key`).
+    #[doc(hidden)]
     pub fn keys(&self) -> &Cache {
         &self.keys
     }
 
     /// Get a mutable reference to the inner `keys` cache-map.
+    ///
+    /// ### Note:
+    /// The type of the cache-map is an implementation detail, and is *not*
+    /// part of the stable [`crate::api`].
+    #[doc(hidden)]
     pub fn keys_mut(&mut self) -> &mut Cache {
         &mut self.keys
     }
@@ -323,115 +2065,272 @@ impl RemoteCache {
     pub fn expiry_time_mut(&mut self) -> &mut Option<u64> {
         &mut self.expiry_time
     }
-}
 
-/// Fetches the according [`Key`]s from the given URI and computes the
-/// respective [`DecodingKey`] for each [`Key`].
-///
-/// The keys are unique by their `kid` (i.e., their Key-ID).
-/// Each JWT can be decrypted by a corresponding [`Key`] that has a matching
-/// `kid`.
-/// Therefore, the returned BTreeMap is indexed as: `kid -> Key`.
-///
-/// This function filters out all keys which don't can't be serialized into a
-/// [`Key`]. Furthermore, this function also filters out all keys whose `kty !=
-/// "RSA"`. This includes valid keys which use a different encryption mechanism.
-///
-/// This function specifically uses the
-/// [`from_rsa_components`](`DecodingKey::from_rsa_components`) function.
-/// This is because we expect that the target is using "RSA" encryption scheme.
-///
-/// The expiry time is calculated by taking the max-age (in Unix-Time) and
-/// adding it to the current time (in Unix-Time). 1hr (i.e, 3600s) are
-/// subtracted in order to provide leeway.
-async fn fetch(uri: http::Uri) -> prelude::Result<(Cache, Option<u64>)> {
-    let https = HttpsConnector::new();
-    let client = Client::builder().build::<_, hyper::Body>(https);
-    let mut response = client.get(uri).await?;
-
-    const CACHE_HEADER: &'static str = "cache-control";
-    const MAX_AGE_HEADER: &'static str = "max-age=";
-
-    let expiry_time = response
-        .headers()
-        .get(CACHE_HEADER)
-        .map(|value| {
-            value.to_str().map(|value| {
-                value
-                    .split(',')
-                    .filter_map(|segment| {
-                        let is_max_age_header =
-                            segment.contains(MAX_AGE_HEADER);
-                        match is_max_age_header {
-                            true => value
-                                .trim()
-                                .replace(MAX_AGE_HEADER, "")
-                                .parse::<u64>()
-                                .ok()
-                                .map(|max_age| {
-                                    let now = Utc::now().timestamp() as u64;
-                                    let one_hour = 3600;
-
-                                    now + max_age - one_hour
-                                }),
-                            false => None,
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .get(0)
-                    .map(u64::clone)
-            })
-        })
-        .transpose()?
-        .flatten();
-
-    let bytes = hyper::body::to_bytes(response.body_mut()).await?;
-    let bytes = bytes.as_ref();
-    let body: Value = serde_json::from_slice(bytes)?;
-    let body = body
-        .get("keys")
-        .ok_or(Error::unable_to_fetch_keys {
-            message: "No 'keys' array contained in the returned object.".into(),
-        })?
-        .clone();
-
-    let keys = serde_json::from_value::<Vec<Value>>(body)?
-        .into_iter()
-        .filter_map(|value| {
-            serde_json::from_value::<Key>(value).ok().and_then(|key| {
-                let Key {
-                    kty,
-                    alg,
-                    e,
-                    n,
-                    kid,
-                    r#use,
-                    ..
-                } = &key;
-
-                match kty {
-                    KeyType::RSA => (),
-                    _ => return None,
-                };
-
-                match alg {
-                    Some(Algorithm::RS256) => (),
-                    _ => return None,
-                };
-
-                match r#use {
-                    Use::sig => (),
-                    Use::enc => return None,
-                };
-
-                let kid = kid.clone();
-
-                DecodingKey::from_rsa_components(n, e)
-                    .ok()
-                    .map(|decoding_key| (kid, (key, decoding_key)))
-            })
-        })
-        .collect::<Cache>();
+    /// The [`Expiry`] of the keys in this cache, if known.
+    pub fn expiry(&self) -> Option<Expiry> {
+        self.expiry_time.map(Expiry::at)
+    }
+
+    /// Set (or, if [`None`], forget) the [`Expiry`] of the keys in this
+    /// cache.
+    pub fn set_expiry(&mut self, expiry: Option<Expiry>) {
+        self.expiry_time = expiry.map(Timestamp::from);
+    }
+
+    /// Move the [`Expiry`] of the keys in this cache back by the given
+    /// duration.
+    ///
+    /// Does nothing if no expiry time is known.
+    pub fn extend_expiry(&mut self, duration: Duration) {
+        let Self { expiry_time, .. } = self;
+
+        if let Some(expiry_time) = expiry_time {
+            *expiry_time = expiry_time.saturating_add(duration.as_secs());
+        };
+    }
+
+    /// Limit the number of verifications that can be performed concurrently by
+    /// this [`RemoteCache`].
+    ///
+    /// `RSA` verification is expensive. Limiting the number of concurrent
+    /// verifications prevents a burst of (potentially bad) tokens from
+    /// monopolizing the `CPU`. Once the limit has been reached:
+    /// - the asynchronous APIs (e.g.,
+    ///   [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`)) wait for a
+    ///   verification to finish (for at most the
+    ///   [`verification_wait`](`RemoteCache::verification_wait`)), and thereby
+    ///   apply backpressure to their callers.
+    /// - the synchronous APIs (e.g., [`decrypt`](`RemoteCache::decrypt`))
+    ///   cannot wait without blocking the current thread, and therefore
+    ///   reject the token immediately.
+    ///
+    /// Either way, rejected tokens fail with
+    /// [`Error::verification_overloaded`], so that callers can shed load
+    /// (e.g., by responding with a `503`).
+    ///
+    /// Passing [`None`] removes the limit. Limits beyond
+    /// [`Semaphore::MAX_PERMITS`] are clamped to it.
+    ///
+    /// Fails with [`Error::invalid_verification_limit`] if the limit is zero
+    /// (in which case the previous limit is kept).
+    pub fn set_max_concurrent_verifications(
+        &mut self,
+        max_concurrent_verifications: Option<usize>,
+    ) -> prelude::Result<()> {
+        self.verification_limit =
+            verification_limit(max_concurrent_verifications)?;
+
+        Ok(())
+    }
+
+    /// How long the asynchronous verifications of this [`RemoteCache`] wait
+    /// for a permit, once the limit on concurrent verifications has been
+    /// reached.
+    pub fn verification_wait(&self) -> Duration {
+        self.verification_wait
+    }
+
+    /// Set how long the asynchronous verifications of this [`RemoteCache`]
+    /// wait for a permit, once the limit on concurrent verifications has been
+    /// reached (by default, [`DEFAULT_VERIFICATION_WAIT`]).
+    ///
+    /// See
+    /// [`set_max_concurrent_verifications`](`RemoteCache::set_max_concurrent_verifications`).
+    pub fn set_verification_wait(&mut self, verification_wait: Duration) {
+        self.verification_wait = verification_wait;
+    }
+
+    /// Check to see if tokens without a `kid` may be verified by the only key
+    /// of this [`RemoteCache`].
+    pub fn single_key_fallback(&self) -> bool {
+        self.single_key_fallback
+    }
+
+    /// Allow (or disallow) tokens without a `kid` to be verified by the only
+    /// key of this [`RemoteCache`].
+    ///
+    /// Some (legacy) providers never set a `kid`, since they only ever have a
+    /// single active key. Without this fallback, their tokens are rejected with
+    /// [`Error::no_kid_present`].
+    ///
+    /// ### Note:
+    /// The fallback only applies while the cache holds *exactly* one key, and
+    /// only to tokens which carry neither a `kid` nor a certificate
+    /// thumbprint. Tokens are still rejected (with
+    /// [`Error::no_kid_present`]) while the provider publishes a second key
+    /// (e.g., during a rotation).
+    pub fn set_single_key_fallback(&mut self, single_key_fallback: bool) {
+        self.single_key_fallback = single_key_fallback;
+    }
+
+    /// Check to see if tokens whose `kid` is unknown may be verified by any key
+    /// of this [`RemoteCache`].
+    pub fn try_all_keys(&self) -> bool {
+        self.try_all_keys
+    }
 
-    Ok((keys, expiry_time))
+    /// Allow (or disallow) tokens whose `kid` is unknown to be verified by any
+    /// key of this [`RemoteCache`].
+    ///
+    /// Some providers re-label their keys (i.e., change the `kid`) without
+    /// rotating the underlying key material. Without this mode, tokens which
+    /// are still labeled with the previous `kid` are rejected with
+    /// [`Error::no_corresponding_kid_in_store`]. With it, such tokens are
+    /// verified against every key of the cache (all of which are `RS256`
+    /// signature keys) before giving up.
+    ///
+    /// ### Warning:
+    /// A token with an unknown `kid` then costs one `RSA` verification per
+    /// key (i.e., up to N verifications for a cache of N keys, instead of a
+    /// single one), which an attacker can trigger at will. The matching key
+    /// does not verify the signature a second time, but a token matching no
+    /// key always costs all N. Consider limiting the number of concurrent
+    /// verifications (see
+    /// [`set_max_concurrent_verifications`](`RemoteCache::set_max_concurrent_verifications`))
+    /// as well.
+    pub fn set_try_all_keys(&mut self, try_all_keys: bool) {
+        self.try_all_keys = try_all_keys;
+    }
+
+    /// Check to see if the types of the registered claims of tokens are
+    /// checked before their claims are deserialized.
+    pub fn strict_claims(&self) -> bool {
+        self.strict_claims
+    }
+
+    /// Enable (or disable) checking the types of the registered claims of
+    /// tokens (e.g., that `exp` is a number) before their claims are
+    /// deserialized.
+    ///
+    /// Without this check, a token whose `exp` is a string is rejected as if
+    /// it had no `exp` at all, and other mistyped claims fail with an opaque
+    /// `serde` error. With it, such tokens are rejected with
+    /// [`Error::invalid_registered_claim`], naming the claim and its type.
+    ///
+    /// See [`registered`](`crate::key_caches::registered`).
+    ///
+    /// ### Note:
+    /// The claims are parsed twice (once into a [`Value`], and once into the
+    /// requested type), which makes decrypting slightly slower.
+    pub fn set_strict_claims(&mut self, strict_claims: bool) {
+        self.strict_claims = strict_claims;
+    }
+
+    /// Get an immutable reference to the inner [`StalePolicy`].
+    pub fn stale_policy(&self) -> &Option<StalePolicy> {
+        &self.stale_policy
+    }
+
+    /// Get a mutable reference to the inner [`StalePolicy`].
+    pub fn stale_policy_mut(&mut self) -> &mut Option<StalePolicy> {
+        &mut self.stale_policy
+    }
+
+    /// Get an immutable reference to the inner [`RefreshAheadPolicy`].
+    pub fn refresh_ahead_policy(&self) -> &Option<RefreshAheadPolicy> {
+        &self.refresh_ahead_policy
+    }
+
+    /// Get a mutable reference to the inner [`RefreshAheadPolicy`].
+    pub fn refresh_ahead_policy_mut(
+        &mut self,
+    ) -> &mut Option<RefreshAheadPolicy> {
+        &mut self.refresh_ahead_policy
+    }
+
+    /// Get an immutable reference to the inner `http` client configuration.
+    pub fn config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    /// The [`CacheStore`] that snapshots are shared through, if any.
+    pub fn cache_store(&self) -> Option<&Arc<dyn CacheStore>> {
+        self.cache_store.as_ref()
+    }
+
+    /// Set (or unset) the [`CacheStore`] that snapshots are shared through.
+    ///
+    /// See [`store`].
+    pub fn set_cache_store(
+        &mut self,
+        cache_store: Option<Arc<dyn CacheStore>>,
+    ) {
+        self.cache_store = cache_store;
+    }
+
+    /// The [`HeaderCache`] that the parsed headers of tokens are memoized in,
+    /// if any.
+    pub fn header_cache(&self) -> Option<&Arc<dyn HeaderCache>> {
+        self.header_cache.as_ref()
+    }
+
+    /// Set (or unset) the [`HeaderCache`] that the parsed headers of tokens
+    /// are memoized in.
+    ///
+    /// See [`header_cache`](`crate::key_caches::header_cache`).
+    pub fn set_header_cache(
+        &mut self,
+        header_cache: Option<Arc<dyn HeaderCache>>,
+    ) {
+        self.header_cache = header_cache;
+    }
+
+    /// The [`Clock`] of this [`RemoteCache`], if it has one of its own.
+    pub fn clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.clock.as_ref()
+    }
+
+    /// Set (or unset) the [`Clock`] of this [`RemoteCache`].
+    ///
+    /// See [`time`](`crate::time`).
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.clock = clock;
+    }
+
+    /// The [`CacheObserver`] of this [`RemoteCache`], if any.
+    pub fn observer(&self) -> Option<&Arc<dyn CacheObserver>> {
+        self.observer.as_ref()
+    }
+
+    /// Set (or unset) the [`CacheObserver`] of this [`RemoteCache`].
+    ///
+    /// See [`observer`](`crate::observer`).
+    pub fn set_observer(&mut self, observer: Option<Arc<dyn CacheObserver>>) {
+        self.observer = observer;
+    }
+
+    /// The [`ReplayGuard`] that verified tokens are recorded by, if any.
+    pub fn replay_guard(&self) -> Option<&ReplayGuard> {
+        self.replay_guard.as_ref()
+    }
+
+    /// Set (or unset) the [`ReplayGuard`] that verified tokens are recorded
+    /// by.
+    ///
+    /// See [`replay`](`crate::key_caches::replay`).
+    pub fn set_replay_guard(&mut self, replay_guard: Option<ReplayGuard>) {
+        self.replay_guard = replay_guard;
+    }
+
+    /// The size (in bits) of the `RSA` modulus of a key, from which on the
+    /// offloaded verifications with it run on the blocking thread pool.
+    ///
+    /// See [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`).
+    pub fn blocking_threshold(&self) -> usize {
+        self.blocking_threshold
+    }
+
+    /// Set the size (in bits) of the `RSA` modulus of a key, from which on the
+    /// offloaded verifications with it run on the blocking thread pool
+    /// (by default, [`DEFAULT_BLOCKING_THRESHOLD`]).
+    ///
+    /// Handing a task over to the blocking thread pool costs a few
+    /// microseconds, which only pays off for expensive verifications. Passing
+    /// `0` offloads every verification, whereas passing [`usize::MAX`]
+    /// offloads none.
+    ///
+    /// See [`decrypt_offloaded`](`RemoteCache::decrypt_offloaded`).
+    pub fn set_blocking_threshold(&mut self, blocking_threshold: usize) {
+        self.blocking_threshold = blocking_threshold;
+    }
 }
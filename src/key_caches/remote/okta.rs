@@ -0,0 +1,109 @@
+//! `Okta` JWT Claim object.
+//!
+//! Every authorization server of an `Okta` org has keys (and an issuer) of
+//! its own, located by the domain of the org and the id of the server:
+//!
+//! ```ignore
+//! let mut remote_cache =
+//!     RemoteCache::for_okta("example.okta.com", "default")?;
+//! remote_cache.refresh().await?;
+//!
+//! assert_eq!(
+//!     remote_cache.issuer(),
+//!     Some("https://example.okta.com/oauth2/default"),
+//! );
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<OktaClaims, _>(token)?;
+//! ```
+//!
+//! For more information, please visit: <https://developer.okta.com/docs/guides/validate-access-tokens/main/>.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::discovery::origin;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The `iss` claim of the tokens of the given authorization server (e.g.,
+/// `"default"`) of the given `Okta` org (e.g., `"example.okta.com"`).
+pub fn okta_issuer(org: &str, authorization_server: &str) -> String {
+    format!("{}/oauth2/{}", origin(org), authorization_server)
+}
+
+/// The URI for the public `JWK`s of the given authorization server of the
+/// given `Okta` org.
+pub fn okta_jwk_uri(org: &str, authorization_server: &str) -> String {
+    format!("{}/v1/keys", okta_issuer(org, authorization_server))
+}
+
+impl RemoteCache {
+    /// Generate a new [`RemoteCache`] targeting the public keys of the given
+    /// authorization server (e.g., `"default"`) of the `Okta` org with the
+    /// given domain (e.g., `"example.okta.com"`, or a custom domain).
+    ///
+    /// The issuer and the endpoints of the server are derived from its org
+    /// and id (see [`issuer`](`RemoteCache::issuer`)), so no discovery request
+    /// is made. Just like with [`RemoteCache::new`], no keys are fetched yet.
+    ///
+    /// ### Note:
+    /// Tokens of the org authorization server (whose issuer is the org
+    /// itself) cannot be verified by third parties, and are therefore not
+    /// supported.
+    pub fn for_okta(
+        org: &str,
+        authorization_server: &str,
+    ) -> prelude::Result<Self> {
+        let issuer = okta_issuer(org, authorization_server);
+
+        Self::from_metadata(ProviderMetadata {
+            jwks_uri: okta_jwk_uri(org, authorization_server),
+            token_endpoint: Some(format!("{}/v1/token", issuer)),
+            userinfo_endpoint: Some(format!("{}/v1/userinfo", issuer)),
+            id_token_signing_alg_values_supported: vec!["RS256".into()],
+            issuer,
+        })
+    }
+}
+
+/// Claims made by `Okta`.
+///
+/// `JWT`'s issued by `Okta` should have a body (i.e., the second portion of
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Both `ID`
+/// and access tokens are supported; claims which only one of them carries
+/// are optional.
+///
+/// Custom claims (added by the authorization server's claim rules) are kept
+/// inside of `extra`, along with any other unrecognized claim.
+#[derive(Debug, Deserialize)]
+pub struct OktaClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: Audience,
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: Option<String>,
+    pub auth_time: Option<u64>,
+
+    /// The client id of an access token.
+    pub cid: Option<String>,
+
+    /// The id of the user of an access token.
+    pub uid: Option<String>,
+
+    /// The scopes of an access token.
+    #[serde(default)]
+    pub scp: Vec<String>,
+
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
@@ -0,0 +1,44 @@
+//! Policies controlling when the [`Key`](`super::key::Key`)s inside of a
+//! [`super::RemoteCache`] can be used.
+
+use std::time::Duration;
+
+/// Allows a [`super::RemoteCache`] to keep serving its keys for a bounded
+/// grace period after they have expired.
+///
+/// `JWK` endpoints occasionally go down. Without a [`StalePolicy`], a cache
+/// whose keys have expired (and which fails to refresh) is unusable, meaning
+/// that token verification is down for as long as the endpoint is down.
+/// Since providers rotate their keys well after they stop signing with them,
+/// it is usually safe to continue using the previous keys for a short while.
+///
+/// The grace period starts when the keys expire. If the provider does not
+/// specify an expiry time, it starts at the last successful refresh instead.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct StalePolicy {
+    /// How long the keys can be used for after they have expired.
+    pub grace: Duration,
+}
+
+/// Allows a [`super::RemoteCache`] to report that it should be refreshed
+/// *before* its keys actually expire.
+///
+/// Without a [`RefreshAheadPolicy`], the first request after the keys expire
+/// pays for an entire network round trip. With one, callers (or a background
+/// task) can check [`needs_refresh`](`super::RemoteCache::needs_refresh`) and
+/// refresh the cache ahead of time instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RefreshAheadPolicy {
+    /// The fraction (between `0.0` and `1.0`) of the keys' lifetime after
+    /// which the cache should be refreshed.
+    ///
+    /// The lifetime is measured from the last successful
+    /// [`refresh`](`super::RemoteCache::refresh`) until the expiry time.
+    pub threshold: f64,
+}
+
+impl Default for RefreshAheadPolicy {
+    fn default() -> Self {
+        Self { threshold: 0.8 }
+    }
+}
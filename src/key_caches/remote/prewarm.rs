@@ -0,0 +1,197 @@
+//! Pre-warmed [`DecodingKey`]s, for multi-process deployments.
+//!
+//! A supervisor process (e.g., a pre-forking server) fetches and parses the
+//! keys once, and hands [`PrewarmedKeys`] over to each of its workers (e.g.,
+//! through shared memory, or a pipe). The workers then build their
+//! [`RemoteCache`]s from them directly: the `JWK` set is not fetched again,
+//! and the inputs of every [`DecodingKey`] (i.e., the raw `RSA` components)
+//! are used as they are.
+//!
+//! ```ignore
+//! // in the supervisor
+//! remote_cache.refresh().await?;
+//! let bytes = remote_cache.prewarm().to_vec()?;
+//!
+//! // in each worker
+//! let prewarmed = PrewarmedKeys::from_slice(&bytes)?;
+//! let remote_cache = RemoteCache::from_prewarmed(prewarmed)?;
+//! ```
+//!
+//! Only public key material is ever included.
+//!
+//! ### Note:
+//! The keys of the snapshot are filtered just like fetched keys, and the
+//! components of each key are only used if they match its `n` and `e` (so
+//! that mismatched components cannot stand in for another key). Still, only
+//! restore [`PrewarmedKeys`] which were handed over by a trusted process.
+//!
+//! [`DecodingKey`]: `jsonwebtoken::DecodingKey`
+//! [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::error::Error;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::prelude;
+
+/// Identifies a byte-string as [`PrewarmedKeys`].
+pub const PREWARMED_MAGIC: &[u8] = b"webcipher/prewarmed-keys\0";
+
+fn invalid<M>(message: M) -> Error
+where
+    String: From<M>,
+{
+    Error::invalid_snapshot {
+        message: message.into(),
+    }
+}
+
+/// The raw (big-endian) components of an `RSA` public key.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct RsaComponents {
+    /// The modulus.
+    pub n: Vec<u8>,
+
+    /// The public exponent.
+    pub e: Vec<u8>,
+}
+
+impl RsaComponents {
+    /// Check to see if these are the components of the given key (i.e., of
+    /// its `n` and `e`).
+    pub(crate) fn matches(&self, key: &Key) -> bool {
+        let Self { n, e } = self;
+        let decode = |value: &str| URL_SAFE_NO_PAD.decode(value).ok();
+
+        decode(&key.n).as_ref() == Some(n) && decode(&key.e).as_ref() == Some(e)
+    }
+}
+
+/// A [`Snapshot`] of a [`RemoteCache`], along with the inputs of the
+/// [`DecodingKey`] of each of its keys.
+///
+/// Created by calling [`RemoteCache::prewarm`], and restored by calling
+/// [`RemoteCache::restore_prewarmed`] (or [`RemoteCache::from_prewarmed`]).
+///
+/// [`DecodingKey`]: `jsonwebtoken::DecodingKey`
+/// [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+/// [`RemoteCache::prewarm`]: `crate::key_caches::remote::RemoteCache::prewarm`
+/// [`RemoteCache::restore_prewarmed`]: `crate::key_caches::remote::RemoteCache::restore_prewarmed`
+/// [`RemoteCache::from_prewarmed`]: `crate::key_caches::remote::RemoteCache::from_prewarmed`
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct PrewarmedKeys {
+    /// The snapshot of the cache.
+    pub snapshot: Snapshot,
+
+    /// The components of the keys of the snapshot, by `kid`.
+    ///
+    /// Keys without components (or whose components do not match their `n`
+    /// and `e`) are parsed as usual when restored.
+    pub components: BTreeMap<String, RsaComponents>,
+}
+
+impl PrewarmedKeys {
+    /// Serialize these [`PrewarmedKeys`].
+    ///
+    /// The format is [`PREWARMED_MAGIC`], followed by the (length-prefixed)
+    /// serialized [`Snapshot`], followed by the number of components and
+    /// each (length-prefixed) `kid`, `n`, and `e`. Every length is a
+    /// big-endian `u32`.
+    pub fn to_vec(&self) -> prelude::Result<Vec<u8>> {
+        let Self {
+            snapshot,
+            components,
+        } = self;
+
+        let mut bytes = PREWARMED_MAGIC.to_vec();
+        write(&mut bytes, &snapshot.to_vec()?)?;
+        write_len(&mut bytes, components.len())?;
+
+        for (kid, RsaComponents { n, e }) in components {
+            write(&mut bytes, kid.as_bytes())?;
+            write(&mut bytes, n)?;
+            write(&mut bytes, e)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Deserialize [`PrewarmedKeys`] (see [`to_vec`](`PrewarmedKeys::to_vec`)).
+    ///
+    /// Fails with [`Error::invalid_snapshot`] if the bytes are not
+    /// [`PrewarmedKeys`], or if their [`Snapshot`] cannot be read (see
+    /// [`Snapshot::from_slice`]).
+    pub fn from_slice(bytes: &[u8]) -> prelude::Result<Self> {
+        let bytes = bytes
+            .strip_prefix(PREWARMED_MAGIC)
+            .ok_or_else(|| invalid("The magic string is missing."))?;
+        let truncated = || invalid("The pre-warmed keys are truncated.");
+
+        let (snapshot, mut bytes) = read(bytes).ok_or_else(truncated)?;
+        let snapshot = Snapshot::from_slice(snapshot)?;
+
+        let (count, rest) = read_len(bytes).ok_or_else(truncated)?;
+        bytes = rest;
+
+        let mut components = BTreeMap::new();
+        for _ in 0..count {
+            let (kid, rest) = read(bytes).ok_or_else(truncated)?;
+            let (n, rest) = read(rest).ok_or_else(truncated)?;
+            let (e, rest) = read(rest).ok_or_else(truncated)?;
+            bytes = rest;
+
+            let kid = String::from_utf8(kid.to_vec())
+                .map_err(|error| invalid(error.to_string()))?;
+            let rsa_components = RsaComponents {
+                n: n.to_vec(),
+                e: e.to_vec(),
+            };
+
+            let _ = components.insert(kid, rsa_components);
+        }
+
+        match bytes.is_empty() {
+            true => Ok(Self {
+                snapshot,
+                components,
+            }),
+            false => Err(invalid("The pre-warmed keys have trailing bytes.")),
+        }
+    }
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) -> prelude::Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| invalid("The pre-warmed keys are too large."))?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+
+    Ok(())
+}
+
+fn write(bytes: &mut Vec<u8>, value: &[u8]) -> prelude::Result<()> {
+    write_len(bytes, value.len())?;
+    bytes.extend_from_slice(value);
+
+    Ok(())
+}
+
+fn read_len(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    match bytes {
+        [a, b, c, d, rest @ ..] => {
+            let len = u32::from_be_bytes([*a, *b, *c, *d]);
+
+            Some((usize::try_from(len).ok()?, rest))
+        },
+        _ => None,
+    }
+}
+
+fn read(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = read_len(bytes)?;
+
+    (len <= rest.len()).then(|| rest.split_at(len))
+}
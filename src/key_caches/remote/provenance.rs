@@ -0,0 +1,105 @@
+//! Where the keys inside of a [`super::RemoteCache`] came from.
+//!
+//! Every key is tagged with its [`Provenance`]. Keys fetched from the `uri`
+//! (or restored from a snapshot of fetched keys) are
+//! [`Fetched`](`Provenance::Fetched`), keys given to
+//! [`from_keys`](`super::RemoteCache::from_keys`) are
+//! [`Pinned`](`Provenance::Pinned`), and keys can be added with any
+//! provenance by calling [`insert_key`](`super::RemoteCache::insert_key`).
+//!
+//! [`decrypt_with_provenance`](`super::RemoteCache::decrypt_with_provenance`)
+//! reports the provenance of the key which verified a token, so that
+//! operators can alert when traffic is being verified by anything but the
+//! live `JWK` set:
+//!
+//! ```ignore
+//! let Verified { token_data, provenance, .. } =
+//!     remote_cache.decrypt_with_provenance::<Claims, _>(token)?;
+//!
+//! if provenance != Provenance::Fetched {
+//!     eprintln!("Verified by a key which was {}.", provenance.as_str());
+//! }
+//! ```
+//!
+//! Auditing pipelines which need to record the key itself (e.g., its `x5t`,
+//! or its `alg`) can call
+//! [`decrypt_with_key`](`super::RemoteCache::decrypt_with_key`) instead,
+//! which returns a [`VerifiedToken`].
+//!
+//! ### Note:
+//! Keys which were not fetched survive refreshes, unless the fetched `JWK` set
+//! contains a key with the same `kid` (in which case the fetched key replaces
+//! it). Call [`remove_key`](`super::RemoteCache::remove_key`) to drop them.
+
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::key_caches::remote::key::Key;
+
+/// Where a key inside of a [`super::RemoteCache`] came from.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Copy, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Provenance {
+    /// Fetched from the live `JWK` set of the provider.
+    Fetched,
+
+    /// Configured ahead of time by the operator (e.g., for air-gapped
+    /// deployments).
+    Pinned,
+
+    /// Pushed to the cache out-of-band (e.g., by a webhook announcing an
+    /// upcoming rotation).
+    Pushed,
+
+    /// Added during an incident (e.g., while the `JWK` endpoint is down).
+    Emergency,
+}
+
+impl Provenance {
+    /// The name of this [`Provenance`] (e.g., for labelling metrics).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fetched => "fetched",
+            Self::Pinned => "pinned",
+            Self::Pushed => "pushed",
+            Self::Emergency => "emergency",
+        }
+    }
+}
+
+/// A verified token, along with the key that verified it.
+///
+/// Returned by
+/// [`decrypt_with_provenance`](`super::RemoteCache::decrypt_with_provenance`).
+#[derive(Debug)]
+pub struct Verified<Claim> {
+    /// The decrypted token.
+    pub token_data: TokenData<Claim>,
+
+    /// The `kid` of the key which verified the token.
+    pub kid: String,
+
+    /// The provenance of the key which verified the token.
+    pub provenance: Provenance,
+}
+
+/// A verified token, along with (a clone of) the [`Key`] that verified it.
+///
+/// Returned by [`decrypt_with_key`](`super::RemoteCache::decrypt_with_key`).
+#[derive(Debug)]
+pub struct VerifiedToken<Claim> {
+    /// The decrypted token.
+    pub token_data: TokenData<Claim>,
+
+    /// The `kid` of the key which verified the token.
+    ///
+    /// For keys published without a `kid`, this is their thumbprint (see
+    /// [`Key::thumbprint_sha256`]).
+    pub kid: String,
+
+    /// The key which verified the token.
+    pub key: Key,
+}
@@ -0,0 +1,100 @@
+//! Notifications of key rotations.
+//!
+//! Every [`RemoteCache`] broadcasts a [`RotationEvent`] whenever a
+//! [`refresh`](`RemoteCache::refresh`) changes its keys (i.e., `kid`s were
+//! added or removed) or its expiry time. Downstream systems can subscribe in
+//! order to log rotations, or to invalidate caches which were derived from the
+//! previous keys:
+//!
+//! ```ignore
+//! let mut rotations = remote_cache.subscribe_rotations();
+//!
+//! tokio::spawn(async move {
+//!     while let Ok(event) = rotations.recv().await {
+//!         if event.is_rotation() {
+//!             let RotationEvent { uri, added, removed, .. } = event;
+//!             println!("{}: +{:?} -{:?}", uri, added, removed);
+//!         };
+//!     }
+//! });
+//! ```
+//!
+//! ### Note:
+//! Most refreshes move the expiry time forward (i.e., by the `max-age` of the
+//! fetched keys), and are therefore reported even though the keys did not
+//! change. Use [`is_rotation`](`RotationEvent::is_rotation`) in order to
+//! only handle actual rotations.
+//!
+//! Events are only computed while anyone is subscribed. Subscribers which
+//! fall more than [`ROTATION_CHANNEL_CAPACITY`] events behind miss the oldest
+//! ones (see [`tokio::sync::broadcast`]).
+//!
+//! [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+//! [`RemoteCache::refresh`]: `crate::key_caches::remote::RemoteCache::refresh`
+
+use std::collections::BTreeSet;
+
+use crate::time::Expiry;
+
+/// The number of events that each subscriber can fall behind by, before it
+/// misses the oldest ones.
+pub const ROTATION_CHANNEL_CAPACITY: usize = 16;
+
+/// A change to the keys (or the expiry time) of a
+/// [`RemoteCache`](`crate::key_caches::remote::RemoteCache`), caused by a
+/// refresh.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct RotationEvent {
+    /// The `uri` of the cache.
+    pub uri: http::Uri,
+
+    /// The `kid`s of the keys which were added.
+    pub added: BTreeSet<String>,
+
+    /// The `kid`s of the keys which were removed.
+    pub removed: BTreeSet<String>,
+
+    /// The expiry time of the keys before the refresh.
+    pub previous_expiry: Option<Expiry>,
+
+    /// The expiry time of the keys after the refresh.
+    pub expiry: Option<Expiry>,
+}
+
+impl RotationEvent {
+    /// The event of a refresh of the cache at the given `uri`, given its
+    /// `kid`s (and expiry time) before and after the refresh.
+    ///
+    /// Returns [`None`] if nothing changed.
+    pub(crate) fn new(
+        uri: &http::Uri,
+        (previous_kids, previous_expiry): (BTreeSet<String>, Option<Expiry>),
+        (kids, expiry): (BTreeSet<String>, Option<Expiry>),
+    ) -> Option<Self> {
+        let added = kids.difference(&previous_kids).cloned().collect();
+        let removed = previous_kids.difference(&kids).cloned().collect();
+
+        let event = Self {
+            uri: uri.clone(),
+            added,
+            removed,
+            previous_expiry,
+            expiry,
+        };
+
+        match event.is_rotation() || previous_expiry != expiry {
+            true => Some(event),
+            false => None,
+        }
+    }
+
+    /// Check to see if any key was added or removed (instead of only the
+    /// expiry time having changed).
+    pub fn is_rotation(&self) -> bool {
+        let Self { added, removed, .. } = self;
+
+        !added.is_empty() || !removed.is_empty()
+    }
+}
@@ -0,0 +1,136 @@
+//! A versioned, on-disk representation of a [`super::RemoteCache`].
+//!
+//! Persisting a snapshot (e.g., on shutdown) and restoring it (e.g., on
+//! startup) lets a service verify tokens before its first refresh succeeds,
+//! and avoids a thundering herd of fetches when a fleet of instances restarts.
+//!
+//! ```ignore
+//! // on shutdown
+//! std::fs::write("jwks.snapshot", remote_cache.snapshot().to_vec()?)?;
+//!
+//! // on startup
+//! let snapshot = Snapshot::from_slice(&std::fs::read("jwks.snapshot")?)?;
+//! remote_cache.restore(snapshot)?;
+//!
+//! // or, without building the cache first
+//! let remote_cache = RemoteCache::from_snapshot(snapshot)?;
+//! ```
+//!
+//! ### Note:
+//! Snapshots are forward-compatible: unknown fields are ignored, so adding a
+//! field to the format does *not* bump [`SNAPSHOT_VERSION`]. The version is
+//! only bumped on incompatible changes, in which case a migration from the
+//! previous version is added to [`MIGRATIONS`]. Snapshots written by older
+//! versions of this crate are therefore always readable, while snapshots with
+//! a newer (i.e., unknown) version are rejected.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::key_caches::remote::discovery::ProviderMetadata;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::provenance::Provenance;
+use crate::prelude;
+
+/// Identifies a file as a [`Snapshot`].
+pub const SNAPSHOT_MAGIC: &str = "webcipher/remote-cache-snapshot";
+
+/// The version of the [`Snapshot`] format written by this crate.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Upgrades a (`JSON`) snapshot from one version to the next.
+pub type Migration = fn(Value) -> prelude::Result<Value>;
+
+/// The migrations between consecutive versions of the [`Snapshot`] format.
+///
+/// `MIGRATIONS[n]` upgrades a snapshot of version `n + 1` to version `n + 2`.
+/// Version `1` is the first version, so there are none yet.
+pub const MIGRATIONS: [Migration; SNAPSHOT_VERSION as usize - 1] = [];
+
+/// A snapshot of the state of a [`super::RemoteCache`].
+///
+/// Created by calling [`super::RemoteCache::snapshot`], and restored by
+/// calling [`super::RemoteCache::restore`] (or
+/// [`super::RemoteCache::from_snapshot`]).
+#[derive(Clone, Hash, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Always [`SNAPSHOT_MAGIC`].
+    pub magic: String,
+
+    /// The version of the format (see [`SNAPSHOT_VERSION`]).
+    pub version: u32,
+
+    /// The `uri` from which the keys were fetched.
+    pub uri: String,
+
+    /// The keys, ordered by their `kid`.
+    pub keys: Vec<Key>,
+
+    /// The `expiry-time` (in Unix-Time) of the keys.
+    #[serde(default)]
+    pub expiry: Option<u64>,
+
+    /// The time (in Unix-Time) at which the keys were fetched.
+    #[serde(default)]
+    pub fetched_at: Option<u64>,
+
+    /// The provider configuration document, if the `uri` was discovered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_metadata: Option<ProviderMetadata>,
+
+    /// The provenance of every key which was *not* fetched, by `kid`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provenance: BTreeMap<String, Provenance>,
+}
+
+fn invalid<M>(message: M) -> Error
+where
+    String: From<M>,
+{
+    Error::invalid_snapshot {
+        message: message.into(),
+    }
+}
+
+impl Snapshot {
+    /// Serialize this [`Snapshot`].
+    pub fn to_vec(&self) -> prelude::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Deserialize a [`Snapshot`], migrating it from an older version of the
+    /// format if necessary.
+    ///
+    /// Fails with [`Error::invalid_snapshot`] if the bytes are not a
+    /// [`Snapshot`], or if their version is unknown.
+    pub fn from_slice(bytes: &[u8]) -> prelude::Result<Self> {
+        let mut value = serde_json::from_slice::<Value>(bytes)
+            .map_err(|error| invalid(error.to_string()))?;
+
+        match value.get("magic").and_then(Value::as_str) {
+            Some(SNAPSHOT_MAGIC) => (),
+            _ => return Err(invalid("The magic string is missing.")),
+        };
+
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| invalid("The version is missing."))?;
+
+        if version == 0 || version > SNAPSHOT_VERSION {
+            return Err(invalid(format!("Unknown version {}.", version)));
+        };
+
+        for migration in MIGRATIONS.iter().skip(version as usize - 1) {
+            value = migration(value)?;
+        }
+
+        serde_json::from_value(value)
+            .map_err(|error| invalid(error.to_string()))
+    }
+}
@@ -0,0 +1,297 @@
+//! Sharing fetched keys between the replicas of a service.
+//!
+//! A [`super::RemoteCache`] with a [`CacheStore`] persists a [`Snapshot`]
+//! after every successful fetch (i.e., write-through), and checks the store
+//! before fetching (i.e., read-through). A replica refreshing its cache
+//! therefore picks up the keys that another replica has already fetched,
+//! instead of every replica hitting the provider on its own.
+//!
+//! ```ignore
+//! let store = Arc::new(FileStore::new("/var/cache/jwks"));
+//!
+//! let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .cache_store(Arc::clone(&store))
+//!     .build()?;
+//!
+//! // Or, for every cache of a registry:
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .add_remote(Tpa::Apple, APPLE_JWK_URI)
+//!     .cache_store(store)
+//!     .finish()
+//!     .await?;
+//! ```
+//!
+//! A stored snapshot is only used if it has not expired yet, and if it was
+//! fetched *after* the keys currently inside of the cache (so that a refresh
+//! following a key rotation still reaches the provider).
+//!
+//! Stores can also implement a lock (see [`CacheStore::try_lock`]), so that
+//! only one replica fetches at a time: the other replicas wait (for up to
+//! [`CacheStore::lock_wait`]) for its snapshot instead of fetching as well.
+//!
+//! Two stores are provided:
+//! - [`FileStore`], which keeps one file per `uri` inside of a directory.
+//! - [`RedisStore`](`redis::RedisStore`), which keeps one entry per `uri`
+//!   inside of `Redis` (only available when the `redis` feature is enabled).
+//!
+//! ### Note:
+//! Failures of the store never fail a refresh: a snapshot which cannot be
+//! loaded is treated as missing, a snapshot which cannot be saved is dropped,
+//! and a lock which cannot be acquired is ignored.
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::Error;
+use crate::key_caches::remote::file;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::prelude;
+use crate::prelude::Timestamp;
+use crate::time::now;
+
+/// How often a replica waiting on another one checks for its snapshot.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A (shared) place to persist the [`Snapshot`]s of
+/// [`super::RemoteCache`]s, indexed by their `uri`.
+///
+/// See the [module level documentation](`self`).
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    /// Load the latest snapshot of the cache targeting the given `uri`, if
+    /// there is one.
+    async fn load(&self, uri: &http::Uri) -> prelude::Result<Option<Snapshot>>;
+
+    /// Save the given snapshot of the cache targeting the given `uri`,
+    /// replacing the previous one.
+    async fn save(
+        &self,
+        uri: &http::Uri,
+        snapshot: &Snapshot,
+    ) -> prelude::Result<()>;
+
+    /// Try to become the only cache fetching from the given `uri`.
+    ///
+    /// Returns a token to [`unlock`](`CacheStore::unlock`) the `uri` with, or
+    /// [`None`] if another cache holds the lock. Locks should expire on their
+    /// own, in case their holder never unlocks them.
+    ///
+    /// By default, the lock is always acquired (i.e., every cache fetches on
+    /// its own).
+    async fn try_lock(&self, _: &http::Uri) -> prelude::Result<Option<String>> {
+        Ok(Some(String::new()))
+    }
+
+    /// Release the lock on the given `uri`, if it is still held with the
+    /// given token.
+    async fn unlock(&self, _: &http::Uri, _: &str) -> prelude::Result<()> {
+        Ok(())
+    }
+
+    /// How long to wait for a snapshot while another cache holds the lock,
+    /// before fetching anyway.
+    fn lock_wait(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+#[async_trait]
+impl<S> CacheStore for Arc<S>
+where
+    S: CacheStore + ?Sized,
+{
+    async fn load(&self, uri: &http::Uri) -> prelude::Result<Option<Snapshot>> {
+        (**self).load(uri).await
+    }
+
+    async fn save(
+        &self,
+        uri: &http::Uri,
+        snapshot: &Snapshot,
+    ) -> prelude::Result<()> {
+        (**self).save(uri, snapshot).await
+    }
+
+    async fn try_lock(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<Option<String>> {
+        (**self).try_lock(uri).await
+    }
+
+    async fn unlock(
+        &self,
+        uri: &http::Uri,
+        token: &str,
+    ) -> prelude::Result<()> {
+        (**self).unlock(uri, token).await
+    }
+
+    fn lock_wait(&self) -> Duration {
+        (**self).lock_wait()
+    }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::store_failed {
+        message: error.to_string(),
+    }
+}
+
+/// A [`CacheStore`] which keeps one file per `uri` inside of a directory
+/// (e.g., a volume shared between the replicas of a service).
+///
+/// Snapshots are written to a temporary file first, and then moved into
+/// place, so that a concurrent [`load`](`CacheStore::load`) never observes a
+/// partially written snapshot.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    directory: PathBuf,
+}
+
+impl FileStore {
+    /// Create a [`FileStore`] which keeps its files inside of the given
+    /// directory.
+    ///
+    /// The directory is created (if necessary) on the first save.
+    pub fn new<P>(directory: P) -> Self
+    where
+        PathBuf: From<P>,
+    {
+        let directory = directory.into();
+
+        Self { directory }
+    }
+
+    /// The directory that the files are kept inside of.
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    /// The path of the file for the given `uri`.
+    fn path(&self, uri: &http::Uri) -> PathBuf {
+        let name = file::encode(&uri.to_string(), b"-._~");
+
+        self.directory.join(format!("{}.snapshot", name))
+    }
+}
+
+#[async_trait]
+impl CacheStore for FileStore {
+    async fn load(&self, uri: &http::Uri) -> prelude::Result<Option<Snapshot>> {
+        match tokio::fs::read(self.path(uri)).await {
+            Ok(bytes) => Ok(Some(Snapshot::from_slice(&bytes)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                Ok(None)
+            },
+            Err(error) => Err(io_error(error)),
+        }
+    }
+
+    async fn save(
+        &self,
+        uri: &http::Uri,
+        snapshot: &Snapshot,
+    ) -> prelude::Result<()> {
+        let path = self.path(uri);
+        let temporary = path.with_extension(uuid::Uuid::new_v4().to_string());
+
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .map_err(io_error)?;
+        tokio::fs::write(&temporary, snapshot.to_vec()?)
+            .await
+            .map_err(io_error)?;
+
+        if let Err(error) = tokio::fs::rename(&temporary, &path).await {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(io_error(error));
+        };
+
+        Ok(())
+    }
+}
+
+/// What a cache should do after consulting its store.
+pub(crate) enum ReadThrough {
+    /// Restore the given (fresher) snapshot instead of fetching.
+    Restore(Box<Snapshot>),
+
+    /// Fetch, and then unlock the `uri` with the given token (if any).
+    Fetch(Option<String>),
+}
+
+/// Consult the given store before fetching from the given `uri`.
+///
+/// While another cache holds the lock, its snapshot is waited for (for up to
+/// [`CacheStore::lock_wait`]).
+pub(crate) async fn read_through(
+    cache_store: &dyn CacheStore,
+    uri: &http::Uri,
+    refreshed_at: Option<Timestamp>,
+) -> ReadThrough {
+    if let Some(snapshot) = load_fresher(cache_store, uri, refreshed_at).await
+    {
+        return ReadThrough::Restore(Box::new(snapshot));
+    };
+
+    let deadline = tokio::time::Instant::now() + cache_store.lock_wait();
+
+    loop {
+        match cache_store.try_lock(uri).await {
+            Ok(None) if tokio::time::Instant::now() < deadline => (),
+            Ok(token) => return ReadThrough::Fetch(token),
+            Err(_) => return ReadThrough::Fetch(None),
+        };
+
+        tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+
+        if let Some(snapshot) =
+            load_fresher(cache_store, uri, refreshed_at).await
+        {
+            return ReadThrough::Restore(Box::new(snapshot));
+        };
+    }
+}
+
+/// Save the given snapshot (if the fetch succeeded) to the given store, and
+/// release the lock (if it was acquired).
+pub(crate) async fn write_through(
+    cache_store: &dyn CacheStore,
+    uri: &http::Uri,
+    snapshot: Option<Snapshot>,
+    token: Option<String>,
+) {
+    if let Some(snapshot) = snapshot {
+        let _ = cache_store.save(uri, &snapshot).await;
+    };
+
+    if let Some(token) = token {
+        let _ = cache_store.unlock(uri, &token).await;
+    };
+}
+
+/// Load the snapshot of the cache targeting the given `uri` from the given
+/// store, if it is still fresh, and was fetched after `refreshed_at`.
+async fn load_fresher(
+    cache_store: &dyn CacheStore,
+    uri: &http::Uri,
+    refreshed_at: Option<Timestamp>,
+) -> Option<Snapshot> {
+    let snapshot = cache_store.load(uri).await.ok()??;
+
+    let fresh = snapshot.expiry.is_some_and(|expiry| expiry > now());
+    let fresher = snapshot.fetched_at > refreshed_at;
+
+    match snapshot.uri == uri.to_string() && fresh && fresher {
+        true => Some(snapshot),
+        false => None,
+    }
+}
@@ -0,0 +1,239 @@
+//! A [`CacheStore`] backed by `Redis`.
+//!
+//! Only available when the `redis` feature is enabled.
+//!
+//! Every `uri` maps to two entries (by default, prefixed with `webcipher:`):
+//! - `<prefix>snapshot:<uri>`, which holds the latest [`Snapshot`], and
+//!   expires along with it (so that `Redis` never serves expired keys),
+//! - `<prefix>lock:<uri>`, which is held by the replica currently fetching
+//!   from the `uri`, and expires after `lock_ttl` (in case that replica dies
+//!   before unlocking it).
+//!
+//! ```ignore
+//! let client = redis::Client::open("redis://127.0.0.1/")?;
+//! let store = RedisStore::new(client).await?;
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote_cache(Tpa::Google, RemoteCache::new(GOOGLE_JWK_URI)?)
+//!     .cache_store(store)
+//!     .finish()
+//!     .await?;
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::Script;
+
+use crate::error::Error;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::store::CacheStore;
+use crate::prelude;
+use crate::prelude::Timestamp;
+use crate::time::now;
+
+/// Deletes the lock (i.e., `KEYS[1]`) only if it is still held with the given
+/// token (i.e., `ARGV[1]`), so that a lock which expired and was acquired by
+/// another replica is never released.
+const UNLOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+fn failed(error: redis::RedisError) -> Error {
+    Error::store_failed {
+        message: error.to_string(),
+    }
+}
+
+/// The `Redis` key of the given kind of entry for the given `uri`.
+pub(crate) fn key(prefix: &str, kind: &str, uri: &http::Uri) -> String {
+    format!("{}{}:{}", prefix, kind, uri)
+}
+
+/// For how long a snapshot with the given expiry should be kept, as of `now`.
+///
+/// Returns [`None`] if the snapshot has already expired (and should not be
+/// kept at all), or `Some(None)` if it never expires.
+pub(crate) fn ttl(
+    expiry: Option<u64>,
+    now: Timestamp,
+) -> Option<Option<Duration>> {
+    match expiry {
+        None | Some(u64::MAX) => Some(None),
+        Some(expiry) if expiry > now => {
+            Some(Some(Duration::from_secs(expiry - now)))
+        },
+        Some(_) => None,
+    }
+}
+
+/// A [`CacheStore`] which keeps its snapshots (and locks) inside of `Redis`.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone)]
+pub struct RedisStore {
+    connection: ConnectionManager,
+    prefix: String,
+    lock_ttl: Duration,
+    lock_wait: Duration,
+}
+
+impl fmt::Debug for RedisStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("prefix", &self.prefix)
+            .field("lock_ttl", &self.lock_ttl)
+            .field("lock_wait", &self.lock_wait)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisStore {
+    /// Connect to the `Redis` server of the given client.
+    ///
+    /// The connection is re-established automatically whenever it drops.
+    pub async fn new(client: redis::Client) -> prelude::Result<Self> {
+        let connection = ConnectionManager::new(client).await.map_err(failed)?;
+
+        Ok(Self {
+            connection,
+            prefix: "webcipher:".into(),
+            lock_ttl: Duration::from_secs(10),
+            lock_wait: Duration::from_secs(5),
+        })
+    }
+
+    /// Set the prefix of every key (e.g., to share a `Redis` server between
+    /// services).
+    pub fn with_prefix<I>(mut self, prefix: I) -> Self
+    where
+        String: From<I>,
+    {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set for how long a lock is held at most.
+    ///
+    /// Should exceed the time that a refresh (including its retries) takes.
+    pub fn with_lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.lock_ttl = lock_ttl;
+        self
+    }
+
+    /// Set for how long a replica waits for the snapshot of the replica
+    /// holding the lock, before fetching anyway.
+    pub fn with_lock_wait(mut self, lock_wait: Duration) -> Self {
+        self.lock_wait = lock_wait;
+        self
+    }
+
+    /// The prefix of every key.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// For how long a lock is held at most.
+    pub fn lock_ttl(&self) -> Duration {
+        self.lock_ttl
+    }
+}
+
+#[async_trait]
+impl CacheStore for RedisStore {
+    async fn load(&self, uri: &http::Uri) -> prelude::Result<Option<Snapshot>> {
+        let Self {
+            connection, prefix, ..
+        } = self;
+
+        let bytes = redis::cmd("GET")
+            .arg(key(prefix, "snapshot", uri))
+            .query_async::<_, Option<Vec<u8>>>(&mut connection.clone())
+            .await
+            .map_err(failed)?;
+
+        bytes.as_deref().map(Snapshot::from_slice).transpose()
+    }
+
+    async fn save(
+        &self,
+        uri: &http::Uri,
+        snapshot: &Snapshot,
+    ) -> prelude::Result<()> {
+        let Self {
+            connection, prefix, ..
+        } = self;
+
+        let ttl = match ttl(snapshot.expiry, now()) {
+            Some(ttl) => ttl,
+            None => return Ok(()),
+        };
+
+        let mut command = redis::cmd("SET");
+        command
+            .arg(key(prefix, "snapshot", uri))
+            .arg(snapshot.to_vec()?);
+
+        if let Some(ttl) = ttl {
+            command.arg("EX").arg(ttl.as_secs());
+        };
+
+        command
+            .query_async::<_, ()>(&mut connection.clone())
+            .await
+            .map_err(failed)
+    }
+
+    async fn try_lock(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<Option<String>> {
+        let Self {
+            connection,
+            prefix,
+            lock_ttl,
+            ..
+        } = self;
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let acquired = redis::cmd("SET")
+            .arg(key(prefix, "lock", uri))
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(lock_ttl.as_millis().max(1) as u64)
+            .query_async::<_, Option<String>>(&mut connection.clone())
+            .await
+            .map_err(failed)?;
+
+        Ok(acquired.map(|_| token))
+    }
+
+    async fn unlock(
+        &self,
+        uri: &http::Uri,
+        token: &str,
+    ) -> prelude::Result<()> {
+        let Self {
+            connection, prefix, ..
+        } = self;
+
+        Script::new(UNLOCK_SCRIPT)
+            .key(key(prefix, "lock", uri))
+            .arg(token)
+            .invoke_async::<_, i64>(&mut connection.clone())
+            .await
+            .map(drop)
+            .map_err(failed)
+    }
+
+    fn lock_wait(&self) -> Duration {
+        self.lock_wait
+    }
+}
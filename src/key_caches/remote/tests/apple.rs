@@ -0,0 +1,71 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::apple::AppleClaims;
+use crate::key_caches::remote::apple::RealUserStatus;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[test]
+/// An `ID` token (as issued by `Apple`) should deserialize, including its
+/// string-encoded booleans.
+fn test_apple_claims() {
+    let token = sign(&json!({
+        "iss": "https://appleid.apple.com",
+        "aud": "com.example.app",
+        "exp": 20_000_000_000u64,
+        "iat": 1_700_000_000u64,
+        "sub": "001234.abcdef0123456789abcdef0123456789.0123",
+        "c_hash": "h0kXNBfDoZMRUp-v9Xwmpw",
+        "email": "abc123@privaterelay.appleid.com",
+        "email_verified": "true",
+        "is_private_email": "true",
+        "auth_time": 1_700_000_000u64,
+        "nonce_supported": true,
+        "real_user_status": 2,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<AppleClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.aud, Audience::from("com.example.app"));
+    assert_eq!(claims.email_verified, Some(true));
+    assert_eq!(claims.is_private_email, Some(true));
+    assert_eq!(claims.nonce_supported, Some(true));
+    assert_eq!(claims.real_user_status, Some(RealUserStatus::LikelyReal));
+    assert_eq!(claims.transfer_sub, None);
+}
+
+#[test]
+/// Only the booleans (and the strings holding one) should be accepted as
+/// booleans, and only the documented statuses as a `real_user_status`.
+fn test_apple_claims_invalid() {
+    let claims = json!({
+        "iss": "https://appleid.apple.com",
+        "aud": "com.example.app",
+        "exp": 20_000_000_000u64,
+        "iat": 1_700_000_000u64,
+        "sub": "001234.abcdef0123456789abcdef0123456789.0123",
+    });
+
+    let mut with = claims.clone();
+    with["email_verified"] = json!("yes");
+    let token = sign(&with);
+    assert!(remote_cache()
+        .decrypt_unchecked::<AppleClaims, _>(token)
+        .is_err());
+
+    let mut with = claims.clone();
+    with["real_user_status"] = json!(3);
+    let token = sign(&with);
+    assert!(remote_cache()
+        .decrypt_unchecked::<AppleClaims, _>(token)
+        .is_err());
+
+    let token = sign(&claims);
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<AppleClaims, _>(token)
+        .unwrap();
+    assert_eq!(claims.email_verified, None);
+}
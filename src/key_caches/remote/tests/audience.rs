@@ -0,0 +1,25 @@
+use serde_json::json;
+
+use crate::key_caches::registered::Audience;
+
+#[test]
+/// Either form of the `aud` claim should be deserialized, and serialized back
+/// as is.
+fn test_audience() {
+    let single = serde_json::from_value::<Audience>(json!("a")).unwrap();
+    assert_eq!(single, Audience::from("a"));
+    assert_eq!(single.as_slice(), ["a"]);
+    assert!(single.contains("a"));
+    assert_eq!(serde_json::to_value(&single).unwrap(), json!("a"));
+
+    let multiple = serde_json::from_value::<Audience>(json!(["a", "b"]));
+    let multiple = multiple.unwrap();
+    assert_eq!(multiple, Audience::from(vec!["a".into(), "b".into()]));
+    assert!(multiple.contains("b"));
+    assert!(!multiple.contains("c"));
+    assert_eq!(serde_json::to_value(&multiple).unwrap(), json!(["a", "b"]));
+    assert_eq!(multiple.into_vec(), ["a", "b"]);
+
+    assert!(serde_json::from_value::<Audience>(json!(1)).is_err());
+    assert!(serde_json::from_value::<Audience>(json!(["a", 1])).is_err());
+}
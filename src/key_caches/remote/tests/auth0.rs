@@ -0,0 +1,77 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::auth0::Auth0Claims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// The keys, the issuer, and the endpoints of a tenant should be derived from
+/// its domain, with or without a scheme.
+fn test_for_auth0() {
+    use crate::key_caches::remote::RemoteCache;
+
+    for domain in ["example.us.auth0.com", "https://example.us.auth0.com/"] {
+        let remote_cache = RemoteCache::for_auth0(domain).unwrap();
+
+        assert_eq!(
+            remote_cache.uri(),
+            "https://example.us.auth0.com/.well-known/jwks.json",
+        );
+        assert_eq!(
+            remote_cache.issuer(),
+            Some("https://example.us.auth0.com/"),
+        );
+        assert_eq!(
+            remote_cache.token_endpoint(),
+            Some("https://example.us.auth0.com/oauth/token"),
+        );
+    }
+}
+
+#[test]
+/// Namespaced custom claims should be kept, and `aud` should be either a
+/// string or an array.
+fn test_auth0_claims() {
+    let remote_cache = remote_cache();
+
+    let token = sign(&json!({
+        "iss": "https://example.us.auth0.com/",
+        "sub": "auth0|123",
+        "aud": [
+            "https://api.example.com",
+            "https://example.us.auth0.com/userinfo",
+        ],
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "scope": "openid read:users",
+        "permissions": ["read:users"],
+        "https://example.com/roles": ["admin"],
+        "https://example.com/plan": "pro",
+    }));
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_unchecked::<Auth0Claims, _>(token)
+        .unwrap();
+    assert_eq!(claims.aud.as_slice().len(), 2);
+    assert_eq!(claims.permissions, ["read:users"]);
+
+    let namespaced = claims.namespaced("https://example.com/");
+    assert_eq!(namespaced.len(), 2);
+    assert_eq!(namespaced["roles"], &json!(["admin"]));
+    assert_eq!(namespaced["plan"], &json!("pro"));
+
+    let token = sign(&json!({
+        "iss": "https://example.us.auth0.com/",
+        "sub": "auth0|123",
+        "aud": "my-client-id",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+    }));
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_unchecked::<Auth0Claims, _>(token)
+        .unwrap();
+    assert_eq!(claims.aud.as_slice(), ["my-client-id"]);
+    assert!(claims.permissions.is_empty());
+    assert!(claims.namespaced("https://example.com/").is_empty());
+}
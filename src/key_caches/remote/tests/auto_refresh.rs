@@ -0,0 +1,65 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+
+use crate::key_caches::remote::auto_refresh::AutoRefresh;
+use crate::key_caches::remote::auto_refresh::RefreshSchedule;
+use crate::key_caches::remote::tests::SequenceFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+
+#[tokio::test]
+/// The cache should be refreshed immediately, then at every interval, until
+/// the handle is dropped.
+async fn test_auto_refresh_interval() {
+    let idp = Arc::new(MockIdp::new());
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+
+    let handle = RemoteCache::spawn_auto_refresh(
+        &remote_cache,
+        AutoRefresh::new(RefreshSchedule::Interval(Duration::from_millis(10))),
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(remote_cache.read().await.is_cache_fresh());
+    assert!(idp.fetch_count() >= 2);
+
+    handle.shutdown();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let fetch_count = idp.fetch_count();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(idp.fetch_count(), fetch_count);
+}
+
+#[tokio::test]
+/// Failed refreshes should be reported and retried.
+async fn test_auto_refresh_errors() {
+    let remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(SequenceFetcher::default())
+        .build()
+        .unwrap();
+    let remote_cache = Arc::new(RwLock::new(remote_cache));
+
+    let errors = Arc::new(AtomicUsize::new(0));
+    let auto_refresh = AutoRefresh::new(RefreshSchedule::TtlBased)
+        .retry_interval(Duration::from_millis(5))
+        .on_error({
+            let errors = errors.clone();
+            move |error| {
+                assert!(matches!(error, Error::unable_to_fetch_keys { .. }));
+                errors.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+    let _handle = RemoteCache::spawn_auto_refresh(&remote_cache, auto_refresh);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(errors.load(Ordering::SeqCst) >= 2);
+    assert!(remote_cache.read().await.keys().is_empty());
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use http::header::AUTHORIZATION;
+use http::header::USER_AGENT;
+
+use crate::key_caches::remote::config::RedirectPolicy;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+#[test]
+/// The configuration given to the builder should be stored in the cache.
+fn test_builder_configuration() {
+    let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(MockFetcher::default())
+        .timeout(Duration::from_secs(5))
+        .connect_timeout(Duration::from_secs(1))
+        .header(AUTHORIZATION, "Bearer a.b.c")
+        .user_agent("webcipher-tests")
+        .redirect_policy(RedirectPolicy::Limited(2))
+        .build()
+        .unwrap();
+
+    let config = remote_cache.config();
+
+    assert_eq!(config.timeout, Some(Duration::from_secs(5)));
+    assert_eq!(config.connect_timeout, Some(Duration::from_secs(1)));
+    assert_eq!(config.headers[AUTHORIZATION], "Bearer a.b.c");
+    assert_eq!(config.headers[USER_AGENT], "webcipher-tests");
+    assert_eq!(config.redirect_policy, RedirectPolicy::Limited(2));
+    assert!(!remote_cache.is_cache_fresh());
+}
+
+#[test]
+/// Invalid headers should be reported once the cache is built.
+fn test_builder_invalid_header() {
+    let err = RemoteCache::builder(GOOGLE_JWK_URI)
+        .header("invalid header name", "value")
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err, Error::invalid_header);
+}
+
+#[test]
+/// Plain `http` targets should only be allowed when explicitly opted into.
+fn test_builder_insecure_http() {
+    let uri = "http://localhost:8080/certs";
+
+    let err = RemoteCache::builder(uri).build().err().unwrap();
+    assert_eq!(err, Error::invalid_uri);
+
+    let remote_cache = RemoteCache::builder(uri)
+        .fetcher(MockFetcher::default())
+        .allow_insecure_http(true)
+        .build()
+        .unwrap();
+    assert!(remote_cache.config().allow_insecure_http);
+
+    let err = RemoteCache::builder("ftp://localhost/certs")
+        .allow_insecure_http(true)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err, Error::invalid_uri);
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use jsonwebtoken::errors::ErrorKind;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::T;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+use crate::time::Clock;
+use crate::time::ManualClock;
+
+fn is_expired(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::unable_to_verify_token(error)
+            if *error.kind() == ErrorKind::ExpiredSignature,
+    )
+}
+
+#[tokio::test]
+/// The freshness of the keys should be checked against the clock of the
+/// cache.
+async fn test_freshness() {
+    let idp = Arc::new(MockIdp::new());
+    let clock = Arc::new(ManualClock::new(T));
+
+    let mut remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(Arc::clone(&idp))
+        .clock(Arc::clone(&clock))
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert_eq!(remote_cache.expiry_time(), &Some(T + 86_400 - 3600));
+    assert_eq!(remote_cache.refreshed_at, Some(T));
+    assert!(remote_cache.is_cache_fresh());
+
+    clock.advance(Duration::from_secs(86_400));
+    assert!(!remote_cache.is_cache_fresh());
+    assert_eq!(remote_cache.time_until_expiry(), Some(Duration::ZERO));
+
+    remote_cache.set_clock(None);
+    assert!(remote_cache.clock().is_none());
+    assert!(!remote_cache.is_cache_fresh());
+}
+
+#[test]
+/// The `exp` of tokens should be checked against the clock of the cache,
+/// including its leeway.
+fn test_expiry() {
+    let clock = Arc::new(ManualClock::new(T));
+    let mut remote_cache = remote_cache();
+    remote_cache.set_clock(Some(Arc::clone(&clock) as Arc<dyn Clock>));
+
+    let token = sign(&json!({ "sub": "a", "exp": T + 60 }));
+    let data = remote_cache.decrypt_unchecked::<Value, _>(token.as_str());
+    assert_eq!(data.unwrap().claims["sub"], "a");
+
+    clock.advance(Duration::from_secs(120));
+    remote_cache
+        .decrypt_unchecked::<Value, _>(token.as_str())
+        .unwrap();
+
+    clock.advance(Duration::from_secs(1));
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token.as_str())
+        .unwrap_err();
+    assert!(is_expired(&err));
+
+    // Without a clock, the token expired long ago.
+    remote_cache.set_clock(None);
+    clock.set(T);
+    let err = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap_err();
+    assert!(is_expired(&err));
+}
+
+#[test]
+/// Tokens decrypted into a borrowed buffer should be checked against the
+/// clock of the cache as well.
+fn test_decrypt_borrowed() {
+    #[derive(Deserialize)]
+    struct Claims<'a> {
+        sub: &'a str,
+    }
+
+    let clock = Arc::new(ManualClock::new(T));
+    let mut remote_cache = remote_cache();
+    remote_cache.set_clock(Some(Arc::clone(&clock) as Arc<dyn Clock>));
+
+    let token = sign(&json!({ "sub": "a", "exp": T }));
+    let mut buffer = Vec::new();
+
+    let Claims { sub } = remote_cache
+        .decrypt_borrowed::<Claims>(&token, &mut buffer)
+        .unwrap();
+    assert_eq!(sub, "a");
+
+    clock.advance(Duration::from_secs(3600));
+    let err = remote_cache
+        .decrypt_borrowed::<Claims>(&token, &mut buffer)
+        .err()
+        .unwrap();
+    assert!(is_expired(&err));
+}
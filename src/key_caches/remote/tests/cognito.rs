@@ -0,0 +1,100 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::cognito::CognitoClaims;
+use crate::key_caches::remote::cognito::CognitoUserPool;
+use crate::key_caches::remote::cognito::TokenUse;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::prelude::Error;
+
+const REGION: &str = "us-east-1";
+const USER_POOL_ID: &str = "us-east-1_AbCdEfGhI";
+const CLIENT_ID: &str = "1example23456789";
+const ISSUER: &str = "https://cognito-idp.us-east-1.amazonaws.com/us-east-1_AbCdEfGhI";
+
+#[test]
+/// The keys and the issuer of a pool should be located by its region and id.
+fn test_user_pool() {
+    let user_pool = CognitoUserPool::new(REGION, USER_POOL_ID);
+    assert_eq!(user_pool.issuer(), ISSUER);
+    assert_eq!(
+        user_pool.jwk_uri(),
+        format!("{}/.well-known/jwks.json", ISSUER),
+    );
+
+    let remote_cache = user_pool
+        .remote_cache_builder()
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+    assert_eq!(remote_cache.uri().to_string(), user_pool.jwk_uri());
+}
+
+#[test]
+/// Both kinds of tokens should deserialize, and only be accepted as the
+/// requested kind, for the requested app client.
+fn test_validate() {
+    let user_pool = CognitoUserPool::new(REGION, USER_POOL_ID);
+    let remote_cache = remote_cache();
+
+    let id_token = sign(&json!({
+        "iss": ISSUER,
+        "sub": "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee",
+        "aud": CLIENT_ID,
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "auth_time": 1_700_000_000u64,
+        "token_use": "id",
+        "cognito:username": "jane",
+        "cognito:groups": ["admins", "editors"],
+        "email": "jane@example.com",
+        "email_verified": true,
+    }));
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_unchecked::<CognitoClaims, _>(id_token)
+        .unwrap();
+    assert_eq!(claims.token_use, TokenUse::Id);
+    assert_eq!(claims.username.as_deref(), Some("jane"));
+    assert_eq!(claims.groups, ["admins", "editors"]);
+    assert_eq!(claims.app_client(), Some(CLIENT_ID));
+
+    user_pool.validate(&claims, TokenUse::Id, CLIENT_ID).unwrap();
+    assert_eq!(
+        user_pool.validate(&claims, TokenUse::Access, CLIENT_ID),
+        Err(Error::unexpected_token_use),
+    );
+    assert!(matches!(
+        user_pool.validate(&claims, TokenUse::Id, "another-client"),
+        Err(Error::unable_to_verify_token(_)),
+    ));
+
+    let access_token = sign(&json!({
+        "iss": ISSUER,
+        "sub": "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee",
+        "client_id": CLIENT_ID,
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "token_use": "access",
+        "scope": "aws.cognito.signin.user.admin",
+        "username": "jane",
+    }));
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_unchecked::<CognitoClaims, _>(access_token)
+        .unwrap();
+    assert_eq!(claims.username.as_deref(), Some("jane"));
+    assert!(claims.groups.is_empty());
+
+    user_pool.validate(&claims, TokenUse::Access, CLIENT_ID).unwrap();
+    assert_eq!(
+        user_pool.validate(&claims, TokenUse::Id, CLIENT_ID),
+        Err(Error::unexpected_token_use),
+    );
+
+    let another_pool = CognitoUserPool::new("eu-west-1", USER_POOL_ID);
+    assert!(matches!(
+        another_pool.validate(&claims, TokenUse::Access, CLIENT_ID),
+        Err(Error::unable_to_verify_token(_)),
+    ));
+}
@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::testing::KEY_PAIRS;
+
+#[derive(Deserialize)]
+struct Claims<'a> {
+    sub: &'a str,
+    name: Cow<'a, str>,
+    exp: u64,
+}
+
+#[test]
+/// Claims should borrow from the buffer, which can be re-used across tokens.
+fn test_decrypt_borrowed() {
+    let remote_cache = remote_cache();
+    let mut buffer = Vec::new();
+
+    for sub in ["first", "second"] {
+        let token = sign(&json!({
+            "sub": sub,
+            "name": "\"quoted\"",
+            "exp": 20_000_000_000u64,
+        }));
+
+        let claims = remote_cache
+            .decrypt_borrowed::<Claims>(&token, &mut buffer)
+            .unwrap();
+
+        assert_eq!(claims.sub, sub);
+        assert_eq!(claims.name, "\"quoted\"");
+        assert_eq!(claims.exp, 20_000_000_000);
+    }
+}
+
+#[test]
+/// Tokens should be rejected exactly as by `decrypt_unchecked`.
+fn test_decrypt_borrowed_failures() {
+    let remote_cache = remote_cache();
+    let mut buffer = Vec::new();
+
+    let expired = sign(&json!({ "sub": "user", "name": "", "exp": 1u64 }));
+    let missing_exp = sign(&json!({ "sub": "user", "name": "" }));
+    let unknown_key = KEY_PAIRS[1]
+        .sign(&json!({ "sub": "user", "name": "", "exp": 20_000_000_000u64 }))
+        .unwrap();
+
+    let valid =
+        sign(&json!({ "sub": "user", "name": "", "exp": 20_000_000_000u64 }));
+    let (message, _) = valid.rsplit_once('.').unwrap();
+    let (_, forged_signature) = expired.rsplit_once('.').unwrap();
+    let forged = format!("{}.{}", message, forged_signature);
+    let (header, rest) = valid.split_once('.').unwrap();
+    let extra_segment = format!("{}.e30.{}", header, rest);
+
+    for token in [expired, missing_exp, unknown_key, forged, extra_segment] {
+        let borrowed = remote_cache
+            .decrypt_borrowed::<Claims>(&token, &mut buffer)
+            .err()
+            .unwrap();
+        let owned = remote_cache
+            .decrypt_unchecked::<serde_json::Value, _>(token)
+            .unwrap_err();
+
+        assert_eq!(borrowed, owned);
+    }
+}
@@ -0,0 +1,69 @@
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::prelude::Error;
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Subject {
+    sub: String,
+    exp: u64,
+    #[serde(default)]
+    groups: Vec<String>,
+}
+
+#[test]
+/// Only the claims at the given pointers should be deserialized.
+fn test_decrypt_partial() {
+    let remote_cache = remote_cache();
+    let token = sign(&json!({
+        "sub": "user",
+        "exp": 20_000_000_000u64,
+        "groups": ["a", "b"],
+        "nested": { "ignored": true },
+    }));
+
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_partial::<Subject, _, _>(token, &["/sub", "/exp"])
+        .unwrap();
+
+    assert_eq!(claims, Subject {
+        sub: "user".into(),
+        exp: 20_000_000_000,
+        groups: vec![],
+    });
+}
+
+#[test]
+/// Nested pointers should only keep the requested part of the object.
+fn test_decrypt_partial_nested() {
+    let remote_cache = remote_cache();
+    let token = sign(&json!({
+        "exp": 20_000_000_000u64,
+        "realm_access": { "roles": ["admin"], "other": 1 },
+    }));
+
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_partial::<serde_json::Value, _, _>(token, &[
+            "/realm_access/roles",
+        ])
+        .unwrap();
+
+    assert_eq!(claims, json!({ "realm_access": { "roles": ["admin"] } }));
+}
+
+#[test]
+/// The signature must still be verified.
+fn test_decrypt_partial_invalid_signature() {
+    let remote_cache = remote_cache();
+    let token = sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }));
+    let token = format!("{}x", &token[..token.len() - 1]);
+
+    let err = remote_cache
+        .decrypt_partial::<Subject, _, _>(token, &["/sub", "/exp"])
+        .unwrap_err();
+
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+}
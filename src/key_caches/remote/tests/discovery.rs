@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use http::header::CONTENT_TYPE;
+use http::StatusCode;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+
+const ISSUER: &str = "https://accounts.example.com";
+const DISCOVERY_URI: &str =
+    "https://accounts.example.com/.well-known/openid-configuration";
+const JWKS_URI: &str = "https://keys.example.com/oauth2/v3/certs";
+const TOKEN_ENDPOINT: &str = "https://accounts.example.com/token";
+
+fn document(document: Value) -> JwksResponse {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+    JwksResponse {
+        status: StatusCode::OK,
+        headers,
+        body: serde_json::to_vec(&document).unwrap().into(),
+    }
+}
+
+#[tokio::test]
+/// The cache should target the discovered `jwks_uri`, and record the issuer.
+async fn test_discover() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(
+                DISCOVERY_URI,
+                document(json!({
+                    "issuer": ISSUER,
+                    "jwks_uri": JWKS_URI,
+                    "token_endpoint": TOKEN_ENDPOINT,
+                    "id_token_signing_alg_values_supported": ["RS256"],
+                    "response_types_supported": ["code"],
+                })),
+            )
+            .with(JWKS_URI, jwks_response("max-age=7200")),
+    );
+
+    let mut remote_cache = RemoteCache::builder(ISSUER)
+        .fetcher(Arc::clone(&fetcher))
+        .discover()
+        .await
+        .unwrap();
+
+    assert_eq!(remote_cache.uri(), JWKS_URI);
+    assert_eq!(remote_cache.issuer(), Some(ISSUER));
+    assert_eq!(remote_cache.token_endpoint(), Some(TOKEN_ENDPOINT));
+    assert_eq!(remote_cache.userinfo_endpoint(), None);
+    assert_eq!(remote_cache.supported_algorithms(), ["RS256"]);
+    assert!(remote_cache.keys().is_empty());
+
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+    let requests = fetcher.requests.lock().unwrap().clone();
+    assert_eq!(requests, vec![DISCOVERY_URI, JWKS_URI]);
+}
+
+#[tokio::test]
+/// Documents which are unusable, or are for another issuer, should be
+/// rejected.
+async fn test_invalid_documents() {
+    let documents = [
+        json!({ "issuer": ISSUER }),
+        json!({ "issuer": "https://evil.example.com", "jwks_uri": JWKS_URI }),
+    ];
+
+    for body in documents {
+        let fetcher = MockFetcher::default().with(DISCOVERY_URI, document(body));
+
+        let err = RemoteCache::builder(ISSUER)
+            .fetcher(fetcher)
+            .discover()
+            .await
+            .err()
+            .unwrap();
+
+        assert!(matches!(err, Error::invalid_discovery_document { .. }));
+    }
+}
+
+#[tokio::test]
+/// Discovered `jwks_uri`s are held to the same (`https` only) standard.
+async fn test_insecure_jwks_uri() {
+    let fetcher = MockFetcher::default().with(
+        DISCOVERY_URI,
+        document(json!({
+            "issuer": ISSUER,
+            "jwks_uri": "http://keys.example.com/oauth2/v3/certs",
+        })),
+    );
+
+    let err = RemoteCache::builder(ISSUER)
+        .fetcher(fetcher)
+        .discover()
+        .await
+        .err()
+        .unwrap();
+
+    assert_eq!(err, Error::invalid_uri);
+}
+
+#[tokio::test]
+/// The provider configuration document should survive a snapshot.
+async fn test_snapshot() {
+    let fetcher = MockFetcher::default().with(
+        DISCOVERY_URI,
+        document(json!({ "issuer": ISSUER, "jwks_uri": JWKS_URI })),
+    );
+
+    let remote_cache = RemoteCache::builder(ISSUER)
+        .fetcher(fetcher)
+        .discover()
+        .await
+        .unwrap();
+
+    let bytes = remote_cache.snapshot().to_vec().unwrap();
+    let snapshot = Snapshot::from_slice(&bytes).unwrap();
+
+    let mut restored = RemoteCache::builder(JWKS_URI)
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+    restored.restore(snapshot).unwrap();
+
+    assert_eq!(restored.provider_metadata(), remote_cache.provider_metadata());
+}
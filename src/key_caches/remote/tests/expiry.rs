@@ -0,0 +1,71 @@
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "chrono")]
+use chrono::TimeZone;
+#[cfg(feature = "chrono")]
+use chrono::Utc;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::time::now;
+use crate::time::Expiry;
+
+#[test]
+/// The [`Expiry`] of a cache should mirror its `u64` expiry time.
+fn test_expiry() {
+    let mut remote_cache = remote_cache();
+    assert_eq!(remote_cache.expiry(), None);
+
+    remote_cache.set_expiry(Some(Expiry::at(1_000_000_000)));
+    assert_eq!(remote_cache.expiry_time(), &Some(1_000_000_000));
+
+    remote_cache.extend_expiry(Duration::from_secs(3600));
+    assert_eq!(remote_cache.expiry(), Some(Expiry::at(1_000_003_600)));
+    assert!(!remote_cache.is_cache_fresh());
+
+    *remote_cache.expiry_time_mut() = Some(u64::MAX);
+    remote_cache.extend_expiry(Duration::from_secs(3600));
+    assert!(remote_cache.expiry().unwrap().is_never());
+
+    remote_cache.set_expiry(Some(Expiry::after(Duration::from_secs(3600))));
+    assert!(remote_cache.is_cache_fresh());
+
+    remote_cache.set_expiry(None);
+    remote_cache.extend_expiry(Duration::from_secs(3600));
+    assert_eq!(remote_cache.expiry_time(), &None);
+}
+
+#[test]
+/// An [`Expiry`] should convert to (and from) the time types without any
+/// epoch math.
+fn test_conversions() {
+    let time = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+
+    let expiry = Expiry::from(time);
+    assert_eq!(expiry.timestamp(), 1_000_000_000);
+    assert_eq!(expiry.system_time(), Some(time));
+    #[cfg(feature = "chrono")]
+    {
+        let date_time = Utc.timestamp_opt(1_000_000_000, 0).unwrap();
+        assert_eq!(expiry, Expiry::from(date_time));
+        assert_eq!(expiry.date_time(), Some(date_time));
+    }
+    assert!(expiry.is_expired());
+    assert_eq!(expiry.remaining(), Duration::ZERO);
+
+    // Times before the Unix-Epoch are clamped to it.
+    let time = UNIX_EPOCH - Duration::from_secs(1);
+    assert_eq!(Expiry::from(time), Expiry::at(0));
+
+    let mut expiry = Expiry::never();
+    assert_eq!(expiry.system_time(), None);
+    #[cfg(feature = "chrono")]
+    assert_eq!(expiry.date_time(), None);
+    assert!(!expiry.is_expired());
+
+    expiry.set(now() + 3600);
+    expiry.extend(Duration::from_secs(3600));
+    assert!(expiry.remaining() > Duration::from_secs(3600));
+    assert!(expiry.system_time().unwrap() > SystemTime::now());
+}
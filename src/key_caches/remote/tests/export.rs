@@ -0,0 +1,88 @@
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::DecodingKey;
+
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::GOOGLE_JWK_URI;
+
+fn key(kid: &str) -> Key {
+    Key {
+        e: "AQAB".into(),
+        kty: KeyType::RSA,
+        alg: Some(Algorithm::RS256),
+        n: "qR7fa5Gb2rhy".into(),
+        kid: kid.into(),
+        r#use: Use::sig,
+        key_ops: None,
+        x5u: None,
+        x5c: None,
+        x5t: None,
+        x5t_s256: None,
+        crv: None,
+        x: None,
+        y: None,
+    }
+}
+
+fn remote_cache(kids: &[&str]) -> RemoteCache {
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+
+    for kid in kids {
+        let key = key(kid);
+        let decoding_key = DecodingKey::from_rsa_components(&key.n, &key.e)
+            .unwrap();
+        remote_cache
+            .keys_mut()
+            .insert(key.kid.clone(), (key, decoding_key));
+    }
+
+    remote_cache
+}
+
+#[test]
+/// Exports must be ordered by `kid`, regardless of insertion order.
+fn test_export_is_ordered_by_kid() {
+    let remote_cache = remote_cache(&["c", "a", "b"]);
+
+    let kids = remote_cache
+        .export()
+        .keys
+        .into_iter()
+        .map(|Key { kid, .. }| kid)
+        .collect::<Vec<_>>();
+
+    assert_eq!(kids, vec!["a", "b", "c"]);
+}
+
+#[test]
+/// Two caches containing the same keys must serialize to the exact same
+/// output.
+fn test_export_is_stable() {
+    let first = remote_cache(&["c", "a", "b"]);
+    let second = remote_cache(&["b", "c", "a"]);
+
+    let first = serde_json::to_string(&first.export_stamped(Some(1))).unwrap();
+    let second =
+        serde_json::to_string(&second.export_stamped(Some(1))).unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+/// Absent metadata should be omitted from the serialized output.
+fn test_export_stamped_omits_missing_metadata() {
+    let remote_cache = remote_cache(&["a"]);
+
+    let stamped = serde_json::to_value(remote_cache.export_stamped(None))
+        .unwrap();
+
+    assert!(stamped.get("nbf").is_none());
+    assert!(stamped.get("exp").is_none());
+    assert!(stamped.get("keys").is_some());
+}
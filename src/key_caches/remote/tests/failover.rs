@@ -0,0 +1,150 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::key_caches::remote::failover::FailoverEvent;
+use crate::key_caches::remote::failover::FailoverPolicy;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::prelude::Error;
+
+const US_URI: &str = "https://us.login.example.com/certs";
+const EU_URI: &str = "https://eu.login.example.com/certs";
+
+/// A [`JwksFetcher`] serving every `uri`, except for the ones which are down.
+#[derive(Default)]
+struct RegionFetcher {
+    down: Mutex<BTreeSet<String>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl RegionFetcher {
+    fn set_down(&self, uri: &str, down: bool) {
+        let mut regions = self.down.lock().unwrap();
+
+        match down {
+            true => regions.insert(uri.into()),
+            false => regions.remove(uri),
+        };
+    }
+
+    fn take_requests(&self) -> Vec<String> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for RegionFetcher {
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        let uri = uri.to_string();
+        self.requests.lock().unwrap().push(uri.clone());
+
+        match self.down.lock().unwrap().contains(&uri) {
+            true => Err(Error::unable_to_fetch_keys {
+                message: "Region is down.".into(),
+            }),
+            false => Ok(jwks_response("max-age=7200")),
+        }
+    }
+}
+
+fn remote_cache(
+    fetcher: &Arc<RegionFetcher>,
+    probe_interval: Duration,
+) -> (RemoteCache, Arc<Mutex<Vec<FailoverEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+
+    let remote_cache = RemoteCache::builder(US_URI)
+        .fallback_uri(EU_URI)
+        .fetcher(Arc::clone(fetcher))
+        .failover_policy(FailoverPolicy {
+            failure_threshold: 2,
+            probe_interval,
+        })
+        .on_failover(move |event| sink.lock().unwrap().push(event.clone()))
+        .build()
+        .unwrap();
+
+    (remote_cache, events)
+}
+
+#[tokio::test]
+/// The cache should only fail over after sustained failures, and should then
+/// stop fetching from the failing endpoint.
+async fn test_failover() {
+    let fetcher = Arc::new(RegionFetcher::default());
+    fetcher.set_down(US_URI, true);
+
+    let (mut remote_cache, events) =
+        remote_cache(&fetcher, Duration::from_secs(3600));
+
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.active_uri(), US_URI);
+    assert!(events.lock().unwrap().is_empty());
+
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.active_uri(), EU_URI);
+    assert_eq!(
+        *events.lock().unwrap(),
+        [FailoverEvent::FailedOver {
+            from: US_URI.parse().unwrap(),
+            to: EU_URI.parse().unwrap(),
+        }],
+    );
+
+    fetcher.take_requests();
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(fetcher.take_requests(), [EU_URI]);
+}
+
+#[tokio::test]
+/// The cache should fail back once the preferred endpoint has recovered.
+async fn test_failback() {
+    let fetcher = Arc::new(RegionFetcher::default());
+    fetcher.set_down(US_URI, true);
+
+    let (mut remote_cache, events) = remote_cache(&fetcher, Duration::ZERO);
+
+    remote_cache.refresh().await.unwrap();
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.active_uri(), EU_URI);
+
+    fetcher.set_down(US_URI, false);
+    remote_cache.refresh().await.unwrap();
+
+    assert_eq!(remote_cache.active_uri(), US_URI);
+    assert_eq!(
+        events.lock().unwrap().last(),
+        Some(&FailoverEvent::FailedBack {
+            from: EU_URI.parse().unwrap(),
+            to: US_URI.parse().unwrap(),
+        }),
+    );
+}
+
+#[tokio::test]
+/// Without a policy, the preferred endpoint should always be tried first.
+async fn test_no_policy() {
+    let fetcher = Arc::new(RegionFetcher::default());
+    fetcher.set_down(US_URI, true);
+
+    let mut remote_cache = RemoteCache::builder(US_URI)
+        .fallback_uri(EU_URI)
+        .fetcher(Arc::clone(&fetcher))
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        remote_cache.refresh().await.unwrap();
+    }
+
+    assert_eq!(remote_cache.active_uri(), US_URI);
+    assert_eq!(fetcher.take_requests()[4..], [US_URI, EU_URI]);
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use http::StatusCode;
+
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::status_response;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+
+const PRIMARY_URI: &str = "https://login.example.com/certs";
+const EU_URI: &str = "https://eu.login.example.com/certs";
+const US_URI: &str = "https://us.login.example.com/certs";
+
+fn remote_cache(fetcher: &Arc<MockFetcher>) -> RemoteCache {
+    RemoteCache::builder(PRIMARY_URI)
+        .fallback_uri(EU_URI)
+        .fallback_uri(US_URI)
+        .fetcher(Arc::clone(fetcher))
+        .build()
+        .unwrap()
+}
+
+fn requests(fetcher: &MockFetcher) -> Vec<String> {
+    fetcher.requests.lock().unwrap().clone()
+}
+
+#[tokio::test]
+/// Mirrors should not be fetched from while the primary `uri` works.
+async fn test_primary_succeeds() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(PRIMARY_URI, jwks_response("max-age=7200"))
+            .with(EU_URI, jwks_response("max-age=7200")),
+    );
+
+    remote_cache(&fetcher).refresh().await.unwrap();
+
+    assert_eq!(requests(&fetcher), vec![PRIMARY_URI]);
+}
+
+#[tokio::test]
+/// Mirrors should be fetched from, in order, until one of them succeeds.
+async fn test_falls_through_to_mirror() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(PRIMARY_URI, status_response(StatusCode::NOT_FOUND, &[]))
+            .with(US_URI, jwks_response("max-age=7200")),
+    );
+
+    let mut remote_cache = remote_cache(&fetcher);
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+    assert_eq!(requests(&fetcher), vec![PRIMARY_URI, EU_URI, US_URI]);
+}
+
+#[tokio::test]
+/// If every `uri` fails, the error of the last one should be returned.
+async fn test_all_fail() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(PRIMARY_URI, status_response(StatusCode::NOT_FOUND, &[]))
+            .with(EU_URI, status_response(StatusCode::BAD_GATEWAY, &[]))
+            .with(US_URI, status_response(StatusCode::FORBIDDEN, &[])),
+    );
+
+    let err = remote_cache(&fetcher).refresh().await.unwrap_err();
+
+    assert_eq!(err, Error::unexpected_status { status: 403 });
+}
+
+#[test]
+/// Mirrors are held to the same (`https` only) standard as the primary
+/// `uri`.
+fn test_insecure_mirror() {
+    let err = RemoteCache::builder(PRIMARY_URI)
+        .fallback_uri("http://eu.login.example.com/certs")
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err, Error::invalid_uri);
+}
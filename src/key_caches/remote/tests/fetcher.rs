@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use http::header::AUTHORIZATION;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use http::header::COOKIE;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use http::header::USER_AGENT;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use http::HeaderMap;
+use http::StatusCode;
+
+use crate::key_caches::remote::config::RedirectPolicy;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::key_caches::remote::fetcher::without_credentials;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+
+const URI: &str = "https://idp.example.com/certs";
+const CDN_URI: &str = "https://cdn.example.com/certs";
+
+fn redirect(location: &str) -> JwksResponse {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(http::header::LOCATION, location.parse().unwrap());
+
+    JwksResponse {
+        status: StatusCode::FOUND,
+        headers,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+/// Refreshing should go through the configured [`JwksFetcher`], and parse
+/// both the keys and the `max-age` directive of the response.
+async fn test_refresh_with_custom_fetcher() {
+    let fetcher = MockFetcher::default()
+        .with(URI, jwks_response("public, max-age=7200, must-revalidate"));
+
+    let mut remote_cache =
+        RemoteCache::builder(URI).fetcher(fetcher).build().unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+    assert!(remote_cache.expiry_time().is_some());
+    assert!(remote_cache.is_cache_fresh());
+}
+
+#[tokio::test]
+/// Redirects are followed when allowed by the [`RedirectPolicy`].
+async fn test_refresh_follows_redirects() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(URI, redirect("https://cdn.example.com/certs"))
+            .with(CDN_URI, jwks_response("max-age=7200")),
+    );
+
+    let mut remote_cache = RemoteCache::builder(URI)
+        .fetcher(fetcher.clone())
+        .redirect_policy(RedirectPolicy::Limited(1))
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+    assert_eq!(*fetcher.requests.lock().unwrap(), vec![URI, CDN_URI]);
+}
+
+#[tokio::test]
+/// Redirects are followed by default.
+async fn test_refresh_follows_redirects_by_default() {
+    let fetcher = MockFetcher::default()
+        .with(URI, redirect("/moved"))
+        .with("https://idp.example.com/moved", redirect(CDN_URI))
+        .with(CDN_URI, jwks_response("max-age=7200"));
+
+    let mut remote_cache =
+        RemoteCache::builder(URI).fetcher(fetcher).build().unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+}
+
+#[tokio::test]
+/// Redirect loops, too many hops, and downgrades to `http` are rejected.
+async fn test_refresh_rejects_bad_redirects() {
+    let cases = [
+        (
+            MockFetcher::default()
+                .with(URI, redirect(CDN_URI))
+                .with(CDN_URI, redirect(URI)),
+            RedirectPolicy::Limited(3),
+            Error::redirect_loop,
+        ),
+        (
+            MockFetcher::default()
+                .with(URI, redirect(CDN_URI))
+                .with(CDN_URI, redirect("/elsewhere")),
+            RedirectPolicy::Limited(1),
+            Error::too_many_redirects { limit: 1 },
+        ),
+        (
+            MockFetcher::default()
+                .with(URI, redirect("http://cdn.example.com/certs")),
+            RedirectPolicy::default(),
+            Error::invalid_uri,
+        ),
+    ];
+
+    for (fetcher, redirect_policy, expected) in cases {
+        let err = RemoteCache::builder(URI)
+            .fetcher(fetcher)
+            .redirect_policy(redirect_policy)
+            .build()
+            .unwrap()
+            .refresh()
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, expected);
+    }
+}
+
+#[tokio::test]
+/// Credentials should only be sent to the origin of the `JWK` endpoint, and
+/// never along with a redirect to another origin.
+async fn test_redirects_drop_credentials() {
+    let fetcher = Arc::new(
+        MockFetcher::default()
+            .with(URI, redirect("/moved"))
+            .with("https://idp.example.com/moved", redirect(CDN_URI))
+            .with(CDN_URI, jwks_response("max-age=7200")),
+    );
+
+    let mut remote_cache = RemoteCache::builder(URI)
+        .fetcher(fetcher.clone())
+        .header(AUTHORIZATION, "Bearer secret")
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert_eq!(fetcher.requests.lock().unwrap().len(), 3);
+    assert_eq!(*fetcher.anonymous_requests.lock().unwrap(), vec![CDN_URI]);
+}
+
+#[test]
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+/// The [`HyperFetcher`](`crate::key_caches::remote::fetcher::HyperFetcher`)
+/// should not send any credentials along with anonymous requests.
+fn test_without_credentials() {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, "Bearer secret".parse().unwrap());
+    headers.insert(COOKIE, "session=secret".parse().unwrap());
+    headers.insert(USER_AGENT, "webcipher".parse().unwrap());
+
+    let headers = without_credentials(&headers);
+    assert!(!headers.contains_key(AUTHORIZATION));
+    assert!(!headers.contains_key(COOKIE));
+    assert!(headers.contains_key(USER_AGENT));
+}
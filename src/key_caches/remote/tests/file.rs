@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::key_caches::remote::jwks::KeySet;
+use crate::key_caches::remote::tests::claims;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+/// A unique path in the temporary directory, with the given prefix.
+fn temp_path(prefix: &str) -> PathBuf {
+    let name = format!("{}-{}.json", prefix, uuid::Uuid::new_v4());
+
+    std::env::temp_dir().join(name)
+}
+
+/// Write a `JWK` set containing the public halves of the bundled test
+/// key-pairs at the given indices.
+fn write_jwks(path: &PathBuf, indices: &[usize]) {
+    let keys = indices.iter().map(|index| KEY_PAIRS[*index].key()).collect();
+    let jwks = serde_json::to_vec(&KeySet { keys }).unwrap();
+
+    std::fs::write(path, jwks).unwrap();
+}
+
+#[test]
+/// A cache loaded from a file should verify tokens right away.
+fn test_from_file() {
+    let path = temp_path("jwks");
+    write_jwks(&path, &[0]);
+
+    let remote_cache = RemoteCache::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(remote_cache.uri().scheme_str(), Some("file"));
+    assert!(remote_cache.is_cache_fresh());
+    assert!(!remote_cache.needs_refresh());
+    assert!(remote_cache.decrypt::<Value, _>(sign(&claims())).is_ok());
+}
+
+#[test]
+/// Paths which need to be percent-encoded should round-trip.
+fn test_from_file_special_characters() {
+    let path = temp_path("jwks with spaces & ünïcode");
+    write_jwks(&path, &[0]);
+
+    let remote_cache = RemoteCache::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(remote_cache.unwrap().kids().collect::<Vec<_>>(), [KID]);
+}
+
+#[tokio::test]
+/// `file:///` `uri`s should be accepted, and refreshing should re-read the
+/// file.
+async fn test_refresh() {
+    let path = temp_path("jwks");
+    write_jwks(&path, &[0]);
+
+    let uri = format!("file://{}", path.display());
+    let mut remote_cache = RemoteCache::new(uri).unwrap();
+    assert!(remote_cache.key(KID).is_none());
+
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [KID]);
+
+    write_jwks(&path, &[1]);
+    remote_cache.refresh().await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        remote_cache.kids().collect::<Vec<_>>(),
+        [KEY_PAIRS[1].kid],
+    );
+}
+
+#[tokio::test]
+/// Missing files should fail exactly like unreachable endpoints, and leave
+/// the previous keys in place.
+async fn test_missing_file() {
+    let path = temp_path("missing");
+
+    assert!(matches!(
+        RemoteCache::from_file(&path),
+        Err(Error::unable_to_fetch_keys { .. }),
+    ));
+
+    write_jwks(&path, &[0]);
+    let mut remote_cache = RemoteCache::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(remote_cache.refresh().await.is_err());
+    assert!(remote_cache.key(KID).is_some());
+}
+
+#[test]
+/// Files on other hosts, and invalid documents, should be rejected.
+fn test_invalid() {
+    assert!(matches!(
+        RemoteCache::new("file://example.com/keys.json"),
+        Err(Error::invalid_uri),
+    ));
+
+    let path = temp_path("invalid");
+    std::fs::write(&path, "not json").unwrap();
+
+    let result = RemoteCache::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(result.is_err());
+}
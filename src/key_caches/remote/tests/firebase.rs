@@ -0,0 +1,64 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::firebase::FirebaseClaims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::prelude::Error;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// The issuer of a project should be derived from its id, while its keys are
+/// shared by every project.
+fn test_for_firebase() {
+    use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+    use crate::key_caches::remote::RemoteCache;
+
+    let remote_cache = RemoteCache::for_firebase("my-project").unwrap();
+
+    assert_eq!(remote_cache.uri(), FIREBASE_JWK_URI);
+    assert_eq!(
+        remote_cache.issuer(),
+        Some("https://securetoken.google.com/my-project"),
+    );
+    assert_eq!(remote_cache.token_endpoint(), None);
+}
+
+#[test]
+/// The claims of a `Firebase` token should be deserialized, and only be
+/// accepted by the project that they were issued by, and for.
+fn test_firebase_claims() {
+    let token = sign(&json!({
+        "iss": "https://securetoken.google.com/my-project",
+        "aud": "my-project",
+        "auth_time": 1_700_000_000u64,
+        "user_id": "Xx0uid",
+        "sub": "Xx0uid",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "email": "user@example.com",
+        "email_verified": true,
+        "admin": true,
+        "firebase": {
+            "identities": {
+                "google.com": ["1234567890"],
+                "email": ["user@example.com"],
+            },
+            "sign_in_provider": "google.com",
+        },
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<FirebaseClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.auth_time, 1_700_000_000);
+    assert_eq!(claims.firebase.sign_in_provider, "google.com");
+    assert_eq!(claims.firebase.identities["email"], ["user@example.com"]);
+    assert_eq!(claims.firebase.tenant, None);
+    assert_eq!(claims.extra["admin"], true);
+
+    assert_eq!(claims.validate("my-project"), Ok(()));
+
+    let err = claims.validate("other-project").unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(..)));
+}
@@ -0,0 +1,52 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::github_actions::GitHubActionsClaims;
+use crate::key_caches::remote::github_actions::GITHUB_ACTIONS_ISSUER;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[test]
+/// A workload identity token (as documented by `GitHub`) should deserialize,
+/// including its `ref` claim.
+fn test_github_actions_claims() {
+    let token = sign(&json!({
+        "jti": "example-id",
+        "sub": "repo:octo-org/octo-repo:environment:prod",
+        "environment": "prod",
+        "aud": "https://github.com/octo-org",
+        "ref": "refs/heads/main",
+        "sha": "example-sha",
+        "repository": "octo-org/octo-repo",
+        "repository_owner": "octo-org",
+        "actor_id": "12",
+        "repository_visibility": "private",
+        "repository_id": "74",
+        "repository_owner_id": "65",
+        "run_id": "example-run-id",
+        "run_number": "10",
+        "run_attempt": "2",
+        "runner_environment": "github-hosted",
+        "actor": "octocat",
+        "workflow": "example-workflow",
+        "head_ref": "",
+        "base_ref": "",
+        "event_name": "workflow_dispatch",
+        "ref_type": "branch",
+        "job_workflow_ref": "octo-org/octo-automation/.github/workflows/oidc.yml@refs/heads/main",
+        "iss": GITHUB_ACTIONS_ISSUER,
+        "nbf": 1_632_492_967u64,
+        "exp": 20_000_000_000u64,
+        "iat": 1_632_493_867u64,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<GitHubActionsClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.iss, GITHUB_ACTIONS_ISSUER);
+    assert_eq!(claims.repository, "octo-org/octo-repo");
+    assert_eq!(claims.git_ref, "refs/heads/main");
+    assert_eq!(claims.workflow, "example-workflow");
+    assert_eq!(claims.environment.as_deref(), Some("prod"));
+    assert_eq!(claims.workflow_ref, None);
+}
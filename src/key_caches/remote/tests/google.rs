@@ -0,0 +1,64 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::google::GoogleClaims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[test]
+/// An `ID` token with only the `openid` scope (i.e., without any profile or
+/// email claim) should deserialize.
+fn test_google_claims_minimal() {
+    let token = sign(&json!({
+        "iss": "https://accounts.google.com",
+        "azp": "1234987819200.apps.googleusercontent.com",
+        "aud": "1234987819200.apps.googleusercontent.com",
+        "sub": "10769150350006150715113082367",
+        "iat": 1_353_601_026u64,
+        "exp": 20_000_000_000u64,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<GoogleClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.sub, "10769150350006150715113082367");
+    assert_eq!(claims.email, None);
+    assert_eq!(claims.picture, None);
+    assert!(claims.extra.is_empty());
+}
+
+#[test]
+/// An `ID` token with the `email` and `profile` scopes should deserialize,
+/// keeping unrecognized claims.
+fn test_google_claims_profile() {
+    let token = sign(&json!({
+        "iss": "https://accounts.google.com",
+        "azp": "1234987819200.apps.googleusercontent.com",
+        "aud": "1234987819200.apps.googleusercontent.com",
+        "sub": "10769150350006150715113082367",
+        "at_hash": "HK6E_P6Dh8Y93mRNtsDB1Q",
+        "hd": "example.com",
+        "email": "jsmith@example.com",
+        "email_verified": true,
+        "iat": 1_353_601_026u64,
+        "exp": 20_000_000_000u64,
+        "nonce": "0394852-3190485-2490358",
+        "name": "John Smith",
+        "picture": "https://lh3.googleusercontent.com/a/photo.jpg",
+        "given_name": "John",
+        "family_name": "Smith",
+        "nbf": 1_353_600_726u64,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<GoogleClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.hd.as_deref(), Some("example.com"));
+    assert_eq!(claims.email_verified, Some(true));
+    assert_eq!(
+        claims.picture.unwrap().host(),
+        Some("lh3.googleusercontent.com"),
+    );
+    assert_eq!(claims.locale, None);
+    assert_eq!(claims.extra["nbf"], 1_353_600_726u64);
+}
@@ -0,0 +1,104 @@
+use http::header::CONTENT_TYPE;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::key_caches::remote::fetcher::read_body;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+fn remote_cache(fetcher: MockFetcher) -> RemoteCache {
+    RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+/// `JSON` media types (including `+json` suffixed ones) should be accepted.
+async fn test_json_content_types() {
+    for content_type in ["application/json", "application/jwk-set+json"] {
+        let mut response = jwks_response("max-age=7200");
+        response
+            .headers
+            .insert(CONTENT_TYPE, content_type.parse().unwrap());
+
+        let fetcher = MockFetcher::default().with(GOOGLE_JWK_URI, response);
+        let mut remote_cache = remote_cache(fetcher);
+        remote_cache.refresh().await.unwrap();
+
+        assert!(remote_cache.keys().contains_key(KID));
+    }
+}
+
+#[tokio::test]
+/// Responses without a `JSON` content-type should be rejected.
+async fn test_unexpected_content_type() {
+    let mut response = jwks_response("max-age=7200");
+    response
+        .headers
+        .insert(CONTENT_TYPE, "text/html".parse().unwrap());
+
+    let fetcher = MockFetcher::default().with(GOOGLE_JWK_URI, response);
+    let err = remote_cache(fetcher).refresh().await.unwrap_err();
+
+    assert_eq!(err, Error::unexpected_content_type {
+        content_type: "text/html".into(),
+    });
+}
+
+#[tokio::test]
+/// Responses exceeding the maximum body size should be rejected.
+async fn test_response_too_large() {
+    let fetcher = MockFetcher::default()
+        .with(GOOGLE_JWK_URI, jwks_response("max-age=7200"));
+
+    let err = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher)
+        .max_body_size(16)
+        .build()
+        .unwrap()
+        .refresh()
+        .await
+        .unwrap_err();
+
+    assert_eq!(err, Error::response_too_large { limit: 16 });
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[tokio::test]
+/// The [`HyperFetcher`](`crate::key_caches::remote::fetcher::HyperFetcher`)
+/// should stop reading bodies as soon as they exceed the limit.
+async fn test_read_body_limit() {
+    // A declared length is checked upfront...
+    let body = hyper::Body::from("{\"keys\":[]}");
+    let err = read_body(body, 8).await.unwrap_err();
+    assert_eq!(err, Error::response_too_large { limit: 8 });
+
+    // ...while streamed bodies are checked chunk by chunk.
+    let (mut sender, body) = hyper::Body::channel();
+    tokio::spawn(async move {
+        sender.send_data("{\"keys\"".into()).await.unwrap();
+        let _ = sender.send_data(":[]}".into()).await;
+    });
+    let err = read_body(body, 8).await.unwrap_err();
+    assert_eq!(err, Error::response_too_large { limit: 8 });
+
+    let body = hyper::Body::from("{\"keys\":[]}");
+    let body = read_body(body, 64).await.unwrap();
+    assert_eq!(&body[..], b"{\"keys\":[]}");
+}
+
+#[tokio::test]
+/// Unparsable bodies should be rejected as unrecognized responses.
+async fn test_unparsable_body() {
+    let mut response = jwks_response("max-age=7200");
+    response.body = "{\"keys\":[".into();
+
+    let fetcher = MockFetcher::default().with(GOOGLE_JWK_URI, response);
+    let err = remote_cache(fetcher).refresh().await.unwrap_err();
+
+    assert!(matches!(err, Error::unrecognized_response { .. }));
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::Header;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::header_cache::HeaderCache;
+use crate::key_caches::header_cache::LruHeaderCache;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+/// An [`LruHeaderCache`] which counts its hits.
+struct CountingCache {
+    inner: LruHeaderCache,
+    hits: AtomicUsize,
+}
+
+impl HeaderCache for CountingCache {
+    fn get(&self, segment: &str) -> Option<Header> {
+        let header = self.inner.get(segment)?;
+        self.hits.fetch_add(1, Ordering::SeqCst);
+
+        Some(header)
+    }
+
+    fn insert(&self, segment: &str, header: Header) {
+        self.inner.insert(segment, header);
+    }
+}
+
+fn header(kid: &str) -> Header {
+    Header {
+        kid: Some(kid.into()),
+        ..Header::default()
+    }
+}
+
+#[test]
+/// The least recently used header should be evicted once the cache is full.
+fn test_lru_eviction() {
+    let cache = LruHeaderCache::new(2);
+    assert!(cache.is_empty());
+
+    cache.insert("a", header("a"));
+    cache.insert("b", header("b"));
+    assert_eq!(cache.get("a").unwrap().kid.as_deref(), Some("a"));
+
+    // `b` is now the least recently used.
+    cache.insert("c", header("c"));
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("b").is_none());
+    assert!(cache.get("a").is_some());
+    assert!(cache.get("c").is_some());
+
+    // Re-inserting replaces the header, without evicting anything.
+    cache.insert("c", header("d"));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("c").unwrap().kid.as_deref(), Some("d"));
+
+    let cache = LruHeaderCache::new(0);
+    cache.insert("a", header("a"));
+    assert!(cache.get("a").is_none());
+    assert_eq!(cache.capacity(), 0);
+}
+
+#[test]
+/// Tokens sharing the same header should only have it decoded once.
+fn test_memoized_headers() {
+    let counting = Arc::new(CountingCache {
+        inner: LruHeaderCache::new(16),
+        hits: AtomicUsize::new(0),
+    });
+
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    assert!(remote_cache.header_cache().is_none());
+    remote_cache.set_header_cache(Some(counting.clone()));
+
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+
+    for _ in 0..3 {
+        let data = remote_cache
+            .decrypt_unchecked::<Value, _>(token.clone())
+            .unwrap();
+        assert_eq!(data.claims, claims);
+    }
+    assert_eq!(counting.hits.load(Ordering::SeqCst), 2);
+
+    let mut buffer = Vec::new();
+    let data = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap();
+    assert_eq!(data, claims);
+    assert_eq!(counting.hits.load(Ordering::SeqCst), 3);
+
+    // The signature is still verified for a memoized header.
+    let forged = KEY_PAIRS[1].sign(&claims).unwrap();
+    let (_, rest) = forged.split_once('.').unwrap();
+    let (header, _) = token.split_once('.').unwrap();
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(format!("{}.{}", header, rest))
+        .unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+    assert_eq!(counting.hits.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+/// Compressed headers should be rejected every time, and never memoized.
+fn test_compressed_headers() {
+    let cache = Arc::new(LruHeaderCache::new(16));
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    remote_cache.set_header_cache(Some(cache.clone()));
+
+    let header = json!({ "alg": "RS256", "typ": "JWT", "zip": "DEF" });
+    let header = URL_SAFE_NO_PAD.encode(header.to_string());
+    let token = format!("{}.e30.c2ln", header);
+
+    for _ in 0..2 {
+        let err = remote_cache
+            .decrypt_unchecked::<Value, _>(token.clone())
+            .unwrap_err();
+        assert_eq!(err, Error::compressed_token);
+    }
+    assert!(cache.is_empty());
+}
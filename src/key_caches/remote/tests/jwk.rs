@@ -0,0 +1,93 @@
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::jwk::JwkSet;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::key::Curve;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+fn jwk(value: Value) -> Jwk {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+/// A cache built from a `JwkSet` should verify tokens right away, and export
+/// the same set back.
+fn test_from_jwk_set() {
+    let jwk_set = JwkSet {
+        keys: vec![Jwk::try_from(KEY_PAIRS[0].key()).unwrap()],
+    };
+
+    let remote_cache = RemoteCache::from_jwk_set(&jwk_set);
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [KID]);
+    assert!(remote_cache.decrypt::<Value, _>(sign(&claims)).is_ok());
+    assert_eq!(remote_cache.export_jwk_set(), jwk_set);
+}
+
+#[test]
+/// Keys which cannot be converted (or used) should be filtered out.
+fn test_from_jwk_set_filters() {
+    let mut no_use = serde_json::to_value(KEY_PAIRS[2].key()).unwrap();
+    no_use.as_object_mut().unwrap().remove("use");
+
+    let valid = serde_json::to_value(KEY_PAIRS[0].key()).unwrap();
+    let symmetric = json!({ "kty": "oct", "kid": "4", "use": "sig", "k": "" });
+
+    let jwk_set = JwkSet {
+        keys: [valid, no_use, symmetric].map(jwk).to_vec(),
+    };
+
+    let remote_cache = RemoteCache::from_jwk_set(&jwk_set);
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [KID]);
+}
+
+#[test]
+/// Every member should survive a round-trip through a `Jwk`, and the result
+/// should serialize exactly as the original.
+fn test_roundtrip() {
+    let value = json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+        "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+        "use": "sig",
+        "alg": "ES256",
+        "kid": "1",
+        "key_ops": ["verify"],
+        "x5t": "dGhpcyBpcyBhIFNIQTEgdGVzdA",
+    });
+
+    let key = Key::try_from(jwk(value.clone())).unwrap();
+    assert_eq!(key.kty, KeyType::EC);
+    assert_eq!(key.crv, Some(Curve::P256));
+    assert_eq!(key, serde_json::from_value::<Key>(value.clone()).unwrap());
+
+    let jwk = Jwk::try_from(key).unwrap();
+    assert_eq!(serde_json::to_value(jwk).unwrap(), value);
+}
+
+#[test]
+/// Keys which `jsonwebtoken` cannot represent should be rejected.
+fn test_unsupported() {
+    let mut key = KEY_PAIRS[0].key();
+    key.kty = KeyType::Other("x-vendor".into());
+    assert!(matches!(Jwk::try_from(key), Err(Error::invalid_jwk { .. })));
+
+    let mut key = KEY_PAIRS[0].key();
+    key.kty = KeyType::OKP;
+    key.crv = Some(Curve::X25519);
+    key.x = Some("x".into());
+    assert!(matches!(Jwk::try_from(key), Err(Error::invalid_jwk { .. })));
+
+    let mut key = KEY_PAIRS[0].key();
+    key.kty = KeyType::EC;
+    assert!(matches!(Jwk::try_from(key), Err(Error::invalid_jwk { .. })));
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeSet;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use jsonwebtoken::Algorithm;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::fetch::parse_keys;
+use crate::key_caches::remote::key::Curve;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyOperation;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::x509::leaf_public_key;
+use crate::key_caches::remote::x509::PublicKey;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+/// A self-signed certificate of the first bundled key-pair.
+const X5C_RSA: &[u8] = include_bytes!("certs/x5c_rsa.der");
+
+/// A self-signed certificate of a `P-256` key.
+const X5C_EC: &[u8] = include_bytes!("certs/x5c_ec.der");
+
+#[test]
+/// Every member of the RFC should be parsed, and serialized back under the
+/// same name.
+fn test_full_key() {
+    let value = json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+        "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+        "use": "sig",
+        "kid": "1",
+        "key_ops": ["verify", "customOp"],
+        "x5u": "https://example.com/cert.pem",
+        "x5c": ["MIIC+DCCAeCgAwIBAgIJBIGjYW6hFpn2MA0GCSqGSIb3DQEBBQUA"],
+        "x5t": "dGhpcyBpcyBhIFNIQTEgdGVzdA",
+        "x5t#S256": "dGhpcyBpcyBhIFNIQTI1NiB0ZXN0",
+    });
+
+    let key = serde_json::from_value::<Key>(value.clone()).unwrap();
+    let key_ops = BTreeSet::from([
+        KeyOperation::Verify,
+        KeyOperation::Other("customOp".into()),
+    ]);
+
+    assert_eq!(key.kty, KeyType::EC);
+    assert_eq!(key.crv, Some(Curve::P256));
+    assert_eq!(key.key_ops, Some(key_ops));
+    assert_eq!(key.x5c.as_ref().map(Vec::len), Some(1));
+    assert!(key.x5t_s256.is_some());
+    assert!(key.allows(&KeyOperation::Verify));
+    assert!(!key.allows(&KeyOperation::Sign));
+
+    let mut serialized = serde_json::to_value(&key).unwrap();
+    serialized.as_object_mut().unwrap().retain(|_, value| !value.is_null());
+    serialized.as_object_mut().unwrap().remove("e");
+    serialized.as_object_mut().unwrap().remove("n");
+    assert_eq!(serialized, value);
+}
+
+#[test]
+/// Absent members should neither be required, nor serialized.
+fn test_minimal_key() {
+    let key = KEY_PAIRS[0].key();
+    let serialized = serde_json::to_value(&key).unwrap();
+
+    assert!(key.allows(&KeyOperation::Sign));
+    assert!(serialized.get("key_ops").is_none());
+    assert!(serialized.get("x5t#S256").is_none());
+    assert_eq!(serde_json::from_value::<Key>(serialized).unwrap(), key);
+}
+
+#[test]
+/// Keys which may not be used to verify signatures should be filtered out.
+fn test_key_ops_filter() {
+    let mut verify = serde_json::to_value(KEY_PAIRS[0].key()).unwrap();
+    verify["key_ops"] = json!(["verify"]);
+
+    let mut encrypt = serde_json::to_value(KEY_PAIRS[1].key()).unwrap();
+    encrypt["key_ops"] = json!(["encrypt"]);
+
+    let body = json!({ "keys": [verify, encrypt] }).to_string();
+    let cache = parse_keys(body.as_bytes(), JwksFormat::Auto).unwrap();
+
+    assert_eq!(cache.keys().collect::<Vec<_>>(), [KEY_PAIRS[0].kid]);
+}
+
+#[test]
+/// Unknown uses and key-types should be kept (instead of failing to parse the
+/// key), and such keys should be filtered out explicitly.
+fn test_unknown_use_and_kty() {
+    let mut tls = serde_json::to_value(KEY_PAIRS[0].key()).unwrap();
+    tls["use"] = json!("tls");
+
+    let mut vendor = serde_json::to_value(KEY_PAIRS[1].key()).unwrap();
+    vendor["kty"] = json!("x-vendor");
+
+    let key = serde_json::from_value::<Key>(tls.clone()).unwrap();
+    assert_eq!(key.r#use, Use::Other("tls".into()));
+    assert_eq!(serde_json::to_value(&key).unwrap(), tls);
+
+    let key = serde_json::from_value::<Key>(vendor.clone()).unwrap();
+    assert_eq!(key.kty, KeyType::Other("x-vendor".into()));
+    assert_eq!(key.kty.as_str(), "x-vendor");
+
+    let valid = serde_json::to_value(KEY_PAIRS[2].key()).unwrap();
+    let body = json!({ "keys": [tls, vendor, valid] }).to_string();
+    let cache = parse_keys(body.as_bytes(), JwksFormat::Auto).unwrap();
+
+    assert_eq!(cache.keys().collect::<Vec<_>>(), [KEY_PAIRS[2].kid]);
+}
+
+#[test]
+/// Keys without an `alg` should be kept, and used to verify `RS256` tokens.
+fn test_missing_alg() {
+    let mut key = KEY_PAIRS[0].key();
+    key.alg = None;
+
+    let mut value = serde_json::to_value(&key).unwrap();
+    value.as_object_mut().unwrap().remove("alg");
+    assert_eq!(serde_json::from_value::<Key>(value).unwrap(), key);
+
+    let remote_cache = RemoteCache::from_keys(vec![key]);
+    let token = KEY_PAIRS[0]
+        .sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+    let data = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+
+    assert_eq!(data.claims["sub"], "user");
+    assert_eq!(data.header.alg, Algorithm::RS256);
+}
+
+#[test]
+/// Keys which only publish an `x5c` chain should be completed from the leaf
+/// certificate, and used to verify tokens.
+fn test_x5c_rsa() {
+    let mut key = KEY_PAIRS[0].key();
+    key.n = String::new();
+    key.e = String::new();
+    key.x5c = Some(vec![STANDARD.encode(X5C_RSA)]);
+
+    let body = json!({ "keys": [key] }).to_string();
+    let remote_cache = RemoteCache::from_jwks_json(&body).unwrap();
+
+    let cached = remote_cache.key(KEY_PAIRS[0].kid).unwrap();
+    assert_eq!(cached.n, KEY_PAIRS[0].key().n);
+    assert_eq!(cached.e, "AQAB");
+
+    let token = KEY_PAIRS[0]
+        .sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+    let data = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+    assert_eq!(data.claims["sub"], "user");
+}
+
+#[test]
+/// The point of an `EC` leaf certificate should be read, along with its
+/// curve.
+fn test_x5c_ec() {
+    let x5c = [STANDARD.encode(X5C_EC)];
+
+    assert_eq!(
+        leaf_public_key(&x5c).unwrap(),
+        PublicKey::Ec {
+            crv: Curve::P256,
+            x: "DzYyB6AfMDX4PD1WV_2ONazsLQQfOLGxHl-iF-MjdkY".into(),
+            y: "8uNK6wq9oFL-cfCxcuwp3eeq5L_oI7-Y2AbQCE2kEms".into(),
+        },
+    );
+
+    let mut key = KEY_PAIRS[0].key();
+    key.x5c = Some(x5c.to_vec());
+    assert!(matches!(
+        key.complete_from_x5c(),
+        Err(Error::invalid_certificate { .. }),
+    ));
+}
+
+#[test]
+/// Malformed chains should be rejected, and such keys filtered out.
+fn test_x5c_invalid() {
+    let truncated = STANDARD.encode(&X5C_RSA[..X5C_RSA.len() / 2]);
+
+    for x5c in [vec![], vec!["not base64".into()], vec![truncated]] {
+        assert!(matches!(
+            leaf_public_key(&x5c),
+            Err(Error::invalid_certificate { .. }),
+        ));
+
+        let mut key = KEY_PAIRS[0].key();
+        key.n = String::new();
+        key.x5c = Some(x5c);
+
+        let body = json!({ "keys": [key, KEY_PAIRS[1].key()] }).to_string();
+        let cache = parse_keys(body.as_bytes(), JwksFormat::Auto).unwrap();
+        assert_eq!(cache.keys().collect::<Vec<_>>(), [KEY_PAIRS[1].kid]);
+    }
+}
+
+#[test]
+/// A set of certificates which are all unusable (e.g., `EC` ones) should be
+/// rejected, instead of leaving the cache empty.
+fn test_x5c_unusable() {
+    let key = json!({
+        "kty": "EC",
+        "use": "sig",
+        "kid": "ec",
+        "x5c": [STANDARD.encode(X5C_EC)],
+    });
+
+    let body = json!({ "keys": [key] }).to_string();
+    assert!(matches!(
+        parse_keys(body.as_bytes(), JwksFormat::Auto),
+        Err(Error::unable_to_fetch_keys { message })
+            if message.contains("`EC`"),
+    ));
+
+    // An empty set is still accepted.
+    let body = json!({ "keys": [] }).to_string();
+    let cache = parse_keys(body.as_bytes(), JwksFormat::Auto).unwrap();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,84 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::keycloak::KeycloakClaims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// The keys, the issuer, and the endpoints of a realm should be derived from
+/// the base `uri` of the server and the name of the realm.
+fn test_for_keycloak() {
+    use crate::key_caches::remote::RemoteCache;
+
+    let remote_cache =
+        RemoteCache::for_keycloak("https://sso.example.com/auth/", "acme")
+            .unwrap();
+
+    assert_eq!(
+        remote_cache.uri(),
+        "https://sso.example.com/auth/realms/acme/protocol/openid-connect/certs",
+    );
+    assert_eq!(
+        remote_cache.issuer(),
+        Some("https://sso.example.com/auth/realms/acme"),
+    );
+    assert_eq!(
+        remote_cache.token_endpoint(),
+        Some("https://sso.example.com/auth/realms/acme/protocol/openid-connect/token"),
+    );
+
+    let err = RemoteCache::for_keycloak("http://keycloak:8080", "acme");
+    assert!(err.is_err());
+}
+
+#[test]
+/// Realm and client roles should be deserialized, and be checkable.
+fn test_keycloak_claims() {
+    let token = sign(&json!({
+        "iss": "https://sso.example.com/realms/acme",
+        "sub": "f:1234:jane",
+        "aud": ["my-api", "account"],
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "typ": "Bearer",
+        "azp": "my-frontend",
+        "preferred_username": "jane",
+        "realm_access": { "roles": ["offline_access", "staff"] },
+        "resource_access": {
+            "my-api": { "roles": ["admin"] },
+            "account": { "roles": ["manage-account"] },
+        },
+        "tenant": "acme",
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<KeycloakClaims, _>(token)
+        .unwrap();
+
+    let aud = claims.aud.as_ref().unwrap();
+    assert_eq!(aud.as_slice(), ["my-api", "account"]);
+    assert_eq!(claims.azp.as_deref(), Some("my-frontend"));
+    assert_eq!(claims.preferred_username.as_deref(), Some("jane"));
+    assert!(claims.has_realm_role("staff"));
+    assert!(!claims.has_realm_role("admin"));
+    assert!(claims.has_client_role("my-api", "admin"));
+    assert!(!claims.has_client_role("account", "admin"));
+    assert!(!claims.has_client_role("unknown", "admin"));
+    assert_eq!(claims.extra["tenant"], json!("acme"));
+
+    // Service account tokens may carry neither an audience nor any roles.
+    let token = sign(&json!({
+        "iss": "https://sso.example.com/realms/acme",
+        "sub": "service-account",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<KeycloakClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.aud, None);
+    assert!(claims.realm_access.roles.is_empty());
+    assert!(claims.resource_access.is_empty());
+}
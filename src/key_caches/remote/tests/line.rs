@@ -0,0 +1,33 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::line::LineClaims;
+use crate::key_caches::remote::line::LINE_ISSUER;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[test]
+/// An `ID` token (as documented by `LINE`) should deserialize, including its
+/// authentication methods.
+fn test_line_claims() {
+    let token = sign(&json!({
+        "iss": "https://access.line.me",
+        "sub": "U1234567890abcdef1234567890abcdef",
+        "aud": "1234567890",
+        "exp": 20_000_000_000u64,
+        "iat": 1_504_169_092u64,
+        "nonce": "0987654asdf",
+        "amr": ["pwd"],
+        "name": "Taro Line",
+        "picture": "https://sample_line.me/aBcdefg123456",
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<LineClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.iss, LINE_ISSUER);
+    assert_eq!(claims.aud, Audience::from("1234567890"));
+    assert_eq!(claims.amr, ["pwd"]);
+    assert_eq!(claims.email, None);
+}
@@ -1,2 +1,240 @@
+mod apple;
+mod audience;
+mod auth0;
+mod auto_refresh;
+mod builder;
+mod clock;
+mod cognito;
+mod decrypt_borrowed;
+mod decrypt_partial;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
 mod decrypt_unchecked;
+mod discovery;
+mod export;
+mod expiry;
+mod failover;
+mod firebase;
+mod fallback;
+mod fetcher;
+mod github_actions;
+mod google;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+mod file;
+mod header_cache;
+mod hardening;
+mod jwk;
+mod key;
+mod kubernetes;
+mod line;
+mod keycloak;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
 mod new;
+mod nonce;
+mod observer;
+mod normalized;
+mod offload;
+mod okta;
+mod prewarm;
+mod provenance;
+mod redaction;
+mod refresh_ahead;
+mod replay;
+mod retry;
+mod rotation;
+mod snapshot;
+mod standard;
+mod static_keys;
+mod store;
+mod strict_claims;
+mod stale_policy;
+mod thumbprint;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+mod tls;
+mod token_hash;
+mod twitch;
+mod validation;
+mod verification_limit;
+mod well_known;
+mod x509_map;
+
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use jsonwebtoken::DecodingKey;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::jwks::KeySet;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::prelude::GOOGLE_JWK_URI;
+use crate::testing::KEY_PAIRS;
+
+/// The `kid` of the first bundled test key-pair.
+pub(crate) const KID: &str = KEY_PAIRS[0].kid;
+
+/// A time long before the tests are run (i.e., 2001-09-09).
+pub(crate) const T: u64 = 1_000_000_000;
+
+/// A [`RemoteCache`] which contains the public half of the first bundled test
+/// key-pair.
+///
+/// No network requests are performed.
+pub(crate) fn remote_cache() -> RemoteCache {
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+    let key = KEY_PAIRS[0].key();
+    let decoding_key =
+        DecodingKey::from_rsa_components(&key.n, &key.e).unwrap();
+
+    remote_cache
+        .keys_mut()
+        .insert(key.kid.clone(), (key, decoding_key));
+
+    remote_cache
+}
+
+/// Sign the given claims using the first bundled test key-pair.
+pub(crate) fn sign<Claims>(claims: &Claims) -> String
+where
+    Claims: Serialize,
+{
+    KEY_PAIRS[0].sign(claims).unwrap()
+}
+
+/// The claims of a token which never expires.
+pub(crate) fn claims() -> Value {
+    json!({ "sub": "user", "exp": 20_000_000_000u64 })
+}
+
+/// A [`JwksFetcher`] which returns canned responses per `uri`, and records
+/// every `uri` that was requested.
+#[derive(Default)]
+pub(crate) struct MockFetcher {
+    pub(crate) responses: BTreeMap<String, JwksResponse>,
+    pub(crate) requests: Mutex<Vec<String>>,
+    pub(crate) anonymous_requests: Mutex<Vec<String>>,
+}
+
+impl MockFetcher {
+    pub(crate) fn with(mut self, uri: &str, response: JwksResponse) -> Self {
+        self.responses.insert(uri.into(), response);
+        self
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for MockFetcher {
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        let uri = uri.to_string();
+        self.requests.lock().unwrap().push(uri.clone());
+
+        self.responses.get(&uri).cloned().ok_or(
+            crate::prelude::Error::unable_to_fetch_keys {
+                message: "Not found.".into(),
+            },
+        )
+    }
+
+    async fn fetch_anonymous(
+        &self,
+        uri: &http::Uri,
+    ) -> prelude::Result<JwksResponse> {
+        self.anonymous_requests.lock().unwrap().push(uri.to_string());
+        self.fetch(uri).await
+    }
+}
+
+/// A [`JwksFetcher`] which returns the given results in order, regardless of
+/// the requested `uri`.
+#[derive(Default)]
+pub(crate) struct SequenceFetcher {
+    pub(crate) results: Mutex<VecDeque<prelude::Result<JwksResponse>>>,
+}
+
+impl SequenceFetcher {
+    pub(crate) fn new<R>(results: R) -> Self
+    where
+        R: IntoIterator<Item = prelude::Result<JwksResponse>>,
+    {
+        let results = Mutex::new(results.into_iter().collect());
+
+        Self { results }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for SequenceFetcher {
+    async fn fetch(&self, _: &http::Uri) -> prelude::Result<JwksResponse> {
+        self.results.lock().unwrap().pop_front().unwrap_or(Err(
+            crate::prelude::Error::unable_to_fetch_keys {
+                message: "No more results.".into(),
+            },
+        ))
+    }
+}
+
+/// A response with the given status code and headers, and an empty body.
+pub(crate) fn status_response(
+    status: http::StatusCode,
+    headers: &[(http::header::HeaderName, &str)],
+) -> JwksResponse {
+    let headers = headers
+        .iter()
+        .map(|(name, value)| (name.clone(), value.parse().unwrap()))
+        .collect();
+
+    JwksResponse {
+        status,
+        headers,
+        ..Default::default()
+    }
+}
+
+/// A successful response containing the public half of the first bundled test
+/// key-pair.
+pub(crate) fn jwks_response(cache_control: &str) -> JwksResponse {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::CACHE_CONTROL,
+        cache_control.parse().unwrap(),
+    );
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        "application/json; charset=utf-8".parse().unwrap(),
+    );
+
+    JwksResponse {
+        status: http::StatusCode::OK,
+        headers,
+        body: serde_json::to_vec(&KeySet {
+            keys: vec![KEY_PAIRS[0].key()],
+        })
+        .unwrap()
+        .into(),
+    }
+}
+
+/// A [`RemoteCache`] whose (mocked) endpoint serves [`jwks_response`].
+///
+/// No keys are fetched yet.
+pub(crate) fn fetched_remote_cache() -> RemoteCache {
+    let fetcher = MockFetcher::default()
+        .with(GOOGLE_JWK_URI, jwks_response("max-age=7200"));
+
+    RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher)
+        .build()
+        .unwrap()
+}
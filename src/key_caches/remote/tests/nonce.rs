@@ -0,0 +1,50 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::nonce::check_nonce;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::prelude::Error;
+use crate::time::now;
+
+#[test]
+/// Only the tokens which carry the expected `nonce` should be accepted.
+fn test_decrypt_with_nonce() {
+    let mut remote_cache = remote_cache();
+    *remote_cache.expiry_time_mut() = Some(now() + 3600);
+
+    let token = sign(&json!({
+        "exp": 20_000_000_000u64,
+        "nonce": "n-0S6_WzA2Mj",
+    }));
+    let TokenData { claims, .. } = remote_cache
+        .decrypt_with_nonce::<Value, _>(token.as_str(), "n-0S6_WzA2Mj")
+        .unwrap();
+    assert_eq!(claims["nonce"], "n-0S6_WzA2Mj");
+
+    let err = remote_cache
+        .decrypt_with_nonce::<Value, _>(token, "another-nonce")
+        .unwrap_err();
+    assert_eq!(err, Error::nonce_mismatch);
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+    let err = remote_cache
+        .decrypt_with_nonce::<Value, _>(token, "n-0S6_WzA2Mj")
+        .unwrap_err();
+    assert_eq!(err, Error::nonce_mismatch);
+}
+
+#[test]
+/// A `nonce` of any other type than a string should never match.
+fn test_check_nonce() {
+    assert_eq!(check_nonce(&json!({ "nonce": "1" }), "1"), Ok(()));
+    assert_eq!(
+        check_nonce(&json!({ "nonce": 1 }), "1"),
+        Err(Error::nonce_mismatch),
+    );
+    assert_eq!(
+        check_nonce(&json!({ "nonce": ["1"] }), "1"),
+        Err(Error::nonce_mismatch),
+    );
+}
@@ -0,0 +1,84 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::normalized::NormalizedClaims;
+use crate::key_caches::remote::apple::AppleClaims;
+use crate::key_caches::remote::facebook::FacebookClaims;
+use crate::key_caches::remote::google::GoogleClaims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[test]
+/// The claims of each provider should describe the user in the same way.
+fn test_normalized_claims() {
+    let google = sign(&json!({
+        "iss": "https://accounts.google.com",
+        "aud": "1234.apps.googleusercontent.com",
+        "sub": "110169484474386276334",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "email": "user@example.com",
+        "email_verified": true,
+        "name": "Jane Doe",
+        "picture": "https://lh3.googleusercontent.com/a/photo.jpg",
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<GoogleClaims, _>(google)
+        .unwrap();
+    assert_eq!(
+        NormalizedClaims::from(claims),
+        NormalizedClaims {
+            provider: "google".into(),
+            issuer: "https://accounts.google.com".into(),
+            subject: "110169484474386276334".into(),
+            email: Some("user@example.com".into()),
+            email_verified: Some(true),
+            name: Some("Jane Doe".into()),
+            picture: Some(
+                "https://lh3.googleusercontent.com/a/photo.jpg".into(),
+            ),
+        },
+    );
+
+    let apple = sign(&json!({
+        "iss": "https://appleid.apple.com",
+        "aud": "com.example.app",
+        "sub": "001234.abcdef0123456789abcdef0123456789.0123",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "email": "abc123@privaterelay.appleid.com",
+        "email_verified": "true",
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<AppleClaims, _>(apple)
+        .unwrap();
+    let claims = NormalizedClaims::from(claims);
+    assert_eq!(claims.provider, "apple");
+    assert_eq!(claims.email_verified, Some(true));
+    assert_eq!(claims.name, None);
+
+    let facebook = sign(&json!({
+        "iss": "https://www.facebook.com",
+        "aud": "1234567890",
+        "sub": "10229470291284717",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "jti": "a0cd2c1f-d5e4-4b5b-a9f4-0a1a2f0e7f5d",
+        "nonce": "abc123",
+        "email": "user@example.com",
+        "name": "Jane Doe",
+        "given_name": "Jane",
+        "family_name": "Doe",
+        "picture": "https://platform-lookaside.fbsbx.com/photo.jpg",
+        "user_friends": ["10229470291284718"],
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<FacebookClaims, _>(facebook)
+        .unwrap();
+    assert_eq!(claims.extra["user_friends"], json!(["10229470291284718"]));
+    let claims = NormalizedClaims::from(claims);
+    assert_eq!(claims.provider, "facebook");
+    assert_eq!(claims.subject, "10229470291284717");
+    assert_eq!(claims.email_verified, None);
+    assert_eq!(claims.name.as_deref(), Some("Jane Doe"));
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::RemoteCache;
+use crate::observer::CacheObserver;
+use crate::prelude::Error;
+use crate::testing::MockIdp;
+use crate::testing::KEY_PAIRS;
+use crate::testing::MOCK_JWK_URI;
+use crate::time::now;
+
+/// A [`CacheObserver`] which records every event.
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<String>>,
+}
+
+impl RecordingObserver {
+    fn record(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.events.lock().unwrap())
+    }
+}
+
+impl CacheObserver for RecordingObserver {
+    fn on_cache_hit(&self, _: &http::Uri, kid: &str) {
+        self.record(format!("hit {}", kid));
+    }
+
+    fn on_cache_miss(&self, _: &http::Uri, kid: Option<&str>) {
+        self.record(format!("miss {:?}", kid));
+    }
+
+    fn on_refresh_start(&self, uri: &http::Uri) {
+        self.record(format!("refresh {}", uri));
+    }
+
+    fn on_refresh_success(&self, _: &http::Uri) {
+        self.record("refreshed".into());
+    }
+
+    fn on_refresh_failure(&self, _: &http::Uri, _: &Error) {
+        self.record("refresh failed".into());
+    }
+
+    fn on_decrypt_failure(&self, _: &http::Uri, reason: &Error) {
+        self.record(format!("rejected {:?}", reason));
+    }
+}
+
+#[tokio::test]
+/// Hits, misses, refreshes, and rejected tokens should be reported to the
+/// observer of the cache.
+async fn test_observer() {
+    let idp = Arc::new(MockIdp::new());
+    let observer = Arc::new(RecordingObserver::default());
+
+    let mut remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(Arc::clone(&idp))
+        .observer(Arc::clone(&observer))
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(
+        observer.take(),
+        [format!("refresh {}", MOCK_JWK_URI), "refreshed".into()],
+    );
+
+    let token = idp.mint(&json!({ "exp": now() + 3600 })).unwrap();
+    remote_cache.decrypt::<Value, _>(&token).unwrap();
+    assert_eq!(observer.take(), [format!("hit {}", KEY_PAIRS[0].kid)]);
+
+    let token = KEY_PAIRS[1].sign(&json!({ "exp": now() + 3600 })).unwrap();
+    remote_cache.decrypt::<Value, _>(&token).unwrap_err();
+    assert_eq!(
+        observer.take(),
+        [
+            format!("miss {:?}", Some(KEY_PAIRS[1].kid)),
+            "rejected no_corresponding_kid_in_store".into(),
+        ],
+    );
+
+    let token = idp.mint(&json!({ "exp": now() - 3600 })).unwrap();
+    let mut buffer = Vec::new();
+    remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap_err();
+    let events = observer.take();
+    assert_eq!(events.len(), 2);
+    assert!(events[1].starts_with("rejected unable_to_verify_token"));
+
+    remote_cache.set_expiry(Some(0.into()));
+    let token = idp.mint(&json!({ "exp": now() + 3600 })).unwrap();
+    remote_cache.decrypt::<Value, _>(&token).unwrap_err();
+    assert_eq!(observer.take(), ["rejected stale_cache"]);
+    remote_cache
+        .decrypt_with_provenance::<Value, _>(&token)
+        .unwrap_err();
+    remote_cache.decrypt_with_key::<Value, _>(&token).unwrap_err();
+    assert_eq!(observer.take(), ["rejected stale_cache"; 2]);
+
+    idp.set_available(false);
+    remote_cache.refresh().await.unwrap_err();
+    assert_eq!(
+        observer.take(),
+        [format!("refresh {}", MOCK_JWK_URI), "refresh failed".into()],
+    );
+
+    // Without an observer, nothing is reported.
+    remote_cache.set_observer(None);
+    remote_cache.refresh().await.unwrap_err();
+    assert!(observer.take().is_empty());
+}
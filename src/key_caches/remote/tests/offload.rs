@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::thread::ThreadId;
+
+use jsonwebtoken::errors::ErrorKind;
+use serde_json::json;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::key_caches::remote::DEFAULT_BLOCKING_THRESHOLD;
+use crate::prelude::Error;
+use crate::prelude::Timestamp;
+use crate::time::now;
+use crate::time::Clock;
+
+/// A [`Clock`] which records the threads that it is read from (i.e., the
+/// threads that tokens are verified on).
+#[derive(Default)]
+struct ThreadClock {
+    threads: Mutex<Vec<ThreadId>>,
+}
+
+impl Clock for ThreadClock {
+    fn now(&self) -> Timestamp {
+        self.threads.lock().unwrap().push(thread::current().id());
+        now()
+    }
+}
+
+/// A shared [`RemoteCache`], along with the [`ThreadClock`] that it reads.
+fn shared(
+    blocking_threshold: usize,
+) -> (Arc<RwLock<RemoteCache>>, Arc<ThreadClock>) {
+    let clock = Arc::new(ThreadClock::default());
+    let mut remote_cache = remote_cache();
+    remote_cache.set_clock(Some(Arc::clone(&clock) as Arc<dyn Clock>));
+    remote_cache.set_blocking_threshold(blocking_threshold);
+
+    (Arc::new(RwLock::new(remote_cache)), clock)
+}
+
+#[tokio::test]
+/// Tokens signed by large enough keys should be verified on the blocking
+/// thread pool.
+async fn test_offloaded() {
+    let (remote_cache, clock) = shared(DEFAULT_BLOCKING_THRESHOLD);
+    let token = sign(&json!({ "sub": "a", "exp": now() + 3600 }));
+
+    let data = RemoteCache::decrypt_unchecked_offloaded::<Value, _>(
+        &remote_cache,
+        &token,
+    )
+    .await
+    .unwrap();
+    assert_eq!(data.claims["sub"], "a");
+
+    let threads = clock.threads.lock().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_ne!(threads[0], thread::current().id());
+}
+
+#[tokio::test]
+/// Tokens signed by smaller keys should be verified in place.
+async fn test_below_threshold() {
+    let (remote_cache, clock) = shared(usize::MAX);
+    let token = sign(&json!({ "sub": "a", "exp": now() + 3600 }));
+
+    RemoteCache::decrypt_unchecked_offloaded::<Value, _>(&remote_cache, token)
+        .await
+        .unwrap();
+
+    let threads = clock.threads.lock().unwrap();
+    assert_eq!(*threads, [thread::current().id()]);
+}
+
+#[tokio::test]
+/// Offloaded tokens should be rejected exactly as if they were verified in
+/// place.
+async fn test_errors() {
+    let (remote_cache, _) = shared(0);
+
+    let token = sign(&json!({ "exp": now() - 3600 }));
+    let err = RemoteCache::decrypt_unchecked_offloaded::<Value, _>(
+        &remote_cache,
+        &token,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::unable_to_verify_token(error)
+            if *error.kind() == ErrorKind::ExpiredSignature,
+    ));
+
+    let token = sign(&json!({ "exp": now() + 3600 }));
+    remote_cache.write().await.set_expiry(Some(0.into()));
+    let err =
+        RemoteCache::decrypt_offloaded::<Value, _>(&remote_cache, &token)
+            .await
+            .unwrap_err();
+    assert_eq!(err, Error::stale_cache);
+
+    // Tokens which cannot be matched to a key are never offloaded.
+    let token = sign(&json!({ "exp": now() + 3600 }));
+    remote_cache.write().await.keys_mut().clear();
+    let err = RemoteCache::decrypt_unchecked_offloaded::<Value, _>(
+        &remote_cache,
+        &token,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+}
+
+#[test]
+/// The blocking threshold should be configurable on the builder.
+fn test_builder() {
+    let remote_cache = remote_cache();
+    assert_eq!(remote_cache.blocking_threshold(), DEFAULT_BLOCKING_THRESHOLD);
+
+    let remote_cache = RemoteCache::builder("https://example.com/certs")
+        .fetcher(MockFetcher::default())
+        .blocking_threshold(4096)
+        .build()
+        .unwrap();
+    assert_eq!(remote_cache.blocking_threshold(), 4096);
+}
@@ -0,0 +1,54 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::okta::OktaClaims;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// The keys, the issuer, and the endpoints of an authorization server should
+/// be derived from its org and id.
+fn test_for_okta() {
+    use crate::key_caches::remote::RemoteCache;
+
+    let remote_cache =
+        RemoteCache::for_okta("https://example.okta.com/", "default").unwrap();
+
+    assert_eq!(
+        remote_cache.uri(),
+        "https://example.okta.com/oauth2/default/v1/keys",
+    );
+    assert_eq!(
+        remote_cache.issuer(),
+        Some("https://example.okta.com/oauth2/default"),
+    );
+    assert_eq!(
+        remote_cache.userinfo_endpoint(),
+        Some("https://example.okta.com/oauth2/default/v1/userinfo"),
+    );
+}
+
+#[test]
+/// Custom claims should be kept alongside the standard ones.
+fn test_okta_claims() {
+    let token = sign(&json!({
+        "iss": "https://example.okta.com/oauth2/default",
+        "sub": "jane@example.com",
+        "aud": "api://default",
+        "iat": 1_700_000_000u64,
+        "exp": 20_000_000_000u64,
+        "cid": "0oa1example",
+        "uid": "00u1example",
+        "scp": ["openid", "email"],
+        "department": "engineering",
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<OktaClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.cid.as_deref(), Some("0oa1example"));
+    assert_eq!(claims.scp, ["openid", "email"]);
+    assert_eq!(claims.extra["department"], json!("engineering"));
+    assert!(!claims.extra.contains_key("cid"));
+}
@@ -0,0 +1,99 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::prewarm::PrewarmedKeys;
+use crate::key_caches::remote::prewarm::RsaComponents;
+use crate::key_caches::remote::tests::fetched_remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::KID;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[tokio::test]
+/// A worker should verify tokens with the keys of the supervisor, without
+/// fetching them.
+async fn test_roundtrip() {
+    use crate::key_caches::remote::prewarm::PREWARMED_MAGIC;
+    use crate::key_caches::remote::RemoteCache;
+
+    let mut supervisor = fetched_remote_cache();
+    supervisor.refresh().await.unwrap();
+
+    let prewarmed = supervisor.prewarm();
+    assert!(prewarmed.components.contains_key(KID));
+
+    let bytes = prewarmed.to_vec().unwrap();
+    assert!(bytes.starts_with(PREWARMED_MAGIC));
+    assert_eq!(PrewarmedKeys::from_slice(&bytes).unwrap(), prewarmed);
+
+    let worker = RemoteCache::from_prewarmed(prewarmed).unwrap();
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+
+    assert_eq!(worker.export(), supervisor.export());
+    assert_eq!(worker.expiry_time(), supervisor.expiry_time());
+    assert!(worker.is_cache_fresh());
+    assert!(worker.decrypt::<Value, _>(sign(&claims)).is_ok());
+}
+
+#[tokio::test]
+/// Keys without components should be parsed as usual.
+async fn test_missing_components() {
+    let mut supervisor = fetched_remote_cache();
+    supervisor.refresh().await.unwrap();
+
+    let mut prewarmed = supervisor.prewarm();
+    prewarmed.components.clear();
+
+    let mut worker = fetched_remote_cache();
+    worker.restore_prewarmed(prewarmed).unwrap();
+    assert_eq!(worker.export(), supervisor.export());
+}
+
+#[tokio::test]
+/// Components which do not match their key should be ignored, and the keys
+/// of the snapshot should be filtered just like fetched keys.
+async fn test_mismatched_components() {
+    let mut supervisor = fetched_remote_cache();
+    supervisor.refresh().await.unwrap();
+
+    let other = KEY_PAIRS[1].key();
+    let mut prewarmed = supervisor.prewarm();
+    let _ = prewarmed.components.insert(KID.into(), RsaComponents {
+        n: URL_SAFE_NO_PAD.decode(&other.n).unwrap(),
+        e: URL_SAFE_NO_PAD.decode(&other.e).unwrap(),
+    });
+
+    let mut worker = fetched_remote_cache();
+    worker.restore_prewarmed(prewarmed.clone()).unwrap();
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    assert!(worker.decrypt::<Value, _>(sign(&claims)).is_ok());
+
+    for key in prewarmed.snapshot.keys.iter_mut() {
+        key.r#use = Use::enc;
+    }
+
+    let mut worker = fetched_remote_cache();
+    worker.restore_prewarmed(prewarmed).unwrap();
+    assert!(worker.keys().is_empty());
+}
+
+#[tokio::test]
+/// Malformed bytes should be rejected.
+async fn test_invalid() {
+    let mut supervisor = fetched_remote_cache();
+    supervisor.refresh().await.unwrap();
+
+    let bytes = supervisor.prewarm().to_vec().unwrap();
+    let trailing = [&bytes[..], &[0]].concat();
+
+    for bytes in [&bytes[1..], &bytes[..bytes.len() - 1], &trailing] {
+        assert!(matches!(
+            PrewarmedKeys::from_slice(bytes),
+            Err(Error::invalid_snapshot { .. }),
+        ));
+    }
+}
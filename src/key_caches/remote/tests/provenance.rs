@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use jsonwebtoken::Algorithm;
+use serde_json::Value;
+
+use crate::key_caches::remote::provenance::Provenance;
+use crate::key_caches::remote::provenance::Verified;
+use crate::key_caches::remote::provenance::VerifiedToken;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::tests::claims;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::MockIdp;
+use crate::testing::KEY_PAIRS;
+
+async fn setup() -> (Arc<MockIdp>, RemoteCache) {
+    let idp = Arc::new(MockIdp::new());
+    let mut remote_cache = idp.remote_cache().unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    (idp, remote_cache)
+}
+
+#[tokio::test]
+/// Verifications should report the provenance of the key which was used.
+async fn test_decrypt_with_provenance() {
+    let (idp, mut remote_cache) = setup().await;
+    let emergency = &KEY_PAIRS[2];
+    remote_cache
+        .insert_key(emergency.key(), Provenance::Emergency)
+        .unwrap();
+
+    let Verified {
+        token_data,
+        kid,
+        provenance,
+    } = remote_cache
+        .decrypt_with_provenance::<Value, _>(idp.mint(&claims()).unwrap())
+        .unwrap();
+    assert_eq!(token_data.claims, claims());
+    assert_eq!(kid, idp.signing_kid());
+    assert_eq!(provenance, Provenance::Fetched);
+
+    let Verified { kid, provenance, .. } = remote_cache
+        .decrypt_with_provenance::<Value, _>(emergency.sign(&claims()).unwrap())
+        .unwrap();
+    assert_eq!(kid, emergency.kid);
+    assert_eq!(provenance, Provenance::Emergency);
+}
+
+#[tokio::test]
+/// Verifications should return the key which was used, and reject tokens
+/// exactly as `decrypt` does.
+async fn test_decrypt_with_key() {
+    let (idp, mut remote_cache) = setup().await;
+    let pushed = &KEY_PAIRS[1];
+    remote_cache.insert_key(pushed.key(), Provenance::Pushed).unwrap();
+
+    let VerifiedToken {
+        token_data,
+        kid,
+        key,
+    } = remote_cache
+        .decrypt_with_key::<Value, _>(idp.mint(&claims()).unwrap())
+        .unwrap();
+    assert_eq!(token_data.claims, claims());
+    assert_eq!(kid, idp.signing_kid());
+    assert_eq!(key, KEY_PAIRS[0].key());
+
+    let VerifiedToken { kid, key, .. } = remote_cache
+        .decrypt_with_key::<Value, _>(pushed.sign(&claims()).unwrap())
+        .unwrap();
+    assert_eq!(kid, pushed.kid);
+    assert_eq!(key, pushed.key());
+
+    let error = remote_cache
+        .decrypt_with_key::<Value, _>(KEY_PAIRS[2].sign(&claims()).unwrap())
+        .unwrap_err();
+    assert_eq!(error, Error::no_corresponding_kid_in_store);
+
+    *remote_cache.expiry_time_mut() = None;
+    let error = remote_cache
+        .decrypt_with_key::<Value, _>(idp.mint(&claims()).unwrap())
+        .unwrap_err();
+    assert_eq!(error, Error::stale_cache);
+}
+
+#[tokio::test]
+/// Keys which were not fetched should survive refreshes, unless the fetched
+/// `JWK` set replaces them.
+async fn test_refresh() {
+    let (idp, mut remote_cache) = setup().await;
+
+    let pushed = KEY_PAIRS[1].kid;
+    let emergency = KEY_PAIRS[2].kid;
+    remote_cache
+        .insert_key(KEY_PAIRS[1].key(), Provenance::Pushed)
+        .unwrap();
+    remote_cache
+        .insert_key(KEY_PAIRS[2].key(), Provenance::Emergency)
+        .unwrap();
+
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.provenance(pushed), Some(Provenance::Pushed));
+    assert_eq!(
+        remote_cache.provenance(emergency),
+        Some(Provenance::Emergency),
+    );
+
+    // The pushed key is now published.
+    assert_eq!(idp.rotate(), pushed);
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.provenance(pushed), Some(Provenance::Fetched));
+    assert_eq!(
+        remote_cache.provenance(emergency),
+        Some(Provenance::Emergency),
+    );
+
+    assert!(remote_cache.remove_key(emergency).is_some());
+    assert_eq!(remote_cache.provenance(emergency), None);
+
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.provenance(emergency), None);
+}
+
+#[test]
+/// Keys given upfront should be pinned.
+fn test_from_keys() {
+    let remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+
+    assert_eq!(
+        remote_cache.provenance(KEY_PAIRS[0].kid),
+        Some(Provenance::Pinned),
+    );
+    assert_eq!(remote_cache.provenance("unknown"), None);
+}
+
+#[test]
+/// Unusable keys should be rejected.
+fn test_insert_unusable_key() {
+    let mut remote_cache = RemoteCache::from_keys(vec![]);
+
+    let mut key = KEY_PAIRS[0].key();
+    key.alg = Some(Algorithm::HS256);
+
+    assert_eq!(
+        remote_cache.insert_key(key, Provenance::Pushed),
+        Err(Error::unusable_key),
+    );
+    assert!(remote_cache.keys().is_empty());
+}
+
+#[tokio::test]
+/// Snapshots should keep the provenance of every key.
+async fn test_snapshot() {
+    let (idp, mut remote_cache) = setup().await;
+    remote_cache
+        .insert_key(KEY_PAIRS[2].key(), Provenance::Pinned)
+        .unwrap();
+
+    let bytes = remote_cache.snapshot().to_vec().unwrap();
+    let snapshot = Snapshot::from_slice(&bytes).unwrap();
+
+    let mut restored = idp.remote_cache().unwrap();
+    restored.restore(snapshot).unwrap();
+
+    assert_eq!(
+        restored.provenance(KEY_PAIRS[2].kid),
+        Some(Provenance::Pinned),
+    );
+    assert_eq!(
+        restored.provenance(KEY_PAIRS[0].kid),
+        Some(Provenance::Fetched),
+    );
+}
@@ -0,0 +1,78 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tls::Identity;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+const SECRET: &str = "super-secret-claim-value";
+
+/// Assert that neither the `Display` nor the `Debug` output of the given
+/// error contains any (8 character) fragment of the given token.
+fn assert_redacted(error: &Error, token: &str) {
+    let formatted = format!("{} {:?}", error, error);
+
+    assert!(!formatted.contains(SECRET), "{}", formatted);
+
+    for fragment in token.as_bytes().windows(8) {
+        let fragment = std::str::from_utf8(fragment).unwrap();
+        assert!(!formatted.contains(fragment), "{}", formatted);
+    }
+}
+
+#[test]
+/// `JSON` errors quote the offending value; it must not end up in the error.
+fn test_claim_type_mismatch() {
+    #[derive(Debug, Deserialize)]
+    struct Claims {
+        #[allow(dead_code)]
+        sub: u64,
+    }
+
+    let token = sign(&json!({ "sub": SECRET, "exp": 20_000_000_000u64 }));
+    let err = remote_cache()
+        .decrypt_unchecked::<Claims, _>(token.as_str())
+        .unwrap_err();
+
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+    assert_redacted(&err, &token);
+}
+
+#[test]
+/// Malformed segments must not end up in the error either.
+fn test_malformed_tokens() {
+    let token = sign(&json!({ "sub": SECRET, "exp": 20_000_000_000u64 }));
+    let (header, rest) = token.split_once('.').unwrap();
+    let (_, signature) = rest.split_once('.').unwrap();
+
+    let payloads = [
+        URL_SAFE_NO_PAD.encode(format!("{{\"sub\": {}", SECRET)),
+        URL_SAFE_NO_PAD.encode([SECRET.as_bytes(), &[0xff, 0xfe]].concat()),
+        format!("{}!", SECRET),
+    ];
+
+    for payload in payloads {
+        let token = format!("{}.{}.{}", header, payload, signature);
+        let err = remote_cache()
+            .decrypt_unchecked::<serde_json::Value, _>(token.as_str())
+            .unwrap_err();
+
+        assert_redacted(&err, &token);
+    }
+}
+
+#[test]
+/// Credential headers and private keys must never be printed.
+fn test_debug_output() {
+    let builder = RemoteCache::builder(GOOGLE_JWK_URI)
+        .header("authorization", format!("Bearer {}", SECRET));
+    assert!(!format!("{:?}", builder).contains(SECRET));
+
+    let identity = Identity::from_pem("certificate", SECRET);
+    assert!(!format!("{:?}", identity).contains(SECRET));
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::key_caches::remote::policy::RefreshAheadPolicy;
+use crate::key_caches::remote::tests::remote_cache;
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+#[test]
+/// The time until expiry should saturate at zero once the keys have expired.
+fn test_time_until_expiry() {
+    let mut remote_cache = remote_cache();
+    assert_eq!(remote_cache.time_until_expiry(), None);
+
+    *remote_cache.expiry_time_mut() = Some(now() + 3600);
+    let time_until_expiry = remote_cache.time_until_expiry().unwrap();
+    assert!(time_until_expiry > Duration::from_secs(3590));
+
+    *remote_cache.expiry_time_mut() = Some(now() - 1);
+    assert_eq!(remote_cache.time_until_expiry(), Some(Duration::ZERO));
+}
+
+#[test]
+/// A fresh cache should only need a refresh once the threshold has elapsed.
+fn test_needs_refresh() {
+    let mut remote_cache = remote_cache();
+    assert!(remote_cache.needs_refresh());
+
+    // 90% of the lifetime has elapsed.
+    remote_cache.refreshed_at = Some(now() - 900);
+    *remote_cache.expiry_time_mut() = Some(now() + 100);
+    assert!(!remote_cache.needs_refresh());
+
+    *remote_cache.refresh_ahead_policy_mut() =
+        Some(RefreshAheadPolicy::default());
+    assert!(remote_cache.needs_refresh());
+
+    // 50% of the lifetime has elapsed.
+    remote_cache.refreshed_at = Some(now() - 500);
+    *remote_cache.expiry_time_mut() = Some(now() + 500);
+    assert!(!remote_cache.needs_refresh());
+}
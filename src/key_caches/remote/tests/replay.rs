@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::T;
+use crate::key_caches::replay::MemoryReplayStore;
+use crate::key_caches::replay::ReplayGuard;
+use crate::key_caches::replay::ReplayStore;
+use crate::prelude::Error;
+use crate::time::now;
+use crate::time::ManualClock;
+
+#[test]
+/// Every token should only be accepted once, whether it carries a `jti` or
+/// not.
+fn test_replayed_token() {
+    let store = Arc::new(MemoryReplayStore::default());
+    let mut remote_cache = remote_cache();
+    remote_cache.set_replay_guard(Some(ReplayGuard::new(Arc::clone(&store))));
+
+    let with_jti = sign(&json!({
+        "iss": "https://appleid.apple.com",
+        "jti": "a-0",
+        "exp": 20_000_000_000u64,
+    }));
+    let without_jti = sign(&json!({
+        "iss": "https://appleid.apple.com",
+        "exp": 20_000_000_000u64,
+    }));
+
+    for token in [&with_jti, &without_jti] {
+        let data = remote_cache.decrypt_unchecked::<Value, _>(token.as_str());
+        assert_eq!(data.unwrap().claims["iss"], "https://appleid.apple.com");
+
+        let err = remote_cache
+            .decrypt_unchecked::<Value, _>(token.as_str())
+            .unwrap_err();
+        assert_eq!(err, Error::replayed_token);
+    }
+
+    // The same `jti` of another issuer is another token.
+    let token = sign(&json!({
+        "iss": "https://www.facebook.com",
+        "jti": "a-0",
+        "exp": 20_000_000_000u64,
+    }));
+    remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+    assert_eq!(store.len(), 3);
+}
+
+#[test]
+/// Tokens decrypted into a borrowed buffer should be recorded just the same.
+fn test_replayed_borrowed_token() {
+    let store = Arc::new(MemoryReplayStore::default());
+    let mut remote_cache = remote_cache();
+    remote_cache.set_replay_guard(Some(ReplayGuard::new(Arc::clone(&store))));
+
+    let token = sign(&json!({
+        "iss": "https://appleid.apple.com",
+        "exp": 20_000_000_000u64,
+    }));
+
+    let mut buffer = Vec::new();
+    let claims = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap();
+    assert_eq!(claims["iss"], "https://appleid.apple.com");
+
+    let err = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap_err();
+    assert_eq!(err, Error::replayed_token);
+
+    let err = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap_err();
+    assert_eq!(err, Error::replayed_token);
+    assert_eq!(store.len(), 1);
+}
+
+#[test]
+/// Distinct pairs of `iss` and `jti` claims should never be recorded as the
+/// same token.
+fn test_ambiguous_claims() {
+    let store = Arc::new(MemoryReplayStore::default());
+    let guard = ReplayGuard::new(Arc::clone(&store));
+
+    for (iss, jti) in [("a b", "c"), ("a", "b c"), ("a", "1:a b")] {
+        let claims = json!({ "iss": iss, "jti": jti, "exp": T + 60 });
+        guard.check("a.b.c", &claims, T).unwrap();
+    }
+
+    assert_eq!(store.len(), 3);
+}
+
+#[test]
+/// Tokens which fail verification should not be recorded.
+fn test_unverified_token() {
+    let store = Arc::new(MemoryReplayStore::default());
+    let mut remote_cache = remote_cache();
+    remote_cache.set_replay_guard(Some(ReplayGuard::new(Arc::clone(&store))));
+
+    let token = sign(&json!({ "jti": "a-0", "exp": now() - 3600 }));
+    let err = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+    assert!(store.is_empty());
+}
+
+#[test]
+/// Expired keys should be evicted, and then be accepted again.
+fn test_memory_replay_store() {
+    let store = MemoryReplayStore::default();
+
+    assert!(store.insert("a", T + 3600, T).unwrap());
+    assert!(!store.insert("a", T + 3600, T).unwrap());
+
+    assert!(store.insert("b", T - 1, T).unwrap());
+    assert!(store.insert("b", T + 3600, T).unwrap());
+    assert_eq!(store.len(), 2);
+
+    // Only the given time decides which keys have expired.
+    assert!(store.insert("a", T + 7200, T + 3601).unwrap());
+    assert_eq!(store.len(), 1);
+
+    // Tokens without an `exp` could never be forgotten.
+    let guard = ReplayGuard::new(store);
+    let err = guard.check("a.b.c", &json!({ "jti": "c" }), T).unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+}
+
+#[test]
+/// Recorded tokens should expire according to the clock of the cache, not
+/// the system clock.
+fn test_replay_clock() {
+    let store = Arc::new(MemoryReplayStore::default());
+    let mut remote_cache = remote_cache();
+    remote_cache.set_clock(Some(Arc::new(ManualClock::new(T))));
+    remote_cache.set_replay_guard(Some(ReplayGuard::new(Arc::clone(&store))));
+
+    let token = sign(&json!({ "jti": "a-0", "exp": T + 60 }));
+    remote_cache
+        .decrypt_unchecked::<Value, _>(token.as_str())
+        .unwrap();
+
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token.as_str())
+        .unwrap_err();
+    assert_eq!(err, Error::replayed_token);
+    assert_eq!(store.len(), 1);
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::RETRY_AFTER;
+use http::StatusCode;
+
+use crate::key_caches::remote::config::RetryPolicy;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::status_response;
+use crate::key_caches::remote::tests::SequenceFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+fn retry_policy(max_attempts: usize) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+        jitter: true,
+        honor_retry_after: true,
+    }
+}
+
+fn network_error() -> Error {
+    Error::unable_to_fetch_keys {
+        message: "Connection reset.".into(),
+    }
+}
+
+#[tokio::test]
+/// Network errors and `5xx`/`429` responses should be retried.
+async fn test_retry_until_success() {
+    let fetcher = Arc::new(SequenceFetcher::new([
+        Err(network_error()),
+        Ok(status_response(StatusCode::SERVICE_UNAVAILABLE, &[(
+            RETRY_AFTER,
+            "0",
+        )])),
+        Ok(status_response(StatusCode::TOO_MANY_REQUESTS, &[])),
+        Ok(jwks_response("max-age=7200")),
+    ]));
+
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher.clone())
+        .retry_policy(retry_policy(4))
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.keys().contains_key(KID));
+    assert_eq!(fetcher.remaining(), 0);
+}
+
+#[tokio::test]
+/// Once all attempts are exhausted, the last error should be returned.
+async fn test_retry_exhausted() {
+    let fetcher = Arc::new(SequenceFetcher::new([
+        Err(network_error()),
+        Ok(status_response(StatusCode::BAD_GATEWAY, &[])),
+        Ok(jwks_response("max-age=7200")),
+    ]));
+
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher.clone())
+        .retry_policy(retry_policy(2))
+        .build()
+        .unwrap();
+    let err = remote_cache.refresh().await.unwrap_err();
+
+    assert_eq!(err, Error::unexpected_status { status: 502 });
+    assert_eq!(fetcher.remaining(), 1);
+}
+
+#[tokio::test]
+/// Client errors (other than `429`) should not be retried.
+async fn test_no_retry_on_client_error() {
+    let fetcher = Arc::new(SequenceFetcher::new([
+        Ok(status_response(StatusCode::NOT_FOUND, &[])),
+        Ok(jwks_response("max-age=7200")),
+    ]));
+
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher.clone())
+        .retry_policy(retry_policy(3))
+        .build()
+        .unwrap();
+    let err = remote_cache.refresh().await.unwrap_err();
+
+    assert_eq!(err, Error::unexpected_status { status: 404 });
+    assert_eq!(fetcher.remaining(), 1);
+}
+
+#[test]
+/// Delays should grow exponentially, be capped, and honor `Retry-After`.
+fn test_retry_delays() {
+    let retry_policy = RetryPolicy {
+        max_attempts: 10,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(1),
+        jitter: false,
+        honor_retry_after: true,
+    };
+
+    assert_eq!(retry_policy.delay(1, None), Duration::from_millis(100));
+    assert_eq!(retry_policy.delay(2, None), Duration::from_millis(200));
+    assert_eq!(retry_policy.delay(3, None), Duration::from_millis(400));
+    assert_eq!(retry_policy.delay(9, None), Duration::from_secs(1));
+    assert_eq!(
+        retry_policy.delay(1, Some(Duration::from_millis(300))),
+        Duration::from_millis(300)
+    );
+    assert_eq!(
+        retry_policy.delay(1, Some(Duration::from_secs(60))),
+        Duration::from_secs(1)
+    );
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::TryRecvError;
+
+use crate::key_caches::remote::tests::T;
+use crate::key_caches::remote::RemoteCache;
+use crate::testing::MockIdp;
+use crate::testing::KEY_PAIRS;
+use crate::testing::MOCK_JWK_URI;
+use crate::time::ManualClock;
+
+fn kids(kids: &[&str]) -> BTreeSet<String> {
+    kids.iter().map(|kid| kid.to_string()).collect()
+}
+
+#[tokio::test]
+/// Refreshes which change the keys (or the expiry time) should be broadcast
+/// to every subscriber.
+async fn test_rotations() {
+    let idp = Arc::new(MockIdp::new());
+    let clock = Arc::new(ManualClock::new(T));
+
+    let mut remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(Arc::clone(&idp))
+        .clock(Arc::clone(&clock))
+        .build()
+        .unwrap();
+    let mut rotations = remote_cache.subscribe_rotations();
+
+    remote_cache.refresh().await.unwrap();
+    let event = rotations.try_recv().unwrap();
+    assert!(event.is_rotation());
+    assert_eq!(event.uri, MOCK_JWK_URI);
+    assert_eq!(event.added, kids(&[KEY_PAIRS[0].kid]));
+    assert!(event.removed.is_empty());
+    assert_eq!(event.previous_expiry, None);
+    assert_eq!(event.expiry, remote_cache.expiry());
+
+    // Nothing changed.
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(rotations.try_recv(), Err(TryRecvError::Empty));
+
+    // Only the expiry time changed.
+    clock.advance(std::time::Duration::from_secs(60));
+    remote_cache.refresh().await.unwrap();
+    let event = rotations.try_recv().unwrap();
+    assert!(!event.is_rotation());
+    assert_eq!(event.expiry.unwrap().timestamp(), T + 60 + 86_400 - 3600);
+
+    let signing = idp.rotate();
+    let mut late = remote_cache.subscribe_rotations();
+    remote_cache.refresh().await.unwrap();
+    let event = rotations.try_recv().unwrap();
+    assert_eq!(event.added, kids(&[signing]));
+    assert!(event.removed.is_empty());
+    assert_eq!(late.try_recv().unwrap(), event);
+
+    idp.rotate();
+    remote_cache.refresh().await.unwrap();
+    let event = rotations.try_recv().unwrap();
+    assert_eq!(event.removed, kids(&[KEY_PAIRS[0].kid]));
+
+    // Failed refreshes change nothing.
+    idp.set_available(false);
+    remote_cache.refresh().await.unwrap_err();
+    assert_eq!(rotations.try_recv(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,118 @@
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::snapshot::SNAPSHOT_MAGIC;
+use crate::key_caches::remote::snapshot::SNAPSHOT_VERSION;
+use crate::key_caches::remote::tests::fetched_remote_cache;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+#[tokio::test]
+/// A restored snapshot should contain the exact same keys and times.
+async fn test_roundtrip() {
+    let mut original = fetched_remote_cache();
+    original.refresh().await.unwrap();
+
+    let bytes = original.snapshot().to_vec().unwrap();
+    let snapshot = Snapshot::from_slice(&bytes).unwrap();
+
+    let mut restored = fetched_remote_cache();
+    restored.restore(snapshot).unwrap();
+
+    assert!(restored.keys().contains_key(KID));
+    assert_eq!(restored.export(), original.export());
+    assert_eq!(restored.expiry_time(), original.expiry_time());
+    assert!(restored.is_cache_fresh());
+}
+
+#[tokio::test]
+/// Unknown fields (e.g., written by a newer version of this crate) should be
+/// ignored.
+async fn test_unknown_fields() {
+    let mut original = fetched_remote_cache();
+    original.refresh().await.unwrap();
+
+    let mut value = serde_json::to_value(original.snapshot()).unwrap();
+    value["written_by"] = json!("webcipher 9.9.9");
+    let bytes = serde_json::to_vec(&value).unwrap();
+
+    assert_eq!(Snapshot::from_slice(&bytes).unwrap(), original.snapshot());
+}
+
+#[test]
+/// Unknown versions and missing magic strings should be rejected.
+fn test_invalid_snapshots() {
+    let snapshot = |magic: &str, version: u32| {
+        json!({
+            "magic": magic,
+            "version": version,
+            "uri": GOOGLE_JWK_URI,
+            "keys": [],
+        })
+    };
+
+    for value in [
+        snapshot(SNAPSHOT_MAGIC, SNAPSHOT_VERSION + 1),
+        snapshot(SNAPSHOT_MAGIC, 0),
+        snapshot("something else", SNAPSHOT_VERSION),
+        Value::Null,
+    ] {
+        let bytes = serde_json::to_vec(&value).unwrap();
+        let err = Snapshot::from_slice(&bytes).unwrap_err();
+
+        assert!(matches!(err, Error::invalid_snapshot { .. }));
+    }
+
+    let bytes = serde_json::to_vec(&snapshot(SNAPSHOT_MAGIC, 1)).unwrap();
+    assert!(Snapshot::from_slice(&bytes).is_ok());
+}
+
+#[test]
+/// Snapshots of a cache with a different `uri` should be rejected.
+fn test_restore_different_uri() {
+    let mut snapshot = fetched_remote_cache().snapshot();
+    snapshot.uri = "https://other.example.com/certs".into();
+
+    let err = fetched_remote_cache().restore(snapshot).unwrap_err();
+
+    assert!(matches!(err, Error::invalid_snapshot { .. }));
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[tokio::test]
+/// A cache generated from a snapshot should verify tokens without fetching.
+async fn test_from_snapshot() {
+    use crate::key_caches::remote::tests::sign;
+
+    let mut original = fetched_remote_cache();
+    original.refresh().await.unwrap();
+
+    let bytes = original.snapshot().to_vec().unwrap();
+    let snapshot = Snapshot::from_slice(&bytes).unwrap();
+    let restored = RemoteCache::from_snapshot(snapshot).unwrap();
+
+    assert_eq!(restored.uri(), original.uri());
+    assert_eq!(restored.export(), original.export());
+    assert_eq!(restored.expiry_time(), original.expiry_time());
+    assert!(restored.is_cache_fresh());
+    assert!(restored
+        .decrypt::<Value, _>(sign(
+            &json!({ "sub": "user", "exp": 20_000_000_000u64 })
+        ))
+        .is_ok());
+}
+
+#[test]
+/// Snapshots with an invalid `uri` should be rejected.
+fn test_from_snapshot_invalid_uri() {
+    let mut snapshot = fetched_remote_cache().snapshot();
+    snapshot.uri = "http://insecure.example.com/certs".into();
+
+    assert!(matches!(
+        RemoteCache::from_snapshot(snapshot),
+        Err(Error::invalid_uri),
+    ));
+}
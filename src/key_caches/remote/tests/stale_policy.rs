@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::policy::StalePolicy;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::SequenceFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+#[test]
+/// Without a [`StalePolicy`], expired keys should not be used.
+fn test_stale_cache_rejected() {
+    let mut remote_cache = remote_cache();
+    *remote_cache.expiry_time_mut() = Some(now() - 1);
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+    let err = remote_cache.decrypt::<Value, _>(token).unwrap_err();
+
+    assert_eq!(err, Error::stale_cache);
+}
+
+#[test]
+/// Expired keys should be used while within the grace period, and rejected
+/// afterwards.
+fn test_stale_cache_grace_period() {
+    let mut remote_cache = remote_cache();
+    *remote_cache.stale_policy_mut() = Some(StalePolicy {
+        grace: Duration::from_secs(600),
+    });
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+
+    *remote_cache.expiry_time_mut() = Some(now() - 60);
+    remote_cache.decrypt::<Value, _>(token.clone()).unwrap();
+
+    *remote_cache.expiry_time_mut() = Some(now() - 601);
+    let err = remote_cache.decrypt::<Value, _>(token).unwrap_err();
+
+    assert_eq!(err, Error::stale_cache);
+}
+
+#[tokio::test]
+/// A failed refresh should leave the previous keys usable under a
+/// [`StalePolicy`].
+async fn test_stale_if_error() {
+    let fetcher = Arc::new(SequenceFetcher::new([Ok(jwks_response(
+        "max-age=0",
+    ))]));
+
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(fetcher)
+        .stale_policy(StalePolicy {
+            grace: Duration::from_secs(7200),
+        })
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+    assert!(!remote_cache.is_cache_fresh());
+
+    assert!(remote_cache.refresh().await.is_err());
+    assert!(remote_cache.is_cache_usable());
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+    remote_cache.decrypt::<Value, _>(token).unwrap();
+}
+
+#[test]
+/// An empty cache should never be usable, regardless of the
+/// [`StalePolicy`].
+fn test_empty_cache_unusable() {
+    let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(MockFetcher::default())
+        .stale_policy(StalePolicy {
+            grace: Duration::from_secs(600),
+        })
+        .build()
+        .unwrap();
+    *remote_cache.expiry_time_mut() = Some(now());
+
+    assert!(!remote_cache.is_cache_usable());
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::registered::Audience;
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::standard::StandardClaims;
+use crate::time::now;
+
+#[test]
+/// The registered claims should be deserialized (with a single audience as an
+/// array of one), and any other claim should be kept.
+fn test_standard_claims() {
+    let token = sign(&json!({
+        "iss": "https://sso.example.com",
+        "sub": "user-1",
+        "aud": "my-client-id",
+        "exp": now() + 3600,
+        "roles": ["admin"],
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<StandardClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.iss.as_deref(), Some("https://sso.example.com"));
+    assert_eq!(claims.aud, Some(Audience::from("my-client-id")));
+    assert_eq!(claims.jti, None);
+    assert_eq!(claims.extra["roles"], json!(["admin"]));
+
+    assert!(claims.audience_contains("my-client-id"));
+    assert!(!claims.audience_contains("other-client-id"));
+
+    let expires_in = claims.expires_in().unwrap();
+    assert!(expires_in <= Duration::from_secs(3600));
+    assert!(expires_in > Duration::from_secs(3500));
+}
+
+#[test]
+/// Tokens which have already expired (or which never do) should not report
+/// the time left until they expire.
+fn test_standard_claims_expires_in() {
+    let expired = StandardClaims {
+        exp: Some(now() - 60),
+        ..StandardClaims::default()
+    };
+    assert_eq!(expired.expires_in(), None);
+    assert_eq!(StandardClaims::default().expires_in(), None);
+
+    let claims = serde_json::from_value::<StandardClaims>(json!({
+        "aud": ["a", "b"],
+    }))
+    .unwrap();
+    assert!(claims.audience_contains("b"));
+}
@@ -0,0 +1,57 @@
+use jsonwebtoken::Algorithm;
+use serde_json::Value;
+
+use crate::key_caches::remote::jwks::KeySet;
+use crate::key_caches::remote::tests::claims;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+#[test]
+/// A cache built from a `JWK` set should verify tokens right away.
+fn test_from_jwks_json() {
+    let jwks = serde_json::to_string(&KeySet {
+        keys: vec![KEY_PAIRS[0].key()],
+    })
+    .unwrap();
+
+    let remote_cache = RemoteCache::from_jwks_json(&jwks).unwrap();
+
+    assert!(remote_cache.is_cache_fresh());
+    assert!(!remote_cache.needs_refresh());
+    assert!(remote_cache.decrypt::<Value, _>(sign(&claims())).is_ok());
+}
+
+#[test]
+/// Unusable keys should be filtered out, exactly as when fetching them.
+fn test_from_keys() {
+    let mut unusable = KEY_PAIRS[1].key();
+    unusable.alg = Some(Algorithm::HS256);
+
+    let remote_cache =
+        RemoteCache::from_keys(vec![KEY_PAIRS[0].key(), unusable]);
+
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [KID]);
+}
+
+#[test]
+/// Documents which are not `JWK` sets should be rejected.
+fn test_invalid_jwks_json() {
+    assert!(RemoteCache::from_jwks_json("not json").is_err());
+    assert!(RemoteCache::from_jwks_json("{}").is_err());
+}
+
+#[tokio::test]
+/// Refreshing a static cache should never reach the network, and should leave
+/// its keys in place.
+async fn test_refresh() {
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+
+    let error = remote_cache.refresh().await.unwrap_err();
+
+    assert!(matches!(error, Error::unable_to_fetch_keys { .. }));
+    assert!(remote_cache.key(KID).is_some());
+    assert!(remote_cache.is_cache_fresh());
+}
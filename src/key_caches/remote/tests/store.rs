@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::key_caches::remote::auto_refresh::AutoRefresh;
+use crate::key_caches::remote::auto_refresh::RefreshSchedule;
+use crate::key_caches::remote::snapshot::Snapshot;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::store::FileStore;
+use crate::key_caches::remote::tests::SequenceFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::prelude::Error;
+use crate::registry::KeyRegistry;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+
+/// An in-memory [`CacheStore`], standing in for e.g. `Redis`.
+#[derive(Default)]
+struct MemoryStore {
+    snapshots: Mutex<BTreeMap<String, Snapshot>>,
+    available: Mutex<bool>,
+    lock: Mutex<Option<String>>,
+    lock_wait: Duration,
+}
+
+impl MemoryStore {
+    fn new() -> Arc<Self> {
+        let store = Self::default();
+        *store.available.lock().unwrap() = true;
+
+        Arc::new(store)
+    }
+
+    fn with_lock_wait(lock_wait: Duration) -> Arc<Self> {
+        let store = Self {
+            lock_wait,
+            ..Self::default()
+        };
+        *store.available.lock().unwrap() = true;
+
+        Arc::new(store)
+    }
+
+    fn is_locked(&self) -> bool {
+        self.lock.lock().unwrap().is_some()
+    }
+
+    fn get(&self) -> Option<Snapshot> {
+        self.snapshots.lock().unwrap().get(MOCK_JWK_URI).cloned()
+    }
+
+    fn set_available(&self, available: bool) {
+        *self.available.lock().unwrap() = available;
+    }
+
+    fn check(&self) -> prelude::Result<()> {
+        match *self.available.lock().unwrap() {
+            true => Ok(()),
+            false => Err(Error::store_failed {
+                message: "Unavailable.".into(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn load(&self, uri: &http::Uri) -> prelude::Result<Option<Snapshot>> {
+        self.check()?;
+
+        Ok(self.snapshots.lock().unwrap().get(&uri.to_string()).cloned())
+    }
+
+    async fn save(
+        &self,
+        uri: &http::Uri,
+        snapshot: &Snapshot,
+    ) -> prelude::Result<()> {
+        self.check()?;
+
+        let mut snapshots = self.snapshots.lock().unwrap();
+        snapshots.insert(uri.to_string(), snapshot.clone());
+
+        Ok(())
+    }
+
+    async fn try_lock(&self, _: &http::Uri) -> prelude::Result<Option<String>> {
+        self.check()?;
+
+        let mut lock = self.lock.lock().unwrap();
+
+        match *lock {
+            Some(_) => Ok(None),
+            None => {
+                let token = uuid::Uuid::new_v4().to_string();
+                *lock = Some(token.clone());
+
+                Ok(Some(token))
+            },
+        }
+    }
+
+    async fn unlock(&self, _: &http::Uri, token: &str) -> prelude::Result<()> {
+        let mut lock = self.lock.lock().unwrap();
+
+        if lock.as_deref() == Some(token) {
+            *lock = None;
+        };
+
+        Ok(())
+    }
+
+    fn lock_wait(&self) -> Duration {
+        self.lock_wait
+    }
+}
+
+/// A cache (i.e., a replica) fetching from the given identity provider, and
+/// sharing its keys through the given store.
+fn replica(idp: &Arc<MockIdp>, store: &Arc<MemoryStore>) -> RemoteCache {
+    RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(Arc::clone(idp))
+        .cache_store(Arc::clone(store))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+/// Fetched keys should be written through, and read through by the other
+/// replicas instead of fetching them again.
+async fn test_shared_keys() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+
+    let mut first = replica(&idp, &store);
+    first.refresh().await.unwrap();
+    assert_eq!(idp.fetch_count(), 1);
+    assert_eq!(store.get(), Some(first.snapshot()));
+
+    let mut second = replica(&idp, &store);
+    second.refresh().await.unwrap();
+    assert_eq!(idp.fetch_count(), 1);
+    assert_eq!(second.export(), first.export());
+    assert!(second.is_cache_fresh());
+}
+
+#[tokio::test]
+/// Snapshots which are not fresher than the keys inside of the cache (e.g.,
+/// when refreshing after a rotation) should not prevent a fetch.
+async fn test_rotation() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+
+    let mut first = replica(&idp, &store);
+    first.refresh().await.unwrap();
+
+    let mut second = replica(&idp, &store);
+    second.refresh().await.unwrap();
+
+    let kid = idp.rotate();
+    second.refresh().await.unwrap();
+
+    assert_eq!(idp.fetch_count(), 2);
+    assert!(second.key(kid).is_some());
+    assert!(store.get().unwrap().keys.iter().any(|key| key.kid == kid));
+}
+
+#[tokio::test]
+/// Expired snapshots should never be restored.
+async fn test_expired_snapshot() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+
+    let mut first = replica(&idp, &store);
+    first.refresh().await.unwrap();
+
+    let mut snapshot = store.get().unwrap();
+    snapshot.expiry = Some(1);
+    store.save(first.uri(), &snapshot).await.unwrap();
+
+    replica(&idp, &store).refresh().await.unwrap();
+    assert_eq!(idp.fetch_count(), 2);
+}
+
+#[tokio::test]
+/// An unavailable store should never fail a refresh.
+async fn test_unavailable_store() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+    store.set_available(false);
+
+    let mut remote_cache = replica(&idp, &store);
+    remote_cache.refresh().await.unwrap();
+
+    assert!(remote_cache.is_cache_fresh());
+    assert_eq!(idp.fetch_count(), 1);
+}
+
+#[tokio::test]
+/// Background refreshes should read and write through the store as well.
+async fn test_auto_refresh() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+
+    let mut first = replica(&idp, &store);
+    first.refresh().await.unwrap();
+
+    let second = Arc::new(RwLock::new(replica(&idp, &store)));
+    let handle = RemoteCache::spawn_auto_refresh(
+        &second,
+        AutoRefresh::new(RefreshSchedule::TtlBased),
+    );
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    handle.shutdown();
+
+    assert_eq!(idp.fetch_count(), 1);
+    assert_eq!(second.read().await.export(), first.export());
+}
+
+#[tokio::test]
+/// Replicas starting up after another one should restore its keys.
+async fn test_registry() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::new();
+
+    for _ in 0..3 {
+        let registry = KeyRegistry::builder()
+            .add_remote_cache("mock", idp.remote_cache().unwrap())
+            .cache_store(Arc::clone(&store))
+            .finish()
+            .await
+            .unwrap();
+
+        assert!(registry.remote("mock").unwrap().is_cache_fresh());
+    }
+
+    assert_eq!(idp.fetch_count(), 1);
+}
+
+#[tokio::test]
+/// While another replica holds the lock, its snapshot should be waited for
+/// instead of fetching as well.
+async fn test_lock() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::with_lock_wait(Duration::from_secs(5));
+    let uri = MOCK_JWK_URI.parse::<http::Uri>().unwrap();
+
+    let token = store.try_lock(&uri).await.unwrap().unwrap();
+    let waiting = tokio::spawn({
+        let mut remote_cache = replica(&idp, &store);
+
+        async move {
+            remote_cache.refresh().await.unwrap();
+            remote_cache
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let mut first = idp.remote_cache().unwrap();
+    first.refresh().await.unwrap();
+    store.save(&uri, &first.snapshot()).await.unwrap();
+    store.unlock(&uri, &token).await.unwrap();
+
+    let second = waiting.await.unwrap();
+    assert_eq!(idp.fetch_count(), 1);
+    assert_eq!(second.export(), first.export());
+}
+
+#[tokio::test]
+/// A lock which is held for too long should not prevent a fetch, and every
+/// lock should be released after fetching (even unsuccessfully).
+async fn test_lock_wait() {
+    let idp = Arc::new(MockIdp::new());
+    let store = MemoryStore::with_lock_wait(Duration::from_millis(200));
+    let uri = MOCK_JWK_URI.parse::<http::Uri>().unwrap();
+
+    let token = store.try_lock(&uri).await.unwrap().unwrap();
+    replica(&idp, &store).refresh().await.unwrap();
+    assert_eq!(idp.fetch_count(), 1);
+
+    store.unlock(&uri, &token).await.unwrap();
+    store.snapshots.lock().unwrap().clear();
+
+    let mut remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(SequenceFetcher::new([]))
+        .cache_store(Arc::clone(&store))
+        .build()
+        .unwrap();
+    assert!(remote_cache.refresh().await.is_err());
+    assert!(!store.is_locked());
+}
+
+#[cfg(feature = "redis")]
+#[test]
+/// Snapshots should expire from `Redis` along with their keys.
+fn test_redis_ttl() {
+    use crate::key_caches::remote::store::redis::key;
+    use crate::key_caches::remote::store::redis::ttl;
+
+    assert_eq!(ttl(Some(1_060), 1_000), Some(Some(Duration::from_secs(60))));
+    assert_eq!(ttl(Some(u64::MAX), 1_000), Some(None));
+    assert_eq!(ttl(None, 1_000), Some(None));
+    assert_eq!(ttl(Some(1_000), 1_000), None);
+
+    let uri = MOCK_JWK_URI.parse::<http::Uri>().unwrap();
+    assert_eq!(
+        key("webcipher:", "lock", &uri),
+        format!("webcipher:lock:{}", uri),
+    );
+}
+
+#[tokio::test]
+/// Snapshots should round-trip through the file system.
+async fn test_file_store() {
+    let directory =
+        std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    let store = FileStore::new(directory.clone());
+
+    let idp = Arc::new(MockIdp::new());
+    let mut remote_cache = idp.remote_cache().unwrap();
+    assert_eq!(store.load(remote_cache.uri()).await, Ok(None));
+
+    remote_cache.refresh().await.unwrap();
+    let snapshot = remote_cache.snapshot();
+    store.save(remote_cache.uri(), &snapshot).await.unwrap();
+    store.save(remote_cache.uri(), &snapshot).await.unwrap();
+
+    let loaded = store.load(remote_cache.uri()).await;
+    let files = std::fs::read_dir(&directory).unwrap().count();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(loaded, Ok(Some(snapshot)));
+    assert_eq!(files, 1);
+}
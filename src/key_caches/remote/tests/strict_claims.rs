@@ -0,0 +1,131 @@
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::registered::check_registered_claims;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+/// The message of the given [`Error::invalid_registered_claim`].
+fn message(error: Error) -> String {
+    match error {
+        Error::invalid_registered_claim { message } => message,
+        error => panic!("Unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+/// Registered claims of the wrong type should be named, along with their
+/// type (but not their value).
+fn test_check_registered_claims() {
+    let valid = json!({
+        "iss": "https://issuer.example.com",
+        "sub": "user",
+        "aud": ["a", "b"],
+        "exp": 20_000_000_000u64,
+        "nbf": 1.5,
+        "iat": 1,
+        "jti": "id",
+        "custom": "secret",
+    });
+    check_registered_claims(&valid).unwrap();
+    check_registered_claims(&json!({ "aud": "a" })).unwrap();
+    check_registered_claims(&json!({})).unwrap();
+
+    let cases = [
+        (json!({ "exp": "secret" }), "`exp` is a string, instead of a number."),
+        (json!({ "iat": null }), "`iat` is null, instead of a number."),
+        (json!({ "nbf": true }), "`nbf` is a boolean, instead of a number."),
+        (json!({ "iss": 1 }), "`iss` is a number, instead of a string."),
+        (json!({ "sub": ["secret"] }), "`sub` is an array, instead of a string."),
+        (
+            json!({ "aud": 1 }),
+            "`aud` is a number, instead of a string or an array.",
+        ),
+        (
+            json!({ "aud": ["a", { "secret": 1 }] }),
+            "An element of `aud` is an object, instead of a string.",
+        ),
+        (json!("secret"), "The payload is a string, instead of an object."),
+    ];
+
+    for (claims, expected) in cases {
+        let error = check_registered_claims(&claims).unwrap_err();
+        assert!(!error.to_string().contains("secret"));
+        assert_eq!(message(error), expected);
+    }
+}
+
+#[test]
+/// Mistyped registered claims should only be reported precisely if the
+/// claims are strictly checked, and should be rejected either way.
+fn test_strict_claims() {
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    assert!(!remote_cache.strict_claims());
+
+    let claims = json!({ "sub": "user", "exp": "20000000000" });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+
+    let error = remote_cache
+        .decrypt_unchecked::<Value, _>(token.clone())
+        .unwrap_err();
+    assert!(matches!(error, Error::unable_to_verify_token(_)));
+
+    remote_cache.set_strict_claims(true);
+    let error = remote_cache
+        .decrypt_unchecked::<Value, _>(token.clone())
+        .unwrap_err();
+    assert_eq!(message(error), "`exp` is a string, instead of a number.");
+
+    let mut buffer = Vec::new();
+    let error = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap_err();
+    assert_eq!(message(error), "`exp` is a string, instead of a number.");
+
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+    let data = remote_cache
+        .decrypt_unchecked::<Value, _>(token.clone())
+        .unwrap();
+    assert_eq!(data.claims, claims);
+    let data = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap();
+    assert_eq!(data, claims);
+}
+
+#[test]
+/// Strictly checked claims should still be validated as usual (i.e., `exp`
+/// is required, must be an integer, and must not have passed).
+fn test_strict_claims_validation() {
+    let remote_cache = RemoteCache::builder("https://example.com/keys")
+        .fetcher(MockFetcher::default())
+        .strict_claims(true)
+        .build()
+        .unwrap();
+    assert!(remote_cache.strict_claims());
+
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    remote_cache.set_strict_claims(true);
+
+    for claims in [
+        json!({ "sub": "user" }),
+        json!({ "sub": "user", "exp": 20_000_000_000.5 }),
+        json!({ "sub": "user", "exp": 1 }),
+    ] {
+        let token = KEY_PAIRS[0].sign(&claims).unwrap();
+        let error = remote_cache
+            .decrypt_unchecked::<Value, _>(token)
+            .unwrap_err();
+
+        assert!(matches!(error, Error::unable_to_verify_token(_)));
+    }
+
+    remote_cache.set_strict_claims(false);
+    let claims = json!({ "sub": 1, "exp": 20_000_000_000u64 });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+    let data = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+    assert_eq!(data.claims, claims);
+}
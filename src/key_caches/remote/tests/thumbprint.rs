@@ -0,0 +1,237 @@
+use jsonwebtoken::encode;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::EncodingKey;
+use jsonwebtoken::Header;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::key::Curve;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::provenance::Provenance;
+use crate::key_caches::remote::RemoteCache;
+use crate::key_caches::KeyHint;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+
+/// The example key of
+/// [RFC7638, Section 3.1](https://datatracker.ietf.org/doc/html/rfc7638#section-3.1).
+const RFC_N: &str = "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw";
+
+/// The thumbprint of [`RFC_N`].
+const RFC_THUMBPRINT: &str = "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs";
+
+/// Sign the given claims with the first bundled key-pair, using the given
+/// header (i.e., without setting a `kid`).
+fn sign(header: Header) -> String {
+    let header = Header {
+        typ: Some("JWT".into()),
+        ..header
+    };
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let encoding_key = EncodingKey::from_rsa_pem(KEY_PAIRS[0].private_pem);
+
+    encode(&header, &claims, &encoding_key.unwrap()).unwrap()
+}
+
+/// The first bundled key, without a `kid`.
+fn unnamed() -> Key {
+    let mut key = KEY_PAIRS[0].key();
+    key.kid = String::new();
+
+    key
+}
+
+#[test]
+/// The thumbprint should match the example of the RFC, and only depend on the
+/// required members.
+fn test_thumbprint() {
+    let mut key = KEY_PAIRS[0].key();
+    key.n = RFC_N.into();
+    assert_eq!(key.thumbprint_sha256().unwrap(), RFC_THUMBPRINT);
+
+    key.kid = "other".into();
+    key.alg = None;
+    assert_eq!(key.thumbprint_sha256().unwrap(), RFC_THUMBPRINT);
+
+    key.n = String::new();
+    assert!(matches!(
+        key.thumbprint_sha256(),
+        Err(Error::invalid_jwk { .. }),
+    ));
+
+    key.kty = KeyType::EC;
+    key.crv = Some(Curve::P256);
+    key.x = Some("MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4".into());
+    assert!(key.thumbprint_sha256().is_err());
+
+    key.y = Some("4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM".into());
+    assert!(key.thumbprint_sha256().is_ok());
+
+    key.kty = KeyType::oct;
+    assert!(key.thumbprint_sha256().is_err());
+}
+
+#[test]
+/// Keys without a `kid` should be indexed by their thumbprint, and verify
+/// tokens which carry it as their `kid`.
+fn test_missing_kid() {
+    let thumbprint = unnamed().thumbprint_sha256().unwrap();
+
+    let mut value = serde_json::to_value(unnamed()).unwrap();
+    value.as_object_mut().unwrap().remove("kid");
+    let jwks = json!({ "keys": [value] }).to_string();
+
+    let remote_cache = RemoteCache::from_jwks_json(&jwks).unwrap();
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [&*thumbprint]);
+
+    let token = sign(Header {
+        kid: Some(thumbprint.clone()),
+        ..Header::new(Algorithm::RS256)
+    });
+    let data = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+    assert_eq!(data.claims["sub"], "user");
+
+    // Also when converted from a `Jwk`.
+    let jwk = Jwk::try_from(unnamed()).unwrap();
+    assert!(jwk.common.key_id.is_none());
+
+    let jwk_set = JwkSet { keys: vec![jwk] };
+    let remote_cache = RemoteCache::from_jwk_set(&jwk_set);
+    assert_eq!(remote_cache.kids().collect::<Vec<_>>(), [&*thumbprint]);
+}
+
+#[test]
+/// Tokens without a `kid` should be matched by their certificate thumbprint.
+fn test_x5t_header() {
+    let mut key = unnamed();
+    key.x5t = Some("sha-1".into());
+    key.x5t_s256 = Some("sha-256".into());
+    let remote_cache = RemoteCache::from_keys(vec![key]);
+
+    let x5t_s256 = sign(Header {
+        x5t_s256: Some("sha-256".into()),
+        ..Header::new(Algorithm::RS256)
+    });
+    let x5t = sign(Header {
+        x5t: Some("sha-1".into()),
+        ..Header::new(Algorithm::RS256)
+    });
+
+    for token in [x5t_s256, x5t] {
+        let data = remote_cache.decrypt_unchecked::<Value, _>(token).unwrap();
+        assert_eq!(data.claims["sub"], "user");
+    }
+
+    let unknown = sign(Header {
+        x5t_s256: Some("other".into()),
+        ..Header::new(Algorithm::RS256)
+    });
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(unknown)
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    let anonymous = sign(Header::new(Algorithm::RS256));
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(anonymous)
+        .unwrap_err();
+    assert_eq!(err, Error::no_kid_present);
+}
+
+#[test]
+/// Tokens without a `kid` should only be verified by the only key of the
+/// cache, and only if the fallback is enabled.
+fn test_single_key_fallback() {
+    let anonymous = sign(Header::new(Algorithm::RS256));
+
+    let mut remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    assert!(!remote_cache.single_key_fallback());
+
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(anonymous.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::no_kid_present);
+
+    remote_cache.set_single_key_fallback(true);
+    let data = remote_cache
+        .decrypt_unchecked::<Value, _>(anonymous.clone())
+        .unwrap();
+    assert_eq!(data.claims["sub"], "user");
+
+    let mut buffer = Vec::new();
+    let claims = remote_cache
+        .decrypt_borrowed::<Value>(&anonymous, &mut buffer)
+        .unwrap();
+    assert_eq!(claims["sub"], "user");
+
+    // A second key makes the fallback ambiguous.
+    remote_cache.insert_key(KEY_PAIRS[1].key(), Provenance::Pinned).unwrap();
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(anonymous)
+        .unwrap_err();
+    assert_eq!(err, Error::no_kid_present);
+}
+
+#[test]
+/// Tokens whose `kid` is unknown should only be verified by the key whose
+/// signature matches, and only if every key may be tried.
+fn test_try_all_keys() {
+    let mut relabeled = KEY_PAIRS[0].key();
+    relabeled.kid = "relabeled".into();
+
+    let mut remote_cache =
+        RemoteCache::from_keys(vec![relabeled, KEY_PAIRS[2].key()]);
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    let token = KEY_PAIRS[0].sign(&claims).unwrap();
+    assert!(!remote_cache.try_all_keys());
+
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    remote_cache.set_try_all_keys(true);
+    let verified = remote_cache
+        .decrypt_with_provenance::<Value, _>(token.clone())
+        .unwrap();
+    assert_eq!(verified.kid, "relabeled");
+    assert_eq!(verified.token_data.claims["sub"], "user");
+
+    let mut buffer = Vec::new();
+    let data = remote_cache
+        .decrypt_borrowed::<Value>(&token, &mut buffer)
+        .unwrap();
+    assert_eq!(data["sub"], "user");
+
+    // The matching key has already verified the signature.
+    let key_hint = KeyHint {
+        kid: Some(KEY_PAIRS[0].kid),
+        x5t_s256: None,
+        x5t: None,
+    };
+    let (kid, selected) = remote_cache.select(&key_hint, &token).unwrap();
+    assert_eq!(kid, "relabeled");
+    assert!(selected.verified);
+
+    // No key matches the signature.
+    let token = KEY_PAIRS[1].sign(&claims).unwrap();
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token)
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    // The matching key is still subject to every other check.
+    let token = KEY_PAIRS[0].sign(&json!({ "exp": 1 })).unwrap();
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::unable_to_verify_token(error)
+            if *error.kind() == ErrorKind::ExpiredSignature,
+    ));
+}
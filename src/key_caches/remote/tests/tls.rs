@@ -0,0 +1,68 @@
+use crate::key_caches::remote::config::FetchConfig;
+use crate::key_caches::remote::fetcher::HyperFetcher;
+use crate::key_caches::remote::tls::Certificate;
+use crate::key_caches::remote::tls::Identity;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+const CA: &[u8] = include_bytes!("certs/ca.pem");
+const CLIENT_CERTIFICATE: &[u8] = include_bytes!("certs/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("certs/client.key");
+
+#[tokio::test]
+/// Additional root certificates and a client identity should be accepted by
+/// the `TLS` backend.
+async fn test_custom_ca_and_identity() {
+    let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+        .add_root_certificate(Certificate::from_pem(CA))
+        .identity(Identity::from_pem(CLIENT_CERTIFICATE, CLIENT_KEY))
+        .build()
+        .unwrap();
+
+    let FetchConfig {
+        root_certificates,
+        identity,
+        ..
+    } = remote_cache.config();
+
+    assert_eq!(root_certificates, &[Certificate::from_pem(CA)]);
+    assert!(identity.is_some());
+}
+
+#[tokio::test]
+/// Unusable root certificates should be rejected when building.
+async fn test_invalid_root_certificate() {
+    let err = RemoteCache::builder(GOOGLE_JWK_URI)
+        .add_root_certificate(Certificate::from_pem("not a certificate"))
+        .build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(err, Error::invalid_tls_config { .. }));
+}
+
+#[tokio::test]
+/// Unusable client identities should be rejected when building.
+async fn test_invalid_identity() {
+    let config = FetchConfig {
+        identity: Some(Identity::from_pem(CLIENT_CERTIFICATE, "not a key")),
+        ..Default::default()
+    };
+    let err = HyperFetcher::try_new(config).err().unwrap();
+
+    assert!(matches!(err, Error::invalid_tls_config { .. }));
+}
+
+#[test]
+/// The private key of an identity should never be printed.
+fn test_identity_debug() {
+    let identity = Identity::from_pem(CLIENT_CERTIFICATE, CLIENT_KEY);
+    let debug = format!("{:?}", identity);
+
+    let key = std::str::from_utf8(CLIENT_KEY).unwrap();
+    let key = key.lines().nth(1).unwrap();
+
+    assert!(debug.contains("[redacted]"));
+    assert!(!debug.contains(key));
+}
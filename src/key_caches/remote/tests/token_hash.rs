@@ -0,0 +1,52 @@
+use jsonwebtoken::Algorithm;
+
+use crate::key_caches::token_hash::check_at_hash;
+use crate::key_caches::token_hash::check_c_hash;
+use crate::key_caches::token_hash::token_hash;
+use crate::prelude::Error;
+
+/// The access token (and its `at_hash`) of the example `ID` token in
+/// `OpenID Connect Core 1.0`, Appendix A.3.
+const ACCESS_TOKEN: &str = "jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y";
+const AT_HASH: &str = "77QmUPtjPfzWtF2AnpK9RQ";
+
+/// The authorization code (and its `c_hash`) of the example `ID` token in
+/// `OpenID Connect Core 1.0`, Appendix A.4.
+const CODE: &str = "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk";
+const C_HASH: &str = "LDktKdoQak3Pk0cnXxCltA";
+
+#[test]
+/// The hashes of the examples of the specification should match.
+fn test_token_hash() {
+    assert_eq!(token_hash(Algorithm::RS256, ACCESS_TOKEN), AT_HASH);
+    assert_eq!(token_hash(Algorithm::RS256, CODE), C_HASH);
+
+    // Half of a `SHA-384` (or a `SHA-512`) digest, `base64URL` encoded.
+    assert_eq!(token_hash(Algorithm::ES384, ACCESS_TOKEN).len(), 32);
+    assert_eq!(token_hash(Algorithm::EdDSA, ACCESS_TOKEN).len(), 43);
+}
+
+#[test]
+/// Only hashes which bind the given value should be accepted.
+fn test_check_token_hash() {
+    assert_eq!(
+        check_at_hash(Some(AT_HASH), Algorithm::RS256, ACCESS_TOKEN),
+        Ok(()),
+    );
+    assert_eq!(check_c_hash(Some(C_HASH), Algorithm::RS256, CODE), Ok(()));
+
+    let mismatch = Err(Error::token_hash_mismatch {
+        claim: "at_hash".into(),
+    });
+    assert_eq!(check_at_hash(None, Algorithm::RS256, ACCESS_TOKEN), mismatch);
+    assert_eq!(
+        check_at_hash(Some(AT_HASH), Algorithm::RS384, ACCESS_TOKEN),
+        mismatch,
+    );
+    assert_eq!(
+        check_c_hash(Some(AT_HASH), Algorithm::RS256, CODE),
+        Err(Error::token_hash_mismatch {
+            claim: "c_hash".into(),
+        }),
+    );
+}
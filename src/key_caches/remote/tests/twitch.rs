@@ -0,0 +1,33 @@
+use jsonwebtoken::TokenData;
+use serde_json::json;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::twitch::TwitchClaims;
+use crate::key_caches::remote::twitch::TWITCH_ISSUER;
+
+#[test]
+/// An `ID` token (as documented by `Twitch`) should deserialize, including
+/// the claims which were requested explicitly.
+fn test_twitch_claims() {
+    let token = sign(&json!({
+        "aud": "hof5gwx0su6owfnys0yan9c87zr6t",
+        "exp": 20_000_000_000u64,
+        "iat": 1_642_450_000u64,
+        "iss": "https://id.twitch.tv/oauth2",
+        "sub": "713936733",
+        "azp": "hof5gwx0su6owfnys0yan9c87zr6t",
+        "nonce": "abc123",
+        "preferred_username": "MyUserName",
+        "email": "user@example.com",
+        "email_verified": true,
+    }));
+    let TokenData { claims, .. } = remote_cache()
+        .decrypt_unchecked::<TwitchClaims, _>(token)
+        .unwrap();
+
+    assert_eq!(claims.iss, TWITCH_ISSUER);
+    assert_eq!(claims.sub, "713936733");
+    assert_eq!(claims.preferred_username.as_deref(), Some("MyUserName"));
+    assert_eq!(claims.picture, None);
+}
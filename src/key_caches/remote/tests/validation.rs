@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::Validation;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::validation::validate;
+use crate::prelude::Error;
+use crate::testing::KEY_PAIRS;
+use crate::time::now;
+
+/// The outcome of decrypting the given token, without its (borrowed) claims.
+fn outcome<T>(result: Result<T, Error>) -> Result<(), Error> {
+    result.map(drop)
+}
+
+#[test]
+/// Tokens should be verified exactly as `jsonwebtoken` verifies them, even
+/// though the headers and the payload are only decoded once.
+fn test_mirrors_jsonwebtoken() {
+    let remote_cache = remote_cache();
+    let key = KEY_PAIRS[0].key();
+    let decoding_key =
+        DecodingKey::from_rsa_components(&key.n, &key.e).unwrap();
+    let validation = Validation::new(Algorithm::RS256);
+
+    let now = now();
+    let payloads = [
+        json!({ "exp": now + 3600 }),
+        json!({ "exp": (now + 3600) as f64 + 0.5 }),
+        json!({ "exp": now - 30 }),
+        json!({ "exp": now - 3600 }),
+        json!({ "exp": "tomorrow" }),
+        json!({ "exp": -1 }),
+        json!({ "exp": null }),
+        json!({ "sub": "a" }),
+        json!({ "exp": now + 3600, "nbf": now + 3600 }),
+        json!({ "exp": now + 3600, "aud": ["a", "b"], "iss": "c" }),
+        json!({ "exp": now + 3600, "aud": "a\\u0062" }),
+    ];
+
+    for payload in payloads {
+        let token = sign(&payload);
+
+        let expected = jsonwebtoken::decode::<Value>(
+            &token,
+            &decoding_key,
+            &validation,
+        )
+        .map(|data| data.claims)
+        .map_err(Error::from);
+        let actual = remote_cache
+            .decrypt_unchecked::<Value, _>(&token)
+            .map(|data| data.claims);
+        assert_eq!(actual, expected, "{}", payload);
+
+        let mut buffer = Vec::new();
+        let actual = remote_cache
+            .decrypt_borrowed::<Value>(&token, &mut buffer)
+            .map(drop);
+        assert_eq!(outcome(actual), outcome(expected), "{}", payload);
+    }
+
+    // Tokens with more (or fewer) than three segments are invalid.
+    let token = sign(&json!({ "exp": now + 3600 }));
+    for token in [format!("{}.a", token), token.replacen('.', "", 1)] {
+        assert!(remote_cache
+            .decrypt_unchecked::<Value, _>(&token)
+            .is_err());
+    }
+}
+
+#[test]
+/// The `sub`, `iss`, and `aud` claims should be checked exactly as in
+/// `jsonwebtoken`, whether they are given as strings or as arrays.
+fn test_validate() {
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.required_spec_claims = HashSet::new();
+    validation.set_audience(&["a"]);
+    validation.set_issuer(&["b"]);
+    validation.sub = Some("c".into());
+
+    let payloads = [
+        json!({}),
+        json!({ "aud": "a", "iss": "b", "sub": "c" }),
+        json!({ "aud": ["x", "a"], "iss": ["b"], "sub": "c" }),
+        json!({ "aud": "x" }),
+        json!({ "aud": ["x", "y"] }),
+        json!({ "aud": 1 }),
+        json!({ "iss": "x" }),
+        json!({ "sub": "x" }),
+        json!({ "sub": 1 }),
+    ];
+
+    for payload in payloads {
+        let token = sign(&payload);
+        let key = KEY_PAIRS[0].key();
+        let decoding_key =
+            DecodingKey::from_rsa_components(&key.n, &key.e).unwrap();
+
+        let expected =
+            jsonwebtoken::decode::<Value>(&token, &decoding_key, &validation)
+                .map(drop)
+                .map_err(Error::from);
+        let actual =
+            validate(&serde_json::to_vec(&payload).unwrap(), &validation, 0);
+        assert_eq!(actual, expected, "{}", payload);
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
+
+use crate::key_caches::remote::tests::remote_cache;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::GOOGLE_JWK_URI;
+
+#[test]
+/// Synchronous verifications exceeding the limit should be rejected
+/// immediately.
+fn test_verification_overloaded() {
+    let mut remote_cache = remote_cache();
+    remote_cache.set_max_concurrent_verifications(Some(1)).unwrap();
+    let semaphore = remote_cache.verification_limit.clone().unwrap();
+    let _permit = semaphore.try_acquire().unwrap();
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+    let err = remote_cache
+        .decrypt_unchecked::<Value, _>(token)
+        .unwrap_err();
+
+    assert_eq!(err, Error::verification_overloaded);
+}
+
+#[test]
+/// Permits should be released once a verification has finished.
+fn test_verification_permits_are_released() {
+    let mut remote_cache = remote_cache();
+    remote_cache.set_max_concurrent_verifications(Some(1)).unwrap();
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+
+    for _ in 0..3 {
+        remote_cache
+            .decrypt_unchecked::<Value, _>(token.clone())
+            .unwrap();
+    }
+}
+
+#[test]
+/// A limit of zero should be rejected, and limits beyond the capacity of a
+/// semaphore should be clamped.
+fn test_verification_limit_bounds() {
+    let mut remote_cache = remote_cache();
+    remote_cache.set_max_concurrent_verifications(Some(1)).unwrap();
+
+    let err = remote_cache
+        .set_max_concurrent_verifications(Some(0))
+        .unwrap_err();
+    assert_eq!(err, Error::invalid_verification_limit);
+    assert!(remote_cache.verification_limit.is_some());
+
+    remote_cache
+        .set_max_concurrent_verifications(Some(usize::MAX))
+        .unwrap();
+    let semaphore = remote_cache.verification_limit.clone().unwrap();
+    assert_eq!(semaphore.available_permits(), Semaphore::MAX_PERMITS);
+
+    let built = RemoteCache::builder(GOOGLE_JWK_URI)
+        .fetcher(MockFetcher::default())
+        .max_concurrent_verifications(0)
+        .build();
+    assert!(matches!(built, Err(Error::invalid_verification_limit)));
+}
+
+#[tokio::test]
+/// Asynchronous verifications exceeding the limit should wait for a permit
+/// (for at most the verification wait).
+async fn test_verification_backpressure() {
+    let mut remote_cache = remote_cache();
+    remote_cache.set_max_concurrent_verifications(Some(1)).unwrap();
+    remote_cache.set_verification_wait(Duration::from_secs(10));
+    let semaphore = remote_cache.verification_limit.clone().unwrap();
+    let remote_cache = Arc::new(RwLock::new(remote_cache));
+
+    let token = sign(&json!({ "exp": 20_000_000_000u64 }));
+
+    let permit = Arc::clone(&semaphore).try_acquire_owned().unwrap();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(permit);
+    });
+
+    RemoteCache::decrypt_unchecked_offloaded::<Value, _>(&remote_cache, &token)
+        .await
+        .unwrap();
+
+    remote_cache
+        .write()
+        .await
+        .set_verification_wait(Duration::from_millis(20));
+    let _permit = semaphore.try_acquire().unwrap();
+
+    let err = RemoteCache::decrypt_unchecked_offloaded::<Value, _>(
+        &remote_cache,
+        &token,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err, Error::verification_overloaded);
+}
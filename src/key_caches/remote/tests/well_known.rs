@@ -0,0 +1,52 @@
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+use crate::key_caches::remote::google::GOOGLE_JWK_URI;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::well_known::WellKnownTpa;
+use crate::prelude::Error;
+
+#[test]
+/// Every provider should be parsed back from its name, regardless of case.
+fn test_from_str() {
+    for tpa in WellKnownTpa::ALL {
+        assert_eq!(tpa.to_string().parse::<WellKnownTpa>().unwrap(), tpa);
+        assert_eq!(
+            tpa.as_str().to_uppercase().parse::<WellKnownTpa>().unwrap(),
+            tpa,
+        );
+        assert!(tpa.jwk_uri().starts_with("https://"));
+    }
+
+    assert_eq!(
+        "okta".parse::<WellKnownTpa>().unwrap_err(),
+        Error::unknown_well_known_tpa {
+            name: "okta".into(),
+        },
+    );
+}
+
+#[test]
+/// The builder of each provider should target its keys, in their format.
+fn test_remote_cache_builder() {
+    let remote_cache = WellKnownTpa::Google
+        .remote_cache_builder()
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+    assert_eq!(remote_cache.uri(), GOOGLE_JWK_URI);
+    assert_eq!(remote_cache.config.format, JwksFormat::Jwks);
+
+    let remote_cache = WellKnownTpa::Firebase
+        .remote_cache_builder()
+        .fetcher(MockFetcher::default())
+        .build()
+        .unwrap();
+    assert_eq!(remote_cache.uri(), FIREBASE_JWK_URI);
+    assert_eq!(remote_cache.config.format, JwksFormat::X509Map);
+
+    assert_eq!(
+        WellKnownTpa::Google.issuers(),
+        ["https://accounts.google.com", "accounts.google.com"],
+    );
+    assert!(WellKnownTpa::Microsoft.issuers().is_empty());
+}
@@ -0,0 +1,87 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::fetch::parse_keys;
+use crate::key_caches::remote::tests::jwks_response;
+use crate::key_caches::remote::tests::sign;
+use crate::key_caches::remote::tests::MockFetcher;
+use crate::key_caches::remote::tests::KID;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::prelude::FIREBASE_JWK_URI;
+
+/// A self-signed certificate of the first bundled key-pair.
+const CERTIFICATE: &[u8] = include_bytes!("certs/x5c_rsa.der");
+
+/// The certificate, `PEM` encoded (with lines of 64 characters).
+fn pem() -> String {
+    let body = STANDARD.encode(CERTIFICATE);
+    let lines = body
+        .as_bytes()
+        .chunks(64)
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+        lines
+    )
+}
+
+#[tokio::test]
+/// A map of certificates (as served by `Firebase`) should be detected, and
+/// its keys should verify tokens.
+async fn test_x509_map() {
+    let mut response = jwks_response("public, max-age=7200");
+    response.body = json!({ KID: pem() }).to_string().into();
+
+    let fetcher = MockFetcher::default().with(FIREBASE_JWK_URI, response);
+    let mut remote_cache = RemoteCache::builder(FIREBASE_JWK_URI)
+        .fetcher(fetcher)
+        .build()
+        .unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    let key = remote_cache.key(KID).unwrap();
+    assert_eq!(key.e, "AQAB");
+    assert_eq!(key.x5c.as_deref(), Some(&[STANDARD.encode(CERTIFICATE)][..]));
+
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+    assert!(remote_cache.decrypt::<Value, _>(sign(&claims)).is_ok());
+}
+
+#[test]
+/// The format should only be detected as a map if every member is a
+/// certificate, and malformed certificates should be filtered out.
+fn test_detection() {
+    let malformed =
+        "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----";
+    let body = json!({ KID: pem(), "2": malformed }).to_string();
+    let cache = parse_keys(body.as_bytes(), JwksFormat::Auto).unwrap();
+    assert_eq!(cache.keys().collect::<Vec<_>>(), [KID]);
+
+    for body in [json!({ KID: pem(), "other": 1 }), json!({})] {
+        let body = body.to_string();
+        let result = parse_keys(body.as_bytes(), JwksFormat::Auto);
+        assert!(matches!(result, Err(Error::unable_to_fetch_keys { .. })));
+    }
+}
+
+#[test]
+/// A configured format should not be second-guessed.
+fn test_configured_format() {
+    let map = json!({ KID: pem() }).to_string();
+    let result = parse_keys(map.as_bytes(), JwksFormat::Jwks);
+    assert!(matches!(result, Err(Error::unable_to_fetch_keys { .. })));
+
+    let cache = parse_keys(map.as_bytes(), JwksFormat::X509Map).unwrap();
+    assert_eq!(cache.keys().collect::<Vec<_>>(), [KID]);
+
+    let jwks = json!({ "keys": [] }).to_string();
+    let cache = parse_keys(jwks.as_bytes(), JwksFormat::X509Map).unwrap();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,242 @@
+//! `TLS` settings of the default
+//! [`HyperFetcher`](`super::fetcher::HyperFetcher`).
+//!
+//! Private `JWK` endpoints (e.g., an internal token issuer inside of a service
+//! mesh) are often served using a private certificate authority, and may
+//! require clients to authenticate themselves using a certificate (i.e.,
+//! `mTLS`).
+//!
+//! ```ignore
+//! let ca = Certificate::from_pem(std::fs::read("ca.pem")?);
+//! let identity = Identity::from_pem(
+//!     std::fs::read("client.pem")?,
+//!     std::fs::read("client.key")?,
+//! );
+//!
+//! let remote_cache = RemoteCache::builder("https://issuer.internal/certs")
+//!     .add_root_certificate(ca)
+//!     .identity(identity)
+//!     .build()?;
+//! ```
+//!
+//! Both are validated by [`RemoteCacheBuilder::build`], which fails with
+//! [`Error::invalid_tls_config`] if the configured `TLS` backend cannot use
+//! them.
+//!
+//! [`RemoteCacheBuilder::build`]: `super::builder::RemoteCacheBuilder::build`
+
+use std::fmt;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use hyper::client::HttpConnector;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::error::Error;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::key_caches::remote::config::FetchConfig;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::prelude;
+use crate::redact::Redacted;
+
+/// A `PEM` encoded `X.509` certificate, trusted as an additional root
+/// certificate (i.e., on top of the default ones).
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct Certificate {
+    pem: Vec<u8>,
+}
+
+impl Certificate {
+    /// Create a [`Certificate`] from a `PEM` encoded certificate.
+    pub fn from_pem<P>(pem: P) -> Self
+    where
+        Vec<u8>: From<P>,
+    {
+        let pem = pem.into();
+
+        Self { pem }
+    }
+
+    /// The `PEM` encoded certificate.
+    pub fn pem(&self) -> &[u8] {
+        &self.pem
+    }
+}
+
+/// A client identity (i.e., a certificate chain along with its private key),
+/// presented to endpoints which require client authentication.
+///
+/// The private key is never printed by the [`fmt::Debug`] implementation (see
+/// [`Redacted`]).
+#[derive(Clone, PartialEq, Eq)]
+pub struct Identity {
+    certificate_chain: Vec<u8>,
+    private_key: Vec<u8>,
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity")
+            .field("certificate_chain", &self.certificate_chain)
+            .field("private_key", &Redacted::new(()))
+            .finish()
+    }
+}
+
+impl Identity {
+    /// Create an [`Identity`] from a `PEM` encoded certificate chain (leaf
+    /// certificate first) and a `PEM` encoded `PKCS#8` private key.
+    pub fn from_pem<C, K>(certificate_chain: C, private_key: K) -> Self
+    where
+        Vec<u8>: From<C>,
+        Vec<u8>: From<K>,
+    {
+        let certificate_chain = certificate_chain.into();
+        let private_key = private_key.into();
+
+        Self {
+            certificate_chain,
+            private_key,
+        }
+    }
+
+    /// The `PEM` encoded certificate chain.
+    pub fn certificate_chain(&self) -> &[u8] {
+        &self.certificate_chain
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn invalid<E>(error: E) -> Error
+where
+    E: ToString,
+{
+    Error::invalid_tls_config {
+        message: error.to_string(),
+    }
+}
+
+/// The `TLS` connector used by the
+/// [`HyperFetcher`](`super::fetcher::HyperFetcher`), as selected by the
+/// `native-tls` and `rustls` features.
+#[cfg(feature = "native-tls")]
+pub(crate) type Connector = hyper_tls::HttpsConnector<HttpConnector>;
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+pub(crate) type Connector = hyper_rustls::HttpsConnector<HttpConnector>;
+
+#[cfg(feature = "native-tls")]
+pub(crate) fn connector(
+    http: HttpConnector,
+    config: &FetchConfig,
+) -> prelude::Result<Connector> {
+    let FetchConfig {
+        root_certificates,
+        identity,
+        ..
+    } = config;
+
+    let mut builder = native_tls::TlsConnector::builder();
+
+    for Certificate { pem } in root_certificates {
+        let certificate =
+            native_tls::Certificate::from_pem(pem).map_err(invalid)?;
+        builder.add_root_certificate(certificate);
+    }
+
+    if let Some(Identity {
+        certificate_chain,
+        private_key,
+    }) = identity
+    {
+        let identity =
+            native_tls::Identity::from_pkcs8(certificate_chain, private_key)
+                .map_err(invalid)?;
+        builder.identity(identity);
+    };
+
+    let tls = builder.build().map_err(invalid)?;
+
+    Ok(hyper_tls::HttpsConnector::from((http, tls.into())))
+}
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+pub(crate) fn connector(
+    http: HttpConnector,
+    config: &FetchConfig,
+) -> prelude::Result<Connector> {
+    use rustls::OwnedTrustAnchor;
+    use rustls::PrivateKey;
+    use rustls::RootCertStore;
+
+    let FetchConfig {
+        root_certificates,
+        identity,
+        ..
+    } = config;
+
+    let certificates = |pem: &[u8]| {
+        let certificates = rustls_pemfile::certs(&mut &*pem)
+            .map_err(invalid)?
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect::<Vec<_>>();
+
+        match certificates.is_empty() {
+            true => Err(invalid("No `PEM` encoded certificate was found.")),
+            false => Ok(certificates),
+        }
+    };
+
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(
+        |anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        },
+    ));
+
+    for Certificate { pem } in root_certificates {
+        for certificate in certificates(pem)? {
+            roots.add(&certificate).map_err(invalid)?;
+        }
+    }
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+
+    let tls = match identity {
+        Some(Identity {
+            certificate_chain,
+            private_key,
+        }) => {
+            let private_key = rustls_pemfile::pkcs8_private_keys(
+                &mut private_key.as_slice(),
+            )
+            .map_err(invalid)?
+            .into_iter()
+            .next()
+            .map(PrivateKey)
+            .ok_or_else(|| {
+                invalid("No `PEM` encoded `PKCS#8` private key was found.")
+            })?;
+
+            builder
+                .with_client_auth_cert(
+                    certificates(certificate_chain)?,
+                    private_key,
+                )
+                .map_err(invalid)?
+        },
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(tls)
+        .https_or_http()
+        .enable_http1()
+        .wrap_connector(http))
+}
@@ -0,0 +1,58 @@
+//! `Twitch` JWT Claim object.
+//!
+//! Apps which request the `openid` scope receive an `ID` token along with
+//! their access token:
+//!
+//! ```ignore
+//! let mut remote_cache = RemoteCache::new(TWITCH_JWK_URI)?;
+//! remote_cache.refresh().await?;
+//!
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<TwitchClaims, _>(token)?;
+//!
+//! let trusted =
+//!     claims.iss == TWITCH_ISSUER && claims.aud.contains(client_id);
+//! ```
+//!
+//! For more information, please visit: <https://dev.twitch.tv/docs/authentication/getting-tokens-oidc/>.
+
+use serde::Deserialize;
+
+use crate::key_caches::registered::Audience;
+
+/// The `iss` claim of the tokens issued by `Twitch`.
+pub const TWITCH_ISSUER: &str = "https://id.twitch.tv/oauth2";
+
+/// The URI for `Twitch`'s public `JWK`s.
+pub const TWITCH_JWK_URI: &str = "https://id.twitch.tv/oauth2/keys";
+
+/// Claims made by `Twitch`.
+///
+/// `JWT`'s issued by `Twitch` should have a body (i.e., the second portion of
+/// the `JWT`) that are `base64URL` decrypted into the below struct. Claims
+/// which depend on the requested `claims` parameter are optional.
+#[derive(Debug, Deserialize)]
+pub struct TwitchClaims {
+    pub iss: String,
+
+    /// The id of the user.
+    pub sub: String,
+
+    /// The client id of the app.
+    pub aud: Audience,
+
+    pub azp: Option<String>,
+    pub iat: u64,
+    pub exp: u64,
+    pub nonce: Option<String>,
+
+    /// The display name of the user.
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub picture: Option<String>,
+
+    /// The time at which the user last updated their profile (e.g.,
+    /// `"2022-09-27T23:37:04Z"`).
+    pub updated_at: Option<String>,
+}
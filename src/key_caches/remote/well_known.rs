@@ -0,0 +1,154 @@
+//! The third party auth providers which this crate knows about.
+//!
+//! Instead of defining their own (near-identical) provider enum, apps can use
+//! [`WellKnownTpa`] directly as the `Tpa` of a
+//! [`KeyRegistry`](`crate::registry::KeyRegistry`):
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_well_known(WellKnownTpa::Google)
+//!     .add_well_known(WellKnownTpa::Firebase)
+//!     .finish()
+//!     .await?;
+//!
+//! // e.g., from a path segment (`/auth/google`)
+//! let tpa = "google".parse::<WellKnownTpa>()?;
+//! let data: TokenData<Value> = registry.decrypt(&tpa, token)?;
+//! ```
+//!
+//! Each provider maps to the `uri` (and the [`JwksFormat`]) of its keys, and
+//! to the `iss` claims that its tokens carry. Registering providers with
+//! [`KeyRegistry::with_well_known`](`crate::registry::KeyRegistry::with_well_known`)
+//! also rejects tokens which carry any other issuer.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::key_caches::remote::apple::APPLE_JWK_URI;
+use crate::key_caches::remote::builder::RemoteCacheBuilder;
+use crate::key_caches::remote::config::JwksFormat;
+use crate::key_caches::remote::facebook::FACEBOOK_JWK_URI;
+use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+use crate::key_caches::remote::google::GOOGLE_JWK_URI;
+use crate::key_caches::remote::microsoft::MICROSOFT_JWK_URI;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::registry::expected::ExpectedClaims;
+
+/// A third party auth provider which this crate knows about.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WellKnownTpa {
+    Google,
+    Apple,
+    Facebook,
+    Firebase,
+    Microsoft,
+}
+
+impl WellKnownTpa {
+    /// Every well-known provider.
+    pub const ALL: [Self; 5] = [
+        Self::Google,
+        Self::Apple,
+        Self::Facebook,
+        Self::Firebase,
+        Self::Microsoft,
+    ];
+
+    /// The name of this provider (e.g., `"google"`).
+    ///
+    /// This is the name printed by its [`fmt::Display`] implementation, and
+    /// parsed by its [`FromStr`] implementation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::Apple => "apple",
+            Self::Facebook => "facebook",
+            Self::Firebase => "firebase",
+            Self::Microsoft => "microsoft",
+        }
+    }
+
+    /// The `uri` of this provider's public keys.
+    pub fn jwk_uri(&self) -> &'static str {
+        match self {
+            Self::Google => GOOGLE_JWK_URI,
+            Self::Apple => APPLE_JWK_URI,
+            Self::Facebook => FACEBOOK_JWK_URI,
+            Self::Firebase => FIREBASE_JWK_URI,
+            Self::Microsoft => MICROSOFT_JWK_URI,
+        }
+    }
+
+    /// The format in which this provider serves its public keys.
+    pub fn format(&self) -> JwksFormat {
+        match self {
+            Self::Firebase => JwksFormat::X509Map,
+            Self::Google | Self::Apple | Self::Facebook | Self::Microsoft => {
+                JwksFormat::Jwks
+            },
+        }
+    }
+
+    /// The `iss` claims that the tokens of this provider carry.
+    ///
+    /// ### Note:
+    /// The issuers of `Firebase` (i.e., one per project) and of `Microsoft`
+    /// (i.e., one per tenant) depend on the app, and are therefore not known
+    /// ahead of time; for them, this is empty.
+    pub fn issuers(&self) -> &'static [&'static str] {
+        match self {
+            Self::Google => {
+                &["https://accounts.google.com", "accounts.google.com"]
+            },
+            Self::Apple => &["https://appleid.apple.com"],
+            Self::Facebook => &["https://www.facebook.com"],
+            Self::Firebase | Self::Microsoft => &[],
+        }
+    }
+
+    /// A template of the claims that the tokens of this provider carry,
+    /// accepting any of its [`issuers`](`WellKnownTpa::issuers`).
+    ///
+    /// The audience (i.e., the client id of the app) is left empty, since it
+    /// is app-specific; see [`ExpectedClaims::audience`].
+    pub fn expected_claims(&self) -> ExpectedClaims {
+        self.issuers()
+            .iter()
+            .fold(ExpectedClaims::default(), |expected_claims, issuer| {
+                expected_claims.issuer(*issuer)
+            })
+    }
+
+    /// A [`RemoteCacheBuilder`] targeting this provider's public keys, in
+    /// their format.
+    ///
+    /// Any other configuration can be set on the returned builder as usual.
+    pub fn remote_cache_builder(&self) -> RemoteCacheBuilder {
+        RemoteCache::builder(self.jwk_uri()).format(self.format())
+    }
+}
+
+impl fmt::Display for WellKnownTpa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WellKnownTpa {
+    type Err = Error;
+
+    /// Parse the name of a provider (see
+    /// [`as_str`](`WellKnownTpa::as_str`)), ignoring its case.
+    ///
+    /// Fails with [`Error::unknown_well_known_tpa`] for any other name.
+    fn from_str(name: &str) -> prelude::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tpa| tpa.as_str().eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::unknown_well_known_tpa { name: name.into() })
+    }
+}
@@ -0,0 +1,237 @@
+//! Extracting the public key of a [`Key`] from its `x5c` certificate chain.
+//!
+//! Some providers (e.g., the `Firebase` secure-token endpoint, or older `ADFS`
+//! deployments) publish `X.509` certificates instead of the raw public key
+//! parameters (i.e., `n` and `e`). The public key is then read from the
+//! `SubjectPublicKeyInfo` of the leaf certificate (i.e., the first one in the
+//! chain), as according to
+//! [RFC5280, Section 4.1](https://datatracker.ietf.org/doc/html/rfc5280#section-4.1).
+//!
+//! Both `RSA` and `EC` public keys are read, but a [`RemoteCache`] only uses
+//! `RSA` keys; a `JWK` set of `EC` certificates alone fails to refresh with
+//! [`Error::unable_to_fetch_keys`].
+//!
+//! ### Note:
+//! The chain itself is *not* validated (i.e., neither its signatures, nor its
+//! validity period), since the `JWK` set is already trusted as a whole.
+//!
+//! [`Key`]: `crate::key_caches::remote::key::Key`
+//! [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+//! [`Error::unable_to_fetch_keys`]: `crate::error::Error::unable_to_fetch_keys`
+
+use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::error::Error;
+use crate::key_caches::remote::key::Curve;
+use crate::prelude;
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const EXPLICIT_VERSION: u8 = 0xa0;
+
+/// `rsaEncryption` (i.e., `1.2.840.113549.1.1.1`).
+const RSA_ENCRYPTION: &[u8] =
+    &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// `id-ecPublicKey` (i.e., `1.2.840.10045.2.1`).
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+/// `prime256v1` (i.e., `1.2.840.10045.3.1.7`).
+const P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// `secp384r1` (i.e., `1.3.132.0.34`).
+const P384: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+/// `secp521r1` (i.e., `1.3.132.0.35`).
+const P521: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x23];
+
+fn invalid<M>(message: M) -> Error
+where
+    String: From<M>,
+{
+    Error::invalid_certificate {
+        message: message.into(),
+    }
+}
+
+/// The public key of a leaf certificate, with each parameter `base64URL`
+/// encoded (i.e., exactly as it would appear in a [`Key`]).
+///
+/// [`Key`]: `crate::key_caches::remote::key::Key`
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub enum PublicKey {
+    /// An `RSA` public key.
+    Rsa { n: String, e: String },
+
+    /// An `EC` public key.
+    Ec { crv: Curve, x: String, y: String },
+}
+
+/// Read the public key of the leaf certificate of the given (`base64`
+/// encoded, `DER`) certificate chain.
+///
+/// Fails with [`Error::invalid_certificate`] if the chain is empty, the leaf
+/// certificate is malformed, or its key is neither an `RSA` nor a (`NIST`)
+/// `EC` key.
+pub fn leaf_public_key(x5c: &[String]) -> prelude::Result<PublicKey> {
+    let leaf = x5c.first().ok_or_else(|| invalid("The chain is empty."))?;
+    let der = STANDARD
+        .decode(leaf)
+        .map_err(|error| invalid(error.to_string()))?;
+
+    let (algorithm, parameters, key) = subject_public_key_info(&der)
+        .ok_or_else(|| invalid("The certificate is malformed."))?;
+
+    match algorithm {
+        RSA_ENCRYPTION => rsa(key),
+        EC_PUBLIC_KEY => ec(parameters, key),
+        _ => None,
+    }
+    .ok_or_else(|| invalid("The public key is unsupported."))
+}
+
+/// The (`base64` encoded, `DER`) contents of the given `PEM` encoded
+/// certificate, exactly as they would appear in an `x5c` chain.
+pub(crate) fn pem_body(pem: &str) -> Option<String> {
+    let (_, rest) = pem.split_once("-----BEGIN CERTIFICATE-----")?;
+    let (body, _) = rest.split_once("-----END CERTIFICATE-----")?;
+
+    let body = body
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+
+    (!body.is_empty()).then_some(body)
+}
+
+/// A minimal reader of `DER` encoded values.
+struct Der<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    /// Read the next value (i.e., its tag and its contents).
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.bytes.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+
+        let (length, rest) = match first {
+            0x00..=0x7f => (first as usize, rest),
+            0x81..=0x84 => {
+                let count = (first & 0x7f) as usize;
+                let (length, rest) = split_at(rest, count)?;
+                let length = length
+                    .iter()
+                    .fold(0usize, |length, &byte| length << 8 | byte as usize);
+
+                (length, rest)
+            },
+            _ => return None,
+        };
+
+        let (contents, rest) = split_at(rest, length)?;
+        self.bytes = rest;
+
+        Some((tag, contents))
+    }
+
+    /// Read the contents of the next value, which must have the given tag.
+    fn expect(&mut self, expected: u8) -> Option<&'a [u8]> {
+        self.next()
+            .filter(|(tag, _)| *tag == expected)
+            .map(|(_, contents)| contents)
+    }
+}
+
+fn split_at(bytes: &[u8], at: usize) -> Option<(&[u8], &[u8])> {
+    (at <= bytes.len()).then(|| bytes.split_at(at))
+}
+
+/// Read the algorithm, its parameters, and the (raw) key of the
+/// `SubjectPublicKeyInfo` of the given certificate.
+fn subject_public_key_info(der: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let certificate = Der { bytes: der }.expect(SEQUENCE)?;
+    let tbs_certificate = Der { bytes: certificate }.expect(SEQUENCE)?;
+    let mut fields = Der {
+        bytes: tbs_certificate,
+    };
+
+    // The version is optional, and precedes the serial number.
+    let (tag, _) = fields.next()?;
+    if tag == EXPLICIT_VERSION {
+        let _ = fields.expect(INTEGER)?;
+    };
+
+    // The signature algorithm, issuer, validity, and subject.
+    for _ in 0..4 {
+        let _ = fields.expect(SEQUENCE)?;
+    }
+
+    let mut info = Der {
+        bytes: fields.expect(SEQUENCE)?,
+    };
+    let mut algorithm = Der {
+        bytes: info.expect(SEQUENCE)?,
+    };
+
+    let oid = algorithm.expect(OBJECT_IDENTIFIER)?;
+    let parameters = algorithm.next().map_or(&[][..], |(_, contents)| contents);
+
+    // The first byte counts the unused bits, of which there are none.
+    let key = match info.expect(BIT_STRING)?.split_first()? {
+        (0, key) => key,
+        _ => return None,
+    };
+
+    Some((oid, parameters, key))
+}
+
+/// Read an `RSAPublicKey` (i.e., its modulus and exponent).
+fn rsa(key: &[u8]) -> Option<PublicKey> {
+    let mut key = Der {
+        bytes: Der { bytes: key }.expect(SEQUENCE)?,
+    };
+
+    let n = unsigned(key.expect(INTEGER)?);
+    let e = unsigned(key.expect(INTEGER)?);
+
+    Some(PublicKey::Rsa {
+        n: URL_SAFE_NO_PAD.encode(n),
+        e: URL_SAFE_NO_PAD.encode(e),
+    })
+}
+
+/// Read an (uncompressed) `EC` point on the curve named by the given
+/// parameters.
+fn ec(parameters: &[u8], key: &[u8]) -> Option<PublicKey> {
+    let crv = match parameters {
+        P256 => Curve::P256,
+        P384 => Curve::P384,
+        P521 => Curve::P521,
+        _ => return None,
+    };
+
+    let point = match key.split_first()? {
+        (0x04, point) if point.len() % 2 == 0 => point,
+        _ => return None,
+    };
+    let (x, y) = point.split_at(point.len() / 2);
+
+    Some(PublicKey::Ec {
+        crv,
+        x: URL_SAFE_NO_PAD.encode(x),
+        y: URL_SAFE_NO_PAD.encode(y),
+    })
+}
+
+/// Strip the leading zeros of a (non-negative) `DER` integer.
+fn unsigned(integer: &[u8]) -> &[u8] {
+    match integer {
+        [0, rest @ ..] if !rest.is_empty() => unsigned(rest),
+        integer => integer,
+    }
+}
@@ -0,0 +1,236 @@
+//! Rejecting replayed tokens.
+//!
+//! A verified token remains valid until it expires, so a token which was
+//! intercepted (or which a buggy client sends twice, as often happens with
+//! `Sign in with Apple` and `Facebook Limited Login`) is accepted every time
+//! it is sent. A [`super::remote::RemoteCache`] with a [`ReplayGuard`]
+//! records every token it verifies until the token expires, and rejects any
+//! token which it has already seen with [`Error::replayed_token`]:
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder(APPLE_JWK_URI)
+//!     .replay_guard(ReplayGuard::default())
+//!     .build()?;
+//! ```
+//!
+//! Tokens are identified by their `iss` and `jti` claims. Tokens without a
+//! `jti` (e.g., `Apple`'s `ID` tokens) are identified by a digest of their
+//! signature instead.
+//!
+//! By default, tokens are recorded in-process (see [`MemoryReplayStore`]).
+//! Services with multiple replicas need a shared [`ReplayStore`] instead,
+//! which records each token atomically (e.g., `SET NX EXAT` in `Redis`).
+//!
+//! ### Note:
+//! Only the tokens which pass every check of the cache (i.e., their
+//! signature and `exp` time) are recorded, so that forged tokens cannot fill
+//! up the store. Tokens are rejected if their `exp` claim is missing, since
+//! they could never be forgotten.
+//!
+//! [`Error::replayed_token`]: `crate::error::Error::replayed_token`
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::Validation;
+use ring::digest::digest;
+use ring::digest::SHA256;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::prelude;
+use crate::prelude::Timestamp;
+
+/// A (thread-safe) record of the tokens which were already verified.
+///
+/// See the [module level documentation](`self`).
+pub trait ReplayStore: Send + Sync {
+    /// Record the given key until the given time (in Unix-Time).
+    ///
+    /// Returns `false` (keeping the previous record) if the key is already
+    /// recorded, and has not expired yet at the given current time (as read
+    /// from the clock of the cache, see [`crate::time`]). Checking and
+    /// recording a key must be atomic, so that concurrent verifications of
+    /// the same token cannot both succeed.
+    fn insert(
+        &self,
+        key: &str,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> prelude::Result<bool>;
+}
+
+impl<S> ReplayStore for Arc<S>
+where
+    S: ReplayStore + ?Sized,
+{
+    fn insert(
+        &self,
+        key: &str,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> prelude::Result<bool> {
+        (**self).insert(key, expires_at, now)
+    }
+}
+
+/// An in-memory [`ReplayStore`].
+///
+/// Keys which have expired by the current time (as given to
+/// [`insert`](`ReplayStore::insert`)) are evicted whenever a key is inserted.
+#[derive(Debug, Default)]
+pub struct MemoryReplayStore {
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The keys, along with the time that they expire at.
+    keys: BTreeMap<String, u64>,
+
+    /// The keys, ordered by the time that they expire at.
+    expiries: BTreeSet<(u64, String)>,
+}
+
+impl MemoryReplayStore {
+    /// The number of recorded keys (including the expired keys which have
+    /// not been evicted yet).
+    pub fn len(&self) -> usize {
+        self.lock().keys.len()
+    }
+
+    /// Check to see if no key is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ReplayStore for MemoryReplayStore {
+    fn insert(
+        &self,
+        key: &str,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> prelude::Result<bool> {
+        let mut entries = self.lock();
+        let Entries { keys, expiries } = &mut *entries;
+
+        while let Some((expiry, _)) = expiries.first() {
+            if *expiry > now {
+                break;
+            };
+
+            if let Some((_, expired)) = expiries.pop_first() {
+                let _ = keys.remove(&expired);
+            };
+        }
+
+        if keys.contains_key(key) {
+            return Ok(false);
+        };
+
+        let _ = keys.insert(key.into(), expires_at);
+        let _ = expiries.insert((expires_at, key.into()));
+
+        Ok(true)
+    }
+}
+
+/// Records every verified token inside of a [`ReplayStore`], and rejects the
+/// tokens which were already recorded.
+///
+/// Cloning a guard shares its store (e.g., between the caches of providers
+/// which share an issuer).
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone)]
+pub struct ReplayGuard {
+    store: Arc<dyn ReplayStore>,
+}
+
+impl fmt::Debug for ReplayGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplayGuard").finish_non_exhaustive()
+    }
+}
+
+impl Default for ReplayGuard {
+    /// A guard recording tokens inside of a [`MemoryReplayStore`].
+    fn default() -> Self {
+        Self::new(MemoryReplayStore::default())
+    }
+}
+
+impl ReplayGuard {
+    /// Create a [`ReplayGuard`] recording tokens inside of the given store.
+    pub fn new<S>(store: S) -> Self
+    where
+        S: ReplayStore + 'static,
+    {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    /// Record the given (verified) token, along with its claims, at the given
+    /// time (in Unix-Time).
+    ///
+    /// Tokens are recorded until they expire (including the leeway that
+    /// their `exp` is checked with).
+    ///
+    /// Fails with [`Error::replayed_token`] if the token was already
+    /// recorded, with [`Error::unable_to_verify_token`] if its `exp` claim is
+    /// missing, or with the error of the store.
+    pub fn check(
+        &self,
+        token: &str,
+        claims: &Value,
+        now: Timestamp,
+    ) -> prelude::Result<()> {
+        let Self { store } = self;
+
+        let exp = claims.get("exp").and_then(Value::as_u64).ok_or_else(|| {
+            jsonwebtoken::errors::Error::from(ErrorKind::MissingRequiredClaim(
+                "exp".into(),
+            ))
+        })?;
+        let Validation { leeway, .. } = Validation::default();
+
+        let expires_at = exp.saturating_add(leeway);
+
+        match store.insert(&key(token, claims), expires_at, now)? {
+            true => Ok(()),
+            false => Err(Error::replayed_token),
+        }
+    }
+}
+
+/// The key that the given token is recorded by.
+///
+/// The `iss` is prefixed with its length, so that no other pair of `iss` and
+/// `jti` claims (which may contain any character) can share the same key.
+fn key(token: &str, claims: &Value) -> String {
+    let iss = claims.get("iss").and_then(Value::as_str).unwrap_or_default();
+
+    match claims.get("jti").and_then(Value::as_str) {
+        Some(jti) => format!("jti {}:{} {}", iss.len(), iss, jti),
+        None => {
+            let (_, signature) = token.rsplit_once('.').unwrap_or_default();
+            let hash = digest(&SHA256, signature.as_bytes());
+
+            format!("sig {}", URL_SAFE_NO_PAD.encode(hash))
+        },
+    }
+}
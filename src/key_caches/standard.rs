@@ -0,0 +1,70 @@
+//! A general-purpose claims type, for tokens without a claims type of their
+//! own.
+//!
+//! [`StandardClaims`] holds the registered claims of
+//! [RFC7519, Section 4.1](https://datatracker.ietf.org/doc/html/rfc7519#section-4.1)
+//! (each of which is optional), and keeps any other claim as is:
+//!
+//! ```ignore
+//! let TokenData { claims, .. } =
+//!     remote_cache.decrypt::<StandardClaims, _>(token)?;
+//!
+//! if !claims.audience_contains("my-client-id") {
+//!     // ...
+//! }
+//!
+//! let roles = claims.permissions().roles;
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::authorization::permissions::Permissions;
+use crate::key_caches::registered::Audience;
+use crate::time::now;
+
+/// The registered claims of a token, along with any other claim.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct StandardClaims {
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+
+    pub aud: Option<Audience>,
+
+    pub exp: Option<u64>,
+    pub nbf: Option<u64>,
+    pub iat: Option<u64>,
+    pub jti: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl StandardClaims {
+    /// The time left until the token expires.
+    ///
+    /// Returns [`None`] if the token has already expired, or if it has no
+    /// `exp` claim at all.
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.exp
+            .and_then(|exp| exp.checked_sub(now()))
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Check to see if the given audience is one of the token's audiences.
+    pub fn audience_contains(&self, audience: &str) -> bool {
+        self.aud.as_ref().is_some_and(|aud| aud.contains(audience))
+    }
+
+    /// The scopes and roles granted by the token (see
+    /// [`permissions`](`crate::authorization::permissions`)).
+    pub fn permissions(&self) -> Permissions {
+        Permissions::from_fn(|name| self.extra.get(name))
+    }
+}
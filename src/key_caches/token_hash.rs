@@ -0,0 +1,102 @@
+//! Checking the `at_hash` and `c_hash` claims of `ID` tokens.
+//!
+//! An `ID` token which is issued along with an access token (or an
+//! authorization code) binds it through the `at_hash` (or the `c_hash`)
+//! claim: the left-most half of the hash of its `ASCII` representation,
+//! `base64URL` encoded, using the hash algorithm of the `alg` of the `ID`
+//! token (e.g., `SHA-256` for `RS256`). Checking it ensures that the access
+//! token was not swapped for another one:
+//!
+//! ```ignore
+//! let TokenData { header, claims } =
+//!     remote_cache.decrypt::<GoogleClaims, _>(id_token)?;
+//!
+//! check_at_hash(claims.at_hash.as_deref(), header.alg, &access_token)?;
+//! ```
+//!
+//! For more information, please visit: <https://openid.net/specs/openid-connect-core-1_0.html#CodeIDToken>.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::Algorithm;
+use ring::constant_time::verify_slices_are_equal;
+use ring::digest::digest;
+use ring::digest::SHA256;
+use ring::digest::SHA384;
+use ring::digest::SHA512;
+
+use crate::error::Error;
+use crate::prelude;
+
+/// The left-most half of the hash of the given value (e.g., an access token),
+/// `base64URL` encoded, using the hash algorithm of the given `alg`.
+///
+/// `EdDSA` tokens are hashed using `SHA-512` (i.e., as for `Ed25519`).
+pub fn token_hash(alg: Algorithm, value: &str) -> String {
+    let algorithm = match alg {
+        Algorithm::HS256
+        | Algorithm::RS256
+        | Algorithm::PS256
+        | Algorithm::ES256 => &SHA256,
+        Algorithm::HS384
+        | Algorithm::RS384
+        | Algorithm::PS384
+        | Algorithm::ES384 => &SHA384,
+        Algorithm::HS512
+        | Algorithm::RS512
+        | Algorithm::PS512
+        | Algorithm::EdDSA => &SHA512,
+    };
+
+    let hash = digest(algorithm, value.as_bytes());
+    let hash = hash.as_ref();
+    let left_half = hash.iter().take(hash.len() / 2).copied();
+
+    URL_SAFE_NO_PAD.encode(left_half.collect::<Vec<_>>())
+}
+
+/// Check that the given `at_hash` claim binds the given access token.
+///
+/// Fails with [`Error::token_hash_mismatch`] if the claim is missing, or does
+/// not match.
+///
+/// See the [module level documentation](`self`).
+pub fn check_at_hash(
+    at_hash: Option<&str>,
+    alg: Algorithm,
+    access_token: &str,
+) -> prelude::Result<()> {
+    check("at_hash", at_hash, alg, access_token)
+}
+
+/// Check that the given `c_hash` claim binds the given authorization code.
+///
+/// Fails with [`Error::token_hash_mismatch`] if the claim is missing, or does
+/// not match.
+///
+/// See the [module level documentation](`self`).
+pub fn check_c_hash(
+    c_hash: Option<&str>,
+    alg: Algorithm,
+    code: &str,
+) -> prelude::Result<()> {
+    check("c_hash", c_hash, alg, code)
+}
+
+/// Check that the given claim is the hash of the given value.
+fn check(
+    claim: &str,
+    hash: Option<&str>,
+    alg: Algorithm,
+    value: &str,
+) -> prelude::Result<()> {
+    let expected = token_hash(alg, value);
+
+    hash.filter(|hash| {
+        verify_slices_are_equal(hash.as_bytes(), expected.as_bytes()).is_ok()
+    })
+    .map(|_| ())
+    .ok_or_else(|| Error::token_hash_mismatch {
+        claim: claim.into(),
+    })
+}
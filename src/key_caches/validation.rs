@@ -0,0 +1,286 @@
+//! Validating the registered claims of a (verified) token.
+//!
+//! Mirrors the validation of [`jsonwebtoken::decode`], except that the claims
+//! are borrowed from the decoded payload, and are checked against the given
+//! time (instead of always reading the time of the system).
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::Validation;
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+
+use crate::prelude;
+use crate::prelude::Timestamp;
+
+/// A registered claim, which is only checked if it has the expected type.
+#[derive(Default)]
+enum Claim<T> {
+    Parsed(T),
+    Invalid,
+    #[default]
+    Missing,
+}
+
+impl<'de, T> Deserialize<'de> for Claim<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match Option::<T>::deserialize(deserializer) {
+            Ok(Some(value)) => Self::Parsed(value),
+            Ok(None) => Self::Missing,
+            Err(_) => Self::Invalid,
+        })
+    }
+}
+
+impl<T> Claim<T> {
+    fn is_parsed(&self) -> bool {
+        matches!(self, Self::Parsed(_))
+    }
+}
+
+/// A `NumericDate`, which may be given as a (non-negative) float as well.
+struct NumericDate(u64);
+
+impl<'de> Deserialize<'de> for NumericDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor(PhantomData<NumericDate>);
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = NumericDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a non-negative number")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<NumericDate, E>
+            where
+                E: de::Error,
+            {
+                Ok(NumericDate(value))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<NumericDate, E>
+            where
+                E: de::Error,
+            {
+                let in_range = value >= 0.0 && value < u64::MAX as f64;
+
+                match value.is_finite() && in_range {
+                    true => Ok(NumericDate(value.round() as u64)),
+                    false => Err(E::custom("a non-negative number")),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+/// A string (e.g., an `aud` or an `iss`), or an array of them.
+///
+/// Strings without escape sequences are borrowed from the payload.
+enum OneOrMany<'a> {
+    One(Cow<'a, str>),
+    Many(Vec<Cow<'a, str>>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for OneOrMany<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<'a>(PhantomData<OneOrMany<'a>>);
+
+        /// A string, borrowed if possible.
+        struct Borrowed<'a>(Cow<'a, str>);
+
+        impl<'de: 'a, 'a> Deserialize<'de> for Borrowed<'a> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                match deserializer.deserialize_str(Visitor(PhantomData))? {
+                    OneOrMany::One(value) => Ok(Borrowed(value)),
+                    OneOrMany::Many(_) => Err(de::Error::custom("a string")),
+                }
+            }
+        }
+
+        impl<'de: 'a, 'a> de::Visitor<'de> for Visitor<'a> {
+            type Value = OneOrMany<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string, or an array of strings")
+            }
+
+            fn visit_borrowed_str<E>(
+                self,
+                value: &'de str,
+            ) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(OneOrMany::One(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(OneOrMany::One(Cow::Owned(value.into())))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+
+                while let Some(Borrowed(value)) = seq.next_element()? {
+                    values.push(value);
+                }
+
+                Ok(OneOrMany::Many(values))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+impl OneOrMany<'_> {
+    /// Check to see if any of the values is one of the given ones.
+    fn is_any_of<'a, I>(&self, expected: I) -> bool
+    where
+        I: IntoIterator<Item = &'a String> + Clone,
+    {
+        let is_expected =
+            |value: &str| expected.clone().into_iter().any(|e| e == value);
+
+        match self {
+            Self::One(value) => is_expected(value),
+            Self::Many(values) => values.iter().any(|value| is_expected(value)),
+        }
+    }
+}
+
+/// The registered claims which are validated.
+#[derive(Deserialize)]
+struct RegisteredClaims<'a> {
+    #[serde(default)]
+    exp: Claim<NumericDate>,
+
+    #[serde(default)]
+    nbf: Claim<NumericDate>,
+
+    #[serde(borrow, default)]
+    sub: Claim<Cow<'a, str>>,
+
+    #[serde(borrow, default)]
+    iss: Claim<OneOrMany<'a>>,
+
+    #[serde(borrow, default)]
+    aud: Claim<OneOrMany<'a>>,
+}
+
+/// Validate the registered claims of the given (decoded) payload, as of the
+/// given time.
+///
+/// The required claims are checked first, then the `exp` and `nbf` claims,
+/// and then the `sub`, `iss`, and `aud` claims, exactly as in `jsonwebtoken`.
+/// Claims of an unexpected type are treated as missing.
+pub(crate) fn validate(
+    payload: &[u8],
+    validation: &Validation,
+    now: Timestamp,
+) -> prelude::Result<()> {
+    let Validation {
+        required_spec_claims,
+        leeway,
+        validate_exp,
+        validate_nbf,
+        aud: expected_aud,
+        iss: expected_iss,
+        sub: expected_sub,
+        ..
+    } = validation;
+    let RegisteredClaims {
+        exp,
+        nbf,
+        sub,
+        iss,
+        aud,
+    } = serde_json::from_slice(payload)
+        .map_err(jsonwebtoken::errors::Error::from)?;
+
+    let error = |kind| Err(jsonwebtoken::errors::Error::from(kind).into());
+
+    for claim in required_spec_claims {
+        let present = match claim.as_str() {
+            "exp" => exp.is_parsed(),
+            "nbf" => nbf.is_parsed(),
+            "sub" => sub.is_parsed(),
+            "iss" => iss.is_parsed(),
+            "aud" => aud.is_parsed(),
+            _ => continue,
+        };
+
+        if !present {
+            return error(ErrorKind::MissingRequiredClaim(claim.clone()));
+        };
+    }
+
+    match exp {
+        Claim::Parsed(NumericDate(exp))
+            if *validate_exp && exp < now.saturating_sub(*leeway) =>
+        {
+            return error(ErrorKind::ExpiredSignature)
+        },
+        _ => (),
+    };
+
+    match nbf {
+        Claim::Parsed(NumericDate(nbf))
+            if *validate_nbf && nbf > now.saturating_add(*leeway) =>
+        {
+            return error(ErrorKind::ImmatureSignature)
+        },
+        _ => (),
+    };
+
+    match (sub, expected_sub) {
+        (Claim::Parsed(sub), Some(expected)) if *sub != **expected => {
+            return error(ErrorKind::InvalidSubject)
+        },
+        _ => (),
+    };
+
+    match (iss, expected_iss) {
+        (Claim::Parsed(iss), Some(expected)) if !iss.is_any_of(expected) => {
+            return error(ErrorKind::InvalidIssuer)
+        },
+        _ => (),
+    };
+
+    match (aud, expected_aud) {
+        (Claim::Parsed(aud), Some(expected)) if !aud.is_any_of(expected) => {
+            return error(ErrorKind::InvalidAudience)
+        },
+        _ => (),
+    };
+
+    Ok(())
+}
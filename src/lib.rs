@@ -63,11 +63,40 @@
 //! ### Notes
 //! [`crate::key_caches::remote::RemoteCache`] expects the `JWK`s to use the
 //! `RSA` family of cryptographic algorithms.
+//!
+//! ### No panics:
+//! Nothing in this crate panics on untrusted input (i.e., tokens, `JWK` sets,
+//! certificates, snapshots, or headers). This is enforced by denying
+//! `unwrap`, `expect`, `panic!`, and unchecked indexing outside of tests; the
+//! few deliberate exceptions are documented under a `### Panics:` section.
+
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+    )
+)]
 
 pub extern crate jsonwebtoken;
 
+pub mod api;
+pub mod authorization;
+pub mod challenge;
+pub mod diff;
+pub mod doctor;
 pub mod error;
+pub mod insecure;
 pub mod key_caches;
+pub mod observer;
+pub mod redact;
+pub mod registry;
+pub mod tasks;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod time;
 
 pub mod prelude {
     //! Convenience re-exports for when working with this crate.
@@ -78,15 +107,155 @@ pub mod prelude {
     /// purposes.
     pub type Result<T> = std::result::Result<T, crate::error::Error>;
 
+    /// A point in time, in Unix-Time (i.e., seconds since the epoch).
+    pub type Timestamp = u64;
+
+    #[cfg(feature = "cedar")]
+    pub use crate::authorization::cedar::CedarHook;
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub use crate::authorization::opa::OpaHook;
+    pub use crate::authorization::permissions::Permissions;
+    pub use crate::authorization::step_up::StepUpPolicy;
+    pub use crate::authorization::AuthorizationHook;
+    pub use crate::authorization::RequestContext;
+    pub use crate::challenge::BearerChallenge;
+    pub use crate::challenge::BearerError;
+    pub use crate::diff::claims_diff;
+    pub use crate::diff::ClaimChange;
+    pub use crate::diff::ClaimsDiff;
+    pub use crate::doctor::Finding;
+    pub use crate::doctor::KeyReport;
+    pub use crate::doctor::Report;
+    pub use crate::doctor::Severity;
+    pub use crate::error::Advice;
     pub use crate::error::Error;
+    pub use crate::key_caches::header_cache::HeaderCache;
+    pub use crate::key_caches::header_cache::LruHeaderCache;
+    pub use crate::key_caches::key_cache::KeyCache;
+    pub use crate::key_caches::local::quota::FixedWindowQuota;
+    pub use crate::key_caches::local::quota::IssuanceQuota;
+    pub use crate::key_caches::local::RotationApprover;
+    pub use crate::key_caches::nonce::check_nonce;
+    pub use crate::key_caches::normalized::NormalizedClaims;
+    pub use crate::key_caches::registered::check_registered_claims;
+    pub use crate::key_caches::registered::Audience;
     pub use crate::key_caches::remote::apple::AppleClaims;
     pub use crate::key_caches::remote::apple::APPLE_JWK_URI;
+    pub use crate::key_caches::remote::apple::RealUserStatus;
+    pub use crate::key_caches::remote::auth0::auth0_issuer;
+    pub use crate::key_caches::remote::auth0::auth0_jwk_uri;
+    pub use crate::key_caches::remote::auth0::Auth0Claims;
+    pub use crate::key_caches::remote::auto_refresh::AutoRefresh;
+    pub use crate::key_caches::remote::auto_refresh::AutoRefreshHandle;
+    pub use crate::key_caches::remote::auto_refresh::RefreshSchedule;
+    pub use crate::key_caches::remote::builder::RemoteCacheBuilder;
+    pub use crate::key_caches::remote::cognito::cognito_issuer;
+    pub use crate::key_caches::remote::cognito::cognito_jwk_uri;
+    pub use crate::key_caches::remote::cognito::CognitoClaims;
+    pub use crate::key_caches::remote::cognito::CognitoUserPool;
+    pub use crate::key_caches::remote::cognito::TokenUse;
+    pub use crate::key_caches::remote::config::FetchConfig;
+    pub use crate::key_caches::remote::config::JwksFormat;
+    pub use crate::key_caches::remote::config::RedirectPolicy;
+    pub use crate::key_caches::remote::config::RetryPolicy;
+    pub use crate::key_caches::remote::discovery::ProviderMetadata;
     pub use crate::key_caches::remote::facebook::FacebookClaims;
     pub use crate::key_caches::remote::facebook::FACEBOOK_JWK_URI;
+    pub use crate::key_caches::remote::firebase::FirebaseClaims;
+    pub use crate::key_caches::remote::firebase::FirebaseInfo;
+    pub use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+    pub use crate::key_caches::remote::firebase::firebase_issuer;
+    pub use crate::key_caches::remote::failover::FailoverEvent;
+    pub use crate::key_caches::remote::failover::FailoverPolicy;
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub use crate::key_caches::remote::fetcher::HyperFetcher;
+    pub use crate::key_caches::remote::fetcher::JwksFetcher;
+    pub use crate::key_caches::remote::fetcher::JwksResponse;
+    pub use crate::key_caches::remote::github_actions::GitHubActionsClaims;
+    pub use crate::key_caches::remote::github_actions::GITHUB_ACTIONS_ISSUER;
+    pub use crate::key_caches::remote::github_actions::GITHUB_ACTIONS_JWK_URI;
     pub use crate::key_caches::remote::google::GoogleClaims;
     pub use crate::key_caches::remote::google::GOOGLE_JWK_URI;
+    pub use crate::key_caches::remote::jwks::KeySet;
+    pub use crate::key_caches::remote::jwks::Stamped;
+    pub use crate::key_caches::remote::key::Curve;
     pub use crate::key_caches::remote::key::Key;
+    pub use crate::key_caches::remote::key::KeyOperation;
     pub use crate::key_caches::remote::key::KeyType;
     pub use crate::key_caches::remote::key::Use;
+    pub use crate::key_caches::remote::keycloak::keycloak_issuer;
+    pub use crate::key_caches::remote::keycloak::keycloak_jwk_uri;
+    pub use crate::key_caches::remote::keycloak::KeycloakClaims;
+    pub use crate::key_caches::remote::keycloak::KeycloakRoles;
+    pub use crate::key_caches::remote::kubernetes::KubernetesClaims;
+    pub use crate::key_caches::remote::kubernetes::KubernetesInfo;
+    pub use crate::key_caches::remote::kubernetes::KubernetesObject;
+    pub use crate::key_caches::remote::kubernetes::KUBERNETES_JWK_URI;
+    pub use crate::key_caches::remote::kubernetes::SERVICE_ACCOUNT_DIR;
+    pub use crate::key_caches::remote::line::LineClaims;
+    pub use crate::key_caches::remote::line::LINE_ISSUER;
+    pub use crate::key_caches::remote::line::LINE_JWK_URI;
+    pub use crate::key_caches::remote::microsoft::MicrosoftClaims;
+    pub use crate::key_caches::remote::microsoft::MICROSOFT_JWK_URI;
+    pub use crate::key_caches::remote::okta::okta_issuer;
+    pub use crate::key_caches::remote::okta::okta_jwk_uri;
+    pub use crate::key_caches::remote::okta::OktaClaims;
+    pub use crate::key_caches::remote::policy::RefreshAheadPolicy;
+    pub use crate::key_caches::remote::policy::StalePolicy;
+    pub use crate::key_caches::remote::prewarm::PrewarmedKeys;
+    pub use crate::key_caches::remote::prewarm::RsaComponents;
+    pub use crate::key_caches::remote::provenance::Provenance;
+    pub use crate::key_caches::remote::provenance::Verified;
+    pub use crate::key_caches::remote::provenance::VerifiedToken;
+    pub use crate::key_caches::remote::rotation::RotationEvent;
+    pub use crate::key_caches::remote::rotation::ROTATION_CHANNEL_CAPACITY;
+    pub use crate::key_caches::remote::snapshot::Snapshot;
+    pub use crate::key_caches::remote::store::CacheStore;
+    pub use crate::key_caches::remote::store::FileStore;
+    #[cfg(feature = "redis")]
+    pub use crate::key_caches::remote::store::redis::RedisStore;
+    pub use crate::key_caches::remote::tls::Certificate;
+    pub use crate::key_caches::remote::tls::Identity;
+    pub use crate::key_caches::remote::twitch::TwitchClaims;
+    pub use crate::key_caches::remote::twitch::TWITCH_ISSUER;
+    pub use crate::key_caches::remote::twitch::TWITCH_JWK_URI;
+    pub use crate::key_caches::remote::well_known::WellKnownTpa;
+    pub use crate::key_caches::remote::x509::PublicKey;
     pub use crate::key_caches::remote::RemoteCache;
+    pub use crate::key_caches::replay::MemoryReplayStore;
+    pub use crate::key_caches::replay::ReplayGuard;
+    pub use crate::key_caches::replay::ReplayStore;
+    pub use crate::key_caches::standard::StandardClaims;
+    pub use crate::key_caches::token_hash::check_at_hash;
+    pub use crate::key_caches::token_hash::check_c_hash;
+    pub use crate::key_caches::token_hash::token_hash;
+    #[cfg(feature = "metrics")]
+    pub use crate::observer::metrics::MetricsObserver;
+    pub use crate::observer::CacheObserver;
+    pub use crate::redact::Redacted;
+    pub use crate::registry::builder::BuildReport;
+    pub use crate::registry::builder::KeyRegistryBuilder;
+    pub use crate::registry::expected::ExpectedClaims;
+    pub use crate::registry::freshness::CacheStatus;
+    pub use crate::registry::lifetime::Histogram;
+    pub use crate::registry::lifetime::LifetimeStats;
+    pub use crate::registry::lifetime::TokenLifetime;
+    pub use crate::registry::lifetime::TokenLifetimes;
+    pub use crate::registry::maintenance::MaintenanceWindow;
+    #[cfg(feature = "json-schema")]
+    pub use crate::registry::schema::ClaimsSchema;
+    pub use crate::registry::shadow::ShadowComparisons;
+    pub use crate::registry::shadow::ShadowOutcome;
+    pub use crate::registry::shadow::ShadowStats;
+    pub use crate::registry::sharded::ShardedKeyRegistry;
+    pub use crate::registry::shared::SharedKeyRegistry;
+    pub use crate::registry::validator::require_claim;
+    pub use crate::registry::validator::ValidationError;
+    pub use crate::registry::KeyRegistry;
+    pub use crate::registry::RefreshStatus;
+    pub use crate::tasks::TaskSet;
+    pub use crate::time::Clock;
+    pub use crate::time::Expiry;
+    pub use crate::time::ManualClock;
+    pub use crate::time::SystemClock;
 }
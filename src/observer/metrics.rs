@@ -0,0 +1,124 @@
+//! Recording the events of caches through the
+//! [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Every event increments a counter, labeled with the `uri` of the cache:
+//!
+//! | Counter                              | Labels                |
+//! |--------------------------------------|-----------------------|
+//! | [`CACHE_HITS`]                       | `uri`                 |
+//! | [`CACHE_MISSES`]                     | `uri`                 |
+//! | [`REFRESHES_STARTED`]                | `uri`                 |
+//! | [`REFRESHES`]                        | `uri`, `outcome`      |
+//! | [`DECRYPT_FAILURES`]                 | `uri`, `reason`       |
+//!
+//! The `outcome` of a refresh is either `success` or `failure`. The `reason`
+//! of a failure is its [`Advice`] (e.g., `refresh_keys`), which keeps the
+//! number of distinct labels small.
+//!
+//! ```ignore
+//! PrometheusBuilder::new().install()?;
+//!
+//! let observer = MetricsObserver::default();
+//! observer.describe();
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .observer(observer)
+//!     .finish()
+//!     .await?;
+//! ```
+//!
+//! [`Advice`]: `crate::error::Advice`
+
+use crate::error::Error;
+use crate::observer::CacheObserver;
+
+/// The number of tokens whose key was found in the cache.
+pub const CACHE_HITS: &str = "webcipher_cache_hits_total";
+
+/// The number of tokens whose key was not found in the cache.
+pub const CACHE_MISSES: &str = "webcipher_cache_misses_total";
+
+/// The number of refreshes which were started.
+pub const REFRESHES_STARTED: &str = "webcipher_refreshes_started_total";
+
+/// The number of refreshes which finished, by outcome.
+pub const REFRESHES: &str = "webcipher_refreshes_total";
+
+/// The number of tokens which were rejected, by reason.
+pub const DECRYPT_FAILURES: &str = "webcipher_decrypt_failures_total";
+
+/// A [`CacheObserver`] which records every event through the installed
+/// `metrics` recorder.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Copy, Hash, Debug, Default, PartialEq, Eq)]
+pub struct MetricsObserver;
+
+impl MetricsObserver {
+    /// Describe every counter to the installed `metrics` recorder (e.g., for
+    /// the `HELP` lines of a `Prometheus` exporter).
+    pub fn describe(&self) {
+        metrics::describe_counter!(
+            CACHE_HITS,
+            "The number of tokens whose key was found in the cache."
+        );
+        metrics::describe_counter!(
+            CACHE_MISSES,
+            "The number of tokens whose key was not found in the cache."
+        );
+        metrics::describe_counter!(
+            REFRESHES_STARTED,
+            "The number of refreshes which were started."
+        );
+        metrics::describe_counter!(
+            REFRESHES,
+            "The number of refreshes which finished, by outcome."
+        );
+        metrics::describe_counter!(
+            DECRYPT_FAILURES,
+            "The number of tokens which were rejected, by reason."
+        );
+    }
+}
+
+impl CacheObserver for MetricsObserver {
+    fn on_cache_hit(&self, uri: &http::Uri, _: &str) {
+        metrics::increment_counter!(CACHE_HITS, "uri" => uri.to_string());
+    }
+
+    fn on_cache_miss(&self, uri: &http::Uri, _: Option<&str>) {
+        metrics::increment_counter!(CACHE_MISSES, "uri" => uri.to_string());
+    }
+
+    fn on_refresh_start(&self, uri: &http::Uri) {
+        metrics::increment_counter!(
+            REFRESHES_STARTED,
+            "uri" => uri.to_string(),
+        );
+    }
+
+    fn on_refresh_success(&self, uri: &http::Uri) {
+        metrics::increment_counter!(
+            REFRESHES,
+            "uri" => uri.to_string(),
+            "outcome" => "success",
+        );
+    }
+
+    fn on_refresh_failure(&self, uri: &http::Uri, _: &Error) {
+        metrics::increment_counter!(
+            REFRESHES,
+            "uri" => uri.to_string(),
+            "outcome" => "failure",
+        );
+    }
+
+    fn on_decrypt_failure(&self, uri: &http::Uri, reason: &Error) {
+        metrics::increment_counter!(
+            DECRYPT_FAILURES,
+            "uri" => uri.to_string(),
+            "reason" => reason.advice().as_str(),
+        );
+    }
+}
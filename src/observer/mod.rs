@@ -0,0 +1,113 @@
+//! Observing the caches (e.g., for metrics).
+//!
+//! A [`RemoteCache`] with a [`CacheObserver`] reports whether each token's
+//! key was found in the cache, every refresh (along with its outcome), and
+//! every token which it failed to verify:
+//!
+//! ```ignore
+//! let remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .observer(MyObserver::default())
+//!     .build()?;
+//! ```
+//!
+//! A [`KeyRegistry`] gives its observer (see
+//! [`KeyRegistryBuilder::observer`]) to the cache of every provider, and
+//! reports the tokens which it rejects itself (e.g., whose claims fail a
+//! validator) as well. Every event carries the `uri` of the cache, so that
+//! caches can be told apart.
+//!
+//! With the `metrics` feature, [`MetricsObserver`](`metrics::MetricsObserver`)
+//! records every event through the [`metrics`](https://docs.rs/metrics)
+//! facade (e.g., for a `Prometheus` exporter).
+//!
+//! ### Note:
+//! Observers are called synchronously, while the token is being verified (or
+//! the cache is being refreshed), and must therefore be cheap.
+//!
+//! [`RemoteCache`]: `crate::key_caches::remote::RemoteCache`
+//! [`KeyRegistry`]: `crate::registry::KeyRegistry`
+//! [`KeyRegistryBuilder::observer`]: `crate::registry::builder::KeyRegistryBuilder::observer`
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use crate::error::Error;
+
+/// A (thread-safe) observer of the events of caches.
+///
+/// Every method does nothing by default, so that observers only need to
+/// implement the events that they are interested in.
+///
+/// See the [module level documentation](`self`).
+pub trait CacheObserver: Send + Sync {
+    /// The key of a token was found in the cache at the given `uri`.
+    ///
+    /// Reports the `kid` of the key (which differs from the `kid` of the
+    /// token, if the token was matched by its certificate thumbprint, or by
+    /// trying every key).
+    fn on_cache_hit(&self, uri: &http::Uri, kid: &str) {
+        let _ = (uri, kid);
+    }
+
+    /// The key of a token was not found in the cache at the given `uri`.
+    ///
+    /// Reports the `kid` of the token, if it has one.
+    fn on_cache_miss(&self, uri: &http::Uri, kid: Option<&str>) {
+        let _ = (uri, kid);
+    }
+
+    /// The cache at the given `uri` started refreshing its keys.
+    fn on_refresh_start(&self, uri: &http::Uri) {
+        let _ = uri;
+    }
+
+    /// The cache at the given `uri` refreshed its keys.
+    fn on_refresh_success(&self, uri: &http::Uri) {
+        let _ = uri;
+    }
+
+    /// The cache at the given `uri` failed to refresh its keys (and kept the
+    /// previous ones).
+    fn on_refresh_failure(&self, uri: &http::Uri, error: &Error) {
+        let _ = (uri, error);
+    }
+
+    /// A token was rejected by the cache at the given `uri` (or by the
+    /// registry which it belongs to) for the given reason.
+    fn on_decrypt_failure(&self, uri: &http::Uri, reason: &Error) {
+        let _ = (uri, reason);
+    }
+}
+
+impl<O> CacheObserver for Arc<O>
+where
+    O: CacheObserver + ?Sized,
+{
+    fn on_cache_hit(&self, uri: &http::Uri, kid: &str) {
+        (**self).on_cache_hit(uri, kid)
+    }
+
+    fn on_cache_miss(&self, uri: &http::Uri, kid: Option<&str>) {
+        (**self).on_cache_miss(uri, kid)
+    }
+
+    fn on_refresh_start(&self, uri: &http::Uri) {
+        (**self).on_refresh_start(uri)
+    }
+
+    fn on_refresh_success(&self, uri: &http::Uri) {
+        (**self).on_refresh_success(uri)
+    }
+
+    fn on_refresh_failure(&self, uri: &http::Uri, error: &Error) {
+        (**self).on_refresh_failure(uri, error)
+    }
+
+    fn on_decrypt_failure(&self, uri: &http::Uri, reason: &Error) {
+        (**self).on_decrypt_failure(uri, reason)
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use metrics::Counter;
+use metrics::Gauge;
+use metrics::Histogram;
+use metrics::Key;
+use metrics::KeyName;
+use metrics::Recorder;
+use metrics::SharedString;
+use metrics::Unit;
+
+use crate::observer::metrics::MetricsObserver;
+use crate::observer::metrics::CACHE_HITS;
+use crate::observer::metrics::DECRYPT_FAILURES;
+use crate::observer::metrics::REFRESHES;
+use crate::observer::CacheObserver;
+use crate::prelude::Error;
+
+/// A `metrics` recorder which keeps every counter, by its name and labels.
+#[derive(Default)]
+struct CountingRecorder {
+    counters: Mutex<BTreeMap<String, Arc<AtomicU64>>>,
+}
+
+impl CountingRecorder {
+    fn get(&self, key: &str) -> u64 {
+        self.counters
+            .lock()
+            .unwrap()
+            .get(key)
+            .map_or(0, |counter| counter.load(Ordering::SeqCst))
+    }
+}
+
+impl Recorder for CountingRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {
+    }
+
+    fn register_counter(&self, key: &Key) -> Counter {
+        let labels = key
+            .labels()
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect::<Vec<_>>();
+        let key = format!("{}{{{}}}", key.name(), labels.join(","));
+
+        let counter = Arc::clone(
+            self.counters.lock().unwrap().entry(key).or_default(),
+        );
+        Counter::from_arc(counter)
+    }
+
+    fn register_gauge(&self, _: &Key) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+/// Every event should increment its counter, labeled with the `uri` of the
+/// cache.
+fn test_metrics_observer() {
+    let recorder: &'static CountingRecorder =
+        Box::leak(Box::new(CountingRecorder::default()));
+    metrics::set_recorder(recorder).unwrap();
+
+    let uri = http::Uri::from_static("https://example.com/certs");
+    let observer = MetricsObserver;
+    observer.describe();
+
+    observer.on_cache_hit(&uri, "a");
+    observer.on_cache_hit(&uri, "b");
+    observer.on_refresh_success(&uri);
+    observer.on_refresh_failure(&uri, &Error::invalid_uri);
+    observer.on_decrypt_failure(&uri, &Error::stale_cache);
+
+    let key = |name: &str, labels: &str| {
+        format!("{}{{uri=https://example.com/certs{}}}", name, labels)
+    };
+    assert_eq!(recorder.get(&key(CACHE_HITS, "")), 2);
+    assert_eq!(recorder.get(&key(REFRESHES, ",outcome=success")), 1);
+    assert_eq!(recorder.get(&key(REFRESHES, ",outcome=failure")), 1);
+    assert_eq!(
+        recorder.get(&key(DECRYPT_FAILURES, ",reason=refresh_keys")),
+        1,
+    );
+}
@@ -0,0 +1,93 @@
+//! Keeping secrets (i.e., raw tokens, claims, and key material) out of error
+//! messages, [`Debug`](`fmt::Debug`) output, and logs.
+//!
+//! Every [`Error`](`crate::error::Error`) produced by this crate is safe to
+//! log: errors raised while decoding a token are stripped of any fragment of
+//! the token (e.g., the offending claim value of a `JSON` error) before being
+//! returned.
+//!
+//! Applications can use [`Redacted`] for their own secrets:
+//!
+//! ```ignore
+//! let token = Redacted::new(token);
+//!
+//! // Prints `token = [redacted]`.
+//! println!("token = {:?}", token);
+//!
+//! let _ = remote_cache.decrypt::<Claims, _>(token.expose().as_str())?;
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use jsonwebtoken::errors::ErrorKind;
+use serde::de::Error as _;
+
+/// Wraps a secret so that it is never printed.
+///
+/// Both the [`fmt::Debug`] and the [`fmt::Display`] implementations print
+/// `[redacted]`. The secret itself can only be accessed explicitly, by
+/// calling [`expose`](`Redacted::expose`) or
+/// [`into_inner`](`Redacted::into_inner`).
+#[derive(Clone, Copy, Hash, Default, PartialEq, Eq)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    /// Wrap the given secret.
+    pub fn new(secret: T) -> Self {
+        Self(secret)
+    }
+
+    /// Get an immutable reference to the secret.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the secret.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(secret: T) -> Self {
+        Self(secret)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+/// Strip any fragment of the token from the given error.
+///
+/// Namely, `JSON` errors (which quote the offending value, e.g., `invalid
+/// type: string "...", expected u64`) are replaced by their category and
+/// position, and `UTF-8` errors (whose [`fmt::Debug`] output contains the
+/// entire decoded segment) are replaced by [`ErrorKind::InvalidToken`].
+pub(crate) fn redact_jwt_error(
+    error: jsonwebtoken::errors::Error,
+) -> jsonwebtoken::errors::Error {
+    match error.into_kind() {
+        ErrorKind::Json(error) => {
+            let message = format!(
+                "{:?} error at line {} column {} (details redacted)",
+                error.classify(),
+                error.line(),
+                error.column(),
+            );
+
+            ErrorKind::Json(Arc::new(serde_json::Error::custom(message))).into()
+        },
+        ErrorKind::Utf8(_) => ErrorKind::InvalidToken.into(),
+        kind => kind.into(),
+    }
+}
@@ -0,0 +1,596 @@
+//! A builder for configuring a [`KeyRegistry`] before its first fetch.
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .add_remote(Tpa::Apple, APPLE_JWK_URI)
+//!     .maintenance_window(Tpa::Apple, MaintenanceWindow { start, end })
+//!     .cache_store(FileStore::new("/var/cache/jwks"))
+//!     .finish()
+//!     .await?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::key_caches::key_cache::KeyCache;
+use crate::key_caches::remote::firebase::firebase_issuer;
+use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::well_known::WellKnownTpa;
+use crate::key_caches::remote::RemoteCache;
+use crate::observer::CacheObserver;
+use crate::prelude;
+use crate::registry::expected::ExpectedClaims;
+use crate::registry::lifetime::LifetimeCallback;
+use crate::registry::lifetime::TokenLifetime;
+use crate::registry::maintenance::MaintenanceWindow;
+#[cfg(feature = "json-schema")]
+use crate::registry::schema::ClaimsSchema;
+use crate::registry::shadow::ShadowCallback;
+use crate::registry::shadow::ShadowOutcome;
+use crate::registry::validator::ClaimsValidator;
+use crate::registry::validator::ValidationError;
+use crate::registry::KeyRegistry;
+use crate::time::Clock;
+
+/// The providers whose keys could not be fetched while building a
+/// [`KeyRegistry`].
+///
+/// Returned by [`finish_lossy`](`KeyRegistryBuilder::finish_lossy`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildReport<Tpa> {
+    /// The error of each provider whose keys could not be fetched.
+    pub failures: BTreeMap<Tpa, Error>,
+}
+
+impl<Tpa> BuildReport<Tpa> {
+    /// Check to see if the keys of every provider were fetched.
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// A builder for a [`KeyRegistry`].
+///
+/// Created by calling [`KeyRegistry::builder`].
+/// Errors (e.g., an invalid `uri`) are deferred until
+/// [`finish`](`KeyRegistryBuilder::finish`) is called.
+pub struct KeyRegistryBuilder<Tpa> {
+    providers: BTreeMap<Tpa, String>,
+    remotes: BTreeMap<String, RemoteCache>,
+    caches: BTreeMap<Tpa, Box<dyn KeyCache>>,
+    maintenance_windows: BTreeMap<Tpa, Vec<MaintenanceWindow>>,
+    cache_store: Option<Arc<dyn CacheStore>>,
+    observer: Option<Arc<dyn CacheObserver>>,
+    clock: Option<Arc<dyn Clock>>,
+    #[cfg(feature = "json-schema")]
+    claims_schemas: BTreeMap<Tpa, ClaimsSchema>,
+    expected_claims: BTreeMap<Tpa, ExpectedClaims>,
+    claims_validators: BTreeMap<Tpa, Vec<ClaimsValidator>>,
+    on_token_lifetime: Option<LifetimeCallback<Tpa>>,
+    shadows: BTreeMap<Tpa, Tpa>,
+    on_shadow_comparison: Option<ShadowCallback<Tpa>>,
+    issuers: BTreeMap<String, Tpa>,
+    error: Option<Error>,
+}
+
+impl<Tpa> Default for KeyRegistryBuilder<Tpa> {
+    fn default() -> Self {
+        Self {
+            providers: BTreeMap::default(),
+            remotes: BTreeMap::default(),
+            caches: BTreeMap::default(),
+            maintenance_windows: BTreeMap::default(),
+            cache_store: None,
+            observer: None,
+            clock: None,
+            #[cfg(feature = "json-schema")]
+            claims_schemas: BTreeMap::default(),
+            expected_claims: BTreeMap::default(),
+            claims_validators: BTreeMap::default(),
+            on_token_lifetime: None,
+            shadows: BTreeMap::default(),
+            on_shadow_comparison: None,
+            issuers: BTreeMap::default(),
+            error: None,
+        }
+    }
+}
+
+impl<Tpa> KeyRegistryBuilder<Tpa>
+where
+    Tpa: Ord,
+{
+    /// Register a [`RemoteCache`] (with the default configuration) targeting
+    /// the given `uri` for the given provider.
+    ///
+    /// If another provider has already been registered with the same `uri`,
+    /// its cache is shared instead (i.e., the keys are only fetched once).
+    ///
+    /// Registering the same provider twice will overwrite the previous
+    /// registration.
+    pub fn add_remote<I>(mut self, tpa: Tpa, uri: I) -> Self
+    where
+        String: From<I>,
+    {
+        let uri = match String::from(uri).parse::<http::Uri>() {
+            Ok(uri) => uri.to_string(),
+            Err(error) => {
+                self.error = self.error.or(Some(error.into()));
+                return self;
+            },
+        };
+
+        if !self.remotes.contains_key(&uri) {
+            match RemoteCache::new::<String>(uri.clone()) {
+                Ok(remote_cache) => {
+                    let _ = self.remotes.insert(uri.clone(), remote_cache);
+                },
+                Err(error) => {
+                    self.error = self.error.or(Some(error));
+                    return self;
+                },
+            };
+        };
+
+        let _ = self.caches.remove(&tpa);
+        let _ = self.providers.insert(tpa, uri);
+        self
+    }
+
+    /// Register an already configured [`RemoteCache`] (e.g., one built using
+    /// a [`RemoteCacheBuilder`](`crate::key_caches::remote::builder::RemoteCacheBuilder`))
+    /// for the given provider.
+    ///
+    /// Caches are shared by `uri`: if another provider has already been
+    /// registered with the same `uri`, the given cache replaces the previous
+    /// one for *both* providers.
+    ///
+    /// Registering the same provider twice will overwrite the previous
+    /// registration.
+    pub fn add_remote_cache(
+        mut self,
+        tpa: Tpa,
+        remote_cache: RemoteCache,
+    ) -> Self {
+        let uri = remote_cache.uri().to_string();
+
+        let _ = self.remotes.insert(uri.clone(), remote_cache);
+        let _ = self.caches.remove(&tpa);
+        let _ = self.providers.insert(tpa, uri);
+        self
+    }
+
+    /// Register any other [`KeyCache`] (e.g., a
+    /// [`LocalCache`](`crate::key_caches::local::LocalCache`) holding the keys
+    /// of first-party tokens) for the given provider.
+    ///
+    /// The cache is used as is (i.e., it is not refreshed while building),
+    /// and is never shared with other providers. Its tokens are verified
+    /// through [`KeyCache::decrypt`], along with everything else which applies
+    /// to the provider (e.g., its claims schema and shadow).
+    ///
+    /// Registering the same provider twice will overwrite the previous
+    /// registration.
+    /// See [`key_cache`](`crate::key_caches::key_cache`).
+    pub fn add_cache<C>(mut self, tpa: Tpa, cache: C) -> Self
+    where
+        C: KeyCache,
+    {
+        let _ = self.providers.remove(&tpa);
+        let _ = self.caches.insert(tpa, Box::new(cache));
+        self
+    }
+
+    /// Add a known maintenance window for the given provider.
+    ///
+    /// See [`maintenance`](`crate::registry::maintenance`).
+    pub fn maintenance_window(
+        mut self,
+        tpa: Tpa,
+        maintenance_window: MaintenanceWindow,
+    ) -> Self {
+        self.maintenance_windows
+            .entry(tpa)
+            .or_default()
+            .push(maintenance_window);
+        self
+    }
+
+    /// Share snapshots through the given [`CacheStore`], for every cache which
+    /// has not been given a store of its own.
+    ///
+    /// Since the store is consulted before the first fetch, a replica which
+    /// starts up after another one restores the keys that were fetched by the
+    /// other replica instead of fetching them again.
+    ///
+    /// See [`store`](`crate::key_caches::remote::store`).
+    pub fn cache_store<S>(mut self, cache_store: S) -> Self
+    where
+        S: CacheStore + 'static,
+    {
+        self.cache_store = Some(Arc::new(cache_store));
+        self
+    }
+
+    /// Report the events of every cache which has not been given an observer
+    /// of its own (as well as the tokens which the [`KeyRegistry`] rejects
+    /// itself) to the given [`CacheObserver`].
+    ///
+    /// See [`observer`](`crate::observer`).
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: CacheObserver + 'static,
+    {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Check maintenance windows (and the lifetimes of tokens) against the
+    /// given [`Clock`] instead of the time of the system. It is also given to
+    /// every cache which has not been given a clock of its own.
+    ///
+    /// See [`time`](`crate::time`).
+    pub fn clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Validate the claims of the given provider's tokens against the given
+    /// schema.
+    ///
+    /// Only available when the `json-schema` feature is enabled.
+    /// See [`schema`](`crate::registry::schema`).
+    #[cfg(feature = "json-schema")]
+    pub fn claims_schema(
+        mut self,
+        tpa: Tpa,
+        claims_schema: ClaimsSchema,
+    ) -> Self {
+        let _ = self.claims_schemas.insert(tpa, claims_schema);
+        self
+    }
+
+    /// Only accept tokens of the given provider which carry the given issuer
+    /// and audience claims.
+    ///
+    /// Setting the expected claims of the same provider again will overwrite
+    /// the previous ones.
+    /// See [`expected`](`crate::registry::expected`).
+    pub fn expected_claims(
+        mut self,
+        tpa: Tpa,
+        expected_claims: ExpectedClaims,
+    ) -> Self {
+        let _ = self.expected_claims.insert(tpa, expected_claims);
+        self
+    }
+
+    /// Accept the given audience for the tokens of the given provider (in
+    /// addition to any previous one).
+    ///
+    /// See [`expected_claims`](`KeyRegistryBuilder::expected_claims`).
+    pub fn audience<I>(mut self, tpa: Tpa, audience: I) -> Self
+    where
+        String: From<I>,
+    {
+        self.expected_claims
+            .entry(tpa)
+            .or_default()
+            .audiences
+            .push(String::from(audience));
+        self
+    }
+
+    /// Run the given validator on the claims of every (verified) token of the
+    /// given provider, rejecting the tokens that it fails (in addition to any
+    /// previous validator).
+    ///
+    /// Validators run in the order that they were added.
+    /// See [`validator`](`crate::registry::validator`).
+    pub fn claims_validator<F>(mut self, tpa: Tpa, claims_validator: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), ValidationError> + Send + Sync + 'static,
+    {
+        self.claims_validators
+            .entry(tpa)
+            .or_default()
+            .push(Arc::new(claims_validator));
+        self
+    }
+
+    /// Call the given callback with the provider and the lifetime of every
+    /// token verified by the [`KeyRegistry`] (e.g., to record them into
+    /// histograms).
+    ///
+    /// Tokens without an `exp` claim are not reported.
+    /// See [`lifetime`](`crate::registry::lifetime`).
+    pub fn on_token_lifetime<F>(mut self, on_token_lifetime: F) -> Self
+    where
+        F: Fn(&Tpa, &TokenLifetime) + Send + Sync + 'static,
+    {
+        self.on_token_lifetime = Some(Arc::new(on_token_lifetime));
+        self
+    }
+
+    /// Verify the tokens of the given primary provider against the given
+    /// shadow provider as well (e.g., while migrating from one to the other).
+    ///
+    /// Both providers must be registered. Setting another shadow for the same
+    /// primary provider will overwrite the previous one.
+    /// See [`shadow`](`crate::registry::shadow`).
+    pub fn shadow(mut self, primary: Tpa, shadow: Tpa) -> Self {
+        let _ = self.shadows.insert(primary, shadow);
+        self
+    }
+
+    /// Call the given callback with the (primary) provider and the
+    /// [`ShadowOutcome`] of every token verified against a shadow provider.
+    ///
+    /// Without a callback, no shadow verification takes place at all.
+    /// See [`shadow`](`crate::registry::shadow`).
+    pub fn on_shadow_comparison<F>(mut self, on_shadow_comparison: F) -> Self
+    where
+        F: Fn(&Tpa, &ShadowOutcome) + Send + Sync + 'static,
+    {
+        self.on_shadow_comparison = Some(Arc::new(on_shadow_comparison));
+        self
+    }
+
+    /// Route the tokens of the given issuer (i.e., whose `iss` claim is the
+    /// given issuer) to the given provider when calling
+    /// [`decrypt_by_issuer`](`KeyRegistry::decrypt_by_issuer`).
+    ///
+    /// The provider must be registered. A provider may have any number of
+    /// issuers, but mapping the same issuer again will overwrite the previous
+    /// mapping.
+    pub fn issuer<I>(mut self, tpa: Tpa, issuer: I) -> Self
+    where
+        String: From<I>,
+    {
+        let _ = self.issuers.insert(String::from(issuer), tpa);
+        self
+    }
+
+    /// Register the `Firebase` project with the given id for the given
+    /// provider, only accepting the tokens which were issued by, and for, the
+    /// project.
+    ///
+    /// This behaves exactly as [`add_remote`](`KeyRegistryBuilder::add_remote`)
+    /// with the [`FIREBASE_JWK_URI`] (so that several projects share their
+    /// keys), followed by an [`issuer`](`KeyRegistryBuilder::issuer`) and the
+    /// [`expected_claims`](`KeyRegistryBuilder::expected_claims`) of the
+    /// project.
+    pub fn add_firebase(self, tpa: Tpa, project_id: &str) -> Self
+    where
+        Tpa: Clone,
+    {
+        let issuer = firebase_issuer(project_id);
+        let expected_claims = ExpectedClaims::default()
+            .issuer(issuer.as_str())
+            .audience(project_id);
+
+        self.add_remote(tpa.clone(), FIREBASE_JWK_URI)
+            .issuer(tpa.clone(), issuer)
+            .expected_claims(tpa, expected_claims)
+    }
+
+    /// Build the [`KeyRegistry`], fetching the keys of every registered
+    /// provider.
+    ///
+    /// Providers sharing the same `uri` are only fetched once.
+    /// If any fetch fails, the first error is returned.
+    ///
+    /// Fails with [`Error::unknown_tpa`] if either provider of a shadow, or the
+    /// provider of an issuer, has not been registered.
+    pub async fn finish(self) -> prelude::Result<KeyRegistry<Tpa>> {
+        let (registry, _) = self.build(true).await?;
+
+        Ok(registry)
+    }
+
+    /// Build the [`KeyRegistry`], fetching the keys of every registered
+    /// provider, but without failing if some of the fetches fail.
+    ///
+    /// Providers whose keys could not be fetched stay registered (with an
+    /// empty cache), and are listed in the returned [`BuildReport`]. Their
+    /// tokens are rejected (with [`Error::stale_cache`]) until they are
+    /// [`refresh`](`KeyRegistry::refresh`)ed successfully, while every other
+    /// provider can be used right away.
+    ///
+    /// ```ignore
+    /// let (registry, report) = KeyRegistry::builder()
+    ///     .add_well_known(WellKnownTpa::Google)
+    ///     .add_well_known(WellKnownTpa::Facebook)
+    ///     .finish_lossy()
+    ///     .await?;
+    ///
+    /// for (tpa, error) in &report.failures {
+    ///     eprintln!("{} is unavailable: {}", tpa, error);
+    /// }
+    /// ```
+    ///
+    /// Configuration errors (e.g., an invalid `uri`, or an unregistered
+    /// provider of a shadow) still fail the build, exactly as in
+    /// [`finish`](`KeyRegistryBuilder::finish`).
+    pub async fn finish_lossy(
+        self,
+    ) -> prelude::Result<(KeyRegistry<Tpa>, BuildReport<Tpa>)>
+    where
+        Tpa: Clone,
+    {
+        let (registry, failed) = self.build(false).await?;
+
+        let failures = registry
+            .providers
+            .iter()
+            .filter_map(|(tpa, uri)| {
+                let error = failed.get(uri)?;
+                Some((tpa.clone(), error.clone()))
+            })
+            .collect();
+
+        Ok((registry, BuildReport { failures }))
+    }
+
+    /// Build the [`KeyRegistry`] without fetching the keys of any provider.
+    ///
+    /// The cache of each provider starts out empty, and its keys are only
+    /// fetched on the first call to
+    /// [`decrypt_lazy`](`KeyRegistry::decrypt_lazy`) for that provider (or to
+    /// [`refresh`](`KeyRegistry::refresh`)). This way, services with many
+    /// configured, but rarely used, providers do not pay for every fetch
+    /// while booting.
+    ///
+    /// ```ignore
+    /// let mut registry = KeyRegistry::builder()
+    ///     .add_well_known(WellKnownTpa::Google)
+    ///     .add_well_known(WellKnownTpa::Microsoft)
+    ///     .build_lazy()?;
+    ///
+    /// // Fetches Google's keys, but not Microsoft's.
+    /// let data = registry
+    ///     .decrypt_lazy::<Claims, _, _>(&WellKnownTpa::Google, token)
+    ///     .await?;
+    /// ```
+    ///
+    /// Configuration errors fail the build exactly as in
+    /// [`finish`](`KeyRegistryBuilder::finish`).
+    ///
+    /// ### Note:
+    /// Until a provider has been fetched,
+    /// [`decrypt`](`KeyRegistry::decrypt`) rejects its tokens with
+    /// [`Error::stale_cache`].
+    pub fn build_lazy(self) -> prelude::Result<KeyRegistry<Tpa>> {
+        self.assemble()
+    }
+
+    /// Build the [`KeyRegistry`], along with the error of each `uri` whose
+    /// keys could not be fetched.
+    ///
+    /// If `fail_fast` is set, the first such error is returned instead.
+    async fn build(
+        self,
+        fail_fast: bool,
+    ) -> prelude::Result<(KeyRegistry<Tpa>, BTreeMap<String, Error>)> {
+        let mut registry = self.assemble()?;
+        let mut failed = BTreeMap::new();
+
+        for (uri, remote_cache) in registry.remotes.iter_mut() {
+            match (remote_cache.refresh().await, fail_fast) {
+                (Ok(()), _) => (),
+                (Err(error), true) => return Err(error),
+                (Err(error), false) => {
+                    let _ = failed.insert(uri.clone(), error);
+                },
+            };
+        }
+
+        Ok((registry, failed))
+    }
+
+    /// Validate the configuration, and build the [`KeyRegistry`] without
+    /// fetching anything.
+    fn assemble(self) -> prelude::Result<KeyRegistry<Tpa>> {
+        let Self {
+            providers,
+            mut remotes,
+            caches,
+            maintenance_windows,
+            cache_store,
+            observer,
+            clock,
+            #[cfg(feature = "json-schema")]
+            claims_schemas,
+            expected_claims,
+            claims_validators,
+            on_token_lifetime,
+            shadows,
+            on_shadow_comparison,
+            issuers,
+            error,
+        } = self;
+
+        if let Some(error) = error {
+            return Err(error);
+        };
+
+        let registered =
+            |tpa| providers.contains_key(tpa) || caches.contains_key(tpa);
+        let shadowed = shadows.iter().all(|(primary, shadow)| {
+            registered(primary) && registered(shadow)
+        });
+
+        if !shadowed || !issuers.values().all(registered) {
+            return Err(Error::unknown_tpa);
+        };
+
+        // Caches which are no longer used by any provider (e.g., because the
+        // provider was re-registered with a different `uri`) are dropped.
+        remotes.retain(|uri, _| providers.values().any(|used| used == uri));
+
+        for remote_cache in remotes.values_mut() {
+            if remote_cache.cache_store.is_none() {
+                remote_cache.cache_store = cache_store.clone();
+            };
+
+            if remote_cache.observer.is_none() {
+                remote_cache.observer = observer.clone();
+            };
+
+            if remote_cache.clock.is_none() {
+                remote_cache.clock = clock.clone();
+            };
+        }
+
+        Ok(KeyRegistry {
+            providers,
+            remotes,
+            caches,
+            cache_store,
+            observer,
+            clock,
+            maintenance_windows,
+            #[cfg(feature = "json-schema")]
+            claims_schemas,
+            expected_claims,
+            claims_validators,
+            on_token_lifetime,
+            shadows,
+            on_shadow_comparison,
+            issuers,
+        })
+    }
+}
+
+impl KeyRegistryBuilder<WellKnownTpa> {
+    /// Register a [`RemoteCache`] (see
+    /// [`remote_cache_builder`](`WellKnownTpa::remote_cache_builder`)) for the
+    /// given well-known provider, along with its
+    /// [`issuers`](`WellKnownTpa::issuers`).
+    ///
+    /// This behaves exactly as
+    /// [`add_remote_cache`](`KeyRegistryBuilder::add_remote_cache`), followed
+    /// by an [`issuer`](`KeyRegistryBuilder::issuer`) for each issuer.
+    pub fn add_well_known(mut self, tpa: WellKnownTpa) -> Self {
+        let remote_cache = match tpa.remote_cache_builder().build() {
+            Ok(remote_cache) => remote_cache,
+            Err(error) => {
+                self.error = self.error.or(Some(error));
+                return self;
+            },
+        };
+
+        tpa.issuers().iter().fold(
+            self.add_remote_cache(tpa, remote_cache),
+            |builder, issuer| builder.issuer(tpa, *issuer),
+        )
+    }
+}
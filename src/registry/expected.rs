@@ -0,0 +1,118 @@
+//! Checking the issuer and the audience of verified tokens.
+//!
+//! A valid signature only proves that a token was signed by the provider; it
+//! does not prove that the token was issued *for this app*. Any other app
+//! which signs in with the same provider receives tokens signed by the very
+//! same keys. Attaching [`ExpectedClaims`] to a provider rejects tokens which
+//! were issued by anyone else, or for any other audience (i.e., client id):
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Corporate, CORPORATE_JWK_URI)
+//!     .expected_claims(
+//!         Tpa::Corporate,
+//!         ExpectedClaims::default()
+//!             .issuer("https://sso.example.com")
+//!             .audience("my-client-id"),
+//!     )
+//!     .finish()
+//!     .await?;
+//! ```
+//!
+//! Well-known providers come with a template of their issuers (see
+//! [`WellKnownTpa::expected_claims`]), so only the audience is left to the
+//! app:
+//!
+//! ```ignore
+//! let registry = KeyRegistry::with_well_known(&WellKnownTpa::ALL)
+//!     .audience(WellKnownTpa::Google, "1234.apps.googleusercontent.com")
+//!     .finish()
+//!     .await?;
+//! ```
+//!
+//! [`WellKnownTpa::expected_claims`]: `crate::key_caches::remote::well_known::WellKnownTpa::expected_claims`
+
+use jsonwebtoken::errors::ErrorKind;
+use serde_json::Value;
+
+use crate::prelude;
+
+/// The issuers and the audiences that the tokens of a provider must carry.
+///
+/// An empty list accepts any value (including none at all).
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedClaims {
+    /// The accepted `iss` claims.
+    pub issuers: Vec<String>,
+
+    /// The accepted `aud` claims.
+    pub audiences: Vec<String>,
+}
+
+impl ExpectedClaims {
+    /// Accept the given issuer (in addition to any previous one).
+    pub fn issuer<I>(mut self, issuer: I) -> Self
+    where
+        String: From<I>,
+    {
+        self.issuers.push(String::from(issuer));
+        self
+    }
+
+    /// Accept the given audience (in addition to any previous one).
+    pub fn audience<I>(mut self, audience: I) -> Self
+    where
+        String: From<I>,
+    {
+        self.audiences.push(String::from(audience));
+        self
+    }
+
+    /// Check the `iss` and the `aud` claims of the given (verified) claims.
+    ///
+    /// Fails with [`Error::unable_to_verify_token`] if either claim is
+    /// missing (while expected), or is not one of the accepted values. An
+    /// `aud` array is accepted if any of its members is accepted.
+    ///
+    /// [`Error::unable_to_verify_token`]: `crate::error::Error::unable_to_verify_token`
+    pub fn check(&self, claims: &Value) -> prelude::Result<()> {
+        let Self { issuers, audiences } = self;
+
+        let accepted = |expected: &[String], value: &str| {
+            expected.iter().any(|accepted| accepted == value)
+        };
+
+        if !issuers.is_empty() {
+            match claims.get("iss") {
+                None => reject(ErrorKind::MissingRequiredClaim("iss".into()))?,
+                Some(Value::String(iss)) if accepted(issuers, iss) => (),
+                Some(_) => reject(ErrorKind::InvalidIssuer)?,
+            };
+        };
+
+        if !audiences.is_empty() {
+            let matched = match claims.get("aud") {
+                None => reject(ErrorKind::MissingRequiredClaim("aud".into()))?,
+                Some(Value::String(aud)) => accepted(audiences, aud),
+                Some(Value::Array(auds)) => auds
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|aud| accepted(audiences, aud)),
+                Some(_) => false,
+            };
+
+            if !matched {
+                reject(ErrorKind::InvalidAudience)?;
+            };
+        };
+
+        Ok(())
+    }
+}
+
+/// Reject the token with the given kind of error.
+fn reject<T>(error_kind: ErrorKind) -> prelude::Result<T> {
+    Err(jsonwebtoken::errors::Error::from(error_kind).into())
+}
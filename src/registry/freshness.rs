@@ -0,0 +1,59 @@
+//! The freshness of the caches of a [`KeyRegistry`](`super::KeyRegistry`).
+//!
+//! Operators usually want two things from a registry: a single call which
+//! refreshes every provider (e.g., from a cron job), and a single call which
+//! reports the state of every provider (e.g., from a health endpoint):
+//!
+//! ```ignore
+//! // Every cache is refreshed concurrently (and only once, even if it is
+//! // shared by several providers).
+//! for (tpa, result) in registry.refresh_all().await {
+//!     if let Err(error) = result {
+//!         eprintln!("failed to refresh {}: {}", tpa, error);
+//!     };
+//! }
+//!
+//! let healthy = registry
+//!     .freshness()
+//!     .values()
+//!     .all(|status| status.usable);
+//! ```
+//!
+//! A [`CacheStatus`] is serializable, so the whole report can be returned as
+//! the body of a health endpoint as is.
+
+use serde::Serialize;
+
+use crate::prelude::Timestamp;
+
+/// A point-in-time report of the cache of a single provider.
+///
+/// Returned by [`freshness`](`super::KeyRegistry::freshness`).
+#[derive(Clone, Hash, Debug, Serialize, PartialEq, Eq)]
+pub struct CacheStatus {
+    /// The `uri` that the cache fetches its keys from.
+    pub uri: String,
+
+    /// The number of keys inside of the cache.
+    pub key_count: usize,
+
+    /// The time (in Unix-Time) at which the keys expire, if known.
+    pub expiry_time: Option<Timestamp>,
+
+    /// The time (in Unix-Time) at which the keys were last refreshed, if
+    /// ever.
+    pub refreshed_at: Option<Timestamp>,
+
+    /// Whether the keys have not expired yet (see
+    /// [`RemoteCache::is_cache_fresh`](`crate::key_caches::remote::RemoteCache::is_cache_fresh`)).
+    pub fresh: bool,
+
+    /// Whether the provider is currently inside of one of its maintenance
+    /// windows.
+    pub under_maintenance: bool,
+
+    /// Whether tokens of the provider are currently accepted (i.e., instead
+    /// of being rejected with
+    /// [`Error::stale_cache`](`crate::error::Error::stale_cache`)).
+    pub usable: bool,
+}
@@ -0,0 +1,235 @@
+//! Analytics on the lifetimes of the tokens verified by a [`KeyRegistry`].
+//!
+//! Every verified token is reported to the
+//! [`on_token_lifetime`](`crate::registry::builder::KeyRegistryBuilder::on_token_lifetime`)
+//! callback of its registry, along with its provider and its
+//! [`TokenLifetime`] (i.e., its total lifetime, and its remaining time to
+//! expiry). This helps tuning session lengths, and spotting providers which
+//! issue unexpectedly long-lived tokens.
+//!
+//! The callback can feed any metrics library. Alternatively,
+//! [`TokenLifetimes`] keeps a pair of [`Histogram`]s per provider in-process:
+//!
+//! ```ignore
+//! let lifetimes = Arc::new(TokenLifetimes::default());
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .on_token_lifetime({
+//!         let lifetimes = Arc::clone(&lifetimes);
+//!         move |tpa, lifetime| lifetimes.record(tpa, lifetime)
+//!     })
+//!     .finish()
+//!     .await?;
+//!
+//! let stats = lifetimes.get(&Tpa::Google).unwrap();
+//! println!("{} tokens, {:?} on average", stats.lifetime.count(), stats.lifetime.mean());
+//! ```
+//!
+//! [`KeyRegistry`]: `crate::registry::KeyRegistry`
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+
+use crate::prelude::Timestamp;
+
+/// The default upper bounds (in seconds) of the buckets of a [`Histogram`]:
+/// 1 minute, 5 minutes, 15 minutes, 1 hour, 6 hours, 12 hours, 1 day, 1 week,
+/// and 30 days.
+pub const DEFAULT_BUCKETS: [u64; 9] =
+    [60, 300, 900, 3600, 21600, 43200, 86400, 604800, 2592000];
+
+pub(crate) type LifetimeCallback<Tpa> =
+    Arc<dyn Fn(&Tpa, &TokenLifetime) + Send + Sync>;
+
+/// The lifetime of a verified token.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct TokenLifetime {
+    /// The total lifetime of the token (i.e., `exp - iat`).
+    ///
+    /// [`None`] if the token has no `iat`, or was issued after it expires.
+    pub lifetime: Option<Duration>,
+
+    /// The remaining time until the token expires (i.e., `exp - now`).
+    ///
+    /// Tokens which are only accepted because of the leeway have none left.
+    pub time_to_expiry: Duration,
+}
+
+/// Read the lifetime of the given (already verified) token, at the given time
+/// (in Unix-Time).
+///
+/// Returns [`None`] if the token has no `exp`.
+pub(crate) fn observe(token: &str, now: Timestamp) -> Option<TokenLifetime> {
+    #[derive(Deserialize)]
+    struct Times {
+        exp: Option<u64>,
+        iat: Option<u64>,
+    }
+
+    let payload = token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    let Times { exp, iat } = serde_json::from_slice(&payload).ok()?;
+
+    let exp = exp?;
+    let lifetime = iat
+        .and_then(|iat| exp.checked_sub(iat))
+        .map(Duration::from_secs);
+    let time_to_expiry = Duration::from_secs(exp.saturating_sub(now));
+
+    Some(TokenLifetime {
+        lifetime,
+        time_to_expiry,
+    })
+}
+
+/// A distribution of durations, counted into buckets.
+///
+/// Buckets are cumulative (i.e., a bucket counts every duration less than or
+/// equal to its upper bound), as is customary for `Prometheus` histograms.
+#[derive(Clone, Hash, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    bounds: Vec<u64>,
+    counts: Vec<u64>,
+    count: u64,
+    sum: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUCKETS.to_vec())
+    }
+}
+
+impl Histogram {
+    /// Create a [`Histogram`] with the given upper bounds (in seconds) of its
+    /// buckets.
+    ///
+    /// Durations which exceed every bound are only counted by
+    /// [`count`](`Histogram::count`) (i.e., by the implicit `+Inf` bucket).
+    pub fn new(mut bounds: Vec<u64>) -> Self {
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let counts = vec![0; bounds.len()];
+
+        Self {
+            bounds,
+            counts,
+            count: 0,
+            sum: 0,
+        }
+    }
+
+    /// Count the given duration.
+    pub fn record(&mut self, duration: Duration) {
+        let Self {
+            bounds,
+            counts,
+            count,
+            sum,
+        } = self;
+        let seconds = duration.as_secs();
+
+        bounds
+            .iter()
+            .zip(counts.iter_mut())
+            .filter(|(bound, _)| seconds <= **bound)
+            .for_each(|(_, count)| *count += 1);
+
+        *count += 1;
+        *sum = sum.saturating_add(seconds);
+    }
+
+    /// The upper bound (in seconds) of each bucket, along with the number of
+    /// durations counted by it.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.bounds.iter().copied().zip(self.counts.iter().copied())
+    }
+
+    /// The number of counted durations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The sum of the counted durations.
+    pub fn sum(&self) -> Duration {
+        Duration::from_secs(self.sum)
+    }
+
+    /// The mean of the counted durations, if any were counted.
+    pub fn mean(&self) -> Option<Duration> {
+        self.sum.checked_div(self.count).map(Duration::from_secs)
+    }
+}
+
+/// The distributions of the lifetimes of a single provider's tokens.
+#[derive(Clone, Hash, Debug, Default, PartialEq, Eq)]
+pub struct LifetimeStats {
+    /// The distribution of total lifetimes (i.e., `exp - iat`).
+    pub lifetime: Histogram,
+
+    /// The distribution of the remaining time to expiry, at verification.
+    pub time_to_expiry: Histogram,
+}
+
+/// Keeps [`LifetimeStats`] per provider.
+///
+/// See the [module level documentation](`self`).
+#[derive(Debug)]
+pub struct TokenLifetimes<Tpa> {
+    stats: Mutex<BTreeMap<Tpa, LifetimeStats>>,
+}
+
+impl<Tpa> Default for TokenLifetimes<Tpa> {
+    fn default() -> Self {
+        Self {
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<Tpa> TokenLifetimes<Tpa>
+where
+    Tpa: Clone + Ord,
+{
+    /// Count the given lifetime of a token of the given provider.
+    pub fn record(&self, tpa: &Tpa, token_lifetime: &TokenLifetime) {
+        let TokenLifetime {
+            lifetime,
+            time_to_expiry,
+        } = token_lifetime;
+
+        let mut stats = self
+            .stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let stats = stats.entry(tpa.clone()).or_default();
+
+        if let Some(lifetime) = lifetime {
+            stats.lifetime.record(*lifetime);
+        };
+
+        stats.time_to_expiry.record(*time_to_expiry);
+    }
+
+    /// The current distributions of the given provider, if any of its tokens
+    /// have been counted.
+    pub fn get(&self, tpa: &Tpa) -> Option<LifetimeStats> {
+        self.snapshot().remove(tpa)
+    }
+
+    /// The current distributions of every provider.
+    pub fn snapshot(&self) -> BTreeMap<Tpa, LifetimeStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
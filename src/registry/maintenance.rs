@@ -0,0 +1,35 @@
+//! Known maintenance windows of third party auth providers.
+//!
+//! Providers sometimes announce maintenance ahead of time, during which their
+//! `JWK` endpoints may be unreachable. Failures to refresh during such a window
+//! are expected, and should not page anyone.
+//!
+//! Therefore, while a provider is inside of one of its maintenance windows, a
+//! [`KeyRegistry`](`super::KeyRegistry`):
+//! - downgrades refresh failures to warnings (see
+//!   [`RefreshStatus::Deferred`](`super::RefreshStatus::Deferred`)), and
+//! - keeps serving the provider's previous keys, even if they have expired
+//!   (and even past the grace period of a
+//!   [`StalePolicy`](`crate::key_caches::remote::policy::StalePolicy`)), until
+//!   the window ends.
+
+use crate::prelude::Timestamp;
+
+/// A period of time (in Unix-Time) during which a provider is expected to be
+/// under maintenance.
+///
+/// The window includes its `start`, but excludes its `end`.
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MaintenanceWindow {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl MaintenanceWindow {
+    /// Check to see if the given time falls within this window.
+    pub fn contains(&self, time: Timestamp) -> bool {
+        let Self { start, end } = self;
+
+        (*start..*end).contains(&time)
+    }
+}
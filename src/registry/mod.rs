@@ -0,0 +1,995 @@
+//! A registry of key caches, indexed by third party auth provider (`Tpa`).
+//!
+//! Applications which accept tokens from multiple providers would otherwise
+//! need to keep track of a [`RemoteCache`] per provider themselves. A
+//! [`KeyRegistry`] holds all of them, and routes each token to the cache of the
+//! provider it claims to be signed by.
+//!
+//! ```ignore
+//! #[derive(PartialEq, Eq, PartialOrd, Ord)]
+//! enum Tpa {
+//!     Google,
+//!     Facebook,
+//! }
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .add_remote(Tpa::Facebook, FACEBOOK_JWK_URI)
+//!     .finish()
+//!     .await?;
+//!
+//! let token = "a.b.c";
+//! let data: TokenData<GoogleClaims> = registry.decrypt(&Tpa::Google, token)?;
+//! ```
+
+pub mod builder;
+pub mod expected;
+pub mod freshness;
+pub mod lifetime;
+pub mod maintenance;
+#[cfg(feature = "json-schema")]
+pub mod schema;
+pub mod shadow;
+pub mod sharded;
+pub mod shared;
+pub mod validator;
+#[cfg(test)]
+mod tests;
+
+use std::any::Any;
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use futures_util::future::join_all;
+use jsonwebtoken::Header;
+use jsonwebtoken::TokenData;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::insecure::inspect_token;
+use crate::key_caches::key_cache::KeyCache;
+use crate::key_caches::nonce::check_nonce;
+use crate::key_caches::normalized::NormalizedClaims;
+use crate::key_caches::remote::apple::AppleClaims;
+use crate::key_caches::remote::facebook::FacebookClaims;
+use crate::key_caches::remote::firebase::FirebaseClaims;
+use crate::key_caches::remote::google::GoogleClaims;
+use crate::key_caches::remote::microsoft::MicrosoftClaims;
+use crate::key_caches::remote::store::CacheStore;
+use crate::key_caches::remote::RemoteCache;
+use crate::observer::CacheObserver;
+use crate::prelude;
+use crate::prelude::Timestamp;
+use crate::key_caches::remote::well_known::WellKnownTpa;
+use crate::registry::builder::KeyRegistryBuilder;
+use crate::registry::expected::ExpectedClaims;
+use crate::registry::freshness::CacheStatus;
+use crate::registry::lifetime::LifetimeCallback;
+use crate::registry::maintenance::MaintenanceWindow;
+#[cfg(feature = "json-schema")]
+use crate::registry::schema::ClaimsSchema;
+use crate::registry::shadow::ShadowCallback;
+use crate::registry::shadow::ShadowOutcome;
+use crate::registry::validator::ClaimsValidator;
+use crate::time::now;
+use crate::time::Clock;
+
+/// The outcome of refreshing the cache of a single provider.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RefreshStatus {
+    /// The keys were successfully refreshed.
+    Refreshed,
+
+    /// The refresh failed while the provider is inside of a maintenance
+    /// window. The previous keys are kept.
+    ///
+    /// The contained error should be treated as a warning.
+    Deferred(Error),
+}
+
+impl RefreshStatus {
+    /// The outcome of the given refresh of a provider, which may be inside of
+    /// a maintenance window.
+    pub(crate) fn of(
+        refreshed: prelude::Result<()>,
+        under_maintenance: bool,
+    ) -> prelude::Result<Self> {
+        match (refreshed, under_maintenance) {
+            (Ok(()), _) => Ok(Self::Refreshed),
+            (Err(error), true) => Ok(Self::Deferred(error)),
+            (Err(error), false) => Err(error),
+        }
+    }
+}
+
+/// A registry of [`RemoteCache`]s, indexed by third party auth provider.
+///
+/// Providers which share the same `JWK` endpoint (e.g., multiple app
+/// registrations of the same identity provider) also share the same
+/// [`RemoteCache`], while their remaining configuration (e.g., their
+/// maintenance windows) is kept separate.
+///
+/// See the [module level documentation](`self`).
+pub struct KeyRegistry<Tpa> {
+    /// The `uri` of the cache used by each provider.
+    pub(crate) providers: BTreeMap<Tpa, String>,
+
+    /// The caches, indexed by `uri`.
+    pub(crate) remotes: BTreeMap<String, RemoteCache>,
+
+    /// The caches of providers which are not backed by a [`RemoteCache`]
+    /// (see [`KeyRegistryBuilder::add_cache`]).
+    pub(crate) caches: BTreeMap<Tpa, Box<dyn KeyCache>>,
+
+    /// The store given to the caches of providers which are added after
+    /// construction (see [`KeyRegistryBuilder::cache_store`]).
+    pub(crate) cache_store: Option<Arc<dyn CacheStore>>,
+
+    /// The observer given to the caches of providers which are added after
+    /// construction (see [`KeyRegistryBuilder::observer`]).
+    pub(crate) observer: Option<Arc<dyn CacheObserver>>,
+
+    /// The clock of the registry, also given to the caches of providers which
+    /// are added after construction (see [`KeyRegistryBuilder::clock`]).
+    pub(crate) clock: Option<Arc<dyn Clock>>,
+
+    pub(crate) maintenance_windows: BTreeMap<Tpa, Vec<MaintenanceWindow>>,
+
+    #[cfg(feature = "json-schema")]
+    pub(crate) claims_schemas: BTreeMap<Tpa, ClaimsSchema>,
+
+    pub(crate) expected_claims: BTreeMap<Tpa, ExpectedClaims>,
+
+    pub(crate) claims_validators: BTreeMap<Tpa, Vec<ClaimsValidator>>,
+
+    pub(crate) on_token_lifetime: Option<LifetimeCallback<Tpa>>,
+
+    /// The shadow provider of each primary provider.
+    pub(crate) shadows: BTreeMap<Tpa, Tpa>,
+
+    pub(crate) on_shadow_comparison: Option<ShadowCallback<Tpa>>,
+
+    /// The provider of each issuer (see [`KeyRegistry::decrypt_by_issuer`]).
+    pub(crate) issuers: BTreeMap<String, Tpa>,
+}
+
+impl<Tpa> KeyRegistry<Tpa>
+where
+    Tpa: Ord,
+{
+    /// Create a [`KeyRegistryBuilder`].
+    pub fn builder() -> KeyRegistryBuilder<Tpa> {
+        KeyRegistryBuilder::default()
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider.
+    ///
+    /// The token is rejected with [`Error::stale_cache`] if the provider's
+    /// cache is not usable (see [`RemoteCache::is_cache_usable`]), unless the
+    /// provider is currently inside of one of its maintenance windows.
+    ///
+    /// If the provider has a claims schema (see
+    /// [`schema`](`crate::registry::schema`)), the claims are validated
+    /// against it before being deserialized. So are its expected claims, if
+    /// any (see [`expected`](`crate::registry::expected`)).
+    ///
+    /// The lifetime of every verified token is reported to the
+    /// [`on_token_lifetime`](`KeyRegistryBuilder::on_token_lifetime`)
+    /// callback, if any.
+    ///
+    /// If the provider has a shadow provider (see
+    /// [`shadow`](`crate::registry::shadow`)), the token is verified against
+    /// it as well, and the outcome is reported to the
+    /// [`on_shadow_comparison`](`KeyRegistryBuilder::on_shadow_comparison`)
+    /// callback. The returned result is that of the given provider alone.
+    ///
+    /// Just like with a [`BTreeMap`], the provider can be given as any
+    /// borrowed form of `Tpa` (e.g., a `&str` for `String` provider ids).
+    pub fn decrypt<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let token = token.as_ref();
+
+        // Read before the token is verified, but only reported once it is.
+        let lifetime = self
+            .on_token_lifetime
+            .as_ref()
+            .and_then(|_| lifetime::observe(token, self.now()));
+
+        let result = self.decrypt_verified(tpa, token);
+
+        // Only verified if there is a shadow, and anyone to report it to.
+        if let Some(shadow) = self
+            .on_shadow_comparison
+            .as_ref()
+            .and(self.shadows.get(tpa))
+        {
+            self.compare(tpa, shadow, token, result.is_ok());
+        };
+
+        let token_data = result?;
+
+        if let (Some(on_token_lifetime), Some(tpa), Some(lifetime)) =
+            (&self.on_token_lifetime, self.registered(tpa), lifetime)
+        {
+            on_token_lifetime(tpa, &lifetime);
+        };
+
+        Ok(token_data)
+    }
+
+    /// Decrypt (and verify) the given token of the given provider exactly as
+    /// in [`decrypt`](`KeyRegistry::decrypt`), and then check that it carries
+    /// the given `nonce` (i.e., the one that was sent along with the
+    /// authentication request).
+    ///
+    /// Tokens which do not carry the `nonce` are rejected with
+    /// [`Error::nonce_mismatch`].
+    ///
+    /// See [`nonce`](`crate::key_caches::nonce`).
+    pub fn decrypt_with_nonce<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+        nonce: &str,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let TokenData { header, claims } =
+            self.decrypt::<Value, _, _>(tpa, token)?;
+
+        check_nonce(&claims, nonce)?;
+
+        let claims = serde_json::from_value(claims)
+            .map_err(jsonwebtoken::errors::Error::from)?;
+
+        Ok(TokenData { header, claims })
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the provider of
+    /// its issuer, returning that provider along with the token.
+    ///
+    /// The issuer is read from the (not yet verified) `iss` claim of the
+    /// token, and mapped to its provider by the issuers of this registry (see
+    /// [`issuer`](`KeyRegistryBuilder::issuer`)). The token is then verified
+    /// exactly as in [`decrypt`](`KeyRegistry::decrypt`). Since the signature
+    /// covers the `iss` claim, a verified token is guaranteed to carry the
+    /// issuer that it was routed by.
+    ///
+    /// Fails with [`Error::unknown_issuer`] if the token has no `iss` claim,
+    /// or if no provider has been registered for it.
+    ///
+    /// ```ignore
+    /// let registry = KeyRegistry::builder()
+    ///     .add_well_known(WellKnownTpa::Google)
+    ///     .add_remote(Tpa::Corporate, CORPORATE_JWK_URI)
+    ///     .issuer(Tpa::Corporate, "https://sso.example.com")
+    ///     .finish()
+    ///     .await?;
+    ///
+    /// let (tpa, data) = registry.decrypt_by_issuer::<Claims, _>(token)?;
+    /// ```
+    pub fn decrypt_by_issuer<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(&Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+    {
+        let token = token.as_ref();
+
+        let (_, claims) = inspect_token(token)?;
+        let tpa = claims
+            .get("iss")
+            .and_then(Value::as_str)
+            .and_then(|issuer| self.issuers.get(issuer))
+            .ok_or(Error::unknown_issuer)?;
+
+        self.decrypt::<Claims, &str, Tpa>(tpa, token)
+            .map(|token_data| (tpa, token_data))
+    }
+
+    /// Decrypt (and verify) the given token using the keys of whichever
+    /// provider signed it, returning that provider along with the token.
+    ///
+    /// Useful for endpoints which accept tokens from several providers, when
+    /// the client does not indicate which one issued the token (and the token
+    /// carries no known issuer, see
+    /// [`decrypt_by_issuer`](`KeyRegistry::decrypt_by_issuer`)).
+    ///
+    /// The providers are tried in order, each exactly as in
+    /// [`decrypt`](`KeyRegistry::decrypt`), until one of them verifies the
+    /// token. Providers whose cache holds no key for the token fail fast, so
+    /// this usually costs a single signature verification.
+    ///
+    /// If no provider verifies the token, the error of the first provider which
+    /// holds a key for it is returned (e.g., because the token has expired),
+    /// or else [`Error::no_corresponding_kid_in_store`].
+    ///
+    /// ```ignore
+    /// let (tpa, data) = registry.decrypt_any::<Claims, _>(token)?;
+    /// ```
+    ///
+    /// ### Note:
+    /// Only the provider which verified the token reports it to the
+    /// [`on_token_lifetime`](`KeyRegistryBuilder::on_token_lifetime`) and
+    /// [`on_shadow_comparison`](`KeyRegistryBuilder::on_shadow_comparison`)
+    /// callbacks.
+    pub fn decrypt_any<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(&Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+    {
+        let token = token.as_ref();
+        let mut error = Error::no_corresponding_kid_in_store;
+
+        let tpas = self.providers.keys().chain(self.caches.keys());
+
+        for tpa in tpas.collect::<BTreeSet<_>>() {
+            match self.decrypt_verified::<Claims, Tpa>(tpa, token) {
+                Ok(token_data) => {
+                    if let Some(shadow) = self
+                        .on_shadow_comparison
+                        .as_ref()
+                        .and(self.shadows.get(tpa))
+                    {
+                        self.compare(tpa, shadow, token, true);
+                    };
+
+                    let lifetime = lifetime::observe(token, self.now());
+
+                    if let (Some(on_token_lifetime), Some(lifetime)) =
+                        (&self.on_token_lifetime, lifetime)
+                    {
+                        on_token_lifetime(tpa, &lifetime);
+                    };
+
+                    return Ok((tpa, token_data));
+                },
+                Err(Error::no_corresponding_kid_in_store) => (),
+                Err(other) if error == Error::no_corresponding_kid_in_store => {
+                    error = other;
+                },
+                Err(_) => (),
+            };
+        }
+
+        Err(error)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider, fetching them first if they have never been fetched.
+    ///
+    /// Intended for registries built with
+    /// [`build_lazy`](`KeyRegistryBuilder::build_lazy`): the first token of
+    /// each provider fetches its keys (exactly as in
+    /// [`refresh`](`KeyRegistry::refresh`)), while every later token is
+    /// verified exactly as in [`decrypt`](`KeyRegistry::decrypt`), without
+    /// fetching anything.
+    ///
+    /// If the fetch fails, its error is returned (unless the provider is
+    /// inside of one of its maintenance windows), and the next call fetches
+    /// again.
+    pub async fn decrypt_lazy<Claims, I, Q>(
+        &mut self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !self.is_fetched(tpa) {
+            let _ = self.refresh(tpa).await?;
+        };
+
+        self.decrypt(tpa, token)
+    }
+
+    /// Check to see if the keys of the given provider have been fetched (or
+    /// otherwise loaded) at least once.
+    ///
+    /// Unregistered providers are reported as fetched, since there is nothing
+    /// to fetch.
+    pub fn is_fetched<Q>(&self, tpa: &Q) -> bool
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remote(tpa).is_none_or(|remote_cache| {
+            remote_cache.refreshed_at.is_some()
+                || !remote_cache.keys().is_empty()
+        })
+    }
+
+    fn decrypt_verified<Claims, Q>(
+        &self,
+        tpa: &Q,
+        token: &str,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(cache) = self.caches.get(tpa) {
+            let TokenData { header, claims } = cache.decrypt(token)?;
+            return self.checked(tpa, header, claims);
+        };
+
+        let remote_cache = self.remote(tpa).ok_or(Error::unknown_tpa)?;
+        let under_maintenance = self.is_under_maintenance(tpa);
+
+        if !Self::is_usable(remote_cache, under_maintenance) {
+            return remote_cache.observed(Err(Error::stale_cache));
+        };
+
+        let checked = self.expected_claims.contains_key(tpa)
+            || self.claims_validators.contains_key(tpa);
+        #[cfg(feature = "json-schema")]
+        let checked = checked || self.claims_schemas.contains_key(tpa);
+
+        match checked {
+            true => {
+                let TokenData { header, claims } =
+                    remote_cache.decrypt_unchecked::<Value, _>(token)?;
+                let checked = self.checked(tpa, header, claims);
+
+                remote_cache.observed(checked)
+            },
+            false => remote_cache.decrypt_unchecked(token),
+        }
+    }
+
+    /// Check the given (verified) claims against the claims schema, the
+    /// expected claims, and the claims validators of the given provider, and
+    /// then deserialize them.
+    fn checked<Claims, Q>(
+        &self,
+        tpa: &Q,
+        header: Header,
+        claims: Value,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        #[cfg(feature = "json-schema")]
+        if let Some(claims_schema) = self.claims_schemas.get(tpa) {
+            claims_schema.validate(&claims)?;
+        };
+
+        if let Some(expected_claims) = self.expected_claims.get(tpa) {
+            expected_claims.check(&claims)?;
+        };
+
+        let claims_validators = self.claims_validators.get(tpa);
+
+        for claims_validator in claims_validators.into_iter().flatten() {
+            claims_validator(&claims)?;
+        }
+
+        let claims = serde_json::from_value(claims)
+            .map_err(jsonwebtoken::errors::Error::from)?;
+
+        Ok(TokenData { header, claims })
+    }
+
+    /// Check to see if tokens can be verified using the given cache (of a
+    /// provider which may be under maintenance).
+    fn is_usable(remote_cache: &RemoteCache, under_maintenance: bool) -> bool {
+        remote_cache.is_cache_usable()
+            || (under_maintenance && !remote_cache.keys().is_empty())
+    }
+
+    /// The registered provider equal to the given one, if any.
+    pub(crate) fn registered<Q>(&self, tpa: &Q) -> Option<&Tpa>
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Self {
+            providers, caches, ..
+        } = self;
+
+        match providers.get_key_value(tpa) {
+            Some((tpa, _)) => Some(tpa),
+            None => caches.get_key_value(tpa).map(|(tpa, _)| tpa),
+        }
+    }
+
+    /// Verify the given token against the given shadow provider, and report
+    /// whether it agrees with the primary result.
+    fn compare<Q>(&self, tpa: &Q, shadow: &Tpa, token: &str, accepted: bool)
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let shadow = self
+            .decrypt_verified::<IgnoredAny, Tpa>(shadow, token)
+            .map(|_| ());
+
+        if let (Some(on_shadow_comparison), Some(tpa)) =
+            (&self.on_shadow_comparison, self.registered(tpa))
+        {
+            on_shadow_comparison(tpa, &ShadowOutcome::new(accepted, shadow));
+        };
+    }
+
+    /// Refresh the cache of the given provider.
+    ///
+    /// If the refresh fails while the provider is inside of one of its
+    /// maintenance windows, the failure is downgraded to
+    /// [`RefreshStatus::Deferred`].
+    pub async fn refresh<Q>(
+        &mut self,
+        tpa: &Q,
+    ) -> prelude::Result<RefreshStatus>
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let under_maintenance = self.is_under_maintenance(tpa);
+
+        let refreshed = match self.caches.get_mut(tpa) {
+            Some(cache) => cache.refresh().await,
+            None => {
+                let remote_cache =
+                    self.remote_mut(tpa).ok_or(Error::unknown_tpa)?;
+                remote_cache.refresh().await
+            },
+        };
+
+        RefreshStatus::of(refreshed, under_maintenance)
+    }
+
+    /// Refresh the caches of every provider concurrently, returning the
+    /// outcome for each provider.
+    ///
+    /// Caches shared by several providers are only refreshed once, but their
+    /// outcome is reported for each of them. Failures are downgraded exactly
+    /// as in [`refresh`](`KeyRegistry::refresh`) (i.e., for providers inside
+    /// of one of their maintenance windows).
+    ///
+    /// See [`freshness`](`crate::registry::freshness`).
+    pub async fn refresh_all(
+        &mut self,
+    ) -> BTreeMap<Tpa, prelude::Result<RefreshStatus>>
+    where
+        Tpa: Clone,
+    {
+        let Self {
+            remotes, caches, ..
+        } = self;
+
+        let refreshes = remotes.iter_mut().map(|(uri, remote_cache)| {
+            async move { (uri.clone(), remote_cache.refresh().await) }
+        });
+        let cache_refreshes = caches.iter_mut().map(|(tpa, cache)| {
+            async move { (tpa.clone(), cache.refresh().await) }
+        });
+
+        let (refreshed, cache_refreshed) =
+            tokio::join!(join_all(refreshes), join_all(cache_refreshes));
+
+        let mut statuses =
+            self.refresh_statuses(&refreshed.into_iter().collect());
+        for (tpa, refreshed) in cache_refreshed {
+            let under_maintenance = self.is_under_maintenance(&tpa);
+            let status = RefreshStatus::of(refreshed, under_maintenance);
+
+            let _ = statuses.insert(tpa, status);
+        }
+
+        statuses
+    }
+
+    /// The outcome for each provider, given the outcome of refreshing each
+    /// `uri`.
+    pub(crate) fn refresh_statuses(
+        &self,
+        refreshed: &BTreeMap<String, prelude::Result<()>>,
+    ) -> BTreeMap<Tpa, prelude::Result<RefreshStatus>>
+    where
+        Tpa: Clone,
+    {
+        self.providers
+            .iter()
+            .filter_map(|(tpa, uri)| {
+                let refreshed = refreshed.get(uri)?.clone();
+                let under_maintenance = self.is_under_maintenance(tpa);
+
+                let status = RefreshStatus::of(refreshed, under_maintenance);
+                Some((tpa.clone(), status))
+            })
+            .collect()
+    }
+
+    /// Report the [`CacheStatus`] of every provider (e.g., for a health
+    /// endpoint).
+    ///
+    /// Providers with a [`KeyCache`] of their own (see
+    /// [`KeyRegistryBuilder::add_cache`]) are not reported, since a status
+    /// can only be derived from a [`RemoteCache`].
+    ///
+    /// See [`freshness`](`crate::registry::freshness`).
+    pub fn freshness(&self) -> BTreeMap<&Tpa, CacheStatus> {
+        self.providers
+            .iter()
+            .filter_map(|(tpa, uri)| {
+                let remote_cache = self.remotes.get(uri)?;
+                let under_maintenance = self.is_under_maintenance(tpa);
+
+                let status = CacheStatus {
+                    uri: uri.clone(),
+                    key_count: remote_cache.keys().len(),
+                    expiry_time: remote_cache.expiry_time,
+                    refreshed_at: remote_cache.refreshed_at,
+                    fresh: remote_cache.is_cache_fresh(),
+                    under_maintenance,
+                    usable: Self::is_usable(remote_cache, under_maintenance),
+                };
+
+                Some((tpa, status))
+            })
+            .collect()
+    }
+
+    /// Register a [`RemoteCache`] (with the default configuration) targeting
+    /// the given `uri` for the given provider, after construction (e.g., for
+    /// tenants configured at runtime).
+    ///
+    /// If another provider has already been registered with the same `uri`,
+    /// its cache is shared instead (without fetching again). Otherwise, the
+    /// keys are fetched before the provider is registered, so a failed fetch
+    /// leaves the registry unchanged.
+    ///
+    /// Registering a provider which is already registered replaces its `uri`
+    /// (see [`replace_uri`](`KeyRegistry::replace_uri`)), but keeps the rest
+    /// of its configuration (e.g., its maintenance windows).
+    ///
+    /// ```ignore
+    /// registry.add_remote(Tenant::Acme, "https://sso.acme.com/certs").await?;
+    /// ```
+    pub async fn add_remote<I>(
+        &mut self,
+        tpa: Tpa,
+        uri: I,
+    ) -> prelude::Result<()>
+    where
+        String: From<I>,
+    {
+        let uri = String::from(uri).parse::<http::Uri>()?.to_string();
+
+        if !self.remotes.contains_key(&uri) {
+            self.insert_remote(&uri).await?;
+        };
+
+        self.set_uri(tpa, uri);
+        Ok(())
+    }
+
+    /// Point the given (registered) provider to a [`RemoteCache`] (with the
+    /// default configuration) targeting the given `uri`, and fetch its keys.
+    ///
+    /// The keys are fetched even if the `uri` is already used by another
+    /// provider (or by the given provider itself). If the fetch fails, the
+    /// provider keeps using its previous cache.
+    ///
+    /// Fails with [`Error::unknown_tpa`] if the provider has not been
+    /// registered.
+    ///
+    /// ### Note:
+    /// The configuration of the previous cache (e.g., a
+    /// [`StalePolicy`](`crate::key_caches::remote::policy::StalePolicy`)) is
+    /// not carried over.
+    pub async fn replace_uri<Q, I>(
+        &mut self,
+        tpa: &Q,
+        uri: I,
+    ) -> prelude::Result<()>
+    where
+        String: From<I>,
+        Tpa: Borrow<Q> + Clone,
+        Q: Ord + ?Sized,
+    {
+        let tpa = self.registered(tpa).ok_or(Error::unknown_tpa)?.clone();
+        let uri = String::from(uri).parse::<http::Uri>()?.to_string();
+
+        match self.remotes.get_mut(&uri) {
+            Some(remote_cache) => remote_cache.refresh().await?,
+            None => self.insert_remote(&uri).await?,
+        };
+
+        self.set_uri(tpa, uri);
+        Ok(())
+    }
+
+    /// Unregister the given provider, along with its configuration (i.e., its
+    /// maintenance windows, claims schema, expected claims, shadows, and
+    /// issuers).
+    ///
+    /// Its cache is dropped, unless it is shared with another provider.
+    /// Returns whether the provider was registered.
+    pub fn remove<Q>(&mut self, tpa: &Q) -> bool
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Self {
+            providers,
+            remotes,
+            caches,
+            maintenance_windows,
+            #[cfg(feature = "json-schema")]
+            claims_schemas,
+            expected_claims,
+            claims_validators,
+            shadows,
+            issuers,
+            ..
+        } = self;
+
+        let uri = providers.remove(tpa);
+
+        if uri.is_none() && caches.remove(tpa).is_none() {
+            return false;
+        };
+
+        let _ = maintenance_windows.remove(tpa);
+        #[cfg(feature = "json-schema")]
+        let _ = claims_schemas.remove(tpa);
+        let _ = expected_claims.remove(tpa);
+        let _ = claims_validators.remove(tpa);
+        shadows.retain(|primary, shadow| {
+            primary.borrow() != tpa && (*shadow).borrow() != tpa
+        });
+        issuers.retain(|_, issued| (*issued).borrow() != tpa);
+
+        if let Some(uri) = uri {
+            if !providers.values().any(|used| *used == uri) {
+                let _ = remotes.remove(&uri);
+            };
+        };
+
+        true
+    }
+
+    /// Create (and fetch) a cache for the given `uri`, without using it for
+    /// any provider yet.
+    async fn insert_remote(&mut self, uri: &str) -> prelude::Result<()> {
+        let mut remote_cache = self.new_remote(uri)?;
+        remote_cache.refresh().await?;
+
+        let _ = self.remotes.insert(uri.into(), remote_cache);
+        Ok(())
+    }
+
+    /// Create an empty cache (with the default configuration, and the store
+    /// of this registry) for the given `uri`.
+    pub(crate) fn new_remote(&self, uri: &str) -> prelude::Result<RemoteCache> {
+        let mut remote_cache = RemoteCache::new(uri)?;
+        remote_cache.cache_store = self.cache_store.clone();
+        remote_cache.observer = self.observer.clone();
+        remote_cache.clock = self.clock.clone();
+
+        Ok(remote_cache)
+    }
+
+    /// Use the cache of the given `uri` for the given provider, dropping its
+    /// previous cache if no other provider uses it.
+    pub(crate) fn set_uri(&mut self, tpa: Tpa, uri: String) {
+        let Self {
+            providers,
+            remotes,
+            caches,
+            ..
+        } = self;
+
+        let _ = caches.remove(&tpa);
+
+        if let Some(previous) = providers.insert(tpa, uri) {
+            if !providers.values().any(|used| *used == previous) {
+                let _ = remotes.remove(&previous);
+            };
+        };
+    }
+
+    /// The current time, according to the [`Clock`] of this [`KeyRegistry`]
+    /// (or else, the system).
+    pub(crate) fn now(&self) -> Timestamp {
+        self.clock.as_ref().map_or_else(now, |clock| clock.now())
+    }
+
+    /// Check to see if the given provider is currently inside of one of its
+    /// maintenance windows.
+    pub fn is_under_maintenance<Q>(&self, tpa: &Q) -> bool
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let now = self.now();
+
+        self.maintenance_windows.get(tpa).is_some_and(|windows| {
+            windows.iter().any(|window| window.contains(now))
+        })
+    }
+
+    /// Get an immutable reference to the [`RemoteCache`] of the given
+    /// provider.
+    ///
+    /// ### Note:
+    /// The cache may be shared with other providers.
+    pub fn remote<Q>(&self, tpa: &Q) -> Option<&RemoteCache>
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Self {
+            providers, remotes, ..
+        } = self;
+
+        providers.get(tpa).and_then(|uri| remotes.get(uri))
+    }
+
+    /// Get a mutable reference to the [`RemoteCache`] of the given provider.
+    ///
+    /// ### Note:
+    /// The cache may be shared with other providers.
+    pub fn remote_mut<Q>(&mut self, tpa: &Q) -> Option<&mut RemoteCache>
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Self {
+            providers, remotes, ..
+        } = self;
+
+        providers.get(tpa).and_then(|uri| remotes.get_mut(uri))
+    }
+
+    /// Get an immutable reference to the [`KeyCache`] of the given provider
+    /// (see [`KeyRegistryBuilder::add_cache`]), if it is of type `C`.
+    ///
+    /// ```ignore
+    /// let local_cache = registry.cache::<LocalCache, _>(&Tpa::FirstParty)?;
+    /// let token = local_cache.encrypt(claims)?;
+    /// ```
+    pub fn cache<C, Q>(&self, tpa: &Q) -> Option<&C>
+    where
+        C: KeyCache,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let cache: &dyn Any = self.caches.get(tpa)?.as_ref();
+        cache.downcast_ref()
+    }
+
+    /// Get a mutable reference to the [`KeyCache`] of the given provider
+    /// (see [`KeyRegistryBuilder::add_cache`]), if it is of type `C`.
+    pub fn cache_mut<C, Q>(&mut self, tpa: &Q) -> Option<&mut C>
+    where
+        C: KeyCache,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let cache: &mut dyn Any = self.caches.get_mut(tpa)?.as_mut();
+        cache.downcast_mut()
+    }
+
+    /// Get a mutable reference to the maintenance windows of all providers.
+    pub fn maintenance_windows_mut(
+        &mut self,
+    ) -> &mut BTreeMap<Tpa, Vec<MaintenanceWindow>> {
+        &mut self.maintenance_windows
+    }
+}
+
+impl KeyRegistry<WellKnownTpa> {
+    /// Create a [`KeyRegistryBuilder`] with the given well-known providers
+    /// already registered.
+    ///
+    /// Each provider is registered exactly as with
+    /// [`add_well_known`](`KeyRegistryBuilder::add_well_known`), and its
+    /// tokens must carry one of its [`issuers`](`WellKnownTpa::issuers`)
+    /// (see [`WellKnownTpa::expected_claims`]). The audience of each provider
+    /// is app-specific, and can be set by calling
+    /// [`audience`](`KeyRegistryBuilder::audience`).
+    ///
+    /// ```ignore
+    /// let registry = KeyRegistry::with_well_known(&[
+    ///     WellKnownTpa::Google,
+    ///     WellKnownTpa::Apple,
+    /// ])
+    /// .audience(WellKnownTpa::Google, "1234.apps.googleusercontent.com")
+    /// .audience(WellKnownTpa::Apple, "com.example.app")
+    /// .finish()
+    /// .await?;
+    /// ```
+    pub fn with_well_known(
+        tpas: &[WellKnownTpa],
+    ) -> KeyRegistryBuilder<WellKnownTpa> {
+        tpas.iter().fold(Self::builder(), |builder, tpa| {
+            builder
+                .add_well_known(*tpa)
+                .expected_claims(*tpa, tpa.expected_claims())
+        })
+    }
+
+    /// Decrypt (and verify) the given token of the given provider exactly as
+    /// in [`decrypt`](`KeyRegistry::decrypt`), into the provider's claims, and
+    /// then convert them into [`NormalizedClaims`].
+    ///
+    /// ```ignore
+    /// let TokenData { claims, .. } =
+    ///     registry.decrypt_normalized(&WellKnownTpa::Apple, token)?;
+    /// let NormalizedClaims { subject, email, .. } = claims;
+    /// ```
+    ///
+    /// See [`normalized`](`crate::key_caches::normalized`).
+    pub fn decrypt_normalized<I>(
+        &self,
+        tpa: &WellKnownTpa,
+        token: I,
+    ) -> prelude::Result<TokenData<NormalizedClaims>>
+    where
+        I: AsRef<str>,
+    {
+        match tpa {
+            WellKnownTpa::Google => {
+                self.decrypt::<GoogleClaims, _, _>(tpa, token).map(normalize)
+            },
+            WellKnownTpa::Apple => {
+                self.decrypt::<AppleClaims, _, _>(tpa, token).map(normalize)
+            },
+            WellKnownTpa::Facebook => {
+                self.decrypt::<FacebookClaims, _, _>(tpa, token).map(normalize)
+            },
+            WellKnownTpa::Firebase => {
+                self.decrypt::<FirebaseClaims, _, _>(tpa, token).map(normalize)
+            },
+            WellKnownTpa::Microsoft => self
+                .decrypt::<MicrosoftClaims, _, _>(tpa, token)
+                .map(normalize),
+        }
+    }
+}
+
+/// Convert the claims of the given token into [`NormalizedClaims`].
+fn normalize<Claims>(
+    token_data: TokenData<Claims>,
+) -> TokenData<NormalizedClaims>
+where
+    NormalizedClaims: From<Claims>,
+{
+    let TokenData { header, claims } = token_data;
+
+    TokenData {
+        header,
+        claims: NormalizedClaims::from(claims),
+    }
+}
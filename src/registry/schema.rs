@@ -0,0 +1,446 @@
+//! Validating the claims of verified tokens against a `JSON Schema`.
+//!
+//! Only available when the `json-schema` feature is enabled.
+//!
+//! Providers occasionally change the shape of their claims (e.g., a claim
+//! becomes optional, or turns from a string into a boolean). Deserializing
+//! such claims fails with an opaque `serde` error. Attaching a
+//! [`ClaimsSchema`] to a provider instead reports *every* deviation, along
+//! with where it occurred:
+//!
+//! ```ignore
+//! let schema = ClaimsSchema::new(json!({
+//!     "type": "object",
+//!     "required": ["sub", "email"],
+//!     "properties": {
+//!         "email": { "type": "string" },
+//!         "email_verified": { "type": "boolean" }
+//!     }
+//! }))?;
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .claims_schema(Tpa::Google, schema)
+//!     .finish()
+//!     .await?;
+//!
+//! // Fails with `Error::claims_schema_mismatch { message }`, where `message`
+//! // is e.g. "`/email`: expected a string, found a number."
+//! let data = registry.decrypt::<GoogleClaims, _, _>(&Tpa::Google, token)?;
+//! ```
+//!
+//! ### Note:
+//! Only the following keywords are supported: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+//! `maxItems`, `minLength`, `maxLength`, `minimum`, `maximum`, `allOf`, and
+//! `anyOf`. Schemas using any other keyword (except for annotations, such as
+//! `title` or `description`) are rejected upfront, instead of being silently
+//! ignored.
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::prelude;
+
+/// Keywords which are allowed, but have no effect on validation.
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+];
+
+/// The names of the types of `JSON` values.
+const TYPES: &[&str] = &[
+    "null", "boolean", "object", "array", "number", "integer", "string",
+];
+
+/// Keywords which are validated.
+const ASSERTIONS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "allOf",
+    "anyOf",
+];
+
+fn invalid<M>(path: &str, message: M) -> Error
+where
+    M: std::fmt::Display,
+{
+    Error::invalid_schema {
+        message: format!("`{}`: {}", path_or_root(path), message),
+    }
+}
+
+fn path_or_root(path: &str) -> &str {
+    match path {
+        "" => "/",
+        path => path,
+    }
+}
+
+/// The `JSON Schema` name of the type of the given value.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Check to see if the given value is of the given `JSON Schema` type.
+fn is_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("number", Value::Number(_)) => true,
+        ("integer", Value::Number(number)) => {
+            !number.is_f64() || number.as_f64().is_some_and(|n| n.fract() == 0.)
+        },
+        (name, value) => type_name(value) == name,
+    }
+}
+
+/// An article and a type name (e.g., "a string").
+fn described(name: &str) -> String {
+    match name {
+        "array" | "integer" | "object" => format!("an {}", name),
+        "null" => name.into(),
+        name => format!("a {}", name),
+    }
+}
+
+/// The given value, or its elements (if it is an array).
+fn one_or_many(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(values) => values,
+        value => std::slice::from_ref(value),
+    }
+}
+
+/// Escape a property name for use inside of a `JSON Pointer`.
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// A `JSON Schema` which the claims of a provider's tokens are validated
+/// against.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimsSchema {
+    schema: Value,
+}
+
+impl ClaimsSchema {
+    /// Create a [`ClaimsSchema`] from the given `JSON Schema`.
+    ///
+    /// Fails with [`Error::invalid_schema`] if the schema is malformed, or
+    /// uses an unsupported keyword.
+    pub fn new(schema: Value) -> prelude::Result<Self> {
+        check(&schema, "")?;
+
+        Ok(Self { schema })
+    }
+
+    /// The `JSON Schema` itself.
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// Validate the given claims against this schema.
+    ///
+    /// Fails with [`Error::claims_schema_mismatch`], listing every deviation
+    /// (along with its `JSON Pointer`).
+    pub fn validate(&self, claims: &Value) -> prelude::Result<()> {
+        let mut violations = Vec::new();
+        validate(&self.schema, claims, "", &mut violations);
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(Error::claims_schema_mismatch {
+                message: violations.join(" "),
+            }),
+        }
+    }
+}
+
+/// Check that the given schema only uses supported keywords, with values of
+/// the expected shape.
+fn check(schema: &Value, path: &str) -> prelude::Result<()> {
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        _ => return Err(invalid(path, "expected a schema.")),
+    };
+
+    for (keyword, value) in schema {
+        let at = format!("{}/{}", path, escape(keyword));
+
+        match keyword.as_str() {
+            "type" => {
+                let known = one_or_many(value).iter().all(|name| {
+                    name.as_str().is_some_and(|name| TYPES.contains(&name))
+                });
+
+                if !known {
+                    return Err(invalid(&at, "expected type names."));
+                };
+            },
+            "enum" if !value.is_array() => {
+                return Err(invalid(&at, "expected an array."))
+            },
+            "required" => {
+                let names = value
+                    .as_array()
+                    .filter(|names| names.iter().all(Value::is_string));
+
+                if names.is_none() {
+                    return Err(invalid(&at, "expected property names."));
+                };
+            },
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .ok_or_else(|| invalid(&at, "expected an object."))?;
+
+                for (name, schema) in properties {
+                    check(schema, &format!("{}/{}", at, escape(name)))?;
+                }
+            },
+            "additionalProperties" | "items" => check(value, &at)?,
+            "allOf" | "anyOf" => {
+                let schemas = value
+                    .as_array()
+                    .filter(|schemas| !schemas.is_empty())
+                    .ok_or_else(|| invalid(&at, "expected schemas."))?;
+
+                for (index, schema) in schemas.iter().enumerate() {
+                    check(schema, &format!("{}/{}", at, index))?;
+                }
+            },
+            "minItems" | "maxItems" | "minLength" | "maxLength"
+                if !value.is_u64() =>
+            {
+                return Err(invalid(&at, "expected a non-negative integer."))
+            },
+            "minimum" | "maximum" if !value.is_number() => {
+                return Err(invalid(&at, "expected a number."))
+            },
+            keyword
+                if ASSERTIONS.contains(&keyword)
+                    || ANNOTATIONS.contains(&keyword) => {},
+            _ => return Err(invalid(&at, "unsupported keyword.")),
+        };
+    }
+
+    Ok(())
+}
+
+/// Validate the given value against the given (checked) schema, collecting
+/// every violation.
+fn validate(
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            violations.push(format!("`{}`: not allowed.", path_or_root(path)));
+            return;
+        },
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    let mut violation = |path: &str, message: String| {
+        violations.push(format!("`{}`: {}", path_or_root(path), message));
+    };
+
+    if let Some(names) = schema.get("type") {
+        let names = one_or_many(names)
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+
+        if !names.iter().any(|name| is_type(value, name)) {
+            let expected = names
+                .iter()
+                .map(|name| described(name))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            let found = described(type_name(value));
+
+            // Nothing else can be validated meaningfully.
+            return violation(
+                path,
+                format!("expected {}, found {}.", expected, found),
+            );
+        };
+    };
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.contains(value) {
+            let expected = Value::Array(values.clone());
+            violation(path, format!("expected one of {}.", expected));
+        };
+    };
+
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violation(path, format!("expected {}.", expected));
+        };
+    };
+
+    if let (Some(minimum), Some(number)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if number < minimum {
+            violation(path, format!("expected at least {}.", minimum));
+        };
+    };
+
+    if let (Some(maximum), Some(number)) = (
+        schema.get("maximum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if number > maximum {
+            violation(path, format!("expected at most {}.", maximum));
+        };
+    };
+
+    if let Value::String(string) = value {
+        let length = string.chars().count() as u64;
+
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                let message = format!("expected at least {} characters.", min);
+                violation(path, message);
+            };
+        };
+
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                let message = format!("expected at most {} characters.", max);
+                violation(path, message);
+            };
+        };
+    };
+
+    if let Value::Array(items) = value {
+        let length = items.len() as u64;
+
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if length < min {
+                violation(path, format!("expected at least {} items.", min));
+            };
+        };
+
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if length > max {
+                violation(path, format!("expected at most {} items.", max));
+            };
+        };
+    };
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    let at = format!("{}/{}", path, escape(name));
+                    violation(&at, "required, but missing.".into());
+                };
+            }
+        };
+    };
+
+    validate_children(schema, value, path, violations);
+}
+
+/// Validate the properties or items of the given value, as well as the
+/// sub-schemas which apply to the value itself.
+fn validate_children(
+    schema: &Map<String, Value>,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    match value {
+        Value::Object(object) => {
+            let properties =
+                schema.get("properties").and_then(Value::as_object);
+
+            // Sorted, so that violations are reported in the same order
+            // regardless of whether `serde_json` preserves insertion order.
+            let mut object = object.iter().collect::<Vec<_>>();
+            object.sort_unstable_by_key(|(name, _)| *name);
+
+            for (name, property) in object {
+                let at = format!("{}/{}", path, escape(name));
+                let declared =
+                    properties.and_then(|declared| declared.get(name));
+
+                match (declared, schema.get("additionalProperties")) {
+                    (Some(declared), _) => {
+                        validate(declared, property, &at, violations)
+                    },
+                    (None, Some(additional)) => {
+                        validate(additional, property, &at, violations)
+                    },
+                    (None, None) => (),
+                };
+            }
+        },
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    let at = format!("{}/{}", path, index);
+                    validate(item_schema, item, &at, violations);
+                }
+            };
+        },
+        _ => (),
+    };
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for schema in schemas {
+            validate(schema, value, path, violations);
+        }
+    };
+
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        let matched = schemas.iter().any(|schema| {
+            let mut nested = Vec::new();
+            validate(schema, value, path, &mut nested);
+            nested.is_empty()
+        });
+
+        if !matched {
+            violations.push(format!(
+                "`{}`: does not match any of the allowed schemas.",
+                path_or_root(path),
+            ));
+        };
+    };
+}
@@ -0,0 +1,183 @@
+//! Shadow verification, for migrating between providers.
+//!
+//! While migrating from one provider to another (e.g., from `Auth0` to
+//! `Cognito`), a [`KeyRegistry`] can verify the tokens of the current
+//! (primary) provider against the next (shadow) provider as well, and report
+//! whether both of them agree. This gives confidence that the new provider
+//! accepts exactly the tokens that the old one does, before switching over.
+//!
+//! The shadow verification never affects the primary result: its outcome is
+//! only reported to the
+//! [`on_shadow_comparison`](`crate::registry::builder::KeyRegistryBuilder::on_shadow_comparison`)
+//! callback of the registry. The callback can feed any metrics library.
+//! Alternatively, [`ShadowComparisons`] keeps [`ShadowStats`] per provider
+//! in-process:
+//!
+//! ```ignore
+//! let comparisons = Arc::new(ShadowComparisons::default());
+//!
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Auth0, AUTH0_JWK_URI)
+//!     .add_remote(Tpa::Cognito, COGNITO_JWK_URI)
+//!     .shadow(Tpa::Auth0, Tpa::Cognito)
+//!     .on_shadow_comparison({
+//!         let comparisons = Arc::clone(&comparisons);
+//!         move |tpa, outcome| comparisons.record(tpa, outcome)
+//!     })
+//!     .finish()
+//!     .await?;
+//!
+//! let stats = comparisons.get(&Tpa::Auth0).unwrap();
+//! println!("{:?} agreement", stats.agreement_rate());
+//! ```
+//!
+//! ### Note:
+//! The shadow verification runs right after the primary one, on the calling
+//! thread (i.e., it doubles the cost of verifying the primary provider's
+//! tokens). Its claims are not deserialized.
+//!
+//! [`KeyRegistry`]: `crate::registry::KeyRegistry`
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::error::Error;
+
+pub(crate) type ShadowCallback<Tpa> =
+    Arc<dyn Fn(&Tpa, &ShadowOutcome) + Send + Sync>;
+
+/// The outcome of verifying a token against both a primary provider and its
+/// shadow.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShadowOutcome {
+    /// Both providers accepted the token.
+    BothAccepted,
+
+    /// Both providers rejected the token.
+    BothRejected,
+
+    /// Only the primary provider accepted the token.
+    ///
+    /// Contains the error with which the shadow provider rejected it.
+    PrimaryOnly(Error),
+
+    /// Only the shadow provider accepted the token.
+    ShadowOnly,
+}
+
+impl ShadowOutcome {
+    pub(crate) fn new(primary: bool, shadow: Result<(), Error>) -> Self {
+        match (primary, shadow) {
+            (true, Ok(())) => Self::BothAccepted,
+            (false, Err(_)) => Self::BothRejected,
+            (true, Err(error)) => Self::PrimaryOnly(error),
+            (false, Ok(())) => Self::ShadowOnly,
+        }
+    }
+
+    /// Check to see if both providers came to the same conclusion.
+    pub fn is_agreement(&self) -> bool {
+        matches!(self, Self::BothAccepted | Self::BothRejected)
+    }
+}
+
+/// The number of each [`ShadowOutcome`] of a single provider.
+#[derive(Clone, Copy, Hash, Debug, Default, PartialEq, Eq)]
+pub struct ShadowStats {
+    /// The number of [`ShadowOutcome::BothAccepted`] outcomes.
+    pub both_accepted: u64,
+
+    /// The number of [`ShadowOutcome::BothRejected`] outcomes.
+    pub both_rejected: u64,
+
+    /// The number of [`ShadowOutcome::PrimaryOnly`] outcomes.
+    pub primary_only: u64,
+
+    /// The number of [`ShadowOutcome::ShadowOnly`] outcomes.
+    pub shadow_only: u64,
+}
+
+impl ShadowStats {
+    /// Count the given outcome.
+    pub fn record(&mut self, outcome: &ShadowOutcome) {
+        let count = match outcome {
+            ShadowOutcome::BothAccepted => &mut self.both_accepted,
+            ShadowOutcome::BothRejected => &mut self.both_rejected,
+            ShadowOutcome::PrimaryOnly(_) => &mut self.primary_only,
+            ShadowOutcome::ShadowOnly => &mut self.shadow_only,
+        };
+
+        *count += 1;
+    }
+
+    /// The number of counted outcomes.
+    pub fn total(&self) -> u64 {
+        let Self {
+            both_accepted,
+            both_rejected,
+            primary_only,
+            shadow_only,
+        } = self;
+
+        both_accepted + both_rejected + primary_only + shadow_only
+    }
+
+    /// The fraction (between `0.0` and `1.0`) of counted outcomes in which
+    /// both providers agreed, if any were counted.
+    pub fn agreement_rate(&self) -> Option<f64> {
+        let total = self.total();
+        let agreed = self.both_accepted + self.both_rejected;
+
+        (total != 0).then(|| agreed as f64 / total as f64)
+    }
+}
+
+/// Keeps [`ShadowStats`] per (primary) provider.
+///
+/// See the [module level documentation](`self`).
+#[derive(Debug)]
+pub struct ShadowComparisons<Tpa> {
+    stats: Mutex<BTreeMap<Tpa, ShadowStats>>,
+}
+
+impl<Tpa> Default for ShadowComparisons<Tpa> {
+    fn default() -> Self {
+        Self {
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<Tpa> ShadowComparisons<Tpa>
+where
+    Tpa: Clone + Ord,
+{
+    /// Count the given outcome of a token of the given provider.
+    pub fn record(&self, tpa: &Tpa, outcome: &ShadowOutcome) {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(tpa.clone())
+            .or_default()
+            .record(outcome);
+    }
+
+    /// The current counts of the given provider, if any of its tokens have
+    /// been compared.
+    pub fn get(&self, tpa: &Tpa) -> Option<ShadowStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(tpa)
+            .copied()
+    }
+
+    /// The current counts of every provider.
+    pub fn snapshot(&self) -> BTreeMap<Tpa, ShadowStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
@@ -0,0 +1,531 @@
+//! A [`SharedKeyRegistry`] split into shards, for registries with thousands
+//! of (e.g., tenant) providers.
+//!
+//! Every verification of a [`SharedKeyRegistry`] takes its read lock, and
+//! every refresh (or provider added at runtime) briefly takes its write lock.
+//! With thousands of tenants active at once, the write locks stall every
+//! verification of every other tenant.
+//!
+//! A [`ShardedKeyRegistry`] spreads the providers over `N` independent
+//! [`SharedKeyRegistry`]s (each with a lock of its own), so that a write lock
+//! only stalls the verifications of the providers of a single shard. Each
+//! provider is assigned to a shard by a consistent hash of its id (see
+//! [`shard_index`](`ShardedKeyRegistry::shard_index`)), so providers added at
+//! runtime (see [`add_remote`](`ShardedKeyRegistry::add_remote`)) are routed
+//! without any shared state.
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tenant::from("acme"), "https://sso.acme.com/certs")
+//!     .issuer(Tenant::from("acme"), "https://sso.acme.com")
+//!     .finish()
+//!     .await?
+//!     .into_sharded(16);
+//!
+//! // In every handler:
+//! let (tenant, data) = registry.decrypt_by_issuer::<Claims, _>(token)?;
+//!
+//! // Meanwhile, in a background task:
+//! registry.refresh_all().await;
+//! ```
+//!
+//! ### Note:
+//! Caches are only shared within a shard. Providers which share a `uri` (or
+//! which shadow each other, see [`shadow`](`crate::registry::shadow`)) when
+//! the registry is sharded are kept on the same shard, but a provider added
+//! later with the `uri` of a provider on another shard gets a cache of its
+//! own.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use futures_util::future::join_all;
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::insecure::inspect_token;
+use crate::prelude;
+use crate::registry::freshness::CacheStatus;
+use crate::registry::shared::SharedKeyRegistry;
+use crate::registry::KeyRegistry;
+use crate::registry::RefreshStatus;
+
+/// A [`KeyRegistry`] split into [`SharedKeyRegistry`] shards, which can be
+/// shared (and refreshed) by many tasks at once.
+///
+/// Created by calling [`KeyRegistry::into_sharded`]. Clones share the same
+/// shards.
+///
+/// See the [module level documentation](`self`).
+pub struct ShardedKeyRegistry<Tpa> {
+    shards: Arc<Vec<SharedKeyRegistry<Tpa>>>,
+
+    /// The shard of each provider which was kept together with another one
+    /// when the registry was sharded (i.e., which is not at its own hash).
+    pinned: Arc<BTreeMap<Tpa, usize>>,
+
+    /// The shard of the provider of each issuer.
+    issuers: Arc<BTreeMap<String, usize>>,
+}
+
+impl<Tpa> Clone for ShardedKeyRegistry<Tpa> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: Arc::clone(&self.shards),
+            pinned: Arc::clone(&self.pinned),
+            issuers: Arc::clone(&self.issuers),
+        }
+    }
+}
+
+impl<Tpa> KeyRegistry<Tpa>
+where
+    Tpa: Clone + Hash + Ord,
+{
+    /// Split this registry into a [`ShardedKeyRegistry`] of the given number
+    /// of shards (at least one).
+    ///
+    /// The configuration of each provider (e.g., its maintenance windows, and
+    /// its issuers) moves to its shard, while the registry-wide configuration
+    /// (e.g., the [`CacheObserver`](`crate::observer::CacheObserver`)) is
+    /// shared by every shard.
+    pub fn into_sharded(self, shards: usize) -> ShardedKeyRegistry<Tpa> {
+        let Self {
+            providers,
+            mut remotes,
+            caches,
+            cache_store,
+            observer,
+            clock,
+            maintenance_windows,
+            #[cfg(feature = "json-schema")]
+            claims_schemas,
+            expected_claims,
+            claims_validators,
+            on_token_lifetime,
+            shadows,
+            on_shadow_comparison,
+            issuers,
+        } = self;
+
+        let len = shards.max(1);
+        let pinned = pin(&providers, &shadows, len);
+        let index = |tpa: &Tpa| shard_index(&pinned, len, tpa);
+
+        let mut registries = (0..len)
+            .map(|_| KeyRegistry {
+                providers: BTreeMap::new(),
+                remotes: BTreeMap::new(),
+                caches: BTreeMap::new(),
+                cache_store: cache_store.clone(),
+                observer: observer.clone(),
+                clock: clock.clone(),
+                maintenance_windows: BTreeMap::new(),
+                #[cfg(feature = "json-schema")]
+                claims_schemas: BTreeMap::new(),
+                expected_claims: BTreeMap::new(),
+                claims_validators: BTreeMap::new(),
+                on_token_lifetime: on_token_lifetime.clone(),
+                shadows: BTreeMap::new(),
+                on_shadow_comparison: on_shadow_comparison.clone(),
+                issuers: BTreeMap::new(),
+            })
+            .collect::<Vec<_>>();
+
+        // Every entry is moved into the shard of its provider; `index` is
+        // always less than `len`, so no entry is ever dropped.
+        for (tpa, uri) in providers {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                if let Some(remote_cache) = remotes.remove(&uri) {
+                    let _ = registry.remotes.insert(uri.clone(), remote_cache);
+                };
+
+                let _ = registry.providers.insert(tpa, uri);
+            };
+        }
+
+        let mut shard_issuers = BTreeMap::new();
+        for (issuer, tpa) in issuers {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = shard_issuers.insert(issuer.clone(), index(&tpa));
+                let _ = registry.issuers.insert(issuer, tpa);
+            };
+        }
+
+        for (tpa, cache) in caches {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = registry.caches.insert(tpa, cache);
+            };
+        }
+
+        for (tpa, windows) in maintenance_windows {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = registry.maintenance_windows.insert(tpa, windows);
+            };
+        }
+
+        #[cfg(feature = "json-schema")]
+        for (tpa, schema) in claims_schemas {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = registry.claims_schemas.insert(tpa, schema);
+            };
+        }
+
+        for (tpa, expected) in expected_claims {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = registry.expected_claims.insert(tpa, expected);
+            };
+        }
+
+        for (tpa, validators) in claims_validators {
+            if let Some(registry) = registries.get_mut(index(&tpa)) {
+                let _ = registry.claims_validators.insert(tpa, validators);
+            };
+        }
+
+        for (primary, shadow) in shadows {
+            if let Some(registry) = registries.get_mut(index(&primary)) {
+                let _ = registry.shadows.insert(primary, shadow);
+            };
+        }
+
+        ShardedKeyRegistry {
+            shards: Arc::new(
+                registries.into_iter().map(KeyRegistry::into_shared).collect(),
+            ),
+            pinned: Arc::new(pinned),
+            issuers: Arc::new(shard_issuers),
+        }
+    }
+}
+
+impl<Tpa> ShardedKeyRegistry<Tpa>
+where
+    Tpa: Hash + Ord,
+{
+    /// The shards of this registry (e.g., in order to inspect, or lock, a
+    /// single one of them).
+    pub fn shards(&self) -> &[SharedKeyRegistry<Tpa>] {
+        &self.shards
+    }
+
+    /// The index of the shard of the given provider (whether it is registered
+    /// or not).
+    ///
+    /// Just like with a [`BTreeMap`], the provider can be given as any
+    /// borrowed form of `Tpa` (e.g., a `&str` for `String` provider ids).
+    pub fn shard_index<Q>(&self, tpa: &Q) -> usize
+    where
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        shard_index(&self.pinned, self.shards.len(), tpa)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider.
+    ///
+    /// See [`KeyRegistry::decrypt`].
+    pub fn decrypt<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shard(tpa)?.decrypt(tpa, token)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the provider of
+    /// its issuer, returning that provider along with the token.
+    ///
+    /// The issuer is routed to its shard without locking any other shard.
+    ///
+    /// See [`KeyRegistry::decrypt_by_issuer`].
+    pub fn decrypt_by_issuer<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Clone,
+    {
+        let token = token.as_ref();
+
+        let (_, claims) = inspect_token(token)?;
+        let shard = claims
+            .get("iss")
+            .and_then(Value::as_str)
+            .and_then(|issuer| self.issuers.get(issuer))
+            .and_then(|index| self.shards.get(*index))
+            .ok_or(Error::unknown_issuer)?;
+
+        shard.decrypt_by_issuer(token)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of whichever
+    /// provider signed it, returning that provider along with the token.
+    ///
+    /// The shards are tried in order, each exactly as in
+    /// [`SharedKeyRegistry::decrypt_any`], until one of them verifies the
+    /// token. If none does, the error of the first shard with a provider
+    /// which holds a key for the token is returned, or else
+    /// [`Error::no_corresponding_kid_in_store`].
+    ///
+    /// See [`KeyRegistry::decrypt_any`].
+    pub fn decrypt_any<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Clone,
+    {
+        let token = token.as_ref();
+        let mut error = Error::no_corresponding_kid_in_store;
+
+        for shard in self.shards.iter() {
+            match shard.decrypt_any(token) {
+                Ok(decrypted) => return Ok(decrypted),
+                Err(Error::no_corresponding_kid_in_store) => (),
+                Err(other) if error == Error::no_corresponding_kid_in_store => {
+                    error = other;
+                },
+                Err(_) => (),
+            };
+        }
+
+        Err(error)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider, fetching them first if they have never been fetched.
+    ///
+    /// See [`KeyRegistry::decrypt_lazy`].
+    pub async fn decrypt_lazy<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shard(tpa)?.decrypt_lazy(tpa, token).await
+    }
+
+    /// Refresh the cache of the given provider, only locking its shard.
+    ///
+    /// See [`SharedKeyRegistry::refresh`].
+    pub async fn refresh<Q>(&self, tpa: &Q) -> prelude::Result<RefreshStatus>
+    where
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shard(tpa)?.refresh(tpa).await
+    }
+
+    /// Refresh the caches of every provider of every shard concurrently.
+    ///
+    /// See [`SharedKeyRegistry::refresh_all`].
+    pub async fn refresh_all(
+        &self,
+    ) -> BTreeMap<Tpa, prelude::Result<RefreshStatus>>
+    where
+        Tpa: Clone,
+    {
+        let refreshes = self.shards.iter().map(SharedKeyRegistry::refresh_all);
+
+        join_all(refreshes).await.into_iter().flatten().collect()
+    }
+
+    /// Report the [`CacheStatus`] of every provider of every shard.
+    ///
+    /// See [`KeyRegistry::freshness`].
+    pub fn freshness(&self) -> BTreeMap<Tpa, CacheStatus>
+    where
+        Tpa: Clone,
+    {
+        self.shards
+            .iter()
+            .flat_map(SharedKeyRegistry::freshness)
+            .collect()
+    }
+
+    /// Register a cache targeting the given `uri` for the given provider, on
+    /// the shard of the provider.
+    ///
+    /// See [`SharedKeyRegistry::add_remote`].
+    pub async fn add_remote<I>(&self, tpa: Tpa, uri: I) -> prelude::Result<()>
+    where
+        String: From<I>,
+    {
+        self.shard(&tpa)?.add_remote(tpa, uri).await
+    }
+
+    /// Point the given (registered) provider to a cache targeting the given
+    /// `uri`, and fetch its keys without locking its shard.
+    ///
+    /// See [`SharedKeyRegistry::replace_uri`].
+    pub async fn replace_uri<Q, I>(
+        &self,
+        tpa: &Q,
+        uri: I,
+    ) -> prelude::Result<()>
+    where
+        String: From<I>,
+        Tpa: Borrow<Q> + Clone,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shard(tpa)?.replace_uri(tpa, uri).await
+    }
+
+    /// Unregister the given provider, along with its configuration.
+    ///
+    /// See [`KeyRegistry::remove`].
+    pub fn remove<Q>(&self, tpa: &Q) -> bool
+    where
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shard(tpa).is_ok_and(|shard| shard.remove(tpa))
+    }
+
+    /// The shard of the given provider.
+    fn shard<Q>(&self, tpa: &Q) -> prelude::Result<&SharedKeyRegistry<Tpa>>
+    where
+        Tpa: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.shards
+            .get(self.shard_index(tpa))
+            .ok_or(Error::unknown_tpa)
+    }
+}
+
+/// The index (less than `len`) of the shard of the given provider: its pinned
+/// shard, if any, or else its consistent hash.
+fn shard_index<Tpa, Q>(
+    pinned: &BTreeMap<Tpa, usize>,
+    len: usize,
+    tpa: &Q,
+) -> usize
+where
+    Tpa: Borrow<Q> + Ord,
+    Q: Hash + Ord + ?Sized,
+{
+    if let Some(index) = pinned.get(tpa) {
+        return *index;
+    };
+
+    let mut hasher = DefaultHasher::new();
+    tpa.hash(&mut hasher);
+
+    jump_hash(hasher.finish(), len)
+}
+
+/// Map the given key to one of the given number of buckets, as according to
+/// [A Fast, Minimal Memory, Consistent Hash Algorithm](https://arxiv.org/abs/1406.2294).
+///
+/// Growing the number of buckets from `n` to `n + 1` only moves `1 / (n + 1)`
+/// of the keys (all of them into the new bucket).
+fn jump_hash(mut key: u64, buckets: usize) -> usize {
+    let buckets = buckets as u64;
+    let (mut bucket, mut next) = (0, 0);
+
+    while next < buckets {
+        bucket = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+
+        let ratio = (1u64 << 31) as f64 / ((key >> 33) + 1) as f64;
+        next = ((bucket + 1) as f64 * ratio) as u64;
+    }
+
+    bucket as usize
+}
+
+/// Pin the providers which must share a shard with another provider (i.e.,
+/// the ones sharing a `uri`, and the shadows of each other) to the shard of
+/// the least provider among them.
+fn pin<Tpa>(
+    providers: &BTreeMap<Tpa, String>,
+    shadows: &BTreeMap<Tpa, Tpa>,
+    len: usize,
+) -> BTreeMap<Tpa, usize>
+where
+    Tpa: Clone + Hash + Ord,
+{
+    // Every provider points to a lesser provider of its group; the least one
+    // (i.e., the root) points to none.
+    let mut parents = BTreeMap::<&Tpa, &Tpa>::new();
+
+    let mut first_of_uri = BTreeMap::<&String, &Tpa>::new();
+    let mut edges = Vec::new();
+
+    for (tpa, uri) in providers {
+        match first_of_uri.get(uri) {
+            Some(first) => edges.push((*first, tpa)),
+            None => {
+                let _ = first_of_uri.insert(uri, tpa);
+            },
+        };
+    }
+    edges.extend(shadows.iter());
+
+    for (a, b) in edges {
+        let (a, b) = (root(&parents, a), root(&parents, b));
+
+        match a.cmp(b) {
+            Ordering::Less => {
+                let _ = parents.insert(b, a);
+            },
+            Ordering::Greater => {
+                let _ = parents.insert(a, b);
+            },
+            Ordering::Equal => (),
+        };
+    }
+
+    let unpinned = BTreeMap::new();
+
+    parents
+        .keys()
+        .map(|tpa| {
+            let root = root(&parents, tpa);
+            let index = shard_index::<Tpa, Tpa>(&unpinned, len, root);
+
+            ((*tpa).clone(), index)
+        })
+        .collect()
+}
+
+/// The root (i.e., the least provider) of the group of the given provider.
+fn root<'a, Tpa>(
+    parents: &BTreeMap<&'a Tpa, &'a Tpa>,
+    tpa: &'a Tpa,
+) -> &'a Tpa
+where
+    Tpa: Ord,
+{
+    let mut root = tpa;
+    while let Some(parent) = parents.get(root) {
+        root = parent;
+    }
+
+    root
+}
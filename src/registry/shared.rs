@@ -0,0 +1,377 @@
+//! A [`KeyRegistry`] shared between the handlers of a web server.
+//!
+//! Verifying a token only requires a shared reference to a [`KeyRegistry`],
+//! but refreshing its caches (or adding providers at runtime) requires an
+//! exclusive one. Simply wrapping the registry in a lock, which is held while
+//! the keys are being fetched, stalls every verification for the duration of
+//! the fetch.
+//!
+//! A [`SharedKeyRegistry`] is cheap to clone (e.g., into the state of every
+//! handler). Verifications only take a read lock, so any number of them (even
+//! for the same provider) run concurrently. Keys are fetched *without* holding
+//! the lock; the write lock is only taken in order to swap the freshly fetched
+//! keys in (just like with an
+//! [`AutoRefresh`](`crate::key_caches::remote::auto_refresh::AutoRefresh`)).
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_well_known(WellKnownTpa::Google)
+//!     .finish()
+//!     .await?
+//!     .into_shared();
+//!
+//! // In every handler:
+//! let data = registry.decrypt::<Claims, _, _>(&WellKnownTpa::Google, token)?;
+//!
+//! // Meanwhile, in a background task:
+//! registry.refresh_all().await;
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+
+use futures_util::future::join_all;
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::key_caches::remote::auto_refresh::Detached;
+use crate::prelude;
+use crate::registry::freshness::CacheStatus;
+use crate::registry::KeyRegistry;
+use crate::registry::RefreshStatus;
+
+/// A [`KeyRegistry`] which can be shared (and refreshed) by many tasks at
+/// once.
+///
+/// Created by calling [`KeyRegistry::into_shared`]. Clones share the same
+/// registry.
+///
+/// See the [module level documentation](`self`).
+///
+/// ### Note:
+/// Only [`RemoteCache`](`crate::key_caches::remote::RemoteCache`)s can be
+/// refreshed without holding the lock. Providers with a
+/// [`KeyCache`](`crate::key_caches::key_cache::KeyCache`) of their own are
+/// skipped by [`refresh_all`](`SharedKeyRegistry::refresh_all`), and
+/// [`refresh`](`SharedKeyRegistry::refresh`) fails with
+/// [`Error::unknown_tpa`] for them.
+pub struct SharedKeyRegistry<Tpa> {
+    registry: Arc<RwLock<KeyRegistry<Tpa>>>,
+}
+
+impl<Tpa> Clone for SharedKeyRegistry<Tpa> {
+    fn clone(&self) -> Self {
+        Self {
+            registry: Arc::clone(&self.registry),
+        }
+    }
+}
+
+impl<Tpa> From<KeyRegistry<Tpa>> for SharedKeyRegistry<Tpa> {
+    fn from(registry: KeyRegistry<Tpa>) -> Self {
+        Self {
+            registry: Arc::new(RwLock::new(registry)),
+        }
+    }
+}
+
+impl<Tpa> KeyRegistry<Tpa> {
+    /// Wrap this registry into a [`SharedKeyRegistry`].
+    pub fn into_shared(self) -> SharedKeyRegistry<Tpa> {
+        SharedKeyRegistry::from(self)
+    }
+}
+
+impl<Tpa> SharedKeyRegistry<Tpa>
+where
+    Tpa: Ord,
+{
+    /// Lock the registry for reading (e.g., in order to inspect a
+    /// [`RemoteCache`](`crate::key_caches::remote::RemoteCache`)).
+    ///
+    /// ### Warning:
+    /// The guard must not be held across an `.await`, since every refresh
+    /// waits for it in order to swap its keys in.
+    pub fn read(&self) -> RwLockReadGuard<'_, KeyRegistry<Tpa>> {
+        self.registry
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Lock the registry for writing (e.g., in order to change the
+    /// maintenance windows of its providers).
+    ///
+    /// ### Warning:
+    /// The guard must not be held across an `.await`, since every
+    /// verification waits for it.
+    pub fn write(&self) -> RwLockWriteGuard<'_, KeyRegistry<Tpa>> {
+        self.registry
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider.
+    ///
+    /// See [`KeyRegistry::decrypt`].
+    pub fn decrypt<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().decrypt(tpa, token)
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the provider of
+    /// its issuer, returning that provider along with the token.
+    ///
+    /// See [`KeyRegistry::decrypt_by_issuer`].
+    pub fn decrypt_by_issuer<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Clone,
+    {
+        self.read()
+            .decrypt_by_issuer(token)
+            .map(|(tpa, token_data)| (tpa.clone(), token_data))
+    }
+
+    /// Decrypt (and verify) the given token using the keys of whichever
+    /// provider signed it, returning that provider along with the token.
+    ///
+    /// See [`KeyRegistry::decrypt_any`].
+    pub fn decrypt_any<Claims, I>(
+        &self,
+        token: I,
+    ) -> prelude::Result<(Tpa, TokenData<Claims>)>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Clone,
+    {
+        self.read()
+            .decrypt_any(token)
+            .map(|(tpa, token_data)| (tpa.clone(), token_data))
+    }
+
+    /// Decrypt (and verify) the given token using the keys of the given
+    /// provider, fetching them first if they have never been fetched.
+    ///
+    /// See [`KeyRegistry::decrypt_lazy`].
+    pub async fn decrypt_lazy<Claims, I, Q>(
+        &self,
+        tpa: &Q,
+        token: I,
+    ) -> prelude::Result<TokenData<Claims>>
+    where
+        I: AsRef<str>,
+        Claims: for<'a> Deserialize<'a>,
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let fetched = self.read().is_fetched(tpa);
+
+        if !fetched {
+            let _ = self.refresh(tpa).await?;
+        };
+
+        self.decrypt(tpa, token)
+    }
+
+    /// Refresh the cache of the given provider, without holding the lock
+    /// while its keys are being fetched.
+    ///
+    /// See [`KeyRegistry::refresh`].
+    pub async fn refresh<Q>(&self, tpa: &Q) -> prelude::Result<RefreshStatus>
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (uri, detached) = {
+            let registry = self.read();
+            let uri = registry.providers.get(tpa).ok_or(Error::unknown_tpa)?;
+            let remote_cache =
+                registry.remotes.get(uri).ok_or(Error::unknown_tpa)?;
+
+            (uri.clone(), Detached::new(remote_cache))
+        };
+
+        let refreshed = self.refresh_detached(&uri, detached).await;
+        let under_maintenance = self.read().is_under_maintenance(tpa);
+
+        RefreshStatus::of(refreshed, under_maintenance)
+    }
+
+    /// Refresh the caches of every provider concurrently, without holding the
+    /// lock while their keys are being fetched.
+    ///
+    /// See [`KeyRegistry::refresh_all`].
+    pub async fn refresh_all(
+        &self,
+    ) -> BTreeMap<Tpa, prelude::Result<RefreshStatus>>
+    where
+        Tpa: Clone,
+    {
+        let detached = self
+            .read()
+            .remotes
+            .iter()
+            .map(|(uri, remote_cache)| {
+                (uri.clone(), Detached::new(remote_cache))
+            })
+            .collect::<Vec<_>>();
+
+        let refreshes = detached.into_iter().map(|(uri, detached)| async move {
+            let refreshed = self.refresh_detached(&uri, detached).await;
+            (uri, refreshed)
+        });
+        let refreshed = join_all(refreshes).await.into_iter().collect();
+
+        self.read().refresh_statuses(&refreshed)
+    }
+
+    /// Report the [`CacheStatus`] of every provider.
+    ///
+    /// See [`KeyRegistry::freshness`].
+    pub fn freshness(&self) -> BTreeMap<Tpa, CacheStatus>
+    where
+        Tpa: Clone,
+    {
+        self.read()
+            .freshness()
+            .into_iter()
+            .map(|(tpa, status)| (tpa.clone(), status))
+            .collect()
+    }
+
+    /// Register a cache targeting the given `uri` for the given provider,
+    /// without holding the lock while its keys are being fetched.
+    ///
+    /// See [`KeyRegistry::add_remote`].
+    pub async fn add_remote<I>(&self, tpa: Tpa, uri: I) -> prelude::Result<()>
+    where
+        String: From<I>,
+    {
+        let uri = String::from(uri).parse::<http::Uri>()?.to_string();
+
+        self.attach(tpa, uri, false).await
+    }
+
+    /// Point the given (registered) provider to a cache targeting the given
+    /// `uri`, and fetch its keys without holding the lock.
+    ///
+    /// See [`KeyRegistry::replace_uri`].
+    pub async fn replace_uri<Q, I>(
+        &self,
+        tpa: &Q,
+        uri: I,
+    ) -> prelude::Result<()>
+    where
+        String: From<I>,
+        Tpa: Borrow<Q> + Clone,
+        Q: Ord + ?Sized,
+    {
+        let uri = String::from(uri).parse::<http::Uri>()?.to_string();
+
+        let (tpa, detached) = {
+            let registry = self.read();
+            let tpa = registry.registered(tpa).ok_or(Error::unknown_tpa)?;
+
+            (tpa.clone(), registry.remotes.get(&uri).map(Detached::new))
+        };
+
+        if let Some(detached) = detached {
+            self.refresh_detached(&uri, detached).await?;
+        };
+
+        self.attach(tpa, uri, true).await
+    }
+
+    /// Unregister the given provider, along with its configuration.
+    ///
+    /// See [`KeyRegistry::remove`].
+    pub fn remove<Q>(&self, tpa: &Q) -> bool
+    where
+        Tpa: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.write().remove(tpa)
+    }
+
+    /// Refresh the cache of the given `uri` using the given detached state,
+    /// only taking the write lock in order to swap the keys in.
+    async fn refresh_detached(
+        &self,
+        uri: &str,
+        detached: Detached,
+    ) -> prelude::Result<()> {
+        detached
+            .refresh(|fetched| async move {
+                match self.write().remotes.get_mut(uri) {
+                    Some(remote_cache) => fetched.swap_into(remote_cache),
+
+                    // The cache was dropped while its keys were being fetched.
+                    None => Ok(None),
+                }
+            })
+            .await
+    }
+
+    /// Use the cache of the given `uri` for the given provider, first creating
+    /// (and fetching) it if there is none yet.
+    ///
+    /// If `registered` is set, fails with [`Error::unknown_tpa`] if the
+    /// provider is not (or no longer) registered.
+    async fn attach(
+        &self,
+        tpa: Tpa,
+        uri: String,
+        registered: bool,
+    ) -> prelude::Result<()> {
+        let mut fetched = None;
+
+        // Loops at most twice: once the fetched cache has been inserted, it is
+        // always found.
+        loop {
+            let mut remote_cache = {
+                let mut registry = self.write();
+
+                if registered && registry.registered(&tpa).is_none() {
+                    return Err(Error::unknown_tpa);
+                };
+
+                if let Some(remote_cache) = fetched.take() {
+                    let _ = registry
+                        .remotes
+                        .entry(uri.clone())
+                        .or_insert(remote_cache);
+                };
+
+                if registry.remotes.contains_key(&uri) {
+                    registry.set_uri(tpa, uri);
+                    return Ok(());
+                };
+
+                registry.new_remote(&uri)?
+            };
+
+            remote_cache.refresh().await?;
+            fetched = Some(remote_cache);
+        }
+    }
+}
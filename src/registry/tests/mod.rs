@@ -0,0 +1,1131 @@
+mod observer;
+mod sharded;
+mod shared;
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::EncodingKey;
+use serde_json::json;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::key_caches::local::LocalCache;
+use crate::key_caches::remote::tests::T;
+use crate::key_caches::remote::well_known::WellKnownTpa;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::registry::lifetime::Histogram;
+use crate::registry::lifetime::LifetimeStats;
+use crate::registry::lifetime::TokenLifetimes;
+use crate::registry::maintenance::MaintenanceWindow;
+use crate::registry::shadow::ShadowComparisons;
+use crate::registry::shadow::ShadowOutcome;
+use crate::registry::shadow::ShadowStats;
+use crate::registry::validator::require_claim;
+use crate::registry::validator::ValidationError;
+use crate::registry::KeyRegistry;
+use crate::registry::RefreshStatus;
+use crate::testing::MockIdp;
+use crate::testing::KEY_PAIRS;
+use crate::testing::MOCK_JWK_URI;
+use crate::time::ManualClock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Tpa {
+    Mock,
+    Next,
+    Unregistered,
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+async fn registry(
+    idp: &Arc<MockIdp>,
+    window: Option<MaintenanceWindow>,
+) -> KeyRegistry<Tpa> {
+    let builder = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap());
+
+    match window {
+        Some(window) => builder.maintenance_window(Tpa::Mock, window),
+        None => builder,
+    }
+    .finish()
+    .await
+    .unwrap()
+}
+
+fn expire(remote_cache: &mut RemoteCache) {
+    *remote_cache.expiry_time_mut() = Some(now() - 1);
+}
+
+#[tokio::test]
+/// Tokens should be routed to the cache of the given provider.
+async fn test_decrypt() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = registry(&idp, None).await;
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, &token).unwrap();
+
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Unregistered, token.as_str())
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+
+    // Any string type can be given, whether borrowed or owned.
+    let boxed = token.clone().into_boxed_str();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, boxed).unwrap();
+    registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, Cow::Borrowed(token.as_str()))
+        .unwrap();
+    registry.decrypt_any::<Value, _>(&token).unwrap();
+    registry.decrypt_any::<Value, _>(token).unwrap();
+}
+
+#[tokio::test]
+/// Well-known providers should be usable as the `Tpa` directly (e.g., when
+/// parsed from a request).
+async fn test_well_known_tpa() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(WellKnownTpa::Google, idp.remote_cache().unwrap())
+        .finish()
+        .await
+        .unwrap();
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    let tpa = "Google".parse::<WellKnownTpa>().unwrap();
+    registry.decrypt::<Value, _, _>(&tpa, token.clone()).unwrap();
+
+    let err = registry
+        .decrypt::<Value, _, _>(&WellKnownTpa::Apple, token)
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+}
+
+#[tokio::test]
+/// Tokens should only be accepted if they carry an expected issuer and
+/// audience.
+async fn test_expected_claims() {
+    const AUDIENCE: &str = "1234.apps.googleusercontent.com";
+
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(WellKnownTpa::Google, idp.remote_cache().unwrap())
+        .expected_claims(
+            WellKnownTpa::Google,
+            WellKnownTpa::Google.expected_claims(),
+        )
+        .audience(WellKnownTpa::Google, AUDIENCE)
+        .finish()
+        .await
+        .unwrap();
+
+    let accepted = [
+        json!({
+            "iss": "https://accounts.google.com",
+            "aud": AUDIENCE,
+            "exp": 20_000_000_000u64,
+        }),
+        json!({
+            "iss": "accounts.google.com",
+            "aud": ["another-client", AUDIENCE],
+            "exp": 20_000_000_000u64,
+        }),
+    ];
+    for claims in accepted {
+        let data = registry
+            .decrypt::<Value, _, _>(
+                &WellKnownTpa::Google,
+                idp.mint(&claims).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(data.claims, claims);
+    }
+
+    let rejected = [
+        json!({ "aud": AUDIENCE, "exp": 20_000_000_000u64 }),
+        json!({
+            "iss": "https://evil.example.com",
+            "aud": AUDIENCE,
+            "exp": 20_000_000_000u64,
+        }),
+        json!({ "iss": "accounts.google.com", "exp": 20_000_000_000u64 }),
+        json!({
+            "iss": "accounts.google.com",
+            "aud": ["another-client"],
+            "exp": 20_000_000_000u64,
+        }),
+    ];
+    for claims in rejected {
+        let err = registry
+            .decrypt::<Value, _, _>(
+                &WellKnownTpa::Google,
+                idp.mint(&claims).unwrap(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::unable_to_verify_token(_)));
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// Registering well-known providers at once should also expect their issuers.
+fn test_with_well_known() {
+    let tpas = [WellKnownTpa::Google, WellKnownTpa::Apple];
+    let mut registry = KeyRegistry::with_well_known(&tpas)
+        .audience(WellKnownTpa::Apple, "com.example.app")
+        .build_lazy()
+        .unwrap();
+
+    assert!(!registry.is_fetched(&WellKnownTpa::Google));
+    assert!(!registry.is_fetched(&WellKnownTpa::Apple));
+    assert!(registry.is_fetched(&WellKnownTpa::Facebook));
+
+    let expected_claims = &registry.expected_claims;
+    assert_eq!(
+        expected_claims[&WellKnownTpa::Google],
+        WellKnownTpa::Google.expected_claims(),
+    );
+    assert_eq!(
+        expected_claims[&WellKnownTpa::Apple],
+        WellKnownTpa::Apple
+            .expected_claims()
+            .audience("com.example.app"),
+    );
+
+    assert!(registry.remove(&WellKnownTpa::Google));
+    assert!(!registry.expected_claims.contains_key(&WellKnownTpa::Google));
+}
+
+#[tokio::test]
+/// Tokens should be decrypted into the claims of their provider, and then
+/// normalized.
+async fn test_decrypt_normalized() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(WellKnownTpa::Microsoft, idp.remote_cache().unwrap())
+        .finish()
+        .await
+        .unwrap();
+
+    let token = idp
+        .mint(&json!({
+            "iss": "https://login.microsoftonline.com/9188040d/v2.0",
+            "aud": "6cb04018-a3f5-46a7-b995-940c78f5aef3",
+            "sub": "AAAAAAAAAAAAAAAAAAAAAIkzqFVrSaSaFHy782bbtaQ",
+            "iat": now(),
+            "exp": 20_000_000_000u64,
+            "tid": "9188040d",
+            "name": "Abe Lincoln",
+            "preferred_username": "AbeLi@microsoft.com",
+        }))
+        .unwrap();
+    let data = registry
+        .decrypt_normalized(&WellKnownTpa::Microsoft, token)
+        .unwrap();
+
+    assert_eq!(data.claims.provider, "microsoft");
+    assert_eq!(data.claims.name.as_deref(), Some("Abe Lincoln"));
+    assert_eq!(data.claims.email, None);
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    let err = registry
+        .decrypt_normalized(&WellKnownTpa::Microsoft, token)
+        .unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+}
+
+#[tokio::test]
+/// Only the tokens which carry the expected `nonce` should be accepted.
+async fn test_decrypt_with_nonce() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = registry(&idp, None).await;
+
+    let token = idp
+        .mint(&json!({ "exp": 20_000_000_000u64, "nonce": "abc" }))
+        .unwrap();
+    let data = registry
+        .decrypt_with_nonce::<Value, _, _>(&Tpa::Mock, token.as_str(), "abc")
+        .unwrap();
+    assert_eq!(data.claims["nonce"], "abc");
+
+    let err = registry
+        .decrypt_with_nonce::<Value, _, _>(&Tpa::Mock, token, "xyz")
+        .unwrap_err();
+    assert_eq!(err, Error::nonce_mismatch);
+}
+
+#[tokio::test]
+/// Every claims validator of a provider should run after the signature was
+/// verified, in order, and reject the tokens that it fails.
+async fn test_claims_validator() {
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .claims_validator(Tpa::Mock, require_claim("hd", "my-corp.com"))
+        .claims_validator(Tpa::Mock, |claims: &Value| {
+            match claims.get("email_verified") {
+                Some(Value::Bool(true)) => Ok(()),
+                _ => Err(ValidationError::new("The email is not verified.")),
+            }
+        })
+        .finish()
+        .await
+        .unwrap();
+
+    let claims = json!({
+        "exp": 20_000_000_000u64,
+        "hd": "my-corp.com",
+        "email_verified": true,
+    });
+    let token = idp.mint(&claims).unwrap();
+    let data = registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+    assert_eq!(data.claims, claims);
+
+    let token = idp
+        .mint(&json!({ "exp": 20_000_000_000u64, "hd": "other.com" }))
+        .unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token)
+        .unwrap_err();
+    assert_eq!(err, Error::claims_rejected {
+        message: "The `hd` claim has an unexpected value.".into(),
+    });
+
+    let token = idp
+        .mint(&json!({ "exp": 20_000_000_000u64, "hd": "my-corp.com" }))
+        .unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token.as_str())
+        .unwrap_err();
+    assert_eq!(err, Error::claims_rejected {
+        message: "The email is not verified.".into(),
+    });
+
+    // Removing the provider should drop its validators as well.
+    assert!(registry.remove(&Tpa::Mock));
+    assert!(registry.claims_validators.is_empty());
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[test]
+/// Every `Firebase` project should share the keys, but only accept the tokens
+/// which were issued by, and for, itself.
+fn test_add_firebase() {
+    use crate::key_caches::remote::firebase::FIREBASE_JWK_URI;
+    use crate::registry::expected::ExpectedClaims;
+
+    let registry = KeyRegistry::builder()
+        .add_firebase(Tpa::Mock, "project-a")
+        .add_firebase(Tpa::Next, "project-b")
+        .build_lazy()
+        .unwrap();
+
+    assert_eq!(registry.remotes.len(), 1);
+    assert_eq!(registry.providers[&Tpa::Mock], FIREBASE_JWK_URI);
+    assert_eq!(
+        registry.issuers["https://securetoken.google.com/project-b"],
+        Tpa::Next,
+    );
+    assert_eq!(
+        registry.expected_claims[&Tpa::Mock],
+        ExpectedClaims::default()
+            .issuer("https://securetoken.google.com/project-a")
+            .audience("project-a"),
+    );
+}
+
+#[tokio::test]
+/// Outside of a maintenance window, refresh failures should be returned and
+/// expired keys should not be used.
+async fn test_outside_maintenance_window() {
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = registry(
+        &idp,
+        Some(MaintenanceWindow {
+            start: now() + 3600,
+            end: now() + 7200,
+        }),
+    )
+    .await;
+
+    idp.set_available(false);
+    expire(registry.remote_mut(&Tpa::Mock).unwrap());
+
+    let err = registry.refresh(&Tpa::Mock).await.unwrap_err();
+    assert!(matches!(err, Error::unable_to_fetch_keys { .. }));
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    let err = registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap_err();
+    assert_eq!(err, Error::stale_cache);
+}
+
+#[tokio::test]
+/// Inside of a maintenance window, refresh failures should be deferred and
+/// the previous keys should keep being used.
+async fn test_inside_maintenance_window() {
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = registry(
+        &idp,
+        Some(MaintenanceWindow {
+            start: now() - 60,
+            end: now() + 3600,
+        }),
+    )
+    .await;
+    assert!(registry.is_under_maintenance(&Tpa::Mock));
+
+    idp.set_available(false);
+    expire(registry.remote_mut(&Tpa::Mock).unwrap());
+
+    let status = registry.refresh(&Tpa::Mock).await.unwrap();
+    assert!(matches!(status, RefreshStatus::Deferred(..)));
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    idp.set_available(true);
+    let status = registry.refresh(&Tpa::Mock).await.unwrap();
+    assert_eq!(status, RefreshStatus::Refreshed);
+}
+
+#[tokio::test]
+/// Maintenance windows (and the lifetimes of tokens) should be checked
+/// against the clock of the registry, which is also given to its caches.
+async fn test_registry_clock() {
+    let idp = Arc::new(MockIdp::new());
+    let clock = Arc::new(ManualClock::new(T));
+    let lifetimes = Arc::new(TokenLifetimes::default());
+
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .maintenance_window(Tpa::Mock, MaintenanceWindow {
+            start: T,
+            end: T + 3600,
+        })
+        .clock(Arc::clone(&clock))
+        .on_token_lifetime({
+            let lifetimes = Arc::clone(&lifetimes);
+            move |tpa, lifetime| lifetimes.record(tpa, lifetime)
+        })
+        .finish()
+        .await
+        .unwrap();
+    assert!(registry.is_under_maintenance(&Tpa::Mock));
+    assert!(registry.remote(&Tpa::Mock).unwrap().clock().is_some());
+
+    let token = idp.mint(&json!({ "exp": T + 600 })).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    let stats = lifetimes.get(&Tpa::Mock).unwrap();
+    assert_eq!(stats.time_to_expiry.sum(), Duration::from_secs(600));
+
+    clock.advance(Duration::from_secs(3601));
+    assert!(!registry.is_under_maintenance(&Tpa::Mock));
+}
+
+#[tokio::test]
+/// Providers should be accessible through any borrowed form of `Tpa`.
+async fn test_borrowed_tpa() {
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(String::from("tenant-1"), idp.remote_cache().unwrap())
+        .finish()
+        .await
+        .unwrap();
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry.decrypt::<Value, _, _>("tenant-1", token).unwrap();
+
+    let status = registry.refresh("tenant-1").await.unwrap();
+    assert_eq!(status, RefreshStatus::Refreshed);
+    assert!(registry.remote("tenant-2").is_none());
+}
+
+#[tokio::test]
+/// Providers sharing the same `uri` should share the same cache, while
+/// keeping their maintenance windows separate.
+async fn test_shared_uri() {
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum AppRegistration {
+        First,
+        Second,
+    }
+
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(AppRegistration::First, idp.remote_cache().unwrap())
+        .add_remote(AppRegistration::Second, MOCK_JWK_URI)
+        .maintenance_window(AppRegistration::Second, MaintenanceWindow {
+            start: now() - 60,
+            end: now() + 3600,
+        })
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(idp.fetch_count(), 1);
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry
+        .decrypt::<Value, _, _>(&AppRegistration::Second, token)
+        .unwrap();
+
+    registry.refresh(&AppRegistration::First).await.unwrap();
+    assert_eq!(idp.fetch_count(), 2);
+
+    assert!(!registry.is_under_maintenance(&AppRegistration::First));
+    assert!(registry.is_under_maintenance(&AppRegistration::Second));
+}
+
+#[tokio::test]
+/// The lifetime of every verified token should be reported to the callback,
+/// and counted under its provider.
+async fn test_token_lifetime() {
+    let idp = Arc::new(MockIdp::new());
+    let lifetimes = Arc::new(TokenLifetimes::default());
+
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .on_token_lifetime({
+            let lifetimes = Arc::clone(&lifetimes);
+            move |tpa, lifetime| lifetimes.record(tpa, lifetime)
+        })
+        .finish()
+        .await
+        .unwrap();
+
+    let now = now();
+    let claims = json!({ "iat": now - 60, "exp": now + 3540 });
+    let token = idp.mint(&claims).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    // Tokens without an `iat` only have a time to expiry.
+    let token = idp.mint(&json!({ "exp": now + 60 })).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    // Rejected tokens are not reported.
+    let claims = json!({ "iat": now - 7200, "exp": now - 3600 });
+    let token = idp.mint(&claims).unwrap();
+    assert!(registry.decrypt::<Value, _, _>(&Tpa::Mock, token).is_err());
+
+    let LifetimeStats {
+        lifetime,
+        time_to_expiry,
+    } = lifetimes.get(&Tpa::Mock).unwrap();
+
+    assert_eq!(lifetime.count(), 1);
+    assert_eq!(lifetime.sum(), Duration::from_secs(3600));
+    assert_eq!(time_to_expiry.count(), 2);
+    assert!(time_to_expiry.sum() <= Duration::from_secs(3600));
+    assert!(time_to_expiry.sum() >= Duration::from_secs(3590));
+    assert!(lifetimes.get(&Tpa::Unregistered).is_none());
+}
+
+#[tokio::test]
+/// Tokens should be routed to the provider of their issuer.
+async fn test_decrypt_by_issuer() {
+    const ISSUER: &str = "https://idp.webcipher.test";
+
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .issuer(Tpa::Mock, ISSUER)
+        .finish()
+        .await
+        .unwrap();
+
+    let claims = json!({ "iss": ISSUER, "exp": 20_000_000_000u64 });
+    let (tpa, data) = registry
+        .decrypt_by_issuer::<Value, _>(idp.mint(&claims).unwrap())
+        .unwrap();
+    assert_eq!(tpa, &Tpa::Mock);
+    assert_eq!(data.claims, claims);
+
+    let unknown = [
+        json!({ "iss": "https://evil.example.com", "exp": 20_000_000_000u64 }),
+        json!({ "exp": 20_000_000_000u64 }),
+    ];
+    for claims in unknown {
+        let err = registry
+            .decrypt_by_issuer::<Value, _>(idp.mint(&claims).unwrap())
+            .unwrap_err();
+        assert_eq!(err, Error::unknown_issuer);
+    }
+
+    // A known issuer does not vouch for a token signed by someone else.
+    let forged = KEY_PAIRS[1].sign(&claims).unwrap();
+    let err = registry.decrypt_by_issuer::<Value, _>(forged).unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    let result = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .issuer(Tpa::Unregistered, ISSUER)
+        .finish()
+        .await;
+    assert_eq!(result.err(), Some(Error::unknown_tpa));
+}
+
+#[tokio::test]
+/// Tokens should be verified by whichever provider holds the key which signed
+/// them.
+async fn test_decrypt_any() {
+    let idp = Arc::new(MockIdp::new());
+    let next = Arc::new(MockIdp::new());
+    let _ = next.rotate();
+
+    let mut next_cache = next.remote_cache().unwrap();
+    *next_cache.uri_mut() =
+        "https://next.webcipher.test/certs".parse().unwrap();
+
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .add_remote_cache(Tpa::Next, next_cache)
+        .finish()
+        .await
+        .unwrap();
+
+    let claims = json!({ "sub": "user", "exp": 20_000_000_000u64 });
+
+    // Signed by the key which both providers publish.
+    let (tpa, data) = registry
+        .decrypt_any::<Value, _>(idp.mint(&claims).unwrap())
+        .unwrap();
+    assert_eq!(tpa, &Tpa::Mock);
+    assert_eq!(data.claims, claims);
+
+    let (tpa, data) = registry
+        .decrypt_any::<Value, _>(next.mint(&claims).unwrap())
+        .unwrap();
+    assert_eq!(tpa, &Tpa::Next);
+    assert_eq!(data.claims, claims);
+
+    let err = registry
+        .decrypt_any::<Value, _>(KEY_PAIRS[2].sign(&claims).unwrap())
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    // The error of the provider holding the key is kept.
+    let expired = json!({ "sub": "user", "exp": 1 });
+    let err = registry
+        .decrypt_any::<Value, _>(next.mint(&expired).unwrap())
+        .unwrap_err();
+    assert!(matches!(err, Error::unable_to_verify_token(_)));
+}
+
+#[tokio::test]
+/// Providers whose keys cannot be fetched should be reported, without failing
+/// the build (unless it is not lossy).
+async fn test_finish_lossy() {
+    let idp = Arc::new(MockIdp::new());
+    let down = Arc::new(MockIdp::new());
+    down.set_available(false);
+
+    let builder = || {
+        let mut down_cache = down.remote_cache().unwrap();
+        *down_cache.uri_mut() =
+            "https://down.webcipher.test/certs".parse().unwrap();
+
+        KeyRegistry::builder()
+            .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+            .add_remote_cache(Tpa::Next, down_cache)
+    };
+
+    let result = builder().finish().await;
+    assert!(matches!(result, Err(Error::unable_to_fetch_keys { .. })));
+
+    let (mut registry, report) = builder().finish_lossy().await.unwrap();
+    assert_eq!(report.failures.keys().collect::<Vec<_>>(), [&Tpa::Next]);
+    assert!(matches!(
+        report.failures[&Tpa::Next],
+        Error::unable_to_fetch_keys { .. },
+    ));
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token.clone())
+        .unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Next, token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::stale_cache);
+
+    // The provider recovers once it can be refreshed.
+    down.set_available(true);
+    registry.refresh(&Tpa::Next).await.unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Next, token).unwrap();
+
+    let (_, report) = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .finish_lossy()
+        .await
+        .unwrap();
+    assert!(report.is_empty());
+}
+
+#[tokio::test]
+/// Lazily built registries should only fetch the keys of a provider once its
+/// first token is decrypted.
+async fn test_build_lazy() {
+    let idp = Arc::new(MockIdp::new());
+    let next = Arc::new(MockIdp::new());
+    next.rotate();
+
+    let mut next_cache = next.remote_cache().unwrap();
+    *next_cache.uri_mut() =
+        "https://next.webcipher.test/certs".parse().unwrap();
+
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .add_remote_cache(Tpa::Next, next_cache)
+        .build_lazy()
+        .unwrap();
+    assert_eq!(idp.fetch_count(), 0);
+    assert!(!registry.is_fetched(&Tpa::Mock));
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::stale_cache);
+
+    for _ in 0..2 {
+        registry
+            .decrypt_lazy::<Value, _, _>(&Tpa::Mock, token.clone())
+            .await
+            .unwrap();
+    }
+    assert_eq!(idp.fetch_count(), 1);
+    assert!(registry.is_fetched(&Tpa::Mock));
+    assert!(!registry.is_fetched(&Tpa::Next));
+    assert_eq!(next.fetch_count(), 0);
+
+    // Failed fetches are retried on the next token.
+    next.set_available(false);
+    let token = next.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    let err = registry
+        .decrypt_lazy::<Value, _, _>(&Tpa::Next, token.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::unable_to_fetch_keys { .. }));
+
+    next.set_available(true);
+    registry
+        .decrypt_lazy::<Value, _, _>(&Tpa::Next, token.clone())
+        .await
+        .unwrap();
+
+    let err = registry
+        .decrypt_lazy::<Value, _, _>(&Tpa::Unregistered, token)
+        .await
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+
+    let result = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .shadow(Tpa::Mock, Tpa::Next)
+        .build_lazy();
+    assert!(matches!(result, Err(Error::unknown_tpa)));
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+#[tokio::test]
+/// Providers should be addable, re-pointable, and removable after
+/// construction, sharing caches by `uri` just like the builder.
+async fn test_runtime_providers() {
+    use crate::key_caches::remote::jwks::KeySet;
+
+    let idp = Arc::new(MockIdp::new());
+    let mut registry = registry(&idp, None).await;
+
+    let path = std::env::temp_dir()
+        .join(format!("registry-{}.json", uuid::Uuid::new_v4()));
+    let keys = vec![KEY_PAIRS[1].key()];
+    std::fs::write(&path, serde_json::to_vec(&KeySet { keys }).unwrap())
+        .unwrap();
+    let uri = format!("file://localhost{}", path.display());
+
+    let claims = json!({ "exp": 20_000_000_000u64 });
+    let token = KEY_PAIRS[1].sign(&claims).unwrap();
+
+    registry.add_remote(Tpa::Next, uri.clone()).await.unwrap();
+    registry
+        .decrypt::<Value, _, _>(&Tpa::Next, token.clone())
+        .unwrap();
+
+    // A failed fetch leaves the registry unchanged.
+    let missing = format!("{}.missing", uri);
+    let err = registry
+        .add_remote(Tpa::Unregistered, missing.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::unable_to_fetch_keys { .. }));
+    assert!(registry.remote(&Tpa::Unregistered).is_none());
+
+    let err = registry.replace_uri(&Tpa::Mock, missing).await.unwrap_err();
+    assert!(matches!(err, Error::unable_to_fetch_keys { .. }));
+    assert_eq!(registry.remote(&Tpa::Mock).unwrap().uri(), MOCK_JWK_URI);
+
+    // Re-pointing a provider drops its previous cache, once it is unused.
+    registry.replace_uri(&Tpa::Mock, uri.clone()).await.unwrap();
+    registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token.clone())
+        .unwrap();
+    assert_eq!(registry.remotes.len(), 1);
+
+    let err = registry
+        .replace_uri(&Tpa::Unregistered, uri)
+        .await
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+
+    // Removing a provider keeps the cache it shares with another one.
+    assert!(registry.remove(&Tpa::Next));
+    assert!(!registry.remove(&Tpa::Next));
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Next, token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    assert!(registry.remove(&Tpa::Mock));
+    assert!(registry.remotes.is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+/// Every cache should be refreshed at once, and reported on per provider.
+async fn test_refresh_all() {
+    let idp = Arc::new(MockIdp::new());
+    let down = Arc::new(MockIdp::new());
+    down.set_available(false);
+
+    let mut down_cache = down.remote_cache().unwrap();
+    *down_cache.uri_mut() =
+        "https://down.webcipher.test/certs".parse().unwrap();
+
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .add_remote_cache(Tpa::Next, down_cache)
+        .build_lazy()
+        .unwrap();
+
+    let freshness = registry.freshness();
+    assert_eq!(freshness.len(), 2);
+    assert!(freshness.values().all(|status| {
+        status.key_count == 0 && !status.fresh && !status.usable
+    }));
+    assert_eq!(freshness[&Tpa::Mock].uri, MOCK_JWK_URI);
+
+    let refreshed = registry.refresh_all().await;
+    assert!(matches!(refreshed[&Tpa::Mock], Ok(RefreshStatus::Refreshed)));
+    assert!(matches!(
+        refreshed[&Tpa::Next],
+        Err(Error::unable_to_fetch_keys { .. }),
+    ));
+
+    let freshness = registry.freshness();
+    let mock = &freshness[&Tpa::Mock];
+    assert_eq!(mock.key_count, 1);
+    assert!(mock.fresh && mock.usable && !mock.under_maintenance);
+    assert!(mock.refreshed_at.is_some_and(|at| at <= now()));
+    assert!(mock.expiry_time.is_some_and(|at| at > now()));
+    assert!(!freshness[&Tpa::Next].usable);
+
+    // Failures during a maintenance window are downgraded.
+    let window = MaintenanceWindow {
+        start: now() - 60,
+        end: now() + 60,
+    };
+    let _ = registry
+        .maintenance_windows_mut()
+        .insert(Tpa::Next, vec![window]);
+
+    let refreshed = registry.refresh_all().await;
+    assert!(matches!(
+        refreshed[&Tpa::Next],
+        Ok(RefreshStatus::Deferred(Error::unable_to_fetch_keys { .. })),
+    ));
+    assert!(registry.freshness()[&Tpa::Next].under_maintenance);
+    assert_eq!(idp.fetch_count(), 2);
+}
+
+#[tokio::test]
+/// First-party caches should be usable alongside remote ones.
+async fn test_key_cache() {
+    let idp = Arc::new(MockIdp::new());
+
+    let mut local_cache = LocalCache::new(Algorithm::HS256);
+    local_cache.add_key(
+        Uuid::new_v4(),
+        EncodingKey::from_secret("first-party".as_ref()),
+        DecodingKey::from_secret("first-party".as_ref()),
+    );
+
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .add_cache(Tpa::Next, local_cache)
+        .shadow(Tpa::Next, Tpa::Mock)
+        .finish()
+        .await
+        .unwrap();
+
+    let claims = json!({ "exp": 20_000_000_000u64 });
+    let token = registry
+        .cache::<LocalCache, _>(&Tpa::Next)
+        .unwrap()
+        .encrypt(&claims)
+        .unwrap();
+    assert!(registry.cache::<RemoteCache, _>(&Tpa::Next).is_none());
+    assert!(registry.cache::<LocalCache, _>(&Tpa::Mock).is_none());
+
+    let data = registry
+        .decrypt::<Value, _, _>(&Tpa::Next, token.clone())
+        .unwrap();
+    assert_eq!(data.claims, claims);
+
+    let (tpa, _) = registry.decrypt_any::<Value, _>(token.clone()).unwrap();
+    assert_eq!(tpa, &Tpa::Next);
+
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::invalid_algorithm);
+
+    let status = registry.refresh(&Tpa::Next).await.unwrap();
+    assert_eq!(status, RefreshStatus::Refreshed);
+    let refreshed = registry.refresh_all().await;
+    assert_eq!(refreshed.len(), 2);
+    assert!(refreshed.values().all(Result::is_ok));
+    assert_eq!(registry.freshness().len(), 1);
+
+    assert!(registry.remove(&Tpa::Next));
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Next, token)
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+}
+
+#[tokio::test]
+/// Tokens should be verified against the shadow provider as well, without
+/// affecting the result of the primary provider.
+async fn test_shadow() {
+    let primary = Arc::new(MockIdp::new());
+    let shadow = Arc::new(MockIdp::new());
+    let _ = shadow.rotate();
+
+    let mut shadow_cache = shadow.remote_cache().unwrap();
+    *shadow_cache.uri_mut() =
+        "https://next.webcipher.test/certs".parse().unwrap();
+
+    let comparisons = Arc::new(ShadowComparisons::default());
+    let mut registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, primary.remote_cache().unwrap())
+        .add_remote_cache(Tpa::Next, shadow_cache)
+        .shadow(Tpa::Mock, Tpa::Next)
+        .on_shadow_comparison({
+            let comparisons = Arc::clone(&comparisons);
+            move |tpa, outcome| comparisons.record(tpa, outcome)
+        })
+        .finish()
+        .await
+        .unwrap();
+
+    let exp = json!({ "exp": 20_000_000_000u64 });
+
+    // Signed by the key which both providers publish.
+    let token = primary.mint(&exp).unwrap();
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token.clone()).unwrap();
+
+    // Signed by the key which only the shadow provider publishes.
+    let rotated = shadow.mint(&exp).unwrap();
+    assert!(registry.decrypt::<Value, _, _>(&Tpa::Mock, rotated).is_err());
+
+    let expired = primary.mint(&json!({ "exp": now() - 3600 })).unwrap();
+    assert!(registry.decrypt::<Value, _, _>(&Tpa::Mock, expired).is_err());
+
+    // Failures of the shadow provider never fail the primary provider.
+    expire(registry.remote_mut(&Tpa::Next).unwrap());
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+
+    let stats = comparisons.get(&Tpa::Mock).unwrap();
+    assert_eq!(
+        stats,
+        ShadowStats {
+            both_accepted: 1,
+            both_rejected: 1,
+            primary_only: 1,
+            shadow_only: 1,
+        },
+    );
+    assert_eq!(stats.total(), 4);
+    assert_eq!(stats.agreement_rate(), Some(0.5));
+    assert!(comparisons.get(&Tpa::Next).is_none());
+
+    let outcome = ShadowOutcome::new(true, Err(Error::stale_cache));
+    assert_eq!(outcome, ShadowOutcome::PrimaryOnly(Error::stale_cache));
+    assert!(!outcome.is_agreement());
+
+    // Both providers of a shadow must be registered.
+    let result = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, primary.remote_cache().unwrap())
+        .shadow(Tpa::Mock, Tpa::Unregistered)
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::unknown_tpa)));
+}
+
+#[test]
+/// Buckets should be cumulative, and durations exceeding every bound should
+/// only be counted in total.
+fn test_histogram() {
+    let mut histogram = Histogram::new(vec![3600, 60, 300, 60]);
+    assert_eq!(histogram.mean(), None);
+
+    for seconds in [30, 60, 61, 1000, 86400] {
+        histogram.record(Duration::from_secs(seconds));
+    }
+
+    let buckets = histogram.buckets().collect::<Vec<_>>();
+    assert_eq!(buckets, [(60, 2), (300, 3), (3600, 4)]);
+    assert_eq!(histogram.count(), 5);
+    assert_eq!(histogram.sum(), Duration::from_secs(87551));
+    assert_eq!(histogram.mean(), Some(Duration::from_secs(17510)));
+    assert_eq!(Histogram::default().buckets().count(), 9);
+}
+
+#[cfg(feature = "json-schema")]
+#[tokio::test]
+/// Claims should be validated against the schema of their provider, and every
+/// deviation should be reported.
+async fn test_claims_schema() {
+    use crate::registry::schema::ClaimsSchema;
+
+    let schema = ClaimsSchema::new(json!({
+        "type": "object",
+        "required": ["sub", "email"],
+        "properties": {
+            "email": { "type": "string" },
+            "email_verified": { "type": "boolean" }
+        }
+    }))
+    .unwrap();
+
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .claims_schema(Tpa::Mock, schema)
+        .finish()
+        .await
+        .unwrap();
+
+    let token = idp
+        .mint(&json!({
+            "sub": "user",
+            "email": "user@example.com",
+            "exp": 20_000_000_000u64,
+        }))
+        .unwrap();
+    let data = registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+    assert_eq!(data.claims["sub"], "user");
+
+    let token = idp
+        .mint(&json!({
+            "email": 42,
+            "email_verified": "true",
+            "exp": 20_000_000_000u64,
+        }))
+        .unwrap();
+    let error = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, token)
+        .unwrap_err();
+    assert_eq!(error, Error::claims_schema_mismatch {
+        message: "`/sub`: required, but missing. \
+                  `/email`: expected a string, found an integer. \
+                  `/email_verified`: expected a boolean, found a string."
+            .into(),
+    });
+}
+
+#[cfg(feature = "json-schema")]
+#[test]
+/// Nested schemas should be validated, with the location of each deviation.
+fn test_nested_claims_schema() {
+    use crate::registry::schema::ClaimsSchema;
+
+    let schema = ClaimsSchema::new(json!({
+        "properties": {
+            "roles": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "enum": ["admin", "user"] }
+            },
+            "org": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": { "id": { "type": "integer", "minimum": 1 } }
+            },
+            "aud": {
+                "anyOf": [
+                    { "type": "string", "minLength": 1 },
+                    { "type": "array", "items": { "type": "string" } }
+                ]
+            }
+        }
+    }))
+    .unwrap();
+
+    let valid = json!({
+        "roles": ["admin"],
+        "org": { "id": 7 },
+        "aud": ["a", "b"],
+    });
+    assert_eq!(schema.validate(&valid), Ok(()));
+
+    let invalid = json!({
+        "roles": ["root"],
+        "org": { "id": 0, "name": "acme" },
+        "aud": "",
+    });
+    assert_eq!(schema.validate(&invalid), Err(Error::claims_schema_mismatch {
+        message: "`/aud`: does not match any of the allowed schemas. \
+                  `/org/id`: expected at least 1. \
+                  `/org/name`: not allowed. \
+                  `/roles/0`: expected one of [\"admin\",\"user\"]."
+            .into(),
+    }));
+}
+
+#[cfg(feature = "json-schema")]
+#[test]
+/// Malformed schemas, and unsupported keywords, should be rejected upfront.
+fn test_invalid_claims_schema() {
+    use crate::registry::schema::ClaimsSchema;
+
+    let schemas = [
+        json!([]),
+        json!({ "type": "text" }),
+        json!({ "required": "sub" }),
+        json!({ "properties": { "email": { "pattern": "@" } } }),
+        json!({ "minLength": -1 }),
+    ];
+
+    for schema in schemas {
+        assert!(matches!(
+            ClaimsSchema::new(schema),
+            Err(Error::invalid_schema { .. }),
+        ));
+    }
+
+    let schema = json!({ "$schema": "https://json-schema.org/draft/2020-12" });
+    assert!(ClaimsSchema::new(schema).is_ok());
+}
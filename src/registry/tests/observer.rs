@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::observer::CacheObserver;
+use crate::prelude::Error;
+use crate::registry::tests::now;
+use crate::registry::tests::Tpa;
+use crate::registry::validator::require_claim;
+use crate::registry::KeyRegistry;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+
+/// A [`CacheObserver`] which records the `uri`s of refreshes, and the reasons
+/// of rejected tokens.
+#[derive(Default)]
+struct RecordingObserver {
+    refreshed: Mutex<Vec<String>>,
+    rejected: Mutex<Vec<Error>>,
+}
+
+impl CacheObserver for RecordingObserver {
+    fn on_refresh_success(&self, uri: &http::Uri) {
+        self.refreshed.lock().unwrap().push(uri.to_string());
+    }
+
+    fn on_decrypt_failure(&self, _: &http::Uri, reason: &Error) {
+        self.rejected.lock().unwrap().push(reason.clone());
+    }
+}
+
+#[tokio::test]
+/// The observer of a registry should be given to its caches, and be told
+/// about the tokens which the registry rejects itself.
+async fn test_observer() {
+    let idp = Arc::new(MockIdp::new());
+    let observer = Arc::new(RecordingObserver::default());
+
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .claims_validator(Tpa::Mock, require_claim("hd", "example.com"))
+        .observer(Arc::clone(&observer))
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(*observer.refreshed.lock().unwrap(), [MOCK_JWK_URI]);
+
+    let token = idp.mint(&json!({ "exp": now() + 3600 })).unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, &token)
+        .unwrap_err();
+    assert!(matches!(err, Error::claims_rejected { .. }));
+
+    let token = idp.mint(&json!({ "exp": now() - 3600 })).unwrap();
+    registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, &token)
+        .unwrap_err();
+
+    let rejected = observer.rejected.lock().unwrap();
+    assert_eq!(rejected.len(), 2);
+    assert_eq!(rejected[0], err);
+    assert!(matches!(rejected[1], Error::unable_to_verify_token(_)));
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude::Error;
+use crate::registry::builder::KeyRegistryBuilder;
+use crate::registry::KeyRegistry;
+use crate::registry::RefreshStatus;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+
+/// A builder with the given number of tenants, each with a cache (and an
+/// issuer) of its own.
+fn tenants(idp: &Arc<MockIdp>, count: usize) -> KeyRegistryBuilder<String> {
+    (0..count).fold(KeyRegistry::builder(), |builder, tenant| {
+        let remote_cache =
+            RemoteCache::builder(format!("https://{}.test/certs", tenant))
+                .fetcher(Arc::clone(idp))
+                .build()
+                .unwrap();
+
+        builder
+            .add_remote_cache(tenant.to_string(), remote_cache)
+            .issuer(tenant.to_string(), format!("https://{}.test", tenant))
+    })
+}
+
+#[tokio::test]
+/// Every provider (and issuer) should be routed to its own shard.
+async fn test_sharded_routing() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = tenants(&idp, 32).finish().await.unwrap().into_sharded(4);
+    assert_eq!(registry.shards().len(), 4);
+
+    for shard in registry.shards() {
+        assert!(!shard.read().providers.is_empty());
+    }
+
+    for tenant in (0..32).map(|tenant| tenant.to_string()) {
+        let index = registry.shard_index(tenant.as_str());
+        let shard = &registry.shards()[index];
+        assert!(shard.read().remote(tenant.as_str()).is_some());
+
+        let token = idp
+            .mint(&json!({
+                "iss": format!("https://{}.test", tenant),
+                "exp": 20_000_000_000u64,
+            }))
+            .unwrap();
+        registry
+            .decrypt::<Value, _, _>(tenant.as_str(), &token)
+            .unwrap();
+
+        let (tpa, _) = registry.decrypt_by_issuer::<Value, _>(&token).unwrap();
+        assert_eq!(tpa, tenant);
+    }
+
+    let token = idp
+        .mint(&json!({ "iss": "https://other.test", "exp": 20_000_000_000u64 }))
+        .unwrap();
+    let err = registry.decrypt_by_issuer::<Value, _>(&token).unwrap_err();
+    assert_eq!(err, Error::unknown_issuer);
+    registry.decrypt_any::<Value, _>(&token).unwrap();
+
+    let statuses = registry.refresh_all().await;
+    assert_eq!(statuses.len(), 32);
+    assert!(statuses.values().all(|status| {
+        matches!(status, Ok(RefreshStatus::Refreshed))
+    }));
+    assert_eq!(registry.freshness().len(), 32);
+
+    assert!(registry.remove("0"));
+    assert!(!registry.remove("0"));
+    let err = registry.decrypt::<Value, _, _>("0", &token).unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+}
+
+#[tokio::test]
+/// Growing the number of shards should only move providers into the new
+/// shard.
+async fn test_consistent_hashing() {
+    let idp = Arc::new(MockIdp::new());
+    let eight = tenants(&idp, 64).build_lazy().unwrap().into_sharded(8);
+    let nine = tenants(&idp, 64).build_lazy().unwrap().into_sharded(9);
+
+    let moved = (0..64)
+        .map(|tenant| tenant.to_string())
+        .filter(|tenant| {
+            let (before, after) = (
+                eight.shard_index(tenant.as_str()),
+                nine.shard_index(tenant.as_str()),
+            );
+            assert!(before == after || after == 8);
+
+            before != after
+        })
+        .count();
+    assert!(moved < 64 / 2);
+
+    let single = tenants(&idp, 4).build_lazy().unwrap().into_sharded(0);
+    assert_eq!(single.shards().len(), 1);
+    assert_eq!(single.shard_index("3"), 0);
+}
+
+#[tokio::test]
+/// Providers sharing a cache (or shadowing each other) should be kept on the
+/// same shard.
+async fn test_pinned_providers() {
+    let idp = Arc::new(MockIdp::new());
+
+    for shards in 1..16 {
+        let registry = tenants(&idp, 8)
+            .add_remote_cache("shared-0".into(), idp.remote_cache().unwrap())
+            .add_remote("shared-1".into(), MOCK_JWK_URI)
+            .shadow("shared-1".into(), "7".into())
+            .build_lazy()
+            .unwrap()
+            .into_sharded(shards);
+
+        let index = registry.shard_index("shared-0");
+        assert_eq!(registry.shard_index("shared-1"), index);
+        assert_eq!(registry.shard_index("7"), index);
+
+        let shard = registry.shards()[index].read();
+        assert!(shard.remotes.contains_key(MOCK_JWK_URI));
+        assert!(shard.remotes.contains_key("https://7.test/certs"));
+        assert!(shard.shadows.contains_key("shared-1"));
+    }
+
+    let fetches = idp.fetch_count();
+    let registry = KeyRegistry::builder()
+        .add_remote_cache("shared-0".to_string(), idp.remote_cache().unwrap())
+        .add_remote("shared-1".into(), MOCK_JWK_URI)
+        .finish()
+        .await
+        .unwrap()
+        .into_sharded(8);
+    assert_eq!(idp.fetch_count(), fetches + 1);
+
+    let token = idp.mint(&json!({ "exp": 20_000_000_000u64 })).unwrap();
+    registry.decrypt::<Value, _, _>("shared-1", token).unwrap();
+}
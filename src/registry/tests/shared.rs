@@ -0,0 +1,142 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use super::Tpa;
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+use crate::prelude::Error;
+use crate::registry::KeyRegistry;
+use crate::registry::RefreshStatus;
+use crate::testing::MockIdp;
+use crate::testing::MOCK_JWK_URI;
+
+/// Fetches from a [`MockIdp`], but only once a permit has been added to its
+/// gate (one permit per fetch).
+struct GatedFetcher {
+    idp: Arc<MockIdp>,
+    gate: Semaphore,
+    waiting: AtomicBool,
+}
+
+#[async_trait]
+impl JwksFetcher for GatedFetcher {
+    async fn fetch(&self, uri: &http::Uri) -> prelude::Result<JwksResponse> {
+        self.waiting.store(true, Ordering::SeqCst);
+        self.gate.acquire().await.unwrap().forget();
+        self.waiting.store(false, Ordering::SeqCst);
+
+        self.idp.fetch(uri).await
+    }
+}
+
+#[tokio::test]
+/// Tokens should be verified while the keys are being fetched, instead of
+/// waiting for the fetch.
+async fn test_decrypt_during_refresh() {
+    let idp = Arc::new(MockIdp::new());
+    let fetcher = Arc::new(GatedFetcher {
+        idp: Arc::clone(&idp),
+        gate: Semaphore::new(1),
+        waiting: AtomicBool::new(false),
+    });
+
+    let remote_cache = RemoteCache::builder(MOCK_JWK_URI)
+        .fetcher(Arc::clone(&fetcher))
+        .build()
+        .unwrap();
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, remote_cache)
+        .build_lazy()
+        .unwrap()
+        .into_shared();
+
+    let claims = json!({ "exp": 20_000_000_000u64 });
+    let token = idp.mint(&claims).unwrap();
+    registry
+        .decrypt_lazy::<Value, _, _>(&Tpa::Mock, token.clone())
+        .await
+        .unwrap();
+
+    idp.rotate();
+    let refresh = tokio::spawn({
+        let registry = registry.clone();
+        async move { registry.refresh(&Tpa::Mock).await }
+    });
+
+    while !fetcher.waiting.load(Ordering::SeqCst) {
+        tokio::task::yield_now().await;
+    }
+
+    // The fetch is pending, but the lock is not held.
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, token).unwrap();
+    let rotated = idp.mint(&claims).unwrap();
+    let err = registry
+        .decrypt::<Value, _, _>(&Tpa::Mock, rotated.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    fetcher.gate.add_permits(1);
+    let status = refresh.await.unwrap().unwrap();
+    assert_eq!(status, RefreshStatus::Refreshed);
+    registry.decrypt::<Value, _, _>(&Tpa::Mock, rotated).unwrap();
+}
+
+#[tokio::test]
+/// Clones should share the same registry, and be usable from many tasks.
+async fn test_shared_across_tasks() {
+    let idp = Arc::new(MockIdp::new());
+    let registry = KeyRegistry::builder()
+        .add_remote_cache(Tpa::Mock, idp.remote_cache().unwrap())
+        .issuer(Tpa::Mock, "https://mock.webcipher.test")
+        .finish()
+        .await
+        .unwrap()
+        .into_shared();
+
+    let token = idp
+        .mint(&json!({
+            "iss": "https://mock.webcipher.test",
+            "exp": 20_000_000_000u64,
+        }))
+        .unwrap();
+
+    let tasks = (0..8).map(|_| {
+        let registry = registry.clone();
+        let token = token.clone();
+
+        tokio::spawn(async move {
+            let (tpa, _) =
+                registry.decrypt_by_issuer::<Value, _>(token.clone())?;
+            assert_eq!(tpa, Tpa::Mock);
+
+            let (tpa, _) = registry.decrypt_any::<Value, _>(token)?;
+            assert_eq!(tpa, Tpa::Mock);
+
+            registry.refresh_all().await.remove(&Tpa::Mock).unwrap()
+        })
+    });
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap(), RefreshStatus::Refreshed);
+    }
+    assert_eq!(idp.fetch_count(), 9);
+    assert_eq!(registry.freshness()[&Tpa::Mock].key_count, 1);
+
+    assert!(registry.remove(&Tpa::Mock));
+    assert!(registry.read().remote(&Tpa::Mock).is_none());
+    let err = registry.refresh(&Tpa::Mock).await.unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+    let err = registry
+        .replace_uri(&Tpa::Mock, MOCK_JWK_URI)
+        .await
+        .unwrap_err();
+    assert_eq!(err, Error::unknown_tpa);
+}
@@ -0,0 +1,96 @@
+//! Custom checks on the claims of verified tokens.
+//!
+//! Besides its [`ExpectedClaims`], a provider may have any number of claims
+//! validators: closures which are given the (verified) claims of every token
+//! of the provider, and which reject the token by returning a
+//! [`ValidationError`]. They run right after the signature (and the expected
+//! claims) of the token were verified, before its claims are deserialized:
+//!
+//! ```ignore
+//! let registry = KeyRegistry::builder()
+//!     .add_remote(Tpa::Google, GOOGLE_JWK_URI)
+//!     .claims_validator(Tpa::Google, require_claim("hd", "my-corp.com"))
+//!     .claims_validator(Tpa::Google, |claims: &Value| {
+//!         match claims.get("email_verified") {
+//!             Some(Value::Bool(true)) => Ok(()),
+//!             _ => Err(ValidationError::new("The email is not verified.")),
+//!         }
+//!     })
+//!     .finish()
+//!     .await?;
+//!
+//! // Fails with `Error::claims_rejected { message }`.
+//! let data: TokenData<GoogleClaims> = registry.decrypt(&Tpa::Google, token)?;
+//! ```
+//!
+//! ### Note:
+//! The message of a [`ValidationError`] is passed on as is (e.g., into logs),
+//! so it should name the offending claim, but never its value.
+//!
+//! [`ExpectedClaims`]: `crate::registry::expected::ExpectedClaims`
+
+use std::sync::Arc;
+
+use derive_more::Display;
+use serde_json::Value;
+
+use crate::error::Error;
+
+pub(crate) type ClaimsValidator =
+    Arc<dyn Fn(&Value) -> Result<(), ValidationError> + Send + Sync>;
+
+/// The reason that a claims validator rejected a token.
+///
+/// Converted into [`Error::claims_rejected`].
+/// See the [module level documentation](`self`).
+#[derive(Clone, Hash, Debug, PartialEq, Eq, Display)]
+#[display(fmt = "{}", message)]
+pub struct ValidationError {
+    pub message: String,
+}
+
+impl ValidationError {
+    /// Create a new [`ValidationError`] with the given message.
+    pub fn new<I>(message: I) -> Self
+    where
+        String: From<I>,
+    {
+        Self {
+            message: String::from(message),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        let ValidationError { message } = error;
+        Self::claims_rejected { message }
+    }
+}
+
+/// A claims validator which only accepts tokens whose claim with the given
+/// name is equal to the given value (e.g., `require_claim("hd",
+/// "my-corp.com")`, or `require_claim("email_verified", true)`).
+pub fn require_claim<I, V>(
+    name: I,
+    value: V,
+) -> impl Fn(&Value) -> Result<(), ValidationError> + Send + Sync + 'static
+where
+    String: From<I>,
+    Value: From<V>,
+{
+    let name = String::from(name);
+    let value = Value::from(value);
+
+    move |claims| match claims.get(&name) {
+        Some(claim) if *claim == value => Ok(()),
+        Some(_) => Err(ValidationError {
+            message: format!("The `{}` claim has an unexpected value.", name),
+        }),
+        None => Err(ValidationError {
+            message: format!("The `{}` claim is missing.", name),
+        }),
+    }
+}
@@ -0,0 +1,168 @@
+//! Owning background tasks, so that they can be shut down gracefully.
+//!
+//! Every background task of this crate (e.g., an auto-refresh task, see
+//! [`RemoteCache::spawn_auto_refresh_in`]) can be spawned into a [`TaskSet`]
+//! instead of being detached. On shutdown, the embedding application then
+//! cancels all of them at once, and waits for them to stop:
+//!
+//! ```ignore
+//! let tasks = TaskSet::new();
+//!
+//! let auto_refresh = AutoRefresh::new(RefreshSchedule::TtlBased);
+//! RemoteCache::spawn_auto_refresh_in(&google, auto_refresh.clone(), &tasks);
+//! RemoteCache::spawn_auto_refresh_in(&apple, auto_refresh, &tasks);
+//!
+//! // on shutdown
+//! tasks.shutdown(Duration::from_secs(5)).await;
+//! ```
+//!
+//! ### Note:
+//! A cancelled task stops at its next suspension point; an in-flight refresh
+//! is abandoned *before* its keys are swapped in, so a cache is never left
+//! half-updated. Dropping a [`TaskSet`] aborts all of its tasks immediately.
+//!
+//! [`RemoteCache::spawn_auto_refresh_in`]: `crate::key_caches::remote::RemoteCache::spawn_auto_refresh_in`
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Resolves once the [`TaskSet`] which owns a task has been cancelled (or
+/// dropped).
+pub(crate) struct Cancelled(watch::Receiver<bool>);
+
+impl Cancelled {
+    pub(crate) async fn wait(&mut self) {
+        let Self(receiver) = self;
+
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                return;
+            };
+        }
+    }
+}
+
+/// A set of background tasks, which are cancelled and joined together.
+///
+/// See the [module level documentation](`self`).
+pub struct TaskSet {
+    cancel: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl fmt::Debug for TaskSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskSet")
+            .field("len", &self.len())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl Default for TaskSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskSet {
+    /// Create a new, empty [`TaskSet`].
+    pub fn new() -> Self {
+        let (cancel, _) = watch::channel(false);
+        let tasks = Mutex::new(Vec::new());
+
+        Self { cancel, tasks }
+    }
+
+    fn tasks(&self) -> MutexGuard<'_, Vec<JoinHandle<()>>> {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Spawn the task returned by `task` into this [`TaskSet`].
+    ///
+    /// The task is given a [`Cancelled`] signal, which it is expected to stop
+    /// upon.
+    pub(crate) fn spawn<F, T>(&self, task: F)
+    where
+        F: FnOnce(Cancelled) -> T,
+        T: Future<Output = ()> + Send + 'static,
+    {
+        let cancelled = Cancelled(self.cancel.subscribe());
+        let task = tokio::spawn(task(cancelled));
+
+        let mut tasks = self.tasks();
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(task);
+    }
+
+    /// The number of tasks which are still running.
+    pub fn len(&self) -> usize {
+        self.tasks()
+            .iter()
+            .filter(|task| !task.is_finished())
+            .count()
+    }
+
+    /// Check to see if no tasks are running.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Signal every task (including the ones spawned from now on) to stop.
+    ///
+    /// Does not wait for the tasks to stop; see
+    /// [`shutdown`](`TaskSet::shutdown`) for that.
+    pub fn cancel(&self) {
+        self.cancel.send_replace(true);
+    }
+
+    /// Check to see if this [`TaskSet`] has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancel.borrow()
+    }
+
+    /// Cancel every task, and wait for all of them to stop.
+    ///
+    /// Tasks which are still running once the `timeout` has elapsed are
+    /// aborted. Returns `true` if every task stopped on its own (i.e., none
+    /// had to be aborted, and none panicked).
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.cancel();
+
+        let deadline = Instant::now() + timeout;
+        let tasks = std::mem::take(&mut *self.tasks());
+        let mut graceful = true;
+
+        for mut task in tasks {
+            match tokio::time::timeout_at(deadline, &mut task).await {
+                Ok(result) => graceful &= result.is_ok(),
+                Err(_) => {
+                    task.abort();
+                    graceful = false;
+                },
+            };
+        }
+
+        graceful
+    }
+}
+
+impl Drop for TaskSet {
+    fn drop(&mut self) {
+        for task in self.tasks().iter() {
+            task.abort();
+        }
+    }
+}
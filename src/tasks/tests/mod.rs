@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+
+use crate::key_caches::remote::auto_refresh::AutoRefresh;
+use crate::key_caches::remote::auto_refresh::RefreshSchedule;
+use crate::key_caches::remote::RemoteCache;
+use crate::tasks::TaskSet;
+use crate::testing::MockIdp;
+
+fn auto_refresh() -> AutoRefresh {
+    AutoRefresh::new(RefreshSchedule::Interval(Duration::from_millis(10)))
+}
+
+#[tokio::test]
+/// Shutting down should stop every task gracefully, and no task should keep
+/// refreshing afterwards.
+async fn test_shutdown() {
+    let idp = Arc::new(MockIdp::new());
+    let first = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+    let second = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+
+    let tasks = TaskSet::new();
+    RemoteCache::spawn_auto_refresh_in(&first, auto_refresh(), &tasks);
+    RemoteCache::spawn_auto_refresh_in(&second, auto_refresh(), &tasks);
+    assert_eq!(tasks.len(), 2);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(first.read().await.is_cache_fresh());
+    assert!(second.read().await.is_cache_fresh());
+
+    assert!(tasks.shutdown(Duration::from_secs(1)).await);
+    assert!(tasks.is_cancelled());
+    assert!(tasks.is_empty());
+
+    let fetch_count = idp.fetch_count();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(idp.fetch_count(), fetch_count);
+}
+
+#[tokio::test]
+/// Tasks spawned into a cancelled set should stop right away.
+async fn test_spawn_after_cancel() {
+    let idp = Arc::new(MockIdp::new());
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+
+    let tasks = TaskSet::new();
+    tasks.cancel();
+    RemoteCache::spawn_auto_refresh_in(&remote_cache, auto_refresh(), &tasks);
+
+    assert!(tasks.shutdown(Duration::from_secs(1)).await);
+    assert_eq!(idp.fetch_count(), 0);
+}
+
+#[tokio::test]
+/// Tasks which do not stop in time should be aborted.
+async fn test_shutdown_timeout() {
+    let tasks = TaskSet::new();
+    tasks.spawn(|_| std::future::pending());
+
+    assert!(!tasks.shutdown(Duration::from_millis(10)).await);
+    assert!(tasks.is_empty());
+}
+
+#[tokio::test]
+/// Dropping the set should abort every task.
+async fn test_drop() {
+    let idp = Arc::new(MockIdp::new());
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+
+    let tasks = TaskSet::new();
+    RemoteCache::spawn_auto_refresh_in(&remote_cache, auto_refresh(), &tasks);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    drop(tasks);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let fetch_count = idp.fetch_count();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(idp.fetch_count(), fetch_count);
+}
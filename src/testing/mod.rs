@@ -0,0 +1,306 @@
+//! Test-support utilities for applications built on top of this crate.
+//!
+//! Only available when the `testing` feature is enabled.
+//!
+//! The main utility is the [`MockIdp`], an in-process mock identity provider.
+//! It mints `RS256` tokens, publishes the matching `JWK`s (through the
+//! [`JwksFetcher`] trait, so no network requests are ever performed), and can
+//! rotate its signing key on demand.
+//!
+//! ```ignore
+//! let idp = Arc::new(MockIdp::new());
+//! let mut remote_cache = idp.remote_cache()?;
+//! remote_cache.refresh().await?;
+//!
+//! let token = idp.mint(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))?;
+//! let _ = remote_cache.decrypt_unchecked::<Value, _>(token)?;
+//!
+//! // Tokens minted after a rotation are signed by a key the cache has not
+//! // seen yet...
+//! idp.rotate();
+//! let token = idp.mint(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))?;
+//!
+//! // ...until the cache is refreshed.
+//! remote_cache.refresh().await?;
+//! let _ = remote_cache.decrypt_unchecked::<Value, _>(token)?;
+//! ```
+//!
+//! ### Warning:
+//! The key-pairs used by the [`MockIdp`] are bundled with this crate, and are
+//! therefore *public*. Never trust them outside of tests.
+//!
+//! With the `simulation` feature, time itself can be controlled as well (see
+//! [`simulation`]).
+
+// Only ever given trusted (i.e., bundled) input.
+#![allow(clippy::indexing_slicing)]
+
+#[cfg(feature = "simulation")]
+pub mod simulation;
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use async_trait::async_trait;
+use http::header::CACHE_CONTROL;
+use http::header::CONTENT_TYPE;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::StatusCode;
+use jsonwebtoken::encode;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::EncodingKey;
+use jsonwebtoken::Header;
+use serde::Serialize;
+
+use crate::key_caches::remote::fetcher::JwksFetcher;
+use crate::key_caches::remote::fetcher::JwksResponse;
+use crate::key_caches::remote::jwks::KeySet;
+use crate::key_caches::remote::key::Key;
+use crate::key_caches::remote::key::KeyType;
+use crate::key_caches::remote::key::Use;
+use crate::key_caches::remote::RemoteCache;
+use crate::prelude;
+
+/// The `uri` that a [`MockIdp`] pretends to serve its `JWK`s from.
+pub const MOCK_JWK_URI: &str = "https://idp.webcipher.test/certs";
+
+/// A bundled `RSA` key-pair.
+pub struct KeyPair {
+    /// The `kid` under which the public key is published.
+    pub kid: &'static str,
+
+    /// The private key, `PEM` encoded.
+    pub private_pem: &'static [u8],
+
+    /// The (`base64URL` encoded) modulus of the public key.
+    n: &'static str,
+}
+
+impl KeyPair {
+    /// The public key, as a [`Key`].
+    pub fn key(&self) -> Key {
+        Key {
+            e: "AQAB".into(),
+            kty: KeyType::RSA,
+            alg: Some(Algorithm::RS256),
+            n: self.n.trim().into(),
+            kid: self.kid.into(),
+            r#use: Use::sig,
+            key_ops: None,
+            x5u: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+
+    /// Sign the given claims using this key-pair.
+    ///
+    /// The `typ` and `kid` header parameters are always set.
+    pub fn sign<Claims>(&self, claims: &Claims) -> prelude::Result<String>
+    where
+        Claims: Serialize,
+    {
+        let header = Header {
+            typ: Some("JWT".into()),
+            kid: Some(self.kid.into()),
+            ..Header::new(Algorithm::RS256)
+        };
+        let encoding_key = EncodingKey::from_rsa_pem(self.private_pem)?;
+
+        Ok(encode(&header, claims, &encoding_key)?)
+    }
+}
+
+/// The key-pairs bundled with this crate.
+///
+/// A [`MockIdp`] cycles through these (in order) whenever it is rotated.
+pub const KEY_PAIRS: [KeyPair; 3] = [
+    KeyPair {
+        kid: "webcipher-test-key",
+        private_pem: include_bytes!("keys/1.pem"),
+        n: include_str!("keys/1.n"),
+    },
+    KeyPair {
+        kid: "webcipher-test-key-2",
+        private_pem: include_bytes!("keys/2.pem"),
+        n: include_str!("keys/2.n"),
+    },
+    KeyPair {
+        kid: "webcipher-test-key-3",
+        private_pem: include_bytes!("keys/3.pem"),
+        n: include_str!("keys/3.n"),
+    },
+];
+
+struct State {
+    /// Index (into [`KEY_PAIRS`]) of the key-pair used for signing.
+    signing: usize,
+
+    /// Indices (into [`KEY_PAIRS`]) of the published key-pairs.
+    published: Vec<usize>,
+
+    /// The `max-age` directive of the published `cache-control` header.
+    max_age: Option<u64>,
+
+    /// The number of times the `JWK`s have been fetched.
+    fetches: usize,
+
+    /// Whether fetching the `JWK`s succeeds.
+    available: bool,
+}
+
+/// An in-process mock identity provider.
+///
+/// See the [module level documentation](`self`).
+pub struct MockIdp {
+    state: Mutex<State>,
+}
+
+impl Default for MockIdp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockIdp {
+    /// Create a new [`MockIdp`].
+    ///
+    /// The first bundled key-pair is published and used for signing, and the
+    /// `JWK`s are published with a `max-age` of 24hrs.
+    pub fn new() -> Self {
+        let state = State {
+            signing: 0,
+            published: vec![0],
+            max_age: Some(86_400),
+            fetches: 0,
+            available: true,
+        };
+
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Create a [`RemoteCache`] which fetches its keys from this [`MockIdp`].
+    ///
+    /// Just like with [`RemoteCache::new`], no keys are fetched yet.
+    pub fn remote_cache(self: &Arc<Self>) -> prelude::Result<RemoteCache> {
+        RemoteCache::builder(MOCK_JWK_URI)
+            .fetcher(Arc::clone(self))
+            .build()
+    }
+
+    /// Mint a token containing the given claims, signed by the current
+    /// signing key.
+    pub fn mint<Claims>(&self, claims: &Claims) -> prelude::Result<String>
+    where
+        Claims: Serialize,
+    {
+        let signing = self.state().signing;
+
+        KEY_PAIRS[signing].sign(claims)
+    }
+
+    /// Rotate the signing key.
+    ///
+    /// The next bundled key-pair is published and becomes the signing key.
+    /// The previous signing key stays published (so that tokens that are
+    /// already in-flight remain valid), while all older keys are unpublished.
+    ///
+    /// Returns the `kid` of the new signing key.
+    pub fn rotate(&self) -> &'static str {
+        let mut state = self.state();
+
+        let previous = state.signing;
+        let signing = (previous + 1) % KEY_PAIRS.len();
+
+        state.signing = signing;
+        state.published = vec![previous, signing];
+
+        KEY_PAIRS[signing].kid
+    }
+
+    /// Set the `max-age` directive of the published `cache-control` header.
+    ///
+    /// If [`None`], no `cache-control` header is published at all.
+    pub fn set_max_age(&self, max_age: Option<u64>) {
+        self.state().max_age = max_age;
+    }
+
+    /// Simulate an outage (or the end of one).
+    ///
+    /// While unavailable, every fetch fails with
+    /// [`Error::unable_to_fetch_keys`](`crate::error::Error::unable_to_fetch_keys`).
+    pub fn set_available(&self, available: bool) {
+        self.state().available = available;
+    }
+
+    /// The `kid` of the current signing key.
+    pub fn signing_kid(&self) -> &'static str {
+        KEY_PAIRS[self.state().signing].kid
+    }
+
+    /// The currently published `JWK` set.
+    pub fn jwks(&self) -> KeySet {
+        let keys = self
+            .state()
+            .published
+            .iter()
+            .map(|index| KEY_PAIRS[*index].key())
+            .collect();
+
+        KeySet { keys }
+    }
+
+    /// The number of times the `JWK`s have been fetched.
+    pub fn fetch_count(&self) -> usize {
+        self.state().fetches
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for MockIdp {
+    async fn fetch(&self, _: &http::Uri) -> prelude::Result<JwksResponse> {
+        let (max_age, available) = {
+            let mut state = self.state();
+            state.fetches += 1;
+            (state.max_age, state.available)
+        };
+
+        if !available {
+            return Err(crate::error::Error::unable_to_fetch_keys {
+                message: "The identity provider is unavailable.".into(),
+            });
+        };
+
+        let body = serde_json::to_vec(&self.jwks())?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        if let Some(max_age) = max_age {
+            let cache_control = format!("public, max-age={}", max_age);
+            let cache_control = HeaderValue::try_from(cache_control)
+                .map_err(|_| crate::error::Error::unable_to_parse_headers)?;
+            headers.insert(CACHE_CONTROL, cache_control);
+        };
+
+        Ok(JwksResponse {
+            status: StatusCode::OK,
+            headers,
+            body: body.into(),
+        })
+    }
+}
@@ -0,0 +1,116 @@
+//! Deterministic simulations of cache behavior.
+//!
+//! Only available when the `simulation` feature is enabled.
+//!
+//! A [`Simulation`] takes over the clock of this crate (i.e., the expiry of
+//! cached keys, refresh-ahead and stale policies, failover probes, and
+//! maintenance windows), along with the clock of [`tokio`] (i.e., the
+//! schedule of background refresh tasks). Advancing it lets hours of key
+//! rotations, expiries, and back-offs play out in milliseconds, against a
+//! [`MockIdp`](`super::MockIdp`):
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn test_rotation() {
+//!     let simulation = Simulation::start(1_700_000_000);
+//!
+//!     let idp = Arc::new(MockIdp::new());
+//!     idp.set_max_age(Some(3_600));
+//!     let remote_cache = Arc::new(RwLock::new(idp.remote_cache()?));
+//!     let _handle = RemoteCache::spawn_auto_refresh(
+//!         &remote_cache,
+//!         AutoRefresh::new(RefreshSchedule::TtlBased),
+//!     );
+//!
+//!     idp.rotate();
+//!     simulation.advance(Duration::from_secs(3_600)).await;
+//!
+//!     assert!(remote_cache.read().await.key(idp.signing_kid()).is_some());
+//! }
+//! ```
+//!
+//! ### Note:
+//! The simulated time is tracked per thread, and [`tokio`]'s clock can only
+//! be paused on a `current_thread` runtime (the default of
+//! [`tokio::test`]); [`Simulation::start`] panics on any other runtime.
+//!
+//! The `exp` claim of a token is validated by [`jsonwebtoken`], which always
+//! reads the time of the system, unless the cache was given a
+//! [`SystemClock`](`crate::time::SystemClock`) (which follows the simulated
+//! time).
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::prelude::Timestamp;
+use crate::time::SIMULATION;
+
+/// A running simulation, which controls the time of its thread until dropped.
+///
+/// See the [module level documentation](`self`).
+#[derive(Debug)]
+pub struct Simulation {
+    /// The simulated time is thread-local, so the guard must stay on its
+    /// thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Simulation {
+    /// Start a simulation at the given time (in Unix-Time).
+    ///
+    /// Pauses the clock of the current [`tokio`] runtime; from then on, time
+    /// only moves when advanced.
+    ///
+    /// ### Panics:
+    /// If called outside of a `current_thread` [`tokio`] runtime, or while
+    /// another simulation is running on the current thread.
+    pub fn start(start: Timestamp) -> Self {
+        assert!(
+            SIMULATION.with(|simulation| simulation.get().is_none()),
+            "A simulation is already running on this thread.",
+        );
+
+        tokio::time::pause();
+
+        let origin = tokio::time::Instant::now();
+        SIMULATION.with(|simulation| simulation.set(Some((start, origin))));
+
+        Self {
+            _thread: PhantomData,
+        }
+    }
+
+    /// The current (simulated) time, in Unix-Time.
+    pub fn now(&self) -> Timestamp {
+        crate::time::now()
+    }
+
+    /// Advance the time by the given duration.
+    ///
+    /// Every timer which elapses in the meantime (e.g., the delay of a
+    /// background refresh) fires in order, and the task waiting on it runs
+    /// until it waits again, before this returns.
+    pub async fn advance(&self, duration: Duration) {
+        // While paused, the runtime jumps from one timer to the next whenever
+        // it is idle (i.e., once the woken tasks are waiting again).
+        tokio::time::sleep(duration).await;
+
+        // Let the tasks whose timers fire at the very end run as well.
+        tokio::task::yield_now().await;
+    }
+
+    /// Advance the time to the given point (in Unix-Time).
+    ///
+    /// Does nothing if that point has already passed.
+    pub async fn advance_to(&self, time: Timestamp) {
+        let duration = time.saturating_sub(self.now());
+
+        self.advance(Duration::from_secs(duration)).await;
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        SIMULATION.with(|simulation| simulation.set(None));
+    }
+}
@@ -0,0 +1,249 @@
+//! Timestamp utilities.
+//!
+//! By default, [`chrono`](https://docs.rs/chrono) is used to read the current
+//! time. Disabling the `chrono` feature falls back to [`std::time`] instead,
+//! removing the dependency (e.g., for embedded or edge deployments).
+//!
+//! When the `simulation` feature is enabled, a running
+//! [`Simulation`](`crate::testing::simulation::Simulation`) replaces the
+//! current time on its thread.
+//!
+//! Caches can also be given a [`Clock`] of their own, which replaces the
+//! current time for the freshness of their keys and the validation of their
+//! tokens (i.e., the `exp` and `nbf` claims). A [`ManualClock`] only moves when
+//! told to, so that tests can freeze (or advance) the time of a single cache:
+//!
+//! ```ignore
+//! let clock = Arc::new(ManualClock::new(1_700_000_000));
+//!
+//! let mut remote_cache = RemoteCache::builder(GOOGLE_JWK_URI)
+//!     .clock(Arc::clone(&clock))
+//!     .build()?;
+//! remote_cache.refresh().await?;
+//!
+//! clock.advance(Duration::from_secs(86_400));
+//! assert!(!remote_cache.is_cache_fresh());
+//! ```
+//!
+//! The expiry time of the keys of a cache is exposed as an [`Expiry`], which
+//! converts to (and from) [`SystemTime`] and, with the `chrono` feature,
+//! `DateTime<Utc>`, so that no epoch math is needed:
+//!
+//! ```ignore
+//! // The keys of `target.com` are rotated every 4hrs.
+//! remote_cache.set_expiry(Some(Expiry::after(Duration::from_secs(4 * 3600))));
+//!
+//! if let Some(expiry) = remote_cache.expiry() {
+//!     println!("The keys expire at {:?}.", expiry.date_time());
+//! }
+//! ```
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::prelude::Timestamp;
+
+/// A source of the current time.
+///
+/// See the [module level documentation](`self`).
+pub trait Clock: Send + Sync {
+    /// The current time, in Unix-Time.
+    fn now(&self) -> Timestamp;
+}
+
+impl<C> Clock for Arc<C>
+where
+    C: Clock + ?Sized,
+{
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+/// The clock of the system (or of the running
+/// [`Simulation`](`crate::testing::simulation::Simulation`), if any).
+///
+/// Used by every cache which was not given a clock of its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        now()
+    }
+}
+
+/// A clock which only moves when it is set (or advanced).
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    /// Create a [`ManualClock`] frozen at the given time (in Unix-Time).
+    pub fn new(now: Timestamp) -> Self {
+        Self {
+            now: AtomicU64::new(now),
+        }
+    }
+
+    /// Move the clock to the given time (in Unix-Time).
+    pub fn set(&self, now: Timestamp) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Move the clock forward by the given duration (in whole seconds).
+    pub fn advance(&self, duration: Duration) {
+        let _ = self.now.fetch_add(duration.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+/// The time (in Unix-Time) at which something (e.g., the keys of a cache)
+/// expires.
+///
+/// See the [module level documentation](`self`).
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Expiry {
+    timestamp: Timestamp,
+}
+
+impl Expiry {
+    /// An [`Expiry`] at the given time (in Unix-Time).
+    pub const fn at(timestamp: Timestamp) -> Self {
+        Self { timestamp }
+    }
+
+    /// An [`Expiry`] after the given duration (in whole seconds) from now.
+    pub fn after(duration: Duration) -> Self {
+        Self::at(now().saturating_add(duration.as_secs()))
+    }
+
+    /// An [`Expiry`] which never expires.
+    pub const fn never() -> Self {
+        Self::at(u64::MAX)
+    }
+
+    /// The time of this [`Expiry`], in Unix-Time.
+    pub const fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Check to see if this [`Expiry`] never expires.
+    pub const fn is_never(&self) -> bool {
+        self.timestamp == u64::MAX
+    }
+
+    /// The time of this [`Expiry`].
+    ///
+    /// Returns [`None`] if it cannot be represented (e.g., if it never
+    /// expires).
+    pub fn system_time(&self) -> Option<SystemTime> {
+        UNIX_EPOCH.checked_add(Duration::from_secs(self.timestamp))
+    }
+
+    /// The time of this [`Expiry`].
+    ///
+    /// Returns [`None`] if it cannot be represented (e.g., if it never
+    /// expires).
+    #[cfg(feature = "chrono")]
+    pub fn date_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::TimeZone;
+
+        let timestamp = i64::try_from(self.timestamp).ok()?;
+        chrono::Utc.timestamp_opt(timestamp, 0).single()
+    }
+
+    /// The amount of time left until this [`Expiry`], as of now.
+    ///
+    /// Returns [`Duration::ZERO`] if it has already expired.
+    pub fn remaining(&self) -> Duration {
+        Duration::from_secs(self.timestamp.saturating_sub(now()))
+    }
+
+    /// Check to see if this [`Expiry`] has passed, as of now.
+    pub fn is_expired(&self) -> bool {
+        self.timestamp <= now()
+    }
+
+    /// Move this [`Expiry`] to the given time (in Unix-Time).
+    pub fn set(&mut self, timestamp: Timestamp) {
+        self.timestamp = timestamp;
+    }
+
+    /// Move this [`Expiry`] back by the given duration (in whole seconds).
+    pub fn extend(&mut self, duration: Duration) {
+        self.timestamp = self.timestamp.saturating_add(duration.as_secs());
+    }
+}
+
+impl From<Timestamp> for Expiry {
+    fn from(timestamp: Timestamp) -> Self {
+        Self::at(timestamp)
+    }
+}
+
+impl From<Expiry> for Timestamp {
+    fn from(expiry: Expiry) -> Self {
+        expiry.timestamp
+    }
+}
+
+impl From<SystemTime> for Expiry {
+    /// Times before the Unix-Epoch are clamped to it.
+    fn from(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self::at(since_epoch.as_secs())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for Expiry {
+    /// Times before the Unix-Epoch are clamped to it.
+    fn from(time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self::at(u64::try_from(time.timestamp()).unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "simulation")]
+thread_local! {
+    /// The origin of the running simulation (if any): its start time, and the
+    /// (paused) [`tokio`] instant at which it was started.
+    pub(crate) static SIMULATION: std::cell::Cell<
+        Option<(Timestamp, tokio::time::Instant)>,
+    > = const { std::cell::Cell::new(None) };
+}
+
+/// The current time, in Unix-Time.
+pub(crate) fn now() -> Timestamp {
+    #[cfg(feature = "simulation")]
+    if let Some((start, origin)) = SIMULATION.with(std::cell::Cell::get) {
+        return start + origin.elapsed().as_secs();
+    };
+
+    system_now()
+}
+
+/// The current time of the system, in Unix-Time.
+#[cfg(feature = "chrono")]
+fn system_now() -> Timestamp {
+    chrono::Utc::now().timestamp() as u64
+}
+
+/// The current time of the system, in Unix-Time.
+#[cfg(not(feature = "chrono"))]
+fn system_now() -> Timestamp {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
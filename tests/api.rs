@@ -0,0 +1,288 @@
+//! Compile-time checks of the stable `webcipher::api` facade.
+//!
+//! If any of these items are removed, renamed, or have their signatures
+//! changed, this file will stop compiling.
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::Header;
+use jsonwebtoken::TokenData;
+use webcipher::api;
+use webcipher::api::RemoteCache;
+
+fn assert_type<T: ?Sized>() {}
+
+#[test]
+fn test_stable_types() {
+    assert_type::<api::Error>();
+    assert_type::<api::Advice>();
+    assert_type::<api::BearerChallenge>();
+    assert_type::<api::BearerError>();
+    assert_type::<api::ClaimChange>();
+    assert_type::<api::ClaimsDiff>();
+    assert_type::<api::Finding>();
+    assert_type::<api::KeyReport>();
+    assert_type::<api::Report>();
+    assert_type::<api::Severity>();
+    assert_type::<dyn api::AuthorizationHook>();
+    assert_type::<api::RequestContext>();
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    assert_type::<api::OpaHook>();
+    assert_type::<api::StepUpPolicy>();
+    #[cfg(feature = "cedar")]
+    assert_type::<api::CedarHook>();
+    #[cfg(feature = "json-schema")]
+    assert_type::<api::ClaimsSchema>();
+    assert_type::<api::Result<()>>();
+    assert_type::<api::LocalCache>();
+    assert_type::<api::RemoteCache>();
+    assert_type::<api::RemoteCacheBuilder>();
+    assert_type::<api::FetchConfig>();
+    assert_type::<api::JwksFormat>();
+    assert_type::<api::RedirectPolicy>();
+    assert_type::<api::RetryPolicy>();
+    assert_type::<dyn api::JwksFetcher>();
+    assert_type::<api::JwksResponse>();
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    assert_type::<api::HyperFetcher>();
+    assert_type::<api::Certificate>();
+    assert_type::<api::Identity>();
+    assert_type::<api::WellKnownTpa>();
+    assert_type::<api::PublicKey>();
+    assert_type::<api::Snapshot>();
+    assert_type::<api::PrewarmedKeys>();
+    assert_type::<api::RsaComponents>();
+    assert_type::<dyn api::CacheStore>();
+    assert_type::<api::FileStore>();
+    assert_type::<dyn api::HeaderCache>();
+    assert_type::<api::LruHeaderCache>();
+    assert_type::<dyn api::KeyCache>();
+    #[cfg(feature = "redis")]
+    assert_type::<api::RedisStore>();
+    assert_type::<api::Redacted<String>>();
+    assert_type::<api::ProviderMetadata>();
+    assert_type::<api::FailoverPolicy>();
+    assert_type::<api::FailoverEvent>();
+    assert_type::<api::TaskSet>();
+    assert_type::<api::TokenLifetime>();
+    assert_type::<api::TokenLifetimes<String>>();
+    assert_type::<api::LifetimeStats>();
+    assert_type::<api::Histogram>();
+    assert_type::<api::ShadowOutcome>();
+    assert_type::<api::ShadowStats>();
+    assert_type::<api::ShadowComparisons<String>>();
+    assert_type::<api::SharedKeyRegistry<String>>();
+    assert_type::<api::ShardedKeyRegistry<String>>();
+    assert_type::<api::BuildReport<String>>();
+    assert_type::<api::CacheStatus>();
+    assert_type::<api::ExpectedClaims>();
+    assert_type::<api::Key>();
+    assert_type::<api::KeyOperation>();
+    assert_type::<api::Curve>();
+    assert_type::<api::KeyType>();
+    assert_type::<api::Use>();
+    assert_type::<api::Provenance>();
+    assert_type::<api::Verified<()>>();
+    assert_type::<api::VerifiedToken<()>>();
+    assert_type::<api::KeySet>();
+    assert_type::<api::Stamped<api::KeySet>>();
+    assert_type::<api::AppleClaims>();
+    assert_type::<api::RealUserStatus>();
+    assert_type::<api::Auth0Claims>();
+    assert_type::<api::CognitoClaims>();
+    assert_type::<api::CognitoUserPool>();
+    assert_type::<api::TokenUse>();
+    assert_type::<api::FacebookClaims>();
+    assert_type::<api::FirebaseClaims>();
+    assert_type::<api::FirebaseInfo>();
+    assert_type::<api::GitHubActionsClaims>();
+    assert_type::<api::GoogleClaims>();
+    assert_type::<api::KeycloakClaims>();
+    assert_type::<api::KeycloakRoles>();
+    assert_type::<api::KubernetesClaims>();
+    assert_type::<api::KubernetesInfo>();
+    assert_type::<api::KubernetesObject>();
+    assert_type::<api::MicrosoftClaims>();
+    assert_type::<api::LineClaims>();
+    assert_type::<api::OktaClaims>();
+    assert_type::<api::TwitchClaims>();
+    assert_type::<api::StandardClaims>();
+    assert_type::<api::Audience>();
+    assert_type::<api::NormalizedClaims>();
+    assert_type::<api::ValidationError>();
+    assert_type::<api::Permissions>();
+    assert_type::<api::ReplayGuard>();
+    assert_type::<dyn api::CacheObserver>();
+    assert_type::<api::MemoryReplayStore>();
+    assert_type::<api::ManualClock>();
+    assert_type::<api::SystemClock>();
+    assert_type::<api::Expiry>();
+    assert_type::<api::RotationEvent>();
+}
+
+#[test]
+fn test_stable_constants() {
+    let uris: [&str; 12] = [
+        api::APPLE_JWK_URI,
+        api::FACEBOOK_JWK_URI,
+        api::FIREBASE_JWK_URI,
+        api::GITHUB_ACTIONS_ISSUER,
+        api::GITHUB_ACTIONS_JWK_URI,
+        api::GOOGLE_JWK_URI,
+        api::KUBERNETES_JWK_URI,
+        api::LINE_ISSUER,
+        api::LINE_JWK_URI,
+        api::MICROSOFT_JWK_URI,
+        api::TWITCH_ISSUER,
+        api::TWITCH_JWK_URI,
+    ];
+
+    assert!(uris.iter().all(|uri| uri.starts_with("https://")));
+}
+
+#[test]
+fn test_stable_signatures() {
+    let _: fn(&'static str) -> api::Result<RemoteCache> = RemoteCache::new;
+    let _: fn(&'static str) -> api::RemoteCacheBuilder = RemoteCache::builder;
+    let _: fn(Vec<api::Key>) -> RemoteCache = RemoteCache::from_keys;
+    let _: fn(&str) -> api::Result<RemoteCache> = RemoteCache::from_jwks_json;
+    let _: fn(&JwkSet) -> RemoteCache = RemoteCache::from_jwk_set;
+    let _: fn(&RemoteCache) -> JwkSet = RemoteCache::export_jwk_set;
+    let _: fn(&'static str) -> api::Result<RemoteCache> =
+        RemoteCache::from_file;
+    let _: fn(api::Snapshot) -> api::Result<RemoteCache> =
+        RemoteCache::from_snapshot;
+    let _: fn(&RemoteCache) -> api::PrewarmedKeys = RemoteCache::prewarm;
+    let _: fn(&api::Report) -> bool = api::Report::is_ok;
+    let _: fn(&str) -> api::Result<(Header, serde_json::Value)> =
+        api::insecure::inspect_token;
+    let _: fn(&serde_json::Value, &serde_json::Value) -> api::ClaimsDiff =
+        api::claims_diff;
+    let _: fn(&serde_json::Value) -> api::Result<()> =
+        api::check_registered_claims;
+    let _: fn(usize) -> api::LruHeaderCache = api::LruHeaderCache::new;
+    let _: fn(&api::Error) -> api::Advice = api::Error::advice;
+    let _: fn(&api::Advice) -> &'static str = api::Advice::as_str;
+    let _: fn(&api::WellKnownTpa) -> &'static str = api::WellKnownTpa::jwk_uri;
+    let _: fn(&str, &str) -> String = api::cognito_jwk_uri;
+    let _: fn(&str, &str) -> String = api::cognito_issuer;
+    let _: fn(&str) -> String = api::auth0_jwk_uri;
+    let _: fn(&str) -> String = api::auth0_issuer;
+    let _: fn(&str) -> api::Result<RemoteCache> = RemoteCache::for_auth0;
+    let _: fn(&str, &str) -> String = api::okta_jwk_uri;
+    let _: fn(&str, &str) -> String = api::okta_issuer;
+    let _: fn(&str, &str) -> api::Result<RemoteCache> = RemoteCache::for_okta;
+    let _: fn(&str, &str) -> String = api::keycloak_jwk_uri;
+    let _: fn(&str, &str) -> String = api::keycloak_issuer;
+    let _: fn(&str, &str) -> api::Result<RemoteCache> =
+        RemoteCache::for_keycloak;
+    let _: fn() -> api::Result<RemoteCache> = RemoteCache::for_kubernetes;
+    let _: fn(&str) -> String = api::firebase_issuer;
+    let _: fn(&str) -> api::Result<RemoteCache> = RemoteCache::for_firebase;
+    let _: fn(&api::FirebaseClaims, &str) -> api::Result<()> =
+        api::FirebaseClaims::validate;
+    let _: fn(
+        &api::CognitoUserPool,
+        &api::CognitoClaims,
+        api::TokenUse,
+        &str,
+    ) -> api::Result<()> = api::CognitoUserPool::validate;
+    let _: fn(
+        api::KeyRegistryBuilder<api::WellKnownTpa>,
+        api::WellKnownTpa,
+    ) -> api::KeyRegistryBuilder<api::WellKnownTpa> =
+        api::KeyRegistryBuilder::add_well_known;
+    let _: fn(
+        api::KeyRegistryBuilder<api::WellKnownTpa>,
+        api::WellKnownTpa,
+        &str,
+    ) -> api::KeyRegistryBuilder<api::WellKnownTpa> =
+        api::KeyRegistryBuilder::add_firebase;
+    let _: fn(
+        api::KeyRegistryBuilder<api::WellKnownTpa>,
+    ) -> api::Result<api::KeyRegistry<api::WellKnownTpa>> =
+        api::KeyRegistryBuilder::build_lazy;
+    let _: fn(
+        &[api::WellKnownTpa],
+    ) -> api::KeyRegistryBuilder<api::WellKnownTpa> =
+        api::KeyRegistry::with_well_known;
+    let _: fn(
+        &api::KeyRegistry<api::WellKnownTpa>,
+        &api::WellKnownTpa,
+        String,
+    ) -> api::Result<TokenData<api::NormalizedClaims>> =
+        api::KeyRegistry::decrypt_normalized;
+    let _: fn(&api::ExpectedClaims, &serde_json::Value) -> api::Result<()> =
+        api::ExpectedClaims::check;
+    let _: fn(api::PrewarmedKeys) -> api::Result<RemoteCache> =
+        RemoteCache::from_prewarmed;
+    let _: fn(&api::StandardClaims) -> Option<std::time::Duration> =
+        api::StandardClaims::expires_in;
+    let _: fn(&api::StandardClaims, &str) -> bool =
+        api::StandardClaims::audience_contains;
+    let _: fn(&api::Audience, &str) -> bool = api::Audience::contains;
+    let _: fn(&api::StandardClaims) -> api::Permissions =
+        api::StandardClaims::permissions;
+    let _: fn(&serde_json::Value) -> api::Permissions =
+        api::Permissions::from_claims;
+    let _: fn(&api::Permissions, &str) -> bool = api::Permissions::has_scope;
+    let _: fn(&api::Permissions, &str) -> bool = api::Permissions::has_role;
+    let _: fn(&api::Audience) -> &[String] = api::Audience::as_slice;
+    let _: fn(&serde_json::Value, &str) -> api::Result<()> = api::check_nonce;
+    let _: fn(
+        &RemoteCache,
+        String,
+        &str,
+    ) -> api::Result<TokenData<serde_json::Value>> =
+        RemoteCache::decrypt_with_nonce;
+    let _: fn(
+        &RemoteCache,
+        &'static String,
+    ) -> api::Result<TokenData<serde_json::Value>> = RemoteCache::decrypt;
+    let _: fn(String) -> api::ValidationError = api::ValidationError::new;
+    let _: fn(jsonwebtoken::Algorithm, &str) -> String = api::token_hash;
+    let _: fn(Option<&str>, jsonwebtoken::Algorithm, &str) -> api::Result<()> =
+        api::check_at_hash;
+    let _: fn(Option<&str>, jsonwebtoken::Algorithm, &str) -> api::Result<()> =
+        api::check_c_hash;
+    let _: fn(&RemoteCache) -> bool = RemoteCache::is_cache_fresh;
+    let _: fn(&RemoteCache) -> &http::Uri = RemoteCache::uri;
+    let _: fn(&RemoteCache) -> &Option<u64> = RemoteCache::expiry_time;
+    let _: fn(&RemoteCache) -> Option<api::Expiry> = RemoteCache::expiry;
+    let _: fn(&mut RemoteCache, Option<api::Expiry>) = RemoteCache::set_expiry;
+    let _: fn(&api::Expiry) -> std::time::Duration = api::Expiry::remaining;
+    let _: fn(&mut RemoteCache, Option<std::sync::Arc<dyn api::Clock>>) =
+        RemoteCache::set_clock;
+    let _: fn(&api::ManualClock, std::time::Duration) =
+        api::ManualClock::advance;
+    let _: fn(&RemoteCache) -> api::KeySet = RemoteCache::export;
+    let _: for<'a> fn(&'a RemoteCache, &str) -> Option<&'a api::Key> =
+        RemoteCache::key;
+    let _: fn(&api::Key) -> api::Result<String> = api::Key::thumbprint_sha256;
+    let _: fn(api::RemoteCacheBuilder, bool) -> api::RemoteCacheBuilder =
+        api::RemoteCacheBuilder::single_key_fallback;
+    let _: fn(api::RemoteCacheBuilder, bool) -> api::RemoteCacheBuilder =
+        api::RemoteCacheBuilder::try_all_keys;
+    let _: fn(api::RemoteCacheBuilder, bool) -> api::RemoteCacheBuilder =
+        api::RemoteCacheBuilder::strict_claims;
+    let _: fn(api::RemoteCacheBuilder, usize) -> api::RemoteCacheBuilder =
+        api::RemoteCacheBuilder::blocking_threshold;
+    let _: fn(&mut RemoteCache, usize) = RemoteCache::set_blocking_threshold;
+    let _: fn(&mut RemoteCache, Option<usize>) -> api::Result<()> =
+        RemoteCache::set_max_concurrent_verifications;
+    let _: fn(
+        api::RemoteCacheBuilder,
+        std::time::Duration,
+    ) -> api::RemoteCacheBuilder = api::RemoteCacheBuilder::verification_wait;
+    let _: fn(&mut RemoteCache, std::time::Duration) =
+        RemoteCache::set_verification_wait;
+    let _: fn(
+        &mut RemoteCache,
+        Option<std::sync::Arc<dyn api::CacheObserver>>,
+    ) = RemoteCache::set_observer;
+    let _: fn(
+        &RemoteCache,
+    ) -> tokio::sync::broadcast::Receiver<api::RotationEvent> =
+        RemoteCache::subscribe_rotations;
+    let _: fn(api::RemoteCacheBuilder) -> api::Result<RemoteCache> =
+        api::RemoteCacheBuilder::build;
+}
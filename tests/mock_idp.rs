@@ -0,0 +1,138 @@
+//! End-to-end tests of a [`RemoteCache`] against the in-process [`MockIdp`].
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use webcipher::jsonwebtoken::TokenData;
+use webcipher::prelude::Advice;
+use webcipher::prelude::Error;
+use webcipher::prelude::RemoteCache;
+use webcipher::testing::MockIdp;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Claims {
+    sub: String,
+    exp: u64,
+}
+
+fn claims() -> Claims {
+    Claims {
+        sub: "user".into(),
+        exp: 20_000_000_000,
+    }
+}
+
+async fn setup() -> (Arc<MockIdp>, RemoteCache) {
+    let idp = Arc::new(MockIdp::new());
+    let mut remote_cache = idp.remote_cache().unwrap();
+    remote_cache.refresh().await.unwrap();
+
+    (idp, remote_cache)
+}
+
+#[tokio::test]
+/// A token minted by the identity provider can be verified.
+async fn test_login() {
+    let (idp, remote_cache) = setup().await;
+
+    let token = idp.mint(&claims()).unwrap();
+    let TokenData { claims: decrypted, .. } =
+        remote_cache.decrypt_unchecked::<Claims, _>(token).unwrap();
+
+    assert_eq!(decrypted, claims());
+    assert_eq!(idp.fetch_count(), 1);
+}
+
+#[tokio::test]
+/// Tokens signed by a rotated key are only accepted after a refresh, and
+/// tokens signed by unpublished keys are rejected.
+async fn test_rotation() {
+    let (idp, mut remote_cache) = setup().await;
+
+    let old_token = idp.mint(&claims()).unwrap();
+    let _ = idp.rotate();
+    let new_token = idp.mint(&claims()).unwrap();
+
+    let err = remote_cache
+        .decrypt_unchecked::<Claims, _>(new_token.clone())
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+
+    remote_cache.refresh().await.unwrap();
+
+    remote_cache
+        .decrypt_unchecked::<Claims, _>(new_token)
+        .unwrap();
+    remote_cache
+        .decrypt_unchecked::<Claims, _>(old_token.clone())
+        .unwrap();
+
+    let _ = idp.rotate();
+    remote_cache.refresh().await.unwrap();
+
+    let err = remote_cache
+        .decrypt_unchecked::<Claims, _>(old_token)
+        .unwrap_err();
+    assert_eq!(err, Error::no_corresponding_kid_in_store);
+}
+
+#[tokio::test]
+/// The freshness of the cache follows the `cache-control` header of the
+/// identity provider.
+async fn test_stale_cache() {
+    let (idp, mut remote_cache) = setup().await;
+    assert!(remote_cache.is_cache_fresh());
+
+    idp.set_max_age(None);
+    remote_cache.refresh().await.unwrap();
+    assert!(!remote_cache.is_cache_fresh());
+
+    idp.set_max_age(Some(86_400));
+    remote_cache.refresh().await.unwrap();
+    assert!(remote_cache.is_cache_fresh());
+    assert_eq!(idp.fetch_count(), 3);
+}
+
+#[tokio::test]
+/// Each failure should advise the operator on what to do about it.
+async fn test_advice() {
+    let (idp, mut remote_cache) = setup().await;
+
+    let rotated = {
+        let _ = idp.rotate();
+        idp.mint(&claims()).unwrap()
+    };
+    let err = remote_cache
+        .decrypt_unchecked::<Claims, _>(rotated)
+        .unwrap_err();
+    assert_eq!(err.advice(), Advice::RefreshKeys);
+
+    idp.set_available(false);
+    let err = remote_cache.refresh().await.unwrap_err();
+    assert_eq!(err.advice(), Advice::CheckNetwork);
+
+    idp.set_available(true);
+    remote_cache.refresh().await.unwrap();
+
+    let expired = idp
+        .mint(&Claims {
+            exp: 1,
+            ..claims()
+        })
+        .unwrap();
+    let err = remote_cache
+        .decrypt_unchecked::<Claims, _>(expired)
+        .unwrap_err();
+    assert_eq!(err.advice(), Advice::CheckClock);
+
+    let err = remote_cache
+        .decrypt_unchecked::<Claims, _>("a.b.c")
+        .unwrap_err();
+    assert_eq!(err.advice(), Advice::RejectToken);
+
+    let mut codes = Advice::ALL.map(|advice| advice.as_str()).to_vec();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), Advice::ALL.len());
+}
@@ -0,0 +1,141 @@
+//! Mutation-based fuzzing of every parsing path which handles untrusted input.
+//!
+//! Each target starts from a valid input, which is then repeatedly corrupted
+//! (i.e., bytes are flipped, inserted, removed, or the input is truncated).
+//! The targets may reject the corrupted inputs, but must never panic.
+//!
+//! The seed is fixed, so that failures are reproducible.
+
+use fastrand::Rng;
+use serde_json::json;
+use serde_json::Value;
+use webcipher::api::insecure::inspect_token;
+use webcipher::api::LocalCache;
+use webcipher::api::PrewarmedKeys;
+use webcipher::key_caches::remote::x509::leaf_public_key;
+use webcipher::prelude::RemoteCache;
+use webcipher::prelude::Snapshot;
+use webcipher::testing::KEY_PAIRS;
+
+const SEED: u64 = 0x7765_6263_6970_6865;
+const ITERATIONS: usize = 2_000;
+
+const CERTIFICATE: &[u8] =
+    include_bytes!("../src/key_caches/remote/tests/certs/x5c_rsa.der");
+
+/// Corrupt the given input a few times over.
+fn mutate(rng: &Rng, input: &[u8]) -> Vec<u8> {
+    let mut output = input.to_vec();
+
+    for _ in 0..rng.usize(1..=4) {
+        let len = output.len();
+
+        match rng.u8(..4) {
+            0 if len > 0 => output[rng.usize(..len)] = rng.u8(..),
+            1 if len > 0 => output.truncate(rng.usize(..len)),
+            2 if len > 0 => drop(output.remove(rng.usize(..len))),
+            _ => output.insert(rng.usize(..=len), rng.u8(..)),
+        };
+    }
+
+    output
+}
+
+/// Run the given target against corrupted versions of the given input.
+fn fuzz<F>(input: &[u8], target: F)
+where
+    F: Fn(Vec<u8>),
+{
+    let rng = Rng::with_seed(SEED);
+
+    for _ in 0..ITERATIONS {
+        target(mutate(&rng, input));
+    }
+}
+
+fn lossy(bytes: Vec<u8>) -> String {
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[test]
+fn test_tokens() {
+    let remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    let token = KEY_PAIRS[0]
+        .sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+
+    fuzz(token.as_bytes(), |token| {
+        let token = lossy(token);
+        let mut buffer = Vec::new();
+
+        let _ = remote_cache.decrypt_unchecked::<Value, _>(token.clone());
+        let _ = remote_cache.decrypt_borrowed::<Value>(&token, &mut buffer);
+        let _ = remote_cache.decrypt_partial::<Value, _, _>(token, &["/sub"]);
+    });
+}
+
+#[test]
+fn test_inspected_tokens() {
+    let token = KEY_PAIRS[0]
+        .sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+
+    fuzz(token.as_bytes(), |token| {
+        let _ = inspect_token(&lossy(token));
+    });
+}
+
+#[test]
+fn test_local_tokens() {
+    let local_cache = LocalCache::new(jsonwebtoken::Algorithm::RS256);
+    let token = KEY_PAIRS[0]
+        .sign(&json!({ "sub": "user", "exp": 20_000_000_000u64 }))
+        .unwrap();
+
+    assert!(local_cache.encrypt(json!({ "sub": "user" })).is_err());
+
+    fuzz(token.as_bytes(), |token| {
+        let _ = local_cache.decrypt::<Value, _>(lossy(token), true);
+    });
+}
+
+#[test]
+fn test_jwk_sets() {
+    let jwks = json!({ "keys": [KEY_PAIRS[0].key(), KEY_PAIRS[1].key()] });
+
+    fuzz(jwks.to_string().as_bytes(), |jwks| {
+        let _ = RemoteCache::from_jwks_json(&lossy(jwks));
+    });
+}
+
+#[test]
+fn test_certificates() {
+    fuzz(CERTIFICATE, |certificate| {
+        let x5c = [base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            certificate,
+        )];
+
+        let _ = leaf_public_key(&x5c);
+    });
+}
+
+#[test]
+fn test_snapshots() {
+    let remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    let snapshot = remote_cache.snapshot().to_vec().unwrap();
+
+    fuzz(&snapshot, |snapshot| {
+        let _ = Snapshot::from_slice(&snapshot);
+    });
+}
+
+#[test]
+fn test_prewarmed_keys() {
+    let remote_cache = RemoteCache::from_keys(vec![KEY_PAIRS[0].key()]);
+    let prewarmed = remote_cache.prewarm().to_vec().unwrap();
+
+    fuzz(&prewarmed, |prewarmed| {
+        let _ = PrewarmedKeys::from_slice(&prewarmed);
+    });
+}
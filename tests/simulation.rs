@@ -0,0 +1,104 @@
+//! Simulated (i.e., deterministic) runs of a [`RemoteCache`] against the
+//! in-process [`MockIdp`].
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use webcipher::prelude::AutoRefresh;
+use webcipher::prelude::RefreshSchedule;
+use webcipher::prelude::RemoteCache;
+use webcipher::testing::simulation::Simulation;
+use webcipher::testing::MockIdp;
+
+const START: u64 = 1_700_000_000;
+
+const HOUR: Duration = Duration::from_secs(3_600);
+
+#[tokio::test]
+/// Keys should expire exactly when the identity provider said they would
+/// (minus a safety margin of an hour).
+async fn test_expiry() {
+    let simulation = Simulation::start(START);
+    assert_eq!(simulation.now(), START);
+
+    let idp = Arc::new(MockIdp::new());
+    idp.set_max_age(Some(7_200));
+
+    let mut remote_cache = idp.remote_cache().unwrap();
+    remote_cache.refresh().await.unwrap();
+    assert_eq!(remote_cache.expiry_time(), &Some(START + 3_600));
+
+    simulation.advance(HOUR - Duration::from_secs(1)).await;
+    assert!(remote_cache.is_cache_fresh());
+
+    simulation.advance(Duration::from_secs(1)).await;
+    assert!(!remote_cache.is_cache_fresh());
+    assert_eq!(simulation.now(), START + 3_600);
+}
+
+#[tokio::test]
+/// A background refresh task should follow the cache's expiry, and pick up a
+/// rotation within one `max-age`.
+async fn test_rotation() {
+    let simulation = Simulation::start(START);
+
+    let idp = Arc::new(MockIdp::new());
+    idp.set_max_age(Some(7_200));
+
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+    let _handle = RemoteCache::spawn_auto_refresh(
+        &remote_cache,
+        AutoRefresh::new(RefreshSchedule::TtlBased).jitter(false),
+    );
+
+    simulation.advance(Duration::from_secs(1)).await;
+    assert_eq!(idp.fetch_count(), 1);
+
+    let kid = idp.rotate();
+    assert!(remote_cache.read().await.key(kid).is_none());
+
+    simulation.advance(HOUR).await;
+    assert_eq!(idp.fetch_count(), 2);
+    assert!(remote_cache.read().await.key(kid).is_some());
+
+    simulation.advance_to(START + 10 * 3_600).await;
+    assert_eq!(idp.fetch_count(), 10);
+}
+
+#[tokio::test]
+/// Failed refreshes should be retried at the retry interval, until the
+/// identity provider recovers.
+async fn test_outage() {
+    let simulation = Simulation::start(START);
+
+    let idp = Arc::new(MockIdp::new());
+    idp.set_available(false);
+
+    let errors = Arc::new(AtomicUsize::new(0));
+    let remote_cache = Arc::new(RwLock::new(idp.remote_cache().unwrap()));
+    let _handle = RemoteCache::spawn_auto_refresh(
+        &remote_cache,
+        AutoRefresh::new(RefreshSchedule::TtlBased)
+            .jitter(false)
+            .retry_interval(Duration::from_secs(30))
+            .on_error({
+                let errors = errors.clone();
+                move |_| {
+                    errors.fetch_add(1, Ordering::SeqCst);
+                }
+            }),
+    );
+
+    // The first attempt fails right away, then one retry every 30secs.
+    simulation.advance(Duration::from_secs(5 * 60 - 1)).await;
+    assert_eq!(errors.load(Ordering::SeqCst), 10);
+    assert!(remote_cache.read().await.keys().is_empty());
+
+    idp.set_available(true);
+    simulation.advance(Duration::from_secs(2)).await;
+    assert!(remote_cache.read().await.is_cache_fresh());
+    assert_eq!(errors.load(Ordering::SeqCst), 10);
+}